@@ -950,7 +950,28 @@ impl ActiveThread {
                     );
                 }
             }
-            ThreadEvent::CheckpointChanged => cx.notify(),
+            ThreadEvent::ToolReplayed(tool_use_id) => {
+                let thread = self.thread.read(cx);
+                let tool_use = thread
+                    .messages()
+                    .flat_map(|message| thread.tool_uses_for_message(message.id, cx))
+                    .find(|tool_use| &tool_use.id == tool_use_id);
+                if let Some(tool_use) = tool_use {
+                    self.render_tool_use_markdown(
+                        tool_use.id.clone(),
+                        tool_use.ui_text.clone(),
+                        &tool_use.input,
+                        tool_use.status.text(),
+                        cx,
+                    );
+                }
+                self.save_thread(cx);
+                cx.notify();
+            }
+            ThreadEvent::CheckpointChanged => {
+                self.save_thread(cx);
+                cx.notify();
+            }
         }
     }
 
@@ -1097,6 +1118,25 @@ impl ActiveThread {
         }));
     }
 
+    fn fork_from_message(
+        &mut self,
+        message_id: MessageId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let thread = self.thread.clone();
+        if let Some(panel) = workspace.read(cx).panel::<AssistantPanel>(cx) {
+            panel.update(cx, |panel, cx| {
+                panel
+                    .fork_thread(thread, message_id, window, cx)
+                    .detach_and_log_err(cx)
+            });
+        }
+    }
+
     fn start_editing_message(
         &mut self,
         message_id: MessageId,
@@ -1711,6 +1751,27 @@ impl ActiveThread {
                                                         })),
                                                 )
                                             },
+                                        )
+                                        .when(
+                                            edit_message_editor.is_none()
+                                                && !is_first_message
+                                                && !is_generating,
+                                            |this| {
+                                                this.child(
+                                                    Button::new("fork-message", "Fork")
+                                                        .label_size(LabelSize::Small)
+                                                        .tooltip(Tooltip::text(
+                                                            "Fork from here into a new thread",
+                                                        ))
+                                                        .on_click(cx.listener(
+                                                            move |this, _, window, cx| {
+                                                                this.fork_from_message(
+                                                                    message_id, window, cx,
+                                                                );
+                                                            },
+                                                        )),
+                                                )
+                                            },
                                         ),
                                 ),
                         )
@@ -2474,6 +2535,8 @@ impl ActiveThread {
                 ))
         };
 
+        let card = tool_use.card.clone();
+
         div().map(|element| {
             if !edit_tools {
                 element.child(
@@ -2768,6 +2831,7 @@ impl ActiveThread {
                     })
             }
         })
+        .children(card)
     }
 
     fn render_rules_item(&self, cx: &Context<Self>) -> AnyElement {
@@ -3062,6 +3126,13 @@ pub(crate) fn open_context(
         AssistantContext::FetchedUrl(fetched_url_context) => {
             cx.open_url(&fetched_url_context.url);
         }
+        AssistantContext::Resource(resource_context) => {
+            if resource_context.uri.starts_with("http://")
+                || resource_context.uri.starts_with("https://")
+            {
+                cx.open_url(&resource_context.uri);
+            }
+        }
         AssistantContext::Thread(thread_context) => {
             let thread_id = thread_context.thread.read(cx).id().clone();
             workspace.update(cx, |workspace, cx| {
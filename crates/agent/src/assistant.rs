@@ -16,6 +16,7 @@ mod profile_selector;
 mod terminal_codegen;
 mod terminal_inline_assistant;
 mod thread;
+mod thread_export;
 mod thread_history;
 mod thread_store;
 mod tool_compatibility;
@@ -66,6 +67,9 @@ actions!(
         RemoveFocusedContext,
         AcceptSuggestedContext,
         OpenActiveThreadAsMarkdown,
+        ExportThread,
+        ImportThread,
+        ReplayToolCalls,
         OpenAgentDiff,
         Keep,
         Reject,
@@ -87,6 +87,7 @@ impl AddContextServerModal {
                                 env: None,
                             }),
                             settings: Some(json!({})),
+                            ..Default::default()
                         },
                     );
                 });
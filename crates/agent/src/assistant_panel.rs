@@ -16,8 +16,8 @@ use editor::{Editor, EditorEvent, MultiBuffer};
 use fs::Fs;
 use gpui::{
     Action, Animation, AnimationExt as _, AnyElement, App, AsyncWindowContext, Corner, Entity,
-    EventEmitter, FocusHandle, Focusable, FontWeight, KeyContext, Pixels, Subscription, Task,
-    UpdateGlobal, WeakEntity, prelude::*, pulsating_between,
+    EventEmitter, FocusHandle, Focusable, FontWeight, KeyContext, PathPromptOptions, Pixels,
+    PromptLevel, Subscription, Task, UpdateGlobal, WeakEntity, prelude::*, pulsating_between,
 };
 use language::LanguageRegistry;
 use language_model::{LanguageModelProviderTosView, LanguageModelRegistry};
@@ -34,6 +34,7 @@ use ui::{
 use util::ResultExt as _;
 use workspace::Workspace;
 use workspace::dock::{DockPosition, Panel, PanelEvent};
+use workspace::notifications::DetachAndPromptErr as _;
 use zed_actions::agent::OpenConfiguration;
 use zed_actions::assistant::{OpenPromptLibrary, ToggleFocus};
 
@@ -41,12 +42,14 @@ use crate::active_thread::ActiveThread;
 use crate::assistant_configuration::{AssistantConfiguration, AssistantConfigurationEvent};
 use crate::history_store::{HistoryEntry, HistoryStore};
 use crate::message_editor::MessageEditor;
-use crate::thread::{Thread, ThreadError, ThreadId, TokenUsageRatio};
+use crate::thread::{MessageId, Thread, ThreadError, ThreadId, TokenUsageRatio};
+use crate::thread_export::{ExportedThread, export_thread};
 use crate::thread_history::{PastContext, PastThread, ThreadHistory};
 use crate::thread_store::ThreadStore;
 use crate::{
-    AgentDiff, ExpandMessageEditor, InlineAssistant, NewTextThread, NewThread,
-    OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory, ThreadEvent, ToggleContextPicker,
+    AgentDiff, ExpandMessageEditor, ExportThread, ImportThread, InlineAssistant, NewTextThread,
+    NewThread, OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory, ReplayToolCalls,
+    ThreadEvent, ToggleContextPicker,
 };
 
 pub fn init(cx: &mut App) {
@@ -92,6 +95,22 @@ pub fn init(cx: &mut App) {
                         AgentDiff::deploy_in_workspace(thread, workspace, window, cx);
                     }
                 })
+                .register_action(|workspace, _: &ExportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.export_thread(window, cx));
+                    }
+                })
+                .register_action(|workspace, _: &ImportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                        workspace.focus_panel::<AssistantPanel>(window, cx);
+                        panel.update(cx, |panel, cx| panel.import_thread(window, cx));
+                    }
+                })
+                .register_action(|workspace, _: &ReplayToolCalls, window, cx| {
+                    if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.replay_tool_calls(window, cx));
+                    }
+                })
                 .register_action(|workspace, _: &ExpandMessageEditor, window, cx| {
                     if let Some(panel) = workspace.panel::<AssistantPanel>(cx) {
                         workspace.focus_panel::<AssistantPanel>(window, cx);
@@ -530,39 +549,190 @@ impl AssistantPanel {
         cx.spawn_in(window, async move |this, cx| {
             let thread = open_thread_task.await?;
             this.update_in(cx, |this, window, cx| {
-                let thread_view = ActiveView::thread(thread.clone(), window, cx);
-                this.set_active_view(thread_view, window, cx);
-                let message_editor_context_store = cx.new(|_cx| {
-                    crate::context_store::ContextStore::new(
-                        this.project.downgrade(),
-                        Some(this.thread_store.downgrade()),
-                    )
+                this.set_active_thread(thread, window, cx)
+            })
+        })
+    }
+
+    /// Forks `thread` at the given message and switches to the new thread.
+    pub(crate) fn fork_thread(
+        &mut self,
+        thread: Entity<Thread>,
+        message_id: MessageId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let fork_thread_task = self
+            .thread_store
+            .update(cx, |this, cx| this.fork_thread(&thread, message_id, cx));
+
+        cx.spawn_in(window, async move |this, cx| {
+            let thread = fork_thread_task.await?;
+            this.update_in(cx, |this, window, cx| {
+                this.set_active_thread(thread, window, cx)
+            })
+        })
+    }
+
+    /// Opens the diff between where the `base_id` thread and the `target_id`
+    /// thread left the project.
+    pub(crate) fn compare_thread_outcomes(
+        &mut self,
+        base_id: &ThreadId,
+        target_id: &ThreadId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let diff_task = self.thread_store.update(cx, |this, cx| {
+            this.diff_thread_outcomes(base_id, target_id, cx)
+        });
+        let diff_language_task = self.language_registry.language_for_name("Diff");
+        let workspace = self.workspace.clone();
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let diff = diff_task.await?;
+            let diff_language = diff_language_task.await.log_err();
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let project = workspace.project().clone();
+                let buffer = project.update(cx, |project, cx| {
+                    project.create_local_buffer(&diff, diff_language, cx)
                 });
-                this.thread = cx.new(|cx| {
-                    ActiveThread::new(
-                        thread.clone(),
-                        this.thread_store.clone(),
-                        this.language_registry.clone(),
-                        message_editor_context_store.clone(),
-                        this.workspace.clone(),
-                        window,
-                        cx,
-                    )
+                let buffer = cx.new(|cx| {
+                    MultiBuffer::singleton(buffer, cx).with_title("Fork Comparison".into())
                 });
-                this.message_editor = cx.new(|cx| {
-                    MessageEditor::new(
-                        this.fs.clone(),
-                        this.workspace.clone(),
-                        message_editor_context_store,
-                        this.thread_store.downgrade(),
-                        thread,
-                        window,
+
+                workspace.add_item_to_active_pane(
+                    Box::new(cx.new(|cx| {
+                        let mut editor =
+                            Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
+                        editor.set_read_only(true);
+                        editor
+                    })),
+                    None,
+                    true,
+                    window,
+                    cx,
+                );
+            })
+        })
+    }
+
+    fn export_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let thread = self.active_thread(cx);
+        let export_task = export_thread(&thread, cx);
+        let directory = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .unwrap_or_else(|| util::paths::home_dir().clone());
+        let path = cx.prompt_for_new_path(&directory);
+        let fs = self.fs.clone();
+
+        cx.spawn_in(window, async move |_this, _cx| {
+            let Some(path) = path.await?? else {
+                return Ok(());
+            };
+            let json = export_task.await?.to_json()?;
+            fs.atomic_write(path, json).await
+        })
+        .detach_and_prompt_err("Failed to export thread", window, cx, |_, _, _| None);
+    }
+
+    fn import_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        });
+        let fs = self.fs.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return Ok(());
+            };
+            let json = fs.load(&path).await?;
+            let exported_thread = ExportedThread::from_json(json.as_bytes())?;
+            let import_task = this.update(cx, |this, cx| {
+                this.thread_store
+                    .update(cx, |store, cx| store.import_thread(exported_thread, cx))
+            })?;
+            let thread = import_task.await?;
+            this.update_in(cx, |this, window, cx| {
+                this.set_active_thread(thread.clone(), window, cx)
+            })?;
+
+            let has_tool_uses = thread.read_with(cx, |thread, cx| {
+                thread
+                    .messages()
+                    .any(|message| !thread.tool_uses_for_message(message.id, cx).is_empty())
+            })?;
+            if has_tool_uses {
+                let answer = cx.update(|window, cx| {
+                    window.prompt(
+                        PromptLevel::Info,
+                        "Thread imported",
+                        Some("Replay its tool calls against the current project?"),
+                        &["Replay Tool Calls", "Not Now"],
                         cx,
                     )
-                });
-                this.message_editor.focus_handle(cx).focus(window);
-            })
+                })?;
+                if answer.await? == 0 {
+                    thread
+                        .update(cx, |thread, cx| thread.replay_tool_uses(cx))?
+                        .await?;
+                }
+            }
+            anyhow::Ok(())
         })
+        .detach_and_prompt_err("Failed to import thread", window, cx, |_, _, _| None);
+    }
+
+    fn replay_tool_calls(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.active_thread(cx)
+            .update(cx, |thread, cx| thread.replay_tool_uses(cx))
+            .detach_and_prompt_err("Failed to replay tool calls", window, cx, |_, _, _| None);
+    }
+
+    fn set_active_thread(
+        &mut self,
+        thread: Entity<Thread>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let thread_view = ActiveView::thread(thread.clone(), window, cx);
+        self.set_active_view(thread_view, window, cx);
+        let message_editor_context_store = cx.new(|_cx| {
+            crate::context_store::ContextStore::new(
+                self.project.downgrade(),
+                Some(self.thread_store.downgrade()),
+            )
+        });
+        self.thread = cx.new(|cx| {
+            ActiveThread::new(
+                thread.clone(),
+                self.thread_store.clone(),
+                self.language_registry.clone(),
+                message_editor_context_store.clone(),
+                self.workspace.clone(),
+                window,
+                cx,
+            )
+        });
+        self.message_editor = cx.new(|cx| {
+            MessageEditor::new(
+                self.fs.clone(),
+                self.workspace.clone(),
+                message_editor_context_store,
+                self.thread_store.downgrade(),
+                thread,
+                window,
+                cx,
+            )
+        });
+        self.message_editor.focus_handle(cx).focus(window);
     }
 
     pub fn go_back(&mut self, _: &workspace::GoBack, window: &mut Window, cx: &mut Context<Self>) {
@@ -1107,8 +1277,21 @@ impl AssistantPanel {
                                                             Box::new(NewThread {
                                                                 from_thread_id: Some(thread_id.clone()),
                                                             }),
-                                                        ).separator()
+                                                        )
+                                                        .action(
+                                                            "Export Thread…",
+                                                            ExportThread.boxed_clone(),
+                                                        )
+                                                        .action(
+                                                            "Replay Tool Calls",
+                                                            ReplayToolCalls.boxed_clone(),
+                                                        )
+                                                        .separator()
                                                     })
+                                                    .action(
+                                                        "Import Thread…",
+                                                        ImportThread.boxed_clone(),
+                                                    )
                                                     .action(
                                                     "New Text Thread",
                                                     NewTextThread.boxed_clone(),
@@ -429,6 +429,11 @@ impl CodegenAlternative {
             stop: Vec::new(),
             temperature: None,
             messages: vec![request_message],
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         })
     }
 
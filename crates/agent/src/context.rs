@@ -19,12 +19,16 @@ impl ContextId {
         Self(post_inc(&mut self.0))
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ContextKind {
     File,
     Directory,
     Symbol,
     FetchedUrl,
     Thread,
+    Resource,
 }
 
 impl ContextKind {
@@ -35,6 +39,7 @@ impl ContextKind {
             ContextKind::Symbol => IconName::Code,
             ContextKind::FetchedUrl => IconName::Globe,
             ContextKind::Thread => IconName::MessageBubbles,
+            ContextKind::Resource => IconName::Server,
         }
     }
 }
@@ -46,6 +51,7 @@ pub enum AssistantContext {
     Symbol(SymbolContext),
     FetchedUrl(FetchedUrlContext),
     Thread(ThreadContext),
+    Resource(ResourceContext),
 }
 
 impl AssistantContext {
@@ -56,6 +62,7 @@ impl AssistantContext {
             Self::Symbol(symbol) => symbol.id,
             Self::FetchedUrl(url) => url.id,
             Self::Thread(thread) => thread.id,
+            Self::Resource(resource) => resource.id,
         }
     }
 }
@@ -97,6 +104,16 @@ pub struct FetchedUrlContext {
     pub text: SharedString,
 }
 
+/// A resource offered by a context server.
+#[derive(Debug, Clone)]
+pub struct ResourceContext {
+    pub id: ContextId,
+    pub server_id: Arc<str>,
+    pub uri: SharedString,
+    pub name: SharedString,
+    pub text: SharedString,
+}
+
 #[derive(Debug, Clone)]
 pub struct ThreadContext {
     pub id: ContextId,
@@ -165,6 +182,7 @@ pub fn format_context_as_string<'a>(
     let mut symbol_context = Vec::new();
     let mut fetch_context = Vec::new();
     let mut thread_context = Vec::new();
+    let mut resource_context = Vec::new();
 
     for context in contexts {
         match context {
@@ -173,6 +191,7 @@ pub fn format_context_as_string<'a>(
             AssistantContext::Symbol(context) => symbol_context.push(context),
             AssistantContext::FetchedUrl(context) => fetch_context.push(context),
             AssistantContext::Thread(context) => thread_context.push(context),
+            AssistantContext::Resource(context) => resource_context.push(context),
         }
     }
 
@@ -181,6 +200,7 @@ pub fn format_context_as_string<'a>(
         && symbol_context.is_empty()
         && fetch_context.is_empty()
         && thread_context.is_empty()
+        && resource_context.is_empty()
     {
         return None;
     }
@@ -238,6 +258,19 @@ pub fn format_context_as_string<'a>(
         result.push_str("</conversation_threads>\n");
     }
 
+    if !resource_context.is_empty() {
+        result.push_str("<resources>\n");
+        for context in &resource_context {
+            result.push_str(&context.name);
+            result.push_str(" (");
+            result.push_str(&context.uri);
+            result.push_str(")\n");
+            result.push_str(&context.text);
+            result.push('\n');
+        }
+        result.push_str("</resources>\n");
+    }
+
     result.push_str("</context>\n");
     Some(result)
 }
@@ -1,6 +1,7 @@
 mod completion_provider;
 mod fetch_context_picker;
 mod file_context_picker;
+mod resource_context_picker;
 mod symbol_context_picker;
 mod thread_context_picker;
 
@@ -9,6 +10,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{Result, anyhow};
+use context_server::manager::ContextServerManager;
+use context_server::protocol::ServerCapability;
 use editor::display_map::{Crease, FoldId};
 use editor::{Anchor, AnchorRangeExt as _, Editor, ExcerptId, FoldPlaceholder, ToOffset};
 use file_context_picker::render_file_context_entry;
@@ -29,6 +32,8 @@ use crate::AssistantPanel;
 pub use crate::context_picker::completion_provider::ContextPickerCompletionProvider;
 use crate::context_picker::fetch_context_picker::FetchContextPicker;
 use crate::context_picker::file_context_picker::FileContextPicker;
+use crate::context_picker::resource_context_picker::ResourceContextPicker;
+pub(crate) use crate::context_picker::resource_context_picker::read_resource_text;
 use crate::context_picker::thread_context_picker::ThreadContextPicker;
 use crate::context_store::ContextStore;
 use crate::thread::ThreadId;
@@ -40,6 +45,7 @@ enum ContextPickerMode {
     Symbol,
     Fetch,
     Thread,
+    Resource,
 }
 
 impl TryFrom<&str> for ContextPickerMode {
@@ -63,6 +69,7 @@ impl ContextPickerMode {
             Self::Symbol => "symbol",
             Self::Fetch => "fetch",
             Self::Thread => "thread",
+            Self::Resource => "resource",
         }
     }
 
@@ -72,6 +79,7 @@ impl ContextPickerMode {
             Self::Symbol => "Symbols",
             Self::Fetch => "Fetch",
             Self::Thread => "Threads",
+            Self::Resource => "Resources",
         }
     }
 
@@ -81,6 +89,7 @@ impl ContextPickerMode {
             Self::Symbol => IconName::Code,
             Self::Fetch => IconName::Globe,
             Self::Thread => IconName::MessageBubbles,
+            Self::Resource => IconName::Server,
         }
     }
 }
@@ -92,6 +101,7 @@ enum ContextPickerState {
     Symbol(Entity<SymbolContextPicker>),
     Fetch(Entity<FetchContextPicker>),
     Thread(Entity<ThreadContextPicker>),
+    Resource(Entity<ResourceContextPicker>),
 }
 
 pub(super) struct ContextPicker {
@@ -155,7 +165,10 @@ impl ContextPicker {
                 .enumerate()
                 .map(|(ix, entry)| self.recent_menu_item(context_picker.clone(), ix, entry));
 
-            let modes = supported_context_picker_modes(&self.thread_store);
+            let mut modes = supported_context_picker_modes(&self.thread_store);
+            if self.resource_context_server_manager(cx).is_some() {
+                modes.push(ContextPickerMode::Resource);
+            }
 
             menu.when(has_recent, |menu| {
                 menu.custom_row(|_, _| {
@@ -193,6 +206,23 @@ impl ContextPicker {
         menu
     }
 
+    /// Returns the context server manager of the thread store, if any running context server
+    /// offers resources.
+    fn resource_context_server_manager(&self, cx: &App) -> Option<Entity<ContextServerManager>> {
+        let thread_store = self.thread_store.as_ref()?.upgrade()?;
+        let context_server_manager = thread_store.read(cx).context_server_manager();
+        let has_resources = context_server_manager
+            .read(cx)
+            .running_servers()
+            .iter()
+            .any(|server| {
+                server
+                    .client()
+                    .map_or(false, |client| client.capable(ServerCapability::Resources))
+            });
+        has_resources.then_some(context_server_manager)
+    }
+
     /// Whether threads are allowed as context.
     pub fn allow_threads(&self) -> bool {
         self.thread_store.is_some()
@@ -253,6 +283,19 @@ impl ContextPicker {
                     }));
                 }
             }
+            ContextPickerMode::Resource => {
+                if let Some(context_server_manager) = self.resource_context_server_manager(cx) {
+                    self.mode = ContextPickerState::Resource(cx.new(|cx| {
+                        ResourceContextPicker::new(
+                            context_server_manager,
+                            context_picker.clone(),
+                            self.context_store.clone(),
+                            window,
+                            cx,
+                        )
+                    }));
+                }
+            }
         }
 
         cx.notify();
@@ -381,6 +424,7 @@ impl ContextPicker {
             ContextPickerState::Symbol(entity) => entity.update(cx, |_, cx| cx.notify()),
             ContextPickerState::Fetch(entity) => entity.update(cx, |_, cx| cx.notify()),
             ContextPickerState::Thread(entity) => entity.update(cx, |_, cx| cx.notify()),
+            ContextPickerState::Resource(entity) => entity.update(cx, |_, cx| cx.notify()),
         }
     }
 }
@@ -395,6 +439,7 @@ impl Focusable for ContextPicker {
             ContextPickerState::Symbol(symbol_picker) => symbol_picker.focus_handle(cx),
             ContextPickerState::Fetch(fetch_picker) => fetch_picker.focus_handle(cx),
             ContextPickerState::Thread(thread_picker) => thread_picker.focus_handle(cx),
+            ContextPickerState::Resource(resource_picker) => resource_picker.focus_handle(cx),
         }
     }
 }
@@ -410,6 +455,9 @@ impl Render for ContextPicker {
                 ContextPickerState::Symbol(symbol_picker) => parent.child(symbol_picker.clone()),
                 ContextPickerState::Fetch(fetch_picker) => parent.child(fetch_picker.clone()),
                 ContextPickerState::Thread(thread_picker) => parent.child(thread_picker.clone()),
+                ContextPickerState::Resource(resource_picker) => {
+                    parent.child(resource_picker.clone())
+                }
             })
     }
 }
@@ -94,6 +94,8 @@ fn search(
                 Task::ready(Vec::new())
             }
         }
+        // Resources are only offered by the context picker, which lists them per server.
+        Some(ContextPickerMode::Resource) => Task::ready(Vec::new()),
         None => {
             if query.is_empty() {
                 let mut matches = recent_entries
@@ -0,0 +1,946 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use anyhow::{Context as _, Result};
+use context_server::manager::{ContextServer, ContextServerManager};
+use context_server::protocol::{InitializedContextServerProtocol, ServerCapability};
+use context_server::types;
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Subscription, Task, WeakEntity};
+use http_client::Url;
+use picker::{Picker, PickerDelegate};
+use ui::{ListItem, prelude::*};
+use util::ResultExt as _;
+
+use crate::context_picker::ContextPicker;
+use crate::context_store::{ContextStore, ResourceKey};
+
+pub struct ResourceContextPicker {
+    picker: Entity<Picker<ResourceContextPickerDelegate>>,
+    _load_entries: Task<()>,
+    _context_server_subscription: Subscription,
+}
+
+impl ResourceContextPicker {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<ContextStore>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = ResourceContextPickerDelegate::new(
+            context_server_manager.clone(),
+            context_picker,
+            context_store,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let load_entries = Self::load_entries(&context_server_manager, &picker, window, cx);
+
+        // Servers that start, stop or change their resources change what can be picked.
+        let context_server_subscription = cx.subscribe_in(
+            &context_server_manager,
+            window,
+            |this, context_server_manager, event, window, cx| match event {
+                context_server::manager::Event::ServerStarted { .. }
+                | context_server::manager::Event::ServerStopped { .. }
+                | context_server::manager::Event::ResourcesChanged { .. } => {
+                    this._load_entries =
+                        Self::load_entries(context_server_manager, &this.picker, window, cx);
+                }
+                _ => {}
+            },
+        );
+
+        Self {
+            picker,
+            _load_entries: load_entries,
+            _context_server_subscription: context_server_subscription,
+        }
+    }
+
+    fn load_entries(
+        context_server_manager: &Entity<ContextServerManager>,
+        picker: &Entity<Picker<ResourceContextPickerDelegate>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        let load_entries = load_resource_entries(context_server_manager, cx);
+        let weak_picker = picker.downgrade();
+        cx.spawn_in(window, async move |_, cx| {
+            let entries = load_entries.await;
+            weak_picker
+                .update_in(cx, |picker, window, cx| {
+                    picker.delegate.entries = entries;
+                    picker.refresh(window, cx);
+                })
+                .ok();
+        })
+    }
+}
+
+impl Focusable for ResourceContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for ResourceContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+/// A resource or resource template offered by a context server.
+#[derive(Clone)]
+pub struct ResourceEntry {
+    server: Arc<ContextServer>,
+    name: SharedString,
+    kind: ResourceEntryKind,
+}
+
+#[derive(Clone)]
+enum ResourceEntryKind {
+    Resource { uri: Url },
+    Template { uri_template: String },
+}
+
+impl ResourceEntry {
+    fn uri(&self) -> &str {
+        match &self.kind {
+            ResourceEntryKind::Resource { uri } => uri.as_str(),
+            ResourceEntryKind::Template { uri_template } => uri_template,
+        }
+    }
+}
+
+/// The arguments of a resource template being filled in, one variable at a time.
+struct TemplateArguments {
+    entry: ResourceEntry,
+    uri_template: String,
+    variables: Vec<String>,
+    values: Vec<(String, String)>,
+}
+
+impl TemplateArguments {
+    fn current_variable(&self) -> Option<&str> {
+        self.variables
+            .get(self.values.len())
+            .map(|variable| variable.as_str())
+    }
+}
+
+enum ResourceMatch {
+    Entry(ResourceEntry),
+    /// A value for the template variable being filled in.
+    Value(String),
+}
+
+pub struct ResourceContextPickerDelegate {
+    context_server_manager: Entity<ContextServerManager>,
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<ContextStore>,
+    entries: Vec<ResourceEntry>,
+    template_arguments: Option<TemplateArguments>,
+    matches: Vec<ResourceMatch>,
+    selected_index: usize,
+}
+
+impl ResourceContextPickerDelegate {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<ContextStore>,
+    ) -> Self {
+        ResourceContextPickerDelegate {
+            context_server_manager,
+            context_picker,
+            context_store,
+            entries: Vec::new(),
+            template_arguments: None,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    fn add_resource(
+        &self,
+        server: Arc<ContextServer>,
+        uri: Url,
+        name: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(client) = server.client() else {
+            return;
+        };
+        let context_server_manager = self.context_server_manager.clone();
+        let context_store = self.context_store.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let text = read_resource_text(&client, uri.clone()).await?;
+            if client.supports_resource_subscriptions() {
+                client.subscribe_resource(uri.clone()).await.log_err();
+            }
+            let key = ResourceKey {
+                server_id: server.id(),
+                uri: uri.to_string(),
+            };
+            context_store.update(cx, |context_store, cx| {
+                context_store.add_resource(context_server_manager, key, name, text, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn start_template(
+        &mut self,
+        entry: ResourceEntry,
+        uri_template: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let variables = uri_template_variables(&uri_template);
+        self.template_arguments = Some(TemplateArguments {
+            entry,
+            uri_template,
+            variables,
+            values: Vec::new(),
+        });
+        self.next_template_argument(window, cx);
+    }
+
+    /// Asks for the next argument of the template being filled in, or adds the resource once all
+    /// of them were given.
+    fn next_template_argument(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(arguments) = self.template_arguments.as_ref() else {
+            return;
+        };
+        if arguments.current_variable().is_none() {
+            let arguments = self.template_arguments.take().unwrap();
+            let uri = expand_uri_template(&arguments.uri_template, &arguments.values);
+            match Url::parse(&uri).context("invalid resource URI") {
+                Ok(uri) => {
+                    let name = format!("{} ({})", arguments.entry.name, uri).into();
+                    self.add_resource(arguments.entry.server, uri, name, window, cx);
+                }
+                Err(error) => log::error!("{error:#}"),
+            }
+        }
+
+        cx.defer_in(window, |picker, window, cx| {
+            // Clearing a query that is already empty doesn't update the matches.
+            if picker.query(cx).is_empty() {
+                picker.refresh(window, cx);
+            } else {
+                picker.set_query("", window, cx);
+            }
+            picker.refresh_placeholder(window, cx);
+        });
+    }
+}
+
+impl PickerDelegate for ResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match &self.template_arguments {
+            Some(arguments) => format!(
+                "Enter {} for {}…",
+                arguments.current_variable().unwrap_or_default(),
+                arguments.entry.name
+            )
+            .into(),
+            None => "Search resources…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        if self.template_arguments.is_some() {
+            Some("Type a value".into())
+        } else {
+            Some("No resources found".into())
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        if let Some(arguments) = &self.template_arguments {
+            let completions = arguments.entry.server.client().and_then(|client| {
+                let variable = arguments.current_variable()?.to_string();
+                let reference = types::CompletionReference::Resource(types::ResourceReference {
+                    r#type: types::PromptReferenceType::Resource,
+                    uri: arguments.uri_template.clone(),
+                });
+                let value = query.clone();
+                Some(async move { client.completion(reference, variable, value).await })
+            });
+
+            return cx.spawn_in(window, async move |this, cx| {
+                let mut values = Vec::new();
+                if !query.is_empty() {
+                    values.push(query.clone());
+                }
+                if let Some(completions) = completions {
+                    // Not every server offers completions for its templates.
+                    if let Ok(completion) = completions.await {
+                        values.extend(
+                            completion
+                                .values
+                                .into_iter()
+                                .filter(|value| *value != query),
+                        );
+                    }
+                }
+                this.update(cx, |this, cx| {
+                    this.delegate.matches = values.into_iter().map(ResourceMatch::Value).collect();
+                    this.delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .ok();
+            });
+        }
+
+        let entries = self.entries.clone();
+        let search_task = search_resources(query, Arc::new(AtomicBool::default()), entries, cx);
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = search_task.await;
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches.into_iter().map(ResourceMatch::Entry).collect();
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+
+        match mat {
+            ResourceMatch::Entry(entry) => {
+                let entry = entry.clone();
+                match &entry.kind {
+                    ResourceEntryKind::Resource { uri } => {
+                        let uri = uri.clone();
+                        self.add_resource(entry.server, uri, entry.name, window, cx);
+                    }
+                    ResourceEntryKind::Template { uri_template } => {
+                        let uri_template = uri_template.clone();
+                        self.start_template(entry, uri_template, window, cx);
+                    }
+                }
+            }
+            ResourceMatch::Value(value) => {
+                let value = value.clone();
+                if let Some(arguments) = self.template_arguments.as_mut() {
+                    let variable = arguments.current_variable().unwrap_or_default().to_string();
+                    arguments.values.push((variable, value));
+                }
+                self.next_template_argument(window, cx);
+            }
+        }
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix).inset(true).toggle_state(selected);
+        match &self.matches[ix] {
+            ResourceMatch::Entry(entry) => Some(item.child(render_resource_context_entry(
+                entry,
+                self.context_store.clone(),
+                cx,
+            ))),
+            ResourceMatch::Value(value) => Some(item.child(Label::new(value.clone()))),
+        }
+    }
+}
+
+fn render_resource_context_entry(
+    entry: &ResourceEntry,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let added = match &entry.kind {
+        ResourceEntryKind::Resource { uri } => {
+            context_store.upgrade().map_or(false, |context_store| {
+                let key = ResourceKey {
+                    server_id: entry.server.id(),
+                    uri: uri.to_string(),
+                };
+                context_store.read(cx).includes_resource(&key).is_some()
+            })
+        }
+        ResourceEntryKind::Template { .. } => false,
+    };
+    let icon = match entry.kind {
+        ResourceEntryKind::Resource { .. } => IconName::File,
+        ResourceEntryKind::Template { .. } => IconName::FileTree,
+    };
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .justify_between()
+        .child(
+            h_flex()
+                .gap_1p5()
+                .max_w_72()
+                .child(Icon::new(icon).size(IconSize::XSmall).color(Color::Muted))
+                .child(Label::new(entry.name.clone()).truncate())
+                .child(
+                    Label::new(entry.server.id())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .truncate(),
+                ),
+        )
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
+
+/// Lists the resources and resource templates of all running context servers.
+fn load_resource_entries(
+    context_server_manager: &Entity<ContextServerManager>,
+    cx: &App,
+) -> Task<Vec<ResourceEntry>> {
+    let servers = context_server_manager
+        .read(cx)
+        .running_servers()
+        .into_iter()
+        .filter_map(|server| {
+            let client = server.client()?;
+            client
+                .capable(ServerCapability::Resources)
+                .then_some((server, client))
+        })
+        .collect::<Vec<_>>();
+
+    cx.foreground_executor().spawn(async move {
+        let mut entries = Vec::new();
+        for (server, client) in servers {
+            if let Some(response) = client.list_resources().await.log_err() {
+                entries.extend(
+                    response
+                        .resources
+                        .into_iter()
+                        .map(|resource| ResourceEntry {
+                            server: server.clone(),
+                            name: resource.name.into(),
+                            kind: ResourceEntryKind::Resource { uri: resource.uri },
+                        }),
+                );
+            }
+            // Servers that don't have templates may not support listing them.
+            if let Ok(response) = client.list_resource_templates().await {
+                entries.extend(response.resource_templates.into_iter().map(|template| {
+                    ResourceEntry {
+                        server: server.clone(),
+                        name: template.name.into(),
+                        kind: ResourceEntryKind::Template {
+                            uri_template: template.uri_template,
+                        },
+                    }
+                }));
+            }
+        }
+        entries
+    })
+}
+
+fn search_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    entries: Vec<ResourceEntry>,
+    cx: &mut App,
+) -> Task<Vec<ResourceEntry>> {
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        if query.is_empty() {
+            return entries;
+        }
+
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(id, &format!("{} {}", entry.name, entry.uri()))
+            })
+            .collect::<Vec<_>>();
+        let matches = fuzzy::match_strings(
+            &candidates,
+            &query,
+            false,
+            100,
+            &cancellation_flag,
+            executor,
+        )
+        .await;
+
+        matches
+            .into_iter()
+            .map(|mat| entries[mat.candidate_id].clone())
+            .collect()
+    })
+}
+
+/// Reads a resource, returning its text contents. Binary contents are left out.
+pub(crate) async fn read_resource_text(
+    client: &InitializedContextServerProtocol,
+    uri: Url,
+) -> Result<String> {
+    let response = client.read_resource(uri).await?;
+    let mut text = String::new();
+    for contents in response.contents {
+        match contents {
+            types::ResourceContentsType::Text(contents) => {
+                text.push_str(&contents.text);
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            types::ResourceContentsType::Blob(contents) => {
+                writeln!(text, "[binary contents of {} omitted]", contents.uri)?;
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Returns the names of the variables in a URI template, such as `path` in `file:///{path}`.
+fn uri_template_variables(uri_template: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    let mut rest = uri_template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let expression = &rest[start + 1..start + end];
+        for variable in expression
+            .trim_start_matches(['+', '#', '.', '/', ';', '?', '&'])
+            .split(',')
+        {
+            let variable = variable.trim_end_matches('*');
+            if !variable.is_empty() && !variables.iter().any(|name| name == variable) {
+                variables.push(variable.to_string());
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    variables
+}
+
+/// Fills in the variables of a URI template. Values are percent-encoded, except in reserved
+/// expansions such as `{+path}`, which may contain slashes.
+fn expand_uri_template(uri_template: &str, values: &[(String, String)]) -> String {
+    let value = |name: &str| {
+        values
+            .iter()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    };
+
+    let mut uri = String::new();
+    let mut rest = uri_template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        uri.push_str(&rest[..start]);
+        let expression = &rest[start + 1..start + end];
+        let (reserved, names) = match expression.strip_prefix('+') {
+            Some(names) => (true, names),
+            None => (false, expression),
+        };
+        let expanded = names
+            .split(',')
+            .map(|name| {
+                let value = value(name.trim_end_matches('*'));
+                if reserved {
+                    value.to_string()
+                } else {
+                    percent_encode(value)
+                }
+            })
+            .collect::<Vec<_>>();
+        uri.push_str(&expanded.join(","));
+        rest = &rest[start + end + 1..];
+    }
+    uri.push_str(rest);
+    uri
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            write!(encoded, "%{byte:02X}").unwrap();
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::AssistantContext;
+    use context_server::{ContextServerFactoryRegistry, FakeTransport};
+    use gpui::{TestAppContext, VisualTestContext};
+    use parking_lot::Mutex;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+    use workspace::Workspace;
+
+    #[gpui::test]
+    async fn test_filling_template_arguments(cx: &mut TestAppContext) {
+        let (transport, context_store, picker, cx) = init_test(cx).await;
+        transport.on_request("completion/complete", |params| {
+            let values = match params["argument"]["name"].as_str().unwrap() {
+                "schema" => vec!["public", "private"],
+                _ => vec![],
+            };
+            let value = params["argument"]["value"].as_str().unwrap();
+            let values = values
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(value))
+                .collect::<Vec<_>>();
+            json!({ "completion": { "values": values } })
+        });
+
+        assert_eq!(match_labels(&picker, cx), ["Readme", "Table"]);
+        confirm_match(&picker, 1, cx);
+        assert_eq!(placeholder(&picker, cx), "Enter schema for Table…");
+        assert_eq!(match_labels(&picker, cx), ["public", "private"]);
+
+        // The typed value is offered along with the server's completions for it.
+        picker.update_in(cx, |picker, window, cx| picker.set_query("pri", window, cx));
+        cx.run_until_parked();
+        assert_eq!(match_labels(&picker, cx), ["pri", "private"]);
+        confirm_match(&picker, 1, cx);
+
+        assert_eq!(placeholder(&picker, cx), "Enter table for Table…");
+        assert!(match_labels(&picker, cx).is_empty());
+        picker.update_in(cx, |picker, window, cx| {
+            picker.set_query("my table", window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(match_labels(&picker, cx), ["my table"]);
+        confirm_match(&picker, 0, cx);
+
+        // Once every argument is given, the expanded resource is added and the resources are
+        // offered again.
+        assert_eq!(placeholder(&picker, cx), "Search resources…");
+        assert_eq!(match_labels(&picker, cx), ["Readme", "Table"]);
+        assert_eq!(
+            resource_contexts(&context_store, cx),
+            [(
+                "Table (db://private/tables/my%20table)".to_string(),
+                "contents of db://private/tables/my%20table\n".to_string()
+            )]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_refreshing_resources(cx: &mut TestAppContext) {
+        let (transport, context_store, picker, cx) = init_test(cx).await;
+        let readme = Arc::new(Mutex::new("Read me".to_string()));
+        transport.on_request("resources/read", {
+            let readme = readme.clone();
+            move |params| {
+                json!({
+                    "contents": [{ "uri": params["uri"], "text": readme.lock().clone() }]
+                })
+            }
+        });
+        let subscriptions = Arc::new(Mutex::new(Vec::new()));
+        for method in ["resources/subscribe", "resources/unsubscribe"] {
+            let subscriptions = subscriptions.clone();
+            transport.on_request(method, move |params| {
+                subscriptions
+                    .lock()
+                    .push(format!("{method} {}", params["uri"].as_str().unwrap()));
+                json!({})
+            });
+        }
+
+        // Resources are listed again when the server reports that they changed.
+        assert_eq!(match_labels(&picker, cx), ["Readme", "Table"]);
+        transport.on_request("resources/list", |_| {
+            json!({
+                "resources": [
+                    { "uri": "file:///readme", "name": "Readme" },
+                    { "uri": "file:///changelog", "name": "Changelog" },
+                ]
+            })
+        });
+        transport.notify("notifications/resources/list_changed", json!({}));
+        cx.run_until_parked();
+        assert_eq!(match_labels(&picker, cx), ["Readme", "Changelog", "Table"]);
+
+        confirm_match(&picker, 0, cx);
+        assert_eq!(
+            resource_contexts(&context_store, cx),
+            [("Readme".to_string(), "Read me\n".to_string())]
+        );
+        assert_eq!(
+            *subscriptions.lock(),
+            ["resources/subscribe file:///readme"]
+        );
+
+        // Updates to subscribed resources are read into the context, while other updates are
+        // ignored.
+        *readme.lock() = "Read me again".to_string();
+        transport.notify(
+            "notifications/resources/updated",
+            json!({ "uri": "file:///changelog" }),
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            resource_contexts(&context_store, cx),
+            [("Readme".to_string(), "Read me\n".to_string())]
+        );
+        transport.notify(
+            "notifications/resources/updated",
+            json!({ "uri": "file:///readme" }),
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            resource_contexts(&context_store, cx),
+            [("Readme".to_string(), "Read me again\n".to_string())]
+        );
+
+        // Removing the resource from the context stops its updates.
+        context_store.update(cx, |context_store, cx| {
+            let id = context_store.context()[0].id();
+            context_store.remove_context(id, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            *subscriptions.lock(),
+            [
+                "resources/subscribe file:///readme",
+                "resources/unsubscribe file:///readme"
+            ]
+        );
+    }
+
+    /// Opens a resource picker for a running server that offers a resource and a resource
+    /// template.
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (
+        FakeTransport,
+        Entity<ContextStore>,
+        Entity<Picker<ResourceContextPickerDelegate>>,
+        &mut VisualTestContext,
+    ) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init_settings(cx);
+            command_palette_hooks::init(cx);
+            context_server::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/test"), json!({})).await;
+        let project = Project::test(fs, [path!("/test").as_ref()], cx).await;
+        let context_server_manager = cx.new(|cx| {
+            ContextServerManager::new(
+                cx.new(|_| ContextServerFactoryRegistry::new()),
+                project.clone(),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let transport = FakeTransport::new();
+        transport.on_request("initialize", |_| {
+            json!({
+                "protocolVersion": types::LATEST_PROTOCOL_VERSION,
+                "capabilities": { "resources": { "subscribe": true, "listChanged": true } },
+                "serverInfo": { "name": "test", "version": "1.0.0" },
+            })
+        });
+        transport.on_request(
+            "resources/list",
+            |_| json!({ "resources": [{ "uri": "file:///readme", "name": "Readme" }] }),
+        );
+        transport.on_request("resources/templates/list", |_| {
+            json!({
+                "resourceTemplates": [
+                    { "uriTemplate": "db://{schema}/tables/{table}", "name": "Table" }
+                ]
+            })
+        });
+        transport.on_request("resources/read", |params| {
+            let uri = params["uri"].as_str().unwrap();
+            json!({ "contents": [{ "uri": uri, "text": format!("contents of {uri}") }] })
+        });
+        transport.on_request("resources/subscribe", |_| json!({}));
+        context_server_manager
+            .update(cx, |manager, cx| {
+                manager.start_fake_server("test".into(), transport.clone(), cx)
+            })
+            .await
+            .unwrap();
+
+        let context_store = cx.new(|_| ContextStore::new(project.downgrade(), None));
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let context_picker = cx.new_window_entity(|window, cx| {
+            ContextPicker::new(
+                workspace.downgrade(),
+                None,
+                context_store.downgrade(),
+                window,
+                cx,
+            )
+        });
+        let resource_picker = cx.new_window_entity(|window, cx| {
+            ResourceContextPicker::new(
+                context_server_manager,
+                context_picker.downgrade(),
+                context_store.downgrade(),
+                window,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let picker =
+            resource_picker.read_with(cx, |resource_picker, _| resource_picker.picker.clone());
+        (transport, context_store, picker, cx)
+    }
+
+    fn match_labels(
+        picker: &Entity<Picker<ResourceContextPickerDelegate>>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<String> {
+        picker.read_with(cx, |picker, _| {
+            picker
+                .delegate
+                .matches
+                .iter()
+                .map(|mat| match mat {
+                    ResourceMatch::Entry(entry) => entry.name.to_string(),
+                    ResourceMatch::Value(value) => value.clone(),
+                })
+                .collect()
+        })
+    }
+
+    fn placeholder(
+        picker: &Entity<Picker<ResourceContextPickerDelegate>>,
+        cx: &mut VisualTestContext,
+    ) -> String {
+        picker.update_in(cx, |picker, window, cx| {
+            picker.delegate.placeholder_text(window, cx).to_string()
+        })
+    }
+
+    fn confirm_match(
+        picker: &Entity<Picker<ResourceContextPickerDelegate>>,
+        ix: usize,
+        cx: &mut VisualTestContext,
+    ) {
+        picker.update_in(cx, |picker, window, cx| {
+            picker.delegate.set_selected_index(ix, window, cx);
+            picker.delegate.confirm(false, window, cx);
+        });
+        cx.run_until_parked();
+    }
+
+    /// Returns the names and texts of the resources in the context.
+    fn resource_contexts(
+        context_store: &Entity<ContextStore>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<(String, String)> {
+        context_store.read_with(cx, |context_store, _| {
+            context_store
+                .context()
+                .iter()
+                .filter_map(|context| match context {
+                    AssistantContext::Resource(resource) => {
+                        Some((resource.name.to_string(), resource.text.to_string()))
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_uri_templates() {
+        assert_eq!(
+            uri_template_variables("db://{schema}/tables/{+path}{?limit,schema}"),
+            ["schema", "path", "limit"]
+        );
+        assert_eq!(
+            expand_uri_template(
+                "db://{schema}/tables/{+path}",
+                &[
+                    ("schema".into(), "my schema".into()),
+                    ("path".into(), "a/b".into())
+                ]
+            ),
+            "db://my%20schema/tables/a/b"
+        );
+        assert_eq!(expand_uri_template("file:///readme", &[]), "file:///readme");
+    }
+}
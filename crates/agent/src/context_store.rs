@@ -4,9 +4,11 @@ use std::sync::Arc;
 
 use anyhow::{Context as _, Result, anyhow};
 use collections::{BTreeMap, HashMap, HashSet};
+use context_server::manager::ContextServerManager;
 use futures::future::join_all;
 use futures::{self, Future, FutureExt, future};
-use gpui::{App, AppContext as _, Context, Entity, SharedString, Task, WeakEntity};
+use gpui::{App, AppContext as _, Context, Entity, SharedString, Subscription, Task, WeakEntity};
+use http_client::Url;
 use language::{Buffer, File};
 use project::{Project, ProjectItem, ProjectPath, Worktree};
 use rope::Rope;
@@ -16,8 +18,9 @@ use util::{ResultExt as _, maybe};
 use crate::ThreadStore;
 use crate::context::{
     AssistantContext, ContextBuffer, ContextId, ContextSymbol, ContextSymbolId, DirectoryContext,
-    FetchedUrlContext, FileContext, SymbolContext, ThreadContext,
+    FetchedUrlContext, FileContext, ResourceContext, SymbolContext, ThreadContext,
 };
+use crate::context_picker::read_resource_text;
 use crate::context_strip::SuggestedContext;
 use crate::thread::{Thread, ThreadId};
 
@@ -35,6 +38,18 @@ pub struct ContextStore {
     threads: HashMap<ThreadId, ContextId>,
     thread_summary_tasks: Vec<Task<()>>,
     fetched_urls: HashMap<String, ContextId>,
+    resources: HashMap<ResourceKey, ContextId>,
+    /// The manager of the context servers that resources were added from, whose updates to
+    /// subscribed resources are applied to the context.
+    context_server_manager: Option<WeakEntity<ContextServerManager>>,
+    _context_server_subscription: Option<Subscription>,
+}
+
+/// Identifies a resource by the context server it belongs to and its URI.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResourceKey {
+    pub server_id: Arc<str>,
+    pub uri: String,
 }
 
 impl ContextStore {
@@ -55,6 +70,9 @@ impl ContextStore {
             threads: HashMap::default(),
             thread_summary_tasks: Vec::new(),
             fetched_urls: HashMap::default(),
+            resources: HashMap::default(),
+            context_server_manager: None,
+            _context_server_subscription: None,
         }
     }
 
@@ -66,12 +84,15 @@ impl ContextStore {
         self.context().iter().find(|context| context.id() == id)
     }
 
-    pub fn clear(&mut self) {
+    pub fn clear(&mut self, cx: &mut App) {
         self.context.clear();
         self.files.clear();
         self.directories.clear();
         self.threads.clear();
         self.fetched_urls.clear();
+        for key in std::mem::take(&mut self.resources).into_keys() {
+            self.unsubscribe_resource(key, cx);
+        }
     }
 
     pub fn add_file_from_path(
@@ -416,6 +437,101 @@ impl ContextStore {
         cx.notify();
     }
 
+    pub fn add_resource(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        key: ResourceKey,
+        name: SharedString,
+        text: impl Into<SharedString>,
+        cx: &mut Context<ContextStore>,
+    ) {
+        if self.includes_resource(&key).is_some() {
+            return;
+        }
+
+        if self.context_server_manager.is_none() {
+            self._context_server_subscription =
+                Some(cx.subscribe(&context_server_manager, Self::handle_context_server_event));
+            self.context_server_manager = Some(context_server_manager.downgrade());
+        }
+
+        let id = self.next_context_id.post_inc();
+        self.resources.insert(key.clone(), id);
+        self.context
+            .push(AssistantContext::Resource(ResourceContext {
+                id,
+                server_id: key.server_id,
+                uri: key.uri.into(),
+                name,
+                text: text.into(),
+            }));
+        cx.notify();
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        event: &context_server::manager::Event,
+        cx: &mut Context<Self>,
+    ) {
+        let context_server::manager::Event::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+        let key = ResourceKey {
+            server_id: server_id.clone(),
+            uri: uri.to_string(),
+        };
+        let Some(id) = self.includes_resource(&key) else {
+            return;
+        };
+        let Some(client) = context_server_manager
+            .read(cx)
+            .get_server(server_id)
+            .and_then(|server| server.client())
+        else {
+            return;
+        };
+
+        let uri = uri.clone();
+        cx.spawn(async move |this, cx| {
+            let text = read_resource_text(&client, uri).await?;
+            this.update(cx, |this, cx| {
+                let Some(AssistantContext::Resource(context)) = this.context_for_id(id) else {
+                    return;
+                };
+                let context = ResourceContext {
+                    text: text.into(),
+                    ..context.clone()
+                };
+                this.replace_context(AssistantContext::Resource(context));
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Stops receiving updates to a resource that is no longer part of the context.
+    fn unsubscribe_resource(&self, key: ResourceKey, cx: &mut App) {
+        let Some(client) = self
+            .context_server_manager
+            .as_ref()
+            .and_then(|manager| manager.upgrade())
+            .and_then(|manager| manager.read(cx).get_server(&key.server_id))
+            .and_then(|server| server.client())
+        else {
+            return;
+        };
+        if !client.supports_resource_subscriptions() {
+            return;
+        }
+        let Some(uri) = Url::parse(&key.uri).log_err() else {
+            return;
+        };
+        cx.foreground_executor()
+            .spawn(async move { client.unsubscribe_resource(uri).await.log_err() })
+            .detach();
+    }
+
     pub fn accept_suggested_context(
         &mut self,
         suggested: &SuggestedContext,
@@ -471,6 +587,14 @@ impl ContextStore {
             AssistantContext::Thread(_) => {
                 self.threads.retain(|_, context_id| *context_id != id);
             }
+            AssistantContext::Resource(resource) => {
+                let key = ResourceKey {
+                    server_id: resource.server_id,
+                    uri: resource.uri.to_string(),
+                };
+                self.resources.remove(&key);
+                self.unsubscribe_resource(key, cx);
+            }
         }
 
         cx.notify();
@@ -571,6 +695,10 @@ impl ContextStore {
         self.fetched_urls.get(url).copied()
     }
 
+    pub fn includes_resource(&self, key: &ResourceKey) -> Option<ContextId> {
+        self.resources.get(key).copied()
+    }
+
     /// Replaces the context that matches the ID of the new context, if any match.
     fn replace_context(&mut self, new_context: AssistantContext) {
         let id = new_context.id();
@@ -593,7 +721,8 @@ impl ContextStore {
                 AssistantContext::Directory(_)
                 | AssistantContext::Symbol(_)
                 | AssistantContext::FetchedUrl(_)
-                | AssistantContext::Thread(_) => None,
+                | AssistantContext::Thread(_)
+                | AssistantContext::Resource(_) => None,
             })
             .collect()
     }
@@ -779,6 +908,8 @@ pub fn refresh_context_store_text(
                 // and doing the caching properly could be tricky (unless it's already handled by
                 // the HttpClient?).
                 AssistantContext::FetchedUrl(_) => {}
+                // Resources are refreshed when their context server reports that they changed.
+                AssistantContext::Resource(_) => {}
             }
 
             None
@@ -1,7 +1,9 @@
 use assistant_context_editor::SavedContextMetadata;
 use chrono::{DateTime, Utc};
+use collections::{HashMap, HashSet};
 use gpui::{Entity, prelude::*};
 
+use crate::thread::ThreadId;
 use crate::thread_store::{SerializedThreadMetadata, ThreadStore};
 
 #[derive(Debug)]
@@ -60,10 +62,61 @@ impl HistoryStore {
         }
 
         history_entries.sort_unstable_by_key(|entry| std::cmp::Reverse(entry.updated_at()));
-        history_entries
+        order_forks_after_parents(history_entries)
     }
 
     pub fn recent_entries(&self, limit: usize, cx: &mut Context<Self>) -> Vec<HistoryEntry> {
         self.entries(cx).into_iter().take(limit).collect()
     }
 }
+
+/// Reorders the entries so that threads forked from another thread directly follow
+/// it, keeping the existing order among siblings.
+fn order_forks_after_parents(entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+    let thread_ids = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            HistoryEntry::Thread(thread) => Some(thread.id.clone()),
+            HistoryEntry::Context(_) => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let mut roots = Vec::new();
+    let mut forks_by_parent = HashMap::<ThreadId, Vec<HistoryEntry>>::default();
+    for entry in entries {
+        let parent_id = match &entry {
+            HistoryEntry::Thread(thread) => thread
+                .forked_from
+                .as_ref()
+                .map(|forked_from| &forked_from.thread_id)
+                .filter(|parent_id| thread_ids.contains(*parent_id))
+                .cloned(),
+            HistoryEntry::Context(_) => None,
+        };
+        match parent_id {
+            Some(parent_id) => forks_by_parent.entry(parent_id).or_default().push(entry),
+            None => roots.push(entry),
+        }
+    }
+
+    fn push_with_forks(
+        entry: HistoryEntry,
+        forks_by_parent: &mut HashMap<ThreadId, Vec<HistoryEntry>>,
+        ordered: &mut Vec<HistoryEntry>,
+    ) {
+        let forks = match &entry {
+            HistoryEntry::Thread(thread) => forks_by_parent.remove(&thread.id),
+            HistoryEntry::Context(_) => None,
+        };
+        ordered.push(entry);
+        for fork in forks.into_iter().flatten() {
+            push_with_forks(fork, forks_by_parent, ordered);
+        }
+    }
+
+    let mut ordered = Vec::new();
+    for entry in roots {
+        push_with_forks(entry, &mut forks_by_parent, &mut ordered);
+    }
+    ordered
+}
@@ -265,6 +265,11 @@ impl TerminalInlineAssistant {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         })
     }
 
@@ -1,5 +1,6 @@
 use std::fmt::Write as _;
 use std::io::Write;
+use std::mem;
 use std::ops::Range;
 use std::sync::Arc;
 use std::time::Instant;
@@ -10,6 +11,7 @@ use assistant_tool::{ActionLog, Tool, ToolWorkingSet};
 use chrono::{DateTime, Utc};
 use collections::{BTreeMap, HashMap};
 use feature_flags::{self, FeatureFlagAppExt};
+use futures::channel::oneshot;
 use futures::future::Shared;
 use futures::{FutureExt, StreamExt as _};
 use git::repository::DiffType;
@@ -33,6 +35,7 @@ use util::{ResultExt as _, TryFutureExt as _, post_inc};
 use uuid::Uuid;
 
 use crate::context::{AssistantContext, ContextId, format_context_as_string};
+use crate::thread_export::ExportedContext;
 use crate::thread_store::{
     SerializedMessage, SerializedMessageSegment, SerializedThread, SerializedToolResult,
     SerializedToolUse, SharedProjectContext,
@@ -179,12 +182,25 @@ pub struct GitState {
     pub diff: Option<String>,
 }
 
-#[derive(Clone)]
+/// The point in another thread that a thread was forked from.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ForkedFrom {
+    pub thread_id: ThreadId,
+    pub message_id: MessageId,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ThreadCheckpoint {
     message_id: MessageId,
     git_checkpoint: GitStoreCheckpoint,
 }
 
+impl ThreadCheckpoint {
+    pub fn git_checkpoint(&self) -> &GitStoreCheckpoint {
+        &self.git_checkpoint
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ThreadFeedback {
     Positive,
@@ -260,6 +276,14 @@ pub struct Thread {
     action_log: Entity<ActionLog>,
     last_restore_checkpoint: Option<LastRestoreCheckpoint>,
     pending_checkpoint: Option<ThreadCheckpoint>,
+    /// The project state after the most recent turn, kept so that forks of this
+    /// thread can be compared with each other.
+    outcome_checkpoint: Option<GitStoreCheckpoint>,
+    forked_from: Option<ForkedFrom>,
+    imported_context: Vec<ExportedContext>,
+    /// The tool uses being replayed by [`Thread::replay_tool_uses`], which are notified when
+    /// they finish.
+    replayed_tool_uses: HashMap<LanguageModelToolUseId, oneshot::Sender<()>>,
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     cumulative_token_usage: TokenUsage,
     exceeded_window_error: Option<ExceededWindowError>,
@@ -303,6 +327,10 @@ impl Thread {
             tools: tools.clone(),
             last_restore_checkpoint: None,
             pending_checkpoint: None,
+            outcome_checkpoint: None,
+            forked_from: None,
+            imported_context: Vec::new(),
+            replayed_tool_uses: HashMap::default(),
             tool_use: ToolUseState::new(tools.clone()),
             action_log: cx.new(|_| ActionLog::new(project.clone())),
             initial_project_snapshot: {
@@ -367,11 +395,19 @@ impl Thread {
             context: BTreeMap::default(),
             context_by_message: HashMap::default(),
             project_context,
-            checkpoints_by_message: HashMap::default(),
+            checkpoints_by_message: serialized
+                .checkpoints
+                .into_iter()
+                .map(|checkpoint| (checkpoint.message_id, checkpoint))
+                .collect(),
             completion_count: 0,
             pending_completions: Vec::new(),
             last_restore_checkpoint: None,
             pending_checkpoint: None,
+            outcome_checkpoint: serialized.outcome_checkpoint,
+            forked_from: serialized.forked_from,
+            imported_context: serialized.imported_context,
+            replayed_tool_uses: HashMap::default(),
             project: project.clone(),
             prompt_builder,
             tools,
@@ -406,6 +442,10 @@ impl Thread {
         self.summary.clone()
     }
 
+    pub fn forked_from(&self) -> Option<&ForkedFrom> {
+        self.forked_from.as_ref()
+    }
+
     pub fn project_context(&self) -> SharedProjectContext {
         self.project_context.clone()
     }
@@ -557,11 +597,17 @@ impl Thread {
                     })?;
                 }
 
-                git_store
-                    .update(cx, |store, cx| {
-                        store.delete_checkpoint(final_checkpoint, cx)
-                    })?
-                    .detach();
+                this.update(cx, |this, cx| {
+                    if this.keeps_outcome_checkpoint() {
+                        this.set_outcome_checkpoint(final_checkpoint, cx)
+                    } else {
+                        git_store
+                            .update(cx, |store, cx| {
+                                store.delete_checkpoint(final_checkpoint, cx)
+                            })
+                            .detach();
+                    }
+                })?;
 
                 Ok(())
             }
@@ -572,6 +618,69 @@ impl Thread {
         .detach();
     }
 
+    /// Returns the checkpoint to restore when forking this thread at the given
+    /// message, i.e. the project state right before that message was sent.
+    ///
+    /// Turns that didn't change the project don't keep a checkpoint, so this is
+    /// the checkpoint of the first message from `message_id` onwards that has one.
+    /// When there is none, the project hasn't changed since and the current state
+    /// is already the right one.
+    pub fn checkpoint_for_fork(&self, message_id: MessageId) -> Option<GitStoreCheckpoint> {
+        let message_ix = self
+            .messages
+            .iter()
+            .position(|message| message.id == message_id)?;
+        self.messages[message_ix..].iter().find_map(|message| {
+            self.checkpoints_by_message
+                .get(&message.id)
+                .map(|checkpoint| checkpoint.git_checkpoint.clone())
+        })
+    }
+
+    pub fn checkpoints(&self) -> impl Iterator<Item = &ThreadCheckpoint> {
+        self.checkpoints_by_message.values()
+    }
+
+    pub fn insert_checkpoints(
+        &mut self,
+        checkpoints: impl IntoIterator<Item = ThreadCheckpoint>,
+        cx: &mut Context<Self>,
+    ) {
+        for checkpoint in checkpoints {
+            if self.message(checkpoint.message_id).is_some() {
+                self.insert_checkpoint(checkpoint, cx);
+            }
+        }
+    }
+
+    pub fn outcome_checkpoint(&self) -> Option<GitStoreCheckpoint> {
+        self.outcome_checkpoint.clone()
+    }
+
+    /// Where a thread left the project is only used to compare forks, so it's only
+    /// kept for threads that were forked or that were forked from.
+    fn keeps_outcome_checkpoint(&self) -> bool {
+        self.forked_from.is_some() || self.outcome_checkpoint.is_some()
+    }
+
+    /// Replaces the checkpoint recording where this thread left the project,
+    /// deleting the previous one.
+    pub fn set_outcome_checkpoint(
+        &mut self,
+        checkpoint: GitStoreCheckpoint,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(previous_checkpoint) = self.outcome_checkpoint.replace(checkpoint) {
+            let git_store = self.project.read(cx).git_store().clone();
+            git_store
+                .update(cx, |store, cx| {
+                    store.delete_checkpoint(previous_checkpoint, cx)
+                })
+                .detach();
+        }
+        cx.emit(ThreadEvent::CheckpointChanged);
+    }
+
     fn insert_checkpoint(&mut self, checkpoint: ThreadCheckpoint, cx: &mut Context<Self>) {
         self.checkpoints_by_message
             .insert(checkpoint.message_id, checkpoint);
@@ -594,6 +703,9 @@ impl Thread {
         for deleted_message in self.messages.drain(message_ix..) {
             self.context_by_message.remove(&deleted_message.id);
             self.checkpoints_by_message.remove(&deleted_message.id);
+            self.tool_use.remove_message(deleted_message.id);
+            self.imported_context
+                .retain(|context| context.message_id != deleted_message.id);
         }
         cx.notify();
     }
@@ -609,6 +721,12 @@ impl Thread {
             })
     }
 
+    /// Returns the context of the thread this one was imported from, which is kept as text
+    /// because it can't be resolved to this project's files and threads.
+    pub fn imported_context(&self) -> &[ExportedContext] {
+        &self.imported_context
+    }
+
     /// Returns whether all of the tool uses have finished running.
     pub fn all_tools_finished(&self) -> bool {
         // If the only pending tool uses left are the ones with errors, then
@@ -677,7 +795,9 @@ impl Thread {
                                 cx,
                             );
                         }
-                        AssistantContext::FetchedUrl(_) | AssistantContext::Thread(_) => {}
+                        AssistantContext::FetchedUrl(_)
+                        | AssistantContext::Thread(_)
+                        | AssistantContext::Resource(_) => {}
                     }
                 }
             });
@@ -747,6 +867,9 @@ impl Thread {
         };
         self.messages.remove(index);
         self.context_by_message.remove(&id);
+        self.tool_use.remove_message(id);
+        self.imported_context
+            .retain(|context| context.message_id != id);
         self.touch_updated_at();
         cx.emit(ThreadEvent::MessageDeleted(id));
         true
@@ -831,6 +954,10 @@ impl Thread {
                 cumulative_token_usage: this.cumulative_token_usage,
                 detailed_summary_state: this.detailed_summary_state.clone(),
                 exceeded_window_error: this.exceeded_window_error.clone(),
+                forked_from: this.forked_from.clone(),
+                outcome_checkpoint: this.outcome_checkpoint.clone(),
+                checkpoints: this.checkpoints_by_message.values().cloned().collect(),
+                imported_context: this.imported_context.clone(),
             })
         })
     }
@@ -890,6 +1017,11 @@ impl Thread {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         };
 
         if let Some(project_context) = self.project_context.borrow().as_ref() {
@@ -1426,6 +1558,10 @@ impl Thread {
             )
         };
 
+        if let Some(card) = tool_result.card {
+            self.tool_use.insert_tool_card(tool_use_id.clone(), card);
+        }
+
         cx.spawn({
             async move |thread: WeakEntity<Thread>, cx| {
                 let output = tool_result.output.await;
@@ -1445,6 +1581,93 @@ impl Thread {
         })
     }
 
+    /// Runs every recorded tool use again, in order, against the current state
+    /// of the project, replacing the recorded results with the new ones. Tools
+    /// that need confirmation wait for it, like the model's tool uses do.
+    ///
+    /// This is used to reproduce imported threads without involving a model.
+    pub fn replay_tool_uses(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let mut tool_uses = Vec::new();
+        for message in &self.messages {
+            tool_uses.extend(
+                self.tool_uses_for_message(message.id, cx)
+                    .into_iter()
+                    .map(|tool_use| (message.id, tool_use)),
+            );
+        }
+
+        cx.spawn(async move |this, cx| {
+            for (message_id, tool_use) in tool_uses {
+                let tool_use_id = tool_use.id.clone();
+                let finished = this.update(cx, |this, cx| {
+                    this.replay_tool_use(message_id, tool_use, cx)
+                })?;
+                // The replay stops when its tool use is canceled.
+                if finished.await.is_err() {
+                    break;
+                }
+                this.update(cx, |_, cx| cx.emit(ThreadEvent::ToolReplayed(tool_use_id)))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs a recorded tool use again like a pending one, asking for confirmation first if
+    /// the tool needs it. Returns a receiver that's notified when it finishes.
+    fn replay_tool_use(
+        &mut self,
+        message_id: MessageId,
+        tool_use: ToolUse,
+        cx: &mut Context<Self>,
+    ) -> oneshot::Receiver<()> {
+        let (finished_tx, finished_rx) = oneshot::channel();
+        let tool_name: Arc<str> = tool_use.name.as_ref().into();
+        let Some(tool) = self.tools.read(cx).tool(&tool_name, cx) else {
+            self.tool_use.insert_tool_output(
+                tool_use.id,
+                tool_name.clone(),
+                Err(anyhow!("tool not found: {tool_name}")),
+                cx,
+            );
+            finished_tx.send(()).ok();
+            return finished_rx;
+        };
+
+        self.tool_use.replay_tool_use(
+            message_id,
+            tool_use.id.clone(),
+            tool_name,
+            tool_use.ui_text.clone(),
+            tool_use.input.clone(),
+        );
+        self.replayed_tool_uses
+            .insert(tool_use.id.clone(), finished_tx);
+
+        let messages = Arc::new(self.to_completion_request(RequestKind::Chat, cx).messages);
+        if tool.needs_confirmation(&tool_use.input, cx)
+            && !AssistantSettings::get_global(cx).always_allow_tool_actions
+        {
+            self.tool_use.confirm_tool_use(
+                tool_use.id,
+                tool_use.ui_text,
+                tool_use.input,
+                messages,
+                tool,
+            );
+            cx.emit(ThreadEvent::ToolConfirmationNeeded);
+        } else {
+            self.run_tool(
+                tool_use.id,
+                tool_use.ui_text,
+                tool_use.input,
+                &messages,
+                tool,
+                cx,
+            );
+        }
+        finished_rx
+    }
+
     fn tool_finished(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -1452,6 +1675,17 @@ impl Thread {
         canceled: bool,
         cx: &mut Context<Self>,
     ) {
+        // Replayed tool uses record new results for the existing messages, so they don't
+        // continue the conversation.
+        if let Some(replay_finished) = self.replayed_tool_uses.remove(&tool_use_id) {
+            replay_finished.send(()).ok();
+            cx.emit(ThreadEvent::ToolFinished {
+                tool_use_id,
+                pending_tool_use,
+            });
+            return;
+        }
+
         if self.all_tools_finished() {
             let model_registry = LanguageModelRegistry::read_global(cx);
             if let Some(ConfiguredModel { model, .. }) = model_registry.default_model() {
@@ -1489,8 +1723,18 @@ impl Thread {
             true
         } else {
             let mut canceled = false;
+            // Dropping the replayed tool uses' senders stops the replay, rather than moving on
+            // to the next tool use.
+            let replayed_tool_uses = mem::take(&mut self.replayed_tool_uses);
             for pending_tool_use in self.tool_use.cancel_pending() {
                 canceled = true;
+                if replayed_tool_uses.contains_key(&pending_tool_use.id) {
+                    cx.emit(ThreadEvent::ToolFinished {
+                        tool_use_id: pending_tool_use.id.clone(),
+                        pending_tool_use: Some(pending_tool_use),
+                    });
+                    continue;
+                }
                 self.tool_finished(
                     pending_tool_use.id.clone(),
                     Some(pending_tool_use),
@@ -1966,6 +2210,8 @@ pub enum ThreadEvent {
         /// The pending tool use that corresponds to this tool.
         pending_tool_use: Option<PendingToolUse>,
     },
+    /// A recorded tool use was run again by [`Thread::replay_tool_uses`].
+    ToolReplayed(LanguageModelToolUseId),
     CheckpointChanged,
     ToolConfirmationNeeded,
 }
@@ -1980,10 +2226,12 @@ struct PendingCompletion {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::thread_export::{ExportedThread, export_thread};
     use crate::{ThreadStore, context_store::ContextStore, thread_store};
     use assistant_settings::AssistantSettings;
     use context_server::ContextServerSettings;
     use editor::EditorSettings;
+    use fs::RealFs;
     use gpui::TestAppContext;
     use project::{FakeFs, Project};
     use prompt_store::PromptBuilder;
@@ -2311,6 +2559,187 @@ fn main() {{
         );
     }
 
+    #[gpui::test]
+    async fn test_export_import_round_trip(cx: &mut TestAppContext) {
+        init_test_settings(cx);
+        let database_dir = tempfile::tempdir().unwrap();
+        cx.update(|cx| thread_store::init_test(database_dir.path().join("threads.mdb"), cx));
+
+        let project = create_test_project(cx, json!({"code.rs": "fn main() {}\n"})).await;
+        let (_workspace, thread_store, thread, context_store) =
+            setup_test_environment(cx, project.clone()).await;
+        add_file_to_context(&project, &context_store, "test/code.rs", cx)
+            .await
+            .unwrap();
+        let context =
+            context_store.update(cx, |store, _| store.context().first().cloned().unwrap());
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message("Please explain this code", vec![context], None, cx);
+            thread.insert_message(
+                Role::Assistant,
+                vec![MessageSegment::Text("It does nothing.".to_string())],
+                cx,
+            );
+        });
+
+        let exported_thread = cx.update(|cx| export_thread(&thread, cx)).await.unwrap();
+        let exported_json = exported_thread.to_json().unwrap();
+        assert_eq!(exported_thread.context.len(), 1);
+
+        let imported_thread = thread_store
+            .update(cx, |store, cx| {
+                store.import_thread(
+                    ExportedThread::from_json(exported_json.as_bytes()).unwrap(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.read(|cx| {
+            let thread = thread.read(cx);
+            let imported_thread = imported_thread.read(cx);
+            assert_ne!(thread.id(), imported_thread.id());
+            assert_eq!(
+                thread.summary_or_default(),
+                imported_thread.summary_or_default()
+            );
+            let messages = thread.messages().collect::<Vec<_>>();
+            let imported_messages = imported_thread.messages().collect::<Vec<_>>();
+            assert_eq!(messages.len(), imported_messages.len());
+            for (message, imported_message) in messages.iter().zip(&imported_messages) {
+                assert_eq!(message.role, imported_message.role);
+                assert_eq!(message.segments, imported_message.segments);
+                assert_eq!(message.context, imported_message.context);
+            }
+        });
+
+        // Exporting the imported thread keeps the context it was imported with.
+        let reexported_thread = cx
+            .update(|cx| export_thread(&imported_thread, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&reexported_thread.context).unwrap(),
+            serde_json::to_value(&exported_thread.context).unwrap()
+        );
+
+        // Context that's only exported as a list is formatted for the model on import.
+        let mut exported_thread = ExportedThread::from_json(exported_json.as_bytes()).unwrap();
+        for message in &mut exported_thread.thread.messages {
+            message.context.clear();
+        }
+        let imported_thread = thread_store
+            .update(cx, |store, cx| store.import_thread(exported_thread, cx))
+            .await
+            .unwrap();
+        cx.read(|cx| {
+            let original_context = &thread.read(cx).messages().next().unwrap().context;
+            let imported_context = &imported_thread.read(cx).messages().next().unwrap().context;
+            assert!(!original_context.is_empty());
+            assert_eq!(imported_context, original_context);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_fork_thread_and_diff_outcomes(cx: &mut TestAppContext) {
+        init_test_settings(cx);
+        cx.executor().allow_parking();
+        let database_dir = tempfile::tempdir().unwrap();
+        cx.update(|cx| thread_store::init_test(database_dir.path().join("threads.mdb"), cx));
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let file_path = repo_dir.path().join("file.txt");
+        std::fs::write(&file_path, "one\n").unwrap();
+        let project = Project::test(
+            Arc::new(RealFs::new(None, cx.executor())),
+            [repo_dir.path()],
+            cx,
+        )
+        .await;
+        cx.executor().run_until_parked();
+        let (_workspace, thread_store, thread, _context_store) =
+            setup_test_environment(cx, project.clone()).await;
+        let git_store = project.read_with(cx, |project, _| project.git_store().clone());
+
+        // The agent changes the file in response to the first message.
+        let checkpoint = cx
+            .update(|cx| git_store.read(cx).checkpoint(cx))
+            .await
+            .unwrap();
+        let message_id = thread.update(cx, |thread, cx| {
+            let message_id = thread.insert_user_message("Change the file", vec![], None, cx);
+            thread.insert_checkpoint(
+                ThreadCheckpoint {
+                    message_id,
+                    git_checkpoint: checkpoint,
+                },
+                cx,
+            );
+            thread.insert_message(
+                Role::Assistant,
+                vec![MessageSegment::Text("Changed it.".to_string())],
+                cx,
+            );
+            message_id
+        });
+        std::fs::write(&file_path, "two\n").unwrap();
+        thread_store
+            .update(cx, |store, cx| store.save_thread(&thread, cx))
+            .await
+            .unwrap();
+
+        // Checkpoints survive reopening the thread.
+        let parent_id = thread.read_with(cx, |thread, _| thread.id().clone());
+        let reopened_thread = thread_store
+            .update(cx, |store, cx| store.open_thread(&parent_id, cx))
+            .await
+            .unwrap();
+        reopened_thread.read_with(cx, |thread, _| {
+            assert!(thread.checkpoint_for_fork(message_id).is_some());
+        });
+
+        let fork = thread_store
+            .update(cx, |store, cx| {
+                store.fork_thread(&reopened_thread, message_id, cx)
+            })
+            .await
+            .unwrap();
+        fork.read_with(cx, |fork, _| {
+            assert!(fork.is_empty());
+            let forked_from = fork.forked_from().unwrap();
+            assert_eq!(forked_from.thread_id, parent_id);
+            assert_eq!(forked_from.message_id, message_id);
+        });
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "one\n");
+
+        let fork_id = fork.read_with(cx, |fork, _| fork.id().clone());
+        let diff = thread_store
+            .update(cx, |store, cx| {
+                store.diff_thread_outcomes(&parent_id, &fork_id, cx)
+            })
+            .await
+            .unwrap();
+        assert!(diff.contains("-two"), "unexpected diff: {diff}");
+        assert!(diff.contains("+one"), "unexpected diff: {diff}");
+
+        // A thread that never changed the project has no outcome to compare.
+        let other_thread = thread_store.update(cx, |store, cx| store.create_thread(cx));
+        thread_store
+            .update(cx, |store, cx| store.save_thread(&other_thread, cx))
+            .await
+            .unwrap();
+        let other_id = other_thread.read_with(cx, |thread, _| thread.id().clone());
+        assert!(
+            thread_store
+                .update(cx, |store, cx| {
+                    store.diff_thread_outcomes(&parent_id, &other_id, cx)
+                })
+                .await
+                .is_err()
+        );
+    }
+
     fn init_test_settings(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
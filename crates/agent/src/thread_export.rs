@@ -0,0 +1,209 @@
+use std::fmt::Write as _;
+
+use anyhow::{Result, anyhow};
+use assistant_settings::{AgentProfileId, AssistantSettings};
+use chrono::{DateTime, Utc};
+use gpui::{App, Entity, Task};
+use itertools::Itertools as _;
+use language_model::LanguageModelRegistry;
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+
+use crate::context::{AssistantContext, ContextKind};
+use crate::thread::{MessageId, Thread, ThreadCheckpoint};
+use crate::thread_store::SerializedThread;
+
+/// A self-contained representation of a thread that can be shared, e.g. to
+/// reproduce an agent session in a bug report or to seed an eval.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedThread {
+    pub version: String,
+    pub exported_at: DateTime<Utc>,
+    /// The model that was selected when the thread was exported.
+    pub model: Option<ExportedModel>,
+    /// The profile that was selected when the thread was exported.
+    pub profile: Option<AgentProfileId>,
+    pub thread: SerializedThread,
+    /// The context the user attached to each message.
+    #[serde(default)]
+    pub context: Vec<ExportedContext>,
+    /// The project checkpoints taken before each message. These refer to commits
+    /// in the repository the thread was exported from, so they can only be
+    /// restored there.
+    #[serde(default)]
+    pub checkpoints: Vec<ThreadCheckpoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedModel {
+    pub provider: String,
+    pub model: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportedContext {
+    pub message_id: MessageId,
+    pub kind: ContextKind,
+    /// The path, URL or name identifying the context.
+    pub name: String,
+    pub text: String,
+}
+
+impl ExportedThread {
+    pub const VERSION: &'static str = "0.1.0";
+
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        let exported_thread_json = serde_json::from_slice::<serde_json::Value>(json)?;
+        match exported_thread_json.get("version") {
+            Some(serde_json::Value::String(version)) if version == ExportedThread::VERSION => Ok(
+                serde_json::from_value::<ExportedThread>(exported_thread_json)?,
+            ),
+            version => Err(anyhow!(
+                "unrecognized exported thread version: {:?}",
+                version
+            )),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Exports the given thread along with the model and profile that are currently
+/// selected.
+pub fn export_thread(thread: &Entity<Thread>, cx: &mut App) -> Task<Result<ExportedThread>> {
+    let model = LanguageModelRegistry::read_global(cx)
+        .default_model()
+        .map(|configured_model| ExportedModel {
+            provider: configured_model.provider.id().0.to_string(),
+            model: configured_model.model.id().0.to_string(),
+        });
+    let profile = AssistantSettings::get_global(cx).default_profile.clone();
+
+    let serialized_thread = thread.update(cx, |thread, cx| thread.serialize(cx));
+    let thread = thread.read(cx);
+    let context = thread
+        .messages()
+        .flat_map(|message| {
+            thread
+                .context_for_message(message.id)
+                .map(|context| exported_context(message.id, context, cx))
+        })
+        .chain(thread.imported_context().iter().cloned())
+        .collect::<Vec<_>>();
+    let checkpoints = thread.checkpoints().cloned().collect::<Vec<_>>();
+
+    cx.spawn(async move |_cx| {
+        Ok(ExportedThread {
+            version: ExportedThread::VERSION.to_string(),
+            exported_at: Utc::now(),
+            model,
+            profile: Some(profile),
+            thread: serialized_thread.await?,
+            context,
+            checkpoints,
+        })
+    })
+}
+
+/// Formats the imported context attached to a message the way it's included in the
+/// message sent to the model.
+pub fn format_exported_context<'a>(
+    contexts: impl IntoIterator<Item = &'a ExportedContext>,
+) -> Option<String> {
+    let contexts = contexts.into_iter().collect::<Vec<_>>();
+    if contexts.is_empty() {
+        return None;
+    }
+
+    let mut result = String::new();
+    result.push_str("\n<context>\n\
+        The following items were attached by the user. You don't need to use other tools to read them.\n\n");
+    for (kind, tag) in [
+        (ContextKind::File, "files"),
+        (ContextKind::Directory, "directories"),
+        (ContextKind::Symbol, "symbols"),
+        (ContextKind::FetchedUrl, "fetched_urls"),
+        (ContextKind::Thread, "conversation_threads"),
+        (ContextKind::Resource, "resources"),
+    ] {
+        let mut contexts = contexts
+            .iter()
+            .filter(|context| context.kind == kind)
+            .peekable();
+        if contexts.peek().is_none() {
+            continue;
+        }
+
+        writeln!(result, "<{tag}>").ok();
+        for context in contexts {
+            // File and directory contexts already include their paths.
+            if !matches!(kind, ContextKind::File | ContextKind::Directory) {
+                writeln!(result, "{}", context.name).ok();
+            }
+            result.push_str(&context.text);
+            if !context.text.ends_with('\n') {
+                result.push('\n');
+            }
+        }
+        writeln!(result, "</{tag}>").ok();
+    }
+    result.push_str("</context>\n");
+    Some(result)
+}
+
+fn exported_context(
+    message_id: MessageId,
+    context: &AssistantContext,
+    cx: &App,
+) -> ExportedContext {
+    let (kind, name, text) = match context {
+        AssistantContext::File(context) => (
+            ContextKind::File,
+            context
+                .context_buffer
+                .file
+                .full_path(cx)
+                .to_string_lossy()
+                .to_string(),
+            context.context_buffer.text.to_string(),
+        ),
+        AssistantContext::Directory(context) => (
+            ContextKind::Directory,
+            context.path.to_string_lossy().to_string(),
+            context
+                .context_buffers
+                .iter()
+                .map(|context_buffer| context_buffer.text.as_ref())
+                .join(""),
+        ),
+        AssistantContext::Symbol(context) => (
+            ContextKind::Symbol,
+            context.context_symbol.id.name.to_string(),
+            context.context_symbol.text.to_string(),
+        ),
+        AssistantContext::FetchedUrl(context) => (
+            ContextKind::FetchedUrl,
+            context.url.to_string(),
+            context.text.to_string(),
+        ),
+        AssistantContext::Thread(context) => (
+            ContextKind::Thread,
+            context.summary(cx).to_string(),
+            context.text.to_string(),
+        ),
+        AssistantContext::Resource(context) => (
+            ContextKind::Resource,
+            context.uri.to_string(),
+            context.text.to_string(),
+        ),
+    };
+
+    ExportedContext {
+        message_id,
+        kind,
+        name,
+        text,
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use assistant_context_editor::SavedContextMetadata;
+use collections::HashMap;
 use editor::{Editor, EditorEvent};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
@@ -15,6 +16,7 @@ use ui::{
 use util::ResultExt;
 
 use crate::history_store::{HistoryEntry, HistoryStore};
+use crate::thread::ThreadId;
 use crate::thread_store::SerializedThreadMetadata;
 use crate::{AssistantPanel, RemoveSelectedThread};
 
@@ -26,6 +28,8 @@ pub struct ThreadHistory {
     search_query: SharedString,
     search_editor: Entity<Editor>,
     all_entries: Arc<Vec<HistoryEntry>>,
+    /// How many forks deep each thread is, used to render the history as a tree.
+    fork_depths: HashMap<ThreadId, usize>,
     matches: Vec<StringMatch>,
     _subscriptions: Vec<gpui::Subscription>,
     _search_task: Option<Task<()>>,
@@ -72,6 +76,7 @@ impl ThreadHistory {
             scroll_handle,
             selected_index: 0,
             search_query: SharedString::new_static(""),
+            fork_depths: fork_depths(&entries),
             all_entries: entries,
             matches: Vec::new(),
             search_editor,
@@ -87,6 +92,7 @@ impl ThreadHistory {
             .history_store
             .update(cx, |store, cx| store.entries(cx))
             .into();
+        self.fork_depths = fork_depths(&self.all_entries);
         self.matches.clear();
         self.update_search(cx);
     }
@@ -382,6 +388,9 @@ impl Render for ThreadHistory {
                                 move |history, range, _window, _cx| {
                                     let range_start = range.start;
                                     let assistant_panel = history.assistant_panel.clone();
+                                    // Search results are ranked by relevance, so they're shown flat.
+                                    let show_tree = !history.has_search_query();
+                                    let fork_depths = &history.fork_depths;
 
                                     let render_item = |index: usize,
                                                        entry: &HistoryEntry,
@@ -394,6 +403,11 @@ impl Render for ThreadHistory {
                                                 selected_index == index + range_start,
                                                 highlight_positions,
                                             )
+                                            .fork_depth(if show_tree {
+                                                fork_depths.get(&thread.id).copied().unwrap_or(0)
+                                            } else {
+                                                0
+                                            })
                                             .into_any_element(),
                                             HistoryEntry::Context(context) => PastContext::new(
                                                 context.clone(),
@@ -442,12 +456,29 @@ impl Render for ThreadHistory {
     }
 }
 
+fn fork_depths(entries: &[HistoryEntry]) -> HashMap<ThreadId, usize> {
+    // Entries are ordered so that forks come after the thread they were forked from.
+    let mut depths = HashMap::default();
+    for entry in entries {
+        if let HistoryEntry::Thread(thread) = entry {
+            let depth = thread
+                .forked_from
+                .as_ref()
+                .and_then(|forked_from| depths.get(&forked_from.thread_id))
+                .map_or(0, |parent_depth| parent_depth + 1);
+            depths.insert(thread.id.clone(), depth);
+        }
+    }
+    depths
+}
+
 #[derive(IntoElement)]
 pub struct PastThread {
     thread: SerializedThreadMetadata,
     assistant_panel: WeakEntity<AssistantPanel>,
     selected: bool,
     highlight_positions: Vec<usize>,
+    fork_depth: usize,
 }
 
 impl PastThread {
@@ -462,8 +493,15 @@ impl PastThread {
             assistant_panel,
             selected,
             highlight_positions,
+            fork_depth: 0,
         }
     }
+
+    /// Indents the thread beneath the thread it was forked from.
+    pub fn fork_depth(mut self, fork_depth: usize) -> Self {
+        self.fork_depth = fork_depth;
+        self
+    }
 }
 
 impl RenderOnce for PastThread {
@@ -479,10 +517,41 @@ impl RenderOnce for PastThread {
             time_format::TimestampFormat::EnhancedAbsolute,
         );
 
+        let compare_with_parent = self
+            .thread
+            .forked_from
+            .clone()
+            .filter(|_| self.fork_depth > 0)
+            .map(|forked_from| {
+                IconButton::new("compare-with-parent", IconName::Diff)
+                    .shape(IconButtonShape::Square)
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted)
+                    .tooltip(Tooltip::text("Compare Outcome with Parent Thread"))
+                    .on_click({
+                        let assistant_panel = self.assistant_panel.clone();
+                        let id = self.thread.id.clone();
+                        move |_event, window, cx| {
+                            assistant_panel
+                                .update(cx, |this, cx| {
+                                    this.compare_thread_outcomes(
+                                        &forked_from.thread_id,
+                                        &id,
+                                        window,
+                                        cx,
+                                    )
+                                    .detach_and_log_err(cx);
+                                })
+                                .ok();
+                        }
+                    })
+            });
+
         ListItem::new(SharedString::from(self.thread.id.to_string()))
             .rounded()
             .toggle_state(self.selected)
             .spacing(ListItemSpacing::Sparse)
+            .indent_level(self.fork_depth)
             .start_slot(
                 div().max_w_4_5().child(
                     HighlightedLabel::new(summary, self.highlight_positions)
@@ -498,6 +567,7 @@ impl RenderOnce for PastThread {
                             .color(Color::Muted)
                             .size(LabelSize::XSmall),
                     )
+                    .children(compare_with_parent)
                     .child(
                         IconButton::new("delete", IconName::TrashAlt)
                             .shape(IconButtonShape::Square)
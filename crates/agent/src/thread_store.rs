@@ -21,6 +21,7 @@ use gpui::{
 use heed::Database;
 use heed::types::SerdeBincode;
 use language_model::{LanguageModelToolUseId, Role, TokenUsage};
+use project::git_store::GitStoreCheckpoint;
 use project::{Project, Worktree};
 use prompt_store::{ProjectContext, PromptBuilder, RulesFileContext, WorktreeContext};
 use serde::{Deserialize, Serialize};
@@ -28,8 +29,10 @@ use settings::{Settings as _, SettingsStore};
 use util::ResultExt as _;
 
 use crate::thread::{
-    DetailedSummaryState, ExceededWindowError, MessageId, ProjectSnapshot, Thread, ThreadId,
+    DetailedSummaryState, ExceededWindowError, ForkedFrom, MessageId, ProjectSnapshot, Thread,
+    ThreadCheckpoint, ThreadId,
 };
+use crate::thread_export::{ExportedContext, ExportedThread, format_exported_context};
 
 const RULES_FILE_NAMES: [&'static str; 6] = [
     ".rules",
@@ -41,7 +44,13 @@ const RULES_FILE_NAMES: [&'static str; 6] = [
 ];
 
 pub fn init(cx: &mut App) {
-    ThreadsDatabase::init(cx);
+    ThreadsDatabase::init(paths::data_dir().join("threads/threads-db.1.mdb"), cx);
+}
+
+/// Stores threads in the given database rather than the user's one.
+#[cfg(test)]
+pub(crate) fn init_test(database_path: PathBuf, cx: &mut App) {
+    ThreadsDatabase::init(database_path, cx);
 }
 
 /// A system prompt shared by all threads created by this ThreadStore
@@ -325,13 +334,240 @@ impl ThreadStore {
         })
     }
 
+    /// Creates a new thread that shares the history of `thread` up to (but not
+    /// including) the given message, and restores the project to the state it
+    /// was in when that message was sent.
+    pub fn fork_thread(
+        &mut self,
+        thread: &Entity<Thread>,
+        message_id: MessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Thread>>> {
+        let git_store = self.project.read(cx).git_store().clone();
+        let (parent_id, fork_checkpoint, serialized_thread) = thread.update(cx, |thread, cx| {
+            (
+                thread.id().clone(),
+                thread.checkpoint_for_fork(message_id),
+                thread.serialize(cx),
+            )
+        });
+        let parent_outcome = fork_checkpoint
+            .is_some()
+            .then(|| git_store.update(cx, |git_store, cx| git_store.checkpoint(cx)));
+        let parent = thread.clone();
+
+        cx.spawn(async move |this, cx| {
+            let mut serialized_thread = serialized_thread.await?;
+            let message_ix = serialized_thread
+                .messages
+                .iter()
+                .position(|message| message.id == message_id)
+                .ok_or_else(|| anyhow!("no message found with ID: {message_id:?}"))?;
+            serialized_thread.messages.truncate(message_ix);
+            serialized_thread.checkpoints.retain(|checkpoint| {
+                serialized_thread
+                    .messages
+                    .iter()
+                    .any(|message| message.id == checkpoint.message_id)
+            });
+            serialized_thread.updated_at = Utc::now();
+            serialized_thread.detailed_summary_state = DetailedSummaryState::NotGenerated;
+            serialized_thread.exceeded_window_error = None;
+            serialized_thread.forked_from = Some(ForkedFrom {
+                thread_id: parent_id,
+                message_id,
+            });
+
+            if let Some((fork_checkpoint, parent_outcome)) = fork_checkpoint.zip(parent_outcome) {
+                // Remember where the original branch left the project before rewinding it,
+                // so that the outcomes of both branches can still be compared.
+                let parent_outcome = parent_outcome.await?;
+                parent.update(cx, |parent, cx| {
+                    parent.set_outcome_checkpoint(parent_outcome, cx)
+                })?;
+                this.update(cx, |this, cx| this.save_thread(&parent, cx))?
+                    .await?;
+
+                git_store
+                    .update(cx, |git_store, cx| {
+                        git_store.restore_checkpoint(fork_checkpoint, cx)
+                    })?
+                    .await?;
+            }
+
+            serialized_thread.outcome_checkpoint = git_store
+                .update(cx, |git_store, cx| git_store.checkpoint(cx))?
+                .await
+                .log_err();
+
+            let thread = this.update(cx, |this, cx| {
+                cx.new(|cx| {
+                    Thread::deserialize(
+                        ThreadId::new(),
+                        serialized_thread,
+                        this.project.clone(),
+                        this.tools.clone(),
+                        this.prompt_builder.clone(),
+                        this.project_context.clone(),
+                        cx,
+                    )
+                })
+            })?;
+            this.update(cx, |this, cx| this.save_thread(&thread, cx))?
+                .await?;
+
+            Ok(thread)
+        })
+    }
+
+    /// Adds a thread that was exported with [`crate::thread_export::export_thread`]
+    /// to this store.
+    pub fn import_thread(
+        &mut self,
+        exported_thread: ExportedThread,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Thread>>> {
+        let mut serialized_thread = exported_thread.thread;
+        // The original thread owns its outcome checkpoint, and the thread it was
+        // forked from most likely doesn't exist here.
+        serialized_thread.outcome_checkpoint = None;
+        serialized_thread.forked_from = None;
+        serialized_thread.checkpoints.clear();
+        serialized_thread.updated_at = Utc::now();
+        for message in &mut serialized_thread.messages {
+            if message.context.is_empty() {
+                message.context = format_exported_context(
+                    exported_thread
+                        .context
+                        .iter()
+                        .filter(|context| context.message_id == message.id),
+                )
+                .unwrap_or_default();
+            }
+        }
+        serialized_thread.imported_context = exported_thread.context;
+
+        // Checkpoints can only be restored if they were taken in a clone of this
+        // project's repositories that shares their commits.
+        let git_store = self.project.read(cx).git_store().clone();
+        let checkpoints_exist = exported_thread
+            .checkpoints
+            .iter()
+            .map(|checkpoint| {
+                git_store.update(cx, |git_store, cx| {
+                    git_store.checkpoint_exists(checkpoint.git_checkpoint().clone(), cx)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(async move |this, cx| {
+            let checkpoints_exist = future::join_all(checkpoints_exist).await;
+            let checkpoints = exported_thread
+                .checkpoints
+                .into_iter()
+                .zip(checkpoints_exist)
+                .filter_map(|(checkpoint, exists)| exists.then_some(checkpoint))
+                .collect::<Vec<_>>();
+
+            let (thread, save_task) = this.update(cx, |this, cx| {
+                let thread = cx.new(|cx| {
+                    let mut thread = Thread::deserialize(
+                        ThreadId::new(),
+                        serialized_thread,
+                        this.project.clone(),
+                        this.tools.clone(),
+                        this.prompt_builder.clone(),
+                        this.project_context.clone(),
+                        cx,
+                    );
+                    thread.insert_checkpoints(checkpoints, cx);
+                    thread
+                });
+                let save_task = this.save_thread(&thread, cx);
+                (thread, save_task)
+            })?;
+            save_task.await?;
+            Ok(thread)
+        })
+    }
+
+    /// Computes the diff between where two threads left the project.
+    pub fn diff_thread_outcomes(
+        &self,
+        base_id: &ThreadId,
+        target_id: &ThreadId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<String>> {
+        let base_id = base_id.clone();
+        let target_id = target_id.clone();
+        let git_store = self.project.read(cx).git_store().clone();
+        let database_future = ThreadsDatabase::global_future(cx);
+        cx.spawn(async move |_, cx| {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            let mut outcomes = Vec::new();
+            for id in [base_id, target_id] {
+                let thread = database
+                    .try_find_thread(id.clone())
+                    .await?
+                    .ok_or_else(|| anyhow!("no thread found with ID: {id:?}"))?;
+                outcomes.push(thread.outcome_checkpoint.ok_or_else(|| {
+                    anyhow!("\"{}\" hasn't changed the project yet", thread.summary)
+                })?);
+            }
+            let target = outcomes.pop().unwrap();
+            let base = outcomes.pop().unwrap();
+
+            git_store
+                .update(cx, |git_store, cx| {
+                    git_store.diff_checkpoints(base, target, cx)
+                })?
+                .await
+        })
+    }
+
+    /// Deletes a thread along with the project checkpoints that no other thread
+    /// refers to. Forks and imported threads share the checkpoints of the thread
+    /// they were made from.
     pub fn delete_thread(&mut self, id: &ThreadId, cx: &mut Context<Self>) -> Task<Result<()>> {
         let id = id.clone();
+        let git_store = self.project.read(cx).git_store().clone();
         let database_future = ThreadsDatabase::global_future(cx);
         cx.spawn(async move |this, cx| {
             let database = database_future.await.map_err(|err| anyhow!(err))?;
+            let mut checkpoints = Vec::new();
+            if let Some(thread) = database.try_find_thread(id.clone()).await? {
+                checkpoints.extend(thread.outcome_checkpoint);
+                checkpoints.extend(
+                    thread
+                        .checkpoints
+                        .into_iter()
+                        .map(|checkpoint| checkpoint.git_checkpoint().clone()),
+                );
+            }
             database.delete_thread(id.clone()).await?;
 
+            if !checkpoints.is_empty() {
+                for metadata in database.list_threads().await? {
+                    let Some(thread) = database.try_find_thread(metadata.id).await? else {
+                        continue;
+                    };
+                    checkpoints.retain(|checkpoint| {
+                        thread.outcome_checkpoint.as_ref() != Some(checkpoint)
+                            && !thread
+                                .checkpoints
+                                .iter()
+                                .any(|other| other.git_checkpoint() == checkpoint)
+                    });
+                }
+            }
+            for checkpoint in checkpoints {
+                git_store
+                    .update(cx, |git_store, cx| {
+                        git_store.delete_checkpoint(checkpoint, cx)
+                    })?
+                    .detach();
+            }
+
             this.update(cx, |this, cx| {
                 this.threads.retain(|thread| thread.id != id);
                 cx.notify();
@@ -427,68 +663,75 @@ impl ThreadStore {
         event: &context_server::manager::Event,
         cx: &mut Context<Self>,
     ) {
-        let tool_working_set = self.tools.clone();
         match event {
-            context_server::manager::Event::ServerStarted { server_id } => {
-                if let Some(server) = context_server_manager.read(cx).get_server(server_id) {
-                    let context_server_manager = context_server_manager.clone();
-                    cx.spawn({
-                        let server = server.clone();
-                        let server_id = server_id.clone();
-                        async move |this, cx| {
-                            let Some(protocol) = server.client() else {
-                                return;
-                            };
-
-                            if protocol.capable(context_server::protocol::ServerCapability::Tools) {
-                                if let Some(tools) = protocol.list_tools().await.log_err() {
-                                    let tool_ids = tool_working_set
-                                        .update(cx, |tool_working_set, _| {
-                                            tools
-                                                .tools
-                                                .into_iter()
-                                                .map(|tool| {
-                                                    log::info!(
-                                                        "registering context server tool: {:?}",
-                                                        tool.name
-                                                    );
-                                                    tool_working_set.insert(Arc::new(
-                                                        ContextServerTool::new(
-                                                            context_server_manager.clone(),
-                                                            server.id(),
-                                                            tool,
-                                                        ),
-                                                    ))
-                                                })
-                                                .collect::<Vec<_>>()
-                                        })
-                                        .log_err();
-
-                                    if let Some(tool_ids) = tool_ids {
-                                        this.update(cx, |this, cx| {
-                                            this.context_server_tool_ids
-                                                .insert(server_id, tool_ids);
-                                            this.load_default_profile(cx);
-                                        })
-                                        .log_err();
-                                    }
-                                }
-                            }
-                        }
-                    })
-                    .detach();
-                }
+            context_server::manager::Event::ServerStarted { server_id }
+            | context_server::manager::Event::ToolsChanged { server_id } => {
+                self.reload_context_server_tools(context_server_manager, server_id, cx);
             }
             context_server::manager::Event::ServerStopped { server_id } => {
                 if let Some(tool_ids) = self.context_server_tool_ids.remove(server_id) {
-                    tool_working_set.update(cx, |tool_working_set, _| {
+                    self.tools.update(cx, |tool_working_set, _| {
                         tool_working_set.remove(&tool_ids);
                     });
                     self.load_default_profile(cx);
                 }
             }
+            context_server::manager::Event::PromptsChanged { .. }
+            | context_server::manager::Event::ResourcesChanged { .. }
+            | context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
+
+    /// Registers the tools of a context server, replacing the ones registered before.
+    fn reload_context_server_tools(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        server_id: &Arc<str>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(server) = context_server_manager.read(cx).get_server(server_id) else {
+            return;
+        };
+        let tool_working_set = self.tools.clone();
+        let server_id = server_id.clone();
+        cx.spawn(async move |this, cx| {
+            let Some(protocol) = server.client() else {
+                return;
+            };
+            if !protocol.capable(context_server::protocol::ServerCapability::Tools) {
+                return;
+            }
+            let Some(tools) = protocol.list_tools().await.log_err() else {
+                return;
+            };
+
+            this.update(cx, |this, cx| {
+                let previous_tool_ids = this
+                    .context_server_tool_ids
+                    .remove(&server_id)
+                    .unwrap_or_default();
+                let tool_ids = tool_working_set.update(cx, |tool_working_set, _| {
+                    tool_working_set.remove(&previous_tool_ids);
+                    tools
+                        .tools
+                        .into_iter()
+                        .map(|tool| {
+                            log::info!("registering context server tool: {:?}", tool.name);
+                            tool_working_set.insert(Arc::new(ContextServerTool::new(
+                                context_server_manager.clone(),
+                                server.id(),
+                                tool,
+                            )))
+                        })
+                        .collect::<Vec<_>>()
+                });
+                this.context_server_tool_ids.insert(server_id, tool_ids);
+                this.load_default_profile(cx);
+            })
+            .log_err();
+        })
+        .detach();
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -496,6 +739,7 @@ pub struct SerializedThreadMetadata {
     pub id: ThreadId,
     pub summary: SharedString,
     pub updated_at: DateTime<Utc>,
+    pub forked_from: Option<ForkedFrom>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -512,6 +756,17 @@ pub struct SerializedThread {
     pub detailed_summary_state: DetailedSummaryState,
     #[serde(default)]
     pub exceeded_window_error: Option<ExceededWindowError>,
+    #[serde(default)]
+    pub forked_from: Option<ForkedFrom>,
+    #[serde(default)]
+    pub outcome_checkpoint: Option<GitStoreCheckpoint>,
+    /// The project checkpoints taken before each message, which forks are restored to.
+    #[serde(default)]
+    pub checkpoints: Vec<ThreadCheckpoint>,
+    /// The context attached to the messages of an imported thread, which can't be
+    /// resolved to this project's files and threads.
+    #[serde(default)]
+    pub imported_context: Vec<ExportedContext>,
 }
 
 impl SerializedThread {
@@ -599,6 +854,10 @@ impl LegacySerializedThread {
             cumulative_token_usage: TokenUsage::default(),
             detailed_summary_state: DetailedSummaryState::default(),
             exceeded_window_error: None,
+            forked_from: None,
+            outcome_checkpoint: None,
+            checkpoints: Vec::new(),
+            imported_context: Vec::new(),
         }
     }
 }
@@ -664,12 +923,11 @@ impl ThreadsDatabase {
         GlobalThreadsDatabase::global(cx).0.clone()
     }
 
-    fn init(cx: &mut App) {
+    fn init(database_path: PathBuf, cx: &mut App) {
         let executor = cx.background_executor().clone();
         let database_future = executor
             .spawn({
                 let executor = executor.clone();
-                let database_path = paths::data_dir().join("threads/threads-db.1.mdb");
                 async move { ThreadsDatabase::new(database_path, executor) }
             })
             .then(|result| future::ready(result.map(Arc::new).map_err(Arc::new)))
@@ -714,6 +972,7 @@ impl ThreadsDatabase {
                     id: key,
                     summary: value.summary,
                     updated_at: value.updated_at,
+                    forked_from: value.forked_from,
                 });
             }
 
@@ -5,7 +5,7 @@ use assistant_tool::{Tool, ToolWorkingSet};
 use collections::HashMap;
 use futures::FutureExt as _;
 use futures::future::Shared;
-use gpui::{App, Entity, SharedString, Task};
+use gpui::{AnyView, App, Entity, SharedString, Task};
 use language_model::{
     LanguageModelRegistry, LanguageModelRequestMessage, LanguageModelToolResult,
     LanguageModelToolUse, LanguageModelToolUseId, MessageContent, Role,
@@ -25,6 +25,8 @@ pub struct ToolUse {
     pub input: serde_json::Value,
     pub icon: ui::IconName,
     pub needs_confirmation: bool,
+    /// The view the tool provided to show its progress, if any.
+    pub card: Option<AnyView>,
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +56,7 @@ pub struct ToolUseState {
     tool_uses_by_user_message: HashMap<MessageId, Vec<LanguageModelToolUseId>>,
     tool_results: HashMap<LanguageModelToolUseId, LanguageModelToolResult>,
     pending_tool_uses_by_id: HashMap<LanguageModelToolUseId, PendingToolUse>,
+    tool_cards: HashMap<LanguageModelToolUseId, AnyView>,
 }
 
 pub const USING_TOOL_MARKER: &str = "<using_tool>";
@@ -66,6 +69,7 @@ impl ToolUseState {
             tool_uses_by_user_message: HashMap::default(),
             tool_results: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
+            tool_cards: HashMap::default(),
         }
     }
 
@@ -160,6 +164,22 @@ impl ToolUseState {
         pending_tools
     }
 
+    /// Forgets the tool uses and tool results recorded for the given message, along with the
+    /// cards rendered for them.
+    pub fn remove_message(&mut self, id: MessageId) {
+        if let Some(tool_uses) = self.tool_uses_by_assistant_message.remove(&id) {
+            for tool_use in tool_uses {
+                self.pending_tool_uses_by_id.remove(&tool_use.id);
+                self.tool_cards.remove(&tool_use.id);
+            }
+        }
+        if let Some(tool_use_ids) = self.tool_uses_by_user_message.remove(&id) {
+            for tool_use_id in tool_use_ids {
+                self.tool_results.remove(&tool_use_id);
+            }
+        }
+    }
+
     pub fn pending_tool_uses(&self) -> Vec<&PendingToolUse> {
         self.pending_tool_uses_by_id.values().collect()
     }
@@ -214,6 +234,7 @@ impl ToolUseState {
                 status,
                 icon,
                 needs_confirmation,
+                card: self.tool_cards.get(&tool_use.id).cloned(),
             })
         }
 
@@ -291,6 +312,28 @@ impl ToolUseState {
         );
     }
 
+    /// Makes a recorded tool use pending again, so that it's run like a new one.
+    pub fn replay_tool_use(
+        &mut self,
+        assistant_message_id: MessageId,
+        tool_use_id: LanguageModelToolUseId,
+        name: Arc<str>,
+        ui_text: impl Into<Arc<str>>,
+        input: serde_json::Value,
+    ) {
+        self.pending_tool_uses_by_id.insert(
+            tool_use_id.clone(),
+            PendingToolUse {
+                assistant_message_id,
+                id: tool_use_id,
+                name,
+                ui_text: ui_text.into(),
+                input,
+                status: PendingToolUseStatus::Idle,
+            },
+        );
+    }
+
     pub fn run_pending_tool(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -305,6 +348,10 @@ impl ToolUseState {
         }
     }
 
+    pub fn insert_tool_card(&mut self, tool_use_id: LanguageModelToolUseId, card: AnyView) {
+        self.tool_cards.insert(tool_use_id, card);
+    }
+
     pub fn confirm_tool_use(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
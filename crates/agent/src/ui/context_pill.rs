@@ -309,6 +309,16 @@ impl AddedContext {
                 summarizing: false,
             },
 
+            AssistantContext::Resource(resource_context) => AddedContext {
+                id: resource_context.id,
+                kind: ContextKind::Resource,
+                name: resource_context.name.clone(),
+                parent: Some(resource_context.server_id.to_string().into()),
+                tooltip: Some(resource_context.uri.clone()),
+                icon_path: None,
+                summarizing: false,
+            },
+
             AssistantContext::Thread(thread_context) => AddedContext {
                 id: thread_context.id,
                 kind: ContextKind::Thread,
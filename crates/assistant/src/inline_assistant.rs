@@ -2982,6 +2982,11 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         })
     }
 
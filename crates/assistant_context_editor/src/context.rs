@@ -2559,6 +2559,11 @@ impl AssistantContext {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         };
         for message in self.messages(cx) {
             if message.status != MessageStatus::Done {
@@ -836,58 +836,72 @@ impl ContextStore {
         event: &context_server::manager::Event,
         cx: &mut Context<Self>,
     ) {
-        let slash_command_working_set = self.slash_commands.clone();
         match event {
-            context_server::manager::Event::ServerStarted { server_id } => {
-                if let Some(server) = context_server_manager.read(cx).get_server(server_id) {
-                    let context_server_manager = context_server_manager.clone();
-                    cx.spawn({
-                        let server = server.clone();
-                        let server_id = server_id.clone();
-                        async move |this, cx| {
-                            let Some(protocol) = server.client() else {
-                                return;
-                            };
-
-                            if protocol.capable(context_server::protocol::ServerCapability::Prompts) {
-                                if let Some(prompts) = protocol.list_prompts().await.log_err() {
-                                    let slash_command_ids = prompts
-                                        .into_iter()
-                                        .filter(assistant_slash_commands::acceptable_prompt)
-                                        .map(|prompt| {
-                                            log::info!(
-                                                "registering context server command: {:?}",
-                                                prompt.name
-                                            );
-                                            slash_command_working_set.insert(Arc::new(
-                                                assistant_slash_commands::ContextServerSlashCommand::new(
-                                                    context_server_manager.clone(),
-                                                    &server,
-                                                    prompt,
-                                                ),
-                                            ))
-                                        })
-                                        .collect::<Vec<_>>();
-
-                                    this.update( cx, |this, _cx| {
-                                        this.context_server_slash_command_ids
-                                            .insert(server_id.clone(), slash_command_ids);
-                                    })
-                                    .log_err();
-                                }
-                            }
-                        }
-                    })
-                    .detach();
-                }
+            context_server::manager::Event::ServerStarted { server_id }
+            | context_server::manager::Event::PromptsChanged { server_id } => {
+                self.reload_context_server_slash_commands(context_server_manager, server_id, cx);
             }
             context_server::manager::Event::ServerStopped { server_id } => {
                 if let Some(slash_command_ids) =
                     self.context_server_slash_command_ids.remove(server_id)
                 {
-                    slash_command_working_set.remove(&slash_command_ids);
+                    self.slash_commands.remove(&slash_command_ids);
                 }
             }
+            context_server::manager::Event::ToolsChanged { .. }
+            | context_server::manager::Event::ResourcesChanged { .. }
+            | context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
+
+    /// Registers the prompts of a context server as slash commands, replacing the ones registered
+    /// before.
+    fn reload_context_server_slash_commands(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        server_id: &Arc<str>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(server) = context_server_manager.read(cx).get_server(server_id) else {
+            return;
+        };
+        let slash_command_working_set = self.slash_commands.clone();
+        let server_id = server_id.clone();
+        cx.spawn(async move |this, cx| {
+            let Some(protocol) = server.client() else {
+                return;
+            };
+            if !protocol.capable(context_server::protocol::ServerCapability::Prompts) {
+                return;
+            }
+            let Some(prompts) = protocol.list_prompts().await.log_err() else {
+                return;
+            };
+
+            this.update(cx, |this, _cx| {
+                if let Some(previous_ids) = this.context_server_slash_command_ids.remove(&server_id)
+                {
+                    slash_command_working_set.remove(&previous_ids);
+                }
+                let slash_command_ids = prompts
+                    .into_iter()
+                    .filter(assistant_slash_commands::acceptable_prompt)
+                    .map(|prompt| {
+                        log::info!("registering context server command: {:?}", prompt.name);
+                        slash_command_working_set.insert(Arc::new(
+                            assistant_slash_commands::ContextServerSlashCommand::new(
+                                context_server_manager.clone(),
+                                &server,
+                                prompt,
+                            ),
+                        ))
+                    })
+                    .collect::<Vec<_>>();
+                this.context_server_slash_command_ids
+                    .insert(server_id, slash_command_ids);
+            })
+            .log_err();
+        })
+        .detach();
+    }
 }
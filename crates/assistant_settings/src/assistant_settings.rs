@@ -36,6 +36,93 @@ pub enum NotifyWhenAgentWaiting {
     Never,
 }
 
+/// Settings for the agent's terminal tool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TerminalToolSettings {
+    /// How long commands may run before they're stopped, in seconds. Zero means no limit.
+    pub timeout_secs: u64,
+    /// Whether to run commands in a sandbox on Linux.
+    pub sandbox: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct TerminalToolSettingsContent {
+    /// How long commands run by the agent may run before they're stopped, in seconds.
+    /// Set to 0 to let commands run until they complete.
+    ///
+    /// Default: 300
+    pub timeout_secs: Option<u64>,
+    /// Whether to run commands in a sandbox on Linux, where the file system is read-only
+    /// outside of the project's worktrees and there's no network access.
+    /// Requires `bwrap` (bubblewrap) to be installed on the machine running the commands.
+    ///
+    /// Default: false
+    pub sandbox: Option<bool>,
+}
+
+/// A tool defined in the settings that runs a shell command or a task.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CustomToolSettings {
+    /// What the tool does and when the model should use it.
+    pub description: String,
+    /// The JSON schema of the tool's input.
+    ///
+    /// Default: an object without properties
+    #[serde(default = "default_custom_tool_input_schema")]
+    pub input_schema: serde_json::Value,
+    /// What the tool runs.
+    #[serde(flatten)]
+    pub run: CustomToolRun,
+    /// The name of the project's root directory to run in. May be omitted in single-root
+    /// projects.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// When to ask for confirmation before running the tool.
+    ///
+    /// Default: always
+    #[serde(default)]
+    pub confirm: CustomToolConfirmation,
+    /// How much of the tool's output is passed to the model, in bytes.
+    ///
+    /// Default: 16384
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    /// How long the tool may run before it's stopped, in seconds. Zero means no limit.
+    ///
+    /// Default: the terminal tool's `timeout_secs`
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+fn default_custom_tool_input_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {},
+    })
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomToolRun {
+    /// A shell command to run, in which `{{name}}` is replaced with the shell-quoted value
+    /// of the input named `name`.
+    Command(String),
+    /// The label of a task to run, in which `$ZED_CUSTOM_<name>` is replaced with the
+    /// shell-quoted value of the input named `name`. The unquoted values are available as
+    /// environment variables of the same name.
+    Task(String),
+}
+
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomToolConfirmation {
+    /// Ask for confirmation each time the tool runs, unless `always_allow_tool_actions` is set.
+    #[default]
+    Always,
+    /// Run the tool without asking for confirmation.
+    Never,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(tag = "name", rename_all = "snake_case")]
 pub enum AssistantProviderContentV1 {
@@ -87,6 +174,8 @@ pub struct AssistantSettings {
     pub profiles: IndexMap<AgentProfileId, AgentProfile>,
     pub always_allow_tool_actions: bool,
     pub notify_when_agent_waiting: NotifyWhenAgentWaiting,
+    pub terminal_tool: TerminalToolSettings,
+    pub custom_tools: IndexMap<Arc<str>, CustomToolSettings>,
 }
 
 impl AssistantSettings {
@@ -209,6 +298,8 @@ impl AssistantSettingsContent {
                     profiles: None,
                     always_allow_tool_actions: None,
                     notify_when_agent_waiting: None,
+                    terminal_tool: None,
+                    custom_tools: None,
                 },
                 VersionedAssistantSettingsContent::V2(ref settings) => settings.clone(),
             },
@@ -236,6 +327,8 @@ impl AssistantSettingsContent {
                 profiles: None,
                 always_allow_tool_actions: None,
                 notify_when_agent_waiting: None,
+                terminal_tool: None,
+                custom_tools: None,
             },
         }
     }
@@ -457,6 +550,8 @@ impl Default for VersionedAssistantSettingsContent {
             profiles: None,
             always_allow_tool_actions: None,
             notify_when_agent_waiting: None,
+            terminal_tool: None,
+            custom_tools: None,
         })
     }
 }
@@ -512,6 +607,11 @@ pub struct AssistantSettingsContentV2 {
     ///
     /// Default: "primary_screen"
     notify_when_agent_waiting: Option<NotifyWhenAgentWaiting>,
+    /// Settings for the terminal tool.
+    terminal_tool: Option<TerminalToolSettingsContent>,
+    /// Tools that run shell commands or tasks, keyed by the name the model calls them by.
+    /// Enable them in a profile's `tools` like the built-in tools.
+    custom_tools: Option<IndexMap<Arc<str>, CustomToolSettings>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -675,6 +775,16 @@ impl Settings for AssistantSettings {
                 value.notify_when_agent_waiting,
             );
             merge(&mut settings.default_profile, value.default_profile);
+            if let Some(terminal_tool) = value.terminal_tool {
+                merge(
+                    &mut settings.terminal_tool.timeout_secs,
+                    terminal_tool.timeout_secs,
+                );
+                merge(&mut settings.terminal_tool.sandbox, terminal_tool.sandbox);
+            }
+            if let Some(custom_tools) = value.custom_tools {
+                settings.custom_tools.extend(custom_tools);
+            }
 
             if let Some(profiles) = value.profiles {
                 settings
@@ -771,6 +881,8 @@ mod tests {
                             profiles: None,
                             always_allow_tool_actions: None,
                             notify_when_agent_waiting: None,
+                            terminal_tool: None,
+                            custom_tools: None,
                         }),
                     ))
                 },
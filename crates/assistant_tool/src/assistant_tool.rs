@@ -9,7 +9,7 @@ use std::fmt::Formatter;
 use std::sync::Arc;
 
 use anyhow::Result;
-use gpui::{App, Entity, SharedString, Task};
+use gpui::{AnyView, App, Entity, SharedString, Task};
 use icons::IconName;
 use language_model::LanguageModelRequestMessage;
 use language_model::LanguageModelToolSchemaFormat;
@@ -28,12 +28,14 @@ pub fn init(cx: &mut App) {
 pub struct ToolResult {
     /// The asynchronous task that will eventually resolve to the tool's output
     pub output: Task<Result<String>>,
+    /// A view showing the progress of the tool while it runs, displayed in the thread
+    pub card: Option<AnyView>,
 }
 
 impl From<Task<Result<String>>> for ToolResult {
     /// Convert from a task to a ToolResult
     fn from(output: Task<Result<String>>) -> Self {
-        Self { output }
+        Self { output, card: None }
     }
 }
 
@@ -5,6 +5,7 @@ mod contents_tool;
 mod copy_path_tool;
 mod create_directory_tool;
 mod create_file_tool;
+mod custom_tool;
 mod delete_path_tool;
 mod diagnostics_tool;
 mod fetch_tool;
@@ -78,16 +79,22 @@ pub fn init(http_client: Arc<HttpClientWithUrl>, cx: &mut App) {
     registry.register_tool(RenameTool);
     registry.register_tool(ThinkingTool);
     registry.register_tool(FetchTool::new(http_client));
+
+    custom_tool::register_custom_tools(cx);
 }
 
 #[cfg(test)]
 mod tests {
+    use assistant_settings::AssistantSettings;
     use http_client::FakeHttpClient;
+    use settings::{Settings as _, SettingsStore};
 
     use super::*;
 
     #[gpui::test]
     fn test_builtin_tool_schema_compatibility(cx: &mut App) {
+        cx.set_global(SettingsStore::test(cx));
+        AssistantSettings::register(cx);
         crate::init(
             Arc::new(http_client::HttpClientWithUrl::new(
                 FakeHttpClient::with_200_response(),
@@ -0,0 +1,474 @@
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use assistant_settings::{
+    AssistantSettings, CustomToolConfirmation, CustomToolRun, CustomToolSettings,
+};
+use assistant_tool::{ActionLog, Tool, ToolResult};
+use collections::HashSet;
+use gpui::{App, Entity, Task};
+use itertools::Itertools;
+use language_model::{LanguageModelRequestMessage, LanguageModelToolSchemaFormat};
+use project::Project;
+use regex::Regex;
+use settings::Settings;
+use task::{
+    HideStrategy, RevealStrategy, ShellBuilder, TaskContext, TaskTemplate, TaskVariables,
+    VariableName,
+};
+use ui::IconName;
+use util::markdown::MarkdownString;
+
+use crate::terminal_tool::{LIMIT, run_command, run_task_in_terminal, working_dir};
+
+/// A tool defined in the `custom_tools` setting, which runs a shell command or a task with
+/// the model's input.
+pub struct CustomTool {
+    name: Arc<str>,
+    settings: CustomToolSettings,
+}
+
+impl CustomTool {
+    pub fn new(name: Arc<str>, settings: CustomToolSettings) -> Self {
+        Self { name, settings }
+    }
+
+    fn timeout(&self, cx: &App) -> Option<Duration> {
+        let timeout_secs = self
+            .settings
+            .timeout_secs
+            .unwrap_or(AssistantSettings::get_global(cx).terminal_tool.timeout_secs);
+        (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs))
+    }
+}
+
+impl Tool for CustomTool {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &App) -> bool {
+        self.settings.confirm == CustomToolConfirmation::Always
+    }
+
+    fn description(&self) -> String {
+        self.settings.description.clone()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Terminal
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        let mut schema = self.settings.input_schema.clone();
+        assistant_tool::adapt_schema_to_format(&mut schema, format)?;
+        Ok(schema)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match &self.settings.run {
+            CustomToolRun::Command(command) => match render_command(command, input) {
+                Ok(command) => MarkdownString::inline_code(&command).0,
+                Err(_) => format!("Run {}", MarkdownString::inline_code(&self.name)),
+            },
+            CustomToolRun::Task(label) => {
+                format!("Run task {}", MarkdownString::inline_code(label))
+            }
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _messages: &[LanguageModelRequestMessage],
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        cx: &mut App,
+    ) -> ToolResult {
+        if !input.is_object() {
+            return Task::ready(Err(anyhow!(
+                "The input of `{}` must be an object",
+                self.name
+            )))
+            .into();
+        }
+
+        let cd = self.settings.cwd.as_deref().unwrap_or(".");
+        let working_dir = match working_dir(cd, project.read(cx), cx) {
+            Ok(working_dir) => working_dir,
+            Err(err) => return Task::ready(Err(err)).into(),
+        };
+        let timeout = self.timeout(cx);
+        let output_limit = self.settings.max_output_bytes.unwrap_or(LIMIT);
+
+        match &self.settings.run {
+            CustomToolRun::Command(command) => {
+                let command = match render_command(command, &input) {
+                    Ok(command) => command,
+                    Err(err) => return Task::ready(Err(err)).into(),
+                };
+                run_command(command, working_dir, timeout, output_limit, project, cx)
+            }
+            CustomToolRun::Task(label) => {
+                if AssistantSettings::get_global(cx).terminal_tool.sandbox {
+                    return Task::ready(Err(anyhow!(
+                        "Tasks can't be run in the terminal sandbox. Disable `terminal_tool.sandbox` in the assistant settings to run `{}`.",
+                        self.name
+                    )))
+                    .into();
+                }
+                let Some(window) = cx.active_window() else {
+                    return Task::ready(Err(anyhow!("Tasks can only be run from a window"))).into();
+                };
+                let spawn_task = match resolve_task(label, &working_dir, &input, &project, cx) {
+                    Ok(spawn_task) => spawn_task,
+                    Err(err) => return Task::ready(Err(err)).into(),
+                };
+                run_task_in_terminal(spawn_task, timeout, output_limit, project, window, cx)
+            }
+        }
+    }
+}
+
+/// Registers the tools defined in the settings, and updates them when the settings change.
+pub(crate) fn register_custom_tools(cx: &mut App) {
+    let mut registered_tools = HashSet::default();
+    update_custom_tools(&mut registered_tools, cx);
+    cx.observe_global::<settings::SettingsStore>(move |cx| {
+        update_custom_tools(&mut registered_tools, cx);
+    })
+    .detach();
+}
+
+fn update_custom_tools(registered_tools: &mut HashSet<Arc<str>>, cx: &mut App) {
+    let registry = assistant_tool::ToolRegistry::global(cx);
+    for name in registered_tools.drain() {
+        registry.unregister_tool_by_name(&name);
+    }
+
+    let custom_tools = AssistantSettings::get_global(cx).custom_tools.clone();
+    for (name, settings) in custom_tools {
+        if registry.tool(&name).is_some() {
+            log::error!("custom tool `{name}` has the same name as a built-in tool");
+            continue;
+        }
+        registry.register_tool(CustomTool::new(name.clone(), settings));
+        registered_tools.insert(name);
+    }
+}
+
+static TEMPLATE_VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z0-9_-]+)\s*\}\}").unwrap());
+static TASK_VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{?ZED_CUSTOM_[A-Za-z0-9_-]+\}?").unwrap());
+
+/// Replaces each `{{name}}` in the command with the shell-quoted value of the `name` input.
+fn render_command(command: &str, input: &serde_json::Value) -> Result<String> {
+    check_unquoted_variables(command, &TEMPLATE_VARIABLE)?;
+    let mut error = None;
+    let command = TEMPLATE_VARIABLE.replace_all(command, |captures: &regex::Captures| {
+        let value = input_value(input.get(&captures[1]));
+        match shlex::try_quote(&value) {
+            Ok(quoted) => quoted.into_owned(),
+            Err(err) => {
+                error.get_or_insert(anyhow!("invalid value for `{}`: {err}", &captures[1]));
+                String::new()
+            }
+        }
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(command.into_owned()),
+    }
+}
+
+/// Fails if one of the variables appears inside quotes in the command. The values of the
+/// variables are quoted when they're substituted, which only makes them safe outside of quotes:
+/// in `"{{message}}"`, a message containing `$(...)` would be run by the shell.
+fn check_unquoted_variables(command: &str, variable: &Regex) -> Result<()> {
+    for variable in variable.find_iter(command) {
+        if is_quoted_at(command, variable.start()) {
+            return Err(anyhow!(
+                "`{}` must not be quoted in `{command}`, as inputs are quoted when they're substituted",
+                variable.as_str()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the given offset of a shell command is within single or double quotes.
+fn is_quoted_at(command: &str, offset: usize) -> bool {
+    let mut quote = None;
+    let mut escaped = false;
+    for (ix, ch) in command.char_indices() {
+        if ix >= offset {
+            break;
+        }
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, ch) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(ch),
+            (Some('"'), '"') => quote = None,
+            _ => {}
+        }
+    }
+    quote.is_some()
+}
+
+/// Returns an input as it should appear in a command, or an empty string when the model
+/// didn't provide it.
+fn input_value(value: Option<&serde_json::Value>) -> Cow<'_, str> {
+    match value {
+        None | Some(serde_json::Value::Null) => Cow::Borrowed(""),
+        Some(serde_json::Value::String(value)) => Cow::Borrowed(value),
+        Some(value) => Cow::Owned(value.to_string()),
+    }
+}
+
+/// Finds the task with the given label in the worktree's and the global tasks and resolves
+/// it with the input.
+fn resolve_task(
+    label: &str,
+    working_dir: &Path,
+    input: &serde_json::Value,
+    project: &Entity<Project>,
+    cx: &App,
+) -> Result<task::SpawnInTerminal> {
+    let project = project.read(cx);
+    let inventory = project
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .ok_or_else(|| anyhow!("Tasks aren't available in this project"))?;
+    let worktree_id = project
+        .worktrees(cx)
+        .find(|worktree| worktree.read(cx).abs_path().as_ref() == working_dir)
+        .map(|worktree| worktree.read(cx).id());
+    let (_, template) = inventory
+        .read(cx)
+        .list_tasks(None, None, worktree_id, cx)
+        .into_iter()
+        .find(|(_, template)| template.label == label)
+        .ok_or_else(|| anyhow!("No task labeled `{label}` was found"))?;
+    resolve_task_template(&template, working_dir, input, project.is_local())
+}
+
+/// Resolves the task with the input as `$ZED_CUSTOM_<name>` variables. Tasks are run by a
+/// shell, so the values substituted into the command are shell-quoted, while the environment
+/// variables hold them as they are.
+fn resolve_task_template(
+    template: &TaskTemplate,
+    working_dir: &Path,
+    input: &serde_json::Value,
+    is_local: bool,
+) -> Result<task::SpawnInTerminal> {
+    // The shell receives the command and the arguments joined by spaces.
+    let command = std::iter::once(&template.command)
+        .chain(&template.args)
+        .join(" ");
+    check_unquoted_variables(&command, &TASK_VARIABLE)?;
+
+    let mut task_variables = TaskVariables::default();
+    task_variables.insert(
+        VariableName::WorktreeRoot,
+        working_dir.to_string_lossy().to_string(),
+    );
+    let mut input_env = Vec::new();
+    if let Some(input) = input.as_object() {
+        for (name, value) in input {
+            let variable_name = VariableName::Custom(Cow::Owned(name.clone()));
+            let value = input_value(Some(value));
+            let quoted_value = shlex::try_quote(&value)
+                .map_err(|err| anyhow!("invalid value for `{name}`: {err}"))?;
+            task_variables.insert(variable_name.clone(), quoted_value.into_owned());
+            input_env.push((variable_name.to_string(), value.into_owned()));
+        }
+    }
+    let task_context = TaskContext {
+        cwd: Some(working_dir.to_path_buf()),
+        task_variables,
+        project_env: Default::default(),
+    };
+
+    let mut spawn_task = template
+        .resolve_task("custom_tool", &task_context)
+        .and_then(|resolved_task| resolved_task.resolved)
+        .ok_or_else(|| anyhow!("The task labeled `{}` couldn't be resolved", template.label))?;
+    spawn_task.env.extend(input_env);
+    // Like the terminal panel, run the task with a shell rather than executing its command.
+    let (command, args) = ShellBuilder::new(is_local, &spawn_task.shell)
+        .build(spawn_task.command.clone(), &spawn_task.args);
+    spawn_task.command = command;
+    spawn_task.args = args;
+    // Like the terminal tool's commands, tasks run in the background and the agent reads
+    // their output, rather than the user.
+    spawn_task.use_new_terminal = true;
+    spawn_task.allow_concurrent_runs = true;
+    spawn_task.reveal = RevealStrategy::Never;
+    spawn_task.hide = HideStrategy::Never;
+    spawn_task.show_summary = false;
+    spawn_task.show_command = false;
+    spawn_task.show_rerun = false;
+    Ok(spawn_task)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt as _;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use terminal::terminal_settings::{TerminalSettings, VenvSettings};
+
+    use super::*;
+
+    #[test]
+    fn test_render_command() {
+        let input = json!({
+            "path": "src/main.rs",
+            "pattern": "fn main",
+            "count": 3,
+        });
+        assert_eq!(
+            render_command("wc -l {{path}}", &input).unwrap(),
+            "wc -l src/main.rs"
+        );
+        assert_eq!(
+            render_command("grep -c {{ pattern }} {{path}} | head -n {{count}}", &input).unwrap(),
+            "grep -c 'fn main' src/main.rs | head -n 3"
+        );
+        assert_eq!(
+            render_command("echo {{missing}}", &input).unwrap(),
+            "echo ''"
+        );
+        assert_eq!(
+            render_command("echo \\\"{{path}} 'a\"b'", &input).unwrap(),
+            "echo \\\"src/main.rs 'a\"b'"
+        );
+
+        // Quoting the values only protects them outside of quotes.
+        assert!(render_command("echo \"{{path}}\"", &input).is_err());
+        assert!(render_command("echo 'x {{path}}'", &input).is_err());
+    }
+
+    #[test]
+    fn test_resolve_task_template() {
+        let template = TaskTemplate {
+            label: "search".to_string(),
+            command: "grep -rn $ZED_CUSTOM_pattern".to_string(),
+            args: vec!["$ZED_CUSTOM_path".to_string()],
+            ..Default::default()
+        };
+        let input = json!({
+            "pattern": "x; rm -rf ~",
+            "path": "$(touch pwned)",
+        });
+        let spawn_task =
+            resolve_task_template(&template, Path::new("/root"), &input, true).unwrap();
+        assert_eq!(
+            spawn_task.args.last().unwrap(),
+            "grep -rn 'x; rm -rf ~' '$(touch pwned)'"
+        );
+        assert_eq!(spawn_task.env["ZED_CUSTOM_pattern"], "x; rm -rf ~");
+        assert_eq!(spawn_task.env["ZED_CUSTOM_path"], "$(touch pwned)");
+        assert_eq!(spawn_task.reveal, RevealStrategy::Never);
+
+        let input = json!({ "pattern": "nul\0byte" });
+        assert!(resolve_task_template(&template, Path::new("/root"), &input, true).is_err());
+
+        let template = TaskTemplate {
+            label: "echo".to_string(),
+            command: "echo".to_string(),
+            args: vec!["\"${ZED_CUSTOM_message}\"".to_string()],
+            ..Default::default()
+        };
+        let input = json!({ "message": "$(touch pwned)" });
+        assert!(resolve_task_template(&template, Path::new("/root"), &input, true).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_run_task_in_terminal(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            TerminalSettings::register(cx);
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<TerminalSettings>(cx, |settings| {
+                    settings.detect_venv = Some(VenvSettings::Off);
+                });
+            });
+        });
+
+        // The terminal runs the task in a real directory.
+        let root = std::env::temp_dir();
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(&root, json!({})).await;
+        let project = Project::test(fs, [root.as_path()], cx).await;
+        let cx = cx.add_empty_window();
+        let window = cx.update(|window, _| window.window_handle());
+
+        let template = TaskTemplate {
+            label: "echo".to_string(),
+            command: "printf '%s\\n'".to_string(),
+            args: vec!["$ZED_CUSTOM_message".to_string()],
+            ..Default::default()
+        };
+        let input = json!({ "message": "$(echo pwned);  done" });
+        let spawn_task = resolve_task_template(&template, &root, &input, true).unwrap();
+        let mut output = cx
+            .update(|_, cx| run_task_in_terminal(spawn_task, None, LIMIT, project, window, cx))
+            .output;
+
+        // The terminal batches its output with timers, which only advance with the clock.
+        let mut attempts = 0;
+        let output = loop {
+            if let Some(output) = (&mut output).now_or_never() {
+                break output.unwrap();
+            }
+            attempts += 1;
+            assert!(attempts < 1000, "the task didn't complete");
+            cx.executor().advance_clock(Duration::from_millis(10));
+            cx.run_until_parked();
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert!(
+            output.lines().any(|line| line == "$(echo pwned);  done"),
+            "unexpected output: {output}"
+        );
+        assert!(!output.lines().any(|line| line == "pwned"));
+    }
+
+    #[gpui::test]
+    fn test_needs_confirmation(cx: &mut App) {
+        let settings: CustomToolSettings = serde_json::from_value(json!({
+            "description": "Counts the lines of a file",
+            "command": "wc -l {{path}}",
+        }))
+        .unwrap();
+        assert_eq!(settings.confirm, CustomToolConfirmation::Always);
+        let tool = CustomTool::new("count_lines".into(), settings.clone());
+        assert!(tool.needs_confirmation(&json!({ "path": "a.rs" }), cx));
+
+        let tool = CustomTool::new(
+            "count_lines".into(),
+            CustomToolSettings {
+                confirm: CustomToolConfirmation::Never,
+                ..settings
+            },
+        );
+        assert!(!tool.needs_confirmation(&json!({ "path": "a.rs" }), cx));
+    }
+}
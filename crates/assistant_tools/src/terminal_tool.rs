@@ -1,19 +1,27 @@
 use crate::schema::json_schema_for;
 use anyhow::{Context as _, Result, anyhow};
+use assistant_settings::AssistantSettings;
 use assistant_tool::{ActionLog, Tool, ToolResult};
 use futures::io::BufReader;
 use futures::{AsyncBufReadExt, AsyncReadExt, FutureExt};
-use gpui::{App, AppContext, Entity, Task};
+use gpui::{
+    AnyWindowHandle, App, AppContext, AsyncApp, BackgroundExecutor, Entity, Subscription, Task,
+    WeakEntity,
+};
 use language_model::{LanguageModelRequestMessage, LanguageModelToolSchemaFormat};
-use project::Project;
+use project::{Project, terminals::TerminalKind};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::future;
+use settings::Settings;
+use std::future::{self, Future};
+use std::time::Duration;
+use task::{HideStrategy, RevealStrategy, RevealTarget, Shell, SpawnInTerminal, TaskId};
+use terminal::Terminal;
+use ui::prelude::*;
 use util::get_system_shell;
 
 use std::path::Path;
 use std::sync::Arc;
-use ui::IconName;
 use util::command::new_smol_command;
 use util::markdown::MarkdownString;
 
@@ -85,71 +93,529 @@ impl Tool for TerminalTool {
             Err(err) => return Task::ready(Err(anyhow!(err))).into(),
         };
 
-        let project = project.read(cx);
-        let input_path = Path::new(&input.cd);
-        let working_dir = if input.cd == "." {
-            // Accept "." as meaning "the one worktree" if we only have one worktree.
-            let mut worktrees = project.worktrees(cx);
+        let working_dir = match working_dir(&input.cd, project.read(cx), cx) {
+            Ok(working_dir) => working_dir,
+            Err(err) => return Task::ready(Err(err)).into(),
+        };
+
+        let timeout_secs = AssistantSettings::get_global(cx).terminal_tool.timeout_secs;
+        let timeout = (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs));
+        run_command(input.command, working_dir, timeout, LIMIT, project, cx)
+    }
+}
+
+/// Runs the command in the given working directory, returning at most `output_limit` bytes
+/// of its output.
+pub(crate) fn run_command(
+    command: String,
+    working_dir: Arc<Path>,
+    timeout: Option<Duration>,
+    output_limit: usize,
+    project: Entity<Project>,
+    cx: &mut App,
+) -> ToolResult {
+    let sandbox_paths = AssistantSettings::get_global(cx)
+        .terminal_tool
+        .sandbox
+        .then(|| {
+            project
+                .read(cx)
+                .worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path())
+                .collect::<Vec<_>>()
+        });
+    let is_remote = project.read(cx).is_via_ssh();
+    if sandbox_paths.is_some() && !is_remote && !cfg!(target_os = "linux") {
+        return Task::ready(Err(anyhow!(
+            "The terminal sandbox is only supported on Linux. Disable `terminal_tool.sandbox` in the assistant settings to run commands."
+        )))
+        .into();
+    }
+
+    let Some(window) = cx.active_window() else {
+        // Without a window, such as when running headless, the command can't be shown in a
+        // terminal, so it's run directly instead.
+        if !project.read(cx).is_local() {
+            return Task::ready(Err(anyhow!(
+                "Commands can only be run in remote projects from a window"
+            )))
+            .into();
+        }
+        let executor = cx.background_executor().clone();
+        let command = run_command_limited(working_dir, command, sandbox_paths, output_limit);
+        return cx
+            .background_spawn(async move {
+                match timeout {
+                    Some(timeout) => with_timeout(command, timeout, &executor).await,
+                    None => command.await,
+                }
+            })
+            .into();
+    };
 
-            let only_worktree = match worktrees.next() {
-                Some(worktree) => worktree,
+    run_in_terminal(
+        command,
+        working_dir,
+        sandbox_paths,
+        is_remote,
+        timeout,
+        output_limit,
+        project,
+        window,
+        cx,
+    )
+}
+
+/// Resolves a directory given by the model or the user to one of the project's worktrees.
+pub(crate) fn working_dir(cd: &str, project: &Project, cx: &App) -> Result<Arc<Path>> {
+    let input_path = Path::new(cd);
+    if cd == "." {
+        // Accept "." as meaning "the one worktree" if we only have one worktree.
+        let mut worktrees = project.worktrees(cx);
+
+        let only_worktree = worktrees
+            .next()
+            .ok_or_else(|| anyhow!("No worktrees found in the project"))?;
+
+        if worktrees.next().is_some() {
+            return Err(anyhow!(
+                "'.' is ambiguous in multi-root workspaces. Please specify a root directory explicitly."
+            ));
+        }
+
+        Ok(only_worktree.read(cx).abs_path())
+    } else if input_path.is_absolute() {
+        // Absolute paths are allowed, but only if they're in one of the project's worktrees.
+        if !project
+            .worktrees(cx)
+            .any(|worktree| input_path.starts_with(&worktree.read(cx).abs_path()))
+        {
+            return Err(anyhow!(
+                "The absolute path must be within one of the project's worktrees"
+            ));
+        }
+
+        Ok(input_path.into())
+    } else {
+        let worktree = project
+            .worktree_for_root_name(cd, cx)
+            .ok_or_else(|| anyhow!("`cd` directory {} not found in the project", cd))?;
+
+        Ok(worktree.read(cx).abs_path())
+    }
+}
+
+/// Runs the command in a terminal created by the project, so that it runs on the remote host
+/// for remote projects, and shows its output in a card while it runs.
+#[allow(clippy::too_many_arguments)]
+fn run_in_terminal(
+    command: String,
+    working_dir: Arc<Path>,
+    sandbox_paths: Option<Vec<Arc<Path>>>,
+    is_remote: bool,
+    timeout: Option<Duration>,
+    output_limit: usize,
+    project: Entity<Project>,
+    window: AnyWindowHandle,
+    cx: &mut App,
+) -> ToolResult {
+    let (program, args) = match (&sandbox_paths, is_remote) {
+        (Some(sandbox_paths), false) => {
+            sandboxed_command(&get_system_shell(), &command, &working_dir, sandbox_paths)
+        }
+        // The remote user's shell isn't known here, so the sandbox runs the command with `sh`.
+        (Some(sandbox_paths), true) => {
+            sandboxed_command("sh", &command, &working_dir, sandbox_paths)
+        }
+        (None, false) => (get_system_shell(), vec!["-c".to_string(), command.clone()]),
+        (None, true) => remote_shell_command(&command),
+    };
+
+    let spawn_task = SpawnInTerminal {
+        id: TaskId("terminal_tool".to_string()),
+        full_label: command.clone(),
+        label: command.clone(),
+        command: program,
+        args,
+        command_label: command.clone(),
+        cwd: Some(working_dir.to_path_buf()),
+        // Pagers would wait for input that never comes.
+        env: [("PAGER".to_string(), "cat".to_string())]
+            .into_iter()
+            .collect(),
+        use_new_terminal: true,
+        allow_concurrent_runs: true,
+        reveal: RevealStrategy::Never,
+        reveal_target: RevealTarget::Dock,
+        hide: HideStrategy::Never,
+        shell: Shell::System,
+        show_summary: false,
+        show_command: false,
+        show_rerun: false,
+    };
+    run_task_in_terminal(spawn_task, timeout, output_limit, project, window, cx)
+}
+
+/// Runs the task in a terminal created by the project, showing its output in a card while it
+/// runs and returning at most `output_limit` bytes of it.
+pub(crate) fn run_task_in_terminal(
+    spawn_task: SpawnInTerminal,
+    timeout: Option<Duration>,
+    output_limit: usize,
+    project: Entity<Project>,
+    window: AnyWindowHandle,
+    cx: &mut App,
+) -> ToolResult {
+    let command = spawn_task.command_label.clone();
+    let terminal = project.update(cx, |project, cx| {
+        project.create_terminal(TerminalKind::Task(spawn_task), window, cx)
+    });
+
+    let card = cx.new(|_| TerminalToolCard::new(command));
+    let output = cx.spawn({
+        let card = card.downgrade();
+        async move |cx| {
+            let mut kill_on_drop = KillOnDrop {
+                terminal: None,
+                card: card.clone(),
+                cx: cx.clone(),
+            };
+            let terminal = terminal.await?;
+            kill_on_drop.terminal = Some(terminal.downgrade());
+            card.update(cx, |card, cx| card.set_terminal(terminal.clone(), cx))
+                .ok();
+
+            let completed =
+                terminal.read_with(cx, |terminal, cx| terminal.wait_for_completed_task(cx))?;
+            let timed_out = match timeout {
+                Some(timeout) => {
+                    let mut completed = completed.fuse();
+                    let mut timer = cx.background_executor().timer(timeout).fuse();
+                    futures::select_biased! {
+                        _ = completed => false,
+                        _ = timer => true,
+                    }
+                }
                 None => {
-                    return Task::ready(Err(anyhow!("No worktrees found in the project"))).into();
+                    completed.await;
+                    false
                 }
             };
 
-            if worktrees.next().is_some() {
-                return Task::ready(Err(anyhow!(
-                    "'.' is ambiguous in multi-root workspaces. Please specify a root directory explicitly."
-                ))).into();
-            }
+            let (content, exit_code) = terminal.update(cx, |terminal, _| {
+                if timed_out {
+                    terminal.kill_active_task();
+                }
+                (
+                    terminal.get_content(),
+                    terminal.task().and_then(|task| task.exit_code),
+                )
+            })?;
+            let status = match timeout {
+                Some(timeout) if timed_out => CommandStatus::TimedOut(timeout),
+                _ => CommandStatus::Exited(exit_code),
+            };
+            card.update(cx, |card, cx| card.finish(status, cx)).ok();
 
-            only_worktree.read(cx).abs_path()
-        } else if input_path.is_absolute() {
-            // Absolute paths are allowed, but only if they're in one of the project's worktrees.
-            if !project
-                .worktrees(cx)
-                .any(|worktree| input_path.starts_with(&worktree.read(cx).abs_path()))
-            {
-                return Task::ready(Err(anyhow!(
-                    "The absolute path must be within one of the project's worktrees"
-                )))
-                .into();
-            }
+            Ok(terminal_output(&content, status, output_limit))
+        }
+    });
 
-            input_path.into()
-        } else {
-            let Some(worktree) = project.worktree_for_root_name(&input.cd, cx) else {
-                return Task::ready(Err(anyhow!(
-                    "`cd` directory {} not found in the project",
-                    &input.cd
-                )))
-                .into();
-            };
+    ToolResult {
+        output,
+        card: Some(card.into()),
+    }
+}
+
+/// Runs the command with the remote user's shell.
+///
+/// The program and its arguments are quoted before they're sent to the remote host, so `sh`
+/// resolves `$SHELL` there and receives the command as a positional argument.
+fn remote_shell_command(command: &str) -> (String, Vec<String>) {
+    (
+        "sh".to_string(),
+        vec![
+            "-c".to_string(),
+            r#"exec "${SHELL:-sh}" -c "$1""#.to_string(),
+            "sh".to_string(),
+            command.to_string(),
+        ],
+    )
+}
+
+/// Wraps the command to run in a `bwrap` sandbox, where the file system is read-only outside
+/// of the given paths and there's no network access.
+///
+/// `bwrap` is looked up where the command runs, which may be a remote host, and the command
+/// fails with an explanation when it isn't installed.
+fn sandboxed_command(
+    shell: &str,
+    command: &str,
+    working_dir: &Path,
+    writable_paths: &[Arc<Path>],
+) -> (String, Vec<String>) {
+    let mut args = [
+        "-c",
+        BWRAP_SCRIPT,
+        "sh",
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+    ]
+    .into_iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>();
+    for path in writable_paths {
+        let path = path.to_string_lossy().to_string();
+        args.extend(["--bind".to_string(), path.clone(), path]);
+    }
+    args.extend([
+        "--unshare-net".to_string(),
+        "--die-with-parent".to_string(),
+        "--chdir".to_string(),
+        working_dir.to_string_lossy().to_string(),
+        "--".to_string(),
+        shell.to_string(),
+        "-c".to_string(),
+        command.to_string(),
+    ]);
+    ("sh".to_string(), args)
+}
+
+const BWRAP_SCRIPT: &str = "command -v bwrap >/dev/null 2>&1 || { \
+    echo 'The terminal sandbox requires bwrap (bubblewrap), which is not installed. \
+    Install it or set `assistant.terminal_tool.sandbox` to false.' >&2; exit 127; }; \
+    exec bwrap \"$@\"";
+
+/// Stops the command when the tool is canceled, which drops its task.
+struct KillOnDrop {
+    /// The terminal the command runs in, once it has been created.
+    terminal: Option<WeakEntity<Terminal>>,
+    card: WeakEntity<TerminalToolCard>,
+    cx: AsyncApp,
+}
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let terminal = self.terminal.clone();
+        let card = self.card.clone();
+        // The app may be borrowed while the task is dropped, so stop the command on the next tick.
+        self.cx
+            .spawn(async move |cx| {
+                if let Some(terminal) = terminal {
+                    terminal
+                        .update(cx, |terminal, _| terminal.kill_active_task())
+                        .ok();
+                }
+                card.update(cx, |card, cx| card.cancel(cx)).ok();
+            })
+            .detach();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CommandStatus {
+    Running,
+    Exited(Option<i32>),
+    TimedOut(Duration),
+    Canceled,
+}
+
+/// Shows the output of a command while it runs.
+pub struct TerminalToolCard {
+    command: SharedString,
+    terminal: Option<Entity<Terminal>>,
+    status: CommandStatus,
+    _subscription: Option<Subscription>,
+}
+
+const CARD_LINE_COUNT: usize = 12;
+
+impl TerminalToolCard {
+    fn new(command: String) -> Self {
+        Self {
+            command: command.into(),
+            terminal: None,
+            status: CommandStatus::Running,
+            _subscription: None,
+        }
+    }
+
+    fn set_terminal(&mut self, terminal: Entity<Terminal>, cx: &mut Context<Self>) {
+        self._subscription = Some(cx.subscribe(&terminal, |_, _, _, cx| cx.notify()));
+        self.terminal = Some(terminal);
+        cx.notify();
+    }
+
+    fn finish(&mut self, status: CommandStatus, cx: &mut Context<Self>) {
+        self.status = status;
+        self._subscription = None;
+        cx.notify();
+    }
 
-            worktree.read(cx).abs_path()
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        if self.status == CommandStatus::Running {
+            self.finish(CommandStatus::Canceled, cx);
+        }
+    }
+}
+
+impl Render for TerminalToolCard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let lines = self
+            .terminal
+            .as_ref()
+            .map(|terminal| terminal.read(cx).last_n_non_empty_lines(CARD_LINE_COUNT))
+            .unwrap_or_default();
+
+        let (status, color): (SharedString, Color) = match self.status {
+            CommandStatus::Running => ("Running…".into(), Color::Muted),
+            CommandStatus::Exited(Some(0)) => ("Completed".into(), Color::Success),
+            CommandStatus::Exited(Some(code)) => {
+                (format!("Exited with code {code}").into(), Color::Error)
+            }
+            CommandStatus::Exited(None) => ("Stopped".into(), Color::Warning),
+            CommandStatus::TimedOut(_) => ("Timed out".into(), Color::Warning),
+            CommandStatus::Canceled => ("Canceled".into(), Color::Muted),
         };
 
-        cx.background_spawn(run_command_limited(working_dir, input.command))
-            .into()
+        v_flex()
+            .my_2()
+            .rounded_lg()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .overflow_hidden()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .justify_between()
+                    .bg(cx.theme().colors().editor_background)
+                    .child(
+                        h_flex()
+                            .gap_1p5()
+                            .min_w_0()
+                            .child(
+                                Icon::new(IconName::Terminal)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                Label::new(self.command.clone())
+                                    .size(LabelSize::Small)
+                                    .buffer_font(cx)
+                                    .truncate(),
+                            ),
+                    )
+                    .child(Label::new(status).size(LabelSize::XSmall).color(color)),
+            )
+            .when(!lines.is_empty(), |card| {
+                card.child(
+                    v_flex()
+                        .p_2()
+                        .border_t_1()
+                        .border_color(cx.theme().colors().border)
+                        .children(lines.into_iter().map(|line| {
+                            Label::new(line)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .buffer_font(cx)
+                        })),
+                )
+            })
     }
 }
 
-const LIMIT: usize = 16 * 1024;
+/// Formats the contents of the terminal a command ran in as the tool's output, keeping the end
+/// of long outputs.
+fn terminal_output(content: &str, status: CommandStatus, limit: usize) -> String {
+    let output = if content.len() > limit {
+        let mut start = content.len() - limit;
+        while !content.is_char_boundary(start) {
+            start += 1;
+        }
+        // Valid to find `\n` in UTF-8 since 0-127 ASCII characters are not used in
+        // multi-byte characters.
+        if let Some(newline_ix) = content[start..].find('\n') {
+            start += newline_ix + 1;
+        }
+        let content = &content[start..];
+        format!(
+            "Command output too long. The last {} bytes:\n\n{}",
+            content.len(),
+            output_block(content),
+        )
+    } else if content.is_empty() {
+        String::new()
+    } else {
+        output_block(content)
+    };
 
-async fn run_command_limited(working_dir: Arc<Path>, command: String) -> Result<String> {
+    match status {
+        CommandStatus::Exited(Some(0)) if output.is_empty() => {
+            "Command executed successfully.".to_string()
+        }
+        CommandStatus::Exited(Some(0)) => output,
+        CommandStatus::Exited(Some(code)) => {
+            format!("Command failed with exit code {code}.\n\n{output}")
+        }
+        CommandStatus::TimedOut(timeout) => format!(
+            "Command timed out after {} seconds and was stopped.\n\n{output}",
+            timeout.as_secs()
+        ),
+        CommandStatus::Exited(None) | CommandStatus::Running | CommandStatus::Canceled => {
+            format!("Command was stopped before it completed.\n\n{output}")
+        }
+    }
+}
+
+pub(crate) async fn with_timeout(
+    command: impl Future<Output = Result<String>>,
+    timeout: Duration,
+    executor: &BackgroundExecutor,
+) -> Result<String> {
+    let mut command = Box::pin(command.fuse());
+    let mut timer = executor.timer(timeout).fuse();
+    futures::select_biased! {
+        output = command => output,
+        _ = timer => Ok(format!(
+            "Command timed out after {} seconds and was stopped.",
+            timeout.as_secs()
+        )),
+    }
+}
+
+pub(crate) const LIMIT: usize = 16 * 1024;
+
+async fn run_command_limited(
+    working_dir: Arc<Path>,
+    command: String,
+    sandbox_paths: Option<Vec<Arc<Path>>>,
+    limit: usize,
+) -> Result<String> {
     let shell = get_system_shell();
+    let (program, args) = match &sandbox_paths {
+        Some(sandbox_paths) => sandboxed_command(&shell, &command, &working_dir, sandbox_paths),
+        None => (shell.clone(), vec!["-c".to_string(), command]),
+    };
 
-    let mut cmd = new_smol_command(&shell)
-        .arg("-c")
-        .arg(&command)
+    let mut cmd = new_smol_command(&program)
+        .args(&args)
         .current_dir(working_dir)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
         .spawn()
         .context("Failed to execute terminal command")?;
 
-    let mut combined_buffer = String::with_capacity(LIMIT + 1);
+    let mut combined_buffer = String::with_capacity(limit + 1);
 
     let mut out_reader = BufReader::new(cmd.stdout.take().context("Failed to get stdout")?);
     let mut out_tmp_buffer = String::with_capacity(512);
@@ -171,7 +637,7 @@ async fn run_command_limited(working_dir: Arc<Path>, command: String) -> Result<
 
     let mut has_stdout = true;
     let mut has_stderr = true;
-    while (has_stdout || has_stderr) && combined_buffer.len() < LIMIT + 1 {
+    while (has_stdout || has_stderr) && combined_buffer.len() < limit + 1 {
         futures::select_biased! {
             read = out_line => {
                 drop(out_line);
@@ -198,8 +664,8 @@ async fn run_command_limited(working_dir: Arc<Path>, command: String) -> Result<
 
     drop((out_line, err_line));
 
-    let truncated = combined_buffer.len() > LIMIT;
-    combined_buffer.truncate(LIMIT);
+    let truncated = combined_buffer.len() > limit;
+    combined_buffer.truncate(limit);
 
     consume_reader(out_reader, truncated).await?;
     consume_reader(err_reader, truncated).await?;
@@ -276,8 +742,13 @@ mod tests {
     async fn test_run_command_simple(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
 
-        let result =
-            run_command_limited(Path::new(".").into(), "echo 'Hello, World!'".to_string()).await;
+        let result = run_command_limited(
+            Path::new(".").into(),
+            "echo 'Hello, World!'".to_string(),
+            None,
+            LIMIT,
+        )
+        .await;
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "```\nHello, World!\n```");
@@ -288,7 +759,8 @@ mod tests {
         cx.executor().allow_parking();
 
         let command = "echo 'stdout 1' && sleep 0.01 && echo 'stderr 1' >&2 && sleep 0.01 && echo 'stdout 2' && sleep 0.01 && echo 'stderr 2' >&2";
-        let result = run_command_limited(Path::new(".").into(), command.to_string()).await;
+        let result =
+            run_command_limited(Path::new(".").into(), command.to_string(), None, LIMIT).await;
 
         assert!(result.is_ok());
         assert_eq!(
@@ -305,6 +777,8 @@ mod tests {
         let result = run_command_limited(
             Path::new(".").into(),
             "echo '1'; sleep 0.01; echo '2'; sleep 0.01; echo '3'".to_string(),
+            None,
+            LIMIT,
         )
         .await;
 
@@ -318,7 +792,7 @@ mod tests {
 
         let cmd = format!("echo '{}'; sleep 0.01;", "X".repeat(LIMIT * 2));
 
-        let result = run_command_limited(Path::new(".").into(), cmd).await;
+        let result = run_command_limited(Path::new(".").into(), cmd, None, LIMIT).await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -336,7 +810,7 @@ mod tests {
         cx.executor().allow_parking();
 
         let cmd = format!("echo '{}'; ", "X".repeat(120)).repeat(160);
-        let result = run_command_limited(Path::new(".").into(), cmd).await;
+        let result = run_command_limited(Path::new(".").into(), cmd, None, LIMIT).await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -354,7 +828,8 @@ mod tests {
     async fn test_command_failure(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
 
-        let result = run_command_limited(Path::new(".").into(), "exit 42".to_string()).await;
+        let result =
+            run_command_limited(Path::new(".").into(), "exit 42".to_string(), None, LIMIT).await;
 
         assert!(result.is_ok());
         let output = result.unwrap();
@@ -368,4 +843,147 @@ mod tests {
         );
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn test_sandboxed_command() {
+        let writable_paths: Vec<Arc<Path>> = vec![Path::new("/project").into()];
+        let (program, args) = sandboxed_command(
+            "bash",
+            "cargo test",
+            Path::new("/project/crate"),
+            &writable_paths,
+        );
+
+        assert_eq!(program, "sh");
+        assert_eq!(args[..3], ["-c", BWRAP_SCRIPT, "sh"]);
+        let bwrap_args = &args[3..];
+        assert!(
+            bwrap_args
+                .windows(3)
+                .any(|window| window == ["--bind", "/project", "/project"])
+        );
+        assert!(bwrap_args.contains(&"--unshare-net".to_string()));
+        assert!(
+            bwrap_args
+                .windows(2)
+                .any(|window| window == ["--chdir", "/project/crate"])
+        );
+        assert!(bwrap_args.ends_with(&[
+            "--".to_string(),
+            "bash".to_string(),
+            "-c".to_string(),
+            "cargo test".to_string(),
+        ]));
+    }
+
+    #[test]
+    fn test_remote_shell_command() {
+        let (program, args) = remote_shell_command("echo \"$0\" 'a  b'");
+        let output = std::process::Command::new(program)
+            .args(args)
+            .env("SHELL", "/bin/sh")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "/bin/sh a  b\n");
+    }
+
+    #[gpui::test]
+    async fn test_with_timeout(cx: &mut TestAppContext) {
+        let executor = cx.executor();
+
+        let output = with_timeout(
+            async { Ok("done".to_string()) },
+            Duration::from_secs(5),
+            &executor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "done");
+
+        let task = executor.spawn({
+            let executor = executor.clone();
+            async move {
+                with_timeout(
+                    future::pending::<Result<String>>(),
+                    Duration::from_secs(5),
+                    &executor,
+                )
+                .await
+            }
+        });
+        executor.advance_clock(Duration::from_secs(5));
+        assert_eq!(
+            task.await.unwrap(),
+            "Command timed out after 5 seconds and was stopped."
+        );
+    }
+
+    #[test]
+    fn test_terminal_output() {
+        assert_eq!(
+            terminal_output("", CommandStatus::Exited(Some(0)), LIMIT),
+            "Command executed successfully."
+        );
+        assert_eq!(
+            terminal_output("ok\n", CommandStatus::Exited(Some(0)), LIMIT),
+            "```\nok\n```"
+        );
+        assert_eq!(
+            terminal_output("oops\n", CommandStatus::Exited(Some(2)), LIMIT),
+            "Command failed with exit code 2.\n\n```\noops\n```"
+        );
+        assert_eq!(
+            terminal_output("", CommandStatus::TimedOut(Duration::from_secs(60)), LIMIT),
+            "Command timed out after 60 seconds and was stopped.\n\n"
+        );
+        assert_eq!(
+            terminal_output("", CommandStatus::Canceled, LIMIT),
+            "Command was stopped before it completed.\n\n"
+        );
+
+        // Long outputs keep their last lines, starting from a line boundary.
+        let content = (0..100)
+            .map(|ix| format!("line {ix}\n"))
+            .collect::<String>();
+        let output = terminal_output(&content, CommandStatus::Exited(Some(0)), 40);
+        assert_eq!(
+            output,
+            "Command output too long. The last 32 bytes:\n\n```\nline 96\nline 97\nline 98\nline 99\n```"
+        );
+
+        // Truncation doesn't split multi-byte characters.
+        let content = "é".repeat(10);
+        let output = terminal_output(&content, CommandStatus::Exited(Some(0)), 5);
+        assert_eq!(
+            output,
+            "Command output too long. The last 4 bytes:\n\n```\néé\n```"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_kill_on_drop_cancels_running_command(cx: &mut TestAppContext) {
+        let running_card = cx.new(|_| TerminalToolCard::new("sleep 100".to_string()));
+        let finished_card = cx.new(|_| TerminalToolCard::new("true".to_string()));
+        finished_card.update(cx, |card, cx| {
+            card.finish(CommandStatus::Exited(Some(0)), cx)
+        });
+
+        for card in [&running_card, &finished_card] {
+            drop(KillOnDrop {
+                terminal: None,
+                card: card.downgrade(),
+                cx: cx.to_async(),
+            });
+        }
+        cx.run_until_parked();
+
+        running_card.read_with(cx, |card, _| {
+            assert_eq!(card.status, CommandStatus::Canceled)
+        });
+        finished_card.read_with(cx, |card, _| {
+            assert_eq!(card.status, CommandStatus::Exited(Some(0)))
+        });
+    }
 }
@@ -8,7 +8,9 @@ use aws_sdk_bedrockruntime as bedrock;
 pub use aws_sdk_bedrockruntime as bedrock_client;
 pub use aws_sdk_bedrockruntime::types::{
     AutoToolChoice as BedrockAutoToolChoice, ContentBlock as BedrockInnerContent,
-    Tool as BedrockTool, ToolChoice as BedrockToolChoice, ToolConfiguration as BedrockToolConfig,
+    InferenceConfiguration as BedrockInferenceConfig,
+    SpecificToolChoice as BedrockSpecificToolChoice, Tool as BedrockTool,
+    ToolChoice as BedrockToolChoice, ToolConfiguration as BedrockToolConfig,
     ToolInputSchema as BedrockToolInputSchema, ToolSpecification as BedrockToolSpec,
 };
 use aws_smithy_types::{Document, Number as AwsNumber};
@@ -60,6 +62,15 @@ pub async fn stream_completion(
                 response = response.set_tool_config(request.tools);
             }
 
+            response = response.inference_config(
+                BedrockInferenceConfig::builder()
+                    .max_tokens(request.max_tokens.try_into().unwrap_or(i32::MAX))
+                    // Thinking requires the default temperature.
+                    .set_temperature(request.temperature.filter(|_| request.thinking.is_none()))
+                    .set_top_p(request.top_p)
+                    .build(),
+            );
+
             let response = response.send().await;
 
             match response {
@@ -8,8 +8,8 @@ use std::sync::Arc;
 
 pub use channel_buffer::{ACKNOWLEDGE_DEBOUNCE_INTERVAL, ChannelBuffer, ChannelBufferEvent};
 pub use channel_chat::{
-    ChannelChat, ChannelChatEvent, ChannelMessage, ChannelMessageId, MessageParams,
-    mentions_to_proto,
+    ChannelChat, ChannelChatEvent, ChannelMessage, ChannelMessageId, ChannelMessageReaction,
+    ChannelThread, MessageParams, mentions_to_proto,
 };
 pub use channel_store::{Channel, ChannelEvent, ChannelMembership, ChannelStore};
 
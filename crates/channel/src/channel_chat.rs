@@ -4,12 +4,13 @@ use client::{
     ChannelId, Client, Subscription, TypedEnvelope, UserId, proto,
     user::{User, UserStore},
 };
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use futures::lock::Mutex;
 use gpui::{App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Task, WeakEntity};
 use rand::prelude::*;
 use rpc::AnyProtoClient;
 use std::{
+    iter,
     ops::{ControlFlow, Range},
     sync::Arc,
 };
@@ -26,6 +27,7 @@ pub struct ChannelChat {
     last_acknowledged_id: Option<u64>,
     next_pending_message_id: usize,
     first_loaded_message_id: Option<u64>,
+    threads: HashMap<u64, ChannelThread>,
     user_store: Entity<UserStore>,
     rpc: Arc<Client>,
     outgoing_messages_lock: Arc<Mutex<()>>,
@@ -50,6 +52,25 @@ pub struct ChannelMessage {
     pub mentions: Vec<(Range<usize>, UserId)>,
     pub reply_to_message_id: Option<u64>,
     pub edited_at: Option<OffsetDateTime>,
+    pub reactions: Vec<ChannelMessageReaction>,
+    pub thread_root_message_id: Option<u64>,
+    pub thread_reply_count: usize,
+    pub pinned: bool,
+}
+
+/// The users who reacted to a message with a given emoji.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelMessageReaction {
+    pub emoji: String,
+    pub user_ids: Vec<UserId>,
+}
+
+/// A message and the replies posted in the thread it started. Replies in a thread are kept out of
+/// the channel's main list of messages.
+#[derive(Clone, Debug)]
+pub struct ChannelThread {
+    pub root: ChannelMessage,
+    pub replies: Vec<ChannelMessage>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -90,6 +111,9 @@ pub enum ChannelChatEvent {
         channel_id: ChannelId,
         message_id: u64,
     },
+    ThreadUpdated {
+        root_message_id: u64,
+    },
 }
 
 impl EventEmitter<ChannelChatEvent> for ChannelChat {}
@@ -131,6 +155,7 @@ impl ChannelChat {
                 last_acknowledged_id: None,
                 rng: StdRng::from_entropy(),
                 first_loaded_message_id: None,
+                threads: Default::default(),
                 _subscription: subscription.set_entity(&cx.entity(), &cx.to_async()),
             }
         })?;
@@ -194,6 +219,10 @@ impl ChannelChat {
                     nonce,
                     reply_to_message_id: message.reply_to_message_id,
                     edited_at: None,
+                    reactions: Vec::new(),
+                    thread_root_message_id: None,
+                    thread_reply_count: 0,
+                    pinned: false,
                 },
                 &(),
             ),
@@ -212,6 +241,7 @@ impl ChannelChat {
                 nonce: Some(nonce.into()),
                 mentions: mentions_to_proto(&message.mentions),
                 reply_to_message_id: message.reply_to_message_id,
+                thread_root_message_id: None,
             });
             let response = request.await?;
             drop(outgoing_message_guard);
@@ -271,6 +301,137 @@ impl ChannelChat {
         }))
     }
 
+    /// Sends a reply in the thread started by the given message.
+    pub fn send_thread_message(
+        &mut self,
+        thread_root_message_id: u64,
+        message: MessageParams,
+        cx: &mut Context<Self>,
+    ) -> Result<Task<Result<u64>>> {
+        if message.text.trim().is_empty() {
+            Err(anyhow!("message body can't be empty"))?;
+        }
+
+        let nonce: u128 = self.rng.r#gen();
+        let request = self.rpc.request(proto::SendChannelMessage {
+            channel_id: self.channel_id.0,
+            body: message.text,
+            nonce: Some(nonce.into()),
+            mentions: mentions_to_proto(&message.mentions),
+            reply_to_message_id: message.reply_to_message_id,
+            thread_root_message_id: Some(thread_root_message_id),
+        });
+        let user_store = self.user_store.clone();
+        Ok(cx.spawn(async move |this, cx| {
+            let response = request.await?;
+            let message = response.message.ok_or_else(|| anyhow!("invalid message"))?;
+            let id = message.id;
+            let message = ChannelMessage::from_proto(message, &user_store, cx).await?;
+            this.update(cx, |this, cx| this.insert_thread_message(message, cx))?;
+            Ok(id)
+        }))
+    }
+
+    /// Loads the thread started by the given message, replacing any previously loaded copy.
+    pub fn load_thread(
+        &mut self,
+        thread_root_message_id: u64,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let request = self.rpc.request(proto::GetChannelThreadMessages {
+            channel_id: self.channel_id.0,
+            root_message_id: thread_root_message_id,
+        });
+        let user_store = self.user_store.clone();
+        cx.spawn(async move |this, cx| {
+            let response = request.await?;
+            let mut messages =
+                ChannelMessage::from_proto_vec(response.messages, &user_store, cx).await?;
+            let root_ix = messages
+                .iter()
+                .position(|message| message.id == ChannelMessageId::Saved(thread_root_message_id))
+                .ok_or_else(|| anyhow!("no such message"))?;
+            let root = messages.remove(root_ix);
+            this.update(cx, |this, cx| {
+                this.threads.insert(
+                    thread_root_message_id,
+                    ChannelThread {
+                        root,
+                        replies: messages,
+                    },
+                );
+                cx.emit(ChannelChatEvent::ThreadUpdated {
+                    root_message_id: thread_root_message_id,
+                });
+                cx.notify();
+            })
+        })
+    }
+
+    pub fn thread(&self, thread_root_message_id: u64) -> Option<&ChannelThread> {
+        self.threads.get(&thread_root_message_id)
+    }
+
+    /// Adds the current user's reaction to a message, or removes it if they've already reacted
+    /// with the same emoji. The message is updated once the server broadcasts the change.
+    pub fn toggle_reaction(
+        &mut self,
+        message_id: u64,
+        emoji: &str,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let user_id = self.rpc.user_id();
+        let has_reacted = self
+            .find_loaded_message(message_id)
+            .map_or(false, |message| {
+                message.reactions.iter().any(|reaction| {
+                    reaction.emoji == emoji
+                        && user_id.map_or(false, |user_id| reaction.user_ids.contains(&user_id))
+                })
+            });
+        let request = self.rpc.request(proto::ToggleChannelMessageReaction {
+            channel_id: self.channel_id.0,
+            message_id,
+            emoji: emoji.to_string(),
+            add: !has_reacted,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    pub fn set_message_pinned(
+        &mut self,
+        message_id: u64,
+        pinned: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let request = self.rpc.request(proto::SetChannelMessagePinned {
+            channel_id: self.channel_id.0,
+            message_id,
+            pinned,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    pub fn load_pinned_messages(
+        &self,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<ChannelMessage>>> {
+        let request = self.rpc.request(proto::GetPinnedChannelMessages {
+            channel_id: self.channel_id.0,
+        });
+        let user_store = self.user_store.clone();
+        cx.spawn(async move |_, cx| {
+            let response = request.await?;
+            ChannelMessage::from_proto_vec(response.messages, &user_store, cx).await
+        })
+    }
+
     pub fn load_more_messages(&mut self, cx: &mut Context<Self>) -> Option<Task<Option<()>>> {
         if self.loaded_all_messages {
             return None;
@@ -309,11 +470,19 @@ impl ChannelChat {
         self.first_loaded_message_id
     }
 
-    /// Load a message by its id, if it's already stored locally.
+    /// Load a message by its id, if it's already stored locally, either in the channel's list of
+    /// messages or in a loaded thread.
     pub fn find_loaded_message(&self, id: u64) -> Option<&ChannelMessage> {
-        self.messages.iter().find(|message| match message.id {
+        let is_message = |message: &&ChannelMessage| match message.id {
             ChannelMessageId::Saved(message_id) => message_id == id,
             ChannelMessageId::Pending(_) => false,
+        };
+        self.messages.iter().find(is_message).or_else(|| {
+            self.threads.values().find_map(|thread| {
+                iter::once(&thread.root)
+                    .chain(&thread.replies)
+                    .find(is_message)
+            })
         })
     }
 
@@ -467,6 +636,7 @@ impl ChannelChat {
                         mentions: mentions_to_proto(&pending_message.mentions),
                         nonce: Some(pending_message.nonce.into()),
                         reply_to_message_id: pending_message.reply_to_message_id,
+                        thread_root_message_id: None,
                     });
                     let response = request.await?;
                     let message = ChannelMessage::from_proto(
@@ -539,6 +709,10 @@ impl ChannelChat {
 
         let message = ChannelMessage::from_proto(message, &user_store, &mut cx).await?;
         this.update(&mut cx, |this, cx| {
+            if message.thread_root_message_id.is_some() {
+                this.insert_thread_message(message, cx);
+                return;
+            }
             this.insert_messages(SumTree::from_item(message, &()), cx);
             cx.emit(ChannelChatEvent::NewMessage {
                 channel_id: this.channel_id,
@@ -573,18 +747,62 @@ impl ChannelChat {
 
         let message = ChannelMessage::from_proto(message, &user_store, &mut cx).await?;
 
-        this.update(&mut cx, |this, cx| {
-            this.message_update(
-                message.id,
-                message.body,
-                message.mentions,
-                message.edited_at,
-                cx,
-            )
-        })?;
+        this.update(&mut cx, |this, cx| this.message_replaced(message, cx))?;
         Ok(())
     }
 
+    fn insert_thread_message(&mut self, message: ChannelMessage, cx: &mut Context<Self>) {
+        let Some(root_message_id) = message.thread_root_message_id else {
+            return;
+        };
+        if let Some(thread) = self.threads.get_mut(&root_message_id) {
+            if let Err(ix) = thread
+                .replies
+                .binary_search_by_key(&message.id, |reply| reply.id)
+            {
+                thread.replies.insert(ix, message);
+            }
+            cx.emit(ChannelChatEvent::ThreadUpdated { root_message_id });
+            cx.notify();
+        }
+    }
+
+    /// Replaces the locally stored copies of a message with a new version from the server.
+    fn message_replaced(&mut self, message: ChannelMessage, cx: &mut Context<Self>) {
+        for (root_message_id, thread) in &mut self.threads {
+            let existing = iter::once(&mut thread.root)
+                .chain(&mut thread.replies)
+                .find(|existing| existing.id == message.id);
+            if let Some(existing) = existing {
+                *existing = message.clone();
+                cx.emit(ChannelChatEvent::ThreadUpdated {
+                    root_message_id: *root_message_id,
+                });
+            }
+        }
+
+        let mut cursor = self.messages.cursor::<ChannelMessageId>(&());
+        let mut messages = cursor.slice(&message.id, Bias::Left, &());
+        let ix = messages.summary().count;
+        let message_id = message.id;
+        let found = cursor.item().map_or(false, |item| item.id == message_id);
+        if found {
+            messages.push(message, &());
+            cursor.next(&());
+        }
+        messages.append(cursor.suffix(&()), &());
+        drop(cursor);
+        self.messages = messages;
+
+        if found {
+            cx.emit(ChannelChatEvent::UpdateMessage {
+                message_ix: ix,
+                message_id,
+            });
+        }
+        cx.notify();
+    }
+
     fn insert_messages(&mut self, messages: SumTree<ChannelMessage>, cx: &mut Context<Self>) {
         if let Some((first_message, last_message)) = messages.first().zip(messages.last()) {
             let nonces = messages
@@ -645,6 +863,23 @@ impl ChannelChat {
     }
 
     fn message_removed(&mut self, id: u64, cx: &mut Context<Self>) {
+        if self.threads.remove(&id).is_some() {
+            cx.emit(ChannelChatEvent::ThreadUpdated {
+                root_message_id: id,
+            });
+        }
+        for (root_message_id, thread) in &mut self.threads {
+            let reply_count = thread.replies.len();
+            thread
+                .replies
+                .retain(|reply| reply.id != ChannelMessageId::Saved(id));
+            if thread.replies.len() != reply_count {
+                cx.emit(ChannelChatEvent::ThreadUpdated {
+                    root_message_id: *root_message_id,
+                });
+            }
+        }
+
         let mut cursor = self.messages.cursor::<ChannelMessageId>(&());
         let mut messages = cursor.slice(&ChannelMessageId::Saved(id), Bias::Left, &());
         if let Some(item) = cursor.item() {
@@ -759,6 +994,17 @@ impl ChannelMessage {
                 .into(),
             reply_to_message_id: message.reply_to_message_id,
             edited_at,
+            reactions: message
+                .reactions
+                .into_iter()
+                .map(|reaction| ChannelMessageReaction {
+                    emoji: reaction.emoji,
+                    user_ids: reaction.user_ids,
+                })
+                .collect(),
+            thread_root_message_id: message.thread_root_message_id,
+            thread_reply_count: message.thread_reply_count as usize,
+            pinned: message.pinned,
         })
     }
 
@@ -189,6 +189,10 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     nonce: Some(1.into()),
                     reply_to_message_id: None,
                     edited_at: None,
+                    reactions: Vec::new(),
+                    thread_root_message_id: None,
+                    thread_reply_count: 0,
+                    pinned: false,
                 },
                 proto::ChannelMessage {
                     id: 11,
@@ -199,6 +203,10 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     nonce: Some(2.into()),
                     reply_to_message_id: None,
                     edited_at: None,
+                    reactions: Vec::new(),
+                    thread_root_message_id: None,
+                    thread_reply_count: 0,
+                    pinned: false,
                 },
             ],
             done: false,
@@ -250,6 +258,10 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
             nonce: Some(3.into()),
             reply_to_message_id: None,
             edited_at: None,
+            reactions: Vec::new(),
+            thread_root_message_id: None,
+            thread_reply_count: 0,
+            pinned: false,
         }),
     });
 
@@ -307,6 +319,10 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     mentions: vec![],
                     reply_to_message_id: None,
                     edited_at: None,
+                    reactions: Vec::new(),
+                    thread_root_message_id: None,
+                    thread_reply_count: 0,
+                    pinned: false,
                 },
                 proto::ChannelMessage {
                     id: 9,
@@ -317,6 +333,10 @@ async fn test_channel_messages(cx: &mut TestAppContext) {
                     mentions: vec![],
                     reply_to_message_id: None,
                     edited_at: None,
+                    reactions: Vec::new(),
+                    thread_root_message_id: None,
+                    thread_reply_count: 0,
+                    pinned: false,
                 },
             ],
         },
@@ -13,6 +13,9 @@ pub enum CliRequest {
     Open {
         paths: Vec<String>,
         urls: Vec<String>,
+        /// Pairs of `[old, new]` paths to open in a diff view.
+        diff_paths: Vec<[String; 2]>,
+        merge: Option<MergePaths>,
         wait: bool,
         open_new_workspace: Option<bool>,
         env: Option<HashMap<String, String>>,
@@ -20,6 +23,18 @@ pub enum CliRequest {
     },
 }
 
+/// The files of a three-way merge, as passed by `git mergetool`.
+///
+/// When waiting, the CLI exits successfully only if `output` no longer contains conflict
+/// markers once it has been closed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergePaths {
+    pub base: String,
+    pub local: String,
+    pub remote: String,
+    pub output: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum CliResponse {
     Ping,
@@ -5,7 +5,7 @@
 
 use anyhow::{Context as _, Result};
 use clap::Parser;
-use cli::{CliRequest, CliResponse, IpcHandshake, ipc::IpcOneShotServer};
+use cli::{CliRequest, CliResponse, IpcHandshake, MergePaths, ipc::IpcOneShotServer};
 use collections::HashMap;
 use parking_lot::Mutex;
 use std::{
@@ -49,7 +49,11 @@ Examples:
     `zed path-to-your-project`
           Open your project in Zed
     `zed -n path-to-file `
-          Open file/folder in a new window",
+          Open file/folder in a new window
+    `zed --diff old-file new-file`
+          Compare two files
+    `zed --wait --merge base local remote -o output`
+          Resolve a three-way merge, e.g. as `git mergetool`",
     after_help = "To read from stdin, append '-', e.g. 'ps axf | zed -'"
 )]
 struct Args {
@@ -73,6 +77,18 @@ struct Args {
     ///
     /// Use `path:line:column` syntax to open a file at the given line and column.
     paths_with_position: Vec<String>,
+    /// Open a diff view comparing two files. May be given more than once.
+    #[arg(long, action = clap::ArgAction::Append, num_args = 2, value_names = ["OLD_PATH", "NEW_PATH"])]
+    diff: Vec<String>,
+    /// Resolve a three-way merge of LOCAL and REMOTE, which both descend from BASE, into the
+    /// file given with `--output`. With `--wait`, exits with a non-zero status unless all
+    /// conflicts were resolved.
+    #[arg(long, num_args = 3, value_names = ["BASE", "LOCAL", "REMOTE"], requires = "output", conflicts_with = "diff")]
+    merge: Option<Vec<String>>,
+    /// The file to write the result of `--merge` to. If it doesn't exist, it's created
+    /// containing the conflicts between LOCAL and REMOTE.
+    #[arg(short, long, value_name = "PATH", requires = "merge")]
+    output: Option<String>,
     /// Print Zed's version and the app path.
     #[arg(short, long)]
     version: bool,
@@ -126,6 +142,11 @@ fn parse_path_with_position(argument_str: &str) -> anyhow::Result<String> {
     Ok(canonicalized.to_string(|path| path.to_string_lossy().to_string()))
 }
 
+fn canonicalize_path(path: &str) -> anyhow::Result<String> {
+    let path = fs::canonicalize(path).with_context(|| format!("resolving path {path}"))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 fn main() -> Result<()> {
     // Exit flatpak sandbox if needed
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -255,6 +276,25 @@ fn main() -> Result<()> {
         }
     }
 
+    let diff_paths = args
+        .diff
+        .chunks(2)
+        .map(|pair| Ok([canonicalize_path(&pair[0])?, canonicalize_path(&pair[1])?]))
+        .collect::<Result<Vec<_>>>()?;
+
+    let merge = match (&args.merge, &args.output) {
+        (Some(merge), Some(output)) => Some(MergePaths {
+            base: canonicalize_path(&merge[0])?,
+            local: canonicalize_path(&merge[1])?,
+            remote: canonicalize_path(&merge[2])?,
+            output: std::path::absolute(output)
+                .with_context(|| format!("resolving path {output}"))?
+                .to_string_lossy()
+                .to_string(),
+        }),
+        _ => None,
+    };
+
     if let Some(_) = args.dev_server_token {
         return Err(anyhow::anyhow!(
             "Dev servers were removed in v0.157.x please upgrade to SSH remoting: https://zed.dev/docs/remote-development"
@@ -271,6 +311,8 @@ fn main() -> Result<()> {
             tx.send(CliRequest::Open {
                 paths,
                 urls,
+                diff_paths,
+                merge,
                 wait: args.wait,
                 open_new_workspace,
                 env,
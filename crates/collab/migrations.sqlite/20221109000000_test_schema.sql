@@ -288,13 +288,16 @@ CREATE TABLE IF NOT EXISTS "channel_messages" (
     "sent_at" TIMESTAMP,
     "edited_at" TIMESTAMP,
     "nonce" BLOB NOT NULL,
-    "reply_to_message_id" INTEGER DEFAULT NULL
+    "reply_to_message_id" INTEGER DEFAULT NULL,
+    "thread_root_message_id" INTEGER DEFAULT NULL REFERENCES channel_messages (id) ON DELETE CASCADE
 );
 
 CREATE INDEX "index_channel_messages_on_channel_id" ON "channel_messages" ("channel_id");
 
 CREATE UNIQUE INDEX "index_channel_messages_on_sender_id_nonce" ON "channel_messages" ("sender_id", "nonce");
 
+CREATE INDEX "index_channel_messages_on_thread_root_message_id" ON "channel_messages" ("thread_root_message_id");
+
 CREATE TABLE "channel_message_mentions" (
     "message_id" INTEGER NOT NULL REFERENCES channel_messages (id) ON DELETE CASCADE,
     "start_offset" INTEGER NOT NULL,
@@ -303,6 +306,23 @@ CREATE TABLE "channel_message_mentions" (
     PRIMARY KEY (message_id, start_offset)
 );
 
+CREATE TABLE "channel_message_reactions" (
+    "message_id" INTEGER NOT NULL REFERENCES channel_messages (id) ON DELETE CASCADE,
+    "user_id" INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    "emoji" TEXT NOT NULL,
+    "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (message_id, user_id, emoji)
+);
+
+CREATE TABLE "channel_message_pins" (
+    "message_id" INTEGER PRIMARY KEY REFERENCES channel_messages (id) ON DELETE CASCADE,
+    "channel_id" INTEGER NOT NULL REFERENCES channels (id) ON DELETE CASCADE,
+    "pinned_by" INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    "pinned_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX "index_channel_message_pins_on_channel_id" ON "channel_message_pins" ("channel_id");
+
 CREATE TABLE "channel_members" (
     "id" INTEGER PRIMARY KEY AUTOINCREMENT,
     "channel_id" INTEGER NOT NULL REFERENCES channels (id) ON DELETE CASCADE,
@@ -535,3 +555,33 @@ CREATE TABLE IF NOT EXISTS "breakpoints" (
 );
 
 CREATE INDEX "index_breakpoints_on_project_id" ON "breakpoints" ("project_id");
+
+CREATE TABLE IF NOT EXISTS "comment_threads" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "project_id" INTEGER REFERENCES projects (id) ON DELETE CASCADE,
+    "channel_id" INTEGER REFERENCES channels (id) ON DELETE CASCADE,
+    "host_user_id" INTEGER REFERENCES users (id) ON DELETE CASCADE,
+    "worktree_abs_path" TEXT NOT NULL,
+    "path" TEXT NOT NULL,
+    "start_anchor" BLOB NOT NULL,
+    "end_anchor" BLOB NOT NULL,
+    "anchored_text" TEXT NOT NULL,
+    "start_row" INTEGER NOT NULL,
+    "resolved" BOOLEAN NOT NULL DEFAULT FALSE,
+    "created_by" INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX "index_comment_threads_on_project_id" ON "comment_threads" ("project_id");
+
+CREATE INDEX "index_comment_threads_on_channel_id_and_host_user_id" ON "comment_threads" ("channel_id", "host_user_id");
+
+CREATE TABLE IF NOT EXISTS "comments" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "thread_id" INTEGER NOT NULL REFERENCES comment_threads (id) ON DELETE CASCADE,
+    "author_id" INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    "body" TEXT NOT NULL,
+    "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX "index_comments_on_thread_id" ON "comments" ("thread_id");
@@ -0,0 +1,21 @@
+alter table channel_messages
+    add column thread_root_message_id integer references channel_messages (id) on delete cascade;
+
+create index "index_channel_messages_on_thread_root_message_id" on channel_messages (thread_root_message_id);
+
+create table channel_message_reactions (
+    message_id integer not null references channel_messages (id) on delete cascade,
+    user_id integer not null references users (id) on delete cascade,
+    emoji text not null,
+    created_at timestamp without time zone not null default now(),
+    primary key (message_id, user_id, emoji)
+);
+
+create table channel_message_pins (
+    message_id integer primary key references channel_messages (id) on delete cascade,
+    channel_id integer not null references channels (id) on delete cascade,
+    pinned_by integer not null references users (id) on delete cascade,
+    pinned_at timestamp without time zone not null default now()
+);
+
+create index "index_channel_message_pins_on_channel_id" on channel_message_pins (channel_id);
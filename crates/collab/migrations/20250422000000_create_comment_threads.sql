@@ -0,0 +1,28 @@
+create table comment_threads (
+    id serial primary key,
+    project_id integer references projects (id) on delete cascade,
+    channel_id integer references channels (id) on delete cascade,
+    host_user_id integer references users (id) on delete cascade,
+    worktree_abs_path text not null,
+    path text not null,
+    start_anchor bytea not null,
+    end_anchor bytea not null,
+    anchored_text text not null,
+    start_row integer not null,
+    resolved boolean not null default false,
+    created_by integer not null references users (id) on delete cascade,
+    created_at timestamp without time zone not null default now()
+);
+
+create index "index_comment_threads_on_project_id" on comment_threads (project_id);
+create index "index_comment_threads_on_channel_id_and_host_user_id" on comment_threads (channel_id, host_user_id);
+
+create table comments (
+    id serial primary key,
+    thread_id integer not null references comment_threads (id) on delete cascade,
+    author_id integer not null references users (id) on delete cascade,
+    body text not null,
+    created_at timestamp without time zone not null default now()
+);
+
+create index "index_comments_on_thread_id" on comments (thread_id);
@@ -479,6 +479,12 @@ pub struct CreatedChannelMessage {
     pub notifications: NotificationBatch,
 }
 
+pub struct RemovedChannelMessage {
+    pub participant_connection_ids: Vec<ConnectionId>,
+    pub deleted_notification_ids: Vec<NotificationId>,
+    pub thread_root_message_id: Option<MessageId>,
+}
+
 pub struct UpdatedChannelMessage {
     pub message_id: MessageId,
     pub participant_connection_ids: Vec<ConnectionId>,
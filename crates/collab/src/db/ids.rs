@@ -78,6 +78,8 @@ id_type!(ChannelBufferCollaboratorId);
 id_type!(ChannelChatParticipantId);
 id_type!(ChannelId);
 id_type!(ChannelMemberId);
+id_type!(CommentId);
+id_type!(CommentThreadId);
 id_type!(ContactId);
 id_type!(ExtensionId);
 id_type!(FlagId);
@@ -0,0 +1,356 @@
+use super::*;
+use prost::Message;
+
+/// Where the comment threads of a shared project are stored.
+#[derive(Clone, Copy)]
+enum CommentScope {
+    Project(ProjectId),
+    /// Projects shared in a channel's room keep their threads in the channel, so that they can be
+    /// found again the next time their host shares them there.
+    Channel {
+        channel_id: ChannelId,
+        host_user_id: UserId,
+    },
+}
+
+impl CommentScope {
+    fn condition(&self) -> Condition {
+        match self {
+            CommentScope::Project(project_id) => {
+                Condition::all().add(comment_thread::Column::ProjectId.eq(*project_id))
+            }
+            CommentScope::Channel {
+                channel_id,
+                host_user_id,
+            } => Condition::all()
+                .add(comment_thread::Column::ChannelId.eq(*channel_id))
+                .add(comment_thread::Column::HostUserId.eq(*host_user_id)),
+        }
+    }
+}
+
+impl Database {
+    /// Returns the comment threads on the files of the given project's worktrees.
+    pub async fn get_comment_threads(
+        &self,
+        project_id: ProjectId,
+        connection_id: ConnectionId,
+    ) -> Result<Vec<proto::CommentThread>> {
+        self.transaction(|tx| async move {
+            let (project, _) = self
+                .access_project(project_id, connection_id, Capability::ReadOnly, &tx)
+                .await?;
+            let scope = self.comment_scope(&project, &tx).await?;
+            let worktree_ids = self.worktree_ids_by_abs_path(project_id, &tx).await?;
+            let threads = comment_thread::Entity::find()
+                .filter(scope.condition())
+                .filter(comment_thread::Column::WorktreeAbsPath.is_in(worktree_ids.keys().cloned()))
+                .order_by_asc(comment_thread::Column::Id)
+                .all(&*tx)
+                .await?;
+            self.comment_threads_to_proto(threads, &worktree_ids, &tx)
+                .await
+        })
+        .await
+    }
+
+    /// Starts a new comment thread on a range of a file in the given project.
+    pub async fn create_comment_thread(
+        &self,
+        request: &proto::CreateCommentThread,
+        connection_id: ConnectionId,
+        user_id: UserId,
+    ) -> Result<(Vec<ConnectionId>, proto::CommentThread)> {
+        let project_id = ProjectId::from_proto(request.project_id);
+        self.transaction(|tx| async move {
+            // Guests who can't edit the project can still review it.
+            let (project, _) = self
+                .access_project(project_id, connection_id, Capability::ReadOnly, &tx)
+                .await?;
+            let scope = self.comment_scope(&project, &tx).await?;
+            let worktree = worktree::Entity::find_by_id((request.worktree_id as i64, project_id))
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such worktree"))?;
+            let start = request
+                .start
+                .as_ref()
+                .ok_or_else(|| anyhow!("missing start anchor"))?;
+            let end = request
+                .end
+                .as_ref()
+                .ok_or_else(|| anyhow!("missing end anchor"))?;
+
+            let (project_id_column, channel_id_column, host_user_id_column) = match scope {
+                CommentScope::Project(project_id) => (Some(project_id), None, None),
+                CommentScope::Channel {
+                    channel_id,
+                    host_user_id,
+                } => (None, Some(channel_id), Some(host_user_id)),
+            };
+            let thread = comment_thread::ActiveModel {
+                id: ActiveValue::NotSet,
+                project_id: ActiveValue::Set(project_id_column),
+                channel_id: ActiveValue::Set(channel_id_column),
+                host_user_id: ActiveValue::Set(host_user_id_column),
+                worktree_abs_path: ActiveValue::Set(worktree.abs_path),
+                path: ActiveValue::Set(request.path.clone()),
+                start_anchor: ActiveValue::Set(start.encode_to_vec()),
+                end_anchor: ActiveValue::Set(end.encode_to_vec()),
+                anchored_text: ActiveValue::Set(request.anchored_text.clone()),
+                start_row: ActiveValue::Set(request.start_row as i32),
+                resolved: ActiveValue::Set(false),
+                created_by: ActiveValue::Set(user_id),
+                created_at: ActiveValue::NotSet,
+            }
+            .insert(&*tx)
+            .await?;
+            self.insert_comment(thread.id, user_id, &request.body, &tx)
+                .await?;
+
+            self.comment_thread_update(project_id, thread.id, connection_id, &tx)
+                .await
+        })
+        .await
+    }
+
+    /// Adds a comment to an existing thread.
+    pub async fn add_comment(
+        &self,
+        project_id: ProjectId,
+        thread_id: CommentThreadId,
+        body: &str,
+        connection_id: ConnectionId,
+        user_id: UserId,
+    ) -> Result<(Vec<ConnectionId>, proto::CommentThread)> {
+        self.transaction(|tx| async move {
+            self.access_comment_thread(project_id, thread_id, connection_id, &tx)
+                .await?;
+            self.insert_comment(thread_id, user_id, body, &tx).await?;
+            self.comment_thread_update(project_id, thread_id, connection_id, &tx)
+                .await
+        })
+        .await
+    }
+
+    /// Marks a comment thread as resolved, or reopens it.
+    pub async fn set_comment_thread_resolved(
+        &self,
+        project_id: ProjectId,
+        thread_id: CommentThreadId,
+        resolved: bool,
+        connection_id: ConnectionId,
+    ) -> Result<(Vec<ConnectionId>, proto::CommentThread)> {
+        self.transaction(|tx| async move {
+            let thread = self
+                .access_comment_thread(project_id, thread_id, connection_id, &tx)
+                .await?;
+            comment_thread::ActiveModel {
+                id: ActiveValue::Unchanged(thread.id),
+                resolved: ActiveValue::Set(resolved),
+                ..Default::default()
+            }
+            .update(&*tx)
+            .await?;
+            self.comment_thread_update(project_id, thread_id, connection_id, &tx)
+                .await
+        })
+        .await
+    }
+
+    /// Deletes one of the user's own comments. Returns the updated thread, or `None` if the thread
+    /// was removed because it has no comments left.
+    pub async fn delete_comment(
+        &self,
+        project_id: ProjectId,
+        thread_id: CommentThreadId,
+        comment_id: CommentId,
+        connection_id: ConnectionId,
+        user_id: UserId,
+    ) -> Result<(Vec<ConnectionId>, Option<proto::CommentThread>)> {
+        self.transaction(|tx| async move {
+            self.access_comment_thread(project_id, thread_id, connection_id, &tx)
+                .await?;
+            let result = comment::Entity::delete_many()
+                .filter(comment::Column::Id.eq(comment_id))
+                .filter(comment::Column::ThreadId.eq(thread_id))
+                .filter(comment::Column::AuthorId.eq(user_id))
+                .exec(&*tx)
+                .await?;
+            if result.rows_affected == 0 {
+                Err(anyhow!("no such comment"))?;
+            }
+
+            let remaining_comments = comment::Entity::find()
+                .filter(comment::Column::ThreadId.eq(thread_id))
+                .count(&*tx)
+                .await?;
+            if remaining_comments == 0 {
+                comment_thread::Entity::delete_by_id(thread_id)
+                    .exec(&*tx)
+                    .await?;
+                let connection_ids = self
+                    .internal_project_connection_ids(project_id, connection_id, false, &tx)
+                    .await?;
+                return Ok((connection_ids.into_iter().collect(), None));
+            }
+
+            let (connection_ids, thread) = self
+                .comment_thread_update(project_id, thread_id, connection_id, &tx)
+                .await?;
+            Ok((connection_ids, Some(thread)))
+        })
+        .await
+    }
+
+    async fn comment_scope(
+        &self,
+        project: &project::Model,
+        tx: &DatabaseTransaction,
+    ) -> Result<CommentScope> {
+        let channel_id = if let Some(room_id) = project.room_id {
+            room::Entity::find_by_id(room_id)
+                .one(tx)
+                .await?
+                .and_then(|room| room.channel_id)
+        } else {
+            None
+        };
+        Ok(match (channel_id, project.host_user_id) {
+            (Some(channel_id), Some(host_user_id)) => CommentScope::Channel {
+                channel_id,
+                host_user_id,
+            },
+            _ => CommentScope::Project(project.id),
+        })
+    }
+
+    /// Threads are keyed by the absolute path of their worktree on the host, which stays the
+    /// same when a project is shared again, unlike worktree ids.
+    async fn worktree_ids_by_abs_path(
+        &self,
+        project_id: ProjectId,
+        tx: &DatabaseTransaction,
+    ) -> Result<HashMap<String, u64>> {
+        let mut worktree_ids = HashMap::default();
+        for worktree in worktree::Entity::find()
+            .filter(worktree::Column::ProjectId.eq(project_id))
+            .order_by_asc(worktree::Column::Id)
+            .all(tx)
+            .await?
+        {
+            worktree_ids
+                .entry(worktree.abs_path)
+                .or_insert(worktree.id as u64);
+        }
+        Ok(worktree_ids)
+    }
+
+    /// Checks that the connection can comment in the given project, and that the thread belongs
+    /// to it.
+    async fn access_comment_thread(
+        &self,
+        project_id: ProjectId,
+        thread_id: CommentThreadId,
+        connection_id: ConnectionId,
+        tx: &DatabaseTransaction,
+    ) -> Result<comment_thread::Model> {
+        let (project, _) = self
+            .access_project(project_id, connection_id, Capability::ReadOnly, tx)
+            .await?;
+        let scope = self.comment_scope(&project, tx).await?;
+        Ok(comment_thread::Entity::find_by_id(thread_id)
+            .filter(scope.condition())
+            .one(tx)
+            .await?
+            .ok_or_else(|| anyhow!("no such comment thread"))?)
+    }
+
+    async fn insert_comment(
+        &self,
+        thread_id: CommentThreadId,
+        user_id: UserId,
+        body: &str,
+        tx: &DatabaseTransaction,
+    ) -> Result<()> {
+        comment::ActiveModel {
+            id: ActiveValue::NotSet,
+            thread_id: ActiveValue::Set(thread_id),
+            author_id: ActiveValue::Set(user_id),
+            body: ActiveValue::Set(body.to_string()),
+            created_at: ActiveValue::NotSet,
+        }
+        .insert(tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns a thread along with the connections that should be told about its changes.
+    async fn comment_thread_update(
+        &self,
+        project_id: ProjectId,
+        thread_id: CommentThreadId,
+        connection_id: ConnectionId,
+        tx: &DatabaseTransaction,
+    ) -> Result<(Vec<ConnectionId>, proto::CommentThread)> {
+        let thread = comment_thread::Entity::find_by_id(thread_id)
+            .one(tx)
+            .await?
+            .ok_or_else(|| anyhow!("no such comment thread"))?;
+        let worktree_ids = self.worktree_ids_by_abs_path(project_id, tx).await?;
+        let thread = self
+            .comment_threads_to_proto(vec![thread], &worktree_ids, tx)
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("comment thread is not in a shared worktree"))?;
+        let connection_ids = self
+            .internal_project_connection_ids(project_id, connection_id, false, tx)
+            .await?;
+        Ok((connection_ids.into_iter().collect(), thread))
+    }
+
+    async fn comment_threads_to_proto(
+        &self,
+        threads: Vec<comment_thread::Model>,
+        worktree_ids: &HashMap<String, u64>,
+        tx: &DatabaseTransaction,
+    ) -> Result<Vec<proto::CommentThread>> {
+        let mut comments_by_thread_id = HashMap::<CommentThreadId, Vec<proto::Comment>>::default();
+        let mut comments = comment::Entity::find()
+            .filter(comment::Column::ThreadId.is_in(threads.iter().map(|thread| thread.id)))
+            .order_by_asc(comment::Column::Id)
+            .stream(tx)
+            .await?;
+        while let Some(comment) = comments.next().await {
+            let comment = comment?;
+            comments_by_thread_id
+                .entry(comment.thread_id)
+                .or_default()
+                .push(proto::Comment {
+                    id: comment.id.to_proto(),
+                    author_id: comment.author_id.to_proto(),
+                    body: comment.body,
+                    created_at: comment.created_at.assume_utc().unix_timestamp() as u64,
+                });
+        }
+        drop(comments);
+
+        Ok(threads
+            .into_iter()
+            .filter_map(|thread| {
+                let worktree_id = *worktree_ids.get(&thread.worktree_abs_path)?;
+                Some(proto::CommentThread {
+                    id: thread.id.to_proto(),
+                    worktree_id,
+                    path: thread.path,
+                    start: proto::Anchor::decode(thread.start_anchor.as_slice()).ok(),
+                    end: proto::Anchor::decode(thread.end_anchor.as_slice()).ok(),
+                    anchored_text: thread.anchored_text,
+                    start_row: thread.start_row as u32,
+                    resolved: thread.resolved,
+                    comments: comments_by_thread_id.remove(&thread.id).unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+}
@@ -92,8 +92,9 @@ impl Database {
             self.check_user_is_channel_participant(&channel, user_id, &tx)
                 .await?;
 
-            let mut condition =
-                Condition::all().add(channel_message::Column::ChannelId.eq(channel_id));
+            let mut condition = Condition::all()
+                .add(channel_message::Column::ChannelId.eq(channel_id))
+                .add(channel_message::Column::ThreadRootMessageId.is_null());
 
             if let Some(before_message_id) = before_message_id {
                 condition = condition.add(channel_message::Column::Id.lt(before_message_id));
@@ -143,6 +144,62 @@ impl Database {
         .await
     }
 
+    /// Returns the root message of a thread, followed by all of its replies.
+    pub async fn get_channel_thread_messages(
+        &self,
+        channel_id: ChannelId,
+        user_id: UserId,
+        root_message_id: MessageId,
+    ) -> Result<Vec<proto::ChannelMessage>> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &tx).await?;
+            self.check_user_is_channel_participant(&channel, user_id, &tx)
+                .await?;
+
+            let rows = channel_message::Entity::find()
+                .filter(channel_message::Column::ChannelId.eq(channel_id))
+                .filter(
+                    Condition::any()
+                        .add(channel_message::Column::Id.eq(root_message_id))
+                        .add(channel_message::Column::ThreadRootMessageId.eq(root_message_id)),
+                )
+                .order_by_desc(channel_message::Column::Id)
+                .all(&*tx)
+                .await?;
+
+            self.load_channel_messages(rows, &tx).await
+        })
+        .await
+    }
+
+    /// Returns the pinned messages in the specified channel.
+    pub async fn get_pinned_channel_messages(
+        &self,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> Result<Vec<proto::ChannelMessage>> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &tx).await?;
+            self.check_user_is_channel_participant(&channel, user_id, &tx)
+                .await?;
+
+            let pinned_message_ids = channel_message_pin::Entity::find()
+                .filter(channel_message_pin::Column::ChannelId.eq(channel_id))
+                .all(&*tx)
+                .await?
+                .into_iter()
+                .map(|pin| pin.message_id);
+            let rows = channel_message::Entity::find()
+                .filter(channel_message::Column::Id.is_in(pinned_message_ids))
+                .order_by_desc(channel_message::Column::Id)
+                .all(&*tx)
+                .await?;
+
+            self.load_channel_messages(rows, &tx).await
+        })
+        .await
+    }
+
     async fn load_channel_messages(
         &self,
         rows: Vec<channel_message::Model>,
@@ -166,10 +223,18 @@ impl Database {
                     edited_at: row
                         .edited_at
                         .map(|t| t.assume_utc().unix_timestamp() as u64),
+                    reactions: Vec::new(),
+                    thread_root_message_id: row.thread_root_message_id.map(|id| id.to_proto()),
+                    thread_reply_count: 0,
+                    pinned: false,
                 }
             })
             .collect::<Vec<_>>();
         messages.reverse();
+        let message_ids = messages
+            .iter()
+            .map(|message| MessageId::from_proto(message.id))
+            .collect::<Vec<_>>();
 
         let mut mentions = channel_message_mention::Entity::find()
             .filter(channel_message_mention::Column::MessageId.is_in(messages.iter().map(|m| m.id)))
@@ -199,10 +264,95 @@ impl Database {
                 }
             }
         }
+        drop(mentions);
+
+        let message_ixs_by_id = messages
+            .iter()
+            .enumerate()
+            .map(|(ix, message)| (message.id, ix))
+            .collect::<HashMap<_, _>>();
+
+        let mut reactions = channel_message_reaction::Entity::find()
+            .filter(channel_message_reaction::Column::MessageId.is_in(message_ids.iter().copied()))
+            .order_by_asc(channel_message_reaction::Column::CreatedAt)
+            .stream(tx)
+            .await?;
+        while let Some(reaction) = reactions.next().await {
+            let reaction = reaction?;
+            let Some(ix) = message_ixs_by_id.get(&reaction.message_id.to_proto()) else {
+                continue;
+            };
+            let reactions = &mut messages[*ix].reactions;
+            let user_id = reaction.user_id.to_proto();
+            if let Some(existing) = reactions.iter_mut().find(|r| r.emoji == reaction.emoji) {
+                existing.user_ids.push(user_id);
+            } else {
+                reactions.push(proto::ChatReaction {
+                    emoji: reaction.emoji,
+                    user_ids: vec![user_id],
+                });
+            }
+        }
+        drop(reactions);
+
+        let pins = channel_message_pin::Entity::find()
+            .filter(channel_message_pin::Column::MessageId.is_in(message_ids.iter().copied()))
+            .all(tx)
+            .await?;
+        for pin in pins {
+            if let Some(ix) = message_ixs_by_id.get(&pin.message_id.to_proto()) {
+                messages[*ix].pinned = true;
+            }
+        }
+
+        #[derive(Debug, Clone, Copy, EnumIter, DeriveColumn)]
+        enum QueryThreadRootIds {
+            ThreadRootMessageId,
+        }
+
+        let thread_root_ids: Vec<MessageId> = channel_message::Entity::find()
+            .select_only()
+            .column(channel_message::Column::ThreadRootMessageId)
+            .filter(channel_message::Column::ThreadRootMessageId.is_in(message_ids))
+            .into_values::<_, QueryThreadRootIds>()
+            .all(tx)
+            .await?;
+        for root_id in thread_root_ids {
+            if let Some(ix) = message_ixs_by_id.get(&root_id.to_proto()) {
+                messages[*ix].thread_reply_count += 1;
+            }
+        }
 
         Ok(messages)
     }
 
+    async fn get_channel_message_internal(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        tx: &DatabaseTransaction,
+    ) -> Result<channel_message::Model> {
+        Ok(channel_message::Entity::find_by_id(message_id)
+            .filter(channel_message::Column::ChannelId.eq(channel_id))
+            .one(tx)
+            .await?
+            .ok_or_else(|| anyhow!("no such message"))?)
+    }
+
+    async fn channel_chat_connection_ids(
+        &self,
+        channel_id: ChannelId,
+        tx: &DatabaseTransaction,
+    ) -> Result<Vec<ConnectionId>> {
+        Ok(channel_chat_participant::Entity::find()
+            .filter(channel_chat_participant::Column::ChannelId.eq(channel_id))
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(|row| row.connection())
+            .collect())
+    }
+
     fn format_mentions_to_entities(
         &self,
         message_id: MessageId,
@@ -238,12 +388,22 @@ impl Database {
         timestamp: OffsetDateTime,
         nonce: u128,
         reply_to_message_id: Option<MessageId>,
+        thread_root_message_id: Option<MessageId>,
     ) -> Result<CreatedChannelMessage> {
         self.transaction(|tx| async move {
             let channel = self.get_channel_internal(channel_id, &tx).await?;
             self.check_user_is_channel_participant(&channel, user_id, &tx)
                 .await?;
 
+            if let Some(thread_root_message_id) = thread_root_message_id {
+                let thread_root = self
+                    .get_channel_message_internal(channel_id, thread_root_message_id, &tx)
+                    .await?;
+                if thread_root.thread_root_message_id.is_some() {
+                    Err(anyhow!("can't start a thread from a reply in a thread"))?;
+                }
+            }
+
             let mut rows = channel_chat_participant::Entity::find()
                 .filter(channel_chat_participant::Column::ChannelId.eq(channel_id))
                 .stream(&*tx)
@@ -278,6 +438,7 @@ impl Database {
                 id: ActiveValue::NotSet,
                 reply_to_message_id: ActiveValue::Set(reply_to_message_id),
                 edited_at: ActiveValue::NotSet,
+                thread_root_message_id: ActiveValue::Set(thread_root_message_id),
             })
             .on_conflict(
                 OnConflict::columns([
@@ -306,6 +467,25 @@ impl Database {
                             .await?;
                     }
 
+                    // Users who are mentioned in a reply are only notified of the mention.
+                    let mut replied_to_user_ids = HashSet::default();
+                    for replied_to_message_id in
+                        reply_to_message_id.into_iter().chain(thread_root_message_id)
+                    {
+                        let replied_to_message =
+                            channel_message::Entity::find_by_id(replied_to_message_id)
+                                .one(&*tx)
+                                .await?;
+                        if let Some(replied_to_message) = replied_to_message {
+                            let sender_id = replied_to_message.sender_id;
+                            if sender_id != user_id
+                                && !mentioned_user_ids.contains(&sender_id.to_proto())
+                            {
+                                replied_to_user_ids.insert(sender_id);
+                            }
+                        }
+                    }
+
                     for mentioned_user in mentioned_user_ids {
                         notifications.extend(
                             self.create_notification(
@@ -322,6 +502,24 @@ impl Database {
                         );
                     }
 
+                    for replied_to_user in replied_to_user_ids {
+                        notifications.extend(
+                            self.create_notification(
+                                replied_to_user,
+                                rpc::Notification::ChannelMessageReply {
+                                    message_id: message_id.to_proto(),
+                                    sender_id: user_id.to_proto(),
+                                    channel_id: channel_id.to_proto(),
+                                    thread_root_message_id: thread_root_message_id
+                                        .map(|id| id.to_proto()),
+                                },
+                                false,
+                                &tx,
+                            )
+                            .await?,
+                        );
+                    }
+
                     self.observe_channel_message_internal(channel_id, user_id, message_id, &tx)
                         .await?;
                 }
@@ -366,6 +564,19 @@ impl Database {
                 )
                 .await?,
             );
+            batch.extend(
+                self.mark_notification_as_read(
+                    user_id,
+                    &Notification::ChannelMessageReply {
+                        message_id: message_id.to_proto(),
+                        sender_id: Default::default(),
+                        channel_id: Default::default(),
+                        thread_root_message_id: Default::default(),
+                    },
+                    &tx,
+                )
+                .await?,
+            );
             Ok(batch)
         })
         .await
@@ -452,7 +663,8 @@ impl Database {
                     ) as row_number
                 FROM channel_messages
                 WHERE
-                    channel_id in ({values})
+                    channel_id in ({values}) AND
+                    thread_root_message_id IS NULL
             ) AS messages
             WHERE
                 row_number = 1
@@ -489,7 +701,7 @@ impl Database {
         channel_id: ChannelId,
         message_id: MessageId,
         user_id: UserId,
-    ) -> Result<(Vec<ConnectionId>, Vec<NotificationId>)> {
+    ) -> Result<RemovedChannelMessage> {
         self.transaction(|tx| async move {
             let mut rows = channel_chat_participant::Entity::find()
                 .filter(channel_chat_participant::Column::ChannelId.eq(channel_id))
@@ -511,6 +723,11 @@ impl Database {
                 Err(anyhow!("not a chat participant"))?;
             }
 
+            let thread_root_message_id = channel_message::Entity::find_by_id(message_id)
+                .one(&*tx)
+                .await?
+                .and_then(|message| message.thread_root_message_id);
+
             let result = channel_message::Entity::delete_by_id(message_id)
                 .filter(channel_message::Column::SenderId.eq(user_id))
                 .exec(&*tx)
@@ -534,12 +751,14 @@ impl Database {
                 }
             }
 
-            let notification_kind_id =
-                self.get_notification_kind_id_by_name("ChannelMessageMention");
+            let notification_kind_ids = ["ChannelMessageMention", "ChannelMessageReply"]
+                .into_iter()
+                .filter_map(|kind| self.get_notification_kind_id_by_name(kind))
+                .collect::<Vec<_>>();
 
             let existing_notifications = notification::Entity::find()
                 .filter(notification::Column::EntityId.eq(message_id))
-                .filter(notification::Column::Kind.eq(notification_kind_id))
+                .filter(notification::Column::Kind.is_in(notification_kind_ids.iter().copied()))
                 .select_column(notification::Column::Id)
                 .all(&*tx)
                 .await?;
@@ -549,14 +768,18 @@ impl Database {
                 .map(|notification| notification.id)
                 .collect();
 
-            // remove all the mention notifications for this message
+            // remove all the mention and reply notifications for this message
             notification::Entity::delete_many()
                 .filter(notification::Column::EntityId.eq(message_id))
-                .filter(notification::Column::Kind.eq(notification_kind_id))
+                .filter(notification::Column::Kind.is_in(notification_kind_ids))
                 .exec(&*tx)
                 .await?;
 
-            Ok((participant_connection_ids, existing_notification_ids))
+            Ok(RemovedChannelMessage {
+                participant_connection_ids,
+                deleted_notification_ids: existing_notification_ids,
+                thread_root_message_id,
+            })
         })
         .await
     }
@@ -614,6 +837,9 @@ impl Database {
                 body: ActiveValue::Set(body.to_string()),
                 edited_at: ActiveValue::Set(Some(edited_at)),
                 reply_to_message_id: ActiveValue::Unchanged(channel_message.reply_to_message_id),
+                thread_root_message_id: ActiveValue::Unchanged(
+                    channel_message.thread_root_message_id,
+                ),
                 id: ActiveValue::Unchanged(message_id),
                 channel_id: ActiveValue::Unchanged(channel_id),
                 sender_id: ActiveValue::Unchanged(user_id),
@@ -721,4 +947,110 @@ impl Database {
         })
         .await
     }
+
+    /// Adds or removes a user's reaction to a channel message.
+    ///
+    /// Returns the connections participating in the channel's chat, along with the updated message.
+    pub async fn toggle_channel_message_reaction(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        user_id: UserId,
+        emoji: &str,
+        add: bool,
+    ) -> Result<(Vec<ConnectionId>, proto::ChannelMessage)> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &tx).await?;
+            self.check_user_is_channel_participant(&channel, user_id, &tx)
+                .await?;
+            let message = self
+                .get_channel_message_internal(channel_id, message_id, &tx)
+                .await?;
+
+            if add {
+                channel_message_reaction::Entity::insert(channel_message_reaction::ActiveModel {
+                    message_id: ActiveValue::Set(message_id),
+                    user_id: ActiveValue::Set(user_id),
+                    emoji: ActiveValue::Set(emoji.to_string()),
+                    created_at: ActiveValue::NotSet,
+                })
+                .on_conflict(
+                    OnConflict::columns([
+                        channel_message_reaction::Column::MessageId,
+                        channel_message_reaction::Column::UserId,
+                        channel_message_reaction::Column::Emoji,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(&*tx)
+                .await?;
+            } else {
+                channel_message_reaction::Entity::delete_many()
+                    .filter(channel_message_reaction::Column::MessageId.eq(message_id))
+                    .filter(channel_message_reaction::Column::UserId.eq(user_id))
+                    .filter(channel_message_reaction::Column::Emoji.eq(emoji))
+                    .exec(&*tx)
+                    .await?;
+            }
+
+            let connection_ids = self.channel_chat_connection_ids(channel_id, &tx).await?;
+            let message = self
+                .load_channel_messages(vec![message], &tx)
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("no such message"))?;
+            Ok((connection_ids, message))
+        })
+        .await
+    }
+
+    /// Pins or unpins a channel message. Only channel members can pin messages.
+    ///
+    /// Returns the connections participating in the channel's chat, along with the updated message.
+    pub async fn set_channel_message_pinned(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        user_id: UserId,
+        pinned: bool,
+    ) -> Result<(Vec<ConnectionId>, proto::ChannelMessage)> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &tx).await?;
+            self.check_user_is_channel_member(&channel, user_id, &tx)
+                .await?;
+            let message = self
+                .get_channel_message_internal(channel_id, message_id, &tx)
+                .await?;
+
+            if pinned {
+                channel_message_pin::Entity::insert(channel_message_pin::ActiveModel {
+                    message_id: ActiveValue::Set(message_id),
+                    channel_id: ActiveValue::Set(channel_id),
+                    pinned_by: ActiveValue::Set(user_id),
+                    pinned_at: ActiveValue::NotSet,
+                })
+                .on_conflict(
+                    OnConflict::column(channel_message_pin::Column::MessageId)
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .exec_without_returning(&*tx)
+                .await?;
+            } else {
+                channel_message_pin::Entity::delete_by_id(message_id)
+                    .exec(&*tx)
+                    .await?;
+            }
+
+            let connection_ids = self.channel_chat_connection_ids(channel_id, &tx).await?;
+            let message = self
+                .load_channel_messages(vec![message], &tx)
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("no such message"))?;
+            Ok((connection_ids, message))
+        })
+        .await
+    }
 }
@@ -1261,7 +1261,7 @@ impl Database {
         .await
     }
 
-    async fn internal_project_connection_ids(
+    pub(in crate::db) async fn internal_project_connection_ids(
         &self,
         project_id: ProjectId,
         connection_id: ConnectionId,
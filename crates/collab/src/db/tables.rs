@@ -11,6 +11,10 @@ pub mod channel_chat_participant;
 pub mod channel_member;
 pub mod channel_message;
 pub mod channel_message_mention;
+pub mod channel_message_pin;
+pub mod channel_message_reaction;
+pub mod comment;
+pub mod comment_thread;
 pub mod contact;
 pub mod contributor;
 pub mod embedding;
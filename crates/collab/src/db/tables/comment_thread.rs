@@ -0,0 +1,68 @@
+use crate::db::{ChannelId, CommentThreadId, ProjectId, UserId};
+use sea_orm::entity::prelude::*;
+use time::PrimitiveDateTime;
+
+/// A thread of comments on a range of a file in a shared project.
+///
+/// Threads on projects shared in a channel belong to the channel and the project's host rather
+/// than the project, so that they're still around the next time the host shares it there.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "comment_threads")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: CommentThreadId,
+    pub project_id: Option<ProjectId>,
+    pub channel_id: Option<ChannelId>,
+    /// The host of the project, for threads that belong to a channel.
+    pub host_user_id: Option<UserId>,
+    /// The worktree's absolute path on the host.
+    pub worktree_abs_path: String,
+    pub path: String,
+    /// An encoded `proto::Anchor`.
+    pub start_anchor: Vec<u8>,
+    /// An encoded `proto::Anchor`.
+    pub end_anchor: Vec<u8>,
+    pub anchored_text: String,
+    pub start_row: i32,
+    pub resolved: bool,
+    pub created_by: UserId,
+    pub created_at: PrimitiveDateTime,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::channel::Entity",
+        from = "Column::ChannelId",
+        to = "super::channel::Column::Id"
+    )]
+    Channel,
+    #[sea_orm(has_many = "super::comment::Entity")]
+    Comments,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::channel::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Channel.def()
+    }
+}
+
+impl Related<super::comment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Comments.def()
+    }
+}
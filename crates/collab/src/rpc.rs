@@ -5,10 +5,11 @@ use crate::llm::LlmTokenClaims;
 use crate::{
     AppState, Config, Error, RateLimit, Result, auth,
     db::{
-        self, BufferId, Capability, Channel, ChannelId, ChannelRole, ChannelsForUser,
-        CreatedChannelMessage, Database, InviteMemberResult, MembershipUpdated, MessageId,
-        NotificationId, Project, ProjectId, RejoinedProject, RemoveChannelMemberResult, ReplicaId,
-        RespondToChannelInvite, RoomId, ServerId, UpdatedChannelMessage, User, UserId,
+        self, BufferId, Capability, Channel, ChannelId, ChannelRole, ChannelsForUser, CommentId,
+        CommentThreadId, CreatedChannelMessage, Database, InviteMemberResult, MembershipUpdated,
+        MessageId, NotificationId, Project, ProjectId, RejoinedProject, RemoveChannelMemberResult,
+        RemovedChannelMessage, ReplicaId, RespondToChannelInvite, RoomId, ServerId,
+        UpdatedChannelMessage, User, UserId,
     },
     executor::Executor,
 };
@@ -84,6 +85,8 @@ pub const CLEANUP_TIMEOUT: Duration = Duration::from_secs(15);
 
 const MESSAGE_COUNT_PER_PAGE: usize = 100;
 const MAX_MESSAGE_LEN: usize = 1024;
+const MAX_REACTION_LEN: usize = 32;
+const MAX_COMMENT_LEN: usize = 8192;
 const NOTIFICATION_COUNT_PER_PAGE: usize = 50;
 
 type MessageHandler =
@@ -298,6 +301,11 @@ impl Server {
             .add_message_handler(update_language_server)
             .add_message_handler(update_diagnostic_summary)
             .add_message_handler(update_worktree_settings)
+            .add_request_handler(get_comment_threads)
+            .add_request_handler(create_comment_thread)
+            .add_request_handler(add_comment)
+            .add_request_handler(delete_comment)
+            .add_request_handler(set_comment_thread_resolved)
             .add_request_handler(forward_read_only_project_request::<proto::GetHover>)
             .add_request_handler(forward_read_only_project_request::<proto::GetDefinition>)
             .add_request_handler(forward_read_only_project_request::<proto::GetTypeDefinition>)
@@ -394,6 +402,10 @@ impl Server {
             .add_request_handler(update_channel_message)
             .add_request_handler(get_channel_messages)
             .add_request_handler(get_channel_messages_by_id)
+            .add_request_handler(get_channel_thread_messages)
+            .add_request_handler(get_pinned_channel_messages)
+            .add_request_handler(toggle_channel_message_reaction)
+            .add_request_handler(set_channel_message_pinned)
             .add_request_handler(get_notifications)
             .add_request_handler(mark_notification_as_read)
             .add_request_handler(move_channel)
@@ -416,6 +428,7 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::GetRemotes>)
             .add_request_handler(forward_read_only_project_request::<proto::GitShow>)
             .add_request_handler(forward_read_only_project_request::<proto::LoadCommitDiff>)
+            .add_request_handler(forward_read_only_project_request::<proto::LoadMergeStages>)
             .add_request_handler(forward_read_only_project_request::<proto::GitReset>)
             .add_request_handler(forward_read_only_project_request::<proto::GitCheckoutFiles>)
             .add_request_handler(forward_mutating_project_request::<proto::SetIndexText>)
@@ -2137,6 +2150,149 @@ async fn update_worktree_settings(
     Ok(())
 }
 
+/// Load the comment threads on files in a shared project.
+async fn get_comment_threads(
+    request: proto::GetCommentThreads,
+    response: Response<proto::GetCommentThreads>,
+    session: Session,
+) -> Result<()> {
+    let threads = session
+        .db()
+        .await
+        .get_comment_threads(
+            ProjectId::from_proto(request.project_id),
+            session.connection_id,
+        )
+        .await?;
+    response.send(proto::GetCommentThreadsResponse { threads })?;
+    Ok(())
+}
+
+/// Start a comment thread on a range of a file in a shared project.
+async fn create_comment_thread(
+    mut request: proto::CreateCommentThread,
+    response: Response<proto::CreateCommentThread>,
+    session: Session,
+) -> Result<()> {
+    request.body = validate_comment_body(&request.body)?;
+    let (connection_ids, thread) = session
+        .db()
+        .await
+        .create_comment_thread(&request, session.connection_id, session.user_id())
+        .await?;
+    response.send(proto::Ack {})?;
+    broadcast_comment_thread_update(&session, request.project_id, connection_ids, thread);
+    Ok(())
+}
+
+/// Reply to a comment thread.
+async fn add_comment(
+    request: proto::AddComment,
+    response: Response<proto::AddComment>,
+    session: Session,
+) -> Result<()> {
+    let body = validate_comment_body(&request.body)?;
+    let (connection_ids, thread) = session
+        .db()
+        .await
+        .add_comment(
+            ProjectId::from_proto(request.project_id),
+            CommentThreadId::from_proto(request.thread_id),
+            &body,
+            session.connection_id,
+            session.user_id(),
+        )
+        .await?;
+    response.send(proto::Ack {})?;
+    broadcast_comment_thread_update(&session, request.project_id, connection_ids, thread);
+    Ok(())
+}
+
+/// Delete one of your comments, removing its thread if it was the last one.
+async fn delete_comment(
+    request: proto::DeleteComment,
+    response: Response<proto::DeleteComment>,
+    session: Session,
+) -> Result<()> {
+    let (connection_ids, thread) = session
+        .db()
+        .await
+        .delete_comment(
+            ProjectId::from_proto(request.project_id),
+            CommentThreadId::from_proto(request.thread_id),
+            CommentId::from_proto(request.comment_id),
+            session.connection_id,
+            session.user_id(),
+        )
+        .await?;
+    response.send(proto::Ack {})?;
+    if let Some(thread) = thread {
+        broadcast_comment_thread_update(&session, request.project_id, connection_ids, thread);
+    } else {
+        broadcast(None, connection_ids, |connection_id| {
+            session.peer.send(
+                connection_id,
+                proto::RemoveCommentThread {
+                    project_id: request.project_id,
+                    thread_id: request.thread_id,
+                },
+            )
+        });
+    }
+    Ok(())
+}
+
+/// Resolve or reopen a comment thread.
+async fn set_comment_thread_resolved(
+    request: proto::SetCommentThreadResolved,
+    response: Response<proto::SetCommentThreadResolved>,
+    session: Session,
+) -> Result<()> {
+    let (connection_ids, thread) = session
+        .db()
+        .await
+        .set_comment_thread_resolved(
+            ProjectId::from_proto(request.project_id),
+            CommentThreadId::from_proto(request.thread_id),
+            request.resolved,
+            session.connection_id,
+        )
+        .await?;
+    response.send(proto::Ack {})?;
+    broadcast_comment_thread_update(&session, request.project_id, connection_ids, thread);
+    Ok(())
+}
+
+fn validate_comment_body(body: &str) -> Result<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(anyhow!("comment can't be blank"))?;
+    }
+    if body.len() > MAX_COMMENT_LEN {
+        return Err(anyhow!("comment is too long"))?;
+    }
+    Ok(body.to_string())
+}
+
+/// Send the new state of a comment thread to everyone in the project, including the connection
+/// that changed it.
+fn broadcast_comment_thread_update(
+    session: &Session,
+    project_id: u64,
+    connection_ids: Vec<ConnectionId>,
+    thread: proto::CommentThread,
+) {
+    broadcast(None, connection_ids, |connection_id| {
+        session.peer.send(
+            connection_id,
+            proto::UpdateCommentThread {
+                project_id,
+                thread: Some(thread.clone()),
+            },
+        )
+    });
+}
+
 /// Notify other participants that a language server has started.
 async fn start_language_server(
     request: proto::StartLanguageServer,
@@ -3463,6 +3619,7 @@ async fn send_channel_message(
         .ok_or_else(|| anyhow!("nonce can't be blank"))?;
 
     let channel_id = ChannelId::from_proto(request.channel_id);
+    let thread_root_message_id = request.thread_root_message_id.map(MessageId::from_proto);
     let CreatedChannelMessage {
         message_id,
         participant_connection_ids,
@@ -3478,6 +3635,7 @@ async fn send_channel_message(
             timestamp,
             nonce.clone().into(),
             request.reply_to_message_id.map(MessageId::from_proto),
+            thread_root_message_id,
         )
         .await?;
 
@@ -3490,6 +3648,10 @@ async fn send_channel_message(
         nonce: Some(nonce),
         reply_to_message_id: request.reply_to_message_id,
         edited_at: None,
+        reactions: Vec::new(),
+        thread_root_message_id: request.thread_root_message_id,
+        thread_reply_count: 0,
+        pinned: false,
     };
     broadcast(
         Some(session.connection_id),
@@ -3508,6 +3670,30 @@ async fn send_channel_message(
         message: Some(message),
     })?;
 
+    // Replies in a thread only update the thread's root message, and don't count as unread
+    // messages in the channel.
+    if let Some(thread_root_message_id) = thread_root_message_id {
+        let thread_root = session
+            .db()
+            .await
+            .get_channel_messages_by_id(session.user_id(), &[thread_root_message_id])
+            .await?;
+        for thread_root in thread_root {
+            broadcast_channel_message_update(
+                &session,
+                channel_id,
+                participant_connection_ids.iter().copied(),
+                thread_root,
+            );
+        }
+        send_notifications(
+            &*session.connection_pool().await,
+            &session.peer,
+            notifications,
+        );
+        return Ok(());
+    }
+
     let pool = &*session.connection_pool().await;
     let non_participants =
         pool.channel_connection_ids(channel_id)
@@ -3543,12 +3729,32 @@ async fn remove_channel_message(
 ) -> Result<()> {
     let channel_id = ChannelId::from_proto(request.channel_id);
     let message_id = MessageId::from_proto(request.message_id);
-    let (connection_ids, existing_notification_ids) = session
+    let RemovedChannelMessage {
+        participant_connection_ids: connection_ids,
+        deleted_notification_ids: existing_notification_ids,
+        thread_root_message_id,
+    } = session
         .db()
         .await
         .remove_channel_message(channel_id, message_id, session.user_id())
         .await?;
 
+    if let Some(thread_root_message_id) = thread_root_message_id {
+        let thread_root = session
+            .db()
+            .await
+            .get_channel_messages_by_id(session.user_id(), &[thread_root_message_id])
+            .await?;
+        for thread_root in thread_root {
+            broadcast_channel_message_update(
+                &session,
+                channel_id,
+                connection_ids.iter().copied(),
+                thread_root,
+            );
+        }
+    }
+
     broadcast(
         Some(session.connection_id),
         connection_ids,
@@ -3583,10 +3789,9 @@ async fn update_channel_message(
         message_id,
         participant_connection_ids,
         notifications,
-        reply_to_message_id,
-        timestamp,
         deleted_mention_notification_ids,
         updated_mention_notifications,
+        ..
     } = session
         .db()
         .await
@@ -3600,21 +3805,19 @@ async fn update_channel_message(
         )
         .await?;
 
-    let nonce = request
+    request
         .nonce
-        .clone()
+        .as_ref()
         .ok_or_else(|| anyhow!("nonce can't be blank"))?;
 
-    let message = proto::ChannelMessage {
-        sender_id: session.user_id().to_proto(),
-        id: message_id.to_proto(),
-        body: request.body.clone(),
-        mentions: request.mentions.clone(),
-        timestamp: timestamp.assume_utc().unix_timestamp() as u64,
-        nonce: Some(nonce),
-        reply_to_message_id: reply_to_message_id.map(|id| id.to_proto()),
-        edited_at: Some(updated_at.unix_timestamp() as u64),
-    };
+    // Reload the message so that its reactions, pin and thread are sent along with the edit.
+    let message = session
+        .db()
+        .await
+        .get_channel_messages_by_id(session.user_id(), &[message_id])
+        .await?
+        .pop()
+        .ok_or_else(|| anyhow!("no such message"))?;
 
     response.send(proto::Ack {})?;
 
@@ -3658,6 +3861,75 @@ async fn update_channel_message(
     Ok(())
 }
 
+/// Add or remove the current user's reaction to a channel message
+async fn toggle_channel_message_reaction(
+    request: proto::ToggleChannelMessageReaction,
+    response: Response<proto::ToggleChannelMessageReaction>,
+    session: Session,
+) -> Result<()> {
+    let emoji = request.emoji.trim();
+    if emoji.is_empty() || emoji.len() > MAX_REACTION_LEN {
+        return Err(anyhow!("invalid reaction"))?;
+    }
+
+    let channel_id = ChannelId::from_proto(request.channel_id);
+    let (connection_ids, message) = session
+        .db()
+        .await
+        .toggle_channel_message_reaction(
+            channel_id,
+            MessageId::from_proto(request.message_id),
+            session.user_id(),
+            emoji,
+            request.add,
+        )
+        .await?;
+    response.send(proto::Ack {})?;
+    broadcast_channel_message_update(&session, channel_id, connection_ids, message);
+    Ok(())
+}
+
+/// Pin or unpin a channel message
+async fn set_channel_message_pinned(
+    request: proto::SetChannelMessagePinned,
+    response: Response<proto::SetChannelMessagePinned>,
+    session: Session,
+) -> Result<()> {
+    let channel_id = ChannelId::from_proto(request.channel_id);
+    let (connection_ids, message) = session
+        .db()
+        .await
+        .set_channel_message_pinned(
+            channel_id,
+            MessageId::from_proto(request.message_id),
+            session.user_id(),
+            request.pinned,
+        )
+        .await?;
+    response.send(proto::Ack {})?;
+    broadcast_channel_message_update(&session, channel_id, connection_ids, message);
+    Ok(())
+}
+
+/// Send the new state of a channel message to everyone in the channel's chat, including the
+/// connection that changed it.
+fn broadcast_channel_message_update(
+    session: &Session,
+    channel_id: ChannelId,
+    connection_ids: impl IntoIterator<Item = ConnectionId>,
+    message: proto::ChannelMessage,
+) {
+    broadcast(None, connection_ids, |connection| {
+        session.peer.send(
+            connection,
+            proto::ChannelMessageUpdate {
+                channel_id: channel_id.to_proto(),
+                message: Some(message.clone()),
+            },
+        )
+    });
+}
+
 /// Mark a channel message as read
 async fn acknowledge_channel_message(
     request: proto::AckChannelMessage,
@@ -4022,6 +4294,46 @@ async fn get_channel_messages_by_id(
     Ok(())
 }
 
+/// Retrieve the root message and replies of a thread in a channel's chat
+async fn get_channel_thread_messages(
+    request: proto::GetChannelThreadMessages,
+    response: Response<proto::GetChannelThreadMessages>,
+    session: Session,
+) -> Result<()> {
+    let messages = session
+        .db()
+        .await
+        .get_channel_thread_messages(
+            ChannelId::from_proto(request.channel_id),
+            session.user_id(),
+            MessageId::from_proto(request.root_message_id),
+        )
+        .await?;
+    response.send(proto::GetChannelMessagesResponse {
+        done: true,
+        messages,
+    })?;
+    Ok(())
+}
+
+/// Retrieve the pinned messages in a channel's chat
+async fn get_pinned_channel_messages(
+    request: proto::GetPinnedChannelMessages,
+    response: Response<proto::GetPinnedChannelMessages>,
+    session: Session,
+) -> Result<()> {
+    let messages = session
+        .db()
+        .await
+        .get_pinned_channel_messages(ChannelId::from_proto(request.channel_id), session.user_id())
+        .await?;
+    response.send(proto::GetChannelMessagesResponse {
+        done: true,
+        messages,
+    })?;
+    Ok(())
+}
+
 /// Retrieve the current users notifications
 async fn get_notifications(
     request: proto::GetNotifications,
@@ -3,7 +3,10 @@ use call::ActiveCall;
 use chrono::Utc;
 use editor::Editor;
 use gpui::{BackgroundExecutor, TestAppContext};
+use language::Point;
 use rpc::proto;
+use serde_json::json;
+use util::path;
 
 #[gpui::test]
 async fn test_channel_guests(
@@ -63,6 +66,135 @@ async fn test_channel_guests(
     assert!(room_b.read_with(cx_b, |room, _| room.is_muted()));
 }
 
+#[gpui::test]
+async fn test_channel_guest_comments(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    // Client A shares a project in the channel
+    let project_a = client_a.build_test_project(cx_a).await;
+    active_call_a
+        .update(cx_a, |call, cx| call.join_channel(channel_id, cx))
+        .await
+        .unwrap();
+    active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    cx_a.executor().run_until_parked();
+
+    // Client B joins channel A as a guest, who can't edit the project but can comment on it.
+    cx_b.update(|cx| workspace::join_channel(channel_id, client_b.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    let active_call_b = cx_b.read(ActiveCall::global);
+    let project_b =
+        active_call_b.read_with(cx_b, |call, _| call.location().unwrap().upgrade().unwrap());
+    assert!(project_b.read_with(cx_b, |project, cx| project.is_read_only(cx)));
+    let worktree_id = project_b.read_with(cx_b, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let buffer_b = project_b
+        .update(cx_b, |project, cx| {
+            project.open_buffer((worktree_id, "1.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let range = buffer_b.read_with(cx_b, |buffer, _| {
+        buffer.anchor_before(Point::new(1, 0))..buffer.anchor_after(Point::new(1, 3))
+    });
+    let comment_store_b = project_b.read_with(cx_b, |project, _| project.comment_store());
+    comment_store_b
+        .update(cx_b, |store, cx| {
+            store.create_thread(&buffer_b, range, "Why twice?".into(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let thread_id = comment_store_b.update(cx_b, |store, cx| {
+        store.threads_for_buffer(&buffer_b, cx)[0].id
+    });
+    comment_store_b
+        .update(cx_b, |store, cx| {
+            store.add_comment(thread_id, "Or three times?".into(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    // A project with the same root name from another directory doesn't show the thread.
+    client_a
+        .fs()
+        .insert_tree(path!("/other/a"), json!({ "1.txt": "one\none\none" }))
+        .await;
+    let (other_project_a, other_worktree_id) =
+        client_a.build_local_project(path!("/other/a"), cx_a).await;
+    active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(other_project_a.clone(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let other_buffer_a = other_project_a
+        .update(cx_a, |project, cx| {
+            project.open_buffer((other_worktree_id, "1.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let other_comment_store_a =
+        other_project_a.read_with(cx_a, |project, _| project.comment_store());
+    other_comment_store_a.update(cx_a, |store, cx| {
+        assert!(store.threads_for_buffer(&other_buffer_a, cx).is_empty())
+    });
+
+    // The thread is still there when the project is shared again.
+    project_a
+        .update(cx_a, |project, cx| project.unshare(cx))
+        .unwrap();
+    active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    let buffer_a = project_a
+        .update(cx_a, |project, cx| {
+            let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+            project.open_buffer((worktree_id, "1.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let comment_store_a = project_a.read_with(cx_a, |project, _| project.comment_store());
+    comment_store_a.update(cx_a, |store, cx| {
+        let threads = store.threads_for_buffer(&buffer_a, cx);
+        assert_eq!(threads.len(), 1);
+        assert_eq!(
+            threads[0]
+                .comments
+                .iter()
+                .map(|comment| comment.body.as_str())
+                .collect::<Vec<_>>(),
+            ["Why twice?", "Or three times?"]
+        );
+        assert_eq!(
+            threads[0].comments[0].author_id,
+            client_b.user_id().unwrap()
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_channel_guest_promotion(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
     let mut server = TestServer::start(cx_a.executor()).await;
@@ -498,6 +498,162 @@ async fn test_chat_replies(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext)
     });
 }
 
+#[gpui::test]
+async fn test_chat_threads_reactions_and_pins(
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(cx_a.executor()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+
+    let channel_id = server
+        .make_channel(
+            "the-channel",
+            None,
+            (&client_a, cx_a),
+            &mut [(&client_b, cx_b)],
+        )
+        .await;
+
+    let channel_chat_a = client_a
+        .channel_store()
+        .update(cx_a, |store, cx| store.open_channel_chat(channel_id, cx))
+        .await
+        .unwrap();
+    let channel_chat_b = client_b
+        .channel_store()
+        .update(cx_b, |store, cx| store.open_channel_chat(channel_id, cx))
+        .await
+        .unwrap();
+
+    let root_id = channel_chat_a
+        .update(cx_a, |c, cx| c.send_message("root".into(), cx).unwrap())
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+
+    // Client A opens the thread before client B replies in it.
+    channel_chat_a
+        .update(cx_a, |c, cx| c.load_thread(root_id, cx))
+        .await
+        .unwrap();
+    let reply_id = channel_chat_b
+        .update(cx_b, |c, cx| {
+            c.send_thread_message(root_id, "in a thread".into(), cx)
+                .unwrap()
+        })
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    cx_b.run_until_parked();
+
+    // Replies stay out of the channel's messages, but are counted on the root.
+    for (chat, cx) in [(&channel_chat_a, &mut *cx_a), (&channel_chat_b, &mut *cx_b)] {
+        chat.update(cx, |c, _| {
+            assert_eq!(
+                c.messages()
+                    .iter()
+                    .map(|m| (m.body.as_str(), m.thread_reply_count))
+                    .collect::<Vec<_>>(),
+                vec![("root", 1)]
+            );
+        });
+    }
+    channel_chat_a.update(cx_a, |c, _| {
+        let thread = c.thread(root_id).unwrap();
+        assert_eq!(thread.root.body, "root");
+        assert_eq!(
+            thread
+                .replies
+                .iter()
+                .map(|m| (m.body.as_str(), m.thread_root_message_id))
+                .collect::<Vec<_>>(),
+            vec![("in a thread", Some(root_id))]
+        );
+    });
+
+    // The author of the message that started the thread is notified of the reply.
+    client_a.notification_store().read_with(cx_a, |store, _| {
+        assert_eq!(
+            store.notification_at(0).unwrap().notification,
+            Notification::ChannelMessageReply {
+                message_id: reply_id,
+                sender_id: client_b.id(),
+                channel_id: channel_id.0,
+                thread_root_message_id: Some(root_id),
+            }
+        );
+        assert_eq!(
+            store.channel_message_for_id(reply_id).unwrap().body,
+            "in a thread"
+        );
+    });
+
+    // Reacting twice with the same emoji removes the reaction.
+    channel_chat_b
+        .update(cx_b, |c, cx| c.toggle_reaction(root_id, "🎉", cx))
+        .await
+        .unwrap();
+    channel_chat_a
+        .update(cx_a, |c, cx| c.toggle_reaction(root_id, "🎉", cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    cx_b.run_until_parked();
+    channel_chat_a.update(cx_a, |c, _| {
+        let reactions = &c.find_loaded_message(root_id).unwrap().reactions;
+        assert_eq!(reactions.len(), 1);
+        assert_eq!(reactions[0].emoji, "🎉");
+        let mut user_ids = reactions[0].user_ids.clone();
+        user_ids.sort();
+        assert_eq!(user_ids, vec![client_a.id(), client_b.id()]);
+    });
+
+    channel_chat_b
+        .update(cx_b, |c, cx| c.toggle_reaction(root_id, "🎉", cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    cx_b.run_until_parked();
+    channel_chat_a.update(cx_a, |c, _| {
+        let reactions = &c.find_loaded_message(root_id).unwrap().reactions;
+        assert_eq!(reactions[0].user_ids, vec![client_a.id()]);
+    });
+
+    // Pinned messages are marked as such and can be listed.
+    channel_chat_b
+        .update(cx_b, |c, cx| c.set_message_pinned(root_id, true, cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    channel_chat_a.update(cx_a, |c, _| {
+        assert!(c.find_loaded_message(root_id).unwrap().pinned);
+    });
+    let pinned_messages = channel_chat_a
+        .update(cx_a, |c, cx| c.load_pinned_messages(cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        pinned_messages.iter().map(|m| m.id).collect::<Vec<_>>(),
+        vec![ChannelMessageId::Saved(root_id)]
+    );
+
+    // Removing a reply updates the thread and its root.
+    channel_chat_b
+        .update(cx_b, |c, cx| c.remove_message(reply_id, cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    channel_chat_a.update(cx_a, |c, _| {
+        assert!(c.thread(root_id).unwrap().replies.is_empty());
+        assert_eq!(
+            c.find_loaded_message(root_id).unwrap().thread_reply_count,
+            0
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_chat_editing(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
     let mut server = TestServer::start(cx_a.executor()).await;
@@ -6932,3 +6932,134 @@ async fn test_remote_git_branches(
 
     assert_eq!(host_branch.name, "totally-new-branch");
 }
+
+#[gpui::test(iterations = 10)]
+async fn test_comment_threads(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(path!("/dir"), json!({ "a.txt": "one\ntwo\nthree\n" }))
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project(path!("/dir"), cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    let comment_store_a = project_a.read_with(cx_a, |project, _| project.comment_store());
+    let comment_store_b = project_b.read_with(cx_b, |project, _| project.comment_store());
+
+    let buffer_a = project_a
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+
+    // The guest comments on the second line.
+    let range = buffer_b.read_with(cx_b, |buffer, _| {
+        buffer.anchor_before(Point::new(1, 0))..buffer.anchor_after(Point::new(1, 3))
+    });
+    comment_store_b
+        .update(cx_b, |store, cx| {
+            store.create_thread(&buffer_b, range, "Rename this?".into(), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    let thread_id = comment_store_a.update(cx_a, |store, cx| {
+        let threads = store.threads_for_buffer(&buffer_a, cx);
+        assert_eq!(threads.len(), 1);
+        let thread = &threads[0];
+        assert_eq!(thread.anchored_text, "two");
+        assert!(!thread.resolved);
+        assert_eq!(thread.comments.len(), 1);
+        assert_eq!(thread.comments[0].body, "Rename this?");
+        assert_eq!(thread.comments[0].author_id, client_b.user_id().unwrap());
+        thread.id
+    });
+
+    // The thread's range follows edits.
+    buffer_a.update(cx_a, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    executor.run_until_parked();
+    comment_store_b.update(cx_b, |store, cx| {
+        let threads = store.threads_for_buffer(&buffer_b, cx);
+        let buffer = buffer_b.read(cx);
+        assert_eq!(
+            threads[0].range.to_point(buffer),
+            Point::new(2, 0)..Point::new(2, 3)
+        );
+    });
+
+    // The host replies, and the guest resolves the thread.
+    comment_store_a
+        .update(cx_a, |store, cx| {
+            store.add_comment(thread_id, "Sure".into(), cx)
+        })
+        .await
+        .unwrap();
+    comment_store_b
+        .update(cx_b, |store, cx| store.set_resolved(thread_id, true, cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    comment_store_a.read_with(cx_a, |store, _| {
+        let thread = store.thread(thread_id).unwrap();
+        assert!(thread.resolved);
+        assert_eq!(
+            thread
+                .comments
+                .iter()
+                .map(|comment| comment.body.as_str())
+                .collect::<Vec<_>>(),
+            ["Rename this?", "Sure"]
+        );
+    });
+
+    // Only authors can delete their comments, and the thread goes away with its last comment.
+    let guest_comment_id = comment_store_a.read_with(cx_a, |store, _| {
+        store.thread(thread_id).unwrap().comments[0].id
+    });
+    let host_comment_id = comment_store_a.read_with(cx_a, |store, _| {
+        store.thread(thread_id).unwrap().comments[1].id
+    });
+    comment_store_a
+        .update(cx_a, |store, cx| {
+            store.delete_comment(thread_id, guest_comment_id, cx)
+        })
+        .await
+        .unwrap_err();
+    comment_store_b
+        .update(cx_b, |store, cx| {
+            store.delete_comment(thread_id, guest_comment_id, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    comment_store_b.read_with(cx_b, |store, _| {
+        assert_eq!(store.thread(thread_id).unwrap().comments.len(), 1);
+    });
+    comment_store_a
+        .update(cx_a, |store, cx| {
+            store.delete_comment(thread_id, host_comment_id, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    comment_store_b.read_with(cx_b, |store, _| assert!(store.thread(thread_id).is_none()));
+    comment_store_a.read_with(cx_a, |store, _| assert!(store.thread(thread_id).is_none()));
+}
@@ -0,0 +1,542 @@
+use client::UserStore;
+use collections::{HashMap, HashSet};
+use editor::{
+    Addon, Editor, EditorMode,
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
+};
+use gpui::{
+    AnyElement, App, Context, Empty, Entity, Focusable, FontWeight, Subscription, WeakEntity,
+    Window, actions,
+};
+use language::{Anchor, Buffer, Point, ToPoint as _};
+use project::comment_store::{CommentStore, CommentStoreEvent, CommentThread};
+use std::{any::Any, ops::Range, sync::Arc};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{Avatar, Tooltip, prelude::*};
+
+actions!(collab, [AddComment]);
+
+/// The number of lines a reply editor grows to before scrolling.
+const REPLY_EDITOR_MAX_LINES: usize = 4;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|editor: &mut Editor, window, cx| {
+        let Some(window) = window else {
+            return;
+        };
+        BufferComments::register(editor, window, cx);
+    })
+    .detach();
+}
+
+struct BufferCommentsAddon {
+    _comments: Entity<BufferComments>,
+}
+
+impl Addon for BufferCommentsAddon {
+    fn to_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Shows the comment threads left on an editor's buffer by the collaborators in a shared project:
+/// their ranges are marked in the gutter, and each thread is rendered in a block below its range.
+pub struct BufferComments {
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    comment_store: Entity<CommentStore>,
+    user_store: Entity<UserStore>,
+    threads: Vec<CommentThread>,
+    thread_blocks: Vec<CustomBlockId>,
+    reply_editors: HashMap<u64, Entity<Editor>>,
+    expanded_resolved_threads: HashSet<u64>,
+    draft: Option<Draft>,
+    local_timezone: UtcOffset,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// A thread that's being written, and isn't on the server yet.
+struct Draft {
+    range: Range<Anchor>,
+    editor: Entity<Editor>,
+    block_id: Option<CustomBlockId>,
+}
+
+impl BufferComments {
+    fn register(editor: &mut Editor, window: &mut Window, cx: &mut Context<Editor>) {
+        if !matches!(editor.mode(), EditorMode::Full { .. }) {
+            return;
+        }
+        let Some(project) = editor.project.clone() else {
+            return;
+        };
+        let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+            return;
+        };
+
+        let editor_handle = cx.entity().downgrade();
+        let comments = cx.new(|cx| {
+            let comment_store = project.read(cx).comment_store();
+            let user_store = project.read(cx).user_store();
+            let local_offset = chrono::Local::now().offset().local_minus_utc();
+            let subscription = cx.subscribe_in(
+                &comment_store,
+                window,
+                |this, _, event, window, cx| match event {
+                    CommentStoreEvent::ThreadsUpdated => this.refresh(window, cx),
+                },
+            );
+            Self {
+                editor: editor_handle,
+                buffer,
+                comment_store,
+                user_store,
+                threads: Vec::new(),
+                thread_blocks: Vec::new(),
+                reply_editors: HashMap::default(),
+                expanded_resolved_threads: HashSet::default(),
+                draft: None,
+                local_timezone: UtcOffset::from_whole_seconds(local_offset).unwrap(),
+                _subscriptions: vec![subscription],
+            }
+        });
+
+        let weak_comments = comments.downgrade();
+        editor
+            .register_action(move |_: &AddComment, window, cx| {
+                weak_comments
+                    .update(cx, |comments, cx| comments.start_draft(window, cx))
+                    .ok();
+            })
+            .detach();
+        editor.register_addon(BufferCommentsAddon {
+            _comments: comments.clone(),
+        });
+
+        // The editor is still being created, so wait until it can be updated.
+        window.defer(cx, move |window, cx| {
+            comments.update(cx, |comments, cx| comments.refresh(window, cx));
+        });
+    }
+
+    /// Starts a new thread on the newest selection, or on its line if nothing is selected.
+    fn start_draft(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.comment_store.read(cx).is_available() {
+            return;
+        }
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let selection = editor.read(cx).selections.newest_anchor().clone();
+        let snapshot = self.buffer.read(cx).snapshot();
+        let mut range = selection.start.text_anchor..selection.end.text_anchor;
+        if range.start == range.end {
+            let row = range.start.to_point(&snapshot).row;
+            range = snapshot.anchor_before(Point::new(row, 0))
+                ..snapshot.anchor_after(Point::new(row, snapshot.line_len(row)));
+        }
+
+        let draft_editor = self.draft_editor("Leave a comment…", window, cx);
+        window.focus(&draft_editor.focus_handle(cx));
+        self.draft = Some(Draft {
+            range,
+            editor: draft_editor,
+            block_id: None,
+        });
+        self.refresh(window, cx);
+    }
+
+    fn cancel_draft(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.draft.take().is_some() {
+            self.refresh(window, cx);
+        }
+    }
+
+    fn submit_draft(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(draft) = self.draft.as_ref() else {
+            return;
+        };
+        let body = draft.editor.read(cx).text(cx);
+        if body.trim().is_empty() {
+            return;
+        }
+        let range = draft.range.clone();
+        self.comment_store
+            .update(cx, |store, cx| {
+                store.create_thread(&self.buffer, range, body, cx)
+            })
+            .detach_and_log_err(cx);
+        self.cancel_draft(window, cx);
+    }
+
+    fn submit_reply(&mut self, thread_id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(editor) = self.reply_editors.get(&thread_id).cloned() else {
+            return;
+        };
+        let body = editor.read(cx).text(cx);
+        if body.trim().is_empty() {
+            return;
+        }
+        editor.update(cx, |editor, cx| editor.clear(window, cx));
+        self.comment_store
+            .update(cx, |store, cx| store.add_comment(thread_id, body, cx))
+            .detach_and_log_err(cx);
+    }
+
+    fn set_resolved(&mut self, thread_id: u64, resolved: bool, cx: &mut Context<Self>) {
+        self.expanded_resolved_threads.remove(&thread_id);
+        self.comment_store
+            .update(cx, |store, cx| store.set_resolved(thread_id, resolved, cx))
+            .detach_and_log_err(cx);
+    }
+
+    fn delete_comment(&mut self, thread_id: u64, comment_id: u64, cx: &mut Context<Self>) {
+        self.comment_store
+            .update(cx, |store, cx| {
+                store.delete_comment(thread_id, comment_id, cx)
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn toggle_expanded(&mut self, thread_id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.expanded_resolved_threads.remove(&thread_id) {
+            self.expanded_resolved_threads.insert(thread_id);
+        }
+        self.refresh(window, cx);
+    }
+
+    fn draft_editor(
+        &self,
+        placeholder: &'static str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Editor> {
+        cx.new(|cx| {
+            let mut editor = Editor::auto_height(REPLY_EDITOR_MAX_LINES, window, cx);
+            editor.set_placeholder_text(placeholder, cx);
+            editor
+        })
+    }
+
+    /// Re-renders the gutter markers and thread blocks from the comment store's threads.
+    fn refresh(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        self.threads = self
+            .comment_store
+            .update(cx, |store, cx| store.threads_for_buffer(&self.buffer, cx));
+        if !self.comment_store.read(cx).is_available() {
+            self.draft = None;
+        }
+
+        let thread_ids = self
+            .threads
+            .iter()
+            .map(|thread| thread.id)
+            .collect::<HashSet<_>>();
+        self.reply_editors.retain(|id, _| thread_ids.contains(id));
+        self.expanded_resolved_threads
+            .retain(|id| thread_ids.contains(id));
+        for thread in &self.threads {
+            if !self.reply_editors.contains_key(&thread.id) {
+                let reply_editor = self.draft_editor("Reply…", window, cx);
+                self.reply_editors.insert(thread.id, reply_editor);
+            }
+        }
+        self.fetch_missing_authors(cx);
+
+        let this = cx.entity().downgrade();
+        let mut blocks = Vec::new();
+        let mut gutter_ranges = Vec::new();
+        let old_blocks = std::mem::take(&mut self.thread_blocks)
+            .into_iter()
+            .chain(self.draft.as_mut().and_then(|draft| draft.block_id.take()))
+            .collect::<HashSet<_>>();
+        let draft_range = self.draft.as_ref().map(|draft| draft.range.clone());
+
+        editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_blocks, None, cx);
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let Some((&excerpt_id, _, _)) = snapshot.as_singleton() else {
+                return;
+            };
+            let to_multibuffer = |range: &Range<Anchor>| {
+                Some(
+                    snapshot.anchor_in_excerpt(excerpt_id, range.start)?
+                        ..snapshot.anchor_in_excerpt(excerpt_id, range.end)?,
+                )
+            };
+
+            for thread in &self.threads {
+                let Some(range) = to_multibuffer(&thread.range) else {
+                    continue;
+                };
+                if !thread.resolved {
+                    gutter_ranges.push(range.clone());
+                }
+                let thread_id = thread.id;
+                let this = this.clone();
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Below(range.end),
+                    height: Some(self.thread_height(thread)),
+                    style: BlockStyle::Sticky,
+                    render: Arc::new(move |cx| {
+                        render_block(&this, cx, |this, gutter, cx| {
+                            this.render_thread(thread_id, gutter, cx)
+                        })
+                    }),
+                    priority: 0,
+                });
+            }
+            if let Some(range) = draft_range.as_ref().and_then(to_multibuffer) {
+                gutter_ranges.push(range.clone());
+                let this = this.clone();
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Below(range.end),
+                    height: Some(REPLY_EDITOR_MAX_LINES as u32 + 2),
+                    style: BlockStyle::Sticky,
+                    render: Arc::new(move |cx| render_block(&this, cx, Self::render_draft)),
+                    priority: 0,
+                });
+            }
+
+            if gutter_ranges.is_empty() {
+                editor.clear_gutter_highlights::<Self>(cx);
+            } else {
+                editor.highlight_gutter::<Self>(&gutter_ranges, |cx| cx.theme().status().info, cx);
+            }
+            let mut block_ids = editor.insert_blocks(blocks, None, cx);
+            if draft_range.is_some() {
+                if let Some(draft) = self.draft.as_mut() {
+                    draft.block_id = block_ids.pop();
+                }
+            }
+            self.thread_blocks = block_ids;
+        });
+    }
+
+    fn fetch_missing_authors(&self, cx: &mut Context<Self>) {
+        let user_store = self.user_store.read(cx);
+        let missing_user_ids = self
+            .threads
+            .iter()
+            .flat_map(|thread| &thread.comments)
+            .map(|comment| comment.author_id)
+            .filter(|user_id| user_store.get_cached_user(*user_id).is_none())
+            .collect::<HashSet<_>>();
+        if missing_user_ids.is_empty() {
+            return;
+        }
+        let users = self.user_store.update(cx, |user_store, cx| {
+            user_store.get_users(missing_user_ids.into_iter().collect(), cx)
+        });
+        let editor = self.editor.clone();
+        cx.spawn(async move |_, cx| {
+            users.await?;
+            editor.update(cx, |_, cx| cx.notify())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// An estimate of the number of lines a thread takes up, since block heights are fixed.
+    fn thread_height(&self, thread: &CommentThread) -> u32 {
+        if thread.resolved && !self.expanded_resolved_threads.contains(&thread.id) {
+            return 1;
+        }
+        let comment_lines = thread
+            .comments
+            .iter()
+            .map(|comment| comment.body.lines().count().max(1) as u32 + 1)
+            .sum::<u32>();
+        comment_lines + REPLY_EDITOR_MAX_LINES as u32 + 3
+    }
+
+    fn render_thread(
+        &mut self,
+        thread_id: u64,
+        gutter_width: Pixels,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let Some(thread) = self.threads.iter().find(|thread| thread.id == thread_id) else {
+            return Empty.into_any_element();
+        };
+        let resolved = thread.resolved;
+        let expanded = !resolved || self.expanded_resolved_threads.contains(&thread_id);
+        let reply_count = thread.comments.len();
+
+        let header = h_flex()
+            .gap_1()
+            .child(
+                IconButton::new(
+                    ("toggle-comment-thread", thread_id),
+                    if expanded {
+                        IconName::ChevronDown
+                    } else {
+                        IconName::ChevronRight
+                    },
+                )
+                .icon_size(IconSize::Small)
+                .disabled(!resolved)
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    this.toggle_expanded(thread_id, window, cx)
+                })),
+            )
+            .child(
+                Label::new(if resolved {
+                    format!("Resolved thread ({reply_count})")
+                } else if reply_count == 1 {
+                    "1 comment".to_string()
+                } else {
+                    format!("{reply_count} comments")
+                })
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new(
+                    ("resolve-comment-thread", thread_id),
+                    if resolved { "Reopen" } else { "Resolve" },
+                )
+                .label_size(LabelSize::Small)
+                .on_click(
+                    cx.listener(move |this, _, _, cx| this.set_resolved(thread_id, !resolved, cx)),
+                ),
+            );
+
+        let mut container = v_flex()
+            .id(("comment-thread", thread_id))
+            .ml(gutter_width)
+            .mr_4()
+            .my_0p5()
+            .px_2()
+            .py_1()
+            .max_w(rems(40.))
+            .rounded_sm()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_background)
+            .child(header);
+        if !expanded {
+            return container.into_any_element();
+        }
+
+        let current_user_id = self.user_store.read(cx).current_user().map(|user| user.id);
+        for comment in &thread.comments {
+            let author = self.user_store.read(cx).get_cached_user(comment.author_id);
+            let comment_id = comment.id;
+            container = container.child(
+                v_flex()
+                    .py_0p5()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .when_some(author.as_ref(), |el, author| {
+                                el.child(Avatar::new(author.avatar_uri.clone()).size(rems(1.)))
+                                    .child(
+                                        Label::new(author.github_login.clone())
+                                            .size(LabelSize::Small)
+                                            .weight(FontWeight::BOLD),
+                                    )
+                            })
+                            .child(
+                                Label::new(time_format::format_localized_timestamp(
+                                    comment.created_at,
+                                    OffsetDateTime::now_utc(),
+                                    self.local_timezone,
+                                    time_format::TimestampFormat::EnhancedAbsolute,
+                                ))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                            )
+                            .when(current_user_id == Some(comment.author_id), |el| {
+                                el.child(
+                                    IconButton::new(
+                                        ("delete-comment", comment_id),
+                                        IconName::Trash,
+                                    )
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(Tooltip::text("Delete Comment"))
+                                    .on_click(cx.listener(
+                                        move |this, _, _, cx| {
+                                            this.delete_comment(thread_id, comment_id, cx)
+                                        },
+                                    )),
+                                )
+                            }),
+                    )
+                    .child(Label::new(comment.body.clone())),
+            );
+        }
+
+        if let Some(reply_editor) = self.reply_editors.get(&thread_id) {
+            container = container.child(
+                h_flex()
+                    .gap_1()
+                    .items_end()
+                    .child(div().flex_1().child(reply_editor.clone()))
+                    .child(
+                        Button::new(("reply-to-comment-thread", thread_id), "Reply")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.submit_reply(thread_id, window, cx)
+                            })),
+                    ),
+            );
+        }
+        container.into_any_element()
+    }
+
+    fn render_draft(&mut self, gutter_width: Pixels, cx: &mut Context<Self>) -> AnyElement {
+        let Some(draft) = self.draft.as_ref() else {
+            return Empty.into_any_element();
+        };
+        v_flex()
+            .id("comment-draft")
+            .ml(gutter_width)
+            .mr_4()
+            .my_0p5()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .max_w(rems(40.))
+            .rounded_sm()
+            .border_1()
+            .border_color(cx.theme().colors().border_focused)
+            .bg(cx.theme().colors().editor_background)
+            .child(draft.editor.clone())
+            .child(
+                h_flex()
+                    .gap_1()
+                    .justify_end()
+                    .child(
+                        Button::new("cancel-comment", "Cancel")
+                            .label_size(LabelSize::Small)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.cancel_draft(window, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("submit-comment", "Comment")
+                            .label_size(LabelSize::Small)
+                            .style(ButtonStyle::Filled)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.submit_draft(window, cx)),
+                            ),
+                    ),
+            )
+            .into_any_element()
+    }
+}
+
+fn render_block(
+    this: &WeakEntity<BufferComments>,
+    cx: &mut BlockContext,
+    render: impl FnOnce(&mut BufferComments, Pixels, &mut Context<BufferComments>) -> AnyElement,
+) -> AnyElement {
+    let gutter_width = cx.gutter_dimensions.full_width();
+    this.update(cx.app, |this, cx| render(this, gutter_width, cx))
+        .unwrap_or_else(|_| Empty.into_any_element())
+}
@@ -2,7 +2,7 @@ use crate::{ChatPanelButton, ChatPanelSettings, collab_panel};
 use anyhow::Result;
 use call::{ActiveCall, room};
 use channel::{ChannelChat, ChannelChatEvent, ChannelMessage, ChannelMessageId, ChannelStore};
-use client::{ChannelId, Client};
+use client::{ChannelId, Client, UserStore};
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
 use editor::{Editor, actions};
@@ -19,7 +19,7 @@ use project::Fs;
 use rich_text::{Highlight, RichText};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use std::{sync::Arc, time::Duration};
+use std::{iter, sync::Arc, time::Duration};
 use time::{OffsetDateTime, UtcOffset};
 use ui::{
     Avatar, Button, ContextMenu, IconButton, IconName, KeyBinding, Label, PopoverMenu, Tab, TabBar,
@@ -35,6 +35,7 @@ mod message_editor;
 
 const MESSAGE_LOADING_THRESHOLD: usize = 50;
 const CHAT_PANEL_KEY: &str = "ChatPanel";
+const REACTION_EMOJIS: [&str; 6] = ["👍", "❤️", "😄", "🎉", "👀", "🚀"];
 
 pub fn init(cx: &mut App) {
     cx.observe_new(|workspace: &mut Workspace, _, _| {
@@ -48,6 +49,7 @@ pub fn init(cx: &mut App) {
 pub struct ChatPanel {
     client: Arc<Client>,
     channel_store: Entity<ChannelStore>,
+    user_store: Entity<UserStore>,
     languages: Arc<LanguageRegistry>,
     message_list: ListState,
     active_chat: Option<(Entity<ChannelChat>, Subscription)>,
@@ -64,6 +66,15 @@ pub struct ChatPanel {
     open_context_menu: Option<(u64, Subscription)>,
     highlighted_message: Option<(u64, Task<()>)>,
     last_acknowledged_message_id: Option<u64>,
+    open_thread: Option<OpenThread>,
+    pinned_messages: Option<Vec<ChannelMessage>>,
+    load_pinned_messages_task: Task<Option<()>>,
+}
+
+struct OpenThread {
+    root_message_id: u64,
+    message_editor: Entity<MessageEditor>,
+    markdown_data: HashMap<ChannelMessageId, RichText>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -125,6 +136,7 @@ impl ChatPanel {
                 fs,
                 client,
                 channel_store,
+                user_store,
                 languages,
                 message_list,
                 active_chat: Default::default(),
@@ -140,6 +152,9 @@ impl ChatPanel {
                 open_context_menu: None,
                 highlighted_message: None,
                 last_acknowledged_message_id: None,
+                open_thread: None,
+                pinned_messages: None,
+                load_pinned_messages_task: Task::ready(None),
             };
 
             if let Some(channel_id) = ActiveCall::global(cx)
@@ -250,6 +265,8 @@ impl ChatPanel {
             });
             let subscription = cx.subscribe(&chat, Self::channel_did_change);
             self.active_chat = Some((chat, subscription));
+            self.open_thread = None;
+            self.pinned_messages = None;
             self.acknowledge_last_message(cx);
             cx.notify();
         }
@@ -277,6 +294,21 @@ impl ChatPanel {
             } => {
                 self.message_list.splice(*message_ix..*message_ix + 1, 1);
                 self.markdown_data.remove(message_id);
+                if self.pinned_messages.is_some() {
+                    self.load_pinned_messages(cx);
+                }
+            }
+            ChannelChatEvent::ThreadUpdated { root_message_id } => {
+                if let Some((open_thread, (chat, _))) =
+                    self.open_thread.as_mut().zip(self.active_chat.as_ref())
+                {
+                    if open_thread.root_message_id == *root_message_id {
+                        open_thread.markdown_data.clear();
+                        if chat.read(cx).thread(*root_message_id).is_none() {
+                            self.open_thread = None;
+                        }
+                    }
+                }
             }
             ChannelChatEvent::NewMessage {
                 channel_id,
@@ -425,6 +457,7 @@ impl ChatPanel {
             });
 
         let _is_pending = message.is_pending();
+        let is_pinned = message.pinned;
 
         let belongs_to_user = Some(message.sender.id) == self.client.user_id();
         let can_delete_message = belongs_to_user || is_admin;
@@ -528,6 +561,22 @@ impl ChatPanel {
                         },
                     )
                     .when(mentioning_you || replied_to_you, |this| this.my_0p5())
+                    .when(message.pinned, |this| {
+                        this.child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Icon::new(IconName::Pin)
+                                        .size(IconSize::XSmall)
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    Label::new("Pinned")
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted),
+                                ),
+                        )
+                    })
                     .map(|el| {
                         let text = self.markdown_data.entry(message.id).or_insert_with(|| {
                             Self::render_markdown_with_mentions(
@@ -548,7 +597,35 @@ impl ChatPanel {
                         .when(self.has_open_menu(message_id), |el| {
                             el.bg(cx.theme().colors().element_selected)
                         })
-                    }),
+                    })
+                    .child(self.render_reactions(&message, cx))
+                    .when_some(
+                        message_id.filter(|_| message.thread_reply_count > 0),
+                        |el, message_id| {
+                            let reply_count = message.thread_reply_count;
+                            el.child(
+                                Button::new(
+                                    ("open-thread", message_id),
+                                    if reply_count == 1 {
+                                        "1 reply".to_string()
+                                    } else {
+                                        format!("{reply_count} replies")
+                                    },
+                                )
+                                .icon(IconName::MessageBubbles)
+                                .icon_position(IconPosition::Start)
+                                .icon_size(IconSize::XSmall)
+                                .icon_color(Color::Muted)
+                                .label_size(LabelSize::XSmall)
+                                .color(Color::Accent)
+                                .on_click(cx.listener(
+                                    move |this, _, window, cx| {
+                                        this.open_thread(message_id, window, cx)
+                                    },
+                                )),
+                            )
+                        },
+                    ),
             )
             .when(
                 self.last_acknowledged_message_id
@@ -573,8 +650,14 @@ impl ChatPanel {
                 },
             )
             .child(
-                self.render_popover_buttons(message_id, can_delete_message, can_edit_message, cx)
-                    .mt_neg_2p5(),
+                self.render_popover_buttons(
+                    message_id,
+                    can_delete_message,
+                    can_edit_message,
+                    is_pinned,
+                    cx,
+                )
+                .mt_neg_2p5(),
             )
     }
 
@@ -598,6 +681,7 @@ impl ChatPanel {
         message_id: Option<u64>,
         can_delete_message: bool,
         can_edit_message: bool,
+        is_pinned: bool,
         cx: &mut Context<Self>,
     ) -> Div {
         h_flex()
@@ -632,6 +716,30 @@ impl ChatPanel {
                     ),
                 )
             })
+            .when_some(message_id, |el, message_id| {
+                let this = cx.entity().clone();
+
+                el.child(
+                    self.render_popover_button(
+                        cx,
+                        div()
+                            .child(
+                                PopoverMenu::new(("reactions", message_id))
+                                    .trigger(IconButton::new(
+                                        ("add-reaction", message_id),
+                                        IconName::Plus,
+                                    ))
+                                    .menu(move |window, cx| {
+                                        Some(Self::render_reaction_menu(
+                                            &this, message_id, window, cx,
+                                        ))
+                                    }),
+                            )
+                            .id("react")
+                            .tooltip(Tooltip::text("Add Reaction")),
+                    ),
+                )
+            })
             .when_some(message_id, |el, message_id| {
                 el.when(can_edit_message, |el| {
                     el.child(
@@ -696,6 +804,7 @@ impl ChatPanel {
                                             &this,
                                             message_id,
                                             can_delete_message,
+                                            is_pinned,
                                             window,
                                             cx,
                                         ))
@@ -712,12 +821,31 @@ impl ChatPanel {
         this: &Entity<Self>,
         message_id: u64,
         can_delete_message: bool,
+        is_pinned: bool,
         window: &mut Window,
         cx: &mut App,
     ) -> Entity<ContextMenu> {
         let menu = {
             ContextMenu::build(window, cx, move |menu, window, _| {
                 menu.entry(
+                    "Reply in Thread",
+                    None,
+                    window.handler_for(this, move |this, window, cx| {
+                        this.open_thread(message_id, window, cx)
+                    }),
+                )
+                .entry(
+                    if is_pinned {
+                        "Unpin Message"
+                    } else {
+                        "Pin Message"
+                    },
+                    None,
+                    window.handler_for(this, move |this, _, cx| {
+                        this.set_message_pinned(message_id, !is_pinned, cx)
+                    }),
+                )
+                .entry(
                     "Copy message text",
                     None,
                     window.handler_for(this, move |this, _, cx| {
@@ -740,9 +868,41 @@ impl ChatPanel {
                 })
             })
         };
+        Self::track_open_menu(this, message_id, &menu, window, cx);
+        menu
+    }
+
+    fn render_reaction_menu(
+        this: &Entity<Self>,
+        message_id: u64,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<ContextMenu> {
+        let menu = ContextMenu::build(window, cx, move |menu, window, _| {
+            REACTION_EMOJIS.into_iter().fold(menu, |menu, emoji| {
+                menu.entry(
+                    emoji,
+                    None,
+                    window.handler_for(this, move |this, _, cx| {
+                        this.toggle_reaction(message_id, emoji, cx)
+                    }),
+                )
+            })
+        });
+        Self::track_open_menu(this, message_id, &menu, window, cx);
+        menu
+    }
+
+    fn track_open_menu(
+        this: &Entity<Self>,
+        message_id: u64,
+        menu: &Entity<ContextMenu>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
         this.update(cx, |this, cx| {
             let subscription = cx.subscribe_in(
-                &menu,
+                menu,
                 window,
                 |this: &mut Self, _, _: &DismissEvent, _, _| {
                     this.open_context_menu = None;
@@ -750,7 +910,308 @@ impl ChatPanel {
             );
             this.open_context_menu = Some((message_id, subscription));
         });
-        menu
+    }
+
+    fn render_reactions(&self, message: &ChannelMessage, cx: &mut Context<Self>) -> Div {
+        let current_user_id = self.client.user_id();
+        let ChannelMessageId::Saved(message_id) = message.id else {
+            return div();
+        };
+
+        h_flex()
+            .flex_wrap()
+            .gap_1()
+            .when(!message.reactions.is_empty(), |el| el.mt_0p5())
+            .children(message.reactions.iter().map(|reaction| {
+                let emoji = reaction.emoji.clone();
+                let reacted =
+                    current_user_id.map_or(false, |user_id| reaction.user_ids.contains(&user_id));
+                Button::new(
+                    ElementId::Name(format!("reaction-{message_id}-{emoji}").into()),
+                    format!("{} {}", reaction.emoji, reaction.user_ids.len()),
+                )
+                .style(ButtonStyle::Filled)
+                .label_size(LabelSize::XSmall)
+                .toggle_state(reacted)
+                .on_click(
+                    cx.listener(move |this, _, _, cx| this.toggle_reaction(message_id, &emoji, cx)),
+                )
+            }))
+    }
+
+    fn toggle_reaction(&mut self, message_id: u64, emoji: &str, cx: &mut Context<Self>) {
+        if let Some((chat, _)) = self.active_chat.as_ref() {
+            chat.update(cx, |chat, cx| chat.toggle_reaction(message_id, emoji, cx))
+                .detach_and_log_err(cx);
+        }
+    }
+
+    fn set_message_pinned(&mut self, message_id: u64, pinned: bool, cx: &mut Context<Self>) {
+        if let Some((chat, _)) = self.active_chat.as_ref() {
+            chat.update(cx, |chat, cx| {
+                chat.set_message_pinned(message_id, pinned, cx)
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    fn toggle_pinned_messages(&mut self, cx: &mut Context<Self>) {
+        if self.pinned_messages.take().is_none() {
+            self.pinned_messages = Some(Vec::new());
+            self.load_pinned_messages(cx);
+        }
+        cx.notify();
+    }
+
+    fn load_pinned_messages(&mut self, cx: &mut Context<Self>) {
+        let Some((chat, _)) = self.active_chat.as_ref() else {
+            return;
+        };
+        let task = chat.update(cx, |chat, cx| chat.load_pinned_messages(cx));
+        self.load_pinned_messages_task = cx.spawn(async move |this, cx| {
+            let messages = task.await.log_err()?;
+            this.update(cx, |this, cx| {
+                if this.pinned_messages.is_some() {
+                    this.pinned_messages = Some(messages);
+                    cx.notify();
+                }
+            })
+            .ok()
+        });
+    }
+
+    /// Opens the thread started by the given message of the active chat beside its messages.
+    pub fn open_thread(
+        &mut self,
+        root_message_id: u64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((chat, _)) = self.active_chat.as_ref() else {
+            return;
+        };
+        if self
+            .open_thread
+            .as_ref()
+            .is_some_and(|thread| thread.root_message_id == root_message_id)
+        {
+            return;
+        }
+
+        let message_editor = cx.new(|cx| {
+            let message_editor = MessageEditor::new(
+                self.languages.clone(),
+                self.user_store.clone(),
+                Some(chat.clone()),
+                cx.new(|cx| Editor::auto_height(4, window, cx)),
+                window,
+                cx,
+            );
+            message_editor.editor.update(cx, |editor, cx| {
+                editor.set_placeholder_text("Reply in thread", cx);
+            });
+            message_editor
+        });
+        window.focus(&message_editor.read(cx).focus_handle(cx));
+        self.open_thread = Some(OpenThread {
+            root_message_id,
+            message_editor,
+            markdown_data: Default::default(),
+        });
+        chat.update(cx, |chat, cx| chat.load_thread(root_message_id, cx))
+            .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn close_thread(&mut self, cx: &mut Context<Self>) {
+        self.open_thread = None;
+        cx.notify();
+    }
+
+    fn render_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Option<Div> {
+        let chat = self.active_chat()?;
+        let open_thread = self.open_thread.as_ref()?;
+        let thread = chat.read(cx).thread(open_thread.root_message_id).cloned();
+        let message_editor = open_thread.message_editor.clone();
+
+        let messages = thread
+            .map(|thread| {
+                iter::once(thread.root)
+                    .chain(thread.replies)
+                    .map(|message| self.render_thread_message(message, window, cx))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        Some(
+            v_flex()
+                .flex_1()
+                .min_w_0()
+                .h_full()
+                .border_l_1()
+                .border_color(cx.theme().colors().border)
+                .child(
+                    h_flex()
+                        .h(Tab::container_height(cx))
+                        .px_2()
+                        .justify_between()
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(Label::new("Thread"))
+                        .child(
+                            IconButton::new("close-thread", IconName::Close)
+                                .shape(ui::IconButtonShape::Square)
+                                .tooltip(Tooltip::text("Close Thread"))
+                                .on_click(cx.listener(|this, _, _, cx| this.close_thread(cx))),
+                        ),
+                )
+                .child(
+                    v_flex()
+                        .id("thread-messages")
+                        .flex_grow()
+                        .overflow_y_scroll()
+                        .px_2()
+                        .when(messages.is_empty(), |el| {
+                            el.child(
+                                Label::new("Loading thread…")
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                        })
+                        .children(messages),
+                )
+                .child(
+                    h_flex()
+                        .p_2()
+                        .border_t_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(message_editor),
+                ),
+        )
+    }
+
+    fn render_thread_message(
+        &mut self,
+        message: ChannelMessage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let text = match self.open_thread.as_mut() {
+            Some(open_thread) => open_thread
+                .markdown_data
+                .entry(message.id)
+                .or_insert_with(|| {
+                    Self::render_markdown_with_mentions(
+                        &self.languages,
+                        self.client.id(),
+                        &message,
+                        self.local_timezone,
+                        cx,
+                    )
+                })
+                .element("body".into(), window, cx),
+            None => div().into_any_element(),
+        };
+        let element_id: ElementId = match message.id {
+            ChannelMessageId::Saved(id) => ("thread-message", id).into(),
+            ChannelMessageId::Pending(id) => ("pending-thread-message", id).into(),
+        };
+        let this = cx.entity().clone();
+
+        v_flex()
+            .id(element_id)
+            .py_1()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .text_ui_sm(cx)
+                    .child(Avatar::new(message.sender.avatar_uri.clone()).size(rems(1.)))
+                    .child(
+                        Label::new(message.sender.github_login.clone())
+                            .size(LabelSize::Small)
+                            .weight(FontWeight::BOLD),
+                    )
+                    .child(
+                        Label::new(time_format::format_localized_timestamp(
+                            message.timestamp,
+                            OffsetDateTime::now_utc(),
+                            self.local_timezone,
+                            time_format::TimestampFormat::EnhancedAbsolute,
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .when_some(Option::<u64>::from(message.id), |el, message_id| {
+                        el.child(
+                            PopoverMenu::new(("thread-reactions", message_id))
+                                .trigger(
+                                    IconButton::new(
+                                        ("thread-add-reaction", message_id),
+                                        IconName::Plus,
+                                    )
+                                    .icon_size(IconSize::XSmall)
+                                    .tooltip(Tooltip::text("Add Reaction")),
+                                )
+                                .menu(move |window, cx| {
+                                    Some(Self::render_reaction_menu(&this, message_id, window, cx))
+                                }),
+                        )
+                    }),
+            )
+            .child(v_flex().w_full().text_ui_sm(cx).child(text))
+            .child(self.render_reactions(&message, cx))
+    }
+
+    fn render_pinned_messages(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let pinned_messages = self.pinned_messages.as_ref()?;
+        let channel_id = self.channel_id(cx)?;
+
+        Some(
+            v_flex()
+                .id("pinned-messages")
+                .max_h_40()
+                .overflow_y_scroll()
+                .px_2()
+                .py_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border)
+                .bg(cx.theme().colors().background)
+                .when(pinned_messages.is_empty(), |el| {
+                    el.child(
+                        Label::new("No pinned messages")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                })
+                .children(pinned_messages.iter().filter_map(|message| {
+                    let message_id = Option::<u64>::from(message.id)?;
+                    Some(
+                        h_flex()
+                            .id(("pinned-message", message_id))
+                            .gap_1()
+                            .py_0p5()
+                            .overflow_hidden()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .hover(|style| style.bg(cx.theme().colors().element_hover))
+                            .child(Avatar::new(message.sender.avatar_uri.clone()).size(rems(0.7)))
+                            .child(
+                                Label::new(message.sender.github_login.clone())
+                                    .size(LabelSize::Small)
+                                    .weight(FontWeight::SEMIBOLD),
+                            )
+                            .child(
+                                Label::new(message.body.replace('\n', " "))
+                                    .size(LabelSize::Small)
+                                    .truncate(),
+                            )
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.select_channel(channel_id, Some(message_id), cx)
+                                    .detach_and_log_err(cx)
+                            })),
+                    )
+                })),
+        )
     }
 
     fn render_markdown_with_mentions(
@@ -808,6 +1269,28 @@ impl ChatPanel {
 
     fn send(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
         if let Some((chat, _)) = self.active_chat.as_ref() {
+            if let Some(open_thread) = self.open_thread.as_ref() {
+                let message_editor = open_thread.message_editor.clone();
+                if message_editor
+                    .read(cx)
+                    .focus_handle(cx)
+                    .contains_focused(window, cx)
+                {
+                    let root_message_id = open_thread.root_message_id;
+                    let message =
+                        message_editor.update(cx, |editor, cx| editor.take_message(window, cx));
+                    if let Some(task) = chat
+                        .update(cx, |chat, cx| {
+                            chat.send_thread_message(root_message_id, message, cx)
+                        })
+                        .log_err()
+                    {
+                        task.detach();
+                    }
+                    return;
+                }
+            }
+
             let message = self
                 .message_editor
                 .update(cx, |editor, cx| editor.take_message(window, cx));
@@ -948,11 +1431,12 @@ impl Render for ChatPanel {
         let reply_to_message_id = message_editor.reply_to_message_id();
         let edit_message_id = message_editor.edit_message_id();
 
-        v_flex()
-            .key_context("ChatPanel")
-            .track_focus(&self.focus_handle)
-            .size_full()
-            .on_action(cx.listener(Self::send))
+        let show_pinned_messages = self.pinned_messages.is_some();
+
+        let messages = v_flex()
+            .flex_1()
+            .min_w_0()
+            .h_full()
             .child(
                 h_flex().child(
                     TabBar::new("chat_header").child(
@@ -960,6 +1444,7 @@ impl Render for ChatPanel {
                             .w_full()
                             .h(Tab::container_height(cx))
                             .px_2()
+                            .justify_between()
                             .child(Label::new(
                                 self.active_chat
                                     .as_ref()
@@ -967,10 +1452,22 @@ impl Render for ChatPanel {
                                         Some(format!("#{}", c.0.read(cx).channel(cx)?.name))
                                     })
                                     .unwrap_or("Chat".to_string()),
-                            )),
+                            ))
+                            .when(self.active_chat.is_some(), |el| {
+                                el.child(
+                                    IconButton::new("toggle-pinned-messages", IconName::Pin)
+                                        .shape(ui::IconButtonShape::Square)
+                                        .toggle_state(show_pinned_messages)
+                                        .tooltip(Tooltip::text("Pinned Messages"))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.toggle_pinned_messages(cx)
+                                        })),
+                                )
+                            }),
                     ),
                 ),
             )
+            .children(self.render_pinned_messages(cx))
             .child(div().flex_grow().px_2().map(|this| {
                 if self.active_chat.is_some() {
                     this.child(list(self.message_list.clone()).size_full())
@@ -1101,7 +1598,15 @@ impl Render for ChatPanel {
                         .map(|el| el.child(self.message_editor.clone())),
                 )
                 .filter(|_| self.active_chat.is_some()),
-            )
+            );
+
+        h_flex()
+            .key_context("ChatPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .on_action(cx.listener(Self::send))
+            .child(messages)
+            .children(self.render_thread(window, cx))
             .into_any()
     }
 }
@@ -1224,6 +1729,10 @@ mod tests {
             mentions: vec![(ranges[0].clone(), 101), (ranges[1].clone(), 102)],
             reply_to_message_id: None,
             edited_at: None,
+            reactions: Vec::new(),
+            thread_root_message_id: None,
+            thread_reply_count: 0,
+            pinned: false,
         };
 
         let message = ChatPanel::render_markdown_with_mentions(
@@ -1280,6 +1789,10 @@ mod tests {
             mentions: Vec::new(),
             reply_to_message_id: None,
             edited_at: None,
+            reactions: Vec::new(),
+            thread_root_message_id: None,
+            thread_reply_count: 0,
+            pinned: false,
         };
 
         let message = ChatPanel::render_markdown_with_mentions(
@@ -1329,6 +1842,10 @@ mod tests {
             mentions: Vec::new(),
             reply_to_message_id: None,
             edited_at: None,
+            reactions: Vec::new(),
+            thread_root_message_id: None,
+            thread_reply_count: 0,
+            pinned: false,
         };
 
         let message = ChatPanel::render_markdown_with_mentions(
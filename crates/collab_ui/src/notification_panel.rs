@@ -423,6 +423,34 @@ impl NotificationPanel {
                     can_navigate: true,
                 })
             }
+            Notification::ChannelMessageReply {
+                sender_id,
+                channel_id,
+                message_id,
+                thread_root_message_id,
+            } => {
+                let sender = user_store.get_cached_user(sender_id)?;
+                let channel = channel_store.channel_for_id(ChannelId(channel_id))?;
+                let message = self
+                    .notification_store
+                    .read(cx)
+                    .channel_message_for_id(message_id)?;
+                let location = if thread_root_message_id.is_some() {
+                    "a thread"
+                } else {
+                    "your message"
+                };
+                Some(NotificationPresenter {
+                    icon: "icons/conversations.svg",
+                    text: format!(
+                        "{} replied to {location} in #{}:\n{}",
+                        sender.github_login, channel.name, message.body,
+                    ),
+                    needs_response: false,
+                    actor: Some(sender),
+                    can_navigate: true,
+                })
+            }
         }
     }
 
@@ -437,7 +465,8 @@ impl NotificationPanel {
             Notification::ContactRequestAccepted { .. } => true,
             Notification::ContactRequest { .. }
             | Notification::ChannelInvitation { .. }
-            | Notification::ChannelMessageMention { .. } => false,
+            | Notification::ChannelMessageMention { .. }
+            | Notification::ChannelMessageReply { .. } => false,
         };
 
         if should_mark_as_read {
@@ -465,25 +494,49 @@ impl NotificationPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Notification::ChannelMessageMention {
-            message_id,
-            channel_id,
-            ..
-        } = notification.clone()
-        {
-            if let Some(workspace) = self.workspace.upgrade() {
-                window.defer(cx, move |window, cx| {
-                    workspace.update(cx, |workspace, cx| {
-                        if let Some(panel) = workspace.focus_panel::<ChatPanel>(window, cx) {
-                            panel.update(cx, |panel, cx| {
-                                panel
-                                    .select_channel(ChannelId(channel_id), Some(message_id), cx)
-                                    .detach_and_log_err(cx);
-                            });
-                        }
-                    });
+        let (channel_id, message_id, thread_root_message_id) = match notification.clone() {
+            Notification::ChannelMessageMention {
+                message_id,
+                channel_id,
+                ..
+            } => (channel_id, message_id, None),
+            Notification::ChannelMessageReply {
+                message_id,
+                channel_id,
+                thread_root_message_id,
+                ..
+            } => (channel_id, message_id, thread_root_message_id),
+            _ => return,
+        };
+
+        if let Some(workspace) = self.workspace.upgrade() {
+            window.defer(cx, move |window, cx| {
+                workspace.update(cx, |workspace, cx| {
+                    if let Some(panel) = workspace.focus_panel::<ChatPanel>(window, cx) {
+                        // Replies in a thread aren't listed with the channel's messages, so
+                        // scroll to the message that started the thread and open it instead.
+                        let select_channel = panel.update(cx, |panel, cx| {
+                            panel.select_channel(
+                                ChannelId(channel_id),
+                                Some(thread_root_message_id.unwrap_or(message_id)),
+                                cx,
+                            )
+                        });
+                        let Some(thread_root_message_id) = thread_root_message_id else {
+                            select_channel.detach_and_log_err(cx);
+                            return;
+                        };
+                        window
+                            .spawn(cx, async move |cx| {
+                                select_channel.await?;
+                                panel.update_in(cx, |panel, window, cx| {
+                                    panel.open_thread(thread_root_message_id, window, cx)
+                                })
+                            })
+                            .detach_and_log_err(cx);
+                    }
                 });
-            }
+            });
         }
     }
 
@@ -492,7 +545,13 @@ impl NotificationPanel {
             return false;
         }
 
-        if let Notification::ChannelMessageMention { channel_id, .. } = &notification {
+        if let Notification::ChannelMessageMention { channel_id, .. }
+        | Notification::ChannelMessageReply {
+            channel_id,
+            thread_root_message_id: None,
+            ..
+        } = &notification
+        {
             if let Some(workspace) = self.workspace.upgrade() {
                 return if let Some(panel) = workspace.read(cx).panel::<ChatPanel>(cx) {
                     let panel = panel.read(cx);
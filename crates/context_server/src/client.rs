@@ -1,6 +1,6 @@
 use anyhow::{Context, Result, anyhow};
 use collections::HashMap;
-use futures::{FutureExt, StreamExt, channel::oneshot, select};
+use futures::{FutureExt, StreamExt, channel::oneshot, future::LocalBoxFuture, select};
 use gpui::{AppContext as _, AsyncApp, BackgroundExecutor, Task};
 use parking_lot::Mutex;
 use postage::barrier;
@@ -19,6 +19,7 @@ use std::{
 use util::TryFutureExt;
 
 use crate::transport::{StdioTransport, Transport};
+use crate::types;
 
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -32,6 +33,8 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncApp)>;
+type RequestHandler =
+    Box<dyn Send + FnMut(Value, AsyncApp) -> LocalBoxFuture<'static, Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -46,6 +49,7 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -78,6 +82,16 @@ struct Request<'a, T> {
     params: T,
 }
 
+#[derive(Deserialize)]
+struct AnyRequest<'a> {
+    #[allow(dead_code)]
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
@@ -122,8 +136,20 @@ struct AnyNotification<'a> {
     params: Option<Value>,
 }
 
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
@@ -158,23 +184,48 @@ impl Client {
             .unwrap_or_else(String::new);
 
         let transport = Arc::new(StdioTransport::new(binary, &cx)?);
+        Ok(Self::from_transport(
+            server_id,
+            server_name.into(),
+            transport,
+            cx,
+        ))
+    }
 
+    /// Creates a new Client instance for a context server that is reached through the given
+    /// transport.
+    pub fn from_transport(
+        server_id: ContextServerId,
+        name: Arc<str>,
+        transport: Arc<dyn Transport>,
+        cx: AsyncApp,
+    ) -> Self {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
 
         let stdout_input_task = cx.spawn({
             let notification_handlers = notification_handlers.clone();
+            let request_handlers = request_handlers.clone();
             let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
             let transport = transport.clone();
             async move |cx| {
-                Self::handle_input(transport, notification_handlers, response_handlers, cx)
-                    .log_err()
-                    .await
+                Self::handle_input(
+                    transport,
+                    notification_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    cx,
+                )
+                .log_err()
+                .await
             }
         });
         let stderr_input_task = cx.spawn({
@@ -197,36 +248,84 @@ impl Client {
             .log_err()
         });
 
-        Ok(Self {
+        // Servers may check that the client is still alive at any time.
+        request_handlers.lock().insert(
+            types::RequestType::Ping.as_str(),
+            Box::new(|_, _| async { Ok(Value::Object(Default::default())) }.boxed_local()),
+        );
+
+        Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
-            name: server_name.into(),
+            name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
             transport,
-        })
+        }
     }
 
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches them
+    /// to the appropriate handlers. It processes requests (which are answered by
+    /// registered handlers), responses (which are matched to pending requests) and
+    /// notifications (which trigger registered handlers).
     async fn handle_input(
         transport: Arc<dyn Transport>,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
 
         while let Some(message) = receiver.next().await {
-            if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
+            if let Ok(request) = serde_json::from_str::<AnyRequest>(&message) {
+                let response = request_handlers
+                    .lock()
+                    .get_mut(request.method.as_str())
+                    .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+                let id = request.id;
+                let method = request.method;
+                let outbound_tx = outbound_tx.clone();
+                cx.spawn(async move |_| {
+                    let (result, error) = match response {
+                        Some(response) => match response.await {
+                            Ok(result) => (Some(result), None),
+                            Err(error) => (
+                                None,
+                                Some(Error {
+                                    code: INTERNAL_ERROR,
+                                    message: error.to_string(),
+                                }),
+                            ),
+                        },
+                        None => (
+                            None,
+                            Some(Error {
+                                code: METHOD_NOT_FOUND,
+                                message: format!("method not found: {method}"),
+                            }),
+                        ),
+                    };
+                    let response = serde_json::to_string(&OutgoingResponse {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id,
+                        result,
+                        error,
+                    })
+                    .unwrap();
+                    outbound_tx.send(response).await.ok();
+                })
+                .detach();
+            } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut() {
                     if let Some(handler) = handlers.remove(&response.id) {
                         handler(Ok(message.to_string()));
@@ -367,6 +466,19 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers a handler for requests the context server sends to the client, whose
+    /// result is sent back as the response.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncApp) -> LocalBoxFuture<'static, Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
+    pub fn has_request_handler(&self, method: &str) -> bool {
+        self.request_handlers.lock().contains_key(method)
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -390,3 +502,46 @@ impl fmt::Debug for Client {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::FakeTransport;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_answering_server_requests(cx: &mut TestAppContext) {
+        let transport = FakeTransport::new();
+        let client = Client::from_transport(
+            ContextServerId("test".into()),
+            "test".into(),
+            Arc::new(transport.clone()),
+            cx.to_async(),
+        );
+        client.on_request("roots/list", |_, _| {
+            async { Ok(serde_json::json!({ "roots": [] })) }.boxed_local()
+        });
+
+        let mut responses = Vec::new();
+        for (id, method) in [
+            (serde_json::json!(1), "roots/list"),
+            (serde_json::json!(2), "ping"),
+            (serde_json::json!("3"), "unknown"),
+        ] {
+            responses.push(transport.request(id, method, Value::Null).await);
+        }
+
+        assert_eq!(
+            responses,
+            [
+                serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "roots": [] } }),
+                serde_json::json!({ "jsonrpc": "2.0", "id": 2, "result": {} }),
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": "3",
+                    "error": { "code": METHOD_NOT_FOUND, "message": "method not found: unknown" }
+                }),
+            ]
+        );
+    }
+}
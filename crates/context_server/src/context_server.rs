@@ -4,15 +4,20 @@ mod extension_context_server;
 pub mod manager;
 pub mod protocol;
 mod registry;
+mod sampling;
 mod transport;
 pub mod types;
 
 use command_palette_hooks::CommandPaletteFilter;
-pub use context_server_settings::{ContextServerSettings, ServerCommand, ServerConfig};
+pub use context_server_settings::{
+    BearerTokenSource, ContextServerSettings, HttpTransportKind, ServerCommand, ServerConfig,
+};
 use gpui::{App, actions};
 
 pub use crate::context_server_tool::ContextServerTool;
 pub use crate::registry::ContextServerFactoryRegistry;
+#[cfg(any(test, feature = "test-support"))]
+pub use crate::transport::FakeTransport;
 
 actions!(context_servers, [Restart]);
 
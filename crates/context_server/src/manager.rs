@@ -17,17 +17,21 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{Result, bail};
+use anyhow::{Context as _, Result, bail};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
+use credentials_provider::CredentialsProvider;
+use futures::FutureExt as _;
+use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
+use http_client::Url;
 use log;
 use parking_lot::RwLock;
 use project::Project;
 use settings::{Settings, SettingsStore};
 use util::ResultExt as _;
 
-use crate::{ContextServerSettings, ServerConfig};
+use crate::transport::{HttpTransport, HttpTransportOptions};
+use crate::{BearerTokenSource, ContextServerSettings, ServerConfig};
 
 use crate::{
     CONTEXT_SERVERS_NAMESPACE, ContextServerFactoryRegistry,
@@ -62,22 +66,39 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncApp) -> Result<()> {
+    pub async fn start(
+        self: Arc<Self>,
+        manager: WeakEntity<ContextServerManager>,
+        cx: &AsyncApp,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let client = if let Some(url) = &self.config.url {
+            self.http_client(url, cx).await?
+        } else if let Some(command) = &self.config.command {
+            Client::new(
+                client::ContextServerId(self.id.clone()),
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
+        } else {
+            bail!("no command or URL specified for server {}", self.id);
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
+        self.initialize(client, manager).await
+    }
 
+    /// Performs the initialization handshake over the given client, making the server usable once
+    /// it completes.
+    async fn initialize(
+        &self,
+        client: Client,
+        manager: WeakEntity<ContextServerManager>,
+    ) -> Result<()> {
         let protocol = crate::protocol::ModelContextProtocol::new(client);
+        self.register_handlers(&protocol, manager);
         let client_info = types::Implementation {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
@@ -94,6 +115,128 @@ impl ContextServer {
         Ok(())
     }
 
+    /// Creates a client that connects to the server at the given URL over HTTP.
+    async fn http_client(&self, url: &str, cx: &AsyncApp) -> Result<Client> {
+        let parsed_url = Url::parse(url)
+            .with_context(|| format!("invalid URL for context server {}", self.id))?;
+        let mut headers = self
+            .config
+            .headers
+            .iter()
+            .flatten()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        match self.config.bearer_token {
+            Some(BearerTokenSource::Keychain) => {
+                let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx))?;
+                let (_, token) = credentials_provider
+                    .read_credentials(url, cx)
+                    .await?
+                    .with_context(|| {
+                        format!(
+                            "no credentials found in the keychain for context server {} at {url}",
+                            self.id
+                        )
+                    })?;
+                let token = String::from_utf8(token).with_context(|| {
+                    format!("invalid bearer token for context server {}", self.id)
+                })?;
+                headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+            }
+            None => {}
+        }
+
+        log::info!("connecting to context server {} at {url}", self.id);
+        let http_client = cx.update(|cx| cx.http_client())?;
+        let transport = HttpTransport::new(
+            HttpTransportOptions {
+                url: parsed_url,
+                headers,
+                kind: self.config.transport,
+            },
+            http_client,
+            cx,
+        );
+        Ok(Client::from_transport(
+            client::ContextServerId(self.id.clone()),
+            self.id.clone(),
+            Arc::new(transport),
+            cx.clone(),
+        ))
+    }
+
+    /// Registers handlers for the requests and notifications the server sends to the client.
+    fn register_handlers(
+        &self,
+        protocol: &crate::protocol::ModelContextProtocol,
+        manager: WeakEntity<ContextServerManager>,
+    ) {
+        protocol.on_request(types::RequestType::ListRoots.as_str(), {
+            let manager = manager.clone();
+            move |_, cx| {
+                let manager = manager.clone();
+                async move {
+                    let roots = manager.read_with(&cx, |manager, cx| manager.roots(cx))?;
+                    Ok(serde_json::to_value(types::ListRootsResponse {
+                        roots,
+                        meta: None,
+                    })?)
+                }
+                .boxed_local()
+            }
+        });
+
+        protocol.on_request(types::RequestType::CreateMessage.as_str(), {
+            let server_id = self.id.clone();
+            move |params, mut cx| {
+                let server_id = server_id.clone();
+                async move { crate::sampling::create_message(server_id, params, &mut cx).await }
+                    .boxed_local()
+            }
+        });
+
+        let list_changed_events: [(types::NotificationType, fn(Arc<str>) -> Event); 3] = [
+            (types::NotificationType::ToolsListChanged, |server_id| {
+                Event::ToolsChanged { server_id }
+            }),
+            (types::NotificationType::PromptsListChanged, |server_id| {
+                Event::PromptsChanged { server_id }
+            }),
+            (types::NotificationType::ResourcesListChanged, |server_id| {
+                Event::ResourcesChanged { server_id }
+            }),
+        ];
+        protocol.on_notification(types::NotificationType::ResourcesUpdated.as_str(), {
+            let server_id = self.id.clone();
+            let manager = manager.clone();
+            move |params, mut cx| {
+                let Some(params) =
+                    serde_json::from_value::<types::ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                manager
+                    .update(&mut cx, |_, cx| {
+                        cx.emit(Event::ResourceUpdated {
+                            server_id: server_id.clone(),
+                            uri: params.uri,
+                        })
+                    })
+                    .log_err();
+            }
+        });
+
+        for (notification, event) in list_changed_events {
+            let server_id = self.id.clone();
+            let manager = manager.clone();
+            protocol.on_notification(notification.as_str(), move |_, mut cx| {
+                manager
+                    .update(&mut cx, |_, cx| cx.emit(event(server_id.clone())))
+                    .log_err();
+            });
+        }
+    }
+
     pub fn stop(&self) -> Result<()> {
         let mut client = self.client.write();
         if let Some(protocol) = client.take() {
@@ -113,8 +256,29 @@ pub struct ContextServerManager {
 }
 
 pub enum Event {
-    ServerStarted { server_id: Arc<str> },
-    ServerStopped { server_id: Arc<str> },
+    ServerStarted {
+        server_id: Arc<str>,
+    },
+    ServerStopped {
+        server_id: Arc<str>,
+    },
+    /// The server announced that the tools it offers changed.
+    ToolsChanged {
+        server_id: Arc<str>,
+    },
+    /// The server announced that the prompts it offers changed.
+    PromptsChanged {
+        server_id: Arc<str>,
+    },
+    /// The server announced that the resources it offers changed.
+    ResourcesChanged {
+        server_id: Arc<str>,
+    },
+    /// A resource the client subscribed to changed.
+    ResourceUpdated {
+        server_id: Arc<str>,
+        uri: Url,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -133,6 +297,12 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&project, |this, _project, event, _cx| match event {
+                    project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                        this.roots_changed();
+                    }
+                    _ => {}
+                }),
             ],
             project,
             registry,
@@ -174,6 +344,30 @@ impl ContextServerManager {
         }
     }
 
+    /// The roots of the project, which servers are told about to know which files they may
+    /// operate on.
+    fn roots(&self, cx: &App) -> Vec<types::Root> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(types::Root {
+                    uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn roots_changed(&self) {
+        for server in self.running_servers() {
+            if let Some(client) = server.client() {
+                client.notify_roots_list_changed().log_err();
+            }
+        }
+    }
+
     pub fn get_server(&self, id: &str) -> Option<Arc<ContextServer>> {
         self.servers
             .get(id)
@@ -188,7 +382,31 @@ impl ContextServerManager {
     ) -> Task<anyhow::Result<()>> {
         cx.spawn(async move |this, cx| {
             let id = server.id.clone();
-            server.start(&cx).await?;
+            server.start(this.clone(), &cx).await?;
+            this.update(cx, |_, cx| cx.emit(Event::ServerStarted { server_id: id }))?;
+            Ok(())
+        })
+    }
+
+    /// Starts a server that is reached through the given fake transport, rather than one from the
+    /// settings.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn start_fake_server(
+        &mut self,
+        id: Arc<str>,
+        transport: crate::FakeTransport,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let server = Arc::new(ContextServer::new(id.clone(), Arc::default()));
+        self.servers.insert(id.clone(), server.clone());
+        cx.spawn(async move |this, cx| {
+            let client = Client::from_transport(
+                client::ContextServerId(id.clone()),
+                id.clone(),
+                Arc::new(transport),
+                cx.clone(),
+            );
+            server.initialize(client, this.clone()).await?;
             this.update(cx, |_, cx| cx.emit(Event::ServerStarted { server_id: id }))?;
             Ok(())
         })
@@ -217,7 +435,7 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                new_server.clone().start(this.clone(), &cx).await?;
                 this.update(cx, |this, cx| {
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
@@ -267,7 +485,7 @@ impl ContextServerManager {
             registry.read_with(cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.url.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -307,7 +525,7 @@ impl ContextServerManager {
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
+            if server.start(this.clone(), &cx).await.log_err().is_some() {
                 this.update(cx, |_, cx| cx.emit(Event::ServerStarted { server_id: id }))?;
             }
         }
@@ -7,6 +7,9 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use futures::future::LocalBoxFuture;
+use gpui::AsyncApp;
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
@@ -20,6 +23,27 @@ impl ModelContextProtocol {
         Self { inner }
     }
 
+    /// Registers a handler for requests the server sends to the client.
+    ///
+    /// Handlers for sampling and roots must be registered before initializing, so that the
+    /// matching capabilities are advertised to the server.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static
+            + Send
+            + FnMut(serde_json::Value, AsyncApp) -> LocalBoxFuture<'static, Result<serde_json::Value>>,
+    {
+        self.inner.on_request(method, f);
+    }
+
+    /// Registers a handler for notifications the server sends to the client.
+    pub fn on_notification<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(serde_json::Value, AsyncApp),
+    {
+        self.inner.on_notification(method, f);
+    }
+
     fn supported_protocols() -> Vec<types::ProtocolVersion> {
         vec![types::ProtocolVersion(
             types::LATEST_PROTOCOL_VERSION.to_string(),
@@ -34,8 +58,16 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                sampling: self
+                    .inner
+                    .has_request_handler(types::RequestType::CreateMessage.as_str())
+                    .then(|| serde_json::json!({})),
+                roots: self
+                    .inner
+                    .has_request_handler(types::RequestType::ListRoots.as_str())
+                    .then_some(types::RootsCapabilities {
+                        list_changed: Some(true),
+                    }),
             },
             meta: None,
             client_info,
@@ -136,6 +168,67 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// List the MCP resource templates, whose URIs are filled in with arguments.
+    pub async fn list_resource_templates(&self) -> Result<types::ListResourceTemplatesResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let response: types::ListResourceTemplatesResponse = self
+            .inner
+            .request(
+                types::RequestType::ListResourceTemplates.as_str(),
+                serde_json::json!({}),
+            )
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Reads the contents of the MCP resource with the given URI.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Whether the server notifies the client when a resource it subscribed to changes.
+    pub fn supports_resource_subscriptions(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// Asks the server to send `notifications/resources/updated` when the resource changes.
+    pub async fn subscribe_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn unsubscribe_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesUnsubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -224,6 +317,14 @@ impl InitializedContextServerProtocol {
 }
 
 impl InitializedContextServerProtocol {
+    /// Lets the server know that the client's roots changed.
+    pub fn notify_roots_list_changed(&self) -> Result<()> {
+        self.inner.notify(
+            types::NotificationType::RootsListChanged.as_str(),
+            serde_json::json!({}),
+        )
+    }
+
     pub async fn request<R: serde::de::DeserializeOwned>(
         &self,
         method: &str,
@@ -0,0 +1,136 @@
+//! Answers the sampling requests of context servers, which ask the client to generate a message
+//! with a language model on their behalf.
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::StreamExt as _;
+use gpui::{AsyncApp, PromptLevel};
+use language_model::{
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, MessageContent, Role,
+};
+use serde_json::Value;
+
+use crate::types;
+
+/// Generates a message with the default language model, once the user approved the request.
+pub(crate) async fn create_message(
+    server_id: Arc<str>,
+    params: Value,
+    cx: &mut AsyncApp,
+) -> Result<Value> {
+    let params = serde_json::from_value::<types::CreateMessageRequest>(params)
+        .context("invalid sampling request")?;
+
+    let model = cx
+        .update(|cx| LanguageModelRegistry::read_global(cx).default_model())?
+        .context("no language model is configured")?
+        .model;
+
+    let approved = request_approval(&server_id, &params, &model.name().0, cx).await?;
+    if !approved {
+        return Err(anyhow!("the user declined the sampling request"));
+    }
+
+    log::info!(
+        "context server {server_id} is sampling from {}",
+        model.id().0
+    );
+    let mut stream = model
+        .stream_completion_text(language_model_request(params), cx)
+        .await?;
+    let mut text = String::new();
+    while let Some(chunk) = stream.stream.next().await {
+        text.push_str(&chunk?);
+    }
+
+    let result = types::CreateMessageResult {
+        role: types::Role::Assistant,
+        content: types::MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model.id().0.to_string(),
+        stop_reason: Some("endTurn".to_string()),
+    };
+    Ok(serde_json::to_value(result)?)
+}
+
+/// Asks the user to approve a sampling request in the active window, showing what will be sent
+/// to the model.
+async fn request_approval(
+    server_id: &str,
+    params: &types::CreateMessageRequest,
+    model_name: &str,
+    cx: &mut AsyncApp,
+) -> Result<bool> {
+    let window = cx
+        .update(|cx| cx.active_window())?
+        .context("no window to ask for approval of the sampling request")?;
+
+    let message = format!("Context server \"{server_id}\" wants to use {model_name}");
+    let mut detail = String::new();
+    if let Some(system_prompt) = &params.system_prompt {
+        detail.push_str(&format!("System: {system_prompt}\n\n"));
+    }
+    for message in &params.messages {
+        let role = match message.role {
+            types::Role::User => "User",
+            types::Role::Assistant => "Assistant",
+        };
+        let text = match &message.content {
+            types::MessageContent::Text { text, .. } => text.as_str(),
+            types::MessageContent::Image { .. } => "[image]",
+            types::MessageContent::Resource { .. } => "[resource]",
+        };
+        detail.push_str(&format!("{role}: {text}\n\n"));
+    }
+
+    let answer = window.update(cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Info,
+            &message,
+            Some(detail.trim_end()),
+            &["Allow", "Deny"],
+            cx,
+        )
+    })?;
+    Ok(answer.await? == 0)
+}
+
+fn language_model_request(params: types::CreateMessageRequest) -> LanguageModelRequest {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = params.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![MessageContent::Text(system_prompt)],
+            cache: false,
+        });
+    }
+    for message in params.messages {
+        let role = match message.role {
+            types::Role::User => Role::User,
+            types::Role::Assistant => Role::Assistant,
+        };
+        let content = match message.content {
+            types::MessageContent::Text { text, .. } => MessageContent::Text(text),
+            types::MessageContent::Image { .. } | types::MessageContent::Resource { .. } => {
+                log::warn!("ignoring unsupported content in sampling request");
+                continue;
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role,
+            content: vec![content],
+            cache: false,
+        });
+    }
+
+    LanguageModelRequest {
+        messages,
+        stop: params.stop_sequences.unwrap_or_default(),
+        temperature: params.temperature.map(|temperature| temperature as f32),
+        max_tokens: Some(params.max_tokens),
+        ..Default::default()
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(any(test, feature = "test-support"))]
+mod fake_transport;
+mod http_transport;
 mod stdio_transport;
 
 use std::pin::Pin;
@@ -6,6 +9,9 @@ use anyhow::Result;
 use async_trait::async_trait;
 use futures::Stream;
 
+#[cfg(any(test, feature = "test-support"))]
+pub use fake_transport::*;
+pub use http_transport::*;
 pub use stdio_transport::*;
 
 #[async_trait]
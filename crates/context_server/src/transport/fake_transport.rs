@@ -0,0 +1,118 @@
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{Stream, channel::oneshot};
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use smol::channel;
+
+use crate::client::METHOD_NOT_FOUND;
+use crate::transport::Transport;
+
+type RequestHandler = Box<dyn Send + Fn(Value) -> Value>;
+
+/// A transport to a server that lives in the test itself, which answers requests with the
+/// registered handlers and can send notifications and requests to the client.
+#[derive(Clone)]
+pub struct FakeTransport {
+    request_handlers: Arc<Mutex<HashMap<String, RequestHandler>>>,
+    /// Senders for the client's responses to requests, by the requests' serialized ids.
+    pending_responses: Arc<Mutex<HashMap<String, oneshot::Sender<Value>>>>,
+    incoming_tx: channel::Sender<String>,
+    incoming_rx: channel::Receiver<String>,
+}
+
+impl Default for FakeTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeTransport {
+    pub fn new() -> Self {
+        let (incoming_tx, incoming_rx) = channel::unbounded();
+        Self {
+            request_handlers: Arc::default(),
+            pending_responses: Arc::default(),
+            incoming_tx,
+            incoming_rx,
+        }
+    }
+
+    /// Answers requests for the given method with the result of the handler, which is passed the
+    /// parameters of the request. Replaces any previous handler for the method.
+    pub fn on_request(&self, method: &str, handler: impl 'static + Send + Fn(Value) -> Value) {
+        self.request_handlers
+            .lock()
+            .insert(method.to_string(), Box::new(handler));
+    }
+
+    /// Sends a notification to the client.
+    pub fn notify(&self, method: &str, params: Value) {
+        let notification = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.incoming_tx
+            .try_send(notification.to_string())
+            .expect("fake transport was closed");
+    }
+
+    /// Sends a request to the client, returning its response. The parameters are left out of the
+    /// request when they're null.
+    pub async fn request(&self, id: Value, method: &str, params: Value) -> Value {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.pending_responses
+            .lock()
+            .insert(id.to_string(), response_tx);
+        let mut request = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if !params.is_null() {
+            request["params"] = params;
+        }
+        self.incoming_tx
+            .try_send(request.to_string())
+            .expect("fake transport was closed");
+        response_rx.await.expect("client didn't respond")
+    }
+}
+
+#[async_trait]
+impl Transport for FakeTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let message = serde_json::from_str::<Value>(&message)?;
+        // Notifications don't need an answer.
+        let Some(id) = message.get("id") else {
+            return Ok(());
+        };
+        let Some(method) = message["method"].as_str() else {
+            let response_tx = self.pending_responses.lock().remove(&id.to_string());
+            if let Some(response_tx) = response_tx {
+                response_tx.send(message).ok();
+            }
+            return Ok(());
+        };
+
+        let response = match self.request_handlers.lock().get(method) {
+            Some(handler) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": handler(message.get("params").cloned().unwrap_or_default()),
+            }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": METHOD_NOT_FOUND, "message": format!("method not found: {method}") },
+            }),
+        };
+        self.incoming_tx.send(response.to_string()).await?;
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.incoming_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(futures::stream::pending())
+    }
+}
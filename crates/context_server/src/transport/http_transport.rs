@@ -0,0 +1,871 @@
+use std::{mem, pin::Pin, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use async_trait::async_trait;
+use futures::{
+    AsyncBufReadExt as _, AsyncReadExt as _, FutureExt as _, Stream, StreamExt as _,
+    future::BoxFuture, io::BufReader, stream::FuturesUnordered,
+};
+use gpui::{AsyncApp, BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method, Request, Response, StatusCode, Url};
+use parking_lot::Mutex;
+use postage::{prelude::Stream as _, watch};
+use serde::Deserialize;
+use serde_json::Value;
+use smol::channel;
+
+use crate::HttpTransportKind;
+use crate::transport::Transport;
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+const JSON_CONTENT_TYPE: &str = "application/json";
+const INITIALIZE_METHOD: &str = "initialize";
+const INITIALIZED_METHOD: &str = "notifications/initialized";
+
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How to reach a context server over HTTP.
+#[derive(Clone, Debug)]
+pub struct HttpTransportOptions {
+    pub url: Url,
+    /// Headers sent with every request, including any `Authorization` header.
+    pub headers: Vec<(String, String)>,
+    /// The transport to use, or `None` to try the streamable HTTP transport first and fall back
+    /// to the legacy HTTP with SSE transport.
+    pub kind: Option<HttpTransportKind>,
+}
+
+/// Connects to a context server over HTTP.
+///
+/// With the streamable HTTP transport, every message is posted to the server's URL, which
+/// answers with either a JSON message or an event stream of messages. Messages the server
+/// initiates arrive over an event stream that is kept open with a `GET` request. Event streams
+/// that are cut off are resumed from their last event, and when the server forgets the session,
+/// a new one is started by replaying the `initialize` handshake.
+///
+/// With the legacy HTTP with SSE transport, all messages from the server arrive over an event
+/// stream opened at its URL, and messages to it are posted to an endpoint the server announces
+/// on that stream.
+pub struct HttpTransport {
+    connection: Arc<Connection>,
+    inbound_rx: channel::Receiver<String>,
+    _readers_task: Task<()>,
+}
+
+struct Connection {
+    http_client: Arc<dyn HttpClient>,
+    options: HttpTransportOptions,
+    executor: BackgroundExecutor,
+    inbound_tx: channel::Sender<String>,
+    /// Event streams being read, which are all driven by the transport's readers task.
+    readers_tx: channel::Sender<BoxFuture<'static, ()>>,
+    state: Mutex<ConnectionState>,
+    /// The endpoint messages are posted to with the legacy transport.
+    sse_endpoint_tx: Mutex<watch::Sender<Option<Url>>>,
+    sse_endpoint_rx: watch::Receiver<Option<Url>>,
+}
+
+#[derive(Default)]
+struct ConnectionState {
+    kind: Option<HttpTransportKind>,
+    session_id: Option<String>,
+    /// Whether the server has accepted a message, after which failing to send one is no longer
+    /// treated as failing to connect.
+    connected: bool,
+    /// Whether an event stream for messages initiated by the server is open.
+    listening: bool,
+    /// The `initialize` request and `initialized` notification, replayed to start a new session.
+    handshake: Vec<String>,
+}
+
+/// The parts of an outgoing JSON-RPC message that the transport needs to know about.
+#[derive(Default, Deserialize)]
+struct OutgoingMessage {
+    id: Option<Value>,
+    method: Option<String>,
+}
+
+/// Tracks an event stream across reconnections.
+#[derive(Default)]
+struct EventStream {
+    last_event_id: Option<String>,
+    /// The id of the request whose response is expected on the stream.
+    pending_request: Option<Value>,
+    received_events: usize,
+}
+
+impl HttpTransport {
+    pub fn new(
+        options: HttpTransportOptions,
+        http_client: Arc<dyn HttpClient>,
+        cx: &AsyncApp,
+    ) -> Self {
+        let executor = cx.background_executor().clone();
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let (readers_tx, readers_rx) = channel::unbounded::<BoxFuture<'static, ()>>();
+        let (sse_endpoint_tx, sse_endpoint_rx) = watch::channel();
+
+        let readers_task = executor.spawn(async move {
+            let mut readers_rx = readers_rx.fuse();
+            let mut readers = FuturesUnordered::new();
+            loop {
+                futures::select_biased! {
+                    reader = readers_rx.next() => match reader {
+                        Some(reader) => readers.push(reader),
+                        None => break,
+                    },
+                    _ = readers.select_next_some() => {}
+                }
+            }
+        });
+
+        Self {
+            connection: Arc::new(Connection {
+                http_client,
+                state: Mutex::new(ConnectionState {
+                    kind: options.kind,
+                    ..Default::default()
+                }),
+                options,
+                executor,
+                inbound_tx,
+                readers_tx,
+                sse_endpoint_tx: Mutex::new(sse_endpoint_tx),
+                sse_endpoint_rx,
+            }),
+            inbound_rx,
+            _readers_task: readers_task,
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        match self.connection.clone().send(message).await {
+            Ok(()) => Ok(()),
+            // Once connected, keep the client running when a message can't be sent, so that it
+            // recovers when the server becomes reachable again. The request will time out.
+            Err(error) if self.connection.state.lock().connected => {
+                log::error!(
+                    "failed to send message to context server at {}: {error:#}",
+                    self.connection.options.url
+                );
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(futures::stream::pending())
+    }
+}
+
+impl Drop for HttpTransport {
+    fn drop(&mut self) {
+        let Some(session_id) = self.connection.state.lock().session_id.clone() else {
+            return;
+        };
+        // Let the server know that the session is over, so it can release its resources.
+        let request = self
+            .connection
+            .request(Method::DELETE, &self.connection.options.url)
+            .header(SESSION_ID_HEADER, session_id)
+            .body(AsyncBody::empty());
+        if let Ok(request) = request {
+            let http_client = self.connection.http_client.clone();
+            self.connection
+                .executor
+                .spawn(async move {
+                    http_client.send(request).await.ok();
+                })
+                .detach();
+        }
+    }
+}
+
+impl Connection {
+    async fn send(self: Arc<Self>, message: String) -> Result<()> {
+        let outgoing = serde_json::from_str::<OutgoingMessage>(&message).unwrap_or_default();
+        match outgoing.method.as_deref() {
+            Some(INITIALIZE_METHOD) => self.state.lock().handshake = vec![message.clone()],
+            Some(INITIALIZED_METHOD) => self.state.lock().handshake.push(message.clone()),
+            _ => {}
+        }
+
+        let kind = self.state.lock().kind;
+        match kind {
+            Some(HttpTransportKind::Sse) => self.send_sse(message).await?,
+            Some(HttpTransportKind::StreamableHttp) => {
+                let mut response = self.post(&message).await?;
+                let session_expired = response.status() == StatusCode::NOT_FOUND
+                    && self.state.lock().session_id.is_some();
+                if session_expired {
+                    log::info!(
+                        "context server at {} ended the session, starting a new one",
+                        self.options.url
+                    );
+                    self.start_new_session().await?;
+                    response = self.post(&message).await?;
+                }
+                self.handle_response(response, outgoing.id).await?;
+            }
+            None => {
+                let response = self.post(&message).await?;
+                if response.status().is_client_error() {
+                    log::info!(
+                        "context server at {} doesn't support the streamable HTTP transport ({}), falling back to SSE",
+                        self.options.url,
+                        response.status()
+                    );
+                    self.state.lock().kind = Some(HttpTransportKind::Sse);
+                    self.send_sse(message).await?;
+                } else {
+                    self.state.lock().kind = Some(HttpTransportKind::StreamableHttp);
+                    self.handle_response(response, outgoing.id).await?;
+                }
+            }
+        }
+
+        self.state.lock().connected = true;
+        if outgoing.method.as_deref() == Some(INITIALIZED_METHOD)
+            && self.state.lock().kind == Some(HttpTransportKind::StreamableHttp)
+        {
+            self.listen();
+        }
+        Ok(())
+    }
+
+    /// Posts a message with the streamable HTTP transport.
+    async fn post(&self, message: &str) -> Result<Response<AsyncBody>> {
+        self.send_request(|| {
+            let mut request = self
+                .request(Method::POST, &self.options.url)
+                .header("Content-Type", JSON_CONTENT_TYPE)
+                .header(
+                    "Accept",
+                    format!("{JSON_CONTENT_TYPE}, {EVENT_STREAM_CONTENT_TYPE}"),
+                );
+            if let Some(session_id) = self.state.lock().session_id.clone() {
+                request = request.header(SESSION_ID_HEADER, session_id);
+            }
+            Ok(request.body(AsyncBody::from(message.to_string()))?)
+        })
+        .await
+    }
+
+    /// Handles the server's response to a message posted with the streamable HTTP transport.
+    async fn handle_response(
+        self: &Arc<Self>,
+        response: Response<AsyncBody>,
+        request_id: Option<Value>,
+    ) -> Result<()> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = read_body(response).await.unwrap_or_default();
+            bail!("context server responded with {status}: {body}");
+        }
+        if let Some(session_id) = header(&response, SESSION_ID_HEADER) {
+            self.state.lock().session_id = Some(session_id);
+        }
+
+        let content_type = header(&response, "Content-Type").unwrap_or_default();
+        if content_type.starts_with(EVENT_STREAM_CONTENT_TYPE) {
+            let this = self.clone();
+            let body = response.into_body();
+            self.spawn_reader(async move {
+                this.read_response_stream(body, request_id).await;
+            });
+        } else if content_type.starts_with(JSON_CONTENT_TYPE) {
+            let body = read_body(response).await?;
+            self.receive_messages(&body, &mut EventStream::default());
+        }
+        Ok(())
+    }
+
+    /// Starts a new session after the server forgot the current one, by replaying the handshake.
+    async fn start_new_session(self: &Arc<Self>) -> Result<()> {
+        let handshake = {
+            let mut state = self.state.lock();
+            state.session_id = None;
+            state.handshake.clone()
+        };
+        for message in handshake {
+            let response = self.post(&message).await?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = read_body(response).await.unwrap_or_default();
+                bail!("context server responded with {status}: {body}");
+            }
+            if let Some(session_id) = header(&response, SESSION_ID_HEADER) {
+                self.state.lock().session_id = Some(session_id);
+            }
+            // The client already received the responses to the original handshake.
+            read_body(response).await?;
+        }
+        Ok(())
+    }
+
+    /// Reads the event stream a request was answered with, resuming it from its last event if it
+    /// ends before the response arrives.
+    async fn read_response_stream(self: Arc<Self>, body: AsyncBody, request_id: Option<Value>) {
+        let mut stream = EventStream {
+            pending_request: request_id,
+            ..Default::default()
+        };
+        if let Err(error) = self.read_events(body, &mut stream).await {
+            log::warn!(
+                "event stream from context server at {} failed: {error:#}",
+                self.options.url
+            );
+        }
+
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        for _ in 0..MAX_RECONNECT_ATTEMPTS {
+            if stream.pending_request.is_none() || stream.last_event_id.is_none() {
+                return;
+            }
+            self.executor.timer(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            match self.open_event_stream(stream.last_event_id.clone()).await {
+                Ok(Some(body)) => {
+                    if let Err(error) = self.read_events(body, &mut stream).await {
+                        log::warn!(
+                            "resumed event stream from context server at {} failed: {error:#}",
+                            self.options.url
+                        );
+                    }
+                }
+                Ok(None) => return,
+                Err(error) => log::warn!(
+                    "failed to resume event stream from context server at {}: {error:#}",
+                    self.options.url
+                ),
+            }
+        }
+    }
+
+    /// Keeps an event stream open for messages initiated by the server, if it supports that.
+    fn listen(self: &Arc<Self>) {
+        if mem::replace(&mut self.state.lock().listening, true) {
+            return;
+        }
+        let this = self.clone();
+        self.spawn_reader(async move {
+            let mut stream = EventStream::default();
+            this.keep_stream_open(&mut stream, |this, last_event_id| {
+                async move { this.open_event_stream(last_event_id).await }.boxed()
+            })
+            .await;
+            this.state.lock().listening = false;
+        });
+    }
+
+    /// Opens an event stream for messages initiated by the server, or to resume a stream from
+    /// the given event. Returns `None` if the server doesn't offer one.
+    async fn open_event_stream(&self, last_event_id: Option<String>) -> Result<Option<AsyncBody>> {
+        let response = self
+            .send_request(|| {
+                let mut request = self
+                    .request(Method::GET, &self.options.url)
+                    .header("Accept", EVENT_STREAM_CONTENT_TYPE);
+                if let Some(session_id) = self.state.lock().session_id.clone() {
+                    request = request.header(SESSION_ID_HEADER, session_id);
+                }
+                if let Some(last_event_id) = last_event_id.clone() {
+                    request = request.header(LAST_EVENT_ID_HEADER, last_event_id);
+                }
+                Ok(request.body(AsyncBody::empty())?)
+            })
+            .await?;
+        match response.status() {
+            StatusCode::METHOD_NOT_ALLOWED => Ok(None),
+            status if status.is_success() => Ok(Some(response.into_body())),
+            status => {
+                let body = read_body(response).await.unwrap_or_default();
+                Err(anyhow!("context server responded with {status}: {body}"))
+            }
+        }
+    }
+
+    /// Posts a message with the legacy transport, whose response arrives on the event stream.
+    async fn send_sse(self: &Arc<Self>, message: String) -> Result<()> {
+        self.connect_sse();
+        let endpoint = self.sse_endpoint().await?;
+        let response = self
+            .send_request(|| {
+                Ok(self
+                    .request(Method::POST, &endpoint)
+                    .header("Content-Type", JSON_CONTENT_TYPE)
+                    .body(AsyncBody::from(message.clone()))?)
+            })
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = read_body(response).await.unwrap_or_default();
+            bail!("context server responded with {status}: {body}");
+        }
+        Ok(())
+    }
+
+    /// Opens the legacy transport's event stream, reconnecting whenever it closes.
+    fn connect_sse(self: &Arc<Self>) {
+        if mem::replace(&mut self.state.lock().listening, true) {
+            return;
+        }
+        let this = self.clone();
+        self.spawn_reader(async move {
+            let mut stream = EventStream::default();
+            this.keep_stream_open(&mut stream, |this, last_event_id| {
+                async move {
+                    // The endpoint belongs to the stream's session, so wait for a new one.
+                    *this.sse_endpoint_tx.lock().borrow_mut() = None;
+                    this.open_event_stream(last_event_id).await
+                }
+                .boxed()
+            })
+            .await;
+            this.state.lock().listening = false;
+        });
+    }
+
+    async fn sse_endpoint(&self) -> Result<Url> {
+        let mut endpoint_rx = self.sse_endpoint_rx.clone();
+        let mut timeout = self.executor.timer(ENDPOINT_TIMEOUT).fuse();
+        loop {
+            if let Some(endpoint) = endpoint_rx.borrow().clone() {
+                return Ok(endpoint);
+            }
+            futures::select_biased! {
+                endpoint = endpoint_rx.recv().fuse() => {
+                    endpoint.context("context server event stream closed")?;
+                }
+                _ = timeout => bail!("context server didn't announce an endpoint to post messages to"),
+            }
+        }
+    }
+
+    /// Reads events from streams opened with `open`, reopening them from their last event when
+    /// they close, until the server no longer offers a stream or it can't be reached.
+    async fn keep_stream_open(
+        self: &Arc<Self>,
+        stream: &mut EventStream,
+        open: impl Fn(Arc<Self>, Option<String>) -> BoxFuture<'static, Result<Option<AsyncBody>>>,
+    ) {
+        let mut failed_attempts = 0;
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            match open(self.clone(), stream.last_event_id.clone()).await {
+                Ok(Some(body)) => {
+                    let received_events = stream.received_events;
+                    if let Err(error) = self.read_events(body, stream).await {
+                        log::warn!(
+                            "event stream from context server at {} failed: {error:#}",
+                            self.options.url
+                        );
+                    }
+                    if stream.received_events > received_events {
+                        failed_attempts = 0;
+                        delay = INITIAL_RECONNECT_DELAY;
+                    }
+                }
+                Ok(None) => return,
+                Err(error) => {
+                    failed_attempts += 1;
+                    log::warn!(
+                        "failed to open event stream from context server at {}: {error:#}",
+                        self.options.url
+                    );
+                    if failed_attempts >= MAX_RECONNECT_ATTEMPTS {
+                        return;
+                    }
+                }
+            }
+            self.executor.timer(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn read_events(&self, body: AsyncBody, stream: &mut EventStream) -> Result<()> {
+        let mut lines = BufReader::new(body).lines();
+        let mut parser = SseParser::default();
+        while let Some(line) = lines.next().await {
+            let Some(event) = parser.push_line(&line?) else {
+                continue;
+            };
+            stream.received_events += 1;
+            if event.id.is_some() {
+                stream.last_event_id = event.id;
+            }
+            match event.event.as_str() {
+                "endpoint" => {
+                    let endpoint = self
+                        .options
+                        .url
+                        .join(event.data.trim())
+                        .context("invalid endpoint announced by context server")?;
+                    // Messages are posted with the configured headers, which can hold credentials
+                    // that mustn't be sent anywhere but the server they were configured for.
+                    if endpoint.origin() != self.options.url.origin() {
+                        log::warn!(
+                            "ignoring endpoint {endpoint} announced by context server at {}, \
+                             since it isn't on the same origin",
+                            self.options.url
+                        );
+                        continue;
+                    }
+                    *self.sse_endpoint_tx.lock().borrow_mut() = Some(endpoint);
+                }
+                "message" => self.receive_messages(&event.data, stream),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes on a message or batch of messages from the server.
+    fn receive_messages(&self, text: &str, stream: &mut EventStream) {
+        let messages = match serde_json::from_str::<Value>(text) {
+            Ok(Value::Array(messages)) => messages,
+            Ok(message) => vec![message],
+            Err(error) => {
+                log::warn!(
+                    "invalid message from context server at {}: {error}",
+                    self.options.url
+                );
+                return;
+            }
+        };
+        for message in messages {
+            let is_response = message.get("result").is_some() || message.get("error").is_some();
+            if is_response && message.get("id") == stream.pending_request.as_ref() {
+                stream.pending_request = None;
+            }
+            self.inbound_tx.try_send(message.to_string()).ok();
+        }
+    }
+
+    fn request(&self, method: Method, url: &Url) -> http_client::http::request::Builder {
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        for (name, value) in &self.options.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+    }
+
+    /// Sends a request, retrying with a backoff while the server can't be reached.
+    async fn send_request(
+        &self,
+        build_request: impl Fn() -> Result<Request<AsyncBody>>,
+    ) -> Result<Response<AsyncBody>> {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.http_client.send(build_request()?).await {
+                Ok(response) => return Ok(response),
+                Err(error) if attempt < MAX_RECONNECT_ATTEMPTS => {
+                    log::warn!(
+                        "failed to reach context server at {} (attempt {attempt}): {error:#}",
+                        self.options.url
+                    );
+                    self.executor.timer(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn spawn_reader(&self, reader: impl Future<Output = ()> + Send + 'static) {
+        self.readers_tx.try_send(reader.boxed()).ok();
+    }
+}
+
+fn header(response: &Response<AsyncBody>, name: &str) -> Option<String> {
+    let value = response.headers().get(name)?.to_str().ok()?;
+    Some(value.to_string())
+}
+
+async fn read_body(response: Response<AsyncBody>) -> Result<String> {
+    let mut body = String::new();
+    response.into_body().read_to_string(&mut body).await?;
+    Ok(body)
+}
+
+#[derive(Debug, PartialEq)]
+struct SseEvent {
+    id: Option<String>,
+    event: String,
+    data: String,
+}
+
+/// Parses server-sent events line by line.
+#[derive(Default)]
+struct SseParser {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+}
+
+impl SseParser {
+    /// Takes a line without its line ending, returning an event when the line completes one.
+    fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let data = self.data.take();
+            let event = self.event.take();
+            let id = self.id.take();
+            return Some(SseEvent {
+                id,
+                event: event.unwrap_or_else(|| "message".to_string()),
+                data: data?,
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "event" => self.event = Some(value.to_string()),
+            "id" => self.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt as _;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#;
+    const INITIALIZED: &str = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+    const REQUEST: &str = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#;
+
+    fn response(id: u64) -> String {
+        format!(r#"{{"id":{id},"jsonrpc":"2.0","result":{{}}}}"#)
+    }
+
+    fn options(kind: Option<HttpTransportKind>) -> HttpTransportOptions {
+        HttpTransportOptions {
+            url: Url::parse("http://localhost:3000/mcp").unwrap(),
+            headers: vec![("Authorization".into(), "Bearer secret".into())],
+            kind,
+        }
+    }
+
+    async fn request_body(request: Request<AsyncBody>) -> Result<String> {
+        let mut body = String::new();
+        request.into_body().read_to_string(&mut body).await?;
+        Ok(body)
+    }
+
+    fn request_id(body: &str) -> Option<u64> {
+        let message = serde_json::from_str::<Value>(body).ok()?;
+        message.get("id")?.as_u64()
+    }
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        let lines = [
+            ": keep-alive",
+            "",
+            "id: 1",
+            "event: endpoint",
+            "data: /messages?session=a",
+            "",
+            "data: {\"a\":",
+            "data:1}",
+            "",
+        ];
+        let events = lines
+            .into_iter()
+            .filter_map(|line| parser.push_line(line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                SseEvent {
+                    id: Some("1".into()),
+                    event: "endpoint".into(),
+                    data: "/messages?session=a".into(),
+                },
+                SseEvent {
+                    id: None,
+                    event: "message".into(),
+                    data: "{\"a\":\n1}".into(),
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http_transport(cx: &mut TestAppContext) {
+        let sessions = Arc::new(AtomicUsize::new(0));
+        let http_client = FakeHttpClient::create({
+            let sessions = sessions.clone();
+            move |request| {
+                let sessions = sessions.clone();
+                async move {
+                    assert_eq!(request.headers()["Authorization"], "Bearer secret");
+                    if request.method() == Method::GET {
+                        return Ok(Response::builder().status(405).body(AsyncBody::empty())?);
+                    }
+                    if request.method() == Method::DELETE {
+                        return Ok(Response::builder().status(200).body(AsyncBody::empty())?);
+                    }
+
+                    let session_id = request
+                        .headers()
+                        .get(SESSION_ID_HEADER)
+                        .map(|value| value.to_str().unwrap().to_string());
+                    let body = request_body(request).await?;
+                    match request_id(&body) {
+                        Some(0) => {
+                            assert_eq!(session_id, None);
+                            let session = sessions.fetch_add(1, SeqCst) + 1;
+                            Ok(Response::builder()
+                                .header("Content-Type", JSON_CONTENT_TYPE)
+                                .header(SESSION_ID_HEADER, format!("session-{session}"))
+                                .body(response(0).into())?)
+                        }
+                        // The first session expires before the request is made.
+                        Some(id) if session_id.as_deref() == Some("session-1") => {
+                            assert_eq!(id, 1);
+                            Ok(Response::builder().status(404).body(AsyncBody::empty())?)
+                        }
+                        Some(id) => {
+                            assert_eq!(session_id.as_deref(), Some("session-2"));
+                            Ok(Response::builder()
+                                .header("Content-Type", EVENT_STREAM_CONTENT_TYPE)
+                                .body(
+                                    format!("id: 7\nevent: message\ndata: {}\n\n", response(id))
+                                        .into(),
+                                )?)
+                        }
+                        None => Ok(Response::builder().status(202).body(AsyncBody::empty())?),
+                    }
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            options(Some(HttpTransportKind::StreamableHttp)),
+            http_client,
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        transport.send(INITIALIZE.into()).await.unwrap();
+        assert_eq!(messages.next().await, Some(response(0)));
+        transport.send(INITIALIZED.into()).await.unwrap();
+
+        transport.send(REQUEST.into()).await.unwrap();
+        // The handshake is replayed to start a new session before the request is sent again.
+        assert_eq!(messages.next().await, Some(response(1)));
+        assert_eq!(sessions.load(SeqCst), 2);
+    }
+
+    #[gpui::test]
+    async fn test_falling_back_to_sse_transport(cx: &mut TestAppContext) {
+        let (events_tx, events_rx) = channel::unbounded::<String>();
+        let events_rx = Arc::new(Mutex::new(Some(events_rx)));
+        let http_client = FakeHttpClient::create(move |request| {
+            let events_tx = events_tx.clone();
+            let events_rx = events_rx.clone();
+            async move {
+                let path = request.uri().path().to_string();
+                match (request.method().clone(), path.as_str()) {
+                    (Method::POST, "/mcp") => {
+                        Ok(Response::builder().status(405).body(AsyncBody::empty())?)
+                    }
+                    (Method::GET, "/mcp") => {
+                        let events_rx = events_rx.lock().take().expect("stream opened twice");
+                        events_tx
+                            .send("event: endpoint\ndata: /messages?session=a\n\n".into())
+                            .await?;
+                        let body = events_rx
+                            .map(|event| Ok::<_, std::io::Error>(event.into_bytes()))
+                            .into_async_read();
+                        Ok(Response::builder()
+                            .header("Content-Type", EVENT_STREAM_CONTENT_TYPE)
+                            .body(AsyncBody::from_reader(body))?)
+                    }
+                    (Method::POST, "/messages") => {
+                        assert_eq!(request.uri().query(), Some("session=a"));
+                        let body = request_body(request).await?;
+                        if let Some(id) = request_id(&body) {
+                            events_tx
+                                .send(format!("event: message\ndata: {}\n\n", response(id)))
+                                .await?;
+                        }
+                        Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+                    }
+                    (method, path) => panic!("unexpected request {method} {path}"),
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(options(None), http_client, &cx.to_async());
+        let mut messages = transport.receive();
+
+        transport.send(INITIALIZE.into()).await.unwrap();
+        assert_eq!(messages.next().await, Some(response(0)));
+        transport.send(INITIALIZED.into()).await.unwrap();
+        transport.send(REQUEST.into()).await.unwrap();
+        assert_eq!(messages.next().await, Some(response(1)));
+    }
+
+    #[gpui::test]
+    async fn test_ignoring_endpoints_on_other_origins(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            match (request.method().clone(), request.uri().path()) {
+                (Method::POST, "/mcp") => {
+                    Ok(Response::builder().status(405).body(AsyncBody::empty())?)
+                }
+                (Method::GET, "/mcp") => Ok(Response::builder()
+                    .header("Content-Type", EVENT_STREAM_CONTENT_TYPE)
+                    .body("event: endpoint\ndata: http://example.com/messages\n\n".into())?),
+                (method, _) => panic!("unexpected request {method} {}", request.uri()),
+            }
+        });
+
+        let transport = HttpTransport::new(options(None), http_client, &cx.to_async());
+        let mut send = transport.send(INITIALIZE.into());
+        let result = loop {
+            cx.run_until_parked();
+            if let Some(result) = (&mut send).now_or_never() {
+                break result;
+            }
+            cx.executor().advance_clock(ENDPOINT_TIMEOUT);
+        };
+        let error = result.unwrap_err();
+        assert!(
+            format!("{error:#}").contains("didn't announce an endpoint"),
+            "unexpected error: {error:#}"
+        );
+    }
+}
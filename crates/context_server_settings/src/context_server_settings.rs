@@ -18,6 +18,21 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// The URL of a context server to connect to over HTTP, such as a shared
+    /// service or a server running in a local container.
+    ///
+    /// When set, `command` is ignored.
+    pub url: Option<String>,
+    /// Headers to send with every HTTP request to the context server.
+    pub headers: Option<HashMap<String, String>>,
+    /// Where to read a bearer token from, which is sent in the `Authorization`
+    /// header of every HTTP request to the context server.
+    pub bearer_token: Option<BearerTokenSource>,
+    /// The HTTP transport to connect with.
+    ///
+    /// By default, the streamable HTTP transport is tried first, falling back
+    /// to the legacy HTTP with SSE transport if the server doesn't support it.
+    pub transport: Option<HttpTransportKind>,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
@@ -33,6 +48,25 @@ fn server_config_settings_json_schema(_generator: &mut SchemaGenerator) -> Schem
     })
 }
 
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BearerTokenSource {
+    /// The credentials stored in the system keychain for the server's URL,
+    /// whose password is used as the token.
+    Keychain,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpTransportKind {
+    /// The streamable HTTP transport, where every message is posted to the
+    /// server's URL.
+    StreamableHttp,
+    /// The legacy HTTP with SSE transport, where the server sends messages over
+    /// an event stream opened at its URL.
+    Sse,
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct ServerCommand {
     pub path: String,
@@ -1,3 +1,4 @@
+pub mod export;
 pub mod kvp;
 pub mod query;
 
@@ -18,6 +19,7 @@ use sqlez_macros::sql;
 use std::future::Future;
 use std::path::Path;
 use std::sync::{LazyLock, atomic::Ordering};
+use std::time::Duration;
 use std::{env, sync::atomic::AtomicBool};
 use util::{ResultExt, maybe};
 
@@ -36,6 +38,9 @@ const FALLBACK_DB_NAME: &str = "FALLBACK_MEMORY_DB";
 
 const DB_FILE_NAME: &str = "db.sqlite";
 
+/// How often the database file is checked for unused space to reclaim.
+const VACUUM_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 pub static ZED_STATELESS: LazyLock<bool> =
     LazyLock::new(|| env::var("ZED_STATELESS").map_or(false, |v| !v.is_empty()));
 
@@ -78,6 +83,7 @@ async fn open_main_db<M: Migrator>(db_path: &Path) -> Option<ThreadSafeConnectio
     ThreadSafeConnection::<M>::builder(db_path.to_string_lossy().as_ref(), true)
         .with_db_initialization_query(DB_INITIALIZE_QUERY)
         .with_connection_initialize_query(CONNECTION_INITIALIZE_QUERY)
+        .with_vacuum_interval(VACUUM_INTERVAL)
         .build()
         .await
         .log_err()
@@ -0,0 +1,262 @@
+//! Moving persisted state between machines.
+//!
+//! An export is a SQLite database holding the tables and migrations of the selected domains, see
+//! [`sqlez::export`], behind a short header. It can be encrypted with a passphrase, in which case
+//! the database is sealed with ChaCha20-Poly1305 under a key derived from the passphrase.
+
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result, anyhow};
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit as _, Nonce,
+    aead::{Aead as _, Payload},
+};
+use sha2::Sha256;
+use sqlez::{domain::Migrator, thread_safe_connection::ThreadSafeConnection};
+
+const MAGIC: &[u8; 8] = b"ZEDDATA\0";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 2;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+#[cfg(not(test))]
+const KEY_DERIVATION_ROUNDS: u32 = 600_000;
+#[cfg(test)]
+const KEY_DERIVATION_ROUNDS: u32 = 1_000;
+
+/// Returns the domains in the database that can be exported.
+pub fn exportable_domains<M: Migrator>(
+    connection: &ThreadSafeConnection<M>,
+) -> Result<Vec<String>> {
+    connection.domains()
+}
+
+/// Writes the given domains to a file at `destination`, encrypting it if a passphrase is given.
+pub async fn export_domains<M: Migrator>(
+    connection: &ThreadSafeConnection<M>,
+    domains: Vec<String>,
+    destination: PathBuf,
+    passphrase: Option<String>,
+) -> Result<()> {
+    let database = tempfile::NamedTempFile::new()?;
+    let database_path = database.path().to_path_buf();
+    connection
+        .write(move |connection| {
+            let domains = domains.iter().map(String::as_str).collect::<Vec<_>>();
+            connection.export_domains(&domains, &database_path)
+        })
+        .await
+        .context("Failed to export database")?;
+
+    let database = smol::fs::read(database.path()).await?;
+    let contents = encode(&database, passphrase.as_deref())?;
+    smol::fs::write(&destination, contents)
+        .await
+        .with_context(|| format!("Failed to write {destination:?}"))
+}
+
+/// Whether the export at `path` needs a passphrase to be imported.
+pub async fn is_encrypted(path: PathBuf) -> Result<bool> {
+    let contents = smol::fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read {path:?}"))?;
+    parse_header(&contents)
+}
+
+/// Replaces the state of every domain in the export at `source` with the exported state, returning
+/// the names of the imported domains. Exports from older versions are migrated as they're imported.
+pub async fn import_domains<M: Migrator>(
+    connection: &ThreadSafeConnection<M>,
+    source: PathBuf,
+    passphrase: Option<String>,
+) -> Result<Vec<String>> {
+    let contents = smol::fs::read(&source)
+        .await
+        .with_context(|| format!("Failed to read {source:?}"))?;
+    let database = decode(&contents, passphrase.as_deref())?;
+
+    let database_file = tempfile::NamedTempFile::new()?;
+    smol::fs::write(database_file.path(), database).await?;
+    let database_path = database_file.path().to_path_buf();
+    let thread_safe_connection = connection.clone();
+    connection
+        .write(move |connection| {
+            let domains = connection.import_domains(&database_path)?;
+            // State that was loaded before the import would overwrite the imported data.
+            thread_safe_connection.block_writes();
+            anyhow::Ok(domains)
+        })
+        .await
+        .context("Failed to import database")
+}
+
+fn encode(database: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>> {
+    let mut contents = Vec::with_capacity(HEADER_LEN + SALT_LEN + NONCE_LEN + database.len());
+    contents.extend_from_slice(MAGIC);
+    contents.push(FORMAT_VERSION);
+    contents.push(passphrase.is_some() as u8);
+
+    let Some(passphrase) = passphrase else {
+        contents.extend_from_slice(database);
+        return Ok(contents);
+    };
+
+    let salt = rand::random::<[u8; SALT_LEN]>();
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let ciphertext = ChaCha20Poly1305::new(&derive_key(passphrase, &salt))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: database,
+                aad: &contents[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| anyhow!("Failed to encrypt export"))?;
+    contents.extend_from_slice(&salt);
+    contents.extend_from_slice(&nonce);
+    contents.extend_from_slice(&ciphertext);
+    Ok(contents)
+}
+
+fn decode(contents: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>> {
+    let encrypted = parse_header(contents)?;
+    let (header, body) = contents.split_at(HEADER_LEN);
+    if !encrypted {
+        return Ok(body.to_vec());
+    }
+
+    let passphrase = passphrase.context("This export is encrypted and needs a passphrase")?;
+    if body.len() < SALT_LEN + NONCE_LEN {
+        return Err(anyhow!("Export is truncated"));
+    }
+    let (salt, body) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(&derive_key(passphrase, salt))
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow!("Wrong passphrase, or the export is corrupted"))
+}
+
+/// Validates the header, returning whether the export is encrypted.
+fn parse_header(contents: &[u8]) -> Result<bool> {
+    if contents.len() < HEADER_LEN || &contents[..MAGIC.len()] != MAGIC {
+        return Err(anyhow!("Not a Zed data export"));
+    }
+    let version = contents[MAGIC.len()];
+    if version > FORMAT_VERSION {
+        return Err(anyhow!(
+            "Export was created by a newer version of Zed (format {version})"
+        ));
+    }
+    Ok(contents[MAGIC.len() + 1] != 0)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Key {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, KEY_DERIVATION_ROUNDS, &mut key);
+    Key::from(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kvp::KeyValueStore;
+
+    #[test]
+    fn test_encoding_round_trips() {
+        let database = b"SQLite format 3\0...".to_vec();
+
+        let plain = encode(&database, None).unwrap();
+        assert!(!parse_header(&plain).unwrap());
+        assert_eq!(decode(&plain, None).unwrap(), database);
+
+        let encrypted = encode(&database, Some("hunter2")).unwrap();
+        assert!(parse_header(&encrypted).unwrap());
+        assert_eq!(decode(&encrypted, Some("hunter2")).unwrap(), database);
+        assert!(decode(&encrypted, Some("hunter3")).is_err());
+        assert!(decode(&encrypted, None).is_err());
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decode(&tampered, Some("hunter2")).is_err());
+        assert!(decode(b"not an export", None).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_export_and_import(cx: &mut gpui::TestAppContext) {
+        cx.executor().allow_parking();
+
+        let source = crate::open_test_db::<KeyValueStore>("test_export_source").await;
+        write_kvp(&source, "key", "value").await;
+        assert!(
+            exportable_domains(&source)
+                .unwrap()
+                .contains(&"KeyValueStore".to_string())
+        );
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("export.zed-data");
+        export_domains(
+            &source,
+            vec!["KeyValueStore".to_string()],
+            path.clone(),
+            Some("passphrase".to_string()),
+        )
+        .await
+        .unwrap();
+        assert!(is_encrypted(path.clone()).await.unwrap());
+
+        let target = crate::open_test_db::<KeyValueStore>("test_export_target").await;
+        write_kvp(&target, "other", "replaced").await;
+        assert!(
+            import_domains(&target, path.clone(), Some("wrong".to_string()))
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            import_domains(&target, path, Some("passphrase".to_string()))
+                .await
+                .unwrap(),
+            vec!["KeyValueStore".to_string()]
+        );
+        assert_eq!(read_kvp(&target, "key"), Some("value".to_string()));
+        assert_eq!(read_kvp(&target, "other"), None);
+
+        // Later writes can't overwrite the imported state.
+        assert!(
+            target
+                .write(|connection| { connection.exec("UPDATE kv_store SET value = 'stale'")?() })
+                .await
+                .is_err()
+        );
+        assert_eq!(read_kvp(&target, "key"), Some("value".to_string()));
+    }
+
+    async fn write_kvp(
+        connection: &ThreadSafeConnection<KeyValueStore>,
+        key: &'static str,
+        value: &'static str,
+    ) {
+        connection
+            .write(move |connection| {
+                connection
+                    .exec_bound::<(&str, &str)>("INSERT INTO kv_store(key, value) VALUES (?, ?)")?(
+                    (key, value),
+                )
+            })
+            .await
+            .unwrap();
+    }
+
+    fn read_kvp(connection: &ThreadSafeConnection<KeyValueStore>, key: &str) -> Option<String> {
+        connection
+            .select_row_bound::<&str, String>("SELECT value FROM kv_store WHERE key = ?")
+            .unwrap()(key)
+        .unwrap()
+    }
+}
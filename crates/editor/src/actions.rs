@@ -249,6 +249,8 @@ actions!(
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        CompareSelections,
+        CompareWithClipboard,
         ConfirmRename,
         ConfirmCompletionInsert,
         ConfirmCompletionReplace,
@@ -20,6 +20,7 @@ pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod file_diff_view;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -33,6 +34,7 @@ mod lsp_ext;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod persistent_undo;
 mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
@@ -124,6 +126,7 @@ use project::{
     },
 };
 
+pub use file_diff_view::{FileDiffView, FileDiffViewToolbar};
 pub use git::blame::BlameRenderer;
 pub use proposed_changes_editor::{
     ProposedChangeLocation, ProposedChangesEditor, ProposedChangesEditorToolbar,
@@ -1699,6 +1702,7 @@ impl Editor {
                     });
                     this.registered_buffers
                         .insert(buffer.read(cx).remote_id(), handle);
+                    persistent_undo::track_buffer(&buffer, cx);
                 }
             }
         }
@@ -10300,6 +10304,10 @@ impl Editor {
                 self.change_selections(None, window, cx, |s| {
                     s.select_anchors(selections.to_vec());
                 });
+            } else if let Some(position) = self.transaction_start(transaction_id, cx) {
+                self.change_selections(None, window, cx, |s| {
+                    s.select_ranges([position..position]);
+                });
             } else {
                 log::error!(
                     "No entry in selection_history found for undo. \
@@ -10330,6 +10338,10 @@ impl Editor {
                 self.change_selections(None, window, cx, |s| {
                     s.select_anchors(selections.to_vec());
                 });
+            } else if let Some(position) = self.transaction_start(transaction_id, cx) {
+                self.change_selections(None, window, cx, |s| {
+                    s.select_ranges([position..position]);
+                });
             } else {
                 log::error!(
                     "No entry in selection_history found for redo. \
@@ -10345,6 +10357,51 @@ impl Editor {
         }
     }
 
+    /// Moves a singleton buffer to the given state of its undo tree, or to its initial state for
+    /// `None`, placing the cursor where the state's transaction made its edits.
+    pub fn go_to_history_state(
+        &mut self,
+        target: Option<TransactionId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let previous = buffer.read(cx).current_history_state();
+        if !buffer.update(cx, |buffer, cx| buffer.go_to_history_state(target, cx)) {
+            return;
+        }
+        if let Some(position) = target
+            .or(previous)
+            .and_then(|transaction_id| self.transaction_start(transaction_id, cx))
+        {
+            self.change_selections(None, window, cx, |s| {
+                s.select_ranges([position..position]);
+            });
+        }
+        self.request_autoscroll(Autoscroll::fit(), cx);
+        self.unmark_text(window, cx);
+        if let Some(transaction_id) = target.or(previous) {
+            cx.emit(EditorEvent::Edited { transaction_id });
+        }
+    }
+
+    /// Returns where a transaction made its first edit in a singleton buffer. This places the
+    /// cursor for transactions that have no selections recorded in this editor, such as ones
+    /// restored from a previous session.
+    fn transaction_start(&self, transaction_id: TransactionId, cx: &App) -> Option<usize> {
+        let buffer = self.buffer.read(cx).as_singleton()?;
+        let range = buffer
+            .read(cx)
+            .edited_ranges_for_transaction_id::<usize>(transaction_id)
+            .next()?;
+        Some(range.start)
+    }
+
     pub fn finalize_last_transaction(&mut self, cx: &mut Context<Self>) {
         self.buffer
             .update(cx, |buffer, cx| buffer.finalize_last_transaction(cx));
@@ -17455,6 +17512,87 @@ impl Editor {
         });
     }
 
+    fn compare_with_clipboard(
+        &mut self,
+        _: &CompareWithClipboard,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            cx.propagate();
+            return;
+        };
+        let Some(clipboard_text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        let Some((_, buffer, _)) = self.active_excerpt(cx) else {
+            return;
+        };
+
+        // Compare against the selected text if there is any, otherwise the whole buffer.
+        let selection = self.selections.newest::<usize>(cx);
+        let new = if selection.is_empty() {
+            (buffer.clone(), None)
+        } else {
+            let text = self
+                .buffer
+                .read(cx)
+                .snapshot(cx)
+                .text_for_range(selection.range())
+                .collect::<String>();
+            (
+                FileDiffView::buffer_for_text(text, &buffer, cx),
+                Some("Selection".into()),
+            )
+        };
+        let old = (
+            FileDiffView::buffer_for_text(clipboard_text, &buffer, cx),
+            Some("Clipboard".into()),
+        );
+        FileDiffView::compare_buffers(old, new, workspace.downgrade(), window, cx)
+            .detach_and_log_err(cx);
+    }
+
+    fn compare_selections(
+        &mut self,
+        _: &CompareSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            cx.propagate();
+            return;
+        };
+        let Some((_, buffer, _)) = self.active_excerpt(cx) else {
+            return;
+        };
+        let selections = self.selections.all::<usize>(cx);
+        let [old_selection, new_selection] = selections.as_slice() else {
+            return;
+        };
+        if old_selection.is_empty() || new_selection.is_empty() {
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let old_text = snapshot
+            .text_for_range(old_selection.range())
+            .collect::<String>();
+        let new_text = snapshot
+            .text_for_range(new_selection.range())
+            .collect::<String>();
+        let old = (
+            FileDiffView::buffer_for_text(old_text, &buffer, cx),
+            Some("Selection 1".into()),
+        );
+        let new = (
+            FileDiffView::buffer_for_text(new_text, &buffer, cx),
+            Some("Selection 2".into()),
+        );
+        FileDiffView::compare_buffers(old, new, workspace.downgrade(), window, cx)
+            .detach_and_log_err(cx);
+    }
+
     pub fn open_excerpts_in_split(
         &mut self,
         _: &OpenExcerptsSplit,
@@ -16,6 +16,9 @@ pub struct EditorSettings {
     pub hover_popover_delay: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
+    pub sticky_scroll: StickyScroll,
+    pub persistent_undo: PersistentUndo,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -115,6 +118,17 @@ pub struct Scrollbar {
     pub axes: ScrollbarAxes,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub show: ShowMinimap,
+    pub side: MinimapSide,
+    pub width: f32,
+    pub scale: f32,
+    pub selections: bool,
+    pub search_results: bool,
+    pub diagnostics: bool,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -141,6 +155,46 @@ pub enum ShowScrollbar {
     Never,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PersistentUndo {
+    pub enabled: bool,
+    pub max_size_kb: usize,
+}
+
+/// When to show the minimap in the editor.
+///
+/// Default: never
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowMinimap {
+    /// Show the minimap when the buffer doesn't fit in the editor.
+    Auto,
+    /// Always show the minimap.
+    Always,
+    /// Never show the minimap.
+    #[default]
+    Never,
+}
+
+/// Which side of the editor to show the minimap on.
+///
+/// Default: right
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MinimapSide {
+    /// Show the minimap between the gutter and the text.
+    Left,
+    /// Show the minimap on the right edge of the editor.
+    #[default]
+    Right,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -285,6 +339,12 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
+    /// Persistent undo related settings
+    pub persistent_undo: Option<PersistentUndoContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -427,6 +487,67 @@ pub struct ScrollbarContent {
     pub axes: Option<ScrollbarAxesContent>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// When to show the minimap in the editor.
+    ///
+    /// Default: never
+    pub show: Option<ShowMinimap>,
+    /// Which side of the editor to show the minimap on.
+    ///
+    /// Default: right
+    pub side: Option<MinimapSide>,
+    /// The width of the minimap, in pixels.
+    ///
+    /// Default: 100
+    pub width: Option<f32>,
+    /// The height of a line in the minimap, relative to the height of a line in the editor.
+    ///
+    /// Default: 0.15
+    pub scale: Option<f32>,
+    /// Whether to show selections in the minimap.
+    ///
+    /// Default: true
+    pub selections: Option<bool>,
+    /// Whether to show buffer search results in the minimap.
+    ///
+    /// Default: true
+    pub search_results: Option<bool>,
+    /// Whether to show diagnostic indicators in the minimap.
+    ///
+    /// Default: true
+    pub diagnostics: Option<bool>,
+}
+
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the lines that open the enclosing scopes to the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of enclosing scopes to pin.
+    ///
+    /// Default: 3
+    pub max_depth: Option<usize>,
+}
+
+/// Persistent undo related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PersistentUndoContent {
+    /// Whether to keep the undo history of files across restarts. Histories are
+    /// discarded when the file was changed outside of Zed in the meantime.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum size of the undo history saved for each file, in kilobytes.
+    /// The oldest edits are dropped first.
+    ///
+    /// Default: 1024
+    pub max_size_kb: Option<usize>,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct ScrollbarAxesContent {
@@ -13,8 +13,8 @@ use crate::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, FoldId, HighlightedChunk, ToDisplayPoint,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ScrollbarAxes, ScrollbarDiagnostics, ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, MinimapSide, MultiCursorModifier,
+        ScrollBeyondLastLine, ScrollbarAxes, ScrollbarDiagnostics, ShowMinimap, ShowScrollbar,
     },
     git::blame::{BlameRenderer, GitBlame, GlobalBlameRenderer},
     hover_popover::{
@@ -24,7 +24,7 @@ use crate::{
     inlay_hint_settings,
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition},
-    scroll::scroll_amount::ScrollAmount,
+    scroll::{Autoscroll, scroll_amount::ScrollAmount},
 };
 use buffer_diff::{DiffHunkStatus, DiffHunkStatusKind};
 use client::ParticipantIndex;
@@ -39,7 +39,7 @@ use gpui::{
     InteractiveElement, IntoElement, Keystroke, Length, ModifiersChangedEvent, MouseButton,
     MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad, ParentElement, Pixels, ScrollDelta,
     ScrollWheelEvent, ShapedLine, SharedString, Size, StatefulInteractiveElement, Style, Styled,
-    TextRun, TextStyleRefinement, WeakEntity, Window, anchored, deferred, div, fill,
+    StyledText, TextRun, TextStyleRefinement, WeakEntity, Window, anchored, deferred, div, fill,
     linear_color_stop, linear_gradient, outline, point, px, quad, relative, size, solid_background,
     transparent_black,
 };
@@ -409,6 +409,8 @@ impl EditorElement {
         register_action(editor, window, Editor::open_excerpts);
         register_action(editor, window, Editor::open_excerpts_in_split);
         register_action(editor, window, Editor::open_proposed_changes_editor);
+        register_action(editor, window, Editor::compare_with_clipboard);
+        register_action(editor, window, Editor::compare_selections);
         register_action(editor, window, Editor::toggle_soft_wrap);
         register_action(editor, window, Editor::toggle_tab_bar);
         register_action(editor, window, Editor::toggle_line_numbers);
@@ -1464,6 +1466,229 @@ impl EditorElement {
         ))
     }
 
+    fn minimap_width(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        line_height: Pixels,
+        cx: &App,
+    ) -> Pixels {
+        let minimap_settings = EditorSettings::get_global(cx).minimap;
+        if !snapshot.mode.is_full() {
+            return Pixels::ZERO;
+        }
+
+        let show = match minimap_settings.show {
+            ShowMinimap::Never => false,
+            ShowMinimap::Always => true,
+            ShowMinimap::Auto => {
+                snapshot.max_point().row().as_f32() + 1. > bounds.size.height / line_height
+            }
+        };
+        if show {
+            // Never let the minimap take up more than a third of the editor.
+            px(minimap_settings.width).min(bounds.size.width / 3.)
+        } else {
+            Pixels::ZERO
+        }
+    }
+
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        scroll_position: gpui::Point<f32>,
+        height_in_lines: f32,
+        max_scroll_top: f32,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> MinimapLayout {
+        let minimap_settings = EditorSettings::get_global(cx).minimap;
+        let scale = minimap_settings.scale.clamp(0.05, 1.);
+        let font_id = window.text_system().resolve_font(&self.style.text.font());
+        let font_size = self.style.text.font_size.to_pixels(window.rem_size());
+        let line_height = self.style.text.line_height_in_pixels(window.rem_size()) * scale;
+        let column_width = window.text_system().em_advance(font_id, font_size).unwrap() * scale;
+
+        // Scroll the minimap proportionally to the editor, so that the first and last lines of
+        // the buffer are reachable in it once the editor is scrolled to the top or bottom.
+        let minimap_lines = bounds.size.height / line_height;
+        let total_rows = snapshot.max_point().row().next_row().as_f32();
+        let scroll_ratio = if max_scroll_top > 0. {
+            (scroll_position.y / max_scroll_top).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let first_row = (total_rows - minimap_lines).max(0.) * scroll_ratio;
+        let start_row = DisplayRow(first_row.floor() as u32);
+        let end_row = DisplayRow(
+            ((first_row + minimap_lines).ceil() as u32)
+                .min(snapshot.max_point().row().next_row().0),
+        );
+        let max_columns = (bounds.size.width / column_width).ceil() as usize;
+        let row_top = |row: DisplayRow| (row.as_f32() - first_row) * line_height;
+        let row_range_bounds = |start: DisplayRow, end: DisplayRow| {
+            let top = row_top(start.max(start_row));
+            let bottom = row_top(end.min(end_row)) + line_height;
+            Bounds {
+                origin: bounds.origin + point(Pixels::ZERO, top),
+                size: size(bounds.size.width, (bottom - top).max(line_height)),
+            }
+        };
+
+        let mut highlight_quads = Vec::new();
+        let theme = cx.theme().clone();
+
+        if minimap_settings.search_results {
+            if let Some((_, ranges)) = self
+                .editor
+                .read(cx)
+                .background_highlights
+                .get(&TypeId::of::<BufferSearchHighlights>())
+            {
+                let color = theme.colors().search_match_background;
+                for range in ranges.iter() {
+                    let start = range
+                        .start
+                        .to_display_point(&snapshot.display_snapshot)
+                        .row();
+                    let end = range.end.to_display_point(&snapshot.display_snapshot).row();
+                    if end >= start_row && start < end_row {
+                        highlight_quads.push(fill(row_range_bounds(start, end), color));
+                    }
+                }
+            }
+        }
+
+        if minimap_settings.diagnostics {
+            let start_point = DisplayPoint::new(start_row, 0).to_point(&snapshot.display_snapshot);
+            let end_point = DisplayPoint::new(end_row, 0).to_point(&snapshot.display_snapshot);
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<Point>(start_point..end_point)
+                .sorted_by_key(|diagnostic| std::cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let mut color = match diagnostic.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => theme.status().error,
+                    DiagnosticSeverity::WARNING => theme.status().warning,
+                    DiagnosticSeverity::INFORMATION => theme.status().info,
+                    _ => theme.status().hint,
+                };
+                color.fade_out(0.6);
+                let start = diagnostic
+                    .range
+                    .start
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let end = diagnostic
+                    .range
+                    .end
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                highlight_quads.push(fill(row_range_bounds(start, end), color));
+            }
+        }
+
+        if minimap_settings.selections {
+            let selections = self
+                .editor
+                .update(cx, |editor, cx| editor.selections.all::<Point>(cx));
+            let color = theme.players().local().selection;
+            for selection in selections {
+                let start = selection
+                    .start
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let end = selection
+                    .end
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                if end >= start_row && start < end_row {
+                    highlight_quads.push(fill(row_range_bounds(start, end), color));
+                }
+            }
+        }
+
+        let border_color = theme.colors().border_variant;
+        for (row, block) in snapshot.blocks_in_range(start_row..end_row) {
+            if matches!(
+                block,
+                Block::ExcerptBoundary { .. } | Block::FoldedBuffer { .. }
+            ) {
+                highlight_quads.push(fill(
+                    Bounds {
+                        origin: bounds.origin + point(Pixels::ZERO, row_top(row)),
+                        size: size(bounds.size.width, px(1.)),
+                    },
+                    border_color,
+                ));
+            }
+        }
+
+        let mut text_quads = Vec::new();
+        let mut row = start_row;
+        let mut column = 0;
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            let mut color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            color.fade_out(0.4);
+
+            for (ix, line) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    row.0 += 1;
+                    column = 0;
+                }
+
+                let line_len = line.chars().count();
+                let mut run_start = None;
+                for (char_ix, ch) in line.chars().chain(Some(' ')).enumerate() {
+                    let char_column = column + char_ix;
+                    if !ch.is_whitespace() && char_ix < line_len {
+                        run_start.get_or_insert(char_column);
+                    } else if let Some(run_start) = run_start.take() {
+                        if run_start >= max_columns {
+                            continue;
+                        }
+                        let run_end = char_column.min(max_columns);
+                        text_quads.push(fill(
+                            Bounds {
+                                origin: bounds.origin
+                                    + point(
+                                        column_width * run_start as f32,
+                                        row_top(row) + line_height * 0.15,
+                                    ),
+                                size: size(
+                                    column_width * (run_end - run_start) as f32,
+                                    line_height * 0.7,
+                                ),
+                            },
+                            color,
+                        ));
+                    }
+                }
+                column += line_len;
+            }
+        }
+
+        let thumb_bounds = Bounds {
+            origin: bounds.origin
+                + point(Pixels::ZERO, (scroll_position.y - first_row) * line_height),
+            size: size(bounds.size.width, height_in_lines * line_height),
+        };
+
+        MinimapLayout {
+            hitbox: window.insert_hitbox(bounds, false),
+            line_height,
+            first_row,
+            height_in_lines,
+            highlight_quads,
+            text_quads,
+            thumb_bounds,
+        }
+    }
+
     fn prepaint_crease_toggles(
         &self,
         crease_toggles: &mut [Option<AnyElement>],
@@ -3322,6 +3547,124 @@ impl EditorElement {
         header
     }
 
+    fn layout_sticky_scopes(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        content_offset: gpui::Point<Pixels>,
+        text_hitbox: &Hitbox,
+        top_offset_rows: u32,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<StickyScopeLayout> {
+        let sticky_scroll = EditorSettings::get_global(cx).sticky_scroll;
+        if !sticky_scroll.enabled || sticky_scroll.max_depth == 0 || !snapshot.mode.is_full() {
+            return Vec::new();
+        }
+
+        let top_row = scroll_position.y + top_offset_rows as f32;
+        let top_point = DisplayPoint::new(DisplayRow(top_row as u32), 0);
+        let top_offset = top_point.to_offset(&snapshot.display_snapshot, Bias::Left);
+        let Some((_, scopes)) = snapshot
+            .buffer_snapshot
+            .symbols_containing(top_offset, Some(&self.style.syntax))
+        else {
+            return Vec::new();
+        };
+
+        let mut headers = Vec::new();
+        let mut last_header_row = None;
+        for scope in scopes {
+            if headers.len() == sticky_scroll.max_depth {
+                break;
+            }
+
+            let start_row = scope
+                .range
+                .start
+                .to_display_point(&snapshot.display_snapshot)
+                .row();
+            let end_row = scope
+                .range
+                .end
+                .to_display_point(&snapshot.display_snapshot)
+                .row();
+            // Only pin scopes whose opening line would be hidden behind the headers above it,
+            // and never pin the same line twice.
+            if start_row.as_f32() >= top_row + headers.len() as f32
+                || last_header_row == Some(start_row)
+            {
+                continue;
+            }
+            last_header_row = Some(start_row);
+            headers.push((start_row, end_row, scope.range.start));
+        }
+
+        let editor_bg_color = cx.theme().colors().editor_background;
+        let border_color = cx.theme().colors().border_variant;
+        let header_count = headers.len();
+        headers
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (row, end_row, anchor))| {
+                let mut text = String::new();
+                let mut highlights = Vec::new();
+                for chunk in snapshot.highlighted_chunks(row..row.next_row(), true, &self.style) {
+                    let chunk_text = chunk.text.trim_end_matches('\n');
+                    if let Some(style) = chunk.style {
+                        highlights.push((text.len()..text.len() + chunk_text.len(), style));
+                    }
+                    text.push_str(chunk_text);
+                }
+
+                let editor = self.editor.clone();
+                let mut header = div()
+                    .id(("sticky-scope", ix))
+                    .w(text_hitbox.size.width)
+                    .h(line_height)
+                    .overflow_hidden()
+                    .bg(editor_bg_color)
+                    .when(ix + 1 == header_count, |header| {
+                        header.border_b_1().border_color(border_color)
+                    })
+                    .cursor_pointer()
+                    .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                        editor.update(cx, |editor, cx| {
+                            editor.change_selections(
+                                Some(Autoscroll::top_relative(ix)),
+                                window,
+                                cx,
+                                |selections| selections.select_anchor_ranges([anchor..anchor]),
+                            );
+                        });
+                        cx.stop_propagation();
+                    })
+                    .child(div().ml(content_offset.x - scroll_pixel_position.x).child(
+                        StyledText::new(text).with_default_highlights(&self.style.text, highlights),
+                    ))
+                    .into_any_element();
+
+                // Once the end of a scope scrolls into the header area, push its header up
+                // so that it doesn't cover the lines below the scope.
+                let header_top = (top_offset_rows + ix as u32) as f32 * line_height;
+                let scope_bottom = (end_row.as_f32() - scroll_position.y) * line_height;
+                let origin = text_hitbox.origin + point(Pixels::ZERO, header_top.min(scope_bottom));
+                let available_space = size(
+                    AvailableSpace::Definite(text_hitbox.size.width),
+                    AvailableSpace::Definite(line_height),
+                );
+                header.prepaint_as_root(origin, available_space, window, cx);
+                StickyScopeLayout {
+                    row,
+                    origin,
+                    element: header,
+                }
+            })
+            .collect()
+    }
+
     fn layout_cursor_popovers(
         &self,
         line_height: Pixels,
@@ -5117,6 +5460,102 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&self, layout: &EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(minimap) = &layout.minimap else {
+            return;
+        };
+
+        let hitbox = minimap.hitbox.clone();
+        window.paint_layer(hitbox.bounds, |window| {
+            window.paint_quad(fill(hitbox.bounds, cx.theme().colors().editor_background));
+            for highlight in &minimap.highlight_quads {
+                window.paint_quad(highlight.clone());
+            }
+            for text in &minimap.text_quads {
+                window.paint_quad(text.clone());
+            }
+            window.paint_quad(quad(
+                minimap.thumb_bounds,
+                Corners::default(),
+                cx.theme().colors().scrollbar_thumb_background,
+                Edges::default(),
+                cx.theme().colors().scrollbar_thumb_border,
+                BorderStyle::Solid,
+            ));
+        });
+        window.set_cursor_style(CursorStyle::Arrow, Some(&hitbox));
+
+        // Scrolls the editor so that the row under the mouse is centered in the viewport.
+        let scroll_to = {
+            let origin = hitbox.origin;
+            let line_height = minimap.line_height;
+            let first_row = minimap.first_row;
+            let height_in_lines = minimap.height_in_lines;
+            move |editor: &mut Editor,
+                  position: gpui::Point<Pixels>,
+                  window: &mut Window,
+                  cx: &mut Context<Editor>| {
+                let row = first_row + (position.y - origin.y) / line_height;
+                let scroll_y = (row - height_in_lines / 2.).max(0.);
+                let scroll_x = editor.scroll_position(cx).x;
+                editor.set_scroll_position(point(scroll_x, scroll_y), window, cx);
+            }
+        };
+
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = hitbox.clone();
+            move |event: &MouseDownEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture
+                    || event.button != MouseButton::Left
+                    || !hitbox.is_hovered(window)
+                {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    editor.scroll_manager.set_dragging_minimap(true, cx);
+                    scroll_to(editor, event.position, window, cx);
+                });
+                cx.stop_propagation();
+            }
+        });
+
+        if self.editor.read(cx).scroll_manager.is_dragging_minimap() {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseMoveEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            scroll_to(editor, event.position, window, cx);
+                        } else {
+                            editor.scroll_manager.set_dragging_minimap(false, cx);
+                        }
+                    });
+                    cx.stop_propagation();
+                }
+            });
+
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_dragging_minimap(false, cx);
+                    });
+                    cx.stop_propagation();
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -6620,7 +7059,8 @@ impl Element for EditorElement {
                             cx,
                         )
                         .unwrap_or_default();
-                    let text_width = bounds.size.width - gutter_dimensions.width;
+                    let minimap_width = self.minimap_width(&snapshot, bounds, line_height, cx);
+                    let text_width = bounds.size.width - gutter_dimensions.width - minimap_width;
 
                     let editor_width =
                         text_width - gutter_dimensions.margin - em_width - style.scrollbar_width;
@@ -6662,13 +7102,30 @@ impl Element for EditorElement {
                     let hitbox = window.insert_hitbox(bounds, false);
                     let gutter_hitbox =
                         window.insert_hitbox(gutter_bounds(bounds, gutter_dimensions), false);
+                    let minimap_side = EditorSettings::get_global(cx).minimap.side;
+                    let text_origin = match minimap_side {
+                        MinimapSide::Left => {
+                            gutter_hitbox.top_right() + point(minimap_width, px(0.))
+                        }
+                        MinimapSide::Right => gutter_hitbox.top_right(),
+                    };
                     let text_hitbox = window.insert_hitbox(
                         Bounds {
-                            origin: gutter_hitbox.top_right(),
+                            origin: text_origin,
                             size: size(text_width, bounds.size.height),
                         },
                         false,
                     );
+                    let minimap_bounds = (minimap_width > Pixels::ZERO).then(|| {
+                        let origin = match minimap_side {
+                            MinimapSide::Left => gutter_hitbox.top_right(),
+                            MinimapSide::Right => text_hitbox.top_right(),
+                        };
+                        Bounds {
+                            origin,
+                            size: size(minimap_width, bounds.size.height),
+                        }
+                    });
 
                     // Offset the content_bounds from the text_bounds by the gutter margin (which
                     // is roughly half a character wide) to make hit testing work more like how we want.
@@ -6676,7 +7133,7 @@ impl Element for EditorElement {
                     let content_origin = text_hitbox.origin + content_offset;
 
                     let editor_text_bounds =
-                        Bounds::from_corners(content_origin, bounds.bottom_right());
+                        Bounds::from_corners(content_origin, text_hitbox.bottom_right());
 
                     let height_in_lines = editor_text_bounds.size.height / line_height;
 
@@ -7131,6 +7588,24 @@ impl Element for EditorElement {
                         scroll_position.y * line_height,
                     );
 
+                    let sticky_scopes = window.with_element_namespace("sticky_scopes", |window| {
+                        self.layout_sticky_scopes(
+                            &snapshot,
+                            scroll_position,
+                            scroll_pixel_position,
+                            line_height,
+                            content_offset,
+                            &text_hitbox,
+                            if sticky_buffer_header.is_some() {
+                                FILE_HEADER_HEIGHT
+                            } else {
+                                0
+                            },
+                            window,
+                            cx,
+                        )
+                    });
+
                     let indent_guides = self.layout_indent_guides(
                         content_origin,
                         text_hitbox.origin,
@@ -7312,6 +7787,18 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap = minimap_bounds.map(|minimap_bounds| {
+                        self.layout_minimap(
+                            &snapshot,
+                            minimap_bounds,
+                            scroll_position,
+                            height_in_lines,
+                            max_scroll_top,
+                            window,
+                            cx,
+                        )
+                    });
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
                     let mut code_actions_indicator = None;
@@ -7567,6 +8054,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -7592,6 +8080,7 @@ impl Element for EditorElement {
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scopes,
                         expand_toggles,
                     }
                 })
@@ -7653,6 +8142,12 @@ impl Element for EditorElement {
                         });
                     }
 
+                    window.with_element_namespace("sticky_scopes", |window| {
+                        for sticky_scope in &mut layout.sticky_scopes {
+                            sticky_scope.element.paint(window, cx);
+                        }
+                    });
+
                     window.with_element_namespace("blocks", |window| {
                         if let Some(mut sticky_header) = layout.sticky_buffer_header.take() {
                             sticky_header.paint(window, cx)
@@ -7660,6 +8155,7 @@ impl Element for EditorElement {
                     });
 
                     self.paint_scrollbars(layout, window, cx);
+                    self.paint_minimap(layout, window, cx);
                     self.paint_inline_completion_popover(layout, window, cx);
                     self.paint_mouse_context_menu(layout, window, cx);
                 });
@@ -7738,6 +8234,7 @@ pub struct EditorLayout {
     gutter_hitbox: Hitbox,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: Option<EditorScrollbars>,
+    minimap: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -7769,6 +8266,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scopes: Vec<StickyScopeLayout>,
 }
 
 impl EditorLayout {
@@ -7907,6 +8405,19 @@ impl EditorScrollbars {
     }
 }
 
+struct MinimapLayout {
+    hitbox: Hitbox,
+    /// The height of a single row in the minimap.
+    line_height: Pixels,
+    /// The (fractional) display row shown at the top of the minimap.
+    first_row: f32,
+    /// The number of editor rows visible in the viewport.
+    height_in_lines: f32,
+    highlight_quads: Vec<PaintQuad>,
+    text_quads: Vec<PaintQuad>,
+    thumb_bounds: Bounds<Pixels>,
+}
+
 #[derive(Clone)]
 struct ScrollbarLayout {
     hitbox: Hitbox,
@@ -8130,6 +8641,14 @@ impl PositionMap {
     }
 }
 
+/// The header of a scope pinned to the top of the editor.
+struct StickyScopeLayout {
+    /// The row of the scope's opening line, which the header shows.
+    row: DisplayRow,
+    origin: gpui::Point<Pixels>,
+    element: AnyElement,
+}
+
 struct BlockLayout {
     id: BlockId,
     x_offset: Pixels,
@@ -8526,14 +9045,16 @@ fn compute_auto_height_layout(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::editor_settings::{MinimapContent, StickyScrollContent};
     use crate::{
         Editor, MultiBuffer,
         display_map::{BlockPlacement, BlockProperties},
         editor_tests::{init_test, update_test_language_settings},
     };
-    use gpui::{TestAppContext, VisualTestContext};
+    use gpui::{Modifiers, TestAppContext, VisualTestContext};
     use language::language_settings;
     use log::info;
+    use settings::SettingsStore;
     use std::num::NonZeroU32;
     use util::test::sample_text;
 
@@ -8755,6 +9276,259 @@ mod tests {
         );
     }
 
+    fn update_minimap_settings(cx: &mut TestAppContext, f: impl Fn(&mut MinimapContent)) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    f(settings.minimap.get_or_insert(MinimapContent {
+                        show: None,
+                        side: None,
+                        width: None,
+                        scale: None,
+                        selections: None,
+                        search_results: None,
+                        diagnostics: None,
+                    }))
+                });
+            });
+        });
+    }
+
+    fn draw_minimap(
+        editor: &Entity<Editor>,
+        editor_size: Size<Pixels>,
+        cx: &mut VisualTestContext,
+    ) -> EditorLayout {
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        let (_, state) = cx.draw(point(px(0.), px(0.)), editor_size, |_, _| {
+            EditorElement::new(editor, style)
+        });
+        state
+    }
+
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_minimap_settings(cx, |minimap| {
+            minimap.show = Some(ShowMinimap::Auto);
+            minimap.width = Some(90.);
+            minimap.scale = Some(0.5);
+        });
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = MultiBuffer::build_simple(&sample_text(5, 6, 'a'), cx);
+            Editor::new(EditorMode::full(), buffer, None, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let editor_size = size(px(500.), px(500.));
+
+        // The buffer fits in the editor, so there's no minimap.
+        let state = draw_minimap(&editor, editor_size, cx);
+        assert!(state.minimap.is_none());
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_text(sample_text(100, 6, 'a'), window, cx)
+        });
+        let state = draw_minimap(&editor, editor_size, cx);
+        let minimap = state.minimap.as_ref().unwrap();
+        let bounds = minimap.hitbox.bounds;
+        assert_eq!(bounds.size, size(px(90.), px(500.)));
+        assert_eq!(bounds.right(), px(500.));
+        assert_eq!(bounds.left(), state.position_map.text_hitbox.bounds.right());
+        assert_eq!(minimap.line_height, state.position_map.line_height * 0.5);
+
+        // Every line shown in the minimap has a quad for its text, from the top of the minimap.
+        assert_eq!(minimap.first_row, 0.);
+        let minimap_rows = (bounds.size.height / minimap.line_height).ceil() as usize;
+        assert_eq!(minimap.text_quads.len(), minimap_rows.min(100));
+        assert_eq!(minimap.text_quads[0].bounds.left(), bounds.left());
+        assert!(minimap.text_quads[0].bounds.top() >= bounds.top());
+
+        // The thumb covers the rows visible in the editor.
+        assert_eq!(minimap.thumb_bounds.origin, bounds.origin);
+        assert_eq!(
+            minimap.thumb_bounds.size.height,
+            minimap.height_in_lines * minimap.line_height
+        );
+
+        // Once the editor is scrolled to the end, so is the minimap.
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_scroll_position(point(0., 1000.), window, cx)
+        });
+        let state = draw_minimap(&editor, editor_size, cx);
+        let minimap = state.minimap.as_ref().unwrap();
+        let minimap_rows = minimap.hitbox.bounds.size.height / minimap.line_height;
+        assert!(minimap.first_row > 0.);
+        assert!((minimap.first_row + minimap_rows - 100.).abs() < 0.01);
+
+        // The minimap can be shown between the gutter and the text, and never takes more than a
+        // third of the editor's width.
+        update_minimap_settings(cx, |minimap| minimap.side = Some(MinimapSide::Left));
+        let state = draw_minimap(&editor, size(px(150.), px(500.)), cx);
+        let bounds = state.minimap.as_ref().unwrap().hitbox.bounds;
+        assert_eq!(bounds.size.width, px(50.));
+        assert_eq!(
+            bounds.left(),
+            state.position_map.gutter_hitbox.bounds.right()
+        );
+        assert_eq!(bounds.right(), state.position_map.text_hitbox.bounds.left());
+
+        update_minimap_settings(cx, |minimap| minimap.show = Some(ShowMinimap::Never));
+        let state = draw_minimap(&editor, editor_size, cx);
+        assert!(state.minimap.is_none());
+    }
+
+    #[gpui::test]
+    fn test_minimap_click_and_drag_scroll_editor(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_minimap_settings(cx, |minimap| minimap.show = Some(ShowMinimap::Always));
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = MultiBuffer::build_simple(&sample_text(100, 6, 'a'), cx);
+            Editor::new(EditorMode::full(), buffer, None, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let editor_size = size(px(500.), px(500.));
+        cx.simulate_resize(editor_size);
+        cx.run_until_parked();
+
+        let state = draw_minimap(&editor, editor_size, cx);
+        let minimap = state.minimap.as_ref().unwrap();
+        let bounds = minimap.hitbox.bounds;
+        let line_height = minimap.line_height;
+        let height_in_lines = minimap.height_in_lines;
+        assert_eq!(minimap.first_row, 0.);
+        let scroll_top = |editor: &Entity<Editor>, cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| editor.scroll_position(cx).y)
+        };
+        assert_eq!(scroll_top(&editor, cx), 0.);
+
+        // Clicking centers the row under the mouse in the editor.
+        let position = bounds.origin + point(px(10.), line_height * 50.);
+        cx.simulate_mouse_down(position, MouseButton::Left, Modifiers::none());
+        assert!((scroll_top(&editor, cx) - (50. - height_in_lines / 2.)).abs() < 0.01);
+
+        // Dragging keeps scrolling until the mouse is released.
+        let position = bounds.origin + point(px(10.), line_height * 60.);
+        cx.simulate_mouse_move(position, MouseButton::Left, Modifiers::none());
+        assert!((scroll_top(&editor, cx) - (60. - height_in_lines / 2.)).abs() < 0.01);
+
+        cx.simulate_mouse_up(position, MouseButton::Left, Modifiers::none());
+        let scroll_top_after_drag = scroll_top(&editor, cx);
+        let position = bounds.origin + point(px(10.), line_height * 70.);
+        cx.simulate_mouse_move(position, None, Modifiers::none());
+        assert_eq!(scroll_top(&editor, cx), scroll_top_after_drag);
+        assert!(!editor.read_with(cx, |editor, _| {
+            editor.scroll_manager.is_dragging_minimap()
+        }));
+
+        // Clicking near the top doesn't scroll past the start of the buffer.
+        cx.simulate_mouse_down(bounds.origin, MouseButton::Left, Modifiers::none());
+        assert_eq!(scroll_top(&editor, cx), 0.);
+    }
+
+    fn update_sticky_scroll_settings(cx: &mut TestAppContext, max_depth: usize) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: Some(max_depth),
+                    });
+                });
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_sticky_scopes(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_sticky_scroll_settings(cx, 3);
+
+        let language = Arc::new(
+            language::Language::new(
+                language::LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (mod_item "mod" @context name: (_) @name) @item
+                (impl_item "impl" @context type: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = indoc::indoc! {"
+            mod outer {
+                impl Foo {
+                    fn one() {
+                        let a = 1;
+                        let b = 2;
+                        let c = 3;
+                    }
+                    const A: u32 = 1;
+                    const B: u32 = 2;
+                    const C: u32 = 3;
+                    const D: u32 = 4;
+                    fn two() {
+                        let d = 4;
+                    }
+                }
+            }
+        "};
+        let window = cx.add_window(|window, cx| {
+            let buffer = cx.new(|cx| language::Buffer::local(text, cx).with_language(language, cx));
+            let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+            Editor::new(EditorMode::full(), buffer, None, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        cx.run_until_parked();
+
+        // Returns the row each header shows and its offset from the top of the editor, in lines.
+        let sticky_scopes = |scroll_top: f32, cx: &mut VisualTestContext| {
+            editor.update_in(cx, |editor, window, cx| {
+                editor.set_scroll_position(point(0., scroll_top), window, cx)
+            });
+            let (_, state) = cx.draw(point(px(0.), px(0.)), size(px(500.), px(500.)), |_, _| {
+                EditorElement::new(&editor, style.clone())
+            });
+            let text_top = state.position_map.text_hitbox.origin.y;
+            let line_height = state.position_map.line_height;
+            state
+                .sticky_scopes
+                .iter()
+                .map(|scope| (scope.row.0, (scope.origin.y - text_top) / line_height))
+                .collect::<Vec<_>>()
+        };
+
+        // Nothing is pinned while the opening lines are visible.
+        assert!(sticky_scopes(0., cx).is_empty());
+
+        // Nested scopes are pinned outermost first, one line each.
+        assert_eq!(sticky_scopes(3., cx), [(0, 0.), (1, 1.), (2, 2.)]);
+
+        // Once the end of a scope reaches the headers, its header is pushed up with it.
+        assert_eq!(sticky_scopes(5., cx), [(0, 0.), (1, 1.), (2, 1.)]);
+        assert_eq!(sticky_scopes(6.5, cx), [(0, 0.), (1, 1.), (2, -0.5)]);
+
+        // After scrolling past the end of a scope, only the scopes still enclosing the top line
+        // are pinned.
+        assert_eq!(sticky_scopes(8., cx), [(0, 0.), (1, 1.)]);
+        assert_eq!(sticky_scopes(12., cx), [(0, 0.), (1, 1.), (11, 1.)]);
+
+        // No more than the maximum depth of scopes is pinned.
+        update_sticky_scroll_settings(cx, 2);
+        assert_eq!(sticky_scopes(3., cx), [(0, 0.), (1, 1.)]);
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
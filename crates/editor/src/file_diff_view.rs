@@ -0,0 +1,744 @@
+use crate::{Editor, EditorEvent, GoToHunk, GoToPreviousHunk};
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{
+    AnyView, App, AsyncApp, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, IntoElement, Render, Subscription, Task, WeakEntity, Window, actions,
+};
+use language::{Buffer, BufferEvent, Point};
+use multi_buffer::{MultiBuffer, MultiBufferDiffHunk};
+use project::{Project, ProjectPath};
+use std::{any::TypeId, path::PathBuf, time::Duration};
+use text::ToOffset as _;
+use ui::{IconButtonShape, Tooltip, prelude::*};
+use workspace::{
+    Item, ItemHandle as _, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+    notifications::NotifyTaskExt as _, searchable::SearchableItemHandle,
+};
+
+actions!(file_diff_view, [ApplyHunkToLeft, ApplyHunkToRight]);
+
+/// Shows the differences between two buffers, as diff hunks in an editor for the newer
+/// ("right") buffer.
+///
+/// The diff is recalculated whenever either buffer changes. Either buffer may be a file, or a
+/// scratch buffer holding text from elsewhere, such as the clipboard or a selection.
+pub struct FileDiffView {
+    editor: Entity<Editor>,
+    old_buffer: Entity<Buffer>,
+    new_buffer: Entity<Buffer>,
+    old_label: Option<SharedString>,
+    new_label: Option<SharedString>,
+    diff: Entity<BufferDiff>,
+    buffer_changes_tx: mpsc::UnboundedSender<()>,
+    _recalculate_diff_task: Task<Result<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl FileDiffView {
+    /// Opens the two files and adds a view comparing them to the active pane.
+    pub fn open(
+        old_path: ProjectPath,
+        new_path: ProjectPath,
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let project = workspace.project().clone();
+        let open_buffers = project.update(cx, |project, cx| {
+            (
+                project.open_buffer(old_path, cx),
+                project.open_buffer(new_path, cx),
+            )
+        });
+        let workspace = workspace.weak_handle();
+        window.spawn(cx, async move |cx| {
+            let old_buffer = open_buffers.0.await?;
+            let new_buffer = open_buffers.1.await?;
+            Self::add_to_workspace((old_buffer, None), (new_buffer, None), workspace, cx).await
+        })
+    }
+
+    /// Opens two files by their absolute paths, which need not belong to the project, and adds a
+    /// view comparing them to the active pane.
+    pub fn open_abs_paths(
+        old: (PathBuf, Option<SharedString>),
+        new: (PathBuf, Option<SharedString>),
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let project = workspace.project().clone();
+        let workspace = workspace.weak_handle();
+        window.spawn(cx, async move |cx| {
+            let old_buffer = open_abs_path_buffer(&project, old.0, cx).await?;
+            let new_buffer = open_abs_path_buffer(&project, new.0, cx).await?;
+            Self::add_to_workspace((old_buffer, old.1), (new_buffer, new.1), workspace, cx).await
+        })
+    }
+
+    /// Opens the file and adds a view comparing the given text against it to the active pane.
+    pub fn open_with_text(
+        old_text: String,
+        old_label: impl Into<SharedString>,
+        new_path: ProjectPath,
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let open_buffer = workspace
+            .project()
+            .update(cx, |project, cx| project.open_buffer(new_path, cx));
+        let old_label = old_label.into();
+        let workspace = workspace.weak_handle();
+        window.spawn(cx, async move |cx| {
+            let new_buffer = open_buffer.await?;
+            let old_buffer = cx.update(|_, cx| Self::buffer_for_text(old_text, &new_buffer, cx))?;
+            Self::add_to_workspace(
+                (old_buffer, Some(old_label)),
+                (new_buffer, None),
+                workspace,
+                cx,
+            )
+            .await
+        })
+    }
+
+    /// Adds a view comparing two already open buffers to the active pane.
+    ///
+    /// Labels are shown in place of the buffers' file names, which is useful for buffers without
+    /// a file.
+    pub fn compare_buffers(
+        old: (Entity<Buffer>, Option<SharedString>),
+        new: (Entity<Buffer>, Option<SharedString>),
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            Self::add_to_workspace(old, new, workspace, cx).await
+        })
+    }
+
+    /// Creates a scratch buffer containing the given text, using the same language as `like`.
+    pub fn buffer_for_text(text: String, like: &Entity<Buffer>, cx: &mut App) -> Entity<Buffer> {
+        let like = like.read(cx);
+        let language = like.language().cloned();
+        let language_registry = like.language_registry();
+        cx.new(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            if let Some(language_registry) = language_registry {
+                buffer.set_language_registry(language_registry);
+            }
+            buffer.set_language(language, cx);
+            buffer
+        })
+    }
+
+    async fn add_to_workspace(
+        (old_buffer, old_label): (Entity<Buffer>, Option<SharedString>),
+        (new_buffer, new_label): (Entity<Buffer>, Option<SharedString>),
+        workspace: WeakEntity<Workspace>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<Entity<Self>> {
+        let diff = build_buffer_diff(&old_buffer, &new_buffer, cx).await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            let diff_view = cx.new(|cx| {
+                let mut diff_view = FileDiffView::new(
+                    old_buffer,
+                    new_buffer,
+                    diff,
+                    workspace.project().clone(),
+                    window,
+                    cx,
+                );
+                diff_view.old_label = old_label;
+                diff_view.new_label = new_label;
+                diff_view
+            });
+            workspace.add_item_to_active_pane(Box::new(diff_view.clone()), None, true, window, cx);
+            diff_view
+        })
+    }
+
+    pub fn new(
+        old_buffer: Entity<Buffer>,
+        new_buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(new_buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), window, cx);
+            editor.disable_inline_diagnostics();
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let (buffer_changes_tx, mut buffer_changes_rx) = mpsc::unbounded();
+        let subscriptions = [&old_buffer, &new_buffer]
+            .into_iter()
+            .map(|buffer| {
+                cx.subscribe(buffer, |this: &mut Self, _, event, _| {
+                    if let BufferEvent::Edited | BufferEvent::Reloaded = event {
+                        this.buffer_changes_tx.unbounded_send(()).ok();
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            editor,
+            buffer_changes_tx,
+            _recalculate_diff_task: cx.spawn(async move |this, cx| {
+                while buffer_changes_rx.next().await.is_some() {
+                    // Coalesce bursts of edits into a single recalculation.
+                    loop {
+                        cx.background_executor()
+                            .timer(RECALCULATE_DIFF_DEBOUNCE)
+                            .await;
+                        let mut had_further_changes = false;
+                        while let Ok(Some(())) = buffer_changes_rx.try_next() {
+                            had_further_changes = true;
+                        }
+                        if !had_further_changes {
+                            break;
+                        }
+                    }
+
+                    let (old_buffer, new_buffer) = this.read_with(cx, |this, _| {
+                        (this.old_buffer.clone(), this.new_buffer.clone())
+                    })?;
+                    let new_snapshot =
+                        new_buffer.read_with(cx, |buffer, _| buffer.text_snapshot())?;
+                    let diff = this.read_with(cx, |this, _| this.diff.clone())?;
+                    let recalculated = diff.update(cx, |diff, cx| {
+                        diff.set_base_text_buffer(old_buffer, new_snapshot, cx)
+                    })?;
+                    recalculated.await.ok();
+                }
+                Ok(())
+            }),
+            old_buffer,
+            new_buffer,
+            old_label: None,
+            new_label: None,
+            diff,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn old_buffer(&self) -> &Entity<Buffer> {
+        &self.old_buffer
+    }
+
+    pub fn new_buffer(&self) -> &Entity<Buffer> {
+        &self.new_buffer
+    }
+
+    fn buffer_path(buffer: &Entity<Buffer>, cx: &App) -> Option<PathBuf> {
+        buffer.read(cx).file().map(|file| file.full_path(cx))
+    }
+
+    fn buffer_name(
+        buffer: &Entity<Buffer>,
+        label: Option<&SharedString>,
+        cx: &App,
+    ) -> SharedString {
+        if let Some(label) = label {
+            return label.clone();
+        }
+        buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().to_string().into())
+            .unwrap_or_else(|| "untitled".into())
+    }
+
+    fn selected_hunks(&self, window: &mut Window, cx: &mut App) -> Vec<MultiBufferDiffHunk> {
+        self.editor.update(cx, |editor, cx| {
+            let ranges = editor.selections.ranges::<Point>(cx);
+            editor.snapshot(window, cx).hunks_for_ranges(ranges)
+        })
+    }
+
+    /// Whether the diff's base text still matches the old buffer, so that hunks' base ranges can
+    /// be used to edit it.
+    fn is_diff_current(&self, cx: &App) -> bool {
+        let old_buffer = self.old_buffer.read(cx);
+        let base_text = self.diff.read(cx).base_text();
+        base_text.len() == old_buffer.len() && base_text.text() == old_buffer.text()
+    }
+
+    /// Copies the selected hunks from the new buffer into the old one.
+    fn apply_hunk_to_left(
+        &mut self,
+        _: &ApplyHunkToLeft,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.apply_selected_hunks(Self::apply_hunks_to_left, window, cx);
+    }
+
+    /// Replaces the selected hunks in the new buffer with the old buffer's text.
+    fn apply_hunk_to_right(
+        &mut self,
+        _: &ApplyHunkToRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.apply_selected_hunks(Self::apply_hunks_to_right, window, cx);
+    }
+
+    /// Applies the selected hunks with `apply`. Hunks' base ranges and text refer to the old
+    /// buffer as it was when the diff was calculated, so if it changed since, the diff is
+    /// recalculated before the hunks are read.
+    fn apply_selected_hunks(
+        &mut self,
+        apply: fn(&mut Self, Vec<MultiBufferDiffHunk>, &mut Context<Self>),
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_diff_current(cx) {
+            let hunks = self.selected_hunks(window, cx);
+            apply(self, hunks, cx);
+            return;
+        }
+
+        let old_buffer = self.old_buffer.clone();
+        let new_snapshot = self.new_buffer.read(cx).text_snapshot();
+        let recalculated = self.diff.update(cx, |diff, cx| {
+            diff.set_base_text_buffer(old_buffer, new_snapshot, cx)
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            recalculated.await.ok();
+            this.update_in(cx, |this, window, cx| {
+                anyhow::ensure!(
+                    this.is_diff_current(cx),
+                    "The left side changed while its differences were being recalculated"
+                );
+                let hunks = this.selected_hunks(window, cx);
+                apply(this, hunks, cx);
+                Ok(())
+            })?
+        })
+        .detach_and_notify_err(window, cx);
+    }
+
+    fn apply_hunks_to_left(&mut self, hunks: Vec<MultiBufferDiffHunk>, cx: &mut Context<Self>) {
+        let new_buffer = self.new_buffer.read(cx);
+        let edits = hunks
+            .into_iter()
+            .map(|hunk| {
+                let range = hunk.buffer_range.to_offset(new_buffer);
+                let text = new_buffer.text_for_range(range).collect::<String>();
+                (hunk.diff_base_byte_range, text)
+            })
+            .collect::<Vec<_>>();
+        if !edits.is_empty() {
+            self.old_buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        }
+    }
+
+    fn apply_hunks_to_right(&mut self, hunks: Vec<MultiBufferDiffHunk>, cx: &mut Context<Self>) {
+        let base_text = self.diff.read(cx).base_text();
+        let edits = hunks
+            .into_iter()
+            .map(|hunk| {
+                let text = base_text
+                    .text_for_range(hunk.diff_base_byte_range)
+                    .collect::<String>();
+                (hunk.buffer_range, text)
+            })
+            .collect::<Vec<_>>();
+        if !edits.is_empty() {
+            self.new_buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        }
+    }
+}
+
+async fn build_buffer_diff(
+    old_buffer: &Entity<Buffer>,
+    new_buffer: &Entity<Buffer>,
+    cx: &mut AsyncApp,
+) -> Result<Entity<BufferDiff>> {
+    let new_snapshot = new_buffer.read_with(cx, |buffer, _| buffer.text_snapshot())?;
+    let diff = cx.new(|cx| BufferDiff::new(&new_snapshot, cx))?;
+    let calculated = diff.update(cx, |diff, cx| {
+        diff.set_base_text_buffer(old_buffer.clone(), new_snapshot, cx)
+    })?;
+    calculated.await.ok();
+    Ok(diff)
+}
+
+async fn open_abs_path_buffer(
+    project: &Entity<Project>,
+    abs_path: PathBuf,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Buffer>> {
+    let (worktree, relative_path) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(&abs_path, false, cx)
+        })?
+        .await?;
+    let project_path = ProjectPath {
+        worktree_id: worktree.read_with(cx, |worktree, _| worktree.id())?,
+        path: relative_path.into(),
+    };
+    project
+        .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+        .await
+}
+
+impl Render for FileDiffView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .key_context("FileDiffView")
+            .on_action(cx.listener(Self::apply_hunk_to_left))
+            .on_action(cx.listener(Self::apply_hunk_to_right))
+            .child(self.editor.clone())
+    }
+}
+
+impl Focusable for FileDiffView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for FileDiffView {}
+
+impl Item for FileDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff))
+    }
+
+    fn tab_content_text(&self, _window: &Window, cx: &App) -> Option<SharedString> {
+        Some(
+            format!(
+                "{} ↔ {}",
+                Self::buffer_name(&self.old_buffer, self.old_label.as_ref(), cx),
+                Self::buffer_name(&self.new_buffer, self.new_label.as_ref(), cx)
+            )
+            .into(),
+        )
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let describe = |buffer, label: Option<&SharedString>| match label {
+            Some(label) => Some(label.to_string()),
+            None => Some(Self::buffer_path(buffer, cx)?.display().to_string()),
+        };
+        let old = describe(&self.old_buffer, self.old_label.as_ref())?;
+        let new = describe(&self.new_buffer, self.new_label.as_ref())?;
+        Some(format!("{old} ↔ {new}").into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(workspace::item::ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("File Diff View Opened")
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: workspace::ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        [&self.old_buffer, &self.new_buffer]
+            .into_iter()
+            .any(|buffer| buffer.read(cx).file().is_some() && buffer.read(cx).is_dirty())
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.old_buffer.read(cx).has_conflict() || self.new_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        // Hunks applied to the left modify the old buffer, which isn't shown in the editor.
+        let old_buffer = self.old_buffer.read(cx);
+        let save_old_buffer = if old_buffer.file().is_some() && old_buffer.is_dirty() {
+            let old_buffer = self.old_buffer.clone();
+            Some(project.update(cx, |project, cx| project.save_buffer(old_buffer, cx)))
+        } else {
+            None
+        };
+        let save_new_buffer = self.editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        });
+        cx.background_spawn(async move {
+            if let Some(save_old_buffer) = save_old_buffer {
+                save_old_buffer.await?;
+            }
+            save_new_buffer.await
+        })
+    }
+}
+
+/// Toolbar controls for navigating and applying hunks in the active [`FileDiffView`].
+pub struct FileDiffViewToolbar {
+    diff_view: Option<Entity<FileDiffView>>,
+}
+
+impl FileDiffViewToolbar {
+    pub fn new() -> Self {
+        Self { diff_view: None }
+    }
+}
+
+impl Render for FileDiffViewToolbar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(diff_view) = &self.diff_view else {
+            return div();
+        };
+        let focus_handle = diff_view.focus_handle(cx);
+        let button = |id: &'static str,
+                      icon: IconName,
+                      tooltip: &'static str,
+                      action: Box<dyn gpui::Action>| {
+            let focus_handle = focus_handle.clone();
+            IconButton::new(id, icon)
+                .shape(IconButtonShape::Square)
+                .icon_size(IconSize::Small)
+                .tooltip({
+                    let focus_handle = focus_handle.clone();
+                    let action = action.boxed_clone();
+                    move |window, cx| {
+                        Tooltip::for_action_in(tooltip, action.as_ref(), &focus_handle, window, cx)
+                    }
+                })
+                .on_click(move |_, window, cx| {
+                    focus_handle.dispatch_action(action.as_ref(), window, cx)
+                })
+        };
+
+        h_flex()
+            .gap_1()
+            .child(button(
+                "previous-hunk",
+                IconName::ArrowUp,
+                "Previous Hunk",
+                Box::new(GoToPreviousHunk),
+            ))
+            .child(button(
+                "next-hunk",
+                IconName::ArrowDown,
+                "Next Hunk",
+                Box::new(GoToHunk),
+            ))
+            .child(button(
+                "apply-hunk-to-left",
+                IconName::ArrowLeft,
+                "Apply Hunk to Left",
+                Box::new(ApplyHunkToLeft),
+            ))
+            .child(button(
+                "apply-hunk-to-right",
+                IconName::ArrowRight,
+                "Apply Hunk to Right",
+                Box::new(ApplyHunkToRight),
+            ))
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for FileDiffViewToolbar {}
+
+impl ToolbarItemView for FileDiffViewToolbar {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn workspace::ItemHandle>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> ToolbarItemLocation {
+        self.diff_view = active_pane_item.and_then(|item| item.downcast::<FileDiffView>());
+        if self.diff_view.is_some() {
+            ToolbarItemLocation::PrimaryRight
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+
+    async fn compare_texts<'a>(
+        old_text: &str,
+        new_text: &str,
+        cx: &'a mut TestAppContext,
+    ) -> (
+        Entity<FileDiffView>,
+        Entity<Buffer>,
+        Entity<Buffer>,
+        &'a mut VisualTestContext,
+    ) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+        let old_buffer = cx.new(|cx| Buffer::local(old_text, cx));
+        let new_buffer = cx.new(|cx| Buffer::local(new_text, cx));
+        let diff_view = workspace
+            .update_in(cx, |workspace, window, cx| {
+                FileDiffView::compare_buffers(
+                    (old_buffer.clone(), None),
+                    (new_buffer.clone(), None),
+                    workspace.weak_handle(),
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        (diff_view, old_buffer, new_buffer, cx)
+    }
+
+    fn select_row(diff_view: &Entity<FileDiffView>, row: u32, cx: &mut VisualTestContext) {
+        diff_view.update_in(cx, |diff_view, window, cx| {
+            diff_view.editor.update(cx, |editor, cx| {
+                editor.change_selections(None, window, cx, |selections| {
+                    selections.select_ranges([Point::new(row, 0)..Point::new(row, 0)])
+                });
+            });
+        });
+    }
+
+    #[gpui::test]
+    async fn test_apply_hunks(cx: &mut TestAppContext) {
+        let (diff_view, old_buffer, new_buffer, cx) =
+            compare_texts("one\ntwo\nthree\n", "one\nTWO\nthree\nfour\n", cx).await;
+
+        select_row(&diff_view, 1, cx);
+        diff_view.update_in(cx, |diff_view, window, cx| {
+            diff_view.apply_hunk_to_left(&ApplyHunkToLeft, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            old_buffer.read_with(cx, |buffer, _| buffer.text()),
+            "one\nTWO\nthree\n"
+        );
+
+        // Only the selected hunk is applied, and the diff is recalculated to apply it even though
+        // the left side just changed.
+        select_row(&diff_view, 3, cx);
+        diff_view.update_in(cx, |diff_view, window, cx| {
+            diff_view.apply_hunk_to_right(&ApplyHunkToRight, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            new_buffer.read_with(cx, |buffer, _| buffer.text()),
+            "one\nTWO\nthree\n"
+        );
+        assert_eq!(
+            old_buffer.read_with(cx, |buffer, _| buffer.text()),
+            "one\nTWO\nthree\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_apply_hunk_after_left_side_changed(cx: &mut TestAppContext) {
+        let (diff_view, old_buffer, new_buffer, cx) =
+            compare_texts("one\ntwo\nthree\n", "one\nTWO\nthree\n", cx).await;
+
+        // The hunk's base range is stale once a line is added above it on the left.
+        old_buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        select_row(&diff_view, 1, cx);
+        diff_view.update_in(cx, |diff_view, window, cx| {
+            diff_view.apply_hunk_to_left(&ApplyHunkToLeft, window, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            old_buffer.read_with(cx, |buffer, _| buffer.text()),
+            "zero\none\nTWO\nthree\n"
+        );
+        assert_eq!(
+            new_buffer.read_with(cx, |buffer, _| buffer.text()),
+            "one\nTWO\nthree\n"
+        );
+    }
+}
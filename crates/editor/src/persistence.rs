@@ -113,6 +113,13 @@ define_connection!(
     //   start: usize,
     //   end: usize,
     // )
+    //
+    // undo_histories(
+    //   path: PathBuf,
+    //   content_hash: Vec<u8>,
+    //   history: String, // JSON-encoded text::SerializedHistory
+    //   updated_at: String,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> = &[
         sql! (
             CREATE TABLE editors(
@@ -188,6 +195,14 @@ define_connection!(
                 ON DELETE CASCADE
             ) STRICT;
         ),
+        sql! (
+            CREATE TABLE undo_histories (
+                path BLOB NOT NULL PRIMARY KEY,
+                content_hash BLOB NOT NULL,
+                history TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            ) STRICT;
+        ),
     ];
 );
 
@@ -196,6 +211,9 @@ define_connection!(
 // > which defaults to <..> 32766 for SQLite versions after 3.32.0.
 const MAX_QUERY_PLACEHOLDERS: usize = 32000;
 
+/// The number of files whose undo history is kept, most recently edited first.
+const MAX_UNDO_HISTORIES: usize = 500;
+
 impl EditorDb {
     query! {
         pub fn get_serialized_editor(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<SerializedEditor>> {
@@ -372,6 +390,44 @@ VALUES {placeholders};
         Ok(())
     }
 
+    query! {
+        pub fn get_undo_history(path: PathBuf) -> Result<Option<(Vec<u8>, String)>> {
+            SELECT content_hash, history
+            FROM undo_histories
+            WHERE path = ?
+        }
+    }
+
+    pub async fn save_undo_history(
+        &self,
+        path: PathBuf,
+        content_hash: Vec<u8>,
+        history: String,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT INTO undo_histories(path, content_hash, history, updated_at)
+                VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)
+                ON CONFLICT DO UPDATE SET
+                    content_hash = ?2,
+                    history = ?3,
+                    updated_at = CURRENT_TIMESTAMP
+            ))?((path, content_hash, history))?;
+            conn.exec_bound(sql!(
+                DELETE FROM undo_histories WHERE path NOT IN (
+                    SELECT path FROM undo_histories ORDER BY updated_at DESC LIMIT ?
+                )
+            ))?(MAX_UNDO_HISTORIES)
+        })
+        .await
+    }
+
+    query! {
+        pub async fn delete_undo_history(path: PathBuf) -> Result<()> {
+            DELETE FROM undo_histories WHERE path = ?
+        }
+    }
+
     pub async fn delete_unloaded_items(
         &self,
         workspace: WorkspaceId,
@@ -478,4 +534,24 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_undo_history() {
+        let path = PathBuf::from("/undo/history.txt");
+        assert_eq!(DB.get_undo_history(path.clone()).unwrap(), None);
+
+        DB.save_undo_history(path.clone(), vec![1, 2, 3], "first".into())
+            .await
+            .unwrap();
+        DB.save_undo_history(path.clone(), vec![4, 5, 6], "second".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_undo_history(path.clone()).unwrap(),
+            Some((vec![4, 5, 6], "second".to_string()))
+        );
+
+        DB.delete_undo_history(path.clone()).await.unwrap();
+        assert_eq!(DB.get_undo_history(path).unwrap(), None);
+    }
 }
@@ -0,0 +1,181 @@
+//! Keeps the undo history of files across restarts.
+//!
+//! Histories are stored per path along with a hash of the text they were serialized with, so a
+//! history is only restored if the file wasn't changed outside of Zed in the meantime.
+
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::Result;
+use collections::HashMap;
+use gpui::{App, AppContext as _, Entity, EntityId, Global, Subscription, Task};
+use language::{Buffer, BufferEvent, Rope, SerializedHistory};
+use settings::Settings as _;
+use sha2::{Digest as _, Sha256};
+use util::ResultExt as _;
+
+use crate::{EditorSettings, persistence::DB};
+
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct PersistentUndo {
+    buffers: HashMap<EntityId, TrackedBuffer>,
+}
+
+impl Global for PersistentUndo {}
+
+struct TrackedBuffer {
+    /// Whether the saved history was restored or found not to apply. Until then the buffer's
+    /// history isn't saved, so that edits made while it loads don't overwrite it.
+    restored: bool,
+    _subscription: Subscription,
+    _restore_task: Task<()>,
+    _save_task: Option<Task<()>>,
+}
+
+/// Restores the saved undo history of a file's buffer, and saves it whenever it changes from then
+/// on. Buffers are only tracked once, however many editors they're open in.
+pub(crate) fn track_buffer(buffer: &Entity<Buffer>, cx: &mut App) {
+    if !EditorSettings::get_global(cx).persistent_undo.enabled {
+        return;
+    }
+    let Some(abs_path) = abs_path(buffer.read(cx), cx) else {
+        return;
+    };
+    let buffer_id = buffer.entity_id();
+    if cx
+        .default_global::<PersistentUndo>()
+        .buffers
+        .contains_key(&buffer_id)
+    {
+        return;
+    }
+
+    let subscription = cx.subscribe(buffer, |buffer, event, cx| match event {
+        BufferEvent::Edited | BufferEvent::Saved | BufferEvent::Reloaded => {
+            schedule_save(&buffer, cx)
+        }
+        _ => {}
+    });
+    cx.observe_release(buffer, move |buffer, cx| {
+        let restored = cx
+            .default_global::<PersistentUndo>()
+            .buffers
+            .remove(&buffer_id)
+            .is_some_and(|tracked| tracked.restored);
+        if restored {
+            if let Some(save) = save_history(buffer, cx) {
+                save.detach_and_log_err(cx);
+            }
+        }
+    })
+    .detach();
+
+    // A saved history only applies to the text it was saved with, and it's restored before any
+    // edits made in the meantime, so it's compared with the text the buffer was loaded with.
+    let loaded_buffer = buffer.read(cx);
+    let restore_task = if loaded_buffer.peek_undo_stack().is_some()
+        || loaded_buffer.peek_redo_stack().is_some()
+    {
+        None
+    } else {
+        let loaded_text = loaded_buffer.as_rope().clone();
+        let load = cx.background_spawn(async move {
+            let history = DB.get_undo_history(abs_path)?;
+            anyhow::Ok(history.filter(|(content_hash, _)| *content_hash == hash_text(&loaded_text)))
+        });
+        let buffer = buffer.downgrade();
+        Some(cx.spawn(async move |cx| {
+            let history = load.await.log_err().flatten().and_then(|(_, history)| {
+                serde_json::from_str::<SerializedHistory>(&history).log_err()
+            });
+            cx.update(|cx| {
+                let Some(buffer) = buffer.upgrade() else {
+                    return;
+                };
+                if let Some(history) = history {
+                    buffer.update(cx, |buffer, cx| buffer.restore_history(&history, cx));
+                }
+                if let Some(tracked) = cx
+                    .default_global::<PersistentUndo>()
+                    .buffers
+                    .get_mut(&buffer.entity_id())
+                {
+                    tracked.restored = true;
+                }
+                schedule_save(&buffer, cx);
+            })
+            .ok();
+        }))
+    };
+
+    cx.default_global::<PersistentUndo>().buffers.insert(
+        buffer_id,
+        TrackedBuffer {
+            restored: restore_task.is_none(),
+            _subscription: subscription,
+            _restore_task: restore_task.unwrap_or_else(|| Task::ready(())),
+            _save_task: None,
+        },
+    );
+}
+
+fn schedule_save(buffer: &Entity<Buffer>, cx: &mut App) {
+    let buffer_id = buffer.entity_id();
+    let Some(tracked) = cx
+        .default_global::<PersistentUndo>()
+        .buffers
+        .get_mut(&buffer_id)
+    else {
+        return;
+    };
+    if !tracked.restored {
+        return;
+    }
+    let weak_buffer = buffer.downgrade();
+    tracked._save_task = Some(cx.spawn(async move |cx| {
+        cx.background_executor().timer(SAVE_DEBOUNCE).await;
+        let Some(save) = weak_buffer
+            .update(cx, |buffer, cx| save_history(buffer, cx))
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        save.await.log_err();
+    }));
+}
+
+/// Saves the buffer's history. Only a copy of the history is taken on the main thread; undoing
+/// and redoing it to serialize it happens in the background.
+fn save_history(buffer: &Buffer, cx: &App) -> Option<Task<Result<()>>> {
+    let settings = EditorSettings::get_global(cx).persistent_undo;
+    if !settings.enabled {
+        return None;
+    }
+    let abs_path = abs_path(buffer, cx)?;
+    let snapshot = buffer.history_snapshot()?;
+    let max_size = settings.max_size_kb.saturating_mul(1024);
+    Some(cx.background_spawn(async move {
+        let history = snapshot.serialize(max_size);
+        if history.is_empty() {
+            return DB.delete_undo_history(abs_path).await;
+        }
+        let history = serde_json::to_string(&history)?;
+        DB.save_undo_history(abs_path, hash_text(snapshot.text()), history)
+            .await
+    }))
+}
+
+fn abs_path(buffer: &Buffer, cx: &App) -> Option<PathBuf> {
+    let file = buffer.file()?.as_local()?;
+    Some(file.abs_path(cx))
+}
+
+fn hash_text(text: &Rope) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for chunk in text.chunks() {
+        hasher.update(chunk.as_bytes());
+    }
+    hasher.finalize().to_vec()
+}
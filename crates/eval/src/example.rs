@@ -418,6 +418,7 @@ impl Example {
                             ThreadEvent::MessageDeleted(_) |
                             ThreadEvent::SummaryChanged |
                             ThreadEvent::SummaryGenerated |
+                            ThreadEvent::ToolReplayed(_) |
                             ThreadEvent::CheckpointChanged => {
                                 if std::env::var("ZED_EVAL_DEBUG").is_ok() {
                                     println!("{}Event: {:#?}", log_prefix, event);
@@ -506,6 +507,11 @@ impl Example {
             temperature: None,
             tools: Vec::new(),
             stop: Vec::new(),
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         };
 
         let response = send_language_model_request(model, request, cx).await?;
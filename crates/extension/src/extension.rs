@@ -102,6 +102,27 @@ pub trait Extension: Send + Sync + 'static {
         symbols: Vec<Symbol>,
     ) -> Result<Vec<Option<CodeLabel>>>;
 
+    async fn formatter_command(
+        &self,
+        formatter_id: Arc<str>,
+        request: FormatRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Command>;
+
+    async fn linter_command(
+        &self,
+        linter_id: Arc<str>,
+        request: FormatRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Command>;
+
+    async fn linter_diagnostics(
+        &self,
+        linter_id: Arc<str>,
+        request: FormatRequest,
+        output: ProcessOutput,
+    ) -> Result<Vec<LinterDiagnostic>>;
+
     async fn complete_slash_command_argument(
         &self,
         command: SlashCommand,
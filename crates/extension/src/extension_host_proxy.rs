@@ -25,6 +25,7 @@ pub struct ExtensionHostProxy {
     grammar_proxy: RwLock<Option<Arc<dyn ExtensionGrammarProxy>>>,
     language_proxy: RwLock<Option<Arc<dyn ExtensionLanguageProxy>>>,
     language_server_proxy: RwLock<Option<Arc<dyn ExtensionLanguageServerProxy>>>,
+    formatter_proxy: RwLock<Option<Arc<dyn ExtensionFormatterProxy>>>,
     snippet_proxy: RwLock<Option<Arc<dyn ExtensionSnippetProxy>>>,
     slash_command_proxy: RwLock<Option<Arc<dyn ExtensionSlashCommandProxy>>>,
     context_server_proxy: RwLock<Option<Arc<dyn ExtensionContextServerProxy>>>,
@@ -50,6 +51,7 @@ impl ExtensionHostProxy {
             grammar_proxy: RwLock::default(),
             language_proxy: RwLock::default(),
             language_server_proxy: RwLock::default(),
+            formatter_proxy: RwLock::default(),
             snippet_proxy: RwLock::default(),
             slash_command_proxy: RwLock::default(),
             context_server_proxy: RwLock::default(),
@@ -73,6 +75,10 @@ impl ExtensionHostProxy {
         self.language_server_proxy.write().replace(Arc::new(proxy));
     }
 
+    pub fn register_formatter_proxy(&self, proxy: impl ExtensionFormatterProxy) {
+        self.formatter_proxy.write().replace(Arc::new(proxy));
+    }
+
     pub fn register_snippet_proxy(&self, proxy: impl ExtensionSnippetProxy) {
         self.snippet_proxy.write().replace(Arc::new(proxy));
     }
@@ -327,6 +333,70 @@ impl ExtensionLanguageServerProxy for ExtensionHostProxy {
     }
 }
 
+pub trait ExtensionFormatterProxy: Send + Sync + 'static {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        language: LanguageName,
+    );
+
+    fn remove_formatter(&self, language: &LanguageName, formatter_id: &Arc<str>);
+
+    fn register_linter(
+        &self,
+        extension: Arc<dyn Extension>,
+        linter_id: Arc<str>,
+        language: LanguageName,
+    );
+
+    fn remove_linter(&self, language: &LanguageName, linter_id: &Arc<str>);
+}
+
+impl ExtensionFormatterProxy for ExtensionHostProxy {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        language: LanguageName,
+    ) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_formatter(extension, formatter_id, language)
+    }
+
+    fn remove_formatter(&self, language: &LanguageName, formatter_id: &Arc<str>) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.remove_formatter(language, formatter_id)
+    }
+
+    fn register_linter(
+        &self,
+        extension: Arc<dyn Extension>,
+        linter_id: Arc<str>,
+        language: LanguageName,
+    ) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_linter(extension, linter_id, language)
+    }
+
+    fn remove_linter(&self, language: &LanguageName, linter_id: &Arc<str>) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.remove_linter(language, linter_id)
+    }
+}
+
 pub trait ExtensionSnippetProxy: Send + Sync + 'static {
     fn register_snippet(&self, path: &PathBuf, snippet_contents: &str) -> Result<()>;
 }
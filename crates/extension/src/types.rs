@@ -1,8 +1,10 @@
+mod formatter;
 mod lsp;
 mod slash_command;
 
 use std::ops::Range;
 
+pub use formatter::*;
 pub use lsp::*;
 pub use slash_command::*;
 
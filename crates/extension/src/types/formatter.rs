@@ -0,0 +1,56 @@
+/// Information about the buffer that a formatter or linter is run against.
+#[derive(Debug, Clone)]
+pub struct FormatRequest {
+    /// The absolute path of the buffer, if it is backed by a file on disk.
+    pub path: Option<String>,
+    /// The name of the buffer's language.
+    pub language_name: String,
+    /// The number of columns that a tab character represents.
+    pub tab_size: u32,
+    /// Whether to indent lines using tab characters, as opposed to multiple spaces.
+    pub hard_tabs: bool,
+}
+
+/// The output of a finished process.
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    /// The status (exit code) of the process.
+    pub status: Option<i32>,
+    /// The data that the process wrote to stdout.
+    pub stdout: Vec<u8>,
+    /// The data that the process wrote to stderr.
+    pub stderr: Vec<u8>,
+}
+
+/// A position in a buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    /// The zero-based line number.
+    pub line: u32,
+    /// The zero-based column, in UTF-16 code units.
+    pub column: u32,
+}
+
+/// The severity of a linter diagnostic.
+#[derive(Debug, Clone, Copy)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// A diagnostic reported by a linter.
+#[derive(Debug, Clone)]
+pub struct LinterDiagnostic {
+    /// The start of the range the diagnostic applies to.
+    pub start: Position,
+    /// The end of the range the diagnostic applies to.
+    pub end: Position,
+    /// The severity of the diagnostic.
+    pub severity: DiagnosticSeverity,
+    /// The diagnostic's message.
+    pub message: String,
+    /// The diagnostic's code, if any.
+    pub code: Option<String>,
+}
@@ -18,6 +18,7 @@ pub use wit::{
     CodeLabel, CodeLabelSpan, CodeLabelSpanLiteral, Command, DownloadedFileType, EnvVars,
     KeyValueStore, LanguageServerInstallationStatus, Project, Range, Worktree, download_file,
     make_file_executable,
+    zed::extension::formatter::{DiagnosticSeverity, FormatRequest, LinterDiagnostic, Position},
     zed::extension::github::{
         GithubRelease, GithubReleaseAsset, GithubReleaseOptions, github_release_by_tag_name,
         latest_github_release,
@@ -131,6 +132,41 @@ pub trait Extension: Send + Sync {
         None
     }
 
+    /// Returns the command used to run the specified formatter.
+    ///
+    /// The buffer's contents are written to the command's stdin, and the
+    /// formatted contents should be written to its stdout.
+    fn formatter_command(
+        &mut self,
+        _formatter_id: &FormatterId,
+        _request: FormatRequest,
+        _worktree: &Worktree,
+    ) -> Result<Command> {
+        Err("`formatter_command` not implemented".to_string())
+    }
+
+    /// Returns the command used to run the specified linter.
+    ///
+    /// The buffer's contents are written to the command's stdin.
+    fn linter_command(
+        &mut self,
+        _linter_id: &LinterId,
+        _request: FormatRequest,
+        _worktree: &Worktree,
+    ) -> Result<Command> {
+        Err("`linter_command` not implemented".to_string())
+    }
+
+    /// Returns the diagnostics parsed from the output of the specified linter.
+    fn linter_diagnostics(
+        &mut self,
+        _linter_id: &LinterId,
+        _request: FormatRequest,
+        _output: process::Output,
+    ) -> Result<Vec<LinterDiagnostic>> {
+        Ok(Vec::new())
+    }
+
     /// Returns the completions that should be shown when completing the provided slash command with the given query.
     fn complete_slash_command_argument(
         &self,
@@ -319,6 +355,33 @@ impl wit::Guest for Component {
         Ok(labels)
     }
 
+    fn formatter_command(
+        formatter_id: String,
+        request: FormatRequest,
+        worktree: &Worktree,
+    ) -> Result<wit::Command> {
+        let formatter_id = FormatterId(formatter_id);
+        extension().formatter_command(&formatter_id, request, worktree)
+    }
+
+    fn linter_command(
+        linter_id: String,
+        request: FormatRequest,
+        worktree: &Worktree,
+    ) -> Result<wit::Command> {
+        let linter_id = LinterId(linter_id);
+        extension().linter_command(&linter_id, request, worktree)
+    }
+
+    fn linter_diagnostics(
+        linter_id: String,
+        request: FormatRequest,
+        output: process::Output,
+    ) -> Result<Vec<LinterDiagnostic>> {
+        let linter_id = LinterId(linter_id);
+        extension().linter_diagnostics(&linter_id, request, output)
+    }
+
     fn complete_slash_command_argument(
         command: SlashCommand,
         args: Vec<String>,
@@ -371,6 +434,38 @@ impl fmt::Display for LanguageServerId {
     }
 }
 
+/// The ID of a formatter.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct FormatterId(String);
+
+impl AsRef<str> for FormatterId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for FormatterId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The ID of a linter.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct LinterId(String);
+
+impl AsRef<str> for LinterId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for LinterId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// The ID of a context server.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct ContextServerId(String);
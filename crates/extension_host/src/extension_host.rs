@@ -14,9 +14,10 @@ use collections::{BTreeMap, BTreeSet, HashMap, HashSet, btree_map};
 pub use extension::ExtensionManifest;
 use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 use extension::{
-    ExtensionContextServerProxy, ExtensionEvents, ExtensionGrammarProxy, ExtensionHostProxy,
-    ExtensionIndexedDocsProviderProxy, ExtensionLanguageProxy, ExtensionLanguageServerProxy,
-    ExtensionSlashCommandProxy, ExtensionSnippetProxy, ExtensionThemeProxy,
+    ExtensionContextServerProxy, ExtensionEvents, ExtensionFormatterProxy, ExtensionGrammarProxy,
+    ExtensionHostProxy, ExtensionIndexedDocsProviderProxy, ExtensionLanguageProxy,
+    ExtensionLanguageServerProxy, ExtensionSlashCommandProxy, ExtensionSnippetProxy,
+    ExtensionThemeProxy,
 };
 use fs::{Fs, RemoveOptions};
 use futures::{
@@ -1102,6 +1103,16 @@ impl ExtensionStore {
                         .remove_language_server(&language, language_server_name);
                 }
             }
+            for (formatter_id, config) in extension.manifest.formatters.iter() {
+                for language in &config.languages {
+                    self.proxy.remove_formatter(language, formatter_id);
+                }
+            }
+            for (linter_id, config) in extension.manifest.linters.iter() {
+                for language in &config.languages {
+                    self.proxy.remove_linter(language, linter_id);
+                }
+            }
         }
 
         self.wasm_extensions
@@ -1274,6 +1285,26 @@ impl ExtensionStore {
                         }
                     }
 
+                    for (formatter_id, formatter) in &manifest.formatters {
+                        for language in &formatter.languages {
+                            this.proxy.register_formatter(
+                                extension.clone(),
+                                formatter_id.clone(),
+                                language.clone(),
+                            );
+                        }
+                    }
+
+                    for (linter_id, linter) in &manifest.linters {
+                        for language in &linter.languages {
+                            this.proxy.register_linter(
+                                extension.clone(),
+                                linter_id.clone(),
+                                language.clone(),
+                            );
+                        }
+                    }
+
                     for (slash_command_name, slash_command) in &manifest.slash_commands {
                         this.proxy.register_slash_command(
                             extension.clone(),
@@ -1571,7 +1602,10 @@ impl ExtensionStore {
                 .extensions
                 .iter()
                 .filter_map(|(id, entry)| {
-                    if entry.manifest.language_servers.is_empty() {
+                    if entry.manifest.language_servers.is_empty()
+                        && entry.manifest.formatters.is_empty()
+                        && entry.manifest.linters.is_empty()
+                    {
                         return None;
                     }
                     Some(proto::Extension {
@@ -1646,12 +1680,13 @@ impl ExtensionStore {
 
     pub fn register_ssh_client(&mut self, client: Entity<SshRemoteClient>, cx: &mut Context<Self>) {
         let connection_options = client.read(cx).connection_options();
-        if self.ssh_clients.contains_key(&connection_options.ssh_url()) {
+        let connection_string = connection_options.connection_string();
+        if self.ssh_clients.contains_key(&connection_string) {
             return;
         }
 
         self.ssh_clients
-            .insert(connection_options.ssh_url(), client.downgrade());
+            .insert(connection_string, client.downgrade());
         self.ssh_registered_tx.unbounded_send(()).ok();
     }
 }
@@ -4,8 +4,8 @@ use anyhow::{Context as _, Result, anyhow};
 use client::{TypedEnvelope, proto};
 use collections::{HashMap, HashSet};
 use extension::{
-    Extension, ExtensionHostProxy, ExtensionLanguageProxy, ExtensionLanguageServerProxy,
-    ExtensionManifest,
+    Extension, ExtensionFormatterProxy, ExtensionHostProxy, ExtensionLanguageProxy,
+    ExtensionLanguageServerProxy, ExtensionManifest,
 };
 use fs::{Fs, RemoveOptions, RenameOptions};
 use gpui::{App, AppContext as _, AsyncApp, Context, Entity, Task, WeakEntity};
@@ -31,6 +31,8 @@ pub struct HeadlessExtensionStore {
     pub loaded_extensions: HashMap<Arc<str>, Arc<str>>,
     pub loaded_languages: HashMap<Arc<str>, Vec<LanguageName>>,
     pub loaded_language_servers: HashMap<Arc<str>, Vec<(LanguageServerName, LanguageName)>>,
+    pub loaded_formatters: HashMap<Arc<str>, Vec<(Arc<str>, LanguageName)>>,
+    pub loaded_linters: HashMap<Arc<str>, Vec<(Arc<str>, LanguageName)>>,
 }
 
 impl HeadlessExtensionStore {
@@ -57,6 +59,8 @@ impl HeadlessExtensionStore {
             loaded_extensions: Default::default(),
             loaded_languages: Default::default(),
             loaded_language_servers: Default::default(),
+            loaded_formatters: Default::default(),
+            loaded_linters: Default::default(),
         })
     }
 
@@ -125,7 +129,12 @@ impl HeadlessExtensionStore {
 
         let manifest = Arc::new(ExtensionManifest::load(fs.clone(), &extension_dir).await?);
 
-        debug_assert!(!manifest.languages.is_empty() || !manifest.language_servers.is_empty());
+        debug_assert!(
+            !manifest.languages.is_empty()
+                || !manifest.language_servers.is_empty()
+                || !manifest.formatters.is_empty()
+                || !manifest.linters.is_empty()
+        );
 
         if manifest.version.as_ref() != extension.version.as_str() {
             anyhow::bail!(
@@ -165,7 +174,10 @@ impl HeadlessExtensionStore {
             })?;
         }
 
-        if manifest.language_servers.is_empty() {
+        if manifest.language_servers.is_empty()
+            && manifest.formatters.is_empty()
+            && manifest.linters.is_empty()
+        {
             return Ok(());
         }
 
@@ -188,6 +200,38 @@ impl HeadlessExtensionStore {
             }
         }
 
+        for (formatter_id, formatter) in &manifest.formatters {
+            for language in &formatter.languages {
+                this.update(cx, |this, _cx| {
+                    this.loaded_formatters
+                        .entry(manifest.id.clone())
+                        .or_default()
+                        .push((formatter_id.clone(), language.clone()));
+                    this.proxy.register_formatter(
+                        wasm_extension.clone(),
+                        formatter_id.clone(),
+                        language.clone(),
+                    );
+                })?;
+            }
+        }
+
+        for (linter_id, linter) in &manifest.linters {
+            for language in &linter.languages {
+                this.update(cx, |this, _cx| {
+                    this.loaded_linters
+                        .entry(manifest.id.clone())
+                        .or_default()
+                        .push((linter_id.clone(), language.clone()));
+                    this.proxy.register_linter(
+                        wasm_extension.clone(),
+                        linter_id.clone(),
+                        language.clone(),
+                    );
+                })?;
+            }
+        }
+
         Ok(())
     }
 
@@ -213,6 +257,18 @@ impl HeadlessExtensionStore {
                 .remove_language_server(&language, &language_server_name);
         }
 
+        for (formatter_id, language) in self
+            .loaded_formatters
+            .remove(extension_id)
+            .unwrap_or_default()
+        {
+            self.proxy.remove_formatter(&language, &formatter_id);
+        }
+
+        for (linter_id, language) in self.loaded_linters.remove(extension_id).unwrap_or_default() {
+            self.proxy.remove_linter(&language, &linter_id);
+        }
+
         let path = self.extension_dir.join(&extension_id.to_string());
         let fs = self.fs.clone();
         cx.spawn(async move |_, _| {
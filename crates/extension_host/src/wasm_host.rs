@@ -4,8 +4,9 @@ use crate::ExtensionManifest;
 use anyhow::{Context as _, Result, anyhow, bail};
 use async_trait::async_trait;
 use extension::{
-    CodeLabel, Command, Completion, ExtensionHostProxy, KeyValueStoreDelegate, ProjectDelegate,
-    SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, WorktreeDelegate,
+    CodeLabel, Command, Completion, ExtensionHostProxy, FormatRequest, KeyValueStoreDelegate,
+    LinterDiagnostic, ProcessOutput, ProjectDelegate, SlashCommand, SlashCommandArgumentCompletion,
+    SlashCommandOutput, Symbol, WorktreeDelegate,
 };
 use fs::{Fs, normalize_path};
 use futures::future::LocalBoxFuture;
@@ -242,6 +243,68 @@ impl extension::Extension for WasmExtension {
         .await
     }
 
+    async fn formatter_command(
+        &self,
+        formatter_id: Arc<str>,
+        request: FormatRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Command> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                let command = extension
+                    .call_formatter_command(store, &formatter_id, request, resource)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(command.into())
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn linter_command(
+        &self,
+        linter_id: Arc<str>,
+        request: FormatRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Command> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                let command = extension
+                    .call_linter_command(store, &linter_id, request, resource)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(command.into())
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn linter_diagnostics(
+        &self,
+        linter_id: Arc<str>,
+        request: FormatRequest,
+        output: ProcessOutput,
+    ) -> Result<Vec<LinterDiagnostic>> {
+        self.call(|extension, store| {
+            async move {
+                let diagnostics = extension
+                    .call_linter_diagnostics(store, &linter_id, request, output)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(diagnostics)
+            }
+            .boxed()
+        })
+        .await
+    }
+
     async fn complete_slash_command_argument(
         &self,
         command: SlashCommand,
@@ -610,6 +610,75 @@ impl Extension {
         }
     }
 
+    pub async fn call_formatter_command(
+        &self,
+        store: &mut Store<WasmState>,
+        formatter_id: &str,
+        request: extension::FormatRequest,
+        resource: Resource<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<Result<Command, String>> {
+        match self {
+            Extension::V0_4_0(ext) => {
+                ext.call_formatter_command(store, formatter_id, &request.into(), resource)
+                    .await
+            }
+            Extension::V0_0_1(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_3_0(_) => {
+                Err(anyhow!("`formatter_command` not available prior to v0.4.0"))
+            }
+        }
+    }
+
+    pub async fn call_linter_command(
+        &self,
+        store: &mut Store<WasmState>,
+        linter_id: &str,
+        request: extension::FormatRequest,
+        resource: Resource<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<Result<Command, String>> {
+        match self {
+            Extension::V0_4_0(ext) => {
+                ext.call_linter_command(store, linter_id, &request.into(), resource)
+                    .await
+            }
+            Extension::V0_0_1(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_3_0(_) => {
+                Err(anyhow!("`linter_command` not available prior to v0.4.0"))
+            }
+        }
+    }
+
+    pub async fn call_linter_diagnostics(
+        &self,
+        store: &mut Store<WasmState>,
+        linter_id: &str,
+        request: extension::FormatRequest,
+        output: extension::ProcessOutput,
+    ) -> Result<Result<Vec<extension::LinterDiagnostic>, String>> {
+        match self {
+            Extension::V0_4_0(ext) => Ok(ext
+                .call_linter_diagnostics(store, linter_id, &request.into(), &output.into())
+                .await?
+                .map(|diagnostics| diagnostics.into_iter().map(Into::into).collect())),
+            Extension::V0_0_1(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_3_0(_) => Err(anyhow!(
+                "`linter_diagnostics` not available prior to v0.4.0"
+            )),
+        }
+    }
+
     pub async fn call_context_server_command(
         &self,
         store: &mut Store<WasmState>,
@@ -247,6 +247,59 @@ impl From<SlashCommandArgumentCompletion> for extension::SlashCommandArgumentCom
     }
 }
 
+impl From<extension::FormatRequest> for FormatRequest {
+    fn from(value: extension::FormatRequest) -> Self {
+        Self {
+            path: value.path,
+            language_name: value.language_name,
+            tab_size: value.tab_size,
+            hard_tabs: value.hard_tabs,
+        }
+    }
+}
+
+impl From<extension::ProcessOutput> for process::Output {
+    fn from(value: extension::ProcessOutput) -> Self {
+        Self {
+            status: value.status,
+            stdout: value.stdout,
+            stderr: value.stderr,
+        }
+    }
+}
+
+impl From<LinterDiagnostic> for extension::LinterDiagnostic {
+    fn from(value: LinterDiagnostic) -> Self {
+        Self {
+            start: value.start.into(),
+            end: value.end.into(),
+            severity: value.severity.into(),
+            message: value.message,
+            code: value.code,
+        }
+    }
+}
+
+impl From<formatter::Position> for extension::Position {
+    fn from(value: formatter::Position) -> Self {
+        Self {
+            line: value.line,
+            column: value.column,
+        }
+    }
+}
+
+impl From<formatter::DiagnosticSeverity> for extension::DiagnosticSeverity {
+    fn from(value: formatter::DiagnosticSeverity) -> Self {
+        match value {
+            formatter::DiagnosticSeverity::Error => Self::Error,
+            formatter::DiagnosticSeverity::Warning => Self::Warning,
+            formatter::DiagnosticSeverity::Information => Self::Information,
+            formatter::DiagnosticSeverity::Hint => Self::Hint,
+        }
+    }
+}
+
 impl HostKeyValueStore for WasmState {
     async fn insert(
         &mut self,
@@ -497,6 +550,8 @@ impl nodejs::Host for WasmState {
     }
 }
 
+impl formatter::Host for WasmState {}
+
 #[async_trait]
 impl lsp::Host for WasmState {}
 
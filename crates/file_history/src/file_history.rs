@@ -0,0 +1,224 @@
+//! Keeps a local history of the contents of files, independently of version control.
+//!
+//! A snapshot of an open file is taken whenever it is saved, or reloaded because it changed on
+//! disk. Contents are stored under the data directory, named after their hash so that identical
+//! versions of a file (or of different files) are only stored once, while the list of snapshots
+//! lives in a database.
+
+mod file_history_settings;
+mod persistence;
+mod timeline;
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use fs::{Fs, RemoveOptions};
+use futures::lock::Mutex;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Global, Task};
+use language::{Buffer, BufferEvent, Rope};
+use settings::Settings as _;
+use sha2::{Digest as _, Sha256};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+
+pub use file_history_settings::FileHistorySettings;
+pub use persistence::FileSnapshot;
+use persistence::{FILE_HISTORY_DB, Retention};
+pub use timeline::{FileHistoryTimeline, OpenTimeline};
+
+/// How often expired snapshots are removed.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How many snapshots can be added before expired snapshots are removed, without waiting for
+/// the next [`RETENTION_INTERVAL`].
+const RETENTION_BATCH_SIZE: usize = 100;
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut App) {
+    FileHistorySettings::register(cx);
+
+    let store = cx.new(|cx| FileHistoryStore::new(fs, cx));
+    cx.set_global(GlobalFileHistoryStore(store));
+
+    cx.observe_new(|_: &mut Buffer, _, cx| {
+        cx.subscribe_self(|buffer, event, cx| match event {
+            BufferEvent::Saved | BufferEvent::Reloaded => {
+                if let Some(store) = FileHistoryStore::global(cx) {
+                    store.update(cx, |store, cx| store.record_snapshot(buffer, cx));
+                }
+            }
+            _ => {}
+        })
+        .detach();
+    })
+    .detach();
+
+    timeline::init(cx);
+}
+
+struct GlobalFileHistoryStore(Entity<FileHistoryStore>);
+
+impl Global for GlobalFileHistoryStore {}
+
+pub enum FileHistoryEvent {
+    SnapshotsChanged,
+}
+
+/// Records and reads the snapshots of local files.
+pub struct FileHistoryStore {
+    fs: Arc<dyn Fs>,
+    /// Held while snapshots are written or removed, so that contents are never removed while a
+    /// new snapshot starts referring to them.
+    write_lock: Arc<Mutex<()>>,
+    snapshots_since_retention: usize,
+    _maintain_retention: Task<()>,
+}
+
+impl EventEmitter<FileHistoryEvent> for FileHistoryStore {}
+
+impl FileHistoryStore {
+    fn new(fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
+        Self {
+            fs,
+            write_lock: Arc::new(Mutex::new(())),
+            snapshots_since_retention: 0,
+            _maintain_retention: cx.spawn(async move |this, cx| {
+                loop {
+                    if this
+                        .update(cx, |this, cx| this.apply_retention(cx))
+                        .is_err()
+                    {
+                        break;
+                    }
+                    cx.background_executor().timer(RETENTION_INTERVAL).await;
+                }
+            }),
+        }
+    }
+
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalFileHistoryStore>()
+            .map(|global| global.0.clone())
+    }
+
+    /// Returns the snapshots of the file at the given path, newest first.
+    pub fn snapshots(&self, abs_path: PathBuf, cx: &App) -> Task<Result<Vec<FileSnapshot>>> {
+        cx.background_spawn(async move { FILE_HISTORY_DB.snapshots(abs_path) })
+    }
+
+    pub fn load_snapshot(&self, snapshot: &FileSnapshot, cx: &App) -> Task<Result<String>> {
+        let fs = self.fs.clone();
+        let path = content_path(&snapshot.content_hash);
+        cx.background_spawn(async move { fs.load(&path).await })
+    }
+
+    fn record_snapshot(&mut self, buffer: &Buffer, cx: &mut Context<Self>) {
+        let settings = *FileHistorySettings::get_global(cx);
+        if !settings.enabled {
+            return;
+        }
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return;
+        };
+        if file.is_private() {
+            return;
+        }
+        let text = buffer.as_rope().clone();
+        if text.len() as u64 > max_total_size(&settings) {
+            return;
+        }
+
+        let abs_path = file.abs_path(cx);
+        let fs = self.fs.clone();
+        let write_lock = self.write_lock.clone();
+        let write = cx.background_spawn(async move {
+            let _guard = write_lock.lock().await;
+            write_snapshot(fs.as_ref(), abs_path, text).await
+        });
+        cx.spawn(async move |this, cx| {
+            if write.await? {
+                this.update(cx, |this, cx| {
+                    this.snapshots_since_retention += 1;
+                    if this.snapshots_since_retention >= RETENTION_BATCH_SIZE {
+                        this.apply_retention(cx);
+                    }
+                    cx.emit(FileHistoryEvent::SnapshotsChanged);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Removes the snapshots that are too old or exceed the configured limits. This scans every
+    /// snapshot, so it runs periodically and after batches of writes rather than on every save.
+    fn apply_retention(&mut self, cx: &mut Context<Self>) {
+        self.snapshots_since_retention = 0;
+        let settings = *FileHistorySettings::get_global(cx);
+        let fs = self.fs.clone();
+        let write_lock = self.write_lock.clone();
+        cx.background_spawn(async move {
+            let _guard = write_lock.lock().await;
+            remove_expired_snapshots(fs.as_ref(), retention(&settings)).await
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Stores a snapshot of a file, unless its contents didn't change since the last one. Returns
+/// whether a snapshot was added.
+async fn write_snapshot(fs: &dyn Fs, abs_path: PathBuf, text: Rope) -> Result<bool> {
+    let text = text.to_string();
+    let content_hash = content_hash(&text);
+    if FILE_HISTORY_DB
+        .latest_content_hash(abs_path.clone())?
+        .is_some_and(|latest_hash| latest_hash == content_hash)
+    {
+        return Ok(false);
+    }
+
+    let size = text.len() as i64;
+    let content_path = content_path(&content_hash);
+    if !fs.is_file(&content_path).await {
+        fs.create_dir(paths::file_history_dir()).await?;
+        fs.atomic_write(content_path, text).await?;
+    }
+    FILE_HISTORY_DB
+        .insert_snapshot(abs_path, content_hash, size)
+        .await?;
+    Ok(true)
+}
+
+async fn remove_expired_snapshots(fs: &dyn Fs, retention: Retention) -> Result<()> {
+    for content_hash in FILE_HISTORY_DB.apply_retention(retention).await? {
+        fs.remove_file(
+            &content_path(&content_hash),
+            RemoveOptions {
+                ignore_if_not_exists: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .log_err();
+    }
+    Ok(())
+}
+
+fn retention(settings: &FileHistorySettings) -> Retention {
+    Retention {
+        max_snapshots_per_file: settings.max_snapshots_per_file,
+        oldest_created_at: OffsetDateTime::now_utc()
+            - time::Duration::days(settings.max_age_days.into()),
+        max_total_size: max_total_size(settings),
+    }
+}
+
+fn max_total_size(settings: &FileHistorySettings) -> u64 {
+    settings.max_total_size_mb.saturating_mul(1024 * 1024)
+}
+
+fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+fn content_path(content_hash: &str) -> PathBuf {
+    paths::file_history_dir().join(content_hash)
+}
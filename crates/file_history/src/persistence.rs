@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use db::{
+    define_connection, query,
+    sqlez::{bindable::Column, statement::Statement},
+    sqlez_macros::sql,
+};
+use time::OffsetDateTime;
+
+/// A saved version of a file, whose contents are stored separately under their hash so that
+/// identical versions are only stored once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileSnapshot {
+    pub id: i64,
+    pub content_hash: String,
+    pub size: u64,
+    pub created_at: OffsetDateTime,
+}
+
+impl Column for FileSnapshot {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index): (i64, i32) = Column::column(statement, start_index)?;
+        let (content_hash, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (size, next_index): (i64, i32) = Column::column(statement, next_index)?;
+        let (created_at, next_index): (i64, i32) = Column::column(statement, next_index)?;
+        let snapshot = Self {
+            id,
+            content_hash,
+            size: size as u64,
+            created_at: OffsetDateTime::from_unix_timestamp(created_at)?,
+        };
+        Ok((snapshot, next_index))
+    }
+}
+
+/// Limits on how many snapshots are kept.
+#[derive(Clone, Copy, Debug)]
+pub struct Retention {
+    pub max_snapshots_per_file: usize,
+    /// Snapshots created before this time are removed.
+    pub oldest_created_at: OffsetDateTime,
+    /// The total size of the distinct contents of all snapshots. Oldest snapshots are removed
+    /// first to stay within it.
+    pub max_total_size: u64,
+}
+
+define_connection!(pub static ref FILE_HISTORY_DB: FileHistoryDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS file_snapshots(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path BLOB NOT NULL,
+            content_hash TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            created_at INTEGER DEFAULT (unixepoch()) NOT NULL
+        ) STRICT;
+        CREATE INDEX IF NOT EXISTS file_snapshots_by_path ON file_snapshots(path, id);
+    )];
+);
+
+impl FileHistoryDb {
+    query! {
+        pub fn snapshots(path: PathBuf) -> Result<Vec<FileSnapshot>> {
+            SELECT id, content_hash, size, created_at
+            FROM file_snapshots
+            WHERE path = (?)
+            ORDER BY id DESC
+        }
+    }
+
+    query! {
+        pub fn latest_content_hash(path: PathBuf) -> Result<Option<String>> {
+            SELECT content_hash
+            FROM file_snapshots
+            WHERE path = (?)
+            ORDER BY id DESC
+            LIMIT 1
+        }
+    }
+
+    query! {
+        pub async fn insert_snapshot(path: PathBuf, content_hash: String, size: i64) -> Result<()> {
+            INSERT INTO file_snapshots (path, content_hash, size) VALUES ((?), (?), (?))
+        }
+    }
+
+    /// Removes the snapshots that fall outside of the retention limits, returning the hashes of
+    /// the contents that no snapshot refers to anymore.
+    pub async fn apply_retention(&self, retention: Retention) -> Result<Vec<String>> {
+        self.write(move |conn| {
+            let hashes_before = conn
+                .select::<String>(sql!(SELECT DISTINCT content_hash FROM file_snapshots))?()?;
+
+            conn.exec_bound(sql!(
+                DELETE FROM file_snapshots WHERE created_at < (?)
+            ))?(retention.oldest_created_at.unix_timestamp())?;
+            conn.exec_bound(sql!(
+                DELETE FROM file_snapshots WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, ROW_NUMBER() OVER (PARTITION BY path ORDER BY id DESC) AS position
+                        FROM file_snapshots
+                    )
+                    WHERE position > (?)
+                )
+            ))?(retention.max_snapshots_per_file)?;
+
+            let remaining = conn.select::<(i64, String, i64)>(sql!(
+                SELECT id, content_hash, size FROM file_snapshots ORDER BY id ASC
+            ))?()?;
+            if let Some(last_removed_id) =
+                oldest_snapshots_over_size(&remaining, retention.max_total_size)
+            {
+                conn.exec_bound(sql!(
+                    DELETE FROM file_snapshots WHERE id <= (?)
+                ))?(last_removed_id)?;
+            }
+
+            let hashes_after = conn
+                .select::<String>(sql!(SELECT DISTINCT content_hash FROM file_snapshots))?()?
+                .into_iter()
+                .collect::<HashSet<_>>();
+            Ok(hashes_before
+                .into_iter()
+                .filter(|hash| !hashes_after.contains(hash))
+                .collect())
+        })
+        .await
+    }
+}
+
+/// Given snapshots ordered from oldest to newest, returns the id of the newest snapshot that has
+/// to be removed, along with all older ones, for the distinct contents of the rest to fit within
+/// `max_total_size`.
+fn oldest_snapshots_over_size(
+    snapshots: &[(i64, String, i64)],
+    max_total_size: u64,
+) -> Option<i64> {
+    let mut references = HashMap::<&str, usize>::default();
+    let mut total_size = 0;
+    for (_, content_hash, size) in snapshots {
+        let count = references.entry(content_hash.as_str()).or_default();
+        if *count == 0 {
+            total_size += *size as u64;
+        }
+        *count += 1;
+    }
+
+    let mut last_removed_id = None;
+    for (id, content_hash, size) in snapshots {
+        if total_size <= max_total_size {
+            break;
+        }
+        let count = references.entry(content_hash.as_str()).or_default();
+        *count -= 1;
+        if *count == 0 {
+            total_size -= *size as u64;
+        }
+        last_removed_id = Some(*id);
+    }
+    last_removed_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    fn retention() -> Retention {
+        Retention {
+            max_snapshots_per_file: 3,
+            oldest_created_at: OffsetDateTime::now_utc() - Duration::days(1),
+            max_total_size: 100,
+        }
+    }
+
+    #[gpui::test]
+    async fn test_snapshots_are_listed_newest_first() {
+        let db = FileHistoryDb(db::open_test_db("test_snapshots_are_listed_newest_first").await);
+        let path = PathBuf::from("/a/b.rs");
+
+        assert_eq!(db.latest_content_hash(path.clone()).unwrap(), None);
+        db.insert_snapshot(path.clone(), "one".into(), 1)
+            .await
+            .unwrap();
+        db.insert_snapshot(path.clone(), "two".into(), 2)
+            .await
+            .unwrap();
+        db.insert_snapshot("/a/c.rs".into(), "three".into(), 3)
+            .await
+            .unwrap();
+
+        let snapshots = db.snapshots(path.clone()).unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|snapshot| (snapshot.content_hash.as_str(), snapshot.size))
+                .collect::<Vec<_>>(),
+            [("two", 2), ("one", 1)]
+        );
+        assert_eq!(
+            db.latest_content_hash(path).unwrap().as_deref(),
+            Some("two")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_retention_per_file() {
+        let db = FileHistoryDb(db::open_test_db("test_retention_per_file").await);
+        let path = PathBuf::from("/a/b.rs");
+        for hash in ["one", "two", "one", "three", "four"] {
+            db.insert_snapshot(path.clone(), hash.into(), 1)
+                .await
+                .unwrap();
+        }
+
+        let mut orphaned = db.apply_retention(retention()).await.unwrap();
+        orphaned.sort();
+        assert_eq!(orphaned, ["two"]);
+        assert_eq!(
+            db.snapshots(path)
+                .unwrap()
+                .into_iter()
+                .map(|snapshot| snapshot.content_hash)
+                .collect::<Vec<_>>(),
+            ["four", "three", "one"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_retention_by_total_size() {
+        let db = FileHistoryDb(db::open_test_db("test_retention_by_total_size").await);
+        db.insert_snapshot("/a.rs".into(), "a1".into(), 60)
+            .await
+            .unwrap();
+        db.insert_snapshot("/b.rs".into(), "b1".into(), 30)
+            .await
+            .unwrap();
+        // Identical contents only count towards the total size once.
+        db.insert_snapshot("/c.rs".into(), "b1".into(), 30)
+            .await
+            .unwrap();
+        db.insert_snapshot("/a.rs".into(), "a2".into(), 50)
+            .await
+            .unwrap();
+
+        let orphaned = db.apply_retention(retention()).await.unwrap();
+        assert_eq!(orphaned, ["a1"]);
+        assert_eq!(db.snapshots("/a.rs".into()).unwrap().len(), 1);
+        assert_eq!(db.snapshots("/b.rs".into()).unwrap().len(), 1);
+        assert_eq!(db.snapshots("/c.rs".into()).unwrap().len(), 1);
+    }
+}
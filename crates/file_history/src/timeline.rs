@@ -0,0 +1,453 @@
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use editor::{Editor, FileDiffView};
+use gpui::{
+    App, ClipboardItem, Context, Entity, EventEmitter, FocusHandle, Focusable, IntoElement,
+    ParentElement, Render, Styled, Subscription, Task, WeakEntity, Window, actions,
+};
+use language::{Buffer, Capability};
+use multi_buffer::MultiBuffer;
+use project::Project;
+use settings::Settings as _;
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{Item, OpenOptions, OpenVisible, Workspace, item::ItemEvent};
+
+use crate::{FileHistoryEvent, FileHistorySettings, FileHistoryStore, FileSnapshot};
+
+actions!(file_history, [OpenTimeline]);
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(FileHistoryTimeline::open);
+    })
+    .detach();
+}
+
+/// Lists the snapshots of a file, from which any two can be compared, or one can be compared
+/// with the file, restored, opened or copied.
+pub struct FileHistoryTimeline {
+    abs_path: PathBuf,
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    snapshots: Vec<FileSnapshot>,
+    /// The ids of the selected snapshots, at most two.
+    selected: Vec<i64>,
+    local_timezone: UtcOffset,
+    focus_handle: FocusHandle,
+    _load_task: Task<()>,
+    _store_subscription: Option<Subscription>,
+}
+
+impl FileHistoryTimeline {
+    fn open(
+        workspace: &mut Workspace,
+        _: &OpenTimeline,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(abs_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+            .and_then(|buffer| {
+                let file = buffer.read(cx).file()?.as_local()?;
+                Some(file.abs_path(cx))
+            })
+        else {
+            return;
+        };
+
+        if let Some(timeline) = workspace
+            .items_of_type::<Self>(cx)
+            .find(|timeline| timeline.read(cx).abs_path == abs_path)
+        {
+            workspace.activate_item(&timeline, true, true, window, cx);
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let workspace_handle = workspace.weak_handle();
+        let timeline = cx.new(|cx| Self::new(abs_path, project, workspace_handle, cx));
+        workspace.add_item_to_active_pane(Box::new(timeline), None, true, window, cx);
+    }
+
+    fn new(
+        abs_path: PathBuf,
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let store_subscription = FileHistoryStore::global(cx).map(|store| {
+            cx.subscribe(&store, |this, _, event, cx| match event {
+                FileHistoryEvent::SnapshotsChanged => this.reload(cx),
+            })
+        });
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let mut this = Self {
+            abs_path,
+            project,
+            workspace,
+            snapshots: Vec::new(),
+            selected: Vec::new(),
+            local_timezone: UtcOffset::from_whole_seconds(local_offset).unwrap_or(UtcOffset::UTC),
+            focus_handle: cx.focus_handle(),
+            _load_task: Task::ready(()),
+            _store_subscription: store_subscription,
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let Some(store) = FileHistoryStore::global(cx) else {
+            return;
+        };
+        let load = store.read(cx).snapshots(self.abs_path.clone(), cx);
+        self._load_task = cx.spawn(async move |this, cx| {
+            let Some(snapshots) = load.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.selected
+                    .retain(|id| snapshots.iter().any(|snapshot| snapshot.id == *id));
+                this.snapshots = snapshots;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn toggle_selected(&mut self, id: i64, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected.iter().position(|selected| *selected == id) {
+            self.selected.remove(ix);
+        } else {
+            if self.selected.len() == 2 {
+                self.selected.remove(0);
+            }
+            self.selected.push(id);
+        }
+        cx.notify();
+    }
+
+    /// The selected snapshots, newest first.
+    fn selected_snapshots(&self) -> Vec<FileSnapshot> {
+        self.snapshots
+            .iter()
+            .filter(|snapshot| self.selected.contains(&snapshot.id))
+            .cloned()
+            .collect()
+    }
+
+    fn file_name(&self) -> String {
+        self.abs_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.abs_path.display().to_string())
+    }
+
+    fn format_timestamp(&self, snapshot: &FileSnapshot, format: TimestampFormat) -> String {
+        time_format::format_localized_timestamp(
+            snapshot.created_at,
+            OffsetDateTime::now_utc(),
+            self.local_timezone,
+            format,
+        )
+    }
+
+    fn snapshot_title(&self, snapshot: &FileSnapshot) -> String {
+        format!(
+            "{} ({})",
+            self.file_name(),
+            self.format_timestamp(snapshot, TimestampFormat::EnhancedAbsolute)
+        )
+    }
+
+    fn load_snapshot(&self, snapshot: &FileSnapshot, cx: &App) -> Task<Result<String>> {
+        match FileHistoryStore::global(cx) {
+            Some(store) => store.read(cx).load_snapshot(snapshot, cx),
+            None => Task::ready(Err(anyhow::anyhow!("file history is not available"))),
+        }
+    }
+
+    fn open_file_buffer(&self, cx: &mut App) -> Task<Result<Entity<Buffer>>> {
+        self.project.update(cx, |project, cx| {
+            project.open_local_buffer(&self.abs_path, cx)
+        })
+    }
+
+    /// Compares the two selected snapshots, or the selected snapshot with the file's current
+    /// contents.
+    fn compare(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let snapshots = self.selected_snapshots();
+        if snapshots.is_empty() {
+            return;
+        }
+        let loads = snapshots
+            .iter()
+            .map(|snapshot| {
+                (
+                    self.load_snapshot(snapshot, cx),
+                    self.snapshot_title(snapshot),
+                )
+            })
+            .collect::<Vec<_>>();
+        let open_file = self.open_file_buffer(cx);
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let file_buffer = open_file.await?;
+            let mut buffers = Vec::new();
+            for (load, title) in loads {
+                let text = load.await?;
+                let buffer = cx.update(|_, cx| snapshot_buffer(text, &file_buffer, cx))?;
+                buffers.push((buffer, Some(title.into())));
+            }
+            // Snapshots are sorted newest first, so the oldest one goes on the left.
+            let old = buffers.pop().context("no snapshot selected")?;
+            let new = buffers.pop().unwrap_or((file_buffer, None));
+            cx.update(|window, cx| FileDiffView::compare_buffers(old, new, workspace, window, cx))?
+                .await?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the file's contents with the snapshot's, as an edit that can be undone.
+    fn restore(&mut self, snapshot: &FileSnapshot, window: &mut Window, cx: &mut Context<Self>) {
+        let load = self.load_snapshot(snapshot, cx);
+        let abs_path = self.abs_path.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let text = load.await?;
+            let open_file = workspace.update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(
+                    abs_path,
+                    OpenOptions {
+                        visible: Some(OpenVisible::None),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                )
+            })?;
+            let item = open_file.await?;
+            cx.update(|_, cx| {
+                let buffer = item
+                    .act_as::<Editor>(cx)
+                    .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+                    .context("file is not open in an editor")?;
+                buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens the snapshot's contents in a read-only editor, from which parts can be copied.
+    fn open_snapshot(
+        &mut self,
+        snapshot: &FileSnapshot,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let load = self.load_snapshot(snapshot, cx);
+        let open_file = self.open_file_buffer(cx);
+        let title = self.snapshot_title(snapshot);
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let text = load.await?;
+            let file_buffer = open_file.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let buffer = snapshot_buffer(text, &file_buffer, cx);
+                let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                let editor =
+                    cx.new(|cx| Editor::for_multibuffer(multibuffer, Some(project), window, cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn copy_snapshot(&mut self, snapshot: &FileSnapshot, cx: &mut Context<Self>) {
+        let load = self.load_snapshot(snapshot, cx);
+        cx.spawn(async move |_, cx| {
+            let text = load.await?;
+            cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string(text)))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_snapshot(&self, snapshot: &FileSnapshot, cx: &mut Context<Self>) -> impl IntoElement {
+        let id = snapshot.id;
+        let actions = h_flex()
+            .gap_1()
+            .child(
+                IconButton::new(("open-snapshot", id as u64), IconName::Eye)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Open Snapshot"))
+                    .on_click(cx.listener({
+                        let snapshot = snapshot.clone();
+                        move |this, _, window, cx| this.open_snapshot(&snapshot, window, cx)
+                    })),
+            )
+            .child(
+                IconButton::new(("copy-snapshot", id as u64), IconName::Copy)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Copy Contents"))
+                    .on_click(cx.listener({
+                        let snapshot = snapshot.clone();
+                        move |this, _, _, cx| this.copy_snapshot(&snapshot, cx)
+                    })),
+            )
+            .child(
+                IconButton::new(("restore-snapshot", id as u64), IconName::RotateCcw)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Restore Snapshot"))
+                    .on_click(cx.listener({
+                        let snapshot = snapshot.clone();
+                        move |this, _, window, cx| this.restore(&snapshot, window, cx)
+                    })),
+            );
+
+        ListItem::new(("snapshot", id as u64))
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected.contains(&id))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(
+                        self.format_timestamp(snapshot, TimestampFormat::EnhancedAbsolute),
+                    ))
+                    .child(
+                        Label::new(self.format_timestamp(snapshot, TimestampFormat::Relative))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(format_size(snapshot.size))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .end_hover_slot(actions)
+            .on_click(cx.listener(move |this, _, _, cx| this.toggle_selected(id, cx)))
+    }
+}
+
+/// Creates a read-only buffer holding a snapshot's contents, using the file's language.
+fn snapshot_buffer(text: String, file_buffer: &Entity<Buffer>, cx: &mut App) -> Entity<Buffer> {
+    let buffer = FileDiffView::buffer_for_text(text, file_buffer, cx);
+    buffer.update(cx, |buffer, cx| {
+        buffer.set_capability(Capability::ReadOnly, cx)
+    });
+    buffer
+}
+
+fn format_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{size} B")
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f64 / 1024.)
+    } else {
+        format!("{:.1} MB", size as f64 / (1024. * 1024.))
+    }
+}
+
+impl Render for FileHistoryTimeline {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let compare_label = if self.selected.len() == 2 {
+            "Compare Selected"
+        } else {
+            "Compare with Current"
+        };
+        let header = h_flex()
+            .justify_between()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                v_flex()
+                    .child(Label::new(format!("History of {}", self.file_name())))
+                    .child(
+                        Label::new("Select one snapshot to compare it with the file, or two to compare them with each other.")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                Button::new("compare-snapshots", compare_label)
+                    .disabled(self.selected.is_empty())
+                    .on_click(cx.listener(|this, _, window, cx| this.compare(window, cx))),
+            );
+
+        let content = if self.snapshots.is_empty() {
+            let message = if FileHistorySettings::get_global(cx).enabled {
+                "No snapshots of this file yet. A snapshot is taken every time it is saved."
+            } else {
+                "File history is disabled. Enable it with the `file_history.enabled` setting."
+            };
+            v_flex()
+                .p_4()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            v_flex()
+                .id("file-history-snapshots")
+                .flex_1()
+                .p_1()
+                .overflow_y_scroll()
+                .children(
+                    self.snapshots
+                        .iter()
+                        .map(|snapshot| self.render_snapshot(snapshot, cx))
+                        .collect::<Vec<_>>(),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .key_context("FileHistoryTimeline")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(header)
+            .child(content)
+    }
+}
+
+impl Focusable for FileHistoryTimeline {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for FileHistoryTimeline {}
+
+impl Item for FileHistoryTimeline {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _: &Window, _: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn tab_content_text(&self, _: &Window, _: &App) -> Option<SharedString> {
+        Some(format!("History: {}", self.file_name()).into())
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("History of {}", self.abs_path.display()).into())
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("File History Opened")
+    }
+}
@@ -6,7 +6,7 @@ use git::{
     blame::Blame,
     repository::{
         AskPassDelegate, Branch, CommitDetails, CommitOptions, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RepoPath, ResetMode,
+        GitRepositoryCheckpoint, MergeStages, PushOptions, Remote, RepoPath, ResetMode,
     },
     status::{FileStatus, GitStatus, StatusCode, TrackedStatus, UnmergedStatus},
 };
@@ -31,6 +31,7 @@ pub struct FakeGitRepositoryState {
     pub unmerged_paths: HashMap<RepoPath, UnmergedStatus>,
     pub head_contents: HashMap<RepoPath, String>,
     pub index_contents: HashMap<RepoPath, String>,
+    pub merge_stages: HashMap<RepoPath, MergeStages>,
     pub blames: HashMap<RepoPath, Blame>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
@@ -43,6 +44,7 @@ impl FakeGitRepositoryState {
             event_emitter,
             head_contents: Default::default(),
             index_contents: Default::default(),
+            merge_stages: Default::default(),
             unmerged_paths: Default::default(),
             blames: Default::default(),
             current_branch_name: Default::default(),
@@ -102,6 +104,21 @@ impl GitRepository for FakeGitRepository {
         .boxed()
     }
 
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<Option<MergeStages>> {
+        async {
+            self.with_state_async(false, move |state| {
+                state
+                    .merge_stages
+                    .get(path.as_ref())
+                    .ok_or_else(|| anyhow!("no merge conflicts"))
+                    .cloned()
+            })
+            .await
+            .ok()
+        }
+        .boxed()
+    }
+
     fn load_commit(
         &self,
         _commit: String,
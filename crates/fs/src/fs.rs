@@ -1415,6 +1415,22 @@ impl FakeFs {
         .unwrap();
     }
 
+    pub fn set_merge_stages_for_repo(
+        &self,
+        dot_git: &Path,
+        merge_stages: &[(RepoPath, git::repository::MergeStages)],
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.merge_stages.clear();
+            state.merge_stages.extend(
+                merge_stages
+                    .iter()
+                    .map(|(path, stages)| (path.clone(), stages.clone())),
+            );
+        })
+        .unwrap();
+    }
+
     pub fn set_index_for_repo(&self, dot_git: &Path, index_state: &[(RepoPath, String)]) {
         self.with_git_state(dot_git, true, |state| {
             state.index_contents.clear();
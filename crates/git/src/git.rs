@@ -39,6 +39,7 @@ actions!(
         // per-file
         StageFile,
         UnstageFile,
+        ResolveConflicts,
         // repo-wide
         StageAll,
         UnstageAll,
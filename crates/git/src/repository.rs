@@ -10,7 +10,7 @@ use gpui::{AppContext as _, AsyncApp, BackgroundExecutor, SharedString};
 use parking_lot::Mutex;
 use rope::Rope;
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::borrow::{Borrow, Cow};
 use std::ffi::{OsStr, OsString};
 use std::path::Component;
@@ -174,6 +174,17 @@ pub enum ResetMode {
     Mixed,
 }
 
+/// The versions of a file that git records in its index while merging it conflicted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeStages {
+    /// The common ancestor (stage 1), or `None` if the file was added on both sides.
+    pub base: Option<String>,
+    /// The version on the current branch (stage 2), or `None` if it was deleted there.
+    pub ours: Option<String>,
+    /// The version being merged in (stage 3), or `None` if it was deleted there.
+    pub theirs: Option<String>,
+}
+
 pub trait GitRepository: Send + Sync {
     fn reload_index(&self);
 
@@ -187,6 +198,10 @@ pub trait GitRepository: Send + Sync {
     /// Also returns `None` for symlinks.
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<Option<String>>;
 
+    /// Returns the versions of a file recorded in the repository's index while it has merge
+    /// conflicts, or None if there are no conflicts for the given path.
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<Option<MergeStages>>;
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -372,7 +387,7 @@ impl RealGitRepository {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GitRepositoryCheckpoint {
     pub ref_name: String,
     pub commit_sha: Oid,
@@ -618,6 +633,46 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<Option<MergeStages>> {
+        let repo = self.repository.clone();
+        self.executor
+            .spawn(async move {
+                fn logic(repo: &git2::Repository, path: &RepoPath) -> Result<Option<MergeStages>> {
+                    check_path_to_repo_path_errors(path)?;
+
+                    let mut index = repo.index()?;
+                    index.read(false)?;
+
+                    let load_stage = |stage| -> Result<Option<String>> {
+                        let Some(entry) = index.get_path(path, stage) else {
+                            return Ok(None);
+                        };
+                        let content = repo.find_blob(entry.id)?.content().to_owned();
+                        Ok(Some(String::from_utf8(content)?))
+                    };
+                    const STAGE_BASE: i32 = 1;
+                    const STAGE_OURS: i32 = 2;
+                    const STAGE_THEIRS: i32 = 3;
+                    let stages = MergeStages {
+                        base: load_stage(STAGE_BASE)?,
+                        ours: load_stage(STAGE_OURS)?,
+                        theirs: load_stage(STAGE_THEIRS)?,
+                    };
+                    if stages == MergeStages::default() {
+                        return Ok(None);
+                    }
+                    Ok(Some(stages))
+                }
+
+                match logic(&repo.lock(), &path) {
+                    Ok(value) => return value,
+                    Err(err) => log::error!("Error loading merge stages: {:?}", err),
+                }
+                None
+            })
+            .boxed()
+    }
+
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<Option<String>> {
         let repo = self.repository.clone();
         self.executor
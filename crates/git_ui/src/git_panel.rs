@@ -3,6 +3,7 @@ use crate::commit_modal::CommitModal;
 use crate::commit_tooltip::CommitTooltip;
 use crate::commit_view::CommitView;
 use crate::git_panel_settings::StatusStyle;
+use crate::merge_view::MergeView;
 use crate::project_diff::{self, Diff, ProjectDiff};
 use crate::remote_output::{self, RemoteAction, SuccessMessage};
 use crate::{branch_picker, picker_prompt, render_remote_button};
@@ -899,6 +900,30 @@ impl GitPanel {
         });
     }
 
+    fn resolve_conflicts(
+        &mut self,
+        _: &git::ResolveConflicts,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        maybe!({
+            let entry = self.entries.get(self.selected_entry?)?.status_entry()?;
+            let active_repo = self.active_repository.as_ref()?;
+            let path = active_repo
+                .read(cx)
+                .repo_path_to_project_path(&entry.repo_path, cx)?;
+            if !entry.status.is_conflicted() {
+                return None;
+            }
+
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    MergeView::deploy(workspace, path, window, cx);
+                })
+                .ok()
+        });
+    }
+
     fn revert_selected(
         &mut self,
         action: &git::RestoreFile,
@@ -1747,6 +1772,11 @@ impl GitPanel {
                     tools: Vec::new(),
                     stop: Vec::new(),
                     temperature: None,
+                    max_tokens: None,
+                    top_p: None,
+                    reasoning_effort: None,
+                    seed: None,
+                    response_format: None,
                 };
 
                 let stream = model.stream_completion_text(request, &cx);
@@ -3738,9 +3768,14 @@ impl GitPanel {
         } else {
             "Restore File"
         };
+        let is_conflicted = entry.status.is_conflicted();
         let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
             context_menu
                 .context(self.focus_handle.clone())
+                .when(is_conflicted, |menu| {
+                    menu.action("Resolve Conflicts", git::ResolveConflicts.boxed_clone())
+                        .separator()
+                })
                 .action(stage_title, ToggleStaged.boxed_clone())
                 .action(restore_title, git::RestoreFile::default().boxed_clone())
                 .separator()
@@ -4089,6 +4124,7 @@ impl Render for GitPanel {
             .on_action(cx.listener(Self::close_panel))
             .on_action(cx.listener(Self::open_diff))
             .on_action(cx.listener(Self::open_file))
+            .on_action(cx.listener(Self::resolve_conflicts))
             .on_action(cx.listener(Self::focus_changes_list))
             .on_action(cx.listener(Self::focus_editor))
             .on_action(cx.listener(Self::expand_commit_editor))
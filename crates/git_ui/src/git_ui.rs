@@ -22,6 +22,7 @@ pub mod commit_tooltip;
 mod commit_view;
 pub mod git_panel;
 mod git_panel_settings;
+pub mod merge_view;
 pub mod onboarding;
 pub mod picker_prompt;
 pub mod project_diff;
@@ -41,6 +42,7 @@ pub fn init(cx: &mut App) {
         git_panel::register(workspace);
         repository_selector::register(workspace);
         branch_picker::register(workspace);
+        merge_view::register(workspace);
 
         let project = workspace.project().read(cx);
         if project.is_read_only(cx) {
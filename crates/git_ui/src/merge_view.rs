@@ -0,0 +1,1121 @@
+use anyhow::{Context as _, Result};
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent, FileDiffView, scroll::Autoscroll};
+use git::{
+    ResolveConflicts,
+    repository::{MergeStages, RepoPath},
+};
+use gpui::{
+    Action, AnyView, App, AppContext as _, Entity, EventEmitter, FocusHandle, Focusable,
+    PromptLevel, Render, Subscription, Task, Window, actions,
+};
+use language::{Anchor, Buffer, BufferEvent, BufferSnapshot, OffsetRangeExt as _, ToOffset as _};
+use multi_buffer::MultiBuffer;
+use project::{Project, ProjectPath, git_store::Repository};
+use std::{any::TypeId, iter, ops::Range};
+use theme::ActiveTheme;
+use ui::{Tooltip, prelude::*};
+use workspace::{
+    Item, ItemNavHistory, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+    item::ItemEvent, searchable::SearchableItemHandle,
+};
+
+actions!(
+    merge_view,
+    [
+        AcceptOurs,
+        AcceptTheirs,
+        AcceptBoth,
+        NextConflict,
+        PreviousConflict,
+        MarkResolved
+    ]
+);
+
+pub(crate) fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &ResolveConflicts, window, cx| {
+        let Some(project_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+        else {
+            return;
+        };
+        MergeView::deploy(workspace, project_path, window, cx);
+    });
+}
+
+/// Resolves the merge conflicts in a file, showing the base, ours and theirs versions that git
+/// recorded in the index above the file itself.
+///
+/// Conflicts are found by merging those versions rather than by searching for conflict markers,
+/// and are located in the file by diffing it against that merge. A conflict counts as resolved
+/// once its region of the file no longer contains conflict markers.
+pub struct MergeView {
+    project: Entity<Project>,
+    project_path: ProjectPath,
+    repository: Entity<Repository>,
+    repo_path: RepoPath,
+    buffer: Entity<Buffer>,
+    base_buffer: Entity<Buffer>,
+    ours_buffer: Entity<Buffer>,
+    theirs_buffer: Entity<Buffer>,
+    base_editor: Entity<Editor>,
+    ours_editor: Entity<Editor>,
+    theirs_editor: Entity<Editor>,
+    result_editor: Entity<Editor>,
+    conflicts: Vec<Conflict>,
+    _update_conflicts: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug)]
+struct Conflict {
+    result: Range<Anchor>,
+    base: Range<usize>,
+    ours: Range<usize>,
+    theirs: Range<usize>,
+}
+
+struct ConflictHighlight;
+
+impl MergeView {
+    /// Opens a merge view for the given path, or activates an existing one.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        project_path: ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        if let Some(existing) = workspace
+            .items_of_type::<Self>(cx)
+            .find(|merge_view| merge_view.read(cx).project_path == project_path)
+        {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let repository = project
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_project_path(&project_path, cx);
+        let open_buffer = project.update(cx, |project, cx| {
+            project.open_buffer(project_path.clone(), cx)
+        });
+        cx.spawn_in(window, async move |workspace, cx| {
+            let (repository, repo_path) = repository.context("file is not in a git repository")?;
+            let stages = repository
+                .update(cx, |repository, _| {
+                    repository.load_merge_stages(repo_path.clone())
+                })?
+                .await??
+                .with_context(|| format!("{} has no merge conflicts", repo_path.display()))?;
+            let buffer = open_buffer.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let merge_view = cx.new(|cx| {
+                    Self::new(
+                        project_path,
+                        repository,
+                        repo_path,
+                        buffer,
+                        stages,
+                        project,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.add_item_to_active_pane(Box::new(merge_view), None, true, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open merge view", window, cx, |error, _, _| {
+            Some(error.to_string())
+        });
+    }
+
+    fn new(
+        project_path: ProjectPath,
+        repository: Entity<Repository>,
+        repo_path: RepoPath,
+        buffer: Entity<Buffer>,
+        stages: MergeStages,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let version_buffer = |text: Option<String>, cx: &mut Context<Self>| {
+            let version_buffer =
+                FileDiffView::buffer_for_text(text.unwrap_or_default(), &buffer, cx);
+            version_buffer.update(cx, |buffer, cx| {
+                buffer.set_capability(language::Capability::ReadOnly, cx)
+            });
+            version_buffer
+        };
+        let base_buffer = version_buffer(stages.base, cx);
+        let ours_buffer = version_buffer(stages.ours, cx);
+        let theirs_buffer = version_buffer(stages.theirs, cx);
+
+        let base_editor = version_editor(&base_buffer, None, &project, window, cx);
+        let ours_editor = version_editor(&ours_buffer, Some(&base_buffer), &project, window, cx);
+        let theirs_editor =
+            version_editor(&theirs_buffer, Some(&base_buffer), &project, window, cx);
+        let result_editor =
+            cx.new(|cx| Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx));
+
+        let subscriptions = vec![
+            cx.subscribe(&buffer, |this, _, event, cx| match event {
+                BufferEvent::Edited => {
+                    this.highlight_conflicts(cx);
+                    cx.notify();
+                }
+                BufferEvent::Reloaded => this.update_conflicts(cx),
+                _ => {}
+            }),
+            cx.subscribe(&result_editor, |_, _, event: &EditorEvent, cx| {
+                Editor::to_item_events(event, |event| cx.emit(event));
+            }),
+        ];
+
+        let mut this = Self {
+            project,
+            project_path,
+            repository,
+            repo_path,
+            buffer,
+            base_buffer,
+            ours_buffer,
+            theirs_buffer,
+            base_editor,
+            ours_editor,
+            theirs_editor,
+            result_editor,
+            conflicts: Vec::new(),
+            _update_conflicts: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.update_conflicts(cx);
+        this
+    }
+
+    fn update_conflicts(&mut self, cx: &mut Context<Self>) {
+        let base = self.base_buffer.read(cx).text();
+        let ours = self.ours_buffer.read(cx).text();
+        let theirs = self.theirs_buffer.read(cx).text();
+        let snapshot = self.buffer.read(cx).snapshot();
+        let locate_conflicts =
+            cx.background_spawn(async move { locate_conflicts(&base, &ours, &theirs, &snapshot) });
+        self._update_conflicts = cx.spawn(async move |this, cx| {
+            let conflicts = locate_conflicts.await;
+            this.update(cx, |this, cx| {
+                this.conflicts = conflicts;
+                this.highlight_conflicts(cx);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn highlight_conflicts(&mut self, cx: &mut Context<Self>) {
+        let color = cx.theme().status().conflict_background.opacity(0.2);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let unresolved = self
+            .conflicts
+            .iter()
+            .filter(|conflict| !conflict.is_resolved(&snapshot))
+            .map(|conflict| conflict.result.to_offset(&snapshot))
+            .collect::<Vec<_>>();
+        highlight_ranges(&self.result_editor, unresolved, color, cx);
+
+        let version_ranges = [
+            (
+                &self.base_editor,
+                self.conflicts.iter().map(|c| c.base.clone()),
+            ),
+            (
+                &self.ours_editor,
+                self.conflicts.iter().map(|c| c.ours.clone()),
+            ),
+            (
+                &self.theirs_editor,
+                self.conflicts.iter().map(|c| c.theirs.clone()),
+            ),
+        ];
+        for (editor, ranges) in version_ranges {
+            highlight_ranges(editor, ranges.collect(), color, cx);
+        }
+    }
+
+    pub fn conflict_count(&self) -> usize {
+        self.conflicts.len()
+    }
+
+    pub fn unresolved_conflict_count(&self, cx: &App) -> usize {
+        let snapshot = self.buffer.read(cx).snapshot();
+        self.conflicts
+            .iter()
+            .filter(|conflict| !conflict.is_resolved(&snapshot))
+            .count()
+    }
+
+    fn cursor_offset(&self, cx: &mut App) -> usize {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        })
+    }
+
+    fn conflict_at_cursor(&self, cx: &mut App) -> Option<usize> {
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        self.conflicts.iter().position(|conflict| {
+            let range = conflict.result.to_offset(&snapshot);
+            range.start <= cursor && cursor <= range.end
+        })
+    }
+
+    fn go_to_next_conflict(
+        &mut self,
+        _: &NextConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let next = self
+            .conflicts
+            .iter()
+            .position(|conflict| conflict.result.start.to_offset(&snapshot) > cursor)
+            .or_else(|| (!self.conflicts.is_empty()).then_some(0));
+        if let Some(ix) = next {
+            self.select_conflict(ix, window, cx);
+        }
+    }
+
+    fn go_to_previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let previous = self
+            .conflicts
+            .iter()
+            .rposition(|conflict| conflict.result.end.to_offset(&snapshot) < cursor)
+            .or_else(|| self.conflicts.len().checked_sub(1));
+        if let Some(ix) = previous {
+            self.select_conflict(ix, window, cx);
+        }
+    }
+
+    fn select_conflict(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let conflict = &self.conflicts[ix];
+        let start = conflict
+            .result
+            .start
+            .to_offset(&self.buffer.read(cx).snapshot());
+        self.result_editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
+                selections.select_ranges([start..start])
+            });
+        });
+        let version_ranges = [
+            (&self.base_editor, conflict.base.clone()),
+            (&self.ours_editor, conflict.ours.clone()),
+            (&self.theirs_editor, conflict.theirs.clone()),
+        ];
+        for (editor, range) in version_ranges {
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), window, cx, |selections| {
+                    selections.select_ranges([range.start..range.start])
+                });
+            });
+        }
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(true, false, window, cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(false, true, window, cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_conflict_at_cursor(true, true, window, cx);
+    }
+
+    /// Replaces the conflict at the cursor with our side, their side, or ours followed by theirs.
+    fn resolve_conflict_at_cursor(
+        &mut self,
+        ours: bool,
+        theirs: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.conflict_at_cursor(cx) else {
+            return;
+        };
+        let conflict = &self.conflicts[ix];
+        let mut text = String::new();
+        if ours {
+            text.extend(
+                self.ours_buffer
+                    .read(cx)
+                    .text_for_range(conflict.ours.clone()),
+            );
+        }
+        if theirs {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.extend(
+                self.theirs_buffer
+                    .read(cx)
+                    .text_for_range(conflict.theirs.clone()),
+            );
+        }
+        let range = conflict.result.to_offset(&self.buffer.read(cx).snapshot());
+        self.result_editor.update(cx, |editor, cx| {
+            editor.transact(window, cx, |editor, window, cx| {
+                editor.edit([(range.clone(), text)], cx);
+                editor.change_selections(None, window, cx, |selections| {
+                    selections.select_ranges([range.start..range.start])
+                });
+            });
+        });
+    }
+
+    /// Saves and stages the file, which tells git that its conflicts have been resolved.
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        let unresolved = self.unresolved_conflict_count(cx);
+        let prompt = (unresolved > 0).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                &format!(
+                    "{unresolved} {} still unresolved. Mark the file as resolved anyway?",
+                    if unresolved == 1 {
+                        "conflict is"
+                    } else {
+                        "conflicts are"
+                    }
+                ),
+                None,
+                &["Mark as Resolved", "Cancel"],
+                cx,
+            )
+        });
+        let project = self.project.clone();
+        let buffer = self.buffer.clone();
+        let repository = self.repository.clone();
+        let repo_path = self.repo_path.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            if let Some(prompt) = prompt {
+                if prompt.await != Ok(0) {
+                    return Ok(());
+                }
+            }
+            // Staging reads the file from disk, so the resolution has to be saved first.
+            if buffer.read_with(cx, |buffer, _| buffer.is_dirty())? {
+                project
+                    .update(cx, |project, cx| project.save_buffer(buffer, cx))?
+                    .await?;
+            }
+            repository
+                .update(cx, |repository, cx| {
+                    repository.stage_entries(vec![repo_path], cx)
+                })?
+                .await?;
+            this.update(cx, |_, cx| cx.emit(ItemEvent::CloseItem))
+        })
+        .detach_and_prompt_err("Failed to mark as resolved", window, cx, |error, _, _| {
+            Some(error.to_string())
+        });
+    }
+
+    fn render_version(
+        &self,
+        label: &'static str,
+        editor: &Entity<Editor>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .border_r_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(label).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+    }
+}
+
+impl Conflict {
+    fn is_resolved(&self, snapshot: &BufferSnapshot) -> bool {
+        !contains_conflict_markers(snapshot.text_for_range(self.result.clone()))
+    }
+}
+
+fn version_editor(
+    buffer: &Entity<Buffer>,
+    base_buffer: Option<&Entity<Buffer>>,
+    project: &Entity<Project>,
+    window: &mut Window,
+    cx: &mut Context<MergeView>,
+) -> Entity<Editor> {
+    let multibuffer = cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+        if let Some(base_buffer) = base_buffer {
+            let snapshot = buffer.read(cx).text_snapshot();
+            let diff = cx.new(|cx| {
+                let mut diff = BufferDiff::new(&snapshot, cx);
+                let _ = diff.set_base_text_buffer(base_buffer.clone(), snapshot.clone(), cx);
+                diff
+            });
+            multibuffer.add_diff(diff, cx);
+        }
+        multibuffer
+    });
+    cx.new(|cx| {
+        let mut editor = Editor::for_multibuffer(multibuffer, Some(project.clone()), window, cx);
+        editor.set_read_only(true);
+        editor.disable_inline_diagnostics();
+        editor.set_expand_all_diff_hunks(cx);
+        editor
+    })
+}
+
+fn highlight_ranges(
+    editor: &Entity<Editor>,
+    ranges: Vec<Range<usize>>,
+    color: gpui::Hsla,
+    cx: &mut App,
+) {
+    editor.update(cx, |editor, cx| {
+        editor.clear_row_highlights::<ConflictHighlight>();
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        for range in ranges {
+            if range.is_empty() {
+                continue;
+            }
+            let range = snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+            editor.highlight_rows::<ConflictHighlight>(range, color, false, cx);
+        }
+        cx.notify();
+    });
+}
+
+fn contains_conflict_markers<'a>(mut chunks: impl Iterator<Item = &'a str>) -> bool {
+    let text = chunks.by_ref().collect::<String>();
+    text.lines().any(|line| {
+        ["<<<<<<<", "|||||||", "=======", ">>>>>>>"]
+            .iter()
+            .any(|marker| line.starts_with(marker))
+    })
+}
+
+/// Finds the conflicts between ours and theirs, and the regions of the file being resolved that
+/// correspond to them.
+fn locate_conflicts(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    snapshot: &BufferSnapshot,
+) -> Vec<Conflict> {
+    let merge = merge(base, ours, theirs);
+    let result = snapshot.text();
+    let hunks = language::line_diff(&merge.text, &result);
+    let row_offsets = [base, ours, theirs, &result].map(row_offsets);
+    merge
+        .conflicts
+        .into_iter()
+        .map(|conflict| {
+            let result_range = rows_to_offsets(
+                &row_offsets[3],
+                map_rows(&hunks, conflict.merged),
+                result.len(),
+            );
+            Conflict {
+                result: snapshot.anchor_before(result_range.start)
+                    ..snapshot.anchor_after(result_range.end),
+                base: rows_to_offsets(&row_offsets[0], conflict.base, base.len()),
+                ours: rows_to_offsets(&row_offsets[1], conflict.ours, ours.len()),
+                theirs: rows_to_offsets(&row_offsets[2], conflict.theirs, theirs.len()),
+            }
+        })
+        .collect()
+}
+
+/// A region that both sides of a merge changed differently, as rows of each version and of the
+/// merged text.
+#[derive(Debug, PartialEq)]
+struct ConflictRows {
+    base: Range<u32>,
+    ours: Range<u32>,
+    theirs: Range<u32>,
+    merged: Range<u32>,
+}
+
+/// The result of merging both sides' changes to the base, taking ours where they conflict.
+struct Merge {
+    text: String,
+    conflicts: Vec<ConflictRows>,
+}
+
+fn merge(base: &str, ours: &str, theirs: &str) -> Merge {
+    let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+    let ours_lines = ours.split_inclusive('\n').collect::<Vec<_>>();
+    let theirs_lines = theirs.split_inclusive('\n').collect::<Vec<_>>();
+    let mut ours_hunks = language::line_diff(base, ours).into_iter().peekable();
+    let mut theirs_hunks = language::line_diff(base, theirs).into_iter().peekable();
+
+    let mut text = String::new();
+    let mut conflicts = Vec::new();
+    let mut base_row = 0;
+    let mut merged_row = 0;
+    let (mut ours_delta, mut theirs_delta) = (0, 0);
+    loop {
+        let start = match (ours_hunks.peek(), theirs_hunks.peek()) {
+            (None, None) => break,
+            (Some((ours_base, _)), None) => ours_base.start,
+            (None, Some((theirs_base, _))) => theirs_base.start,
+            (Some((ours_base, _)), Some((theirs_base, _))) => {
+                ours_base.start.min(theirs_base.start)
+            }
+        };
+        let ours_start = shift_row(start, ours_delta);
+        let theirs_start = shift_row(start, theirs_delta);
+
+        // Group the hunks from both sides that overlap or touch each other.
+        let mut end = start;
+        let (mut ours_changed, mut theirs_changed) = (false, false);
+        loop {
+            if let Some((base_rows, rows)) = ours_hunks.next_if(|(rows, _)| rows.start <= end) {
+                end = end.max(base_rows.end);
+                ours_delta += rows.len() as i64 - base_rows.len() as i64;
+                ours_changed = true;
+            } else if let Some((base_rows, rows)) =
+                theirs_hunks.next_if(|(rows, _)| rows.start <= end)
+            {
+                end = end.max(base_rows.end);
+                theirs_delta += rows.len() as i64 - base_rows.len() as i64;
+                theirs_changed = true;
+            } else {
+                break;
+            }
+        }
+        let ours_rows = ours_start..shift_row(end, ours_delta);
+        let theirs_rows = theirs_start..shift_row(end, theirs_delta);
+
+        text.extend(
+            base_lines[base_row as usize..start as usize]
+                .iter()
+                .copied(),
+        );
+        merged_row += start - base_row;
+        base_row = end;
+
+        let ours_text = &ours_lines[ours_rows.start as usize..ours_rows.end as usize];
+        let theirs_text = &theirs_lines[theirs_rows.start as usize..theirs_rows.end as usize];
+        let taken = if ours_changed { ours_text } else { theirs_text };
+        text.extend(taken.iter().copied());
+        let merged_rows = merged_row..merged_row + taken.len() as u32;
+        merged_row = merged_rows.end;
+
+        if ours_changed && theirs_changed && ours_text != theirs_text {
+            conflicts.push(ConflictRows {
+                base: start..end,
+                ours: ours_rows,
+                theirs: theirs_rows,
+                merged: merged_rows,
+            });
+        }
+    }
+    text.extend(base_lines[base_row as usize..].iter().copied());
+
+    Merge { text, conflicts }
+}
+
+fn shift_row(row: u32, delta: i64) -> u32 {
+    (row as i64 + delta) as u32
+}
+
+/// Maps rows of the old text of a diff to the rows of the new text that replaced them, including
+/// any hunks that touch them.
+fn map_rows(hunks: &[(Range<u32>, Range<u32>)], rows: Range<u32>) -> Range<u32> {
+    let mut delta = 0;
+    let mut start = None;
+    let mut end = None;
+    for (old, new) in hunks {
+        let touches = if rows.is_empty() || old.is_empty() {
+            old.start <= rows.end && old.end >= rows.start
+        } else {
+            old.start < rows.end && old.end > rows.start
+        };
+        let hunk_delta = new.len() as i64 - old.len() as i64;
+        if touches {
+            start.get_or_insert(shift_row(rows.start, delta).min(new.start));
+            delta += hunk_delta;
+            end = Some(new.end.max(shift_row(rows.end, delta)));
+        } else if old.start >= rows.end {
+            break;
+        } else {
+            delta += hunk_delta;
+        }
+    }
+    let start = start.unwrap_or_else(|| shift_row(rows.start, delta));
+    let end = end.unwrap_or_else(|| shift_row(rows.end, delta));
+    start..end
+}
+
+fn row_offsets(text: &str) -> Vec<usize> {
+    iter::once(0)
+        .chain(text.match_indices('\n').map(|(ix, _)| ix + 1))
+        .collect()
+}
+
+fn rows_to_offsets(row_offsets: &[usize], rows: Range<u32>, len: usize) -> Range<usize> {
+    let offset = |row: u32| row_offsets.get(row as usize).copied().unwrap_or(len);
+    offset(rows.start)..offset(rows.end)
+}
+
+impl Render for MergeView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .key_context("MergeView")
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::go_to_next_conflict))
+            .on_action(cx.listener(Self::go_to_previous_conflict))
+            .on_action(cx.listener(Self::mark_resolved))
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.render_version("Base", &self.base_editor, cx))
+                    .child(self.render_version("Ours", &self.ours_editor, cx))
+                    .child(self.render_version("Theirs", &self.theirs_editor, cx)),
+            )
+            .child(div().flex_1().min_h_0().child(self.result_editor.clone()))
+    }
+}
+
+impl Focusable for MergeView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<ItemEvent> for MergeView {}
+
+impl Item for MergeView {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        let file_name = self
+            .repo_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Some(format!("Merge {file_name}").into())
+    }
+
+    fn tab_tooltip_text(&self, _cx: &App) -> Option<SharedString> {
+        Some(format!("Resolve conflicts in {}", self.repo_path.display()).into())
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Merge View Opened")
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.result_editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.result_editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.result_editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        })
+    }
+}
+
+/// Toolbar controls for navigating and resolving conflicts in the active [`MergeView`].
+pub struct MergeViewToolbar {
+    merge_view: Option<Entity<MergeView>>,
+    _observe_merge_view: Option<Subscription>,
+}
+
+impl MergeViewToolbar {
+    pub fn new() -> Self {
+        Self {
+            merge_view: None,
+            _observe_merge_view: None,
+        }
+    }
+}
+
+impl Render for MergeViewToolbar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(merge_view) = &self.merge_view else {
+            return div();
+        };
+        let focus_handle = merge_view.focus_handle(cx);
+        let merge_view = merge_view.read(cx);
+        let conflict_count = merge_view.conflict_count();
+        let unresolved_count = merge_view.unresolved_conflict_count(cx);
+
+        let tooltip = |tooltip: &'static str, action: Box<dyn Action>| {
+            let focus_handle = focus_handle.clone();
+            move |window: &mut Window, cx: &mut App| {
+                Tooltip::for_action_in(tooltip, action.as_ref(), &focus_handle, window, cx)
+            }
+        };
+        let on_click = |action: Box<dyn Action>| {
+            let focus_handle = focus_handle.clone();
+            move |_: &gpui::ClickEvent, window: &mut Window, cx: &mut App| {
+                focus_handle.dispatch_action(action.as_ref(), window, cx)
+            }
+        };
+        let icon_button =
+            |id: &'static str, icon: IconName, label: &'static str, action: Box<dyn Action>| {
+                IconButton::new(id, icon)
+                    .icon_size(IconSize::Small)
+                    .disabled(conflict_count == 0)
+                    .tooltip(tooltip(label, action.boxed_clone()))
+                    .on_click(on_click(action))
+            };
+        let button = |id: &'static str, label: &'static str, action: Box<dyn Action>| {
+            Button::new(id, label)
+                .label_size(LabelSize::Small)
+                .tooltip(tooltip(label, action.boxed_clone()))
+                .on_click(on_click(action))
+        };
+
+        h_flex()
+            .gap_1()
+            .child(
+                Label::new(format!(
+                    "{unresolved_count} of {conflict_count} conflicts unresolved"
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(icon_button(
+                "previous-conflict",
+                IconName::ArrowUp,
+                "Previous Conflict",
+                Box::new(PreviousConflict),
+            ))
+            .child(icon_button(
+                "next-conflict",
+                IconName::ArrowDown,
+                "Next Conflict",
+                Box::new(NextConflict),
+            ))
+            .child(button("accept-ours", "Accept Ours", Box::new(AcceptOurs)))
+            .child(button(
+                "accept-theirs",
+                "Accept Theirs",
+                Box::new(AcceptTheirs),
+            ))
+            .child(button("accept-both", "Accept Both", Box::new(AcceptBoth)))
+            .child(
+                button("mark-resolved", "Mark as Resolved", Box::new(MarkResolved))
+                    .style(ButtonStyle::Filled),
+            )
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for MergeViewToolbar {}
+
+impl ToolbarItemView for MergeViewToolbar {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn workspace::ItemHandle>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> ToolbarItemLocation {
+        self.merge_view = active_pane_item.and_then(|item| item.downcast::<MergeView>());
+        self._observe_merge_view = self
+            .merge_view
+            .as_ref()
+            .map(|merge_view| cx.observe(merge_view, |_, _, cx| cx.notify()));
+        if self.merge_view.is_some() {
+            ToolbarItemLocation::PrimaryRight
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use unindent::Unindent as _;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = "a\nb\nc\nd\ne\n";
+
+        // Changes to different regions are merged.
+        let merge = merge(base, "a\nB\nc\nd\ne\n", "a\nb\nc\nD\ne\n");
+        assert_eq!(merge.text, "a\nB\nc\nD\ne\n");
+        assert_eq!(merge.conflicts, []);
+
+        // Identical changes don't conflict.
+        let merge = super::merge(base, "a\nb\nC\nd\ne\n", "a\nb\nC\nd\ne\n");
+        assert_eq!(merge.text, "a\nb\nC\nd\ne\n");
+        assert_eq!(merge.conflicts, []);
+
+        // Different changes to the same region conflict, and take ours.
+        let merge = super::merge(base, "a\nB\nX\nd\ne\n", "a\nb\nY\nY\nd\nE\n");
+        assert_eq!(merge.text, "a\nB\nX\nd\nE\n");
+        assert_eq!(
+            merge.conflicts,
+            [ConflictRows {
+                base: 1..3,
+                ours: 1..3,
+                theirs: 1..4,
+                merged: 1..3,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_map_rows_over_conflict_markers() {
+        let merged = "a\nX\nc\n";
+        let result = "a\n<<<<<<< HEAD\nX\n=======\nY\n>>>>>>> branch\nc\n";
+        let hunks = language::line_diff(merged, result);
+        assert_eq!(map_rows(&hunks, 1..2), 1..6);
+
+        // Once resolved, the region covers the text that replaced the conflict.
+        let hunks = language::line_diff(merged, "a\nY\nc\n");
+        assert_eq!(map_rows(&hunks, 1..2), 1..2);
+    }
+
+    #[gpui::test]
+    async fn test_resolve_conflicts(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let base = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+        let ours = "
+            one
+            TWO
+            three
+            four
+        "
+        .unindent();
+        let theirs = "
+            one
+            2
+            three
+            4
+        "
+        .unindent();
+        let conflicted = "
+            one
+            <<<<<<< HEAD
+            TWO
+            =======
+            2
+            >>>>>>> branch
+            three
+            4
+        "
+        .unindent();
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "file.txt": conflicted,
+            }),
+        )
+        .await;
+        fs.set_merge_stages_for_repo(
+            Path::new(path!("/project/.git")),
+            &[(
+                "file.txt".into(),
+                MergeStages {
+                    base: Some(base),
+                    ours: Some(ours),
+                    theirs: Some(theirs),
+                },
+            )],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        cx.run_until_parked();
+
+        let project_path = project
+            .read_with(cx, |project, cx| {
+                project.find_project_path(path!("/project/file.txt"), cx)
+            })
+            .unwrap();
+        workspace.update_in(cx, |workspace, window, cx| {
+            MergeView::deploy(workspace, project_path, window, cx)
+        });
+        cx.run_until_parked();
+
+        let merge_view = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<MergeView>(cx).unwrap()
+        });
+        merge_view.update(cx, |merge_view, cx| {
+            assert_eq!(merge_view.conflict_count(), 1);
+            assert_eq!(merge_view.unresolved_conflict_count(cx), 1);
+        });
+
+        merge_view.update_in(cx, |merge_view, window, cx| {
+            merge_view.go_to_next_conflict(&NextConflict, window, cx);
+            merge_view.accept_both(&AcceptBoth, window, cx);
+        });
+        merge_view.update(cx, |merge_view, cx| {
+            assert_eq!(
+                merge_view.buffer.read(cx).text(),
+                "
+                one
+                TWO
+                2
+                three
+                4
+                "
+                .unindent()
+            );
+            assert_eq!(merge_view.unresolved_conflict_count(cx), 0);
+        });
+
+        merge_view.update_in(cx, |merge_view, window, cx| {
+            merge_view.mark_resolved(&MarkResolved, window, cx);
+        });
+        cx.run_until_parked();
+        assert!(!merge_view.read_with(cx, |merge_view, cx| merge_view.buffer.read(cx).is_dirty()));
+        assert_eq!(
+            fs.load(Path::new(path!("/project/file.txt")))
+                .await
+                .unwrap(),
+            "
+            one
+            TWO
+            2
+            three
+            4
+            "
+            .unindent()
+        );
+    }
+}
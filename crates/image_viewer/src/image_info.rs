@@ -4,10 +4,12 @@ use settings::Settings;
 use ui::prelude::*;
 use workspace::{ItemHandle, StatusItemView, Workspace};
 
-use crate::{ImageFileSizeUnit, ImageView, ImageViewerSettings};
+use crate::{HoveredPixel, ImageFileSizeUnit, ImageView, ImageViewerSettings};
 
 pub struct ImageInfo {
     metadata: Option<ImageMetadata>,
+    zoom: Option<f32>,
+    hovered_pixel: Option<HoveredPixel>,
     _observe_active_image: Option<Subscription>,
     observe_image_item: Option<Subscription>,
 }
@@ -16,13 +18,18 @@ impl ImageInfo {
     pub fn new(_workspace: &Workspace) -> Self {
         Self {
             metadata: None,
+            zoom: None,
+            hovered_pixel: None,
             _observe_active_image: None,
             observe_image_item: None,
         }
     }
 
     fn update_metadata(&mut self, image_view: &Entity<ImageView>, cx: &mut Context<Self>) {
-        let image_item = image_view.read(cx).image_item.clone();
+        let image_view = image_view.read(cx);
+        self.zoom = image_view.zoom();
+        self.hovered_pixel = image_view.hovered_pixel();
+        let image_item = image_view.image_item.clone();
         let current_metadata = image_item.read(cx).image_metadata;
         if current_metadata.is_some() {
             self.metadata = current_metadata;
@@ -68,6 +75,16 @@ impl Render for ImageInfo {
         };
 
         let mut components = Vec::new();
+        if let Some(pixel) = self.hovered_pixel {
+            let [r, g, b, a] = pixel.rgba;
+            components.push(format!(
+                "{}, {}: #{r:02X}{g:02X}{b:02X}{a:02X}",
+                pixel.x, pixel.y
+            ));
+        }
+        if let Some(zoom) = self.zoom {
+            components.push(format!("{:.0}%", zoom * 100.));
+        }
         components.push(format!("{}x{}", metadata.width, metadata.height));
         components.push(format_file_size(metadata.file_size, settings.unit));
 
@@ -118,6 +135,8 @@ impl StatusItemView for ImageInfo {
             }));
         } else {
             self.metadata = None;
+            self.zoom = None;
+            self.hovered_pixel = None;
         }
         cx.notify();
     }
@@ -1,18 +1,24 @@
 mod image_info;
 mod image_viewer_settings;
+mod image_viewport;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::Context as _;
 use editor::{EditorSettings, items::entry_git_aware_label_color};
 use file_icons::FileIcons;
 use gpui::{
-    AnyElement, App, Bounds, Context, Entity, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, ObjectFit, ParentElement, Render, Styled, Task, WeakEntity,
-    Window, canvas, div, fill, img, opaque_grey, point, size,
+    AnyElement, App, Bounds, ContentMask, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    Image, InteractiveElement, IntoElement, MouseButton, MouseDownEvent, MouseMoveEvent, ObjectFit,
+    ParentElement, Pixels, Point, Render, ScrollWheelEvent, Size, Styled, Task, WeakEntity, Window,
+    actions, canvas, div, img, px,
+};
+use image_viewport::{
+    ImageTransform, NEAREST_NEIGHBOR_MIN_ZOOM, paint_checkerboard, paint_pixels, pixel_rgba,
+    render_image_size,
 };
 use persistence::IMAGE_VIEWER;
-use project::{ImageItem, Project, ProjectPath, image_store::ImageItemEvent};
+use project::{ImageItem, Project, ProjectItem as _, ProjectPath, image_store::ImageItemEvent};
 use settings::Settings;
 use theme::Theme;
 use ui::prelude::*;
@@ -25,10 +31,41 @@ use workspace::{
 pub use crate::image_info::*;
 pub use crate::image_viewer_settings::*;
 
+actions!(image_viewer, [ZoomIn, ZoomOut, ZoomToFit, ZoomToActualSize]);
+
+/// How much each zoom action changes the zoom level by.
+const ZOOM_STEP: f32 = 1.25;
+/// How much scrolling by one pixel changes the zoom level by, exponentially.
+const SCROLL_ZOOM_SENSITIVITY: f32 = 0.005;
+const SCROLL_LINE_HEIGHT: Pixels = px(20.);
+
 pub struct ImageView {
     image_item: Entity<ImageItem>,
+    /// Another image shown side by side with this one, for comparison.
+    comparison: Option<Entity<ImageItem>>,
     project: Entity<Project>,
     focus_handle: FocusHandle,
+    /// Shared by both images when comparing, so that they stay aligned.
+    transform: ImageTransform,
+    /// The layout of each image from the last time it was painted.
+    layouts: [Option<ImageLayout>; 2],
+    /// The last mouse position while dragging to pan.
+    drag_position: Option<Point<Pixels>>,
+    hovered_pixel: Option<HoveredPixel>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ImageLayout {
+    viewport: Bounds<Pixels>,
+    image_size: Size<Pixels>,
+}
+
+/// The pixel under the mouse cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HoveredPixel {
+    pub x: u32,
+    pub y: u32,
+    pub rgba: [u8; 4],
 }
 
 impl ImageView {
@@ -40,11 +77,287 @@ impl ImageView {
         cx.subscribe(&image_item, Self::on_image_event).detach();
         Self {
             image_item,
+            comparison: None,
             project,
             focus_handle: cx.focus_handle(),
+            transform: ImageTransform::default(),
+            layouts: [None; 2],
+            drag_position: None,
+            hovered_pixel: None,
         }
     }
 
+    /// Creates a view showing two images side by side, zoomed and panned together.
+    pub fn comparing(
+        image_item: Entity<ImageItem>,
+        comparison: Entity<ImageItem>,
+        project: Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        cx.subscribe(&comparison, Self::on_image_event).detach();
+        Self {
+            comparison: Some(comparison),
+            ..Self::new(image_item, project, cx)
+        }
+    }
+
+    /// Opens the two paths and adds a view comparing them to the active pane, if both are images.
+    pub fn open_comparison(
+        left_path: ProjectPath,
+        right_path: ProjectPath,
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Task<anyhow::Result<Entity<Self>>>> {
+        let project = workspace.project().clone();
+        let open_left = ImageItem::try_open(&project, &left_path, cx)?;
+        let open_right = ImageItem::try_open(&project, &right_path, cx)?;
+        let workspace = workspace.weak_handle();
+        Some(window.spawn(cx, async move |cx| {
+            let left = open_left.await?;
+            let right = open_right.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let view = cx.new(|cx| ImageView::comparing(left, right, project, cx));
+                workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, window, cx);
+                view
+            })
+        }))
+    }
+
+    /// The current zoom level, as a multiple of the image's actual size.
+    pub fn zoom(&self) -> Option<f32> {
+        let layout = self.layouts[0]?;
+        Some(
+            self.transform
+                .effective_zoom(layout.viewport.size, layout.image_size),
+        )
+    }
+
+    pub fn hovered_pixel(&self) -> Option<HoveredPixel> {
+        self.hovered_pixel
+    }
+
+    fn images(&self, cx: &App) -> Vec<Arc<Image>> {
+        Some(&self.image_item)
+            .into_iter()
+            .chain(self.comparison.as_ref())
+            .map(|item| item.read(cx).image.clone())
+            .collect()
+    }
+
+    fn zoom_by(&mut self, factor: f32, cx: &mut Context<Self>) {
+        let Some(layout) = self.layouts[0] else {
+            return;
+        };
+        let zoom = self
+            .transform
+            .effective_zoom(layout.viewport.size, layout.image_size);
+        self.transform.zoom_to(
+            zoom * factor,
+            layout.viewport.center(),
+            layout.viewport,
+            layout.image_size,
+        );
+        cx.notify();
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, _: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_by(ZOOM_STEP, cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, _: &mut Window, cx: &mut Context<Self>) {
+        self.zoom_by(1. / ZOOM_STEP, cx);
+    }
+
+    fn zoom_to_fit(&mut self, _: &ZoomToFit, _: &mut Window, cx: &mut Context<Self>) {
+        self.transform = ImageTransform::default();
+        cx.notify();
+    }
+
+    fn zoom_to_actual_size(
+        &mut self,
+        _: &ZoomToActualSize,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.transform = ImageTransform {
+            zoom: Some(1.),
+            pan: Point::default(),
+        };
+        cx.notify();
+    }
+
+    fn handle_scroll_wheel(
+        &mut self,
+        image_ix: usize,
+        event: &ScrollWheelEvent,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(layout) = self.layouts[image_ix] else {
+            return;
+        };
+        let delta = event.delta.pixel_delta(SCROLL_LINE_HEIGHT);
+        if event.modifiers.secondary() || event.modifiers.control {
+            let zoom = self
+                .transform
+                .effective_zoom(layout.viewport.size, layout.image_size);
+            self.transform.zoom_to(
+                zoom * (delta.y.0 * SCROLL_ZOOM_SENSITIVITY).exp(),
+                event.position,
+                layout.viewport,
+                layout.image_size,
+            );
+        } else {
+            self.transform
+                .pan_by(delta, layout.viewport, layout.image_size);
+        }
+        cx.notify();
+    }
+
+    fn handle_mouse_move(
+        &mut self,
+        image_ix: usize,
+        event: &MouseMoveEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(drag_position) = self.drag_position {
+            if event.pressed_button == Some(MouseButton::Left) {
+                if let Some(layout) = self.layouts[image_ix] {
+                    self.transform.pan_by(
+                        event.position - drag_position,
+                        layout.viewport,
+                        layout.image_size,
+                    );
+                }
+                self.drag_position = Some(event.position);
+                cx.notify();
+            } else {
+                self.drag_position = None;
+            }
+        }
+
+        let hovered_pixel = self.pixel_at(image_ix, event.position, window, cx);
+        if hovered_pixel != self.hovered_pixel {
+            self.hovered_pixel = hovered_pixel;
+            cx.notify();
+        }
+    }
+
+    fn pixel_at(
+        &self,
+        image_ix: usize,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<HoveredPixel> {
+        let layout = self.layouts[image_ix]?;
+        let (x, y) = self
+            .transform
+            .pixel_at(position, layout.viewport, layout.image_size)?;
+        let image = self.images(cx).get(image_ix)?.clone();
+        let render_image = image.use_render_image(window, cx)?;
+        let rgba = pixel_rgba(&render_image, x, y)?;
+        Some(HoveredPixel { x, y, rgba })
+    }
+
+    fn render_image_viewport(
+        &self,
+        image_ix: usize,
+        image: Arc<Image>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement + use<> {
+        let view = cx.entity().downgrade();
+        let transform = self.transform;
+        div()
+            .id(("image", image_ix))
+            .relative()
+            .flex_1()
+            .h_full()
+            .overflow_hidden()
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, event: &MouseDownEvent, _, _| {
+                    this.drag_position = Some(event.position);
+                }),
+            )
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|this, _, _, _| this.drag_position = None),
+            )
+            .on_mouse_move(cx.listener(move |this, event, window, cx| {
+                this.handle_mouse_move(image_ix, event, window, cx)
+            }))
+            .on_scroll_wheel(
+                cx.listener(move |this, event, _, cx| {
+                    this.handle_scroll_wheel(image_ix, event, cx)
+                }),
+            )
+            .on_hover(cx.listener(|this, hovered, _, cx| {
+                if !hovered && this.hovered_pixel.take().is_some() {
+                    cx.notify();
+                }
+            }))
+            .child(
+                canvas(
+                    move |viewport, window, cx| {
+                        let render_image = image.clone().use_render_image(window, cx)?;
+                        let image_size = render_image_size(&render_image);
+                        view.update(cx, |view, cx| {
+                            let layout = Some(ImageLayout {
+                                viewport,
+                                image_size,
+                            });
+                            if view.layouts[image_ix] != layout {
+                                view.layouts[image_ix] = layout;
+                                // The zoom level shown in the status bar depends on the layout.
+                                cx.notify();
+                            }
+                        })
+                        .ok();
+
+                        let image_bounds = transform.image_bounds(viewport, image_size);
+                        let zoom = transform.effective_zoom(viewport.size, image_size);
+                        // Past a certain zoom level, pixels are painted individually instead,
+                        // since the image would be smoothed when scaled.
+                        let element = (zoom < NEAREST_NEIGHBOR_MIN_ZOOM).then(|| {
+                            let mut element = img(image)
+                                .object_fit(ObjectFit::Fill)
+                                .w(image_bounds.size.width)
+                                .h(image_bounds.size.height)
+                                .into_any_element();
+                            element.prepaint_as_root(
+                                image_bounds.origin,
+                                image_bounds.size.into(),
+                                window,
+                                cx,
+                            );
+                            element
+                        });
+                        Some((image_bounds, render_image, element))
+                    },
+                    |viewport, state, window, cx| {
+                        let Some((image_bounds, render_image, element)) = state else {
+                            return;
+                        };
+                        window.with_content_mask(
+                            Some(ContentMask { bounds: viewport }),
+                            |window| {
+                                paint_checkerboard(image_bounds, viewport, window);
+                                match element {
+                                    Some(mut element) => element.paint(window, cx),
+                                    None => {
+                                        paint_pixels(&render_image, image_bounds, viewport, window)
+                                    }
+                                }
+                            },
+                        );
+                    },
+                )
+                .size_full(),
+            )
+    }
+
     fn on_image_event(
         &mut self,
         _: Entity<ImageItem>,
@@ -121,13 +434,23 @@ impl Item for ImageView {
             params.text_color()
         };
 
-        let title = self
-            .image_item
-            .read(cx)
-            .file
-            .file_name(cx)
-            .to_string_lossy()
-            .to_string();
+        let file_name = |item: &Entity<ImageItem>| {
+            item.read(cx)
+                .file
+                .file_name(cx)
+                .to_string_lossy()
+                .to_string()
+        };
+        let title = match &self.comparison {
+            Some(comparison) => {
+                format!(
+                    "{} ↔ {}",
+                    file_name(&self.image_item),
+                    file_name(comparison)
+                )
+            }
+            None => file_name(&self.image_item),
+        };
         Label::new(title)
             .single_line()
             .color(label_color)
@@ -171,10 +494,18 @@ impl Item for ImageView {
     where
         Self: Sized,
     {
-        Some(cx.new(|cx| Self {
-            image_item: self.image_item.clone(),
-            project: self.project.clone(),
-            focus_handle: cx.focus_handle(),
+        Some(cx.new(|cx| {
+            let mut view = match &self.comparison {
+                Some(comparison) => Self::comparing(
+                    self.image_item.clone(),
+                    comparison.clone(),
+                    self.project.clone(),
+                    cx,
+                ),
+                None => Self::new(self.image_item.clone(), self.project.clone(), cx),
+            };
+            view.transform = self.transform;
+            view
         }))
     }
 }
@@ -282,72 +613,23 @@ impl Focusable for ImageView {
 
 impl Render for ImageView {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let image = self.image_item.read(cx).image.clone();
-        let checkered_background = |bounds: Bounds<Pixels>,
-                                    _,
-                                    window: &mut Window,
-                                    _cx: &mut App| {
-            let square_size = 32.0;
-
-            let start_y = bounds.origin.y.0;
-            let height = bounds.size.height.0;
-            let start_x = bounds.origin.x.0;
-            let width = bounds.size.width.0;
-
-            let mut y = start_y;
-            let mut x = start_x;
-            let mut color_swapper = true;
-            // draw checkerboard pattern
-            while y <= start_y + height {
-                // Keeping track of the grid in order to be resilient to resizing
-                let start_swap = color_swapper;
-                while x <= start_x + width {
-                    let rect =
-                        Bounds::new(point(px(x), px(y)), size(px(square_size), px(square_size)));
-
-                    let color = if color_swapper {
-                        opaque_grey(0.6, 0.4)
-                    } else {
-                        opaque_grey(0.7, 0.4)
-                    };
-
-                    window.paint_quad(fill(rect, color));
-                    color_swapper = !color_swapper;
-                    x += square_size;
-                }
-                x = start_x;
-                color_swapper = !start_swap;
-                y += square_size;
-            }
-        };
-
-        let checkered_background = canvas(|_, _, _| (), checkered_background)
-            .border_2()
-            .border_color(cx.theme().styles.colors.border)
-            .size_full()
-            .absolute()
-            .top_0()
-            .left_0();
-
-        div()
+        let images = self.images(cx);
+        h_flex()
+            .key_context("ImageViewer")
             .track_focus(&self.focus_handle(cx))
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::zoom_to_fit))
+            .on_action(cx.listener(Self::zoom_to_actual_size))
             .size_full()
-            .child(checkered_background)
-            .child(
-                div()
-                    .flex()
-                    .justify_center()
-                    .items_center()
-                    .w_full()
-                    // TODO: In browser based Tailwind & Flex this would be h-screen and we'd use w-full
-                    .h_full()
-                    .child(
-                        img(image)
-                            .object_fit(ObjectFit::ScaleDown)
-                            .max_w_full()
-                            .max_h_full()
-                            .id("img"),
-                    ),
+            .border_2()
+            .border_color(cx.theme().styles.colors.border)
+            .gap_0p5()
+            .children(
+                images
+                    .into_iter()
+                    .enumerate()
+                    .map(|(image_ix, image)| self.render_image_viewport(image_ix, image, cx)),
             )
     }
 }
@@ -0,0 +1,353 @@
+use gpui::{
+    Bounds, Pixels, Point, RenderImage, Rgba, Size, Window, fill, opaque_grey, point, px, size,
+};
+
+/// The smallest zoom level, as a multiple of the image's actual size.
+pub const MIN_ZOOM: f32 = 0.02;
+/// The largest zoom level, as a multiple of the image's actual size.
+pub const MAX_ZOOM: f32 = 64.0;
+/// The zoom level from which image pixels are painted as solid squares, rather than
+/// smoothly scaling the image.
+pub const NEAREST_NEIGHBOR_MIN_ZOOM: f32 = 8.0;
+
+const CHECKERBOARD_SQUARE_SIZE: f32 = 16.0;
+
+/// How an image is zoomed and panned within the viewport it's displayed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImageTransform {
+    /// The zoom level, or `None` to fit the image to the viewport.
+    pub zoom: Option<f32>,
+    /// The offset of the image's center from the viewport's center.
+    pub pan: Point<Pixels>,
+}
+
+impl ImageTransform {
+    pub fn effective_zoom(&self, viewport: Size<Pixels>, image_size: Size<Pixels>) -> f32 {
+        self.zoom.unwrap_or_else(|| fit_zoom(viewport, image_size))
+    }
+
+    /// Where the image is painted for the given viewport.
+    pub fn image_bounds(
+        &self,
+        viewport: Bounds<Pixels>,
+        image_size: Size<Pixels>,
+    ) -> Bounds<Pixels> {
+        let zoom = self.effective_zoom(viewport.size, image_size);
+        Bounds::centered_at(
+            viewport.center() + self.pan,
+            size(image_size.width * zoom, image_size.height * zoom),
+        )
+    }
+
+    /// Changes the zoom level, keeping the part of the image under `anchor` in place.
+    pub fn zoom_to(
+        &mut self,
+        zoom: f32,
+        anchor: Point<Pixels>,
+        viewport: Bounds<Pixels>,
+        image_size: Size<Pixels>,
+    ) {
+        let old_zoom = self.effective_zoom(viewport.size, image_size);
+        let new_zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let anchor_offset = anchor - viewport.center() - self.pan;
+        self.pan = anchor - viewport.center() - anchor_offset * (new_zoom / old_zoom);
+        self.zoom = Some(new_zoom);
+        self.clamp_pan(viewport, image_size);
+    }
+
+    /// Moves the image by `delta`, as far as [`Self::clamp_pan`] allows.
+    pub fn pan_by(
+        &mut self,
+        delta: Point<Pixels>,
+        viewport: Bounds<Pixels>,
+        image_size: Size<Pixels>,
+    ) {
+        self.pan = self.pan + delta;
+        self.clamp_pan(viewport, image_size);
+    }
+
+    /// Limits the pan so that an image smaller than the viewport stays inside it, and a larger
+    /// image keeps covering it.
+    fn clamp_pan(&mut self, viewport: Bounds<Pixels>, image_size: Size<Pixels>) {
+        let zoom = self.effective_zoom(viewport.size, image_size);
+        let max_x = (image_size.width.0 * zoom - viewport.size.width.0).abs() / 2.;
+        let max_y = (image_size.height.0 * zoom - viewport.size.height.0).abs() / 2.;
+        self.pan = point(
+            px(self.pan.x.0.clamp(-max_x, max_x)),
+            px(self.pan.y.0.clamp(-max_y, max_y)),
+        );
+    }
+
+    /// Returns the coordinates of the image pixel at `position`, if there is one.
+    pub fn pixel_at(
+        &self,
+        position: Point<Pixels>,
+        viewport: Bounds<Pixels>,
+        image_size: Size<Pixels>,
+    ) -> Option<(u32, u32)> {
+        let bounds = self.image_bounds(viewport, image_size);
+        if !viewport.contains(&position) || !bounds.contains(&position) {
+            return None;
+        }
+        let zoom = self.effective_zoom(viewport.size, image_size);
+        let x = ((position.x - bounds.origin.x).0 / zoom) as u32;
+        let y = ((position.y - bounds.origin.y).0 / zoom) as u32;
+        let max_x = (image_size.width.0 as u32).saturating_sub(1);
+        let max_y = (image_size.height.0 as u32).saturating_sub(1);
+        Some((x.min(max_x), y.min(max_y)))
+    }
+}
+
+/// The zoom level at which the image fits the viewport, without enlarging it.
+fn fit_zoom(viewport: Size<Pixels>, image_size: Size<Pixels>) -> f32 {
+    if image_size.width <= px(0.) || image_size.height <= px(0.) {
+        return 1.;
+    }
+    (viewport.width / image_size.width)
+        .min(viewport.height / image_size.height)
+        .min(1.)
+}
+
+/// The size of the image's first frame, with one image pixel per logical pixel.
+pub fn render_image_size(image: &RenderImage) -> Size<Pixels> {
+    let image_size = image.size(0);
+    size(
+        px(image_size.width.0 as f32),
+        px(image_size.height.0 as f32),
+    )
+}
+
+/// Returns the RGBA components of a pixel in the image's first frame.
+pub fn pixel_rgba(image: &RenderImage, x: u32, y: u32) -> Option<[u8; 4]> {
+    let width = image.size(0).width.0 as usize;
+    let offset = (y as usize * width + x as usize) * 4;
+    // Render images are stored as BGRA.
+    let bgra = image.as_bytes(0)?.get(offset..offset + 4)?;
+    Some([bgra[2], bgra[1], bgra[0], bgra[3]])
+}
+
+/// Paints a checkerboard behind the image, so that transparent areas are visible.
+pub fn paint_checkerboard(
+    image_bounds: Bounds<Pixels>,
+    viewport: Bounds<Pixels>,
+    window: &mut Window,
+) {
+    let visible = image_bounds.intersect(&viewport);
+    if visible.size.width <= px(0.) || visible.size.height <= px(0.) {
+        return;
+    }
+
+    // Align the squares to the image, so that the pattern moves with it when panning.
+    let first_column =
+        ((visible.origin.x - image_bounds.origin.x).0 / CHECKERBOARD_SQUARE_SIZE).floor() as i32;
+    let first_row =
+        ((visible.origin.y - image_bounds.origin.y).0 / CHECKERBOARD_SQUARE_SIZE).floor() as i32;
+    let mut row = first_row;
+    loop {
+        let y = image_bounds.origin.y + px(row as f32 * CHECKERBOARD_SQUARE_SIZE);
+        if y >= visible.bottom() {
+            break;
+        }
+        let mut column = first_column;
+        loop {
+            let x = image_bounds.origin.x + px(column as f32 * CHECKERBOARD_SQUARE_SIZE);
+            if x >= visible.right() {
+                break;
+            }
+            let square = Bounds::new(
+                point(x, y),
+                size(px(CHECKERBOARD_SQUARE_SIZE), px(CHECKERBOARD_SQUARE_SIZE)),
+            )
+            .intersect(&visible);
+            let color = if (row + column) % 2 == 0 {
+                opaque_grey(0.6, 0.4)
+            } else {
+                opaque_grey(0.7, 0.4)
+            };
+            window.paint_quad(fill(square, color));
+            column += 1;
+        }
+        row += 1;
+    }
+}
+
+/// Paints the visible pixels of the image's first frame as solid squares, so that they stay
+/// sharp at high zoom levels.
+///
+/// Runs of identical pixels in a row are painted as a single quad.
+pub fn paint_pixels(
+    image: &RenderImage,
+    image_bounds: Bounds<Pixels>,
+    viewport: Bounds<Pixels>,
+    window: &mut Window,
+) {
+    let Some(bytes) = image.as_bytes(0) else {
+        return;
+    };
+    let image_size = render_image_size(image);
+    let width = image_size.width.0 as usize;
+    let height = image_size.height.0 as usize;
+    if width == 0 || height == 0 {
+        return;
+    }
+    let pixel_size = image_bounds.size.width / image_size.width;
+
+    let first_column = ((viewport.left() - image_bounds.origin.x).0 / pixel_size)
+        .floor()
+        .max(0.) as usize;
+    let last_column = ((viewport.right() - image_bounds.origin.x).0 / pixel_size)
+        .ceil()
+        .max(0.) as usize;
+    let first_row = ((viewport.top() - image_bounds.origin.y).0 / pixel_size)
+        .floor()
+        .max(0.) as usize;
+    let last_row = ((viewport.bottom() - image_bounds.origin.y).0 / pixel_size)
+        .ceil()
+        .max(0.) as usize;
+    let columns = first_column.min(width)..last_column.min(width);
+
+    for row in first_row.min(height)..last_row.min(height) {
+        let row_bytes = &bytes[row * width * 4..(row + 1) * width * 4];
+        let mut column = columns.start;
+        while column < columns.end {
+            let bgra = &row_bytes[column * 4..column * 4 + 4];
+            let mut run_end = column + 1;
+            while run_end < columns.end && &row_bytes[run_end * 4..run_end * 4 + 4] == bgra {
+                run_end += 1;
+            }
+            if bgra[3] > 0 {
+                let color = Rgba {
+                    r: bgra[2] as f32 / 255.,
+                    g: bgra[1] as f32 / 255.,
+                    b: bgra[0] as f32 / 255.,
+                    a: bgra[3] as f32 / 255.,
+                };
+                let origin = image_bounds.origin
+                    + point(px(column as f32 * pixel_size), px(row as f32 * pixel_size));
+                let run_size = size(px((run_end - column) as f32 * pixel_size), px(pixel_size));
+                window.paint_quad(fill(Bounds::new(origin, run_size), color));
+            }
+            column = run_end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport() -> Bounds<Pixels> {
+        Bounds::new(point(px(0.), px(0.)), size(px(200.), px(100.)))
+    }
+
+    #[test]
+    fn test_fit_zoom() {
+        let viewport = viewport().size;
+        assert_eq!(fit_zoom(viewport, size(px(400.), px(200.))), 0.5);
+        // The tighter dimension decides.
+        assert_eq!(fit_zoom(viewport, size(px(100.), px(400.))), 0.25);
+        // Small images aren't enlarged.
+        assert_eq!(fit_zoom(viewport, size(px(50.), px(20.))), 1.);
+        assert_eq!(fit_zoom(viewport, size(px(0.), px(20.))), 1.);
+    }
+
+    #[test]
+    fn test_zoom_to_keeps_anchor_in_place() {
+        let viewport = viewport();
+        let image_size = size(px(400.), px(200.));
+        let anchor = point(px(150.), px(75.));
+        let mut transform = ImageTransform::default();
+        assert_eq!(
+            transform.pixel_at(anchor, viewport, image_size),
+            Some((300, 150))
+        );
+
+        transform.zoom_to(2., anchor, viewport, image_size);
+        assert_eq!(transform.zoom, Some(2.));
+        assert_eq!(transform.pan, point(px(-150.), px(-75.)));
+        assert_eq!(
+            transform.pixel_at(anchor, viewport, image_size),
+            Some((300, 150))
+        );
+
+        transform.zoom_to(1000., anchor, viewport, image_size);
+        assert_eq!(transform.zoom, Some(MAX_ZOOM));
+        transform.zoom_to(0., anchor, viewport, image_size);
+        assert_eq!(transform.zoom, Some(MIN_ZOOM));
+    }
+
+    #[test]
+    fn test_pan_is_clamped() {
+        let viewport = viewport();
+
+        // An image larger than the viewport keeps covering it.
+        let image_size = size(px(400.), px(200.));
+        let mut transform = ImageTransform {
+            zoom: Some(1.),
+            pan: Point::default(),
+        };
+        transform.pan_by(point(px(500.), px(-500.)), viewport, image_size);
+        assert_eq!(transform.pan, point(px(100.), px(-50.)));
+        let bounds = transform.image_bounds(viewport, image_size);
+        assert_eq!(bounds.origin, point(px(0.), px(-100.)));
+
+        // Zooming out far enough brings the image back inside the viewport.
+        transform.zoom_to(0.25, point(px(0.), px(0.)), viewport, image_size);
+        assert_eq!(transform.pan, point(px(-50.), px(-25.)));
+        let bounds = transform.image_bounds(viewport, image_size);
+        assert_eq!(bounds.origin, point(px(0.), px(0.)));
+
+        // An image smaller than the viewport stays inside it.
+        let image_size = size(px(50.), px(20.));
+        let mut transform = ImageTransform::default();
+        transform.pan_by(point(px(-1000.), px(10.)), viewport, image_size);
+        assert_eq!(transform.pan, point(px(-75.), px(10.)));
+    }
+
+    #[test]
+    fn test_pixel_at() {
+        let viewport = viewport();
+
+        let image_size = size(px(400.), px(200.));
+        let transform = ImageTransform::default();
+        assert_eq!(
+            transform.pixel_at(point(px(0.), px(0.)), viewport, image_size),
+            Some((0, 0))
+        );
+        assert_eq!(
+            transform.pixel_at(point(px(199.9), px(99.9)), viewport, image_size),
+            Some((399, 199))
+        );
+        assert_eq!(
+            transform.pixel_at(point(px(250.), px(50.)), viewport, image_size),
+            None
+        );
+
+        // Positions in the viewport but outside of the image don't have a pixel.
+        let image_size = size(px(50.), px(20.));
+        assert_eq!(
+            transform.pixel_at(point(px(10.), px(10.)), viewport, image_size),
+            None
+        );
+        assert_eq!(
+            transform.pixel_at(point(px(76.), px(41.)), viewport, image_size),
+            Some((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_pixel_rgba() {
+        // Render images are stored as BGRA.
+        let bytes = vec![
+            10, 20, 30, 255, //
+            3, 2, 1, 4, //
+        ];
+        let image = RenderImage::new(vec![image::Frame::new(
+            image::RgbaImage::from_raw(2, 1, bytes).unwrap(),
+        )]);
+        assert_eq!(render_image_size(&image), size(px(2.), px(1.)));
+        assert_eq!(pixel_rgba(&image, 0, 0), Some([30, 20, 10, 255]));
+        assert_eq!(pixel_rgba(&image, 1, 0), Some([1, 2, 3, 4]));
+        assert_eq!(pixel_rgba(&image, 0, 1), None);
+    }
+}
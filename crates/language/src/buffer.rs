@@ -59,9 +59,10 @@ use text::operation_queue::OperationQueue;
 use text::*;
 pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
-    OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection, SelectionGoal,
-    Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint, ToPointUtf16,
-    Transaction, TransactionId, Unclipped,
+    HistorySnapshot, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection,
+    SelectionGoal, SerializedEdit, SerializedHistory, Subscription, TextDimension, TextSummary,
+    ToOffset, ToOffsetUtf16, ToPoint, ToPointUtf16, Transaction, TransactionId, Unclipped,
+    UndoTreeNode,
 };
 use theme::{ActiveTheme as _, SyntaxTheme};
 #[cfg(any(test, feature = "test-support"))]
@@ -2639,6 +2640,109 @@ impl Buffer {
         redone
     }
 
+    /// Moves to the given state of the undo tree, or to the initial state for `None`. See
+    /// [`TextBuffer::go_to_history_state`].
+    pub fn go_to_history_state(
+        &mut self,
+        target: Option<TransactionId>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        let operations = self.text.go_to_history_state(target);
+        let moved = !operations.is_empty();
+        for operation in operations {
+            self.send_operation(Operation::Buffer(operation), true, cx);
+        }
+        if moved {
+            self.did_edit(&old_version, was_dirty, cx)
+        }
+        moved
+    }
+
+    /// Restores an undo history serialized by [`TextBuffer::serialize_history`], typically in an
+    /// earlier session, so that edits made before the buffer was loaded can be undone and redone.
+    ///
+    /// The history is replayed as new transactions. If the buffer already has history of its own,
+    /// the restored history is placed before it, so it must have been serialized with the text the
+    /// buffer had before its own edits. Returns whether the history was restored.
+    pub fn restore_history(&mut self, history: &SerializedHistory, cx: &mut Context<Self>) -> bool {
+        if history.is_empty() || self.transaction_depth > 0 {
+            return false;
+        }
+        let Some(own_history) = self.text.serialize_history(usize::MAX) else {
+            return false;
+        };
+        let history = if own_history.is_empty() {
+            Cow::Borrowed(history)
+        } else {
+            Cow::Owned(SerializedHistory {
+                undo: history
+                    .undo
+                    .iter()
+                    .chain(&own_history.undo)
+                    .cloned()
+                    .collect(),
+                redo: own_history.redo,
+            })
+        };
+        let Some(edits_to_oldest_state) = history.edits_to_oldest_state(&self.text()) else {
+            return false;
+        };
+        let was_dirty = self.is_dirty();
+
+        // Rewind to the state before the oldest transaction without recording it, then replay
+        // every transaction, undoing the ones that belong on the redo stack.
+        self.text.forget_history();
+        self.start_transaction();
+        for edits in edits_to_oldest_state {
+            self.edit(edits, None, cx);
+        }
+        if let Some(transaction_id) = self.end_transaction(cx) {
+            self.finalize_last_transaction();
+            self.forget_transaction(transaction_id);
+        }
+        let mut redo_count = 0;
+        for (ix, transaction) in history.undo.iter().chain(&history.redo).enumerate() {
+            self.start_transaction();
+            self.edit(
+                transaction.edits.iter().map(|edit| {
+                    (
+                        edit.offset..edit.offset + edit.old_text.len(),
+                        edit.new_text.as_str(),
+                    )
+                }),
+                None,
+                cx,
+            );
+            if self.end_transaction(cx).is_some() {
+                self.finalize_last_transaction();
+                if ix >= history.undo.len() {
+                    redo_count += 1;
+                }
+            }
+        }
+        let current_state_version = self.version.clone();
+        for _ in 0..redo_count {
+            self.undo(cx);
+        }
+
+        // The text is back to what it was, so if it matched the file before, the replayed state
+        // it's in now is the saved one.
+        if !was_dirty {
+            let is_dirty = self.is_dirty();
+            self.saved_version = current_state_version;
+            let has_unsaved_edits = self.has_edits_since(&self.saved_version);
+            self.has_unsaved_edits
+                .set((self.version.clone(), has_unsaved_edits));
+            if self.is_dirty() != is_dirty {
+                cx.emit(BufferEvent::DirtyChanged);
+            }
+        }
+        true
+    }
+
     /// Override current completion triggers with the user-provided completion triggers.
     pub fn set_completion_triggers(
         &mut self,
@@ -357,6 +357,75 @@ fn test_edit_events(cx: &mut gpui::App) {
     );
 }
 
+#[gpui::test]
+fn test_serialize_and_restore_history(cx: &mut gpui::App) {
+    let buffer = cx.new(|cx| Buffer::local("one two three", cx));
+    let history = buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..3, "ONE")], None, cx);
+        buffer.edit([(4..7, "2"), (8..8, "and ")], None, cx);
+        buffer.edit([(0..0, "zero ")], None, cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE 2 and three");
+        buffer.serialize_history(usize::MAX).unwrap()
+    });
+    assert_eq!(history.undo.len(), 2);
+    assert_eq!(history.redo.len(), 1);
+
+    // The history only applies to the text it was serialized with.
+    let changed = cx.new(|cx| Buffer::local("ONE 2 and four", cx));
+    changed.update(cx, |buffer, cx| {
+        assert!(!buffer.restore_history(&history, cx));
+        assert!(buffer.peek_undo_stack().is_none());
+    });
+
+    let reopened = cx.new(|cx| Buffer::local("ONE 2 and three", cx));
+    reopened.update(cx, |buffer, cx| {
+        assert!(buffer.restore_history(&history, cx));
+        assert_eq!(buffer.text(), "ONE 2 and three");
+        assert!(!buffer.is_dirty());
+
+        buffer.redo(cx);
+        assert_eq!(buffer.text(), "zero ONE 2 and three");
+        buffer.undo(cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE two three");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "one two three");
+        assert!(buffer.undo(cx).is_none());
+
+        // The history doesn't lead up to the state the buffer is in now.
+        assert!(!buffer.restore_history(&history, cx));
+    });
+
+    // History that was restored after the buffer was edited comes before the buffer's own edits.
+    let edited = cx.new(|cx| Buffer::local("ONE 2 and three", cx));
+    edited.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "just ")], None, cx);
+        buffer.edit([(5..8, "one")], None, cx);
+        assert!(buffer.is_dirty());
+
+        assert!(buffer.restore_history(&history, cx));
+        assert_eq!(buffer.text(), "just one 2 and three");
+        assert!(buffer.is_dirty());
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "just ONE 2 and three");
+        buffer.undo(cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE two three");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "one two three");
+        assert!(buffer.undo(cx).is_none());
+    });
+
+    // When the history is too large, the oldest transactions are dropped first.
+    let capped = buffer
+        .read(cx)
+        .serialize_history(history.undo[1].size())
+        .unwrap();
+    assert_eq!(capped.undo, history.undo[1..]);
+    assert!(capped.redo.is_empty());
+}
+
 #[gpui::test]
 async fn test_apply_diff(cx: &mut TestAppContext) {
     let (text, offsets) = marked_text_offsets(
@@ -0,0 +1,87 @@
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use lsp::{DiagnosticSeverity, LanguageServerBinary};
+use text::PointUtf16;
+
+use crate::{LanguageName, LspAdapterDelegate};
+
+/// Information about the buffer that a [`LanguageFormatter`] or [`LanguageLinter`] is run against.
+#[derive(Debug, Clone)]
+pub struct FormatterRequest {
+    /// The absolute path of the buffer, if it is backed by a file on disk.
+    pub abs_path: Option<PathBuf>,
+    /// The name of the buffer's language.
+    pub language_name: LanguageName,
+    /// The number of columns that a tab character represents.
+    pub tab_size: u32,
+    /// Whether to indent lines using tab characters, as opposed to multiple spaces.
+    pub hard_tabs: bool,
+}
+
+/// A formatter that is run as an external process.
+///
+/// The buffer's contents are written to the process's stdin, and the formatted
+/// contents are read back from its stdout.
+#[async_trait]
+pub trait LanguageFormatter: Send + Sync + 'static {
+    /// Returns the name of this formatter, as referenced in the `formatter` setting.
+    fn name(&self) -> Arc<str>;
+
+    /// Returns the command used to format a buffer.
+    async fn command(
+        &self,
+        request: FormatterRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<LanguageServerBinary>;
+}
+
+/// The output of a linter process.
+#[derive(Debug, Clone)]
+pub struct LinterOutput {
+    /// The exit code of the process, if it exited normally.
+    pub exit_code: Option<i32>,
+    /// The data written to the process's stdout.
+    pub stdout: String,
+    /// The data written to the process's stderr.
+    pub stderr: String,
+}
+
+/// A diagnostic reported by a [`LanguageLinter`].
+#[derive(Debug, Clone)]
+pub struct LinterDiagnostic {
+    /// The range of the buffer that the diagnostic applies to.
+    pub range: Range<PointUtf16>,
+    /// The severity of the diagnostic.
+    pub severity: DiagnosticSeverity,
+    /// The diagnostic's message.
+    pub message: String,
+    /// The diagnostic's code, if any.
+    pub code: Option<String>,
+}
+
+/// A linter that is run as an external process, and whose output is turned into diagnostics.
+///
+/// The buffer's contents are written to the process's stdin.
+#[async_trait]
+pub trait LanguageLinter: Send + Sync + 'static {
+    /// Returns the name of this linter, used as the source of its diagnostics.
+    fn name(&self) -> Arc<str>;
+
+    /// Returns the command used to lint a buffer.
+    async fn command(
+        &self,
+        request: FormatterRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<LanguageServerBinary>;
+
+    /// Converts the output of the linter process into diagnostics.
+    async fn diagnostics(
+        &self,
+        request: FormatterRequest,
+        output: LinterOutput,
+    ) -> Result<Vec<LinterDiagnostic>>;
+}
@@ -1,6 +1,6 @@
 use crate::{
-    CachedLspAdapter, File, Language, LanguageConfig, LanguageId, LanguageMatcher,
-    LanguageServerName, LspAdapter, PLAIN_TEXT, ToolchainLister,
+    CachedLspAdapter, File, Language, LanguageConfig, LanguageFormatter, LanguageId,
+    LanguageLinter, LanguageMatcher, LanguageServerName, LspAdapter, PLAIN_TEXT, ToolchainLister,
     language_settings::{
         AllLanguageSettingsContent, LanguageSettingsContent, all_language_settings,
     },
@@ -117,6 +117,8 @@ struct LanguageRegistryState {
     all_lsp_adapters: HashMap<LanguageServerName, Arc<CachedLspAdapter>>,
     available_lsp_adapters:
         HashMap<LanguageServerName, Arc<dyn Fn() -> Arc<CachedLspAdapter> + 'static + Send + Sync>>,
+    formatters: HashMap<LanguageName, Vec<Arc<dyn LanguageFormatter>>>,
+    linters: HashMap<LanguageName, Vec<Arc<dyn LanguageLinter>>>,
     loading_languages: HashMap<LanguageId, Vec<oneshot::Sender<Result<Arc<Language>>>>>,
     subscription: (watch::Sender<()>, watch::Receiver<()>),
     theme: Option<Arc<Theme>>,
@@ -257,6 +259,8 @@ impl LanguageRegistry {
                 lsp_adapters: Default::default(),
                 all_lsp_adapters: Default::default(),
                 available_lsp_adapters: HashMap::default(),
+                formatters: HashMap::default(),
+                linters: HashMap::default(),
                 subscription: watch::channel(),
                 theme: Default::default(),
                 version: 0,
@@ -392,6 +396,65 @@ impl LanguageRegistry {
         cached
     }
 
+    /// Registers a formatter that can be selected for the given language.
+    pub fn register_formatter(
+        &self,
+        language_name: LanguageName,
+        formatter: Arc<dyn LanguageFormatter>,
+    ) {
+        let mut state = self.state.write();
+        let formatters = state.formatters.entry(language_name).or_default();
+        formatters.retain(|existing| existing.name() != formatter.name());
+        formatters.push(formatter);
+    }
+
+    pub fn remove_formatter(&self, language_name: &LanguageName, name: &str) {
+        if let Some(formatters) = self.state.write().formatters.get_mut(language_name) {
+            formatters.retain(|formatter| formatter.name().as_ref() != name);
+        }
+    }
+
+    /// Returns the formatter with the given name, if one is registered for the given language.
+    pub fn formatter_for_language(
+        &self,
+        language_name: &LanguageName,
+        name: &str,
+    ) -> Option<Arc<dyn LanguageFormatter>> {
+        self.state
+            .read()
+            .formatters
+            .get(language_name)?
+            .iter()
+            .find(|formatter| formatter.name().as_ref() == name)
+            .cloned()
+    }
+
+    /// Registers a linter that runs for buffers in the given language.
+    pub fn register_linter(&self, language_name: LanguageName, linter: Arc<dyn LanguageLinter>) {
+        let mut state = self.state.write();
+        let linters = state.linters.entry(language_name).or_default();
+        linters.retain(|existing| existing.name() != linter.name());
+        linters.push(linter);
+    }
+
+    pub fn remove_linter(&self, language_name: &LanguageName, name: &str) {
+        if let Some(linters) = self.state.write().linters.get_mut(language_name) {
+            linters.retain(|linter| linter.name().as_ref() != name);
+        }
+    }
+
+    pub fn linters_for_language(
+        &self,
+        language_name: &LanguageName,
+    ) -> Vec<Arc<dyn LanguageLinter>> {
+        self.state
+            .read()
+            .linters
+            .get(language_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn get_or_register_lsp_adapter(
         &self,
         language_name: LanguageName,
@@ -897,6 +897,11 @@ pub enum Formatter {
         /// The arguments to pass to the program.
         arguments: Option<Arc<[String]>>,
     },
+    /// Format code using a formatter provided by an extension.
+    Extension {
+        /// The name of the formatter, as declared in the extension's manifest.
+        name: Arc<str>,
+    },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
 }
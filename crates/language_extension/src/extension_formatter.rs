@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use extension::{Extension, ExtensionFormatterProxy};
+use language::{
+    FormatterRequest, LanguageFormatter, LanguageLinter, LanguageName, LinterDiagnostic,
+    LinterOutput, LspAdapterDelegate, PointUtf16,
+};
+use lsp::{DiagnosticSeverity, LanguageServerBinary};
+
+use crate::LanguageServerRegistryProxy;
+use crate::extension_lsp_adapter::WorktreeDelegateAdapter;
+
+impl ExtensionFormatterProxy for LanguageServerRegistryProxy {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        language: LanguageName,
+    ) {
+        self.language_registry.register_formatter(
+            language,
+            Arc::new(ExtensionFormatter {
+                extension,
+                formatter_id,
+            }),
+        );
+    }
+
+    fn remove_formatter(&self, language: &LanguageName, formatter_id: &Arc<str>) {
+        self.language_registry
+            .remove_formatter(language, formatter_id);
+    }
+
+    fn register_linter(
+        &self,
+        extension: Arc<dyn Extension>,
+        linter_id: Arc<str>,
+        language: LanguageName,
+    ) {
+        self.language_registry.register_linter(
+            language,
+            Arc::new(ExtensionLinter {
+                extension,
+                linter_id,
+            }),
+        );
+    }
+
+    fn remove_linter(&self, language: &LanguageName, linter_id: &Arc<str>) {
+        self.language_registry.remove_linter(language, linter_id);
+    }
+}
+
+struct ExtensionFormatter {
+    extension: Arc<dyn Extension>,
+    formatter_id: Arc<str>,
+}
+
+#[async_trait]
+impl LanguageFormatter for ExtensionFormatter {
+    fn name(&self) -> Arc<str> {
+        self.formatter_id.clone()
+    }
+
+    async fn command(
+        &self,
+        request: FormatterRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<LanguageServerBinary> {
+        let delegate = Arc::new(WorktreeDelegateAdapter(delegate)) as _;
+        let command = self
+            .extension
+            .formatter_command(
+                self.formatter_id.clone(),
+                to_format_request(request),
+                delegate,
+            )
+            .await?;
+        Ok(binary_from_command(self.extension.as_ref(), command))
+    }
+}
+
+struct ExtensionLinter {
+    extension: Arc<dyn Extension>,
+    linter_id: Arc<str>,
+}
+
+#[async_trait]
+impl LanguageLinter for ExtensionLinter {
+    fn name(&self) -> Arc<str> {
+        self.linter_id.clone()
+    }
+
+    async fn command(
+        &self,
+        request: FormatterRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<LanguageServerBinary> {
+        let delegate = Arc::new(WorktreeDelegateAdapter(delegate)) as _;
+        let command = self
+            .extension
+            .linter_command(self.linter_id.clone(), to_format_request(request), delegate)
+            .await?;
+        Ok(binary_from_command(self.extension.as_ref(), command))
+    }
+
+    async fn diagnostics(
+        &self,
+        request: FormatterRequest,
+        output: LinterOutput,
+    ) -> Result<Vec<LinterDiagnostic>> {
+        let output = extension::ProcessOutput {
+            status: output.exit_code,
+            stdout: output.stdout.into_bytes(),
+            stderr: output.stderr.into_bytes(),
+        };
+        let diagnostics = self
+            .extension
+            .linter_diagnostics(self.linter_id.clone(), to_format_request(request), output)
+            .await?;
+
+        Ok(diagnostics
+            .into_iter()
+            .map(|diagnostic| LinterDiagnostic {
+                range: PointUtf16::new(diagnostic.start.line, diagnostic.start.column)
+                    ..PointUtf16::new(diagnostic.end.line, diagnostic.end.column),
+                severity: match diagnostic.severity {
+                    extension::DiagnosticSeverity::Error => DiagnosticSeverity::ERROR,
+                    extension::DiagnosticSeverity::Warning => DiagnosticSeverity::WARNING,
+                    extension::DiagnosticSeverity::Information => DiagnosticSeverity::INFORMATION,
+                    extension::DiagnosticSeverity::Hint => DiagnosticSeverity::HINT,
+                },
+                message: diagnostic.message,
+                code: diagnostic.code,
+            })
+            .collect())
+    }
+}
+
+fn to_format_request(request: FormatterRequest) -> extension::FormatRequest {
+    extension::FormatRequest {
+        path: request
+            .abs_path
+            .map(|path| path.to_string_lossy().into_owned()),
+        language_name: request.language_name.to_string(),
+        tab_size: request.tab_size,
+        hard_tabs: request.hard_tabs,
+    }
+}
+
+/// Resolves the command returned by an extension, treating relative paths as
+/// relative to the extension's working directory.
+fn binary_from_command(
+    extension: &dyn Extension,
+    command: extension::Command,
+) -> LanguageServerBinary {
+    LanguageServerBinary {
+        path: extension.path_from_extension(command.command.as_ref()),
+        arguments: command.args.into_iter().map(Into::into).collect(),
+        env: Some(command.env.into_iter().collect()),
+    }
+}
@@ -1,3 +1,4 @@
+mod extension_formatter;
 mod extension_lsp_adapter;
 
 use std::path::PathBuf;
@@ -14,7 +15,8 @@ pub fn init(
     let language_server_registry_proxy = LanguageServerRegistryProxy { language_registry };
     extension_host_proxy.register_grammar_proxy(language_server_registry_proxy.clone());
     extension_host_proxy.register_language_proxy(language_server_registry_proxy.clone());
-    extension_host_proxy.register_language_server_proxy(language_server_registry_proxy);
+    extension_host_proxy.register_language_server_proxy(language_server_registry_proxy.clone());
+    extension_host_proxy.register_formatter_proxy(language_server_registry_proxy);
 }
 
 #[derive(Clone)]
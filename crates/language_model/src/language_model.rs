@@ -8,7 +8,7 @@ mod telemetry;
 #[cfg(any(test, feature = "test-support"))]
 pub mod fake_provider;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use client::Client;
 use futures::FutureExt;
 use futures::{StreamExt, future::BoxFuture, stream::BoxStream};
@@ -268,6 +268,56 @@ pub trait LanguageModel: Send + Sync {
         .boxed()
     }
 
+    /// Completes a request that has a `response_format`, returning the response once it has
+    /// been parsed and validated against the format's schema.
+    fn complete_structured(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<'static, Result<serde_json::Value>> {
+        let response_format = request.response_format.clone();
+        let events = self.stream_completion(request, cx);
+
+        async move {
+            let response_format =
+                response_format.context("structured completions require a response format")?;
+            let mut events = events.await?;
+            let mut text = String::new();
+            let mut tool_input = None;
+            while let Some(event) = events.next().await {
+                match event? {
+                    LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+                    // Providers that constrain responses by forcing a tool respond with its input.
+                    LanguageModelCompletionEvent::ToolUse(tool_use)
+                        if tool_use.name.as_ref() == response_format.name =>
+                    {
+                        tool_input = Some(tool_use.input);
+                    }
+                    _ => {}
+                }
+            }
+
+            let response = match tool_input {
+                Some(input) => input,
+                None => {
+                    // Some models wrap JSON in a code block even when asked not to.
+                    let text = text.trim();
+                    let text = text
+                        .strip_prefix("```json")
+                        .or_else(|| text.strip_prefix("```"))
+                        .and_then(|text| text.strip_suffix("```"))
+                        .unwrap_or(text);
+                    serde_json::from_str(text).context("the response isn't valid JSON")?
+                }
+            };
+            response_format
+                .validate(&response)
+                .context("the response doesn't match the schema")?;
+            Ok(response)
+        }
+        .boxed()
+    }
+
     fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
         None
     }
@@ -394,3 +444,73 @@ impl From<String> for LanguageModelProviderName {
         Self(SharedString::from(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_provider::FakeLanguageModel;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    fn structured_request() -> LanguageModelRequest {
+        LanguageModelRequest {
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: json!({
+                    "type": "object",
+                    "properties": { "title": { "type": "string" } },
+                    "required": ["title"],
+                }),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[gpui::test]
+    async fn test_complete_structured(cx: &mut TestAppContext) {
+        let model = FakeLanguageModel::default();
+
+        // JSON wrapped in a code block is unwrapped.
+        let response = model.complete_structured(structured_request(), &cx.to_async());
+        model.stream_last_completion_response("```json\n{\"title\": ".into());
+        model.stream_last_completion_response("\"Crash\"}\n```".into());
+        model.end_last_completion_stream();
+        assert_eq!(response.await.unwrap(), json!({ "title": "Crash" }));
+
+        // Providers that force a tool respond with its input, and any text is ignored.
+        let response = model.complete_structured(structured_request(), &cx.to_async());
+        model.stream_last_completion_response("Here's the summary.".into());
+        model.send_last_completion_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: "tool_1".into(),
+                name: "summary".into(),
+                input: json!({ "title": "Hang" }),
+            },
+        ));
+        model.end_last_completion_stream();
+        assert_eq!(response.await.unwrap(), json!({ "title": "Hang" }));
+
+        let response = model.complete_structured(structured_request(), &cx.to_async());
+        model.stream_last_completion_response("{\"name\": \"Crash\"}".into());
+        model.end_last_completion_stream();
+        assert_eq!(
+            response.await.unwrap_err().to_string(),
+            "the response doesn't match the schema"
+        );
+
+        let response = model.complete_structured(structured_request(), &cx.to_async());
+        model.stream_last_completion_response("The title is Crash.".into());
+        model.end_last_completion_stream();
+        assert_eq!(
+            response.await.unwrap_err().to_string(),
+            "the response isn't valid JSON"
+        );
+
+        let response = model.complete_structured(LanguageModelRequest::default(), &cx.to_async());
+        assert_eq!(
+            response.await.unwrap_err().to_string(),
+            "structured completions require a response format"
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::role::Role;
 use crate::{LanguageModelToolUse, LanguageModelToolUseId};
+use anyhow::{Result, anyhow};
 use base64::write::EncoderWriter;
 use gpui::{
     App, AppContext as _, DevicePixels, Image, ObjectFit, RenderImage, SharedString, Size, Task,
@@ -242,6 +243,211 @@ pub struct LanguageModelRequest {
     pub tools: Vec<LanguageModelRequestTool>,
     pub stop: Vec<String>,
     pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate. Defaults to the model's maximum.
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    /// How much the model should reason before it responds, for models that support it.
+    #[serde(default)]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// A seed for sampling, for providers that support deterministic sampling.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Constrains the response to a JSON value matching a schema.
+    #[serde(default)]
+    pub response_format: Option<LanguageModelResponseFormat>,
+}
+
+impl LanguageModelRequest {
+    /// Returns the number of tokens to ask the provider for: the request's `max_tokens`, capped
+    /// at the model's maximum output, if it has one.
+    pub fn max_tokens_for_model(&self, max_output_tokens: Option<u32>) -> Option<u32> {
+        match (self.max_tokens, max_output_tokens) {
+            (Some(max_tokens), Some(max_output_tokens)) => Some(max_tokens.min(max_output_tokens)),
+            (max_tokens, max_output_tokens) => max_tokens.or(max_output_tokens),
+        }
+    }
+
+    /// Asks for the response format in the last user message, for providers that can't
+    /// constrain responses to a schema themselves.
+    pub fn add_response_format_instructions(&mut self) {
+        let Some(response_format) = self.response_format.as_ref() else {
+            return;
+        };
+        let instructions = MessageContent::Text(response_format.instructions());
+        match self.messages.last_mut() {
+            Some(message) if message.role == Role::User => message.content.push(instructions),
+            _ => self.messages.push(LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![instructions],
+                cache: false,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+}
+
+/// A JSON schema the response must match.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LanguageModelResponseFormat {
+    /// The name of the schema, which providers that constrain responses with a tool use as the
+    /// name of the tool.
+    pub name: String,
+    pub description: Option<String>,
+    pub schema: serde_json::Value,
+}
+
+impl LanguageModelResponseFormat {
+    /// Returns a tool whose input is the response, for providers that constrain responses by
+    /// forcing the model to use a tool.
+    pub fn as_tool(&self) -> LanguageModelRequestTool {
+        LanguageModelRequestTool {
+            name: self.name.clone(),
+            description: self
+                .description
+                .clone()
+                .unwrap_or_else(|| "Respond with the given input.".to_string()),
+            input_schema: self.schema.clone(),
+        }
+    }
+
+    fn instructions(&self) -> String {
+        format!(
+            "Respond only with a JSON value, without any other text or Markdown, that matches this JSON schema:\n\n{}",
+            self.schema
+        )
+    }
+
+    /// Checks that the value matches the schema.
+    ///
+    /// This supports the subset of JSON schema that providers accept for structured output:
+    /// `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`,
+    /// `anyOf`, `oneOf` and `allOf`.
+    pub fn validate(&self, value: &serde_json::Value) -> Result<()> {
+        validate_json(&self.schema, value, "")
+    }
+}
+
+fn validate_json(schema: &serde_json::Value, value: &serde_json::Value, path: &str) -> Result<()> {
+    use serde_json::Value;
+
+    let Value::Object(schema) = schema else {
+        // `true` and `{}` accept any value, and `false` accepts none.
+        return match schema {
+            Value::Bool(false) => Err(anyhow!("unexpected value at `{path}`")),
+            _ => Ok(()),
+        };
+    };
+    let location = if path.is_empty() { "/" } else { path };
+
+    if let Some(types) = schema.get("type") {
+        let matches_type = |ty: &Value| match ty.as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("number") => value.is_number(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("boolean") => value.is_boolean(),
+            Some("null") => value.is_null(),
+            _ => true,
+        };
+        let matches = match types {
+            Value::Array(types) => types.iter().any(matches_type),
+            ty => matches_type(ty),
+        };
+        if !matches {
+            return Err(anyhow!(
+                "expected `{location}` to be of type {types}, got {value}"
+            ));
+        }
+    }
+
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if !values.contains(value) {
+            return Err(anyhow!(
+                "expected `{location}` to be one of {values:?}, got {value}"
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            return Err(anyhow!(
+                "expected `{location}` to be {expected}, got {value}"
+            ));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    return Err(anyhow!("missing required property `{path}/{name}`"));
+                }
+            }
+        }
+        for (name, property_value) in object {
+            let property_path = format!("{path}/{name}");
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property_schema) => {
+                    validate_json(property_schema, property_value, &property_path)?
+                }
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(anyhow!("unexpected property `{property_path}`"));
+                    }
+                    Some(additional_schema) => {
+                        validate_json(additional_schema, property_value, &property_path)?
+                    }
+                    None => {}
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (ix, item) in items.iter().enumerate() {
+            validate_json(item_schema, item, &format!("{path}/{ix}"))?;
+        }
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        for schema in schemas {
+            validate_json(schema, value, path)?;
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(schemas)) = schema.get(key) {
+            if !schemas
+                .iter()
+                .any(|schema| validate_json(schema, value, path).is_ok())
+            {
+                return Err(anyhow!(
+                    "expected `{location}` to match one of the schemas in `{key}`, got {value}"
+                ));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
@@ -249,3 +455,53 @@ pub struct LanguageModelResponseMessage {
     pub role: Option<Role>,
     pub content: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_response_format() {
+        let response_format = LanguageModelResponseFormat {
+            name: "summary".into(),
+            description: None,
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "severity": { "enum": ["low", "high"] },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                },
+                "required": ["title"],
+                "additionalProperties": false,
+            }),
+        };
+
+        response_format
+            .validate(&json!({ "title": "Crash", "severity": "high", "tags": ["ui"] }))
+            .unwrap();
+        assert_eq!(
+            response_format
+                .validate(&json!({ "severity": "low" }))
+                .unwrap_err()
+                .to_string(),
+            "missing required property `/title`"
+        );
+        assert_eq!(
+            response_format
+                .validate(&json!({ "title": "Crash", "tags": ["ui", 1] }))
+                .unwrap_err()
+                .to_string(),
+            "expected `/tags/1` to be of type \"string\", got 1"
+        );
+        assert_eq!(
+            response_format
+                .validate(&json!({ "title": "Crash", "owner": "me" }))
+                .unwrap_err()
+                .to_string(),
+            "unexpected property `/owner`"
+        );
+    }
+}
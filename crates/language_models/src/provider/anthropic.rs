@@ -15,7 +15,7 @@ use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCacheConfiguration, LanguageModelId,
     LanguageModelKnownError, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest, MessageContent,
-    RateLimiter, Role,
+    RateLimiter, ReasoningEffort, Role,
 };
 use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
 use schemars::JsonSchema;
@@ -484,6 +484,9 @@ pub fn into_anthropic(
     max_output_tokens: u32,
     mode: AnthropicModelMode,
 ) -> anthropic::Request {
+    let max_tokens = request
+        .max_tokens_for_model(Some(max_output_tokens))
+        .unwrap_or(max_output_tokens);
     let mut new_messages: Vec<anthropic::Message> = Vec::new();
     let mut system_message = String::new();
 
@@ -566,22 +569,49 @@ pub fn into_anthropic(
         }
     }
 
+    let mut tools = request.tools;
+    let mut tool_choice = None;
+    let mut thinking = match mode {
+        AnthropicModelMode::Thinking { budget_tokens } => match request
+            .reasoning_effort
+            .map(thinking_budget_for_effort)
+            .or(budget_tokens)
+        {
+            Some(budget_tokens) => {
+                thinking_budget_within(budget_tokens, max_tokens).map(|budget_tokens| {
+                    anthropic::Thinking::Enabled {
+                        budget_tokens: Some(budget_tokens),
+                    }
+                })
+            }
+            None => Some(anthropic::Thinking::Enabled {
+                budget_tokens: None,
+            }),
+        },
+        AnthropicModelMode::Default => None,
+    };
+    // Anthropic doesn't support response schemas, so the model is made to respond with the
+    // input of a tool whose schema is the response's.
+    if let Some(response_format) = request.response_format {
+        tools.push(response_format.as_tool());
+        tool_choice = Some(anthropic::ToolChoice::Tool {
+            name: response_format.name,
+        });
+        // Thinking can't be used when a tool is forced.
+        thinking = None;
+    }
+
     anthropic::Request {
         model,
         messages: new_messages,
-        max_tokens: max_output_tokens,
+        max_tokens,
         system: if system_message.is_empty() {
             None
         } else {
             Some(anthropic::StringOrContents::String(system_message))
         },
-        thinking: if let AnthropicModelMode::Thinking { budget_tokens } = mode {
-            Some(anthropic::Thinking::Enabled { budget_tokens })
-        } else {
-            None
-        },
-        tools: request
-            .tools
+        thinking,
+        tools: tools
             .into_iter()
             .map(|tool| anthropic::Tool {
                 name: tool.name,
@@ -589,12 +619,44 @@ pub fn into_anthropic(
                 input_schema: tool.input_schema,
             })
             .collect(),
-        tool_choice: None,
+        tool_choice,
         metadata: None,
         stop_sequences: Vec::new(),
         temperature: request.temperature.or(Some(default_temperature)),
         top_k: None,
-        top_p: None,
+        top_p: top_p_for_thinking(request.top_p, thinking.is_some()),
+    }
+}
+
+/// The smallest thinking budget Anthropic accepts, in tokens.
+const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// The smallest `top_p` Anthropic accepts when thinking is enabled.
+const MIN_THINKING_TOP_P: f32 = 0.95;
+
+/// Caps a thinking budget to half of `max_tokens`, which thinking counts towards, so that the
+/// rest is left for the response. Returns `None` if that leaves less than the minimum budget, in
+/// which case the model can't think.
+pub(crate) fn thinking_budget_within(budget_tokens: u32, max_tokens: u32) -> Option<u32> {
+    let budget_tokens = budget_tokens.min(max_tokens / 2);
+    (budget_tokens >= MIN_THINKING_BUDGET).then_some(budget_tokens)
+}
+
+/// Raises `top_p` to the smallest value Anthropic accepts when thinking is enabled.
+pub(crate) fn top_p_for_thinking(top_p: Option<f32>, thinking: bool) -> Option<f32> {
+    if thinking {
+        top_p.map(|top_p| top_p.max(MIN_THINKING_TOP_P))
+    } else {
+        top_p
+    }
+}
+
+/// The thinking budget used for a reasoning effort, in tokens.
+pub(crate) fn thinking_budget_for_effort(effort: ReasoningEffort) -> u32 {
+    match effort {
+        ReasoningEffort::Low => 1024,
+        ReasoningEffort::Medium => 8192,
+        ReasoningEffort::High => 24576,
     }
 }
 
@@ -975,3 +1037,84 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_into_anthropic() {
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(16384),
+            top_p: Some(0.5),
+            reasoning_effort: Some(ReasoningEffort::High),
+            ..Default::default()
+        };
+        let thinking_mode = AnthropicModelMode::Thinking {
+            budget_tokens: Some(4096),
+        };
+        let into_anthropic = |request: LanguageModelRequest| {
+            serde_json::to_value(into_anthropic(
+                request,
+                "claude-3-7-sonnet-latest".into(),
+                1.0,
+                8192,
+                thinking_mode.clone(),
+            ))
+            .unwrap()
+        };
+
+        // The effort's budget is capped to leave half of the model's maximum output for the
+        // response, and `top_p` is raised to what thinking allows.
+        let anthropic_request = into_anthropic(request.clone());
+        assert_eq!(anthropic_request["max_tokens"], 8192);
+        assert_eq!(anthropic_request["top_p"], json!(MIN_THINKING_TOP_P));
+        assert_eq!(
+            anthropic_request["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 4096 })
+        );
+
+        let anthropic_request = into_anthropic(LanguageModelRequest {
+            max_tokens: Some(4096),
+            reasoning_effort: Some(ReasoningEffort::Low),
+            ..request.clone()
+        });
+        assert_eq!(anthropic_request["max_tokens"], 4096);
+        assert_eq!(
+            anthropic_request["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 1024 })
+        );
+
+        // Below the minimum budget, the model can't think.
+        let anthropic_request = into_anthropic(LanguageModelRequest {
+            max_tokens: Some(2047),
+            ..request.clone()
+        });
+        assert_eq!(anthropic_request["max_tokens"], 2047);
+        assert_eq!(anthropic_request["top_p"], 0.5);
+        assert!(anthropic_request.get("thinking").is_none());
+
+        // Structured responses are the input of a forced tool, which rules out thinking.
+        let anthropic_request = into_anthropic(LanguageModelRequest {
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: json!({ "type": "object" }),
+            }),
+            ..request
+        });
+        assert_eq!(anthropic_request["tools"][0]["name"], "summary");
+        assert_eq!(
+            anthropic_request["tool_choice"],
+            json!({ "type": "tool", "name": "summary" })
+        );
+        assert!(anthropic_request.get("thinking").is_none());
+    }
+}
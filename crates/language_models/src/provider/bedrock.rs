@@ -16,9 +16,10 @@ use bedrock::bedrock_client::types::{
 };
 use bedrock::{
     BedrockAutoToolChoice, BedrockError, BedrockInnerContent, BedrockMessage, BedrockModelMode,
-    BedrockStreamingResponse, BedrockTool, BedrockToolChoice, BedrockToolConfig,
-    BedrockToolInputSchema, BedrockToolResultBlock, BedrockToolResultContentBlock,
-    BedrockToolResultStatus, BedrockToolSpec, BedrockToolUseBlock, Model, value_to_aws_document,
+    BedrockSpecificToolChoice, BedrockStreamingResponse, BedrockTool, BedrockToolChoice,
+    BedrockToolConfig, BedrockToolInputSchema, BedrockToolResultBlock,
+    BedrockToolResultContentBlock, BedrockToolResultStatus, BedrockToolSpec, BedrockToolUseBlock,
+    Model, value_to_aws_document,
 };
 use collections::{BTreeMap, HashMap};
 use credentials_provider::CredentialsProvider;
@@ -48,6 +49,9 @@ use ui::{Icon, IconName, List, Tooltip, prelude::*};
 use util::{ResultExt, default};
 
 use crate::AllLanguageModelSettings;
+use crate::provider::anthropic::{
+    thinking_budget_for_effort, thinking_budget_within, top_p_for_thinking,
+};
 
 const PROVIDER_ID: &str = "amazon-bedrock";
 const PROVIDER_NAME: &str = "Amazon Bedrock";
@@ -600,8 +604,11 @@ pub fn into_bedrock(
     model: String,
     default_temperature: f32,
     max_output_tokens: u32,
-    mode: BedrockModelMode,
+    mut mode: BedrockModelMode,
 ) -> Result<bedrock::Request> {
+    let max_tokens = request
+        .max_tokens_for_model(Some(max_output_tokens))
+        .unwrap_or(max_output_tokens);
     let mut new_messages: Vec<BedrockMessage> = Vec::new();
     let mut system_message = String::new();
 
@@ -680,8 +687,38 @@ pub fn into_bedrock(
         }
     }
 
-    let tool_spec: Vec<BedrockTool> = request
-        .tools
+    if let BedrockModelMode::Thinking { budget_tokens } = mode {
+        let budget_tokens = request
+            .reasoning_effort
+            .map(thinking_budget_for_effort)
+            .or(budget_tokens.map(|budget_tokens| budget_tokens.try_into().unwrap_or(u32::MAX)));
+        if let Some(budget_tokens) = budget_tokens {
+            mode = match thinking_budget_within(budget_tokens, max_tokens) {
+                Some(budget_tokens) => BedrockModelMode::Thinking {
+                    budget_tokens: Some(budget_tokens.into()),
+                },
+                None => BedrockModelMode::Default,
+            };
+        }
+    }
+
+    let mut tools = request.tools;
+    let mut tool_choice = BedrockToolChoice::Auto(BedrockAutoToolChoice::builder().build());
+    // Bedrock doesn't support response schemas, so the model is made to respond with the input
+    // of a tool whose schema is the response's.
+    if let Some(response_format) = request.response_format {
+        tools.push(response_format.as_tool());
+        tool_choice = BedrockToolChoice::Tool(
+            BedrockSpecificToolChoice::builder()
+                .name(response_format.name)
+                .build()
+                .context("failed to build Bedrock tool choice")?,
+        );
+        // Thinking can't be used when a tool is forced.
+        mode = BedrockModelMode::Default;
+    }
+
+    let tool_spec: Vec<BedrockTool> = tools
         .iter()
         .filter_map(|tool| {
             Some(BedrockTool::ToolSpec(
@@ -699,15 +736,14 @@ pub fn into_bedrock(
 
     let tool_config: BedrockToolConfig = BedrockToolConfig::builder()
         .set_tools(Some(tool_spec))
-        .tool_choice(BedrockToolChoice::Auto(
-            BedrockAutoToolChoice::builder().build(),
-        ))
+        .tool_choice(tool_choice)
         .build()?;
 
+    let thinking = matches!(mode, BedrockModelMode::Thinking { .. });
     Ok(bedrock::Request {
         model,
         messages: new_messages,
-        max_tokens: max_output_tokens,
+        max_tokens,
         system: Some(system_message),
         tools: Some(tool_config),
         thinking: if let BedrockModelMode::Thinking { budget_tokens } = mode {
@@ -719,7 +755,7 @@ pub fn into_bedrock(
         stop_sequences: Vec::new(),
         temperature: request.temperature.or(Some(default_temperature)),
         top_k: None,
-        top_p: None,
+        top_p: top_p_for_thinking(request.top_p, thinking),
     })
 }
 
@@ -1371,3 +1407,72 @@ impl ConfigurationView {
             .into_any_element()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{
+        LanguageModelRequestMessage, LanguageModelResponseFormat, ReasoningEffort,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_into_bedrock() {
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(16384),
+            top_p: Some(0.5),
+            reasoning_effort: Some(ReasoningEffort::High),
+            ..Default::default()
+        };
+        let into_bedrock = |request: LanguageModelRequest| {
+            into_bedrock(
+                request,
+                "anthropic.claude-3-7-sonnet".into(),
+                1.0,
+                8192,
+                BedrockModelMode::Thinking {
+                    budget_tokens: Some(4096),
+                },
+            )
+            .unwrap()
+        };
+
+        let bedrock_request = into_bedrock(request.clone());
+        assert_eq!(bedrock_request.max_tokens, 8192);
+        assert_eq!(bedrock_request.top_p, Some(0.95));
+        assert!(matches!(
+            bedrock_request.thinking,
+            Some(bedrock::Thinking::Enabled {
+                budget_tokens: Some(4096)
+            })
+        ));
+
+        let bedrock_request = into_bedrock(LanguageModelRequest {
+            max_tokens: Some(2047),
+            ..request.clone()
+        });
+        assert_eq!(bedrock_request.max_tokens, 2047);
+        assert_eq!(bedrock_request.top_p, Some(0.5));
+        assert!(bedrock_request.thinking.is_none());
+
+        let bedrock_request = into_bedrock(LanguageModelRequest {
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: json!({ "type": "object" }),
+            }),
+            ..request
+        });
+        assert!(bedrock_request.thinking.is_none());
+        let tool_config = bedrock_request.tools.unwrap();
+        assert!(matches!(
+            tool_config.tool_choice(),
+            Some(BedrockToolChoice::Tool(tool_choice)) if tool_choice.name() == "summary"
+        ));
+    }
+}
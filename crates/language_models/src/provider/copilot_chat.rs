@@ -399,9 +399,11 @@ pub fn map_to_language_model_completion_events(
 impl CopilotChatLanguageModel {
     pub fn to_copilot_chat_request(
         &self,
-        request: LanguageModelRequest,
+        mut request: LanguageModelRequest,
     ) -> Result<CopilotChatRequest> {
         let model = self.model.clone();
+        // Copilot Chat doesn't support response schemas, so the schema is given in the prompt.
+        request.add_response_format_instructions();
 
         let mut request_messages: Vec<LanguageModelRequestMessage> = Vec::new();
         for message in request.messages {
@@ -494,7 +496,7 @@ impl CopilotChatLanguageModel {
             intent: true,
             n: 1,
             stream: model.uses_streaming(),
-            temperature: 0.1,
+            temperature: request.temperature.unwrap_or(0.1),
             model,
             messages,
             tools,
@@ -620,3 +622,42 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::LanguageModelResponseFormat;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_copilot_chat_request() {
+        let model = CopilotChatLanguageModel {
+            model: CopilotChatModel::default(),
+            request_limiter: RateLimiter::new(4),
+        };
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            temperature: Some(0.5),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: json!({ "type": "object" }),
+            }),
+            ..Default::default()
+        };
+
+        let copilot_request =
+            serde_json::to_value(model.to_copilot_chat_request(request).unwrap()).unwrap();
+        assert_eq!(copilot_request["temperature"], 0.5);
+        // Copilot Chat can't constrain responses, so the schema is given in the prompt.
+        let messages = copilot_request["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        let content = messages[0]["content"].as_str().unwrap();
+        assert!(content.starts_with("Summarize the crash."));
+        assert!(content.contains(r#"{"type":"object"}"#));
+    }
+}
@@ -359,11 +359,18 @@ impl LanguageModel for DeepSeekLanguageModel {
 }
 
 pub fn into_deepseek(
-    request: LanguageModelRequest,
+    mut request: LanguageModelRequest,
     model: String,
     max_output_tokens: Option<u32>,
 ) -> deepseek::Request {
+    let max_tokens = request.max_tokens_for_model(max_output_tokens);
     let is_reasoner = model == "deepseek-reasoner";
+    // DeepSeek only supports JSON output without a schema, so the schema is given in the prompt.
+    request.add_response_format_instructions();
+    let response_format = request
+        .response_format
+        .take()
+        .map(|_| deepseek::ResponseFormat::JsonObject);
 
     let len = request.messages.len();
     let merged_messages =
@@ -424,13 +431,14 @@ pub fn into_deepseek(
         model,
         messages: merged_messages,
         stream: true,
-        max_tokens: max_output_tokens,
+        max_tokens,
         temperature: if is_reasoner {
             None
         } else {
             request.temperature
         },
-        response_format: None,
+        top_p: if is_reasoner { None } else { request.top_p },
+        response_format,
         tools: request
             .tools
             .into_iter()
@@ -619,3 +627,54 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_into_deepseek() {
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(4096),
+            top_p: Some(0.5),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: json!({ "type": "object" }),
+            }),
+            ..Default::default()
+        };
+
+        let deepseek_request = serde_json::to_value(into_deepseek(
+            request.clone(),
+            "deepseek-chat".into(),
+            Some(1000),
+        ))
+        .unwrap();
+        assert_eq!(deepseek_request["max_tokens"], 1000);
+        assert_eq!(deepseek_request["top_p"], 0.5);
+        assert_eq!(
+            deepseek_request["response_format"],
+            json!({ "type": "json_object" })
+        );
+        // The schema is given in the prompt instead.
+        let messages = deepseek_request["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        let content = messages[0]["content"].as_str().unwrap();
+        assert!(content.starts_with("Summarize the crash."));
+        assert!(content.contains(r#"{"type":"object"}"#));
+
+        // The reasoner doesn't support sampling parameters.
+        let deepseek_request =
+            serde_json::to_value(into_deepseek(request, "deepseek-reasoner".into(), None)).unwrap();
+        assert_eq!(deepseek_request["max_tokens"], 4096);
+        assert!(deepseek_request.get("top_p").is_none());
+    }
+}
@@ -430,10 +430,18 @@ pub fn into_google(
         generation_config: Some(google_ai::GenerationConfig {
             candidate_count: Some(1),
             stop_sequences: Some(request.stop),
-            max_output_tokens: None,
+            max_output_tokens: request.max_tokens.map(|max_tokens| max_tokens as usize),
             temperature: request.temperature.map(|t| t as f64).or(Some(1.0)),
-            top_p: None,
+            top_p: request.top_p.map(|top_p| top_p as f64),
             top_k: None,
+            seed: request.seed,
+            response_mime_type: request
+                .response_format
+                .as_ref()
+                .map(|_| "application/json".to_string()),
+            response_schema: request
+                .response_format
+                .map(|response_format| into_google_schema(response_format.schema)),
         }),
         safety_settings: None,
         tools: (request.tools.len() > 0).then(|| {
@@ -453,6 +461,89 @@ pub fn into_google(
     }
 }
 
+/// Converts a JSON schema into the subset of OpenAPI schemas Google AI accepts for responses,
+/// see https://ai.google.dev/api/caching#Schema. Keywords it doesn't support, such as
+/// `additionalProperties`, are dropped, so the response is validated against the original schema
+/// once it's complete.
+fn into_google_schema(schema: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    const SUPPORTED_KEYS: [&str; 18] = [
+        "type",
+        "title",
+        "description",
+        "nullable",
+        "enum",
+        "maxItems",
+        "minItems",
+        "properties",
+        "required",
+        "minProperties",
+        "maxProperties",
+        "minLength",
+        "maxLength",
+        "pattern",
+        "anyOf",
+        "items",
+        "minimum",
+        "maximum",
+    ];
+
+    // `true` accepts any value, which an empty schema does too.
+    let Value::Object(mut schema) = schema else {
+        return Value::Object(Default::default());
+    };
+
+    if let Some(value) = schema.remove("const") {
+        schema
+            .entry("enum")
+            .or_insert_with(|| Value::Array(vec![value]));
+    }
+    if let Some(schemas) = schema.remove("oneOf") {
+        schema.entry("anyOf").or_insert(schemas);
+    }
+    // Nullable types are a flag rather than a `null` type.
+    if let Some(Value::Array(types)) = schema.remove("type") {
+        let (nulls, types): (Vec<_>, Vec<_>) = types
+            .into_iter()
+            .partition(|ty| ty.as_str() == Some("null"));
+        if !nulls.is_empty() {
+            schema.insert("nullable".into(), Value::Bool(true));
+        }
+        if let [ty] = types.as_slice() {
+            schema.insert("type".into(), ty.clone());
+        } else if !types.is_empty() {
+            schema.insert(
+                "anyOf".into(),
+                types
+                    .into_iter()
+                    .map(|ty| serde_json::json!({ "type": ty }))
+                    .collect(),
+            );
+        }
+    }
+    schema.retain(|key, _| SUPPORTED_KEYS.contains(&key.as_str()));
+
+    for (key, value) in schema.iter_mut() {
+        match (key.as_str(), value) {
+            ("properties", Value::Object(properties)) => {
+                for property in properties.values_mut() {
+                    *property = into_google_schema(property.take());
+                }
+            }
+            ("anyOf", Value::Array(schemas)) => {
+                for schema in schemas {
+                    *schema = into_google_schema(schema.take());
+                }
+            }
+            ("items", value) => *value = into_google_schema(value.take()),
+            _ => {}
+        }
+    }
+
+    Value::Object(schema)
+}
+
 pub fn map_to_language_model_completion_events(
     events: Pin<Box<dyn Send + Stream<Item = Result<GenerateContentResponse>>>>,
 ) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
@@ -780,3 +871,88 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_into_google() {
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(4096),
+            top_p: Some(0.5),
+            seed: Some(7),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: json!({
+                    "type": "object",
+                    "properties": { "title": { "type": "string" } },
+                    "additionalProperties": false,
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let google_request =
+            serde_json::to_value(into_google(request, "gemini-2.0-flash".into())).unwrap();
+        let generation_config = &google_request["generationConfig"];
+        assert_eq!(generation_config["maxOutputTokens"], 4096);
+        assert_eq!(generation_config["topP"], 0.5);
+        assert_eq!(generation_config["seed"], 7);
+        assert_eq!(generation_config["responseMimeType"], "application/json");
+        assert_eq!(
+            generation_config["responseSchema"],
+            json!({
+                "type": "object",
+                "properties": { "title": { "type": "string" } },
+            })
+        );
+    }
+
+    #[test]
+    fn test_into_google_schema() {
+        assert_eq!(
+            into_google_schema(json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "kind": { "const": "crash" },
+                    "owner": { "type": ["string", "null"], "format": "email" },
+                    "id": { "type": ["string", "integer"] },
+                    "severity": {
+                        "oneOf": [{ "enum": ["low", "high"] }, { "type": "integer" }],
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "string", "default": "ui" },
+                    },
+                    "details": true,
+                },
+                "required": ["kind"],
+                "additionalProperties": false,
+            })),
+            json!({
+                "type": "object",
+                "properties": {
+                    "kind": { "enum": ["crash"] },
+                    "owner": { "type": "string", "nullable": true },
+                    "id": { "anyOf": [{ "type": "string" }, { "type": "integer" }] },
+                    "severity": {
+                        "anyOf": [{ "enum": ["low", "high"] }, { "type": "integer" }],
+                    },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "details": {},
+                },
+                "required": ["kind"],
+            })
+        );
+    }
+}
@@ -9,8 +9,8 @@ use language_model::{
     LanguageModelRequest, RateLimiter, Role,
 };
 use lmstudio::{
-    ChatCompletionRequest, ChatMessage, ModelType, get_models, preload_model,
-    stream_chat_completion,
+    ChatCompletionRequest, ChatMessage, ModelType, ResponseFormat, ResponseJsonSchema, get_models,
+    preload_model, stream_chat_completion,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -248,10 +248,24 @@ impl LmStudioLanguageModel {
                 })
                 .collect(),
             stream: true,
-            max_tokens: Some(-1),
+            max_tokens: Some(
+                request
+                    .max_tokens
+                    .map_or(-1, |max_tokens| max_tokens.try_into().unwrap_or(i32::MAX)),
+            ),
             stop: Some(request.stop),
             temperature: request.temperature.or(Some(0.0)),
             tools: vec![],
+            top_p: request.top_p,
+            seed: request.seed,
+            response_format: request.response_format.map(|response_format| {
+                ResponseFormat::JsonSchema {
+                    json_schema: ResponseJsonSchema {
+                        name: response_format.name,
+                        schema: response_format.schema,
+                    },
+                }
+            }),
         }
     }
 }
@@ -518,3 +532,62 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::BlockedHttpClient;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_to_lmstudio_request() {
+        let model = LmStudioLanguageModel {
+            id: LanguageModelId::from("qwen2.5-coder".to_string()),
+            model: lmstudio::Model::new("qwen2.5-coder", None, None),
+            http_client: Arc::new(BlockedHttpClient),
+            request_limiter: RateLimiter::new(4),
+        };
+        let schema = json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" } },
+        });
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            top_p: Some(0.5),
+            seed: Some(7),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: schema.clone(),
+            }),
+            ..Default::default()
+        };
+
+        let lmstudio_request =
+            serde_json::to_value(model.to_lmstudio_request(request.clone())).unwrap();
+        // LM Studio generates until the context is full unless limited.
+        assert_eq!(lmstudio_request["max_tokens"], -1);
+        assert_eq!(lmstudio_request["top_p"], 0.5);
+        assert_eq!(lmstudio_request["seed"], 7);
+        assert_eq!(
+            lmstudio_request["response_format"],
+            json!({
+                "type": "json_schema",
+                "json_schema": { "name": "summary", "schema": schema },
+            })
+        );
+
+        let lmstudio_request =
+            serde_json::to_value(model.to_lmstudio_request(LanguageModelRequest {
+                max_tokens: Some(1000),
+                ..request
+            }))
+            .unwrap();
+        assert_eq!(lmstudio_request["max_tokens"], 1000);
+    }
+}
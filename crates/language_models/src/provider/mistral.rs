@@ -375,6 +375,7 @@ pub fn into_mistral(
     model: String,
     max_output_tokens: Option<u32>,
 ) -> mistral::Request {
+    let max_tokens = request.max_tokens_for_model(max_output_tokens);
     let len = request.messages.len();
     let merged_messages =
         request
@@ -399,9 +400,20 @@ pub fn into_mistral(
         model,
         messages: merged_messages,
         stream: true,
-        max_tokens: max_output_tokens,
+        max_tokens,
         temperature: request.temperature,
-        response_format: None,
+        top_p: request.top_p,
+        random_seed: request.seed,
+        response_format: request.response_format.map(|response_format| {
+            mistral::ResponseFormat::JsonSchema {
+                json_schema: mistral::ResponseJsonSchema {
+                    name: response_format.name,
+                    description: response_format.description,
+                    schema: response_format.schema,
+                    strict: None,
+                },
+            }
+        }),
         tools: request
             .tools
             .into_iter()
@@ -594,3 +606,60 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_into_mistral() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" } },
+        });
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(4096),
+            top_p: Some(0.5),
+            seed: Some(7),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: Some("A summary of the crash".into()),
+                schema: schema.clone(),
+            }),
+            ..Default::default()
+        };
+
+        let mistral_request = serde_json::to_value(into_mistral(
+            request.clone(),
+            "mistral-large-latest".into(),
+            Some(1000),
+        ))
+        .unwrap();
+        assert_eq!(mistral_request["max_tokens"], 1000);
+        assert_eq!(mistral_request["top_p"], 0.5);
+        assert_eq!(mistral_request["random_seed"], 7);
+        assert_eq!(
+            mistral_request["response_format"],
+            json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "summary",
+                    "description": "A summary of the crash",
+                    "schema": schema,
+                },
+            })
+        );
+
+        let mistral_request =
+            serde_json::to_value(into_mistral(request, "mistral-large-latest".into(), None))
+                .unwrap();
+        assert_eq!(mistral_request["max_tokens"], 4096);
+    }
+}
@@ -258,11 +258,16 @@ impl OllamaLanguageModel {
             stream: true,
             options: Some(ChatOptions {
                 num_ctx: Some(self.model.max_tokens),
+                num_predict: request.max_tokens.map(|max_tokens| max_tokens as isize),
                 stop: Some(request.stop),
                 temperature: request.temperature.or(Some(1.0)),
-                ..Default::default()
+                top_p: request.top_p,
+                seed: request.seed,
             }),
             tools: vec![],
+            format: request
+                .response_format
+                .map(|response_format| response_format.schema),
         }
     }
 }
@@ -507,3 +512,47 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::BlockedHttpClient;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_to_ollama_request() {
+        let model = OllamaLanguageModel {
+            id: LanguageModelId::from("llama3.2".to_string()),
+            model: ollama::Model::new("llama3.2", None, None),
+            http_client: Arc::new(BlockedHttpClient),
+            request_limiter: RateLimiter::new(4),
+        };
+        let schema = json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" } },
+        });
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(1000),
+            top_p: Some(0.5),
+            seed: Some(7),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: schema.clone(),
+            }),
+            ..Default::default()
+        };
+
+        let ollama_request = serde_json::to_value(model.to_ollama_request(request)).unwrap();
+        assert_eq!(ollama_request["options"]["num_predict"], 1000);
+        assert_eq!(ollama_request["options"]["top_p"], 0.5);
+        assert_eq!(ollama_request["options"]["seed"], 7);
+        assert_eq!(ollama_request["format"], schema);
+    }
+}
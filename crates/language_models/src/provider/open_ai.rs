@@ -12,7 +12,7 @@ use language_model::{
     AuthenticateError, LanguageModel, LanguageModelCompletionEvent, LanguageModelId,
     LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason,
+    RateLimiter, ReasoningEffort, Role, StopReason,
 };
 use open_ai::{Model, ResponseStreamEvent, stream_completion};
 use schemars::JsonSchema;
@@ -336,6 +336,8 @@ pub fn into_open_ai(
     model: &Model,
     max_output_tokens: Option<u32>,
 ) -> open_ai::Request {
+    let max_tokens = request.max_tokens_for_model(max_output_tokens);
+    let is_reasoning_model = model.is_reasoning_model();
     let stream = !model.id().starts_with("o1-");
 
     let mut messages = Vec::new();
@@ -394,7 +396,8 @@ pub fn into_open_ai(
         stream,
         stop: request.stop,
         temperature: request.temperature.unwrap_or(1.0),
-        max_tokens: max_output_tokens,
+        max_tokens: max_tokens.filter(|_| !is_reasoning_model),
+        max_completion_tokens: max_tokens.filter(|_| is_reasoning_model),
         parallel_tool_calls: if model.supports_parallel_tool_calls() && !request.tools.is_empty() {
             // Disable parallel tool calls, as the Agent currently expects a maximum of one per turn.
             Some(false)
@@ -413,6 +416,26 @@ pub fn into_open_ai(
             })
             .collect(),
         tool_choice: None,
+        top_p: request.top_p.filter(|_| !is_reasoning_model),
+        seed: request.seed,
+        reasoning_effort: request
+            .reasoning_effort
+            .filter(|_| model.supports_reasoning_effort())
+            .map(|effort| match effort {
+                ReasoningEffort::Low => open_ai::ReasoningEffort::Low,
+                ReasoningEffort::Medium => open_ai::ReasoningEffort::Medium,
+                ReasoningEffort::High => open_ai::ReasoningEffort::High,
+            }),
+        response_format: request.response_format.map(|response_format| {
+            open_ai::ResponseFormat::JsonSchema {
+                json_schema: open_ai::ResponseJsonSchema {
+                    name: response_format.name,
+                    description: response_format.description,
+                    schema: response_format.schema,
+                    strict: None,
+                },
+            }
+        }),
     }
 }
 
@@ -739,3 +762,58 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelRequestMessage, LanguageModelResponseFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_into_open_ai() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" } },
+        });
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Summarize the crash.".into()],
+                cache: false,
+            }],
+            max_tokens: Some(4096),
+            top_p: Some(0.5),
+            seed: Some(7),
+            reasoning_effort: Some(ReasoningEffort::High),
+            response_format: Some(LanguageModelResponseFormat {
+                name: "summary".into(),
+                description: None,
+                schema: schema.clone(),
+            }),
+            ..Default::default()
+        };
+
+        let open_ai_request =
+            serde_json::to_value(into_open_ai(request.clone(), &Model::O3Mini, Some(1000)))
+                .unwrap();
+        assert_eq!(open_ai_request["max_completion_tokens"], 1000);
+        assert!(open_ai_request.get("max_tokens").is_none());
+        assert!(open_ai_request.get("top_p").is_none());
+        assert_eq!(open_ai_request["seed"], 7);
+        assert_eq!(open_ai_request["reasoning_effort"], "high");
+        assert_eq!(
+            open_ai_request["response_format"],
+            json!({
+                "type": "json_schema",
+                "json_schema": { "name": "summary", "schema": schema },
+            })
+        );
+
+        let open_ai_request =
+            serde_json::to_value(into_open_ai(request, &Model::FourOmni, None)).unwrap();
+        assert_eq!(open_ai_request["max_tokens"], 4096);
+        assert!(open_ai_request.get("max_completion_tokens").is_none());
+        assert_eq!(open_ai_request["top_p"], 0.5);
+        assert!(open_ai_request.get("reasoning_effort").is_none());
+    }
+}
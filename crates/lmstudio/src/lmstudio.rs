@@ -121,6 +121,24 @@ pub struct ChatCompletionRequest {
     pub stop: Option<Vec<String>>,
     pub temperature: Option<f32>,
     pub tools: Vec<LmStudioTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    JsonSchema { json_schema: ResponseJsonSchema },
+}
+
+#[derive(Serialize, Debug)]
+pub struct ResponseJsonSchema {
+    pub name: String,
+    pub schema: Value,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -230,8 +230,10 @@ impl NotificationStore {
     ) -> Result<()> {
         this.update(&mut cx, |this, cx| {
             if let Some(notification) = envelope.payload.notification {
-                if let Some(rpc::Notification::ChannelMessageMention { message_id, .. }) =
-                    Notification::from_proto(&notification)
+                if let Some(
+                    rpc::Notification::ChannelMessageMention { message_id, .. }
+                    | rpc::Notification::ChannelMessageReply { message_id, .. },
+                ) = Notification::from_proto(&notification)
                 {
                     let fetch_message_task = this.channel_store.update(cx, |this, cx| {
                         this.fetch_channel_messages(vec![message_id], cx)
@@ -298,6 +300,11 @@ impl NotificationStore {
                     sender_id,
                     message_id,
                     ..
+                }
+                | Notification::ChannelMessageReply {
+                    sender_id,
+                    message_id,
+                    ..
                 } => {
                     user_ids.push(sender_id);
                     message_ids.push(message_id);
@@ -163,6 +163,9 @@ pub struct ChatRequest {
     pub keep_alive: KeepAlive,
     pub options: Option<ChatOptions>,
     pub tools: Vec<OllamaTool>,
+    /// Either `"json"` or a JSON schema the response must match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Value>,
 }
 
 impl ChatRequest {
@@ -181,6 +184,7 @@ pub struct ChatOptions {
     pub stop: Option<Vec<String>>,
     pub temperature: Option<f32>,
     pub top_p: Option<f32>,
+    pub seed: Option<u64>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -181,6 +181,20 @@ impl Model {
         }
     }
 
+    /// Returns whether the given model supports the `reasoning_effort` parameter.
+    pub fn supports_reasoning_effort(&self) -> bool {
+        matches!(self, Self::O1 | Self::O3Mini)
+    }
+
+    /// Returns whether the given model is a reasoning model, which limits its output with the
+    /// `max_completion_tokens` parameter instead of `max_tokens`, and rejects `top_p`.
+    pub fn is_reasoning_model(&self) -> bool {
+        matches!(
+            self,
+            Self::O1 | Self::O1Preview | Self::O1Mini | Self::O3Mini
+        )
+    }
+
     /// Returns whether the given model supports the `parallel_tool_calls` parameter.
     ///
     /// If the model does not support the parameter, do not pass it up, or the API will return an error.
@@ -209,6 +223,9 @@ pub struct Request {
     pub stream: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
+    /// Replaces `max_tokens` for reasoning models, counting their reasoning as well.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub stop: Vec<String>,
     pub temperature: f32,
@@ -219,6 +236,42 @@ pub struct Request {
     pub parallel_tool_calls: Option<bool>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<ToolDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: ResponseJsonSchema },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResponseJsonSchema {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub schema: Value,
+    /// Whether the response must follow the schema exactly, which requires every property to
+    /// be required and `additionalProperties` to be `false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -330,6 +330,14 @@ pub fn embeddings_dir() -> &'static PathBuf {
     })
 }
 
+/// Returns the path to the local file history directory.
+///
+/// This is where the saved contents of files are kept for the file history timeline.
+pub fn file_history_dir() -> &'static PathBuf {
+    static FILE_HISTORY_DIR: OnceLock<PathBuf> = OnceLock::new();
+    FILE_HISTORY_DIR.get_or_init(|| data_dir().join("file_history"))
+}
+
 /// Returns the path to the languages directory.
 ///
 /// This is where language servers are downloaded to for languages built-in to Zed.
@@ -0,0 +1,353 @@
+//! Comment threads that collaborators attach to ranges of files in a shared project.
+//!
+//! Threads are stored by the collab server, which sends every change to everyone in the project.
+//! Their ranges are anchors into the shared buffers, so they follow edits. When a project is shared
+//! again in a later session those anchors can't be resolved anymore, and threads are placed by
+//! looking for the text they were left on instead.
+
+use crate::ProjectPath;
+use anyhow::{Result, anyhow};
+use gpui::{AsyncApp, Context, Entity, EventEmitter, Task};
+use language::{
+    Buffer, BufferSnapshot, File,
+    proto::{deserialize_anchor, serialize_anchor},
+};
+use rpc::{
+    AnyProtoClient, TypedEnvelope,
+    proto::{self, FromProto, ToProto},
+};
+use std::{collections::BTreeMap, ops::Range, path::Path, sync::Arc};
+use text::{Anchor, Point, ToOffset as _, ToPoint as _};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use worktree::WorktreeId;
+
+#[derive(Clone, Debug)]
+pub struct CommentThread {
+    pub id: u64,
+    pub project_path: ProjectPath,
+    pub range: Range<Anchor>,
+    /// The commented text, used to place the thread when its anchors can't be resolved.
+    pub anchored_text: String,
+    /// The row the commented text started on when the thread was created.
+    pub start_row: u32,
+    pub resolved: bool,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    pub id: u64,
+    pub author_id: u64,
+    pub body: String,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommentStoreEvent {
+    ThreadsUpdated,
+}
+
+pub struct CommentStore {
+    threads: BTreeMap<u64, CommentThread>,
+    upstream: Option<(AnyProtoClient, u64)>,
+    reload_task: Task<Option<()>>,
+}
+
+impl EventEmitter<CommentStoreEvent> for CommentStore {}
+
+impl CommentStore {
+    pub fn init(client: &AnyProtoClient) {
+        client.add_entity_message_handler(Self::handle_update_comment_thread);
+        client.add_entity_message_handler(Self::handle_remove_comment_thread);
+    }
+
+    pub fn new() -> Self {
+        Self {
+            threads: BTreeMap::default(),
+            upstream: None,
+            reload_task: Task::ready(None),
+        }
+    }
+
+    /// Starts syncing comment threads with the collab server, once the project is shared or
+    /// joined.
+    pub(crate) fn shared(
+        &mut self,
+        project_id: u64,
+        client: AnyProtoClient,
+        cx: &mut Context<Self>,
+    ) {
+        self.upstream = Some((client, project_id));
+        self.reload(cx);
+    }
+
+    pub(crate) fn unshared(&mut self, cx: &mut Context<Self>) {
+        self.upstream = None;
+        self.threads.clear();
+        self.reload_task = Task::ready(None);
+        cx.emit(CommentStoreEvent::ThreadsUpdated);
+    }
+
+    /// Fetches all threads again, e.g. after reconnecting.
+    pub(crate) fn reload(&mut self, cx: &mut Context<Self>) {
+        let Some((client, project_id)) = self.upstream.clone() else {
+            return;
+        };
+        self.reload_task = cx.spawn(async move |this, cx| {
+            let response = client
+                .request(proto::GetCommentThreads { project_id })
+                .await
+                .log_err()?;
+            this.update(cx, |this, cx| {
+                this.threads = response
+                    .threads
+                    .into_iter()
+                    .filter_map(|thread| {
+                        let thread = CommentThread::from_proto(thread).log_err()?;
+                        Some((thread.id, thread))
+                    })
+                    .collect();
+                cx.emit(CommentStoreEvent::ThreadsUpdated);
+            })
+            .ok()
+        });
+    }
+
+    /// Whether comments can be left in this project, which is the case while it's shared.
+    pub fn is_available(&self) -> bool {
+        self.upstream.is_some()
+    }
+
+    pub fn thread(&self, id: u64) -> Option<&CommentThread> {
+        self.threads.get(&id)
+    }
+
+    /// Returns the threads on the given buffer's file, in the order they were created.
+    pub fn threads_for_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Vec<CommentThread> {
+        let buffer = buffer.read(cx);
+        let Some(project_path) = project_path_for_buffer(buffer, cx) else {
+            return Vec::new();
+        };
+        let snapshot = buffer.snapshot();
+        self.threads
+            .values_mut()
+            .filter(|thread| thread.project_path == project_path)
+            .map(|thread| {
+                if !snapshot.can_resolve(&thread.range.start)
+                    || !snapshot.can_resolve(&thread.range.end)
+                {
+                    thread.range = thread.locate(&snapshot);
+                }
+                thread.clone()
+            })
+            .collect()
+    }
+
+    /// Starts a thread on the given range of a buffer.
+    pub fn create_thread(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        range: Range<Anchor>,
+        body: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((client, project_id)) = self.upstream.clone() else {
+            return Task::ready(Err(anyhow!(
+                "comments are only available in shared projects"
+            )));
+        };
+        let buffer = buffer.read(cx);
+        let Some(project_path) = project_path_for_buffer(buffer, cx) else {
+            return Task::ready(Err(anyhow!("buffer has no file")));
+        };
+        let snapshot = buffer.snapshot();
+        let request = client.request(proto::CreateCommentThread {
+            project_id,
+            worktree_id: project_path.worktree_id.to_proto(),
+            path: project_path.path.as_ref().to_proto(),
+            start: Some(serialize_anchor(&range.start)),
+            end: Some(serialize_anchor(&range.end)),
+            anchored_text: snapshot.text_for_range(range.clone()).collect(),
+            start_row: range.start.to_point(&snapshot).row,
+            body,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    pub fn add_comment(
+        &mut self,
+        thread_id: u64,
+        body: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((client, project_id)) = self.upstream.clone() else {
+            return Task::ready(Err(anyhow!(
+                "comments are only available in shared projects"
+            )));
+        };
+        let request = client.request(proto::AddComment {
+            project_id,
+            thread_id,
+            body,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    /// Deletes one of the current user's comments. Deleting the last comment of a thread removes
+    /// the thread.
+    pub fn delete_comment(
+        &mut self,
+        thread_id: u64,
+        comment_id: u64,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((client, project_id)) = self.upstream.clone() else {
+            return Task::ready(Err(anyhow!(
+                "comments are only available in shared projects"
+            )));
+        };
+        let request = client.request(proto::DeleteComment {
+            project_id,
+            thread_id,
+            comment_id,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    pub fn set_resolved(
+        &mut self,
+        thread_id: u64,
+        resolved: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((client, project_id)) = self.upstream.clone() else {
+            return Task::ready(Err(anyhow!(
+                "comments are only available in shared projects"
+            )));
+        };
+        let request = client.request(proto::SetCommentThreadResolved {
+            project_id,
+            thread_id,
+            resolved,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    async fn handle_update_comment_thread(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UpdateCommentThread>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let thread = envelope
+            .payload
+            .thread
+            .ok_or_else(|| anyhow!("missing comment thread"))?;
+        let thread = CommentThread::from_proto(thread)?;
+        this.update(&mut cx, |this, cx| {
+            match this.threads.get_mut(&thread.id) {
+                // Keep the range we've already resolved, in case the anchors from the server
+                // can't be resolved in this session.
+                Some(existing) => {
+                    existing.resolved = thread.resolved;
+                    existing.comments = thread.comments;
+                }
+                None => {
+                    this.threads.insert(thread.id, thread);
+                }
+            }
+            cx.emit(CommentStoreEvent::ThreadsUpdated);
+        })
+    }
+
+    async fn handle_remove_comment_thread(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::RemoveCommentThread>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            if this.threads.remove(&envelope.payload.thread_id).is_some() {
+                cx.emit(CommentStoreEvent::ThreadsUpdated);
+            }
+        })
+    }
+}
+
+impl CommentThread {
+    fn from_proto(thread: proto::CommentThread) -> Result<Self> {
+        let anchor = |anchor: Option<proto::Anchor>| {
+            anchor
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("invalid comment thread anchor"))
+        };
+        Ok(Self {
+            id: thread.id,
+            project_path: ProjectPath {
+                worktree_id: WorktreeId::from_proto(thread.worktree_id),
+                path: Arc::<Path>::from_proto(thread.path),
+            },
+            range: anchor(thread.start)?..anchor(thread.end)?,
+            anchored_text: thread.anchored_text,
+            start_row: thread.start_row,
+            resolved: thread.resolved,
+            comments: thread
+                .comments
+                .into_iter()
+                .map(|comment| Comment {
+                    id: comment.id,
+                    author_id: comment.author_id,
+                    body: comment.body,
+                    created_at: OffsetDateTime::from_unix_timestamp(comment.created_at as i64)
+                        .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+                })
+                .collect(),
+        })
+    }
+
+    /// Finds the occurrence of the commented text closest to the row the thread started on, or
+    /// falls back to the start of that row if the text is gone.
+    fn locate(&self, snapshot: &BufferSnapshot) -> Range<Anchor> {
+        let row = self.start_row.min(snapshot.max_point().row);
+        let row_start = Point::new(row, 0).to_offset(snapshot);
+        let start = if self.anchored_text.is_empty() {
+            None
+        } else {
+            snapshot
+                .text()
+                .match_indices(self.anchored_text.as_str())
+                .map(|(offset, _)| offset)
+                .min_by_key(|offset| offset.abs_diff(row_start))
+        };
+        match start {
+            Some(start) => {
+                snapshot.anchor_before(start)
+                    ..snapshot.anchor_after(start + self.anchored_text.len())
+            }
+            None => snapshot.anchor_before(row_start)..snapshot.anchor_before(row_start),
+        }
+    }
+}
+
+fn project_path_for_buffer(buffer: &Buffer, cx: &gpui::App) -> Option<ProjectPath> {
+    let file = File::from_dyn(buffer.file())?;
+    Some(ProjectPath {
+        worktree_id: file.worktree_id(cx),
+        path: file.path().clone(),
+    })
+}
@@ -22,8 +22,8 @@ use git::{
     parse_git_remote_url,
     repository::{
         Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RemoteCommandOutput, RepoPath, ResetMode,
-        UpstreamTrackingStatus,
+        GitRepositoryCheckpoint, MergeStages, PushOptions, Remote, RemoteCommandOutput, RepoPath,
+        ResetMode, UpstreamTrackingStatus,
     },
     status::{
         FileStatus, GitSummary, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
@@ -43,7 +43,7 @@ use rpc::{
     AnyProtoClient, TypedEnvelope,
     proto::{self, FromProto, SSH_PROJECT_ID, ToProto, git_reset, split_repository_update},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     collections::{BTreeSet, VecDeque},
@@ -158,7 +158,7 @@ struct LocalDownstreamState {
     _task: Task<Result<()>>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GitStoreCheckpoint {
     checkpoints_by_work_dir_abs_path: HashMap<Arc<Path>, GitRepositoryCheckpoint>,
 }
@@ -411,6 +411,7 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_reset);
         client.add_entity_request_handler(Self::handle_show);
         client.add_entity_request_handler(Self::handle_load_commit_diff);
+        client.add_entity_request_handler(Self::handle_load_merge_stages);
         client.add_entity_request_handler(Self::handle_checkout_files);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
         client.add_entity_request_handler(Self::handle_set_index_text);
@@ -831,6 +832,29 @@ impl GitStore {
         })
     }
 
+    /// Returns whether the checkpoint's commits exist in this project's repositories, e.g.
+    /// because it was taken in another clone of them.
+    pub fn checkpoint_exists(&self, checkpoint: GitStoreCheckpoint, cx: &mut App) -> Task<bool> {
+        let repositories_by_work_dir_abs_path = self
+            .repositories
+            .values()
+            .map(|repo| (repo.read(cx).snapshot.work_directory_abs_path.clone(), repo))
+            .collect::<HashMap<_, _>>();
+
+        let mut tasks = Vec::new();
+        for (work_dir_abs_path, checkpoint) in checkpoint.checkpoints_by_work_dir_abs_path {
+            let Some(repository) = repositories_by_work_dir_abs_path.get(&work_dir_abs_path) else {
+                return Task::ready(false);
+            };
+            // Comparing a checkpoint with itself fails when its commit doesn't exist.
+            let compare = repository.update(cx, |repository, _| {
+                repository.compare_checkpoints(checkpoint.clone(), checkpoint)
+            });
+            tasks.push(async move { compare.await? });
+        }
+        cx.background_spawn(async move { future::try_join_all(tasks).await.is_ok() })
+    }
+
     pub fn delete_checkpoint(
         &self,
         checkpoint: GitStoreCheckpoint,
@@ -857,6 +881,38 @@ impl GitStore {
         })
     }
 
+    /// Diffs two checkpoints, returning the concatenated unified diff of every
+    /// repository that is present in both.
+    pub fn diff_checkpoints(
+        &self,
+        base: GitStoreCheckpoint,
+        mut target: GitStoreCheckpoint,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let repositories_by_work_dir_abs_path = self
+            .repositories
+            .values()
+            .map(|repo| (repo.read(cx).snapshot.work_directory_abs_path.clone(), repo))
+            .collect::<HashMap<_, _>>();
+
+        let mut tasks = Vec::new();
+        for (work_dir_abs_path, base_checkpoint) in base.checkpoints_by_work_dir_abs_path {
+            if let Some(target_checkpoint) = target
+                .checkpoints_by_work_dir_abs_path
+                .remove(&work_dir_abs_path)
+            {
+                if let Some(repository) = repositories_by_work_dir_abs_path.get(&work_dir_abs_path)
+                {
+                    let diff = repository.update(cx, |repository, _| {
+                        repository.diff_checkpoints(base_checkpoint, target_checkpoint)
+                    });
+                    tasks.push(async move { diff.await? });
+                }
+            }
+        }
+        cx.background_spawn(async move { Ok(future::try_join_all(tasks).await?.concat()) })
+    }
+
     /// Blames a buffer.
     pub fn blame_buffer(
         &self,
@@ -1802,6 +1858,31 @@ impl GitStore {
         })
     }
 
+    async fn handle_load_merge_stages(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::LoadMergeStages>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadMergeStagesResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let repo_path = RepoPath::from_str(&envelope.payload.path);
+
+        let stages = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.load_merge_stages(repo_path)
+            })?
+            .await??;
+        Ok(match stages {
+            Some(stages) => proto::LoadMergeStagesResponse {
+                has_conflicts: true,
+                base: stages.base,
+                ours: stages.ours,
+                theirs: stages.theirs,
+            },
+            None => proto::LoadMergeStagesResponse::default(),
+        })
+    }
+
     async fn handle_reset(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GitReset>,
@@ -3713,6 +3794,35 @@ impl Repository {
         })
     }
 
+    /// Loads the base, ours and theirs versions of a conflicted path from the index.
+    pub fn load_merge_stages(
+        &mut self,
+        path: RepoPath,
+    ) -> oneshot::Receiver<Result<Option<MergeStages>>> {
+        let id = self.id;
+        self.send_job(None, move |repo, _cx| async move {
+            match repo {
+                RepositoryState::Local { backend, .. } => Ok(backend.load_merge_stages(path).await),
+                RepositoryState::Remote {
+                    client, project_id, ..
+                } => {
+                    let response = client
+                        .request(proto::LoadMergeStages {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            path: path.as_ref().to_proto(),
+                        })
+                        .await?;
+                    Ok(response.has_conflicts.then(|| MergeStages {
+                        base: response.base,
+                        ours: response.ours,
+                        theirs: response.theirs,
+                    }))
+                }
+            }
+        })
+    }
+
     pub fn checkpoint(&mut self) -> oneshot::Receiver<Result<GitRepositoryCheckpoint>> {
         self.send_job(None, |repo, _cx| async move {
             match repo {
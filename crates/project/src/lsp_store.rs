@@ -35,7 +35,7 @@ use http_client::HttpClient;
 use itertools::Itertools as _;
 use language::{
     Bias, BinaryStatus, Buffer, BufferSnapshot, CachedLspAdapter, CodeLabel, Diagnostic,
-    DiagnosticEntry, DiagnosticSet, Diff, File as _, Language, LanguageRegistry,
+    DiagnosticEntry, DiagnosticSet, Diff, File as _, FormatterRequest, Language, LanguageRegistry,
     LanguageToolchainStore, LocalFile, LspAdapter, LspAdapterDelegate, Patch, PointUtf16,
     TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction, Unclipped,
     language_settings::{
@@ -147,6 +147,10 @@ pub struct LocalLspStore {
         HashMap<LanguageServerId, (LanguageServerName, Arc<LanguageServer>)>,
     prettier_store: Entity<PrettierStore>,
     next_diagnostic_group_id: usize,
+    /// The diagnostics slot that extension linters report into, allocated when a linter first
+    /// runs. Linters don't have a language server of their own, so their diagnostics are told
+    /// apart by their source, the linter's name.
+    linter_diagnostics_id: Option<LanguageServerId>,
     diagnostics: HashMap<
         WorktreeId,
         HashMap<
@@ -1383,6 +1387,7 @@ impl LocalLspStore {
                         buffer,
                         command.as_ref(),
                         arguments.as_deref(),
+                        None,
                         cx,
                     )
                     .await
@@ -1403,6 +1408,32 @@ impl LocalLspStore {
                         },
                     )?;
                 }
+                Formatter::Extension { name } => {
+                    let logger = zlog::scoped!(logger => "extension");
+                    zlog::trace!(logger => "formatting");
+                    let _timer = zlog::time!(logger => "Formatting buffer via extension formatter");
+
+                    let diff = Self::format_via_extension_formatter(
+                        &lsp_store, buffer, name, &settings, cx,
+                    )
+                    .await
+                    .with_context(|| {
+                        format!("Failed to format buffer via extension formatter: {}", name)
+                    })?;
+                    let Some(diff) = diff else {
+                        zlog::trace!(logger => "No changes");
+                        continue;
+                    };
+
+                    extend_formatting_transaction(
+                        buffer,
+                        formatting_transaction_id,
+                        cx,
+                        |buffer, cx| {
+                            buffer.apply_diff(diff, cx);
+                        },
+                    )?;
+                }
                 Formatter::LanguageServer { name } => {
                     let logger = zlog::scoped!(logger => "language-server");
                     zlog::trace!(logger => "formatting");
@@ -1972,10 +2003,63 @@ impl LocalLspStore {
         }
     }
 
+    async fn format_via_extension_formatter(
+        lsp_store: &WeakEntity<LspStore>,
+        buffer: &FormattableBuffer,
+        name: &str,
+        settings: &LanguageSettings,
+        cx: &mut AsyncApp,
+    ) -> Result<Option<Diff>> {
+        let (formatter, request, delegate) = lsp_store.update(cx, |lsp_store, cx| {
+            let local = lsp_store
+                .as_local()
+                .context("extension formatters can only be run locally")?;
+            let (language_name, file) = {
+                let buffer = buffer.handle.read(cx);
+                (
+                    buffer.language().map(|language| language.name()),
+                    File::from_dyn(buffer.file()).cloned(),
+                )
+            };
+            let language_name = language_name.context("buffer has no language")?;
+            let formatter = local
+                .languages
+                .formatter_for_language(&language_name, name)
+                .with_context(|| {
+                    format!("no formatter named {name} is registered for {language_name}")
+                })?;
+            let file = file.context("buffer is not backed by a file in a worktree")?;
+            let delegate = LocalLspAdapterDelegate::from_local_lsp(local, &file.worktree, cx);
+            let request = FormatterRequest {
+                abs_path: buffer.abs_path.clone(),
+                language_name,
+                tab_size: settings.tab_size.get(),
+                hard_tabs: settings.hard_tabs,
+            };
+            anyhow::Ok((formatter, request, delegate as Arc<dyn LspAdapterDelegate>))
+        })??;
+
+        let binary = formatter.command(request, delegate).await?;
+        let arguments = binary
+            .arguments
+            .iter()
+            .map(|argument| argument.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        Self::format_via_external_command(
+            buffer,
+            &binary.path.to_string_lossy(),
+            Some(&arguments),
+            binary.env.as_ref(),
+            cx,
+        )
+        .await
+    }
+
     async fn format_via_external_command(
         buffer: &FormattableBuffer,
         command: &str,
         arguments: Option<&[String]>,
+        env: Option<&HashMap<String, String>>,
         cx: &mut AsyncApp,
     ) -> Result<Option<Diff>> {
         let working_dir_path = buffer.handle.update(cx, |buffer, cx| {
@@ -1994,6 +2078,10 @@ impl LocalLspStore {
             child.envs(buffer_env);
         }
 
+        if let Some(env) = env {
+            child.envs(env);
+        }
+
         if let Some(working_dir_path) = working_dir_path {
             child.current_dir(working_dir_path);
         }
@@ -3552,6 +3640,7 @@ impl LspStore {
                 fs,
                 yarn,
                 next_diagnostic_group_id: Default::default(),
+                linter_diagnostics_id: None,
                 diagnostics: Default::default(),
                 _subscription: cx.on_app_quit(|this, cx| {
                     this.as_local_mut().unwrap().shutdown_language_servers(cx)
@@ -6126,6 +6215,122 @@ impl LspStore {
             self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
         }
 
+        self.lint_buffer(&buffer, cx);
+
+        None
+    }
+
+    /// Runs the linters registered for the buffer's language, replacing their
+    /// previous diagnostics for the buffer's path and keeping those of other linters.
+    fn lint_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) -> Option<()> {
+        let local = self.as_local_mut()?;
+        let buffer = buffer.read(cx);
+        let language_name = buffer.language()?.name();
+        let linters = local.languages.linters_for_language(&language_name);
+        if linters.is_empty() {
+            return None;
+        }
+
+        let file = File::from_dyn(buffer.file())?.clone();
+        let abs_path = file.as_local()?.abs_path(cx);
+        let settings = language_settings(Some(language_name.clone()), buffer.file(), cx);
+        let request = FormatterRequest {
+            abs_path: Some(abs_path.clone()),
+            language_name,
+            tab_size: settings.tab_size.get(),
+            hard_tabs: settings.hard_tabs,
+        };
+        let text = buffer.as_rope().clone();
+        let delegate = LocalLspAdapterDelegate::from_local_lsp(local, &file.worktree, cx);
+        let working_dir = file.worktree.read(cx).abs_path();
+        let languages = local.languages.clone();
+        let diagnostics_id = *local
+            .linter_diagnostics_id
+            .get_or_insert_with(|| languages.next_language_server_id());
+        // Keep the linted snapshot, like the ones sent to language servers, so that diagnostics
+        // are placed on the text that was linted even if the buffer is edited in the meantime.
+        let snapshots = local
+            .buffer_snapshots
+            .entry(buffer.remote_id())
+            .or_default()
+            .entry(diagnostics_id)
+            .or_default();
+        let version = snapshots.last().map_or(0, |snapshot| snapshot.version + 1);
+        snapshots.push(LspBufferSnapshot {
+            version,
+            snapshot: buffer.text_snapshot(),
+        });
+
+        for linter in linters {
+            let request = request.clone();
+            let text = text.clone();
+            let delegate = delegate.clone();
+            let working_dir = working_dir.clone();
+            let abs_path = abs_path.clone();
+            cx.spawn(async move |this, cx| {
+                let binary = linter.command(request.clone(), delegate).await?;
+                let mut child = util::command::new_smol_command(&binary.path);
+                child
+                    .args(&binary.arguments)
+                    .envs(binary.env.into_iter().flatten())
+                    .current_dir(working_dir.as_ref())
+                    .stdin(smol::process::Stdio::piped())
+                    .stdout(smol::process::Stdio::piped())
+                    .stderr(smol::process::Stdio::piped());
+                let mut child = child.spawn()?;
+                let mut stdin = child.stdin.take().context("failed to acquire stdin")?;
+                // Read the output while the text is written, since a linter may fill its output
+                // pipe before it has read all of its input.
+                let write_text = async move {
+                    for chunk in text.chunks() {
+                        stdin.write_all(chunk.as_bytes()).await?;
+                    }
+                    stdin.flush().await
+                };
+                let (written, output) = futures::join!(write_text, child.output());
+                let output = output?;
+                written.context("failed to write the buffer to the linter")?;
+                let output = language::LinterOutput {
+                    exit_code: output.status.code(),
+                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                };
+                let source = linter.name();
+                let diagnostics = linter.diagnostics(request, output).await?;
+
+                this.update(cx, |this, cx| {
+                    let local = this.as_local_mut().context("not a local project")?;
+                    let entries = diagnostics
+                        .into_iter()
+                        .map(|diagnostic| DiagnosticEntry {
+                            range: Unclipped(diagnostic.range.start)
+                                ..Unclipped(diagnostic.range.end),
+                            diagnostic: Diagnostic {
+                                source: Some(source.to_string()),
+                                code: diagnostic.code.map(lsp::NumberOrString::String),
+                                severity: diagnostic.severity,
+                                message: diagnostic.message,
+                                group_id: post_inc(&mut local.next_diagnostic_group_id),
+                                is_primary: true,
+                                is_disk_based: false,
+                                is_unnecessary: false,
+                                data: None,
+                            },
+                        })
+                        .collect();
+                    this.merge_diagnostic_entries(
+                        diagnostics_id,
+                        abs_path,
+                        Some(version),
+                        entries,
+                        |diagnostic| diagnostic.source.as_deref() != Some(source.as_ref()),
+                        cx,
+                    )
+                })?
+            })
+            .detach_and_log_err(cx);
+        }
+
         None
     }
 
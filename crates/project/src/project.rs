@@ -1,5 +1,6 @@
 pub mod buffer_store;
 mod color_extractor;
+pub mod comment_store;
 pub mod connection_manager;
 pub mod debounced_delay;
 pub mod debugger;
@@ -38,6 +39,7 @@ use client::{
     Client, Collaborator, PendingEntitySubscription, ProjectId, TypedEnvelope, UserStore, proto,
 };
 use clock::ReplicaId;
+use comment_store::CommentStore;
 
 use dap::{DapRegistry, client::DebugAdapterClient};
 
@@ -81,7 +83,7 @@ use node_runtime::NodeRuntime;
 use parking_lot::Mutex;
 pub use prettier_store::PrettierStore;
 use project_settings::{ProjectSettings, SettingsObserver, SettingsObserverEvent};
-use remote::{SshConnectionOptions, SshRemoteClient};
+use remote::{RemoteConnectionOptions, SshRemoteClient};
 use rpc::{
     AnyProtoClient, ErrorCode,
     proto::{FromProto, LanguageServerPromptResponse, SSH_PROJECT_ID, ToProto},
@@ -169,6 +171,7 @@ pub struct Project {
     dap_store: Entity<DapStore>,
 
     breakpoint_store: Entity<BreakpointStore>,
+    comment_store: Entity<CommentStore>,
     client: Arc<client::Client>,
     join_project_response_message_id: u32,
     task_store: Entity<TaskStore>,
@@ -719,6 +722,7 @@ enum EntitySubscription {
     LspStore(PendingEntitySubscription<LspStore>),
     SettingsObserver(PendingEntitySubscription<SettingsObserver>),
     DapStore(PendingEntitySubscription<DapStore>),
+    CommentStore(PendingEntitySubscription<CommentStore>),
 }
 
 #[derive(Debug, Clone)]
@@ -827,6 +831,7 @@ impl Project {
         ToolchainStore::init(&client);
         DapStore::init(&client);
         BreakpointStore::init(&client);
+        CommentStore::init(&client);
     }
 
     pub fn local(
@@ -864,6 +869,7 @@ impl Project {
 
             let breakpoint_store =
                 cx.new(|_| BreakpointStore::local(worktree_store.clone(), buffer_store.clone()));
+            let comment_store = cx.new(|_| CommentStore::new());
 
             let dap_store = cx.new(|cx| {
                 DapStore::new_local(
@@ -963,6 +969,7 @@ impl Project {
                 fs,
                 ssh_client: None,
                 breakpoint_store,
+                comment_store,
                 dap_store,
 
                 buffers_needing_diff: Default::default(),
@@ -1066,6 +1073,7 @@ impl Project {
 
             let breakpoint_store =
                 cx.new(|_| BreakpointStore::remote(SSH_PROJECT_ID, client.clone().into()));
+            let comment_store = cx.new(|_| CommentStore::new());
 
             let dap_store = cx.new(|_| {
                 DapStore::new_remote(
@@ -1089,6 +1097,7 @@ impl Project {
                 image_store,
                 lsp_store,
                 breakpoint_store,
+                comment_store,
                 dap_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -1205,6 +1214,9 @@ impl Project {
                 client.subscribe_to_entity::<SettingsObserver>(remote_id)?,
             ),
             EntitySubscription::DapStore(client.subscribe_to_entity::<DapStore>(remote_id)?),
+            EntitySubscription::CommentStore(
+                client.subscribe_to_entity::<CommentStore>(remote_id)?,
+            ),
         ];
         let response = client
             .request_envelope(proto::JoinProject {
@@ -1226,7 +1238,7 @@ impl Project {
 
     async fn from_join_project_response(
         response: TypedEnvelope<proto::JoinProjectResponse>,
-        subscriptions: [EntitySubscription; 7],
+        subscriptions: [EntitySubscription; 8],
         client: Arc<Client>,
         run_tasks: bool,
         user_store: Entity<UserStore>,
@@ -1251,6 +1263,7 @@ impl Project {
 
         let breakpoint_store =
             cx.new(|_| BreakpointStore::remote(remote_id, client.clone().into()))?;
+        let comment_store = cx.new(|_| CommentStore::new())?;
         let dap_store = cx.new(|_cx| {
             DapStore::new_remote(remote_id, client.clone().into(), breakpoint_store.clone())
         })?;
@@ -1354,6 +1367,7 @@ impl Project {
                     replica_id,
                 },
                 breakpoint_store,
+                comment_store,
                 dap_store: dap_store.clone(),
                 git_store: git_store.clone(),
                 buffers_needing_diff: Default::default(),
@@ -1400,6 +1414,9 @@ impl Project {
                 EntitySubscription::DapStore(subscription) => {
                     subscription.set_entity(&dap_store, &mut cx)
                 }
+                EntitySubscription::CommentStore(subscription) => {
+                    subscription.set_entity(&comment_store, &mut cx)
+                }
             })
             .collect::<Vec<_>>();
 
@@ -1416,6 +1433,9 @@ impl Project {
         this.update(&mut cx, |this, cx| {
             this.set_collaborators_from_proto(response.payload.collaborators, cx)?;
             this.client_subscriptions.extend(subscriptions);
+            this.comment_store.update(cx, |comment_store, cx| {
+                comment_store.shared(remote_id, this.client.clone().into(), cx)
+            });
             anyhow::Ok(())
         })??;
 
@@ -1604,6 +1624,10 @@ impl Project {
         self.breakpoint_store.clone()
     }
 
+    pub fn comment_store(&self) -> Entity<CommentStore> {
+        self.comment_store.clone()
+    }
+
     pub fn lsp_store(&self) -> Entity<LspStore> {
         self.lsp_store.clone()
     }
@@ -1710,7 +1734,7 @@ impl Project {
             .map(|ssh| ssh.read(cx).connection_state())
     }
 
-    pub fn ssh_connection_options(&self, cx: &App) -> Option<SshConnectionOptions> {
+    pub fn remote_connection_options(&self, cx: &App) -> Option<RemoteConnectionOptions> {
         self.ssh_client
             .as_ref()
             .map(|ssh| ssh.read(cx).connection_options())
@@ -2041,6 +2065,9 @@ impl Project {
             self.client
                 .subscribe_to_entity(project_id)?
                 .set_entity(&self.git_store, &mut cx.to_async()),
+            self.client
+                .subscribe_to_entity(project_id)?
+                .set_entity(&self.comment_store, &mut cx.to_async()),
         ]);
 
         self.buffer_store.update(cx, |buffer_store, cx| {
@@ -2055,6 +2082,9 @@ impl Project {
         self.breakpoint_store.update(cx, |breakpoint_store, _| {
             breakpoint_store.shared(project_id, self.client.clone().into())
         });
+        self.comment_store.update(cx, |comment_store, cx| {
+            comment_store.shared(project_id, self.client.clone().into(), cx)
+        });
         self.dap_store.update(cx, |dap_store, cx| {
             dap_store.shared(project_id, self.client.clone().into(), cx);
         });
@@ -2093,6 +2123,8 @@ impl Project {
                 git_store.shared(remote_id, self.client.clone().into(), cx)
             });
         }
+        self.comment_store
+            .update(cx, |comment_store, cx| comment_store.reload(cx));
         cx.emit(Event::Reshared);
         Ok(())
     }
@@ -2121,6 +2153,8 @@ impl Project {
         });
         self.enqueue_buffer_ordered_message(BufferOrderedMessage::Resync)
             .unwrap();
+        self.comment_store
+            .update(cx, |comment_store, cx| comment_store.reload(cx));
         cx.emit(Event::Rejoined);
         Ok(())
     }
@@ -2153,6 +2187,9 @@ impl Project {
             self.breakpoint_store.update(cx, |breakpoint_store, cx| {
                 breakpoint_store.unshared(cx);
             });
+            self.comment_store.update(cx, |comment_store, cx| {
+                comment_store.unshared(cx);
+            });
             self.dap_store.update(cx, |dap_store, cx| {
                 dap_store.unshared(cx);
             });
@@ -18,14 +18,18 @@ use git2::RepositoryInitOptions;
 use gpui::{App, BackgroundExecutor, SemanticVersion, UpdateGlobal};
 use http_client::Url;
 use language::{
-    Diagnostic, DiagnosticEntry, DiagnosticSet, DiskState, FakeLspAdapter, LanguageConfig,
-    LanguageMatcher, LanguageName, LineEnding, OffsetRangeExt, Point, ToPoint,
-    language_settings::{AllLanguageSettings, LanguageSettingsContent, language_settings},
+    Diagnostic, DiagnosticEntry, DiagnosticSet, DiskState, FakeLspAdapter, FormatterRequest,
+    LanguageConfig, LanguageFormatter, LanguageLinter, LanguageMatcher, LanguageName, LineEnding,
+    LinterDiagnostic, LinterOutput, LspAdapterDelegate, OffsetRangeExt, Point, PointUtf16, ToPoint,
+    language_settings::{
+        AllLanguageSettings, Formatter, FormatterList, LanguageSettingsContent, SelectedFormatter,
+        language_settings,
+    },
     tree_sitter_rust, tree_sitter_typescript,
 };
 use lsp::{
-    DiagnosticSeverity, DocumentChanges, FileOperationFilter, NumberOrString, TextDocumentEdit,
-    WillRenameFiles, notification::DidRenameFiles,
+    DiagnosticSeverity, DocumentChanges, FileOperationFilter, LanguageServerBinary, NumberOrString,
+    TextDocumentEdit, WillRenameFiles, notification::DidRenameFiles,
 };
 use parking_lot::Mutex;
 use paths::{config_dir, tasks_file};
@@ -8500,6 +8504,162 @@ async fn search(
         .collect())
 }
 
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_extension_formatters_and_linters(cx: &mut gpui::TestAppContext) {
+    struct TestFormatter;
+
+    #[async_trait::async_trait]
+    impl LanguageFormatter for TestFormatter {
+        fn name(&self) -> Arc<str> {
+            "test-formatter".into()
+        }
+
+        async fn command(
+            &self,
+            _: FormatterRequest,
+            _: Arc<dyn LspAdapterDelegate>,
+        ) -> Result<LanguageServerBinary> {
+            Ok(LanguageServerBinary {
+                path: "sed".into(),
+                arguments: vec!["s/two/three/".into()],
+                env: None,
+            })
+        }
+    }
+
+    /// Reports the lines of the buffer that contain a word.
+    struct TestLinter {
+        word: &'static str,
+    }
+
+    #[async_trait::async_trait]
+    impl LanguageLinter for TestLinter {
+        fn name(&self) -> Arc<str> {
+            format!("{}-linter", self.word).into()
+        }
+
+        async fn command(
+            &self,
+            _: FormatterRequest,
+            _: Arc<dyn LspAdapterDelegate>,
+        ) -> Result<LanguageServerBinary> {
+            Ok(LanguageServerBinary {
+                path: "cat".into(),
+                arguments: Vec::new(),
+                env: None,
+            })
+        }
+
+        async fn diagnostics(
+            &self,
+            _: FormatterRequest,
+            output: LinterOutput,
+        ) -> Result<Vec<LinterDiagnostic>> {
+            Ok(output
+                .stdout
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.contains(self.word))
+                .map(|(row, line)| LinterDiagnostic {
+                    range: PointUtf16::new(row as u32, 0)
+                        ..PointUtf16::new(row as u32, line.len() as u32),
+                    severity: DiagnosticSeverity::WARNING,
+                    message: format!("found {}", self.word),
+                    code: None,
+                })
+                .collect())
+        }
+    }
+
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let dir = TempTree::new(json!({ "a.rs": "one\ntwo\n" }));
+    let project = Project::test(Arc::new(RealFs::new(None, cx.executor())), [dir.path()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    language_registry.register_formatter("Rust".into(), Arc::new(TestFormatter));
+    language_registry.register_linter("Rust".into(), Arc::new(TestLinter { word: "one" }));
+    language_registry.register_linter("Rust".into(), Arc::new(TestLinter { word: "three" }));
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.formatter = Some(SelectedFormatter::List(FormatterList(
+                    vec![Formatter::Extension {
+                        name: "test-formatter".into(),
+                    }]
+                    .into(),
+                )));
+            });
+        });
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(dir.path().join("a.rs"), cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                LspFormatTarget::Buffers,
+                true,
+                lsp_store::FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "one\nthree\n"));
+
+    // Saving runs both linters, and neither replaces the other's diagnostics. Their diagnostics
+    // stay on the text that was linted when the buffer is edited while they run.
+    let mut events = cx.events(&project);
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    let mut diagnostics_updates = 0;
+    while diagnostics_updates < 2 {
+        if let Event::DiagnosticsUpdated { .. } = events.next().await.unwrap() {
+            diagnostics_updates += 1;
+        }
+    }
+    buffer.read_with(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let mut diagnostics = snapshot
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| {
+                (
+                    entry.range,
+                    entry.diagnostic.source.unwrap(),
+                    entry.diagnostic.message,
+                )
+            })
+            .collect::<Vec<_>>();
+        diagnostics.sort_by(|a, b| a.0.start.cmp(&b.0.start));
+        assert_eq!(
+            diagnostics,
+            [
+                (
+                    Point::new(1, 0)..Point::new(1, 3),
+                    "one-linter".to_string(),
+                    "found one".to_string()
+                ),
+                (
+                    Point::new(2, 0)..Point::new(2, 5),
+                    "three-linter".to_string(),
+                    "found three".to_string()
+                ),
+            ]
+        );
+    });
+}
+
 pub fn init_test(cx: &mut gpui::TestAppContext) {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
@@ -4,6 +4,7 @@ use collections::HashMap;
 use gpui::{AnyWindowHandle, App, AppContext as _, Context, Entity, Task, WeakEntity};
 use itertools::Itertools;
 use language::LanguageName;
+use remote::{RemoteConnectionOptions, RemoteTerminalCommand};
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -45,12 +46,6 @@ pub enum TerminalKind {
     },
 }
 
-/// SshCommand describes how to connect to a remote server
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct SshCommand {
-    arguments: Vec<String>,
-}
-
 impl Project {
     pub fn active_project_directory(&self, cx: &App) -> Option<Arc<Path>> {
         let worktree = self
@@ -72,14 +67,18 @@ impl Project {
         }
     }
 
-    pub fn ssh_details(&self, cx: &App) -> Option<(String, SshCommand)> {
+    pub fn ssh_details(&self, cx: &App) -> Option<(String, RemoteTerminalCommand)> {
         if let Some(ssh_client) = &self.ssh_client {
             let ssh_client = ssh_client.read(cx);
-            if let Some(args) = ssh_client.ssh_args() {
-                return Some((
-                    ssh_client.connection_options().host.clone(),
-                    SshCommand { arguments: args },
-                ));
+            if let Some(terminal_command) = ssh_client.terminal_command() {
+                let host = match ssh_client.connection_options() {
+                    RemoteConnectionOptions::Ssh(options) => options.host,
+                    options => options
+                        .nickname()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| options.connection_string()),
+                };
+                return Some((host, terminal_command));
             }
         }
 
@@ -282,6 +281,7 @@ impl Project {
                     command_label: spawn_task.command_label,
                     hide: spawn_task.hide,
                     status: TaskStatus::Running,
+                    exit_code: None,
                     show_summary: spawn_task.show_summary,
                     show_command: spawn_task.show_command,
                     show_rerun: spawn_task.show_rerun,
@@ -552,7 +552,7 @@ impl Project {
 }
 
 fn wrap_for_ssh(
-    ssh_command: &SshCommand,
+    ssh_command: &RemoteTerminalCommand,
     command: Option<(&String, &Vec<String>)>,
     path: Option<&Path>,
     env: HashMap<String, String>,
@@ -596,14 +596,7 @@ fn wrap_for_ssh(
     } else {
         format!("cd; {env_changes} {to_run}")
     };
-    let shell_invocation = format!("sh -c {}", shlex::try_quote(&commands).unwrap());
-
-    let program = "ssh".to_string();
-    let mut args = ssh_command.arguments.clone();
-
-    args.push("-t".to_string());
-    args.push(shell_invocation);
-    (program, args)
+    ssh_command.for_shell_script(&commands)
 }
 
 fn add_environment_path(env: &mut HashMap<String, String>, new_path: &Path) -> Result<()> {
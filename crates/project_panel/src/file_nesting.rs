@@ -0,0 +1,198 @@
+use collections::{BTreeMap, HashMap, HashSet};
+use project::{GitEntry, ProjectEntryId};
+use std::path::Path;
+
+const CAPTURE: &str = "$(capture)";
+
+/// Which files are nested under other files in the project panel.
+#[derive(Debug, Default)]
+pub(crate) struct NestedFiles {
+    /// Maps each nested file to the file it's nested under.
+    parents: HashMap<ProjectEntryId, ProjectEntryId>,
+    /// The files that have other files nested under them.
+    nesting: HashSet<ProjectEntryId>,
+}
+
+impl NestedFiles {
+    pub(crate) fn clear(&mut self) {
+        self.parents.clear();
+        self.nesting.clear();
+    }
+
+    pub(crate) fn parent(&self, entry_id: ProjectEntryId) -> Option<ProjectEntryId> {
+        self.parents.get(&entry_id).copied()
+    }
+
+    pub(crate) fn has_nested_files(&self, entry_id: ProjectEntryId) -> bool {
+        self.nesting.contains(&entry_id)
+    }
+
+    /// Moves the files in `entries` that match one of the nesting `patterns` directly after the
+    /// file they're nested under, removing them altogether if that file isn't expanded.
+    ///
+    /// `entries` must be sorted in the order they're displayed in.
+    pub(crate) fn nest(
+        &mut self,
+        entries: &mut Vec<GitEntry>,
+        expanded_entry_ids: &[ProjectEntryId],
+        patterns: &BTreeMap<String, Vec<String>>,
+    ) {
+        let mut files_by_directory = HashMap::<&Path, Vec<usize>>::default();
+        for (ix, entry) in entries.iter().enumerate() {
+            if entry.is_file() {
+                if let Some(parent) = entry.path.parent() {
+                    files_by_directory.entry(parent).or_default().push(ix);
+                }
+            }
+        }
+
+        let mut parent_ixs = HashMap::<usize, usize>::default();
+        for file_ixs in files_by_directory.values() {
+            let names = file_ixs
+                .iter()
+                .map(|ix| {
+                    entries[*ix]
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            for (child, parent) in nest_file_names(&names, patterns) {
+                parent_ixs.insert(file_ixs[child], file_ixs[parent]);
+            }
+        }
+        if parent_ixs.is_empty() {
+            return;
+        }
+
+        let mut children_by_parent = HashMap::<usize, Vec<usize>>::default();
+        for (child_ix, parent_ix) in &parent_ixs {
+            let child_id = entries[*child_ix].id;
+            let parent_id = entries[*parent_ix].id;
+            self.parents.insert(child_id, parent_id);
+            self.nesting.insert(parent_id);
+            children_by_parent
+                .entry(*parent_ix)
+                .or_default()
+                .push(*child_ix);
+        }
+
+        let mut slots = std::mem::take(entries)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        for ix in 0..slots.len() {
+            if parent_ixs.contains_key(&ix) {
+                continue;
+            }
+            let Some(entry) = slots[ix].take() else {
+                continue;
+            };
+            let is_expanded = expanded_entry_ids.binary_search(&entry.id).is_ok();
+            entries.push(entry);
+            if is_expanded {
+                if let Some(children) = children_by_parent.get_mut(&ix) {
+                    children.sort_unstable();
+                    entries.extend(children.iter().filter_map(|ix| slots[*ix].take()));
+                }
+            }
+        }
+    }
+}
+
+/// Given the names of the files in a directory, returns pairs of indices of
+/// (nested file, file it's nested under).
+///
+/// Files are only nested one level deep: when a file would be nested under a file
+/// that's nested itself, it's nested under the outermost file instead.
+fn nest_file_names(
+    names: &[impl AsRef<str>],
+    patterns: &BTreeMap<String, Vec<String>>,
+) -> Vec<(usize, usize)> {
+    let mut ixs_by_name = HashMap::<&str, usize>::default();
+    for (ix, name) in names.iter().enumerate() {
+        ixs_by_name.insert(name.as_ref(), ix);
+    }
+
+    let mut parents = HashMap::<usize, usize>::default();
+    for (parent_ix, parent_name) in names.iter().enumerate() {
+        for (parent_pattern, child_patterns) in patterns {
+            let Some(capture) = match_parent_pattern(parent_pattern, parent_name.as_ref()) else {
+                continue;
+            };
+            for child_pattern in child_patterns {
+                let child_pattern = child_pattern.replace(CAPTURE, capture);
+                if child_pattern.contains('*') {
+                    for (child_ix, child_name) in names.iter().enumerate() {
+                        if child_ix != parent_ix
+                            && glob_matches(&child_pattern, child_name.as_ref())
+                        {
+                            parents.entry(child_ix).or_insert(parent_ix);
+                        }
+                    }
+                } else if let Some(child_ix) = ixs_by_name.get(child_pattern.as_str()) {
+                    if *child_ix != parent_ix {
+                        parents.entry(*child_ix).or_insert(parent_ix);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut nested = Vec::with_capacity(parents.len());
+    for (child_ix, parent_ix) in &parents {
+        let mut outermost_ix = *parent_ix;
+        let mut visited = HashSet::from_iter([*child_ix]);
+        while let Some(next_ix) = parents.get(&outermost_ix) {
+            if !visited.insert(outermost_ix) {
+                break;
+            }
+            outermost_ix = *next_ix;
+        }
+        if !visited.contains(&outermost_ix) {
+            nested.push((*child_ix, outermost_ix));
+        }
+    }
+    nested.sort_unstable();
+    nested
+}
+
+/// Matches a file name against a parent pattern, which may contain a single `*`.
+/// Returns the text matched by the `*`, if any.
+fn match_parent_pattern<'a>(pattern: &str, name: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            if name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+            {
+                Some(&name[prefix.len()..name.len() - suffix.len()])
+            } else {
+                None
+            }
+        }
+        None => (pattern == name).then_some(""),
+    }
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return name.is_empty();
+    };
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(ix) => rest = &rest[ix + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
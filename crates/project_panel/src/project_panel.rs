@@ -1,3 +1,4 @@
+mod file_nesting;
 mod project_panel_settings;
 mod utils;
 
@@ -7,7 +8,7 @@ use collections::{BTreeSet, HashMap, hash_map};
 use command_palette_hooks::CommandPaletteFilter;
 use db::kvp::KEY_VALUE_STORE;
 use editor::{
-    Editor, EditorEvent, EditorSettings, ShowScrollbar,
+    Editor, EditorEvent, EditorSettings, FileDiffView, ShowScrollbar,
     items::{
         entry_diagnostic_aware_icon_decoration_and_color,
         entry_diagnostic_aware_icon_name_and_color, entry_git_aware_label_color,
@@ -15,6 +16,7 @@ use editor::{
     scroll::{Autoscroll, ScrollbarAutoHide},
 };
 use file_icons::FileIcons;
+use file_nesting::NestedFiles;
 use git::status::GitSummary;
 use gpui::{
     Action, AnyElement, App, ArcCow, AsyncWindowContext, Bounds, ClipboardItem, Context,
@@ -24,6 +26,7 @@ use gpui::{
     Stateful, Styled, Subscription, Task, UniformListScrollHandle, WeakEntity, Window, actions,
     anchored, deferred, div, impl_actions, point, px, size, uniform_list,
 };
+use image_viewer::ImageView;
 use indexmap::IndexMap;
 use language::DiagnosticSeverity;
 use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
@@ -82,6 +85,7 @@ pub struct ProjectPanel {
     /// Relevant only for auto-fold dirs, where a single project panel entry may actually consist of several
     /// project entries (and all non-leaf nodes are guaranteed to be directories).
     ancestors: HashMap<ProjectEntryId, FoldedAncestors>,
+    nested_files: NestedFiles,
     folded_directory_drag_target: Option<FoldedDirectoryDragTarget>,
     last_worktree_root_id: Option<ProjectEntryId>,
     last_selection_drag_over_entry: Option<ProjectEntryId>,
@@ -159,6 +163,7 @@ struct EntryDetails {
     kind: EntryKind,
     is_ignored: bool,
     is_expanded: bool,
+    has_nested_files: bool,
     is_selected: bool,
     is_marked: bool,
     is_editing: bool,
@@ -218,6 +223,8 @@ actions!(
         SelectPrevDiagnostic,
         SelectNextDirectory,
         SelectPrevDirectory,
+        CompareMarkedFiles,
+        CompareWithClipboard,
     ]
 );
 
@@ -444,11 +451,13 @@ impl ProjectPanel {
             })
             .detach();
 
-            let mut project_panel_settings = *ProjectPanelSettings::get_global(cx);
+            let mut project_panel_settings = ProjectPanelSettings::get_global(cx).clone();
             cx.observe_global::<SettingsStore>(move |this, cx| {
-                let new_settings = *ProjectPanelSettings::get_global(cx);
+                let new_settings = ProjectPanelSettings::get_global(cx).clone();
                 if project_panel_settings != new_settings {
-                    if project_panel_settings.hide_gitignore != new_settings.hide_gitignore {
+                    if project_panel_settings.hide_gitignore != new_settings.hide_gitignore
+                        || project_panel_settings.file_nesting != new_settings.file_nesting
+                    {
                         this.update_visible_entries(None, cx);
                     }
                     project_panel_settings = new_settings;
@@ -466,6 +475,7 @@ impl ProjectPanel {
                 focus_handle,
                 visible_entries: Default::default(),
                 ancestors: Default::default(),
+                nested_files: Default::default(),
                 folded_directory_drag_target: None,
                 last_worktree_root_id: Default::default(),
                 last_external_paths_drag_over_entry: None,
@@ -727,6 +737,7 @@ impl ProjectPanel {
             let is_read_only = project.is_read_only(cx);
             let is_remote = project.is_via_collab();
             let is_local = project.is_local();
+            let can_compare = self.marked_files_to_compare(cx).is_some();
 
             let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -776,6 +787,16 @@ impl ProjectPanel {
                                 "Copy Relative Path",
                                 Box::new(zed_actions::workspace::CopyRelativePath),
                             )
+                            .when(can_compare || !is_dir, |menu| menu.separator())
+                            .when(can_compare, |menu| {
+                                menu.action("Compare Marked Files", Box::new(CompareMarkedFiles))
+                            })
+                            .when(!is_dir, |menu| {
+                                menu.action(
+                                    "Compare with Clipboard",
+                                    Box::new(CompareWithClipboard),
+                                )
+                            })
                             .separator()
                             .when(!is_root || !cfg!(target_os = "windows"), |menu| {
                                 menu.action("Rename", Box::new(Rename))
@@ -858,7 +879,8 @@ impl ProjectPanel {
                     return;
                 }
             }
-            if entry.is_dir() {
+            let is_dir = entry.is_dir();
+            if is_dir || self.nested_files.has_nested_files(entry.id) {
                 let worktree_id = worktree.id();
                 let entry_id = entry.id;
                 let expanded_dir_ids =
@@ -871,9 +893,11 @@ impl ProjectPanel {
                 match expanded_dir_ids.binary_search(&entry_id) {
                     Ok(_) => self.select_next(&SelectNext, window, cx),
                     Err(ix) => {
-                        self.project.update(cx, |project, cx| {
-                            project.expand_entry(worktree_id, entry_id, cx);
-                        });
+                        if is_dir {
+                            self.project.update(cx, |project, cx| {
+                                project.expand_entry(worktree_id, entry_id, cx);
+                            });
+                        }
 
                         expanded_dir_ids.insert(ix, entry_id);
                         self.update_visible_entries(None, cx);
@@ -913,7 +937,12 @@ impl ProjectPanel {
                 return;
             };
 
-        let mut entry = &entry;
+        // Collapsing a nested file collapses the file it's nested under.
+        let mut entry = self
+            .nested_files
+            .parent(entry.id)
+            .and_then(|parent_id| worktree.entry_for_id(parent_id))
+            .unwrap_or(&entry);
         loop {
             let entry_id = entry.id;
             match expanded_dir_ids.binary_search(&entry_id) {
@@ -958,13 +987,16 @@ impl ProjectPanel {
     ) {
         if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) {
             if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+                let has_nested_files = self.nested_files.has_nested_files(entry_id);
                 self.project.update(cx, |project, cx| {
                     match expanded_dir_ids.binary_search(&entry_id) {
                         Ok(ix) => {
                             expanded_dir_ids.remove(ix);
                         }
                         Err(ix) => {
-                            project.expand_entry(worktree_id, entry_id, cx);
+                            if !has_nested_files {
+                                project.expand_entry(worktree_id, entry_id, cx);
+                            }
                             expanded_dir_ids.insert(ix, entry_id);
                         }
                     }
@@ -2307,7 +2339,7 @@ impl ProjectPanel {
     ) {
         let abs_file_paths = {
             let project = self.project.read(cx);
-            self.effective_entries()
+            self.effective_entries_in_display_order()
                 .into_iter()
                 .filter_map(|entry| {
                     let entry_path = project.path_for_entry(entry.entry_id, cx)?.path;
@@ -2336,7 +2368,7 @@ impl ProjectPanel {
     ) {
         let file_paths = {
             let project = self.project.read(cx);
-            self.effective_entries()
+            self.effective_entries_in_display_order()
                 .into_iter()
                 .filter_map(|entry| {
                     Some(
@@ -2391,7 +2423,27 @@ impl ProjectPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Some((worktree, entry)) = self.selected_sub_entry(cx) {
+        let effective_entries = self.effective_entries_in_display_order();
+        let entries = if effective_entries.len() > 1 {
+            let project = self.project.read(cx);
+            effective_entries
+                .into_iter()
+                .filter_map(|selection| {
+                    let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
+                    let entry = worktree.read(cx).entry_for_id(selection.entry_id)?.clone();
+                    Some((worktree, entry))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            self.selected_sub_entry(cx)
+                .map(|(worktree, entry)| (worktree, entry.clone()))
+                .into_iter()
+                .collect()
+        };
+
+        // Open a single terminal for each distinct directory, in the order the entries are displayed.
+        let mut working_directories = Vec::new();
+        for (worktree, entry) in entries {
             let abs_path = match &entry.canonical_path {
                 Some(canonical_path) => Some(canonical_path.to_path_buf()),
                 None => worktree.read(cx).absolutize(&entry.path).ok(),
@@ -2403,12 +2455,101 @@ impl ProjectPanel {
                 abs_path.and_then(|path| Some(path.parent()?.to_path_buf()))
             };
             if let Some(working_directory) = working_directory {
-                window.dispatch_action(
-                    workspace::OpenTerminal { working_directory }.boxed_clone(),
+                if !working_directories.contains(&working_directory) {
+                    working_directories.push(working_directory);
+                }
+            }
+        }
+        for working_directory in working_directories {
+            window.dispatch_action(
+                workspace::OpenTerminal { working_directory }.boxed_clone(),
+                cx,
+            )
+        }
+    }
+
+    fn compare_marked_files(
+        &mut self,
+        _: &CompareMarkedFiles,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((old_path, new_path)) = self.marked_files_to_compare(cx) else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                // Images are compared side by side, everything else as a text diff.
+                match ImageView::open_comparison(
+                    old_path.clone(),
+                    new_path.clone(),
+                    workspace,
+                    window,
+                    cx,
+                ) {
+                    Some(open_comparison) => open_comparison.detach_and_log_err(cx),
+                    None => FileDiffView::open(old_path, new_path, workspace, window, cx)
+                        .detach_and_log_err(cx),
+                }
+            })
+            .ok();
+    }
+
+    fn compare_with_clipboard(
+        &mut self,
+        _: &CompareWithClipboard,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(clipboard_text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        let Some((worktree, entry)) = self.selected_sub_entry(cx) else {
+            return;
+        };
+        if !entry.is_file() {
+            return;
+        }
+        let project_path = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: entry.path.clone(),
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                FileDiffView::open_with_text(
+                    clipboard_text,
+                    "Clipboard",
+                    project_path,
+                    workspace,
+                    window,
                     cx,
                 )
-            }
+                .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    /// Returns the paths of the two marked files, in the order they're displayed in,
+    /// if exactly two files are marked.
+    fn marked_files_to_compare(&self, cx: &App) -> Option<(ProjectPath, ProjectPath)> {
+        let project = self.project.read(cx);
+        let mut paths = self
+            .effective_entries_in_display_order()
+            .into_iter()
+            .map(|selection| {
+                let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
+                let entry = worktree.read(cx).entry_for_id(selection.entry_id)?;
+                entry.is_file().then(|| ProjectPath {
+                    worktree_id: selection.worktree_id,
+                    path: entry.path.clone(),
+                })
+            });
+        let old_path = paths.next()??;
+        let new_path = paths.next()??;
+        if paths.next().is_some() {
+            return None;
         }
+        Some((old_path, new_path))
     }
 
     pub fn new_search_in_directory(
@@ -2607,6 +2748,15 @@ impl ProjectPanel {
         sanitized_entries
     }
 
+    fn effective_entries_in_display_order(&self) -> Vec<SelectedEntry> {
+        let mut entries = self.effective_entries().into_iter().collect::<Vec<_>>();
+        entries.sort_by_cached_key(|entry| {
+            self.index_for_selection(*entry)
+                .map_or(usize::MAX, |(_, _, visible_index)| visible_index)
+        });
+        entries
+    }
+
     fn effective_entries(&self) -> BTreeSet<SelectedEntry> {
         if let Some(selection) = self.selection {
             let selection = SelectedEntry {
@@ -2710,6 +2860,7 @@ impl ProjectPanel {
         let settings = ProjectPanelSettings::get_global(cx);
         let auto_collapse_dirs = settings.auto_fold_dirs;
         let hide_gitignore = settings.hide_gitignore;
+        let file_nesting = &settings.file_nesting;
         let project = self.project.read(cx);
         let repo_snapshots = project.git_store().read(cx).repo_snapshots(cx);
         self.last_worktree_root_id = project
@@ -2720,6 +2871,7 @@ impl ProjectPanel {
 
         let old_ancestors = std::mem::take(&mut self.ancestors);
         self.visible_entries.clear();
+        self.nested_files.clear();
         let mut max_width_item = None;
         for worktree in project.visible_worktrees(cx) {
             let worktree_snapshot = worktree.read(cx).snapshot();
@@ -2910,6 +3062,13 @@ impl ProjectPanel {
             }
 
             project::sort_worktree_entries(&mut visible_worktree_entries);
+            if file_nesting.enabled {
+                self.nested_files.nest(
+                    &mut visible_worktree_entries,
+                    expanded_dir_ids,
+                    &file_nesting.patterns,
+                );
+            }
 
             self.visible_entries
                 .push((worktree_id, visible_worktree_entries, OnceCell::new()));
@@ -2953,6 +3112,12 @@ impl ProjectPanel {
                 project.expand_entry(worktree_id, entry_id, cx);
                 let worktree = worktree.read(cx);
 
+                if let Some(parent_id) = self.nested_files.parent(entry_id) {
+                    if let Err(ix) = expanded_dir_ids.binary_search(&parent_id) {
+                        expanded_dir_ids.insert(ix, parent_id);
+                    }
+                }
+
                 if let Some(mut entry) = worktree.entry_for_id(entry_id) {
                     loop {
                         if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
@@ -3254,8 +3419,11 @@ impl ProjectPanel {
                         }
                     };
 
-                    let (depth, difference) =
+                    let (mut depth, difference) =
                         ProjectPanel::calculate_depth_and_difference(&entry, entries);
+                    if self.nested_files.parent(entry.id).is_some() {
+                        depth += 1;
+                    }
 
                     let filename = match difference {
                         diff if diff > 1 => entry
@@ -3295,6 +3463,7 @@ impl ProjectPanel {
                         kind: entry.kind,
                         is_ignored: entry.is_ignored,
                         is_expanded,
+                        has_nested_files: self.nested_files.has_nested_files(entry.id),
                         is_selected: self.selection == Some(selection),
                         is_marked,
                         is_editing: false,
@@ -3696,6 +3865,8 @@ impl ProjectPanel {
 
         let depth = details.depth;
         let worktree_id = details.worktree_id;
+        let has_nested_files = details.has_nested_files;
+        let is_expanded = details.is_expanded;
         let selections = Arc::new(self.marked_entries.clone());
         let is_local = self.project.read(cx).is_local();
 
@@ -3975,6 +4146,13 @@ impl ProjectPanel {
                         }
                     })
                     .selectable(false)
+                    .when(has_nested_files, |this| {
+                        this.toggle(is_expanded).on_toggle(cx.listener(
+                            move |this, _, window, cx| {
+                                this.toggle_expanded(entry_id, window, cx);
+                            },
+                        ))
+                    })
                     .when_some(canonical_path, |this, path| {
                         this.end_slot::<AnyElement>(
                             div()
@@ -4637,6 +4815,8 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::cancel))
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
+                .on_action(cx.listener(Self::compare_marked_files))
+                .on_action(cx.listener(Self::compare_with_clipboard))
                 .on_action(cx.listener(Self::new_search_in_directory))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
@@ -1,3 +1,4 @@
+use collections::BTreeMap;
 use editor::ShowScrollbar;
 use gpui::Pixels;
 use schemars::JsonSchema;
@@ -28,7 +29,7 @@ pub enum EntrySpacing {
     Standard,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct ProjectPanelSettings {
     pub button: bool,
     pub hide_gitignore: bool,
@@ -44,6 +45,27 @@ pub struct ProjectPanelSettings {
     pub auto_fold_dirs: bool,
     pub scrollbar: ScrollbarSettings,
     pub show_diagnostics: ShowDiagnostics,
+    pub file_nesting: FileNestingSettings,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FileNestingSettings {
+    pub enabled: bool,
+    pub patterns: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FileNestingSettingsContent {
+    /// Whether to nest related files under a parent file in the same directory.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Nesting rules, mapping the name of a parent file to the names of the files
+    /// to nest under it.
+    ///
+    /// A `*` in the parent name matches any text, which can be referred to in the
+    /// nested file names as `$(capture)`. Nested file names may also contain `*`.
+    pub patterns: Option<BTreeMap<String, Vec<String>>>,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -145,6 +167,8 @@ pub struct ProjectPanelSettingsContent {
     pub show_diagnostics: Option<ShowDiagnostics>,
     /// Settings related to indent guides in the project panel.
     pub indent_guides: Option<IndentGuidesSettingsContent>,
+    /// Settings related to nesting files under other files in the project panel.
+    pub file_nesting: Option<FileNestingSettingsContent>,
 }
 
 impl Settings for ProjectPanelSettings {
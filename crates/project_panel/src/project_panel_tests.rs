@@ -318,7 +318,7 @@ async fn test_auto_collapse_dir_paths(cx: &mut gpui::TestAppContext) {
     let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: true,
@@ -3847,7 +3847,7 @@ async fn test_deletion_gitignored(cx: &mut gpui::TestAppContext) {
 
     // Test 1: Auto selection with one gitignored file next to the deleted file
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_gitignore: true,
@@ -3944,7 +3944,7 @@ async fn test_nested_deletion_gitignore(cx: &mut gpui::TestAppContext) {
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_gitignore: true,
@@ -4573,6 +4573,113 @@ fn toggle_expand_dir(
     });
 }
 
+#[gpui::test]
+async fn test_file_nesting(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        path!("/root"),
+        json!({
+            "Cargo.lock": "",
+            "Cargo.toml": "",
+            "src": {
+                "main.js": "",
+                "main.js.map": "",
+                "main.ts": "",
+                "util.ts": "",
+            }
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    cx.update(|_, cx| {
+        let settings = ProjectPanelSettings::get_global(cx).clone();
+        ProjectPanelSettings::override_global(
+            ProjectPanelSettings {
+                file_nesting: project_panel_settings::FileNestingSettings {
+                    enabled: true,
+                    patterns: [
+                        ("Cargo.toml", vec!["Cargo.lock"]),
+                        ("*.ts", vec!["$(capture).js"]),
+                        ("*.js", vec!["$(capture).js.map"]),
+                    ]
+                    .into_iter()
+                    .map(|(parent, children)| {
+                        (
+                            parent.to_string(),
+                            children.into_iter().map(ToString::to_string).collect(),
+                        )
+                    })
+                    .collect(),
+                },
+                ..settings
+            },
+            cx,
+        );
+    });
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    toggle_expand_dir(&panel, "root/src", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src  <== selected",
+            "        > main.ts",
+            "          util.ts",
+            "    > Cargo.toml",
+        ]
+    );
+
+    // Files nested under other nested files are nested under the outermost file.
+    toggle_expand_dir(&panel, "root/src/main.ts", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "        v main.ts  <== selected",
+            "              main.js",
+            "              main.js.map",
+            "          util.ts",
+            "    > Cargo.toml",
+        ]
+    );
+
+    // Collapsing a nested file collapses the file it's nested under.
+    select_path(&panel, "root/src/main.js.map", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.collapse_selected_entry(&CollapseSelectedEntry, window, cx)
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "        > main.ts  <== selected",
+            "          util.ts",
+            "    > Cargo.toml",
+        ]
+    );
+
+    toggle_expand_dir(&panel, "root/Cargo.toml", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "        > main.ts",
+            "          util.ts",
+            "    v Cargo.toml  <== selected",
+            "          Cargo.lock",
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
     init_test_with_editor(cx);
@@ -4613,7 +4720,7 @@ async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
 
     // Test 1: When auto-fold is enabled
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: true,
@@ -4675,7 +4782,7 @@ async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
 
     // Test 2: When auto-fold is disabled
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: false,
@@ -4839,7 +4946,7 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     // Test 2: With auto-fold enabled
     {
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: true,
@@ -4894,7 +5001,7 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     // Test 3: With auto-fold disabled
     {
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: false,
@@ -5033,7 +5140,7 @@ fn visible_entries_as_strings(
             }
 
             let indent = "    ".repeat(details.depth);
-            let icon = if details.kind.is_dir() {
+            let icon = if details.kind.is_dir() || details.has_nested_files {
                 if details.is_expanded { "v " } else { "> " }
             } else {
                 "  "
@@ -28,6 +28,7 @@ messages!(
     (ActivateToolchain, Foreground),
     (ActiveToolchain, Foreground),
     (ActiveToolchainResponse, Foreground),
+    (AddComment, Foreground),
     (AddNotification, Foreground),
     (AddProjectCollaborator, Foreground),
     (AddWorktree, Foreground),
@@ -57,6 +58,7 @@ messages!(
     (CreateBufferForPeer, Foreground),
     (CreateChannel, Foreground),
     (CreateChannelResponse, Foreground),
+    (CreateCommentThread, Foreground),
     (CreateContext, Foreground),
     (CreateContextResponse, Foreground),
     (CreateProjectEntry, Foreground),
@@ -89,6 +91,8 @@ messages!(
     (GetChannelMessages, Background),
     (GetChannelMessagesById, Background),
     (GetChannelMessagesResponse, Background),
+    (GetChannelThreadMessages, Background),
+    (GetPinnedChannelMessages, Background),
     (GetCodeActions, Background),
     (GetCodeActionsResponse, Background),
     (GetCompletions, Background),
@@ -233,6 +237,7 @@ messages!(
     (SendChannelMessage, Background),
     (SendChannelMessageResponse, Background),
     (SetChannelMemberRole, Foreground),
+    (SetChannelMessagePinned, Foreground),
     (SetChannelVisibility, Foreground),
     (SetRoomParticipantRole, Foreground),
     (ShareProject, Foreground),
@@ -245,6 +250,7 @@ messages!(
     (SyncExtensions, Background),
     (SyncExtensionsResponse, Background),
     (BreakpointsForFile, Background),
+    (ToggleChannelMessageReaction, Foreground),
     (ToggleBreakpoint, Foreground),
     (SynchronizeBuffers, Foreground),
     (SynchronizeBuffersResponse, Foreground),
@@ -263,6 +269,7 @@ messages!(
     (UpdateChannelBufferCollaborators, Foreground),
     (UpdateChannelMessage, Foreground),
     (UpdateChannels, Foreground),
+    (UpdateCommentThread, Foreground),
     (UpdateContacts, Foreground),
     (UpdateContext, Foreground),
     (UpdateDiagnosticSummary, Foreground),
@@ -281,6 +288,11 @@ messages!(
     (UpdateWorktreeSettings, Foreground),
     (UpdateRepository, Foreground),
     (RemoveRepository, Foreground),
+    (RemoveCommentThread, Foreground),
+    (DeleteComment, Foreground),
+    (SetCommentThreadResolved, Foreground),
+    (GetCommentThreads, Background),
+    (GetCommentThreadsResponse, Background),
     (UsersResponse, Foreground),
     (GitReset, Background),
     (GitCheckoutFiles, Background),
@@ -302,6 +314,8 @@ messages!(
     (GitDiff, Background),
     (GitDiffResponse, Background),
     (GitInit, Background),
+    (LoadMergeStages, Background),
+    (LoadMergeStagesResponse, Background),
 );
 
 request_messages!(
@@ -332,6 +346,8 @@ request_messages!(
     (GetChannelMembers, GetChannelMembersResponse),
     (GetChannelMessages, GetChannelMessagesResponse),
     (GetChannelMessagesById, GetChannelMessagesResponse),
+    (GetChannelThreadMessages, GetChannelMessagesResponse),
+    (GetPinnedChannelMessages, GetChannelMessagesResponse),
     (GetCodeActions, GetCodeActionsResponse),
     (GetCompletions, GetCompletionsResponse),
     (GetDefinition, GetDefinitionResponse),
@@ -385,6 +401,8 @@ request_messages!(
     (RemoveChannelMember, Ack),
     (RemoveChannelMessage, Ack),
     (UpdateChannelMessage, Ack),
+    (ToggleChannelMessageReaction, Ack),
+    (SetChannelMessagePinned, Ack),
     (RemoveContact, Ack),
     (RenameChannel, RenameChannelResponse),
     (RenameProjectEntry, ProjectEntryResponse),
@@ -459,7 +477,13 @@ request_messages!(
     (CheckForPushedCommits, CheckForPushedCommitsResponse),
     (GitDiff, GitDiffResponse),
     (GitInit, Ack),
+    (LoadMergeStages, LoadMergeStagesResponse),
     (ToggleBreakpoint, Ack),
+    (GetCommentThreads, GetCommentThreadsResponse),
+    (CreateCommentThread, Ack),
+    (AddComment, Ack),
+    (DeleteComment, Ack),
+    (SetCommentThreadResolved, Ack),
 );
 
 entity_messages!(
@@ -577,8 +601,16 @@ entity_messages!(
     CheckForPushedCommits,
     GitDiff,
     GitInit,
+    LoadMergeStages,
     BreakpointsForFile,
     ToggleBreakpoint,
+    GetCommentThreads,
+    CreateCommentThread,
+    AddComment,
+    DeleteComment,
+    SetCommentThreadResolved,
+    UpdateCommentThread,
+    RemoveCommentThread,
 );
 
 entity_messages!(
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use gpui::{ClickEvent, DismissEvent, EventEmitter, FocusHandle, Focusable, Render, WeakEntity};
 use project::project_settings::ProjectSettings;
-use remote::SshConnectionOptions;
+use remote::RemoteConnectionOptions;
 use settings::Settings;
 use ui::{
     Button, ButtonCommon, ButtonStyle, Clickable, Context, ElevationIndex, FluentBuilder, Headline,
@@ -15,7 +15,7 @@ use crate::open_ssh_project;
 
 enum Host {
     RemoteProject,
-    SshRemoteProject(SshConnectionOptions),
+    SshRemoteProject(RemoteConnectionOptions),
 }
 
 pub struct DisconnectedOverlay {
@@ -66,7 +66,7 @@ impl DisconnectedOverlay {
                 }
                 let handle = cx.entity().downgrade();
 
-                let ssh_connection_options = project.read(cx).ssh_connection_options(cx);
+                let ssh_connection_options = project.read(cx).remote_connection_options(cx);
                 let host = if let Some(ssh_connection_options) = ssh_connection_options {
                     Host::SshRemoteProject(ssh_connection_options)
                 } else {
@@ -98,7 +98,7 @@ impl DisconnectedOverlay {
 
     fn reconnect_to_ssh_remote(
         &self,
-        connection_options: SshConnectionOptions,
+        connection_options: RemoteConnectionOptions,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
@@ -106,17 +106,27 @@ impl DisconnectedOverlay {
             return;
         };
 
-        let Some(ssh_project) = workspace.read(cx).serialized_ssh_project() else {
-            return;
-        };
-
         let Some(window_handle) = window.window_handle().downcast::<Workspace>() else {
             return;
         };
 
         let app_state = workspace.read(cx).app_state().clone();
 
-        let paths = ssh_project.paths.iter().map(PathBuf::from).collect();
+        // Only SSH projects are serialized, so reopen other connections' worktrees directly.
+        let paths = if let Some(ssh_project) = workspace.read(cx).serialized_ssh_project() {
+            ssh_project.paths.iter().map(PathBuf::from).collect()
+        } else {
+            workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+        if paths.is_empty() {
+            return;
+        }
 
         cx.spawn_in(window, async move |_, cx| {
             open_ssh_project(
@@ -322,11 +322,7 @@ impl PickerDelegate for RecentProjectsDelegate {
                                 };
 
                                 let connection_options = SshSettings::get_global(cx)
-                                    .connection_options_for(
-                                        ssh_project.host.clone(),
-                                        ssh_project.port,
-                                        ssh_project.user.clone(),
-                                    );
+                                    .connection_options_for_project(ssh_project);
 
                                 let paths = ssh_project.paths.iter().map(PathBuf::from).collect();
 
@@ -18,6 +18,7 @@ use gpui::{
 };
 use picker::Picker;
 use project::Project;
+use remote::RemoteConnectionOptions;
 use remote::SshConnectionOptions;
 use remote::SshRemoteClient;
 use remote::ssh_session::ConnectionIdentifier;
@@ -39,6 +40,7 @@ use workspace::{
 };
 
 use crate::OpenRemote;
+use crate::ssh_connections::CommandConnection;
 use crate::ssh_connections::RemoteSettingsContent;
 use crate::ssh_connections::SshConnection;
 use crate::ssh_connections::SshConnectionHeader;
@@ -121,8 +123,8 @@ impl Focusable for ProjectPicker {
 
 impl ProjectPicker {
     fn new(
-        ix: usize,
-        connection: SshConnectionOptions,
+        server_index: ServerIndex,
+        connection: RemoteConnectionOptions,
         project: Entity<Project>,
         workspace: WeakEntity<Workspace>,
         window: &mut Window,
@@ -141,7 +143,9 @@ impl ProjectPicker {
             picker
         });
         let connection_string = connection.connection_string().into();
-        let nickname = connection.nickname.clone().map(|nick| nick.into());
+        let nickname = connection
+            .nickname()
+            .map(|nick| SharedString::from(nick.to_string()));
         let _path_task = cx
             .spawn_in(window, {
                 let workspace = workspace.clone();
@@ -170,12 +174,8 @@ impl ProjectPicker {
                                 .map(|path| path.to_string_lossy().to_string())
                                 .collect();
                             move |setting, _| {
-                                if let Some(server) = setting
-                                    .ssh_connections
-                                    .as_mut()
-                                    .and_then(|connections| connections.get_mut(ix))
-                                {
-                                    server.projects.insert(SshProject { paths });
+                                if let Some(projects) = server_index.projects_mut(setting) {
+                                    projects.insert(SshProject { paths });
                                 }
                             }
                         });
@@ -237,12 +237,90 @@ impl gpui::Render for ProjectPicker {
     }
 }
 
+/// Where a server is configured in the remote settings.
+#[derive(Clone, Copy)]
+enum ServerIndex {
+    Ssh(usize),
+    Command(usize),
+}
+
+impl ServerIndex {
+    fn projects_mut(
+        self,
+        settings: &mut RemoteSettingsContent,
+    ) -> Option<&mut BTreeSet<SshProject>> {
+        match self {
+            Self::Ssh(ix) => settings
+                .ssh_connections
+                .as_mut()?
+                .get_mut(ix)
+                .map(|connection| &mut connection.projects),
+            Self::Command(ix) => settings
+                .command_connections
+                .as_mut()?
+                .get_mut(ix)
+                .map(|connection| &mut connection.projects),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum ServerConnection {
+    Ssh(SshConnection),
+    Command(CommandConnection),
+}
+
+impl ServerConnection {
+    fn configured(cx: &App) -> impl Iterator<Item = (ServerIndex, ServerConnection)> + use<> {
+        let settings = SshSettings::get_global(cx);
+        let ssh_connections = settings
+            .ssh_connections()
+            .enumerate()
+            .map(|(ix, connection)| (ServerIndex::Ssh(ix), Self::Ssh(connection)));
+        let command_connections = settings
+            .command_connections()
+            .enumerate()
+            .map(|(ix, connection)| (ServerIndex::Command(ix), Self::Command(connection)));
+        ssh_connections.chain(command_connections)
+    }
+
+    fn projects(&self) -> &BTreeSet<SshProject> {
+        match self {
+            Self::Ssh(connection) => &connection.projects,
+            Self::Command(connection) => &connection.projects,
+        }
+    }
+
+    /// Returns the label to show for this server, and the address to show next to it, if any.
+    fn labels(&self) -> (SharedString, Option<SharedString>) {
+        let (nickname, address) = match self {
+            Self::Ssh(connection) => (connection.nickname.clone(), connection.host.clone()),
+            Self::Command(connection) => (
+                connection.nickname.clone(),
+                connection.command.clone().into(),
+            ),
+        };
+        match nickname {
+            Some(nickname) => (nickname.into(), Some(format!("({address})").into())),
+            None => (address, None),
+        }
+    }
+
+    fn connection_options(&self) -> anyhow::Result<RemoteConnectionOptions> {
+        match self {
+            Self::Ssh(connection) => Ok(SshConnectionOptions::from(connection.clone()).into()),
+            Self::Command(connection) => Ok(connection.connection_options()?.into()),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct ProjectEntry {
     open_folder: NavigableEntry,
     projects: Vec<(NavigableEntry, SshProject)>,
     configure: NavigableEntry,
-    connection: SshConnection,
+    index: ServerIndex,
+    connection: ServerConnection,
 }
 
 #[derive(Clone)]
@@ -256,13 +334,12 @@ impl DefaultState {
         let handle = ScrollHandle::new();
         let scrollbar = ScrollbarState::new(handle.clone());
         let add_new_server = NavigableEntry::new(&handle, cx);
-        let servers = SshSettings::get_global(cx)
-            .ssh_connections()
-            .map(|connection| {
+        let servers = ServerConnection::configured(cx)
+            .map(|(index, connection)| {
                 let open_folder = NavigableEntry::new(&handle, cx);
                 let configure = NavigableEntry::new(&handle, cx);
                 let projects = connection
-                    .projects
+                    .projects()
                     .iter()
                     .map(|project| (NavigableEntry::new(&handle, cx), project.clone()))
                     .collect();
@@ -270,6 +347,7 @@ impl DefaultState {
                     open_folder,
                     configure,
                     projects,
+                    index,
                     connection,
                 }
             })
@@ -341,9 +419,9 @@ impl RemoteServerProjects {
         }
     }
 
-    pub fn project_picker(
-        ix: usize,
-        connection_options: remote::SshConnectionOptions,
+    fn project_picker(
+        server_index: ServerIndex,
+        connection_options: RemoteConnectionOptions,
         project: Entity<Project>,
         window: &mut Window,
         cx: &mut Context<Self>,
@@ -351,7 +429,7 @@ impl RemoteServerProjects {
     ) -> Self {
         let mut this = Self::new(window, cx, workspace.clone());
         this.mode = Mode::ProjectPicker(ProjectPicker::new(
-            ix,
+            server_index,
             connection_options,
             project,
             workspace,
@@ -386,11 +464,12 @@ impl RemoteServerProjects {
                 return;
             }
         };
-        let ssh_prompt = cx.new(|cx| SshPrompt::new(&connection_options, window, cx));
+        let ssh_prompt =
+            cx.new(|cx| SshPrompt::new(&connection_options.clone().into(), window, cx));
 
         let connection = connect_over_ssh(
             ConnectionIdentifier::setup(),
-            connection_options.clone(),
+            connection_options.clone().into(),
             ssh_prompt.clone(),
             window,
             cx,
@@ -455,8 +534,8 @@ impl RemoteServerProjects {
 
     fn create_ssh_project(
         &mut self,
-        ix: usize,
-        ssh_connection: SshConnection,
+        server_index: ServerIndex,
+        connection: ServerConnection,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
@@ -464,7 +543,13 @@ impl RemoteServerProjects {
             return;
         };
 
-        let connection_options = ssh_connection.into();
+        let connection_options = match connection.connection_options() {
+            Ok(connection_options) => connection_options,
+            Err(error) => {
+                workspace.update(cx, |workspace, cx| workspace.show_error(&error, cx));
+                return;
+            }
+        };
         workspace.update(cx, |_, cx| {
             cx.defer_in(window, move |workspace, window, cx| {
                 workspace.toggle_modal(window, cx, |window, cx| {
@@ -524,7 +609,7 @@ impl RemoteServerProjects {
                             );
                             workspace.toggle_modal(window, cx, |window, cx| {
                                 RemoteServerProjects::project_picker(
-                                    ix,
+                                    server_index,
                                     connection_options,
                                     project,
                                     window,
@@ -598,13 +683,7 @@ impl RemoteServerProjects {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
-        let (main_label, aux_label) = if let Some(nickname) = ssh_server.connection.nickname.clone()
-        {
-            let aux_label = SharedString::from(format!("({})", ssh_server.connection.host));
-            (nickname.into(), Some(aux_label))
-        } else {
-            (ssh_server.connection.host.clone(), None)
-        };
+        let (main_label, aux_label) = ssh_server.connection.labels();
         v_flex()
             .w_full()
             .child(ListSeparator)
@@ -651,7 +730,7 @@ impl RemoteServerProjects {
                                 let ssh_connection = ssh_server.clone();
                                 move |this, _: &menu::Confirm, window, cx| {
                                     this.create_ssh_project(
-                                        ix,
+                                        ssh_connection.index,
                                         ssh_connection.connection.clone(),
                                         window,
                                         cx,
@@ -674,7 +753,7 @@ impl RemoteServerProjects {
                                         let ssh_connection = ssh_server.clone();
                                         move |this, _, window, cx| {
                                             this.create_ssh_project(
-                                                ix,
+                                                ssh_connection.index,
                                                 ssh_connection.connection.clone(),
                                                 window,
                                                 cx,
@@ -683,45 +762,94 @@ impl RemoteServerProjects {
                                     })),
                             ),
                     )
-                    .child(
-                        h_flex()
-                            .id(("server-options-container", ix))
-                            .track_focus(&ssh_server.configure.focus_handle)
-                            .anchor_scroll(ssh_server.configure.scroll_anchor.clone())
-                            .on_action(cx.listener({
-                                let ssh_connection = ssh_server.clone();
-                                move |this, _: &menu::Confirm, window, cx| {
-                                    this.view_server_options(
-                                        (ix, ssh_connection.connection.clone()),
-                                        window,
-                                        cx,
-                                    );
-                                }
-                            }))
-                            .child(
-                                ListItem::new(("server-options", ix))
-                                    .toggle_state(
-                                        ssh_server
-                                            .configure
-                                            .focus_handle
-                                            .contains_focused(window, cx),
-                                    )
-                                    .inset(true)
-                                    .spacing(ui::ListItemSpacing::Sparse)
-                                    .start_slot(Icon::new(IconName::Settings).color(Color::Muted))
-                                    .child(Label::new("View Server Options"))
-                                    .on_click(cx.listener({
-                                        let ssh_connection = ssh_server.clone();
-                                        move |this, _, window, cx| {
-                                            this.view_server_options(
-                                                (ix, ssh_connection.connection.clone()),
-                                                window,
-                                                cx,
-                                            );
-                                        }
-                                    })),
-                            ),
-                    ),
+                    .child(match &ssh_server.connection {
+                        ServerConnection::Ssh(connection) => self
+                            .render_view_server_options_entry(
+                                ix,
+                                &ssh_server,
+                                connection.clone(),
+                                window,
+                                cx,
+                            )
+                            .into_any_element(),
+                        ServerConnection::Command(_) => self
+                            .render_remove_connection_entry(ix, &ssh_server, window, cx)
+                            .into_any_element(),
+                    }),
+            )
+    }
+
+    fn render_view_server_options_entry(
+        &mut self,
+        ix: usize,
+        ssh_server: &ProjectEntry,
+        connection: SshConnection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let ServerIndex::Ssh(server_index) = ssh_server.index else {
+            unreachable!("only SSH servers have options")
+        };
+        h_flex()
+            .id(("server-options-container", ix))
+            .track_focus(&ssh_server.configure.focus_handle)
+            .anchor_scroll(ssh_server.configure.scroll_anchor.clone())
+            .on_action(cx.listener({
+                let connection = connection.clone();
+                move |this, _: &menu::Confirm, window, cx| {
+                    this.view_server_options((server_index, connection.clone()), window, cx);
+                }
+            }))
+            .child(
+                ListItem::new(("server-options", ix))
+                    .toggle_state(
+                        ssh_server
+                            .configure
+                            .focus_handle
+                            .contains_focused(window, cx),
+                    )
+                    .inset(true)
+                    .spacing(ui::ListItemSpacing::Sparse)
+                    .start_slot(Icon::new(IconName::Settings).color(Color::Muted))
+                    .child(Label::new("View Server Options"))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.view_server_options((server_index, connection.clone()), window, cx);
+                    })),
+            )
+    }
+
+    fn render_remove_connection_entry(
+        &mut self,
+        ix: usize,
+        ssh_server: &ProjectEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let ServerIndex::Command(server_index) = ssh_server.index else {
+            unreachable!("only command connections are removed from the server list")
+        };
+        h_flex()
+            .id(("remove-connection-container", ix))
+            .track_focus(&ssh_server.configure.focus_handle)
+            .anchor_scroll(ssh_server.configure.scroll_anchor.clone())
+            .on_action(cx.listener(move |this, _: &menu::Confirm, _, cx| {
+                this.delete_command_server(server_index, cx);
+            }))
+            .child(
+                ListItem::new(("remove-connection", ix))
+                    .toggle_state(
+                        ssh_server
+                            .configure
+                            .focus_handle
+                            .contains_focused(window, cx),
+                    )
+                    .inset(true)
+                    .spacing(ui::ListItemSpacing::Sparse)
+                    .start_slot(Icon::new(IconName::Trash).color(Color::Muted))
+                    .child(Label::new("Remove Connection"))
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.delete_command_server(server_index, cx);
+                    })),
             )
     }
 
@@ -735,6 +863,7 @@ impl RemoteServerProjects {
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
         let server = server.clone();
+        let server_index = server.index;
         let element_id_base = SharedString::from(format!("remote-project-{server_ix}"));
         let container_element_id_base =
             SharedString::from(format!("remote-project-container-{element_id_base}"));
@@ -750,16 +879,19 @@ impl RemoteServerProjects {
                     return;
                 };
                 let project = project.clone();
-                let server = server.connection.clone();
+                let connection_options = server.connection.connection_options();
                 cx.emit(DismissEvent);
                 cx.spawn_in(window, async move |_, cx| {
-                    let result = open_ssh_project(
-                        server.into(),
-                        project.paths.into_iter().map(PathBuf::from).collect(),
-                        app_state,
-                        OpenOptions::default(),
-                        cx,
-                    )
+                    let result = async {
+                        open_ssh_project(
+                            connection_options?,
+                            project.paths.into_iter().map(PathBuf::from).collect(),
+                            app_state,
+                            OpenOptions::default(),
+                            cx,
+                        )
+                        .await
+                    }
                     .await;
                     if let Err(e) = result {
                         log::error!("Failed to connect: {:?}", e);
@@ -811,7 +943,7 @@ impl RemoteServerProjects {
                                     .size(ButtonSize::Large)
                                     .tooltip(Tooltip::text("Delete Remote Project"))
                                     .on_click(cx.listener(move |this, _, _, cx| {
-                                        this.delete_ssh_project(server_ix, &project, cx)
+                                        this.delete_ssh_project(server_index, &project, cx)
                                     }))
                             })
                             .into_any_element(),
@@ -842,15 +974,24 @@ impl RemoteServerProjects {
         });
     }
 
-    fn delete_ssh_project(&mut self, server: usize, project: &SshProject, cx: &mut Context<Self>) {
+    fn delete_command_server(&mut self, server: usize, cx: &mut Context<Self>) {
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(connections) = setting.command_connections.as_mut() {
+                connections.remove(server);
+            }
+        });
+    }
+
+    fn delete_ssh_project(
+        &mut self,
+        server: ServerIndex,
+        project: &SshProject,
+        cx: &mut Context<Self>,
+    ) {
         let project = project.clone();
         self.update_settings_file(cx, move |setting, _| {
-            if let Some(server) = setting
-                .ssh_connections
-                .as_mut()
-                .and_then(|connections| connections.get_mut(server))
-            {
-                server.projects.remove(&project);
+            if let Some(projects) = server.projects_mut(setting) {
+                projects.remove(&project);
             }
         });
     }
@@ -1242,16 +1383,14 @@ impl RemoteServerProjects {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
-        if SshSettings::get_global(cx)
-            .ssh_connections
-            .as_ref()
-            .map_or(false, |connections| {
-                state
-                    .servers
-                    .iter()
-                    .map(|server| &server.connection)
-                    .ne(connections.iter())
-            })
+        if state
+            .servers
+            .iter()
+            .map(|server| &server.connection)
+            .ne(ServerConnection::configured(cx)
+                .map(|(_, connection)| connection)
+                .collect::<Vec<_>>()
+                .iter())
         {
             self.mode = Mode::default_mode(cx);
             if let Mode::Default(new_state) = &self.mode {
@@ -16,7 +16,10 @@ use language::CursorShape;
 use markdown::{Markdown, MarkdownElement, MarkdownStyle};
 use release_channel::ReleaseChannel;
 use remote::ssh_session::{ConnectionIdentifier, SshPortForwardOption};
-use remote::{SshConnectionOptions, SshPlatform, SshRemoteClient};
+use remote::{
+    CommandConnectionOptions, RemoteConnectionOptions, SshConnectionOptions, SshPlatform,
+    SshRemoteClient,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -25,11 +28,12 @@ use ui::{
     ActiveTheme, Color, Context, Icon, IconName, IconSize, InteractiveElement, IntoElement, Label,
     LabelCommon, Styled, Window, prelude::*,
 };
-use workspace::{AppState, ModalView, Workspace};
+use workspace::{AppState, ModalView, SerializedSshProject, Workspace};
 
 #[derive(Deserialize)]
 pub struct SshSettings {
     pub ssh_connections: Option<Vec<SshConnection>>,
+    pub command_connections: Option<Vec<CommandConnection>>,
 }
 
 impl SshSettings {
@@ -37,6 +41,10 @@ impl SshSettings {
         self.ssh_connections.clone().into_iter().flatten()
     }
 
+    pub fn command_connections(&self) -> impl Iterator<Item = CommandConnection> + use<> {
+        self.command_connections.clone().into_iter().flatten()
+    }
+
     pub fn connection_options_for(
         &self,
         host: String,
@@ -64,6 +72,19 @@ impl SshSettings {
             ..Default::default()
         }
     }
+
+    /// The options to reopen a recent remote project with.
+    pub fn connection_options_for_project(
+        &self,
+        project: &SerializedSshProject,
+    ) -> RemoteConnectionOptions {
+        match &project.command {
+            Some(options) => options.clone().into(),
+            None => self
+                .connection_options_for(project.host.clone(), project.port, project.user.clone())
+                .into(),
+        }
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -107,6 +128,38 @@ impl From<SshConnection> for SshConnectionOptions {
     }
 }
 
+/// A remote host reached through a command that runs programs on it, rather than over SSH.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct CommandConnection {
+    /// The command that runs a program on the remote host, with the program and its
+    /// arguments appended, e.g. `docker exec -i my-container`.
+    pub command: String,
+    /// The command to run terminals with, which should allocate a pseudo-terminal, e.g.
+    /// `docker exec -it my-container`. Defaults to `command`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_command: Option<String>,
+    #[serde(default)]
+    pub projects: BTreeSet<SshProject>,
+    /// Name to use for this connection in UI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+}
+
+impl CommandConnection {
+    pub fn connection_options(&self) -> Result<CommandConnectionOptions> {
+        let mut options = CommandConnectionOptions::parse_command_line(&self.command)?;
+        options.terminal_command = self
+            .terminal_command
+            .as_deref()
+            .map(|command| {
+                CommandConnectionOptions::parse_command_line(command).map(|parsed| parsed.command)
+            })
+            .transpose()?;
+        options.nickname = self.nickname.clone();
+        Ok(options)
+    }
+}
+
 #[derive(Clone, Default, Serialize, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema)]
 pub struct SshProject {
     pub paths: Vec<String>,
@@ -115,6 +168,7 @@ pub struct SshProject {
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct RemoteSettingsContent {
     pub ssh_connections: Option<Vec<SshConnection>>,
+    pub command_connections: Option<Vec<CommandConnection>>,
 }
 
 impl Settings for SshSettings {
@@ -152,12 +206,14 @@ pub struct SshConnectionModal {
 
 impl SshPrompt {
     pub(crate) fn new(
-        connection_options: &SshConnectionOptions,
+        connection_options: &RemoteConnectionOptions,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         let connection_string = connection_options.connection_string().into();
-        let nickname = connection_options.nickname.clone().map(|s| s.into());
+        let nickname = connection_options
+            .nickname()
+            .map(|nickname| nickname.to_string().into());
 
         Self {
             connection_string,
@@ -288,7 +344,7 @@ impl Render for SshPrompt {
 
 impl SshConnectionModal {
     pub(crate) fn new(
-        connection_options: &SshConnectionOptions,
+        connection_options: &RemoteConnectionOptions,
         paths: Vec<PathBuf>,
         window: &mut Window,
         cx: &mut Context<Self>,
@@ -530,13 +586,13 @@ pub fn is_connecting_over_ssh(workspace: &Workspace, cx: &App) -> bool {
 
 pub fn connect_over_ssh(
     unique_identifier: ConnectionIdentifier,
-    connection_options: SshConnectionOptions,
+    connection_options: RemoteConnectionOptions,
     ui: Entity<SshPrompt>,
     window: &mut Window,
     cx: &mut App,
 ) -> Task<Result<Option<Entity<SshRemoteClient>>>> {
     let window = window.window_handle();
-    let known_password = connection_options.password.clone();
+    let known_password = connection_options.password();
     let (tx, rx) = oneshot::channel();
     ui.update(cx, |ui, _cx| ui.set_cancellation_tx(tx));
 
@@ -554,12 +610,13 @@ pub fn connect_over_ssh(
 }
 
 pub async fn open_ssh_project(
-    connection_options: SshConnectionOptions,
+    connection_options: impl Into<RemoteConnectionOptions>,
     paths: Vec<PathBuf>,
     app_state: Arc<AppState>,
     open_options: workspace::OpenOptions,
     cx: &mut AsyncApp,
 ) -> Result<()> {
+    let connection_options = connection_options.into();
     let window = if let Some(window) = open_options.replace_window {
         window
     } else {
@@ -603,7 +660,7 @@ pub async fn open_ssh_project(
                 Some(Arc::new(SshClientDelegate {
                     window: window.window_handle(),
                     ui: ui.downgrade(),
-                    known_password: connection_options.password.clone(),
+                    known_password: connection_options.password(),
                 }))
             }
         })?;
@@ -634,11 +691,15 @@ pub async fn open_ssh_project(
 
         if let Err(e) = did_open_ssh_project {
             log::error!("Failed to open project: {:?}", e);
+            let title = match &connection_options {
+                RemoteConnectionOptions::Ssh(_) => "Failed to connect over SSH",
+                RemoteConnectionOptions::Command(_) => "Failed to connect",
+            };
             let response = window
                 .update(cx, |_, window, cx| {
                     window.prompt(
                         PromptLevel::Critical,
-                        "Failed to connect over SSH",
+                        title,
                         Some(&e.to_string()),
                         &["Retry", "Ok"],
                         cx,
@@ -0,0 +1,359 @@
+use crate::{
+    shell_script,
+    ssh_session::{
+        RemoteConnection, RemoteConnectionOptions, RemoteShell, RemoteTerminalCommand,
+        SshClientDelegate, multiplex, start_proxy_script,
+    },
+};
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use futures::{
+    channel::{
+        mpsc::{Sender, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    future,
+};
+use gpui::{App, AppContext as _, AsyncApp, Task};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use rpc::proto::Envelope;
+use serde::{Deserialize, Serialize};
+use smol::{
+    fs,
+    process::{self, Stdio},
+};
+use std::{
+    iter,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering::SeqCst},
+    },
+};
+
+/// Options for reaching a remote host through any command that runs a program there with its
+/// stdio piped back, such as `docker exec -i my-container` or `kubectl exec -i my-pod --`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CommandConnectionOptions {
+    /// The command, to which the program to run on the remote host and its arguments are
+    /// appended. An empty command runs programs on this machine.
+    pub command: Vec<String>,
+    /// The command used for terminals, which should allocate a pseudo-terminal, e.g.
+    /// `docker exec -it my-container`. Defaults to `command`.
+    pub terminal_command: Option<Vec<String>>,
+    pub nickname: Option<String>,
+}
+
+impl CommandConnectionOptions {
+    pub fn parse_command_line(input: &str) -> Result<Self> {
+        let command = shlex::split(input).ok_or_else(|| anyhow!("invalid command: {input:?}"))?;
+        Ok(Self {
+            command,
+            ..Default::default()
+        })
+    }
+
+    /// Options that run the remote server as a subprocess of this machine, which exercises the
+    /// whole remote stack without a remote host.
+    pub fn local() -> Self {
+        Self::default()
+    }
+
+    pub fn connection_string(&self) -> String {
+        if self.command.is_empty() {
+            return "localhost".to_string();
+        }
+        shlex::try_join(self.command.iter().map(String::as_str))
+            .unwrap_or_else(|_| self.command.join(" "))
+    }
+
+    fn command_with_args(command: &[String], args: &[&str]) -> process::Command {
+        let mut argv = command
+            .iter()
+            .map(String::as_str)
+            .chain(args.iter().copied());
+        let mut command = util::command::new_smol_command(argv.next().unwrap_or("sh"));
+        command.args(argv);
+        command
+    }
+}
+
+pub(crate) struct CommandRemoteConnection {
+    options: CommandConnectionOptions,
+    remote_binary_path: Option<PathBuf>,
+    killed: AtomicBool,
+    /// Stops the proxy started by [`RemoteConnection::start_proxy`], which kills its process.
+    kill_proxy: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl CommandRemoteConnection {
+    pub(crate) async fn new(
+        options: CommandConnectionOptions,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        delegate.set_status(Some("Connecting"), cx);
+
+        let mut this = Self {
+            options,
+            remote_binary_path: None,
+            killed: AtomicBool::new(false),
+            kill_proxy: Mutex::new(None),
+        };
+        this.run_command("true", &[]).await.with_context(|| {
+            format!("failed to connect to {}", this.options.connection_string())
+        })?;
+        this.remote_binary_path = Some(this.install_server_binary(&delegate, cx).await?);
+
+        Ok(this)
+    }
+
+    // Like ssh, we run programs through a shell in the remote user's home directory, so that
+    // relative paths resolve the same way for both transports.
+    fn remote_command(&self, program: &str, args: &[&str]) -> process::Command {
+        let to_run = iter::once(&program)
+            .chain(args.iter())
+            .map(|token| shlex::try_quote(token).unwrap())
+            .join(" ");
+        let to_run = format!("cd; {to_run}");
+        log::debug!("{} {:?}", self.options.connection_string(), to_run);
+        let mut command = CommandConnectionOptions::command_with_args(
+            &self.options.command,
+            &["sh", "-c", &to_run],
+        );
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for CommandRemoteConnection {
+    async fn kill(&self) -> Result<()> {
+        // Other processes are spawned per-command, so the proxy is the only long-lived one.
+        self.killed.store(true, SeqCst);
+        if let Some(kill_proxy) = self.kill_proxy.lock().take() {
+            kill_proxy.send(()).ok();
+        }
+        Ok(())
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.killed.load(SeqCst)
+    }
+
+    fn terminal_command(&self) -> RemoteTerminalCommand {
+        let command = self
+            .options
+            .terminal_command
+            .as_ref()
+            .unwrap_or(&self.options.command);
+        let (program, args) = match command.split_first() {
+            Some((program, args)) => (program.clone(), args.to_vec()),
+            None => ("sh".to_string(), Vec::new()),
+        };
+        RemoteTerminalCommand {
+            program,
+            args,
+            join_shell_invocation: false,
+        }
+    }
+
+    fn connection_options(&self) -> RemoteConnectionOptions {
+        self.options.clone().into()
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: PathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let archive = util::command::new_smol_command("tar")
+            .arg("-C")
+            .arg(&src_path)
+            .args(["-czf", "-", "."])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let script = shell_script!(
+            "mkdir -p {dest_path} && tar -C {dest_path} -xzf -",
+            dest_path = &dest_path.to_string_lossy(),
+        );
+        let extract = self.remote_command("sh", &["-c", &script]).spawn();
+
+        cx.background_spawn(async move {
+            let mut archive = archive?;
+            let mut extract = extract?;
+            let mut archive_stdout = archive.stdout.take().unwrap();
+            let mut extract_stdin = extract.stdin.take().unwrap();
+            futures::io::copy(&mut archive_stdout, &mut extract_stdin).await?;
+            drop(extract_stdin);
+
+            let archive_output = archive.output().await?;
+            let extract_output = extract.output().await?;
+            if !archive_output.status.success() || !extract_output.status.success() {
+                return Err(anyhow!(
+                    "failed to upload directory {} -> {}: {}{}",
+                    src_path.display(),
+                    dest_path.display(),
+                    String::from_utf8_lossy(&archive_output.stderr),
+                    String::from_utf8_lossy(&extract_output.stderr)
+                ));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let Some(remote_binary_path) = self.remote_binary_path.clone() else {
+            return Task::ready(Err(anyhow!("Remote binary path not set")));
+        };
+
+        let start_proxy_command =
+            start_proxy_script(&remote_binary_path, &unique_identifier, reconnect);
+
+        let proxy_process = match self
+            .remote_command("sh", &["-c", &start_proxy_command])
+            // IMPORTANT: we kill this process when we drop the task that uses it.
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(process) => process,
+            Err(error) => {
+                return Task::ready(Err(anyhow!("failed to spawn remote server: {}", error)));
+            }
+        };
+
+        let proxy = multiplex(
+            proxy_process,
+            incoming_tx,
+            outgoing_rx,
+            connection_activity_tx,
+            &cx,
+        );
+        let (kill_tx, kill_rx) = oneshot::channel();
+        *self.kill_proxy.lock() = Some(kill_tx);
+        cx.spawn(async move |_| {
+            let killed = async move {
+                // A proxy that was replaced by a newer one keeps running until it exits.
+                if kill_rx.await.is_err() {
+                    future::pending::<()>().await;
+                }
+                Err(anyhow!("connection was killed"))
+            };
+            // Dropping the proxy task drops its process, which was spawned with `kill_on_drop`.
+            smol::future::or(proxy, killed).await
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteShell for CommandRemoteConnection {
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
+        let output = self.remote_command(program, args).output().await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(anyhow!(
+                "failed to run command: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    // The file is streamed through the stdin of the same command used for everything else,
+    // so that no side channel like scp is needed.
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
+        let mut file = fs::File::open(src_path).await?;
+        let script = shell_script!(
+            "cat > {dest_path}",
+            dest_path = &dest_path.to_string_lossy()
+        );
+        let mut child = self.remote_command("sh", &["-c", &script]).spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        futures::io::copy(&mut file, &mut stdin).await?;
+        drop(stdin);
+
+        let output = child.output().await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    fn upload_binary(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn local_connection() -> CommandRemoteConnection {
+        CommandRemoteConnection {
+            options: CommandConnectionOptions::local(),
+            remote_binary_path: None,
+            killed: AtomicBool::new(false),
+            kill_proxy: Mutex::new(None),
+        }
+    }
+
+    #[test]
+    fn test_local_run_command() {
+        let connection = local_connection();
+        let output = smol::block_on(connection.run_command("echo", &["a b", "$HOME"])).unwrap();
+        assert_eq!(output, "a b $HOME\n");
+
+        let error = smol::block_on(connection.run_command("sh", &["-c", "echo oops >&2; exit 1"]))
+            .unwrap_err();
+        assert!(error.to_string().contains("oops"), "{error}");
+    }
+
+    #[test]
+    fn test_local_upload_file() {
+        let connection = local_connection();
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("source file");
+        let dest_path = dir.path().join("uploaded file");
+        std::fs::write(&src_path, b"contents\n").unwrap();
+
+        smol::block_on(connection.upload_file(&src_path, &dest_path)).unwrap();
+        assert_eq!(std::fs::read(&dest_path).unwrap(), b"contents\n");
+    }
+
+    #[test]
+    fn test_parse_command_line() {
+        let options =
+            CommandConnectionOptions::parse_command_line("docker exec -i 'my container'").unwrap();
+        assert_eq!(options.command, ["docker", "exec", "-i", "my container"]);
+        assert_eq!(options.connection_string(), "docker exec -i 'my container'");
+        assert_eq!(
+            CommandConnectionOptions::local().connection_string(),
+            "localhost"
+        );
+    }
+}
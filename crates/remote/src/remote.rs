@@ -1,9 +1,11 @@
+pub mod command_session;
 pub mod json_log;
 pub mod protocol;
 pub mod proxy;
 pub mod ssh_session;
 
+pub use command_session::CommandConnectionOptions;
 pub use ssh_session::{
-    ConnectionState, SshClientDelegate, SshConnectionOptions, SshPlatform, SshRemoteClient,
-    SshRemoteEvent,
+    ConnectionState, RemoteConnectionOptions, RemoteTerminalCommand, SshClientDelegate,
+    SshConnectionOptions, SshPlatform, SshRemoteClient, SshRemoteEvent,
 };
@@ -38,6 +38,14 @@ pub enum Notification {
         sender_id: u64,
         channel_id: u64,
     },
+    ChannelMessageReply {
+        #[serde(rename = "entity_id")]
+        message_id: u64,
+        sender_id: u64,
+        channel_id: u64,
+        #[serde(default)]
+        thread_root_message_id: Option<u64>,
+    },
 }
 
 impl Notification {
@@ -96,6 +104,12 @@ mod tests {
                 channel_id: 30,
                 message_id: 1,
             },
+            Notification::ChannelMessageReply {
+                sender_id: 200,
+                channel_id: 30,
+                message_id: 2,
+                thread_root_message_id: Some(1),
+            },
         ] {
             let message = notification.to_proto();
             let deserialized = Notification::from_proto(&message).unwrap();
@@ -4,7 +4,7 @@ use crate::{
     ToggleRegex, ToggleReplace, ToggleWholeWord, buffer_search::Deploy,
 };
 use anyhow::Context as _;
-use collections::{HashMap, HashSet};
+use collections::{HashMap, HashSet, IndexMap};
 use editor::{
     Anchor, Editor, EditorElement, EditorEvent, EditorSettings, EditorStyle, MAX_TAB_TITLE_LEN,
     MultiBuffer, actions::SelectAll, items::active_match_index, scroll::Autoscroll,
@@ -16,14 +16,16 @@ use gpui::{
     Render, SharedString, Styled, Subscription, Task, TextStyle, UpdateGlobal, WeakEntity, Window,
     actions, div,
 };
-use language::{Buffer, Language};
+use language::{Bias, Buffer, Language};
 use menu::Confirm;
 use project::{
     Project, ProjectPath,
-    search::{SearchInputKind, SearchQuery},
+    search::{SearchInputKind, SearchQuery, SearchResult},
     search_history::SearchHistoryCursor,
 };
+use semantic_index::{ProjectIndex, SearchFilter, SemanticDb};
 use settings::Settings;
+use smol::channel::Receiver;
 use std::{
     any::{Any, TypeId},
     mem,
@@ -37,7 +39,7 @@ use ui::{
     Icon, IconButton, IconButtonShape, IconName, KeyBinding, Label, LabelCommon, LabelSize,
     Toggleable, Tooltip, h_flex, prelude::*, utils::SearchInputWidth, v_flex,
 };
-use util::paths::PathMatcher;
+use util::{ResultExt as _, paths::PathMatcher};
 use workspace::{
     DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
     ToolbarItemView, Workspace, WorkspaceId,
@@ -47,9 +49,18 @@ use workspace::{
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        ToggleSemanticSearch
+    ]
 );
 
+/// The number of chunks shown by a semantic search.
+const SEMANTIC_SEARCH_LIMIT: usize = 64;
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakEntity<Project>, ProjectSearchSettings>);
 
@@ -169,6 +180,7 @@ pub struct ProjectSearchView {
     filters_enabled: bool,
     replace_enabled: bool,
     included_opened_only: bool,
+    semantic_enabled: bool,
     regex_language: Option<Arc<Language>>,
     _subscriptions: Vec<Subscription>,
 }
@@ -238,24 +250,109 @@ impl ProjectSearch {
     }
 
     fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
-        let search = self.project.update(cx, |project, cx| {
-            project
-                .search_history_mut(SearchInputKind::Query)
-                .add(&mut self.search_history_cursor, query.as_str().to_string());
-            let included = query.as_inner().files_to_include().sources().join(",");
-            if !included.is_empty() {
-                project
-                    .search_history_mut(SearchInputKind::Include)
-                    .add(&mut self.search_included_history_cursor, included);
-            }
-            let excluded = query.as_inner().files_to_exclude().sources().join(",");
-            if !excluded.is_empty() {
-                project
-                    .search_history_mut(SearchInputKind::Exclude)
-                    .add(&mut self.search_excluded_history_cursor, excluded);
-            }
+        let project = self.project.clone();
+        let search = project.update(cx, |project, cx| {
+            self.add_to_search_history(&query, project);
             project.search(query.clone(), cx)
         });
+        self.show_results(query, search, cx);
+    }
+
+    /// Searches the project's semantic index for the chunks that best match the query, both by
+    /// meaning and by the terms they contain, rather than for every occurrence of the query.
+    fn semantic_search(
+        &mut self,
+        query: SearchQuery,
+        project_index: Entity<ProjectIndex>,
+        cx: &mut Context<Self>,
+    ) {
+        let project = self.project.clone();
+        project.update(cx, |project, _| self.add_to_search_history(&query, project));
+
+        let filter = SearchFilter {
+            files_to_include: query.files_to_include().clone(),
+            files_to_exclude: query.files_to_exclude().clone(),
+            languages: Vec::new(),
+        };
+        let search = project_index.read(cx).hybrid_search(
+            query.as_str().to_string(),
+            filter,
+            SEMANTIC_SEARCH_LIMIT,
+            cx,
+        );
+        let (result_tx, result_rx) = smol::channel::unbounded();
+        cx.spawn(async move |_, cx| {
+            let mut ranges_by_path = IndexMap::<ProjectPath, Vec<Range<usize>>>::default();
+            for result in search.await? {
+                let worktree_id = result.worktree.read_with(cx, |worktree, _| worktree.id())?;
+                ranges_by_path
+                    .entry(ProjectPath {
+                        worktree_id,
+                        path: result.path,
+                    })
+                    .or_default()
+                    .push(result.range);
+            }
+
+            for (project_path, mut ranges) in ranges_by_path {
+                let buffer = project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                    .await;
+                let Some(buffer) = buffer.log_err() else {
+                    continue;
+                };
+                // The index may be behind the buffer's contents, so clip the chunks to it.
+                ranges.sort_by_key(|range| range.start);
+                let ranges = buffer.read_with(cx, |buffer, _| {
+                    ranges
+                        .into_iter()
+                        .map(|range| {
+                            let start =
+                                buffer.clip_offset(range.start.min(buffer.len()), Bias::Left);
+                            let end = buffer.clip_offset(range.end.min(buffer.len()), Bias::Right);
+                            buffer.anchor_after(start)..buffer.anchor_before(end)
+                        })
+                        .collect()
+                })?;
+                if result_tx
+                    .send(SearchResult::Buffer { buffer, ranges })
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        self.show_results(query, result_rx, cx);
+    }
+
+    fn add_to_search_history(&mut self, query: &SearchQuery, project: &mut Project) {
+        project
+            .search_history_mut(SearchInputKind::Query)
+            .add(&mut self.search_history_cursor, query.as_str().to_string());
+        let included = query.as_inner().files_to_include().sources().join(",");
+        if !included.is_empty() {
+            project
+                .search_history_mut(SearchInputKind::Include)
+                .add(&mut self.search_included_history_cursor, included);
+        }
+        let excluded = query.as_inner().files_to_exclude().sources().join(",");
+        if !excluded.is_empty() {
+            project
+                .search_history_mut(SearchInputKind::Exclude)
+                .add(&mut self.search_excluded_history_cursor, excluded);
+        }
+    }
+
+    fn show_results(
+        &mut self,
+        query: SearchQuery,
+        search: Receiver<SearchResult>,
+        cx: &mut Context<Self>,
+    ) {
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
         self.active_query = Some(query);
@@ -278,10 +375,10 @@ impl ProjectSearch {
                 let mut buffers_with_ranges = Vec::with_capacity(results.len());
                 for result in results {
                     match result {
-                        project::search::SearchResult::Buffer { buffer, ranges } => {
+                        SearchResult::Buffer { buffer, ranges } => {
                             buffers_with_ranges.push((buffer, ranges));
                         }
-                        project::search::SearchResult::LimitReached => {
+                        SearchResult::LimitReached => {
                             limit_reached = true;
                         }
                     }
@@ -632,8 +729,29 @@ impl ProjectSearchView {
         self.included_opened_only = !self.included_opened_only;
     }
 
+    fn toggle_semantic_search(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
+        self.semantic_enabled = !self.semantic_enabled;
+    }
+
+    /// Returns the index of the searched project, if semantic search is available.
+    fn project_index(&self, cx: &mut App) -> Option<Entity<ProjectIndex>> {
+        if !cx.has_global::<SemanticDb>() {
+            return None;
+        }
+        let project = self.entity.read(cx).project.clone();
+        cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+            // Projects that were open before the semantic index was loaded aren't indexed yet.
+            Some(
+                semantic_db
+                    .project_index(project.clone(), cx)
+                    .unwrap_or_else(|| semantic_db.create_project_index(project, cx)),
+            )
+        })
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, window: &mut Window, cx: &mut Context<Self>) {
-        if self.entity.read(cx).match_ranges.is_empty() {
+        // Semantic search results are whole chunks rather than occurrences of the query.
+        if self.semantic_enabled || self.entity.read(cx).match_ranges.is_empty() {
             return;
         }
         let Some(active_index) = self.active_match_index else {
@@ -656,7 +774,7 @@ impl ProjectSearchView {
         self.replacement_editor.read(cx).text(cx)
     }
     fn replace_all(&mut self, _: &ReplaceAll, window: &mut Window, cx: &mut Context<Self>) {
-        if self.active_match_index.is_none() {
+        if self.semantic_enabled || self.active_match_index.is_none() {
             return;
         }
 
@@ -838,6 +956,7 @@ impl ProjectSearchView {
             filters_enabled,
             replace_enabled: false,
             included_opened_only: false,
+            semantic_enabled: false,
             regex_language: None,
             _subscriptions: subscriptions,
         };
@@ -995,7 +1114,17 @@ impl ProjectSearchView {
 
     fn search(&mut self, cx: &mut Context<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.entity.update(cx, |model, cx| model.search(query, cx));
+            if let Some(project_index) = self
+                .semantic_enabled
+                .then(|| self.project_index(cx))
+                .flatten()
+            {
+                self.entity.update(cx, |model, cx| {
+                    model.semantic_search(query, project_index, cx)
+                });
+            } else {
+                self.entity.update(cx, |model, cx| model.search(query, cx));
+            }
         }
     }
 
@@ -1572,6 +1701,30 @@ impl ProjectSearchBar {
         }
     }
 
+    fn toggle_semantic_search(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_semantic_search(window, cx);
+                if search_view.entity.read(cx).active_query.is_some() {
+                    search_view.search(cx);
+                }
+            });
+
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_semantic_search_enabled(&self, cx: &App) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.read(cx).semantic_enabled
+        } else {
+            false
+        }
+    }
+
     fn is_opened_only_enabled(&self, cx: &App) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.read(cx).included_opened_only
@@ -1810,6 +1963,28 @@ impl Render for ProjectSearchBar {
 
         let mode_column = h_flex()
             .gap_1()
+            .when(cx.has_global::<SemanticDb>(), |this| {
+                this.child(
+                    IconButton::new("project-search-semantic-button", IconName::Sparkle)
+                        .shape(IconButtonShape::Square)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_semantic_search(window, cx);
+                        }))
+                        .toggle_state(self.is_semantic_search_enabled(cx))
+                        .tooltip({
+                            let focus_handle = focus_handle.clone();
+                            move |window, cx| {
+                                Tooltip::for_action_in(
+                                    "Toggle Semantic Search",
+                                    &ToggleSemanticSearch,
+                                    &focus_handle,
+                                    window,
+                                    cx,
+                                )
+                            }
+                        }),
+                )
+            })
             .child(
                 IconButton::new("project-search-filter-button", IconName::Filter)
                     .shape(IconButtonShape::Square)
@@ -2099,6 +2274,9 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, _: &ToggleFilters, window, cx| {
                 this.toggle_filters(window, cx);
             }))
+            .on_action(cx.listener(|this, _: &ToggleSemanticSearch, window, cx| {
+                this.toggle_semantic_search(window, cx);
+            }))
             .capture_action(cx.listener(|this, action, window, cx| {
                 this.tab(action, window, cx);
                 cx.stop_propagation();
@@ -4,7 +4,11 @@ use gpui::Application;
 use http_client::HttpClientWithUrl;
 use language::language_settings::AllLanguageSettings;
 use project::Project;
-use semantic_index::{OpenAiEmbeddingModel, OpenAiEmbeddingProvider, SemanticDb};
+#[cfg(feature = "local-embeddings")]
+use semantic_index::LocalEmbeddingProvider;
+use semantic_index::{
+    EmbeddingProvider, OpenAiEmbeddingModel, OpenAiEmbeddingProvider, SemanticDb,
+};
 use settings::SettingsStore;
 use std::{
     path::{Path, PathBuf},
@@ -46,14 +50,30 @@ fn main() {
 
         // let embedding_provider = semantic_index::FakeEmbeddingProvider;
 
-        let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY not set");
-
-        let embedding_provider = Arc::new(OpenAiEmbeddingProvider::new(
-            http.clone(),
-            OpenAiEmbeddingModel::TextEmbedding3Small,
-            open_ai::OPEN_AI_API_URL.to_string(),
-            api_key,
-        ));
+        let local_model_dir = std::env::var("LOCAL_EMBEDDING_MODEL").ok();
+        let embedding_provider: Arc<dyn EmbeddingProvider> = match local_model_dir {
+            #[cfg(feature = "local-embeddings")]
+            Some(model_dir) => Arc::new(
+                LocalEmbeddingProvider::load(
+                    Path::new(&model_dir),
+                    cx.background_executor().clone(),
+                )
+                .expect("failed to load local embedding model"),
+            ),
+            #[cfg(not(feature = "local-embeddings"))]
+            Some(_) => panic!("LOCAL_EMBEDDING_MODEL requires the `local-embeddings` feature"),
+            None => {
+                let api_key = std::env::var("OPENAI_API_KEY")
+                    .expect("neither LOCAL_EMBEDDING_MODEL nor OPENAI_API_KEY is set");
+
+                Arc::new(OpenAiEmbeddingProvider::new(
+                    http.clone(),
+                    OpenAiEmbeddingModel::TextEmbedding3Small,
+                    open_ai::OPEN_AI_API_URL.to_string(),
+                    api_key,
+                ))
+            }
+        };
 
         cx.spawn(async move |cx| {
             let semantic_index = SemanticDb::new(
@@ -1,10 +1,14 @@
 mod cloud;
 mod lmstudio;
+#[cfg(feature = "local-embeddings")]
+mod local;
 mod ollama;
 mod open_ai;
 
 pub use cloud::*;
 pub use lmstudio::*;
+#[cfg(feature = "local-embeddings")]
+pub use local::*;
 pub use ollama::*;
 pub use open_ai::*;
 use sha2::{Digest, Sha256};
@@ -0,0 +1,246 @@
+use anyhow::{Context as _, Result, anyhow};
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config};
+use futures::{FutureExt, future::BoxFuture};
+use gpui::BackgroundExecutor;
+use std::{path::Path, sync::Arc};
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+use crate::{Embedding, EmbeddingProvider, TextToEmbed};
+
+/// Chunks are truncated to this many tokens, or to the model's maximum input length if it's
+/// smaller.
+const MAX_TOKENS: usize = 512;
+
+/// Runs a BERT sentence-embedding model, such as `all-MiniLM-L6-v2`, in process on the CPU, so
+/// that indexing works without a server or network.
+pub struct LocalEmbeddingProvider {
+    model: Arc<BertModel>,
+    tokenizer: Arc<Tokenizer>,
+    executor: BackgroundExecutor,
+}
+
+impl LocalEmbeddingProvider {
+    /// Loads a model from a directory containing the `config.json`, `tokenizer.json` and
+    /// `model.safetensors` files, as published on Hugging Face.
+    pub fn load(model_dir: &Path, executor: BackgroundExecutor) -> Result<Self> {
+        let config = std::fs::read_to_string(model_dir.join("config.json"))
+            .context("failed to read model config")?;
+        let config: Config =
+            serde_json::from_str(&config).context("failed to parse model config")?;
+
+        let mut tokenizer = Tokenizer::from_file(model_dir.join("tokenizer.json"))
+            .map_err(|error| anyhow!("failed to load tokenizer: {error}"))?;
+        tokenizer
+            .with_padding(Some(PaddingParams {
+                strategy: PaddingStrategy::BatchLongest,
+                ..Default::default()
+            }))
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS.min(config.max_position_embeddings),
+                ..Default::default()
+            }))
+            .map_err(|error| anyhow!("failed to configure tokenizer: {error}"))?;
+
+        let weights_path = model_dir.join("model.safetensors");
+        // Safety: the weights file isn't expected to be modified while the model is loaded.
+        let weights = unsafe {
+            VarBuilder::from_mmaped_safetensors(&[weights_path], DType::F32, &Device::Cpu)
+        }
+        .context("failed to load model weights")?;
+        let model = BertModel::load(weights, &config).context("failed to load model")?;
+
+        Ok(Self {
+            model: Arc::new(model),
+            tokenizer: Arc::new(tokenizer),
+            executor,
+        })
+    }
+}
+
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        // Split the batch across the background threads. Candle parallelizes the matrix
+        // multiplications within a forward pass, but much of the rest of the pass runs on a single
+        // thread, so running several passes at once keeps more cores busy.
+        let texts_per_task = texts.len().div_ceil(self.executor.num_cpus()).max(1);
+        let tasks = texts
+            .chunks(texts_per_task)
+            .map(|texts| {
+                let texts = texts
+                    .iter()
+                    .map(|text| text.text.to_string())
+                    .collect::<Vec<_>>();
+                let model = self.model.clone();
+                let tokenizer = self.tokenizer.clone();
+                self.executor
+                    .spawn(async move { embed_batch(&model, &tokenizer, texts) })
+            })
+            .collect::<Vec<_>>();
+
+        async move {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for batch_embeddings in futures::future::try_join_all(tasks).await? {
+                embeddings.extend(batch_embeddings);
+            }
+            Ok(embeddings)
+        }
+        .boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        // Larger batches are padded to their longest chunk, which wastes work on short ones.
+        32
+    }
+}
+
+/// Embeds the texts with a single forward pass, averaging the output of each text's tokens.
+fn embed_batch(
+    model: &BertModel,
+    tokenizer: &Tokenizer,
+    texts: Vec<String>,
+) -> Result<Vec<Embedding>> {
+    let encodings = tokenizer
+        .encode_batch(texts, true)
+        .map_err(|error| anyhow!("failed to tokenize text: {error}"))?;
+
+    let device = &model.device;
+    let token_ids = encodings
+        .iter()
+        .map(|encoding| Tensor::new(encoding.get_ids(), device))
+        .collect::<candle_core::Result<Vec<_>>>()?;
+    let attention_mask = encodings
+        .iter()
+        .map(|encoding| Tensor::new(encoding.get_attention_mask(), device))
+        .collect::<candle_core::Result<Vec<_>>>()?;
+    let token_ids = Tensor::stack(&token_ids, 0)?;
+    let attention_mask = Tensor::stack(&attention_mask, 0)?;
+    let token_type_ids = token_ids.zeros_like()?;
+
+    let output = model.forward(&token_ids, &token_type_ids, Some(&attention_mask))?;
+    let attention_mask = attention_mask.to_dtype(output.dtype())?.unsqueeze(2)?;
+    let token_counts = attention_mask.sum(1)?;
+    let pooled = output
+        .broadcast_mul(&attention_mask)?
+        .sum(1)?
+        .broadcast_div(&token_counts)?;
+
+    Ok(pooled
+        .to_vec2::<f32>()?
+        .into_iter()
+        .map(Embedding::new)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_nn::VarMap;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::slice;
+
+    /// Writes a tiny, randomly initialized model, so that the provider can be tested without
+    /// downloading one.
+    fn write_test_model(model_dir: &Path, max_position_embeddings: usize) {
+        let vocab = [
+            "[PAD]", "[UNK]", "fn", "main", "struct", "widget", "hello", "world",
+        ];
+        let token_ids = vocab
+            .iter()
+            .enumerate()
+            .map(|(id, token)| (token.to_string(), json!(id)))
+            .collect::<serde_json::Map<_, _>>();
+        let tokenizer = json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": { "type": "Lowercase" },
+            "pre_tokenizer": { "type": "Whitespace" },
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": token_ids,
+                "unk_token": "[UNK]",
+            },
+        });
+        std::fs::write(model_dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+
+        let config = json!({
+            "vocab_size": vocab.len(),
+            "hidden_size": 8,
+            "num_hidden_layers": 1,
+            "num_attention_heads": 2,
+            "intermediate_size": 16,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.0,
+            "max_position_embeddings": max_position_embeddings,
+            "type_vocab_size": 2,
+            "initializer_range": 0.02,
+            "layer_norm_eps": 1e-12,
+            "pad_token_id": 0,
+        });
+        std::fs::write(model_dir.join("config.json"), config.to_string()).unwrap();
+
+        let var_map = VarMap::new();
+        let config: Config = serde_json::from_value(config).unwrap();
+        BertModel::load(
+            VarBuilder::from_varmap(&var_map, DType::F32, &Device::Cpu),
+            &config,
+        )
+        .unwrap();
+        var_map.save(model_dir.join("model.safetensors")).unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_local_embedding_provider(cx: &mut TestAppContext) {
+        let model_dir = tempfile::tempdir().unwrap();
+        write_test_model(model_dir.path(), MAX_TOKENS);
+        let provider = LocalEmbeddingProvider::load(model_dir.path(), cx.executor()).unwrap();
+
+        let texts = [
+            "fn main",
+            "hello world",
+            "struct widget hello world",
+            "fn main",
+        ];
+        let texts = texts
+            .iter()
+            .map(|text| TextToEmbed::new(text))
+            .collect::<Vec<_>>();
+        let embeddings = provider.embed(&texts).await.unwrap();
+        assert_eq!(embeddings.len(), texts.len());
+        assert!(embeddings.iter().all(|embedding| embedding.len() == 8));
+        assert_ne!(embeddings[0], embeddings[1]);
+
+        // Padding a text to the length of the longest one in its batch must not change it.
+        for (text, embedding) in texts.iter().zip(&embeddings) {
+            let unpadded_embedding = provider.embed(slice::from_ref(text)).await.unwrap();
+            let (similarity, _) = embedding.similarity(&unpadded_embedding);
+            assert!(
+                similarity > 0.999,
+                "similarity for {text:?} was {similarity}"
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_truncation_to_max_position_embeddings(cx: &mut TestAppContext) {
+        let model_dir = tempfile::tempdir().unwrap();
+        write_test_model(model_dir.path(), 4);
+        let provider = LocalEmbeddingProvider::load(model_dir.path(), cx.executor()).unwrap();
+
+        // Texts longer than the model's position embeddings must be truncated rather than fail.
+        let long_text = "hello world ".repeat(16);
+        let texts = [
+            TextToEmbed::new(&long_text),
+            TextToEmbed::new("hello world"),
+        ];
+        let embeddings = provider.embed(&texts).await.unwrap();
+        assert_eq!(embeddings.len(), 2);
+        assert!(embeddings.iter().all(|embedding| embedding.len() == 8));
+    }
+}
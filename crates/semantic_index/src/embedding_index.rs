@@ -2,6 +2,7 @@ use crate::{
     chunking::{self, Chunk},
     embedding::{Embedding, EmbeddingProvider, TextToEmbed},
     indexing::{IndexingEntryHandle, IndexingEntrySet},
+    lexical_index::{LexicalChunk, LexicalFile, LexicalIndex},
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::Bound;
@@ -12,7 +13,7 @@ use futures::{FutureExt as _, stream::StreamExt};
 use futures_batch::ChunksTimeoutStreamExt;
 use gpui::{App, AppContext as _, Entity, Task};
 use heed::types::{SerdeBincode, Str};
-use language::LanguageRegistry;
+use language::{LanguageName, LanguageRegistry};
 use log;
 use project::{Entry, UpdatedEntriesSet, Worktree};
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,7 @@ pub struct EmbeddingIndex {
     worktree: Entity<Worktree>,
     db_connection: heed::Env,
     db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+    lexical_index: LexicalIndex,
     fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -37,6 +39,7 @@ impl EmbeddingIndex {
         fs: Arc<dyn Fs>,
         db_connection: heed::Env,
         embedding_db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+        lexical_index: LexicalIndex,
         language_registry: Arc<LanguageRegistry>,
         embedding_provider: Arc<dyn EmbeddingProvider>,
         entry_ids_being_indexed: Arc<IndexingEntrySet>,
@@ -46,6 +49,7 @@ impl EmbeddingIndex {
             fs,
             db_connection,
             db: embedding_db,
+            lexical_index,
             language_registry,
             embedding_provider,
             entry_ids_being_indexed,
@@ -56,6 +60,11 @@ impl EmbeddingIndex {
         &self.db
     }
 
+    /// The index of the terms in the chunks this index embeds.
+    pub fn lexical_index(&self) -> &LexicalIndex {
+        &self.lexical_index
+    }
+
     pub fn index_entries_changed_on_disk(
         &self,
         cx: &App,
@@ -104,6 +113,7 @@ impl EmbeddingIndex {
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let lexical_index = self.lexical_index.clone();
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
         let task = cx.background_spawn(async move {
             let txn = db_connection
@@ -156,7 +166,9 @@ impl EmbeddingIndex {
                     }
                 }
 
-                if entry.mtime != saved_mtime {
+                // Files embedded before the lexical index existed need to be chunked again
+                // to index their terms.
+                if entry.mtime != saved_mtime || !lexical_index.contains(&txn, &entry_db_key)? {
                     let handle = entries_being_indexed.insert(entry.id);
                     updated_entries_tx.send((entry.clone(), handle)).await?;
                 }
@@ -244,12 +256,21 @@ impl EmbeddingIndex {
                                         .language_for_file_path(&entry.path)
                                         .await
                                         .ok();
+                                    let chunks =
+                                        chunking::chunk_text(&text, language.as_ref(), &entry.path);
+                                    let lexical_chunks = chunks
+                                        .iter()
+                                        .map(|chunk| {
+                                            LexicalChunk::new(
+                                                chunk.range.clone(),
+                                                &text[chunk.range.clone()],
+                                            )
+                                        })
+                                        .collect();
                                     let chunked_file = ChunkedFile {
-                                        chunks: chunking::chunk_text(
-                                            &text,
-                                            language.as_ref(),
-                                            &entry.path,
-                                        ),
+                                        chunks,
+                                        lexical_chunks,
+                                        language: language.map(|language| language.name()),
                                         handle,
                                         path: entry.path,
                                         mtime: entry.mtime,
@@ -320,6 +341,11 @@ impl EmbeddingIndex {
 
                 let mut embeddings = embeddings.into_iter();
                 for chunked_file in chunked_files {
+                    let lexical_file = LexicalFile {
+                        path: chunked_file.path.clone(),
+                        language: chunked_file.language,
+                        chunks: chunked_file.lexical_chunks,
+                    };
                     let mut embedded_file = EmbeddedFile {
                         path: chunked_file.path,
                         mtime: chunked_file.mtime,
@@ -341,7 +367,7 @@ impl EmbeddingIndex {
 
                     if embedded_all_chunks {
                         embedded_files_tx
-                            .send((embedded_file, chunked_file.handle))
+                            .send((embedded_file, lexical_file, chunked_file.handle))
                             .await?;
                     }
                 }
@@ -358,11 +384,12 @@ impl EmbeddingIndex {
     fn persist_embeddings(
         &self,
         deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
-        embedded_files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+        embedded_files: channel::Receiver<(EmbeddedFile, LexicalFile, IndexingEntryHandle)>,
         cx: &App,
     ) -> Task<Result<()>> {
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let lexical_index = self.lexical_index.clone();
 
         cx.background_spawn(async move {
             let mut deleted_entry_ranges = pin!(deleted_entry_ranges);
@@ -377,15 +404,17 @@ impl EmbeddingIndex {
                             let end = deletion_range.1.as_ref().map(|end| end.as_str());
                             log::debug!("deleting embeddings in range {:?}", &(start, end));
                             db.delete_range(&mut txn, &(start, end))?;
+                            lexical_index.delete_range(&mut txn, &(start, end))?;
                             txn.commit()?;
                         }
                     },
                     file = embedded_files.next() => {
-                        if let Some((file, _)) = file {
+                        if let Some((file, lexical_file, _)) = file {
                             let mut txn = db_connection.write_txn()?;
                             log::debug!("saving embedding for file {:?}", file.path);
                             let key = db_key_for_path(&file.path);
                             db.put(&mut txn, &key, &file)?;
+                            lexical_index.put_file(&mut txn, &key, &lexical_file)?;
                             txn.commit()?;
                         }
                     },
@@ -446,10 +475,13 @@ pub struct ChunkedFile {
     pub handle: IndexingEntryHandle,
     pub text: String,
     pub chunks: Vec<Chunk>,
+    pub language: Option<LanguageName>,
+    /// The terms in each of `chunks`.
+    pub lexical_chunks: Vec<LexicalChunk>,
 }
 
 pub struct EmbedFiles {
-    pub files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+    pub files: channel::Receiver<(EmbeddedFile, LexicalFile, IndexingEntryHandle)>,
     pub task: Task<Result<()>>,
 }
 
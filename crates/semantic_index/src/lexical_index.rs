@@ -0,0 +1,503 @@
+use anyhow::Result;
+use collections::{BTreeMap, Bound};
+use heed::{
+    RoTxn, RwTxn,
+    types::{DecodeIgnore, SerdeBincode, Str},
+};
+use language::LanguageName;
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, path::Path, sync::Arc};
+
+/// Terms shorter than this are too common to be useful for ranking.
+const MIN_TERM_LEN: usize = 2;
+/// Terms longer than this are most likely hashes or encoded data.
+const MAX_TERM_LEN: usize = 64;
+
+/// BM25 parameters, using the values most search engines default to.
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+/// Separates a term from a file's key in the postings database. Terms only contain
+/// alphanumeric characters and underscores, so it can't occur in a term.
+const POSTINGS_KEY_SEPARATOR: char = '\u{1}';
+/// The key the corpus totals are stored under in the postings database, which no term uses.
+const CORPUS_KEY: &str = "";
+
+/// An inverted index of the terms in each chunk of the worktree's files, so that chunks
+/// containing exact identifiers can be ranked with BM25 alongside the embedding index.
+///
+/// It doesn't scan or chunk files itself: the embedding index writes the chunks it embeds
+/// here, in the same transaction as their embeddings.
+#[derive(Clone)]
+pub struct LexicalIndex {
+    files_db: heed::Database<Str, SerdeBincode<LexicalFileEntry>>,
+    postings_db: heed::Database<Str, SerdeBincode<FilePostings>>,
+}
+
+impl LexicalIndex {
+    pub fn new(
+        files_db: heed::Database<Str, SerdeBincode<LexicalFileEntry>>,
+        postings_db: heed::Database<Str, SerdeBincode<FilePostings>>,
+    ) -> Self {
+        Self {
+            files_db,
+            postings_db,
+        }
+    }
+
+    pub fn contains(&self, txn: &RoTxn, key: &str) -> Result<bool> {
+        Ok(self
+            .files_db
+            .remap_data_type::<DecodeIgnore>()
+            .get(txn, key)?
+            .is_some())
+    }
+
+    pub fn language(&self, txn: &RoTxn, key: &str) -> Result<Option<LanguageName>> {
+        Ok(self
+            .files_db
+            .get(txn, key)?
+            .and_then(|entry| entry.language))
+    }
+
+    /// Returns the number of chunks in the index and their total number of terms.
+    pub fn corpus(&self, txn: &RoTxn) -> Result<Corpus> {
+        Ok(self
+            .postings_db
+            .remap_data_type::<SerdeBincode<Corpus>>()
+            .get(txn, CORPUS_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the chunks of each file that contain the term.
+    pub fn postings<'txn>(
+        &self,
+        txn: &'txn RoTxn,
+        term: &str,
+    ) -> Result<impl Iterator<Item = Result<FilePostings>> + 'txn> {
+        let prefix = format!("{term}{POSTINGS_KEY_SEPARATOR}");
+        Ok(self
+            .postings_db
+            .prefix_iter(txn, &prefix)?
+            .map(|entry| Ok(entry?.1)))
+    }
+
+    /// Replaces the file's postings with those of its new chunks.
+    pub fn put_file(&self, txn: &mut RwTxn, key: &str, file: &LexicalFile) -> Result<()> {
+        self.delete_file(txn, key)?;
+
+        let mut postings_by_term = BTreeMap::<&str, Vec<Posting>>::default();
+        for chunk in &file.chunks {
+            for (term, term_frequency) in &chunk.terms {
+                postings_by_term
+                    .entry(term.as_str())
+                    .or_default()
+                    .push(Posting {
+                        range: chunk.range.clone(),
+                        len: chunk.len,
+                        term_frequency: *term_frequency,
+                    });
+            }
+        }
+
+        let mut terms = Vec::with_capacity(postings_by_term.len());
+        for (term, chunks) in postings_by_term {
+            let postings = FilePostings {
+                path: file.path.clone(),
+                language: file.language.clone(),
+                chunks,
+            };
+            self.postings_db
+                .put(txn, &postings_key(term, key), &postings)?;
+            terms.push(term.to_string());
+        }
+
+        let entry = LexicalFileEntry {
+            language: file.language.clone(),
+            chunk_count: file.chunks.len() as u64,
+            len: file.chunks.iter().map(|chunk| chunk.len as u64).sum(),
+            terms,
+        };
+        let mut corpus = self.corpus(txn)?;
+        corpus.chunk_count += entry.chunk_count;
+        corpus.total_len += entry.len;
+        self.put_corpus(txn, &corpus)?;
+        self.files_db.put(txn, key, &entry)?;
+        Ok(())
+    }
+
+    pub fn delete_range(&self, txn: &mut RwTxn, range: &(Bound<&str>, Bound<&str>)) -> Result<()> {
+        let keys = self
+            .files_db
+            .remap_data_type::<DecodeIgnore>()
+            .range(txn, range)?
+            .map(|entry| Ok(entry?.0.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        for key in keys {
+            self.delete_file(txn, &key)?;
+        }
+        Ok(())
+    }
+
+    fn delete_file(&self, txn: &mut RwTxn, key: &str) -> Result<()> {
+        let Some(entry) = self.files_db.get(txn, key)? else {
+            return Ok(());
+        };
+        for term in &entry.terms {
+            self.postings_db.delete(txn, &postings_key(term, key))?;
+        }
+        let mut corpus = self.corpus(txn)?;
+        corpus.chunk_count = corpus.chunk_count.saturating_sub(entry.chunk_count);
+        corpus.total_len = corpus.total_len.saturating_sub(entry.len);
+        self.put_corpus(txn, &corpus)?;
+        self.files_db.delete(txn, key)?;
+        Ok(())
+    }
+
+    fn put_corpus(&self, txn: &mut RwTxn, corpus: &Corpus) -> Result<()> {
+        self.postings_db
+            .remap_data_type::<SerdeBincode<Corpus>>()
+            .put(txn, CORPUS_KEY, corpus)?;
+        Ok(())
+    }
+}
+
+fn postings_key(term: &str, file_key: &str) -> String {
+    format!("{term}{POSTINGS_KEY_SEPARATOR}{file_key}")
+}
+
+/// A file's chunks and their terms, as produced by the embedding index's chunking.
+#[derive(Debug)]
+pub struct LexicalFile {
+    pub path: Arc<Path>,
+    pub language: Option<LanguageName>,
+    pub chunks: Vec<LexicalChunk>,
+}
+
+/// What the index records about each file, so that its postings can be removed when it
+/// changes or is deleted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LexicalFileEntry {
+    pub language: Option<LanguageName>,
+    pub chunk_count: u64,
+    pub len: u64,
+    /// The distinct terms in the file, sorted.
+    pub terms: Vec<String>,
+}
+
+/// The chunks of a single file that contain a term.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilePostings {
+    pub path: Arc<Path>,
+    pub language: Option<LanguageName>,
+    pub chunks: Vec<Posting>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Posting {
+    pub range: Range<usize>,
+    /// The number of terms in the chunk, counting repetitions.
+    pub len: u32,
+    /// The number of times the term occurs in the chunk.
+    pub term_frequency: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Corpus {
+    pub chunk_count: u64,
+    pub total_len: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct LexicalChunk {
+    pub range: Range<usize>,
+    /// The number of terms in the chunk, counting repetitions.
+    pub len: u32,
+    /// The distinct terms in the chunk, sorted, with the number of times each one occurs.
+    pub terms: Vec<(String, u32)>,
+}
+
+impl LexicalChunk {
+    pub fn new(range: Range<usize>, text: &str) -> Self {
+        let mut terms = terms(text);
+        let len = terms.len() as u32;
+        terms.sort_unstable();
+
+        let mut counted_terms = Vec::<(String, u32)>::new();
+        for term in terms {
+            match counted_terms.last_mut() {
+                Some((last_term, count)) if *last_term == term => *count += 1,
+                _ => counted_terms.push((term, 1)),
+            }
+        }
+
+        Self {
+            range,
+            len,
+            terms: counted_terms,
+        }
+    }
+
+    /// Returns how many times the term occurs in the chunk.
+    pub fn term_frequency(&self, term: &str) -> u32 {
+        self.terms
+            .binary_search_by(|(probe, _)| probe.as_str().cmp(term))
+            .map_or(0, |ix| self.terms[ix].1)
+    }
+}
+
+/// Splits text into lowercase terms. Identifiers are indexed both whole and by their parts,
+/// so that `parse_json` can be found with `json` and `HttpServer` with `server`.
+pub fn terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        let word = word.trim_matches('_');
+        if word.len() > MAX_TERM_LEN || word.chars().count() < MIN_TERM_LEN {
+            continue;
+        }
+
+        terms.push(word.to_lowercase());
+        let parts = identifier_parts(word);
+        if parts.len() > 1 {
+            terms.extend(
+                parts
+                    .into_iter()
+                    .filter(|part| part.chars().count() >= MIN_TERM_LEN)
+                    .map(str::to_lowercase),
+            );
+        }
+    }
+    terms
+}
+
+/// Splits an identifier at underscores and case changes, e.g. `parseHTTPRequest_v2` into
+/// `parse`, `HTTP`, `Request` and `v2`.
+fn identifier_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut prev = None;
+    let mut chars = word.char_indices().peekable();
+    while let Some((ix, c)) = chars.next() {
+        if c == '_' {
+            if start < ix {
+                parts.push(&word[start..ix]);
+            }
+            start = ix + 1;
+            prev = None;
+            continue;
+        }
+
+        if let Some(prev) = prev {
+            let next = chars.peek().map(|(_, next)| *next);
+            let is_boundary = (char::is_lowercase(prev) && c.is_uppercase())
+                || (char::is_uppercase(prev)
+                    && c.is_uppercase()
+                    && next.is_some_and(char::is_lowercase));
+            if is_boundary && start < ix {
+                parts.push(&word[start..ix]);
+                start = ix;
+            }
+        }
+        prev = Some(c);
+    }
+    if start < word.len() {
+        parts.push(&word[start..]);
+    }
+    parts
+}
+
+/// The corpus statistics that BM25 needs to score a chunk.
+#[derive(Clone, Debug, Default)]
+pub struct Bm25Stats {
+    pub chunk_count: u64,
+    pub total_len: u64,
+    /// For each query term, the number of chunks that contain it.
+    pub document_frequencies: Vec<u64>,
+}
+
+impl Bm25Stats {
+    pub fn new(term_count: usize) -> Self {
+        Self {
+            chunk_count: 0,
+            total_len: 0,
+            document_frequencies: vec![0; term_count],
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.chunk_count += other.chunk_count;
+        self.total_len += other.total_len;
+        for (frequency, other_frequency) in self
+            .document_frequencies
+            .iter_mut()
+            .zip(&other.document_frequencies)
+        {
+            *frequency += other_frequency;
+        }
+    }
+
+    /// Scores a chunk of `len` terms, given how many times it contains each query term.
+    pub fn score(&self, len: u32, term_frequencies: &[u32]) -> f32 {
+        if self.chunk_count == 0 {
+            return 0.;
+        }
+
+        let chunk_count = self.chunk_count as f32;
+        let average_len = (self.total_len as f32 / chunk_count).max(1.);
+        let len_norm = 1. - BM25_B + BM25_B * len as f32 / average_len;
+        term_frequencies
+            .iter()
+            .zip(&self.document_frequencies)
+            .filter(|(term_frequency, _)| **term_frequency > 0)
+            .map(|(&term_frequency, &document_frequency)| {
+                let document_frequency = document_frequency as f32;
+                let idf = ((chunk_count - document_frequency + 0.5) / (document_frequency + 0.5)
+                    + 1.)
+                    .ln();
+                let term_frequency = term_frequency as f32;
+                idf * term_frequency * (BM25_K1 + 1.) / (term_frequency + BM25_K1 * len_norm)
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(
+            terms("fn parseHTTPRequest_v2(x: &str) -> JsonValue"),
+            [
+                "fn",
+                "parsehttprequest_v2",
+                "parse",
+                "http",
+                "request",
+                "v2",
+                "str",
+                "jsonvalue",
+                "json",
+                "value",
+            ]
+        );
+        assert_eq!(terms("__init__ a é_über"), ["init", "é_über", "über"]);
+    }
+
+    #[test]
+    fn test_bm25() {
+        let chunk = LexicalChunk::new(0..0, "let index = LexicalIndex::new(index_db);");
+        assert_eq!(chunk.term_frequency("index"), 3);
+        assert_eq!(chunk.term_frequency("lexical"), 1);
+        assert_eq!(chunk.term_frequency("missing"), 0);
+
+        let mut stats = Bm25Stats::new(2);
+        stats.chunk_count = 100;
+        stats.total_len = 1000;
+        stats.document_frequencies = vec![2, 50];
+
+        // Rare terms outweigh common ones, and repeated terms in shorter chunks score higher.
+        assert!(stats.score(10, &[1, 0]) > stats.score(10, &[0, 1]));
+        assert!(stats.score(10, &[2, 0]) > stats.score(10, &[1, 0]));
+        assert!(stats.score(5, &[1, 0]) > stats.score(20, &[1, 0]));
+        assert_eq!(stats.score(10, &[0, 0]), 0.);
+    }
+
+    #[test]
+    fn test_postings() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_connection = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(1024 * 1024 * 1024)
+                .max_dbs(2)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = db_connection.write_txn().unwrap();
+        let index = LexicalIndex::new(
+            db_connection
+                .create_database(&mut txn, Some("files"))
+                .unwrap(),
+            db_connection
+                .create_database(&mut txn, Some("postings"))
+                .unwrap(),
+        );
+
+        let file = |path: &str, chunks: &[&str]| {
+            let mut offset = 0;
+            LexicalFile {
+                path: Path::new(path).into(),
+                language: None,
+                chunks: chunks
+                    .iter()
+                    .map(|text| {
+                        offset += text.len();
+                        LexicalChunk::new(offset - text.len()..offset, text)
+                    })
+                    .collect(),
+            }
+        };
+        let postings = |txn: &RoTxn, term: &str| {
+            index
+                .postings(txn, term)
+                .unwrap()
+                .map(|postings| {
+                    let postings = postings.unwrap();
+                    (
+                        postings.path.to_string_lossy().into_owned(),
+                        postings
+                            .chunks
+                            .into_iter()
+                            .map(|posting| (posting.range, posting.term_frequency))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        index
+            .put_file(&mut txn, "a", &file("a", &["fn index()", "index index"]))
+            .unwrap();
+        index
+            .put_file(&mut txn, "b", &file("b", &["let indexes"]))
+            .unwrap();
+        assert_eq!(
+            postings(&txn, "index"),
+            [("a".to_string(), vec![(0..10, 1), (10..21, 2)])]
+        );
+        assert_eq!(
+            postings(&txn, "indexes"),
+            [("b".to_string(), vec![(0..11, 1)])]
+        );
+        assert_eq!(index.corpus(&txn).unwrap().chunk_count, 3);
+        assert_eq!(index.corpus(&txn).unwrap().total_len, 6);
+
+        // Putting a file again replaces its postings.
+        index
+            .put_file(&mut txn, "a", &file("a", &["fn indexes()"]))
+            .unwrap();
+        assert!(postings(&txn, "index").is_empty());
+        assert_eq!(
+            postings(&txn, "indexes"),
+            [
+                ("a".to_string(), vec![(0..12, 1)]),
+                ("b".to_string(), vec![(0..11, 1)])
+            ]
+        );
+        assert_eq!(index.corpus(&txn).unwrap().chunk_count, 2);
+
+        index
+            .delete_range(&mut txn, &(Bound::Included("a"), Bound::Included("a")))
+            .unwrap();
+        assert!(postings(&txn, "fn").is_empty());
+        assert_eq!(
+            postings(&txn, "indexes"),
+            [("b".to_string(), vec![(0..11, 1)])]
+        );
+        assert_eq!(index.corpus(&txn).unwrap().chunk_count, 1);
+        assert_eq!(index.corpus(&txn).unwrap().total_len, 2);
+        assert!(!index.contains(&txn, "a").unwrap());
+        assert!(index.contains(&txn, "b").unwrap());
+    }
+}
@@ -1,16 +1,17 @@
 use crate::{
     embedding::{EmbeddingProvider, TextToEmbed},
+    lexical_index::{self, Bm25Stats},
     summary_index::FileSummary,
     worktree_index::{WorktreeIndex, WorktreeIndexHandle},
 };
 use anyhow::{Context as _, Result, anyhow};
-use collections::HashMap;
+use collections::{HashMap, hash_map};
 use fs::Fs;
 use futures::FutureExt;
 use gpui::{
     App, AppContext as _, Context, Entity, EntityId, EventEmitter, Subscription, Task, WeakEntity,
 };
-use language::LanguageRegistry;
+use language::{LanguageName, LanguageRegistry};
 use log;
 use project::{Project, Worktree, WorktreeId};
 use serde::{Deserialize, Serialize};
@@ -18,12 +19,20 @@ use smol::channel;
 use std::{
     cmp::Ordering,
     future::Future,
+    hash::Hash,
     num::NonZeroUsize,
     ops::{Range, RangeInclusive},
     path::{Path, PathBuf},
     sync::Arc,
 };
-use util::ResultExt;
+use util::{ResultExt, paths::PathMatcher};
+
+/// The constant from the original reciprocal rank fusion paper. It dampens the weight of the
+/// top ranks, so that a chunk ranked well by both searches beats one ranked first by only one.
+const RRF_K: f32 = 60.;
+
+/// How many results of each search are fused in a hybrid search.
+const HYBRID_SEARCH_CANDIDATES: usize = 64;
 
 #[derive(Debug)]
 pub struct SearchResult {
@@ -51,6 +60,43 @@ pub struct WorktreeSearchResult {
     pub score: f32,
 }
 
+/// Restricts a search to the files matching the path globs and languages.
+#[derive(Clone, Debug, Default)]
+pub struct SearchFilter {
+    pub files_to_include: PathMatcher,
+    pub files_to_exclude: PathMatcher,
+    pub languages: Vec<LanguageName>,
+}
+
+impl SearchFilter {
+    /// Globs are matched against both the worktree-relative path and the path prefixed with
+    /// the worktree's root name, like in project search.
+    fn matches_path(&self, worktree_root_name: &str, path: &Path) -> bool {
+        let full_path = Path::new(worktree_root_name).join(path);
+        let is_match =
+            |matcher: &PathMatcher| matcher.is_match(path) || matcher.is_match(&full_path);
+        (self.files_to_include.sources().is_empty() || is_match(&self.files_to_include))
+            && (self.files_to_exclude.sources().is_empty() || !is_match(&self.files_to_exclude))
+    }
+
+    fn matches_language(&self, language: Option<&LanguageName>) -> bool {
+        self.languages.is_empty()
+            || language.is_some_and(|language| {
+                self.languages
+                    .iter()
+                    .any(|name| name.as_ref().eq_ignore_ascii_case(language.as_ref()))
+            })
+    }
+}
+
+struct LexicalCandidate {
+    worktree_id: WorktreeId,
+    path: Arc<Path>,
+    range: Range<usize>,
+    len: u32,
+    term_frequencies: Vec<u32>,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Status {
     Idle,
@@ -230,12 +276,25 @@ impl ProjectIndex {
         queries: Vec<String>,
         limit: usize,
         cx: &App,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        self.semantic_search(queries, SearchFilter::default(), limit, cx)
+    }
+
+    /// Ranks the chunks matching the filter by the similarity of their embeddings to the
+    /// queries' embeddings.
+    pub fn semantic_search(
+        &self,
+        queries: Vec<String>,
+        filter: SearchFilter,
+        limit: usize,
+        cx: &App,
     ) -> Task<Result<Vec<SearchResult>>> {
         let (chunks_tx, chunks_rx) = channel::bounded(1024);
         let mut worktree_scan_tasks = Vec::new();
         for worktree_index in self.worktree_indices.values() {
             let worktree_index = worktree_index.clone();
             let chunks_tx = chunks_tx.clone();
+            let filter = filter.clone();
             worktree_scan_tasks.push(cx.spawn(async move |cx| {
                 let index = match worktree_index {
                     WorktreeIndexHandle::Loading { index } => {
@@ -246,16 +305,31 @@ impl ProjectIndex {
 
                 index
                     .read_with(cx, |index, cx| {
-                        let worktree_id = index.worktree().read(cx).id();
+                        let worktree = index.worktree().read(cx);
+                        let worktree_id = worktree.id();
+                        let worktree_root_name = worktree.root_name().to_string();
                         let db_connection = index.db_connection().clone();
                         let db = *index.embedding_index().db();
+                        let lexical_index = index.lexical_index().clone();
                         cx.background_spawn(async move {
                             let txn = db_connection
                                 .read_txn()
                                 .context("failed to create read transaction")?;
                             let db_entries = db.iter(&txn).context("failed to iterate database")?;
                             for db_entry in db_entries {
-                                let (_key, db_embedded_file) = db_entry?;
+                                let (key, db_embedded_file) = db_entry?;
+                                if !filter.matches_path(&worktree_root_name, &db_embedded_file.path)
+                                {
+                                    continue;
+                                }
+                                // Embedded files don't record their language, but the lexical
+                                // index stores the same paths under the same keys.
+                                if !filter.languages.is_empty() {
+                                    let language = lexical_index.language(&txn, key)?;
+                                    if !filter.matches_language(language.as_ref()) {
+                                        continue;
+                                    }
+                                }
                                 for chunk in db_embedded_file.chunks {
                                     chunks_tx
                                         .send((worktree_id, db_embedded_file.path.clone(), chunk))
@@ -368,6 +442,178 @@ impl ProjectIndex {
         })
     }
 
+    /// Ranks the chunks matching the filter by how well their terms match the query's terms,
+    /// using BM25.
+    pub fn lexical_search(
+        &self,
+        query: &str,
+        filter: SearchFilter,
+        limit: usize,
+        cx: &App,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let mut query_terms = lexical_index::terms(query);
+        query_terms.sort_unstable();
+        query_terms.dedup();
+        if query_terms.is_empty() {
+            return Task::ready(Ok(Vec::new()));
+        }
+        let query_terms: Arc<[String]> = query_terms.into();
+
+        let mut worktree_scan_tasks = Vec::new();
+        for worktree_index in self.worktree_indices.values() {
+            let worktree_index = worktree_index.clone();
+            let query_terms = query_terms.clone();
+            let filter = filter.clone();
+            worktree_scan_tasks.push(cx.spawn(async move |cx| {
+                let index = match worktree_index {
+                    WorktreeIndexHandle::Loading { index } => {
+                        index.clone().await.map_err(|error| anyhow!(error))?
+                    }
+                    WorktreeIndexHandle::Loaded { index } => index.clone(),
+                };
+
+                index
+                    .read_with(cx, |index, cx| {
+                        let worktree = index.worktree().read(cx);
+                        let worktree_id = worktree.id();
+                        let worktree_root_name = worktree.root_name().to_string();
+                        let db_connection = index.db_connection().clone();
+                        let lexical_index = index.lexical_index().clone();
+                        cx.background_spawn(async move {
+                            let txn = db_connection
+                                .read_txn()
+                                .context("failed to create read transaction")?;
+                            let corpus = lexical_index.corpus(&txn)?;
+                            let mut stats = Bm25Stats::new(query_terms.len());
+                            stats.chunk_count = corpus.chunk_count;
+                            stats.total_len = corpus.total_len;
+
+                            // Only the postings of the query's terms are read. Chunks that
+                            // don't match the filter still count towards the document
+                            // frequencies, which describe the whole worktree.
+                            let mut candidates_by_chunk =
+                                HashMap::<(Arc<Path>, usize), LexicalCandidate>::default();
+                            for (term_ix, term) in query_terms.iter().enumerate() {
+                                for file_postings in lexical_index.postings(&txn, term)? {
+                                    let file_postings = file_postings?;
+                                    stats.document_frequencies[term_ix] +=
+                                        file_postings.chunks.len() as u64;
+                                    if !filter
+                                        .matches_path(&worktree_root_name, &file_postings.path)
+                                        || !filter.matches_language(file_postings.language.as_ref())
+                                    {
+                                        continue;
+                                    }
+                                    for posting in file_postings.chunks {
+                                        let candidate = candidates_by_chunk
+                                            .entry((
+                                                file_postings.path.clone(),
+                                                posting.range.start,
+                                            ))
+                                            .or_insert_with(|| LexicalCandidate {
+                                                worktree_id,
+                                                path: file_postings.path.clone(),
+                                                range: posting.range.clone(),
+                                                len: posting.len,
+                                                term_frequencies: vec![0; query_terms.len()],
+                                            });
+                                        candidate.term_frequencies[term_ix] =
+                                            posting.term_frequency;
+                                    }
+                                }
+                            }
+                            let candidates = candidates_by_chunk.into_values().collect::<Vec<_>>();
+                            anyhow::Ok((stats, candidates))
+                        })
+                    })?
+                    .await
+            }));
+        }
+
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            // Scores depend on statistics of the whole project, so they can only be computed
+            // once every worktree has been scanned.
+            let mut stats = Bm25Stats::new(query_terms.len());
+            let mut candidates = Vec::new();
+            for scan_task in futures::future::join_all(worktree_scan_tasks).await {
+                if let Some((worktree_stats, worktree_candidates)) = scan_task.log_err() {
+                    stats.merge(&worktree_stats);
+                    candidates.extend(worktree_candidates);
+                }
+            }
+
+            let mut results = candidates
+                .into_iter()
+                .map(|candidate| WorktreeSearchResult {
+                    score: stats.score(candidate.len, &candidate.term_frequencies),
+                    worktree_id: candidate.worktree_id,
+                    path: candidate.path,
+                    range: candidate.range,
+                    query_index: 0,
+                })
+                .collect::<Vec<_>>();
+            results
+                .sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            results.truncate(limit);
+
+            project.read_with(cx, |project, cx| {
+                results
+                    .into_iter()
+                    .filter_map(|result| {
+                        Some(SearchResult {
+                            worktree: project.worktree_for_id(result.worktree_id, cx)?,
+                            path: result.path,
+                            range: result.range,
+                            score: result.score,
+                            query_index: result.query_index,
+                        })
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    /// Runs both a semantic and a lexical search and fuses their rankings with reciprocal rank
+    /// fusion, so that chunks containing the query's exact identifiers rank well even when
+    /// their embeddings aren't the closest. The results' scores are the fused scores.
+    pub fn hybrid_search(
+        &self,
+        query: String,
+        filter: SearchFilter,
+        limit: usize,
+        cx: &App,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let candidate_limit = limit.max(HYBRID_SEARCH_CANDIDATES);
+        let lexical_search = self.lexical_search(&query, filter.clone(), candidate_limit, cx);
+        let semantic_search = self.semantic_search(vec![query], filter, candidate_limit, cx);
+        cx.spawn(async move |_| {
+            let (semantic_results, lexical_results) =
+                futures::join!(semantic_search, lexical_search);
+            let mut results = match (semantic_results, lexical_results) {
+                (Ok(semantic_results), Ok(lexical_results)) => {
+                    reciprocal_rank_fusion([semantic_results, lexical_results], |result| {
+                        (
+                            result.worktree.entity_id(),
+                            result.path.clone(),
+                            result.range.start,
+                        )
+                    })
+                    .into_iter()
+                    .map(|(result, score)| SearchResult { score, ..result })
+                    .collect()
+                }
+                (Ok(results), Err(error)) | (Err(error), Ok(results)) => {
+                    log::error!("falling back to a single ranking in hybrid search: {error:?}");
+                    results
+                }
+                (Err(error), Err(_)) => return Err(error),
+            };
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+
     #[cfg(test)]
     pub fn path_count(&self, cx: &App) -> Result<u64> {
         let mut result = 0;
@@ -547,3 +793,63 @@ impl ProjectIndex {
 }
 
 impl EventEmitter<Status> for ProjectIndex {}
+
+/// Fuses rankings of the same items, identified by `key`, by summing `1 / (k + rank)` over
+/// the rankings each item appears in. Returns the items sorted by their fused score.
+fn reciprocal_rank_fusion<T, K: Hash + Eq>(
+    rankings: impl IntoIterator<Item = Vec<T>>,
+    key: impl Fn(&T) -> K,
+) -> Vec<(T, f32)> {
+    let mut fused = Vec::<(T, f32)>::new();
+    let mut fused_ix_by_key = HashMap::<K, usize>::default();
+    for ranking in rankings {
+        for (rank, item) in ranking.into_iter().enumerate() {
+            let score = 1. / (RRF_K + rank as f32 + 1.);
+            match fused_ix_by_key.entry(key(&item)) {
+                hash_map::Entry::Occupied(entry) => fused[*entry.get()].1 += score,
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(fused.len());
+                    fused.push((item, score));
+                }
+            }
+        }
+    }
+    fused.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let semantic = vec!["a", "b", "c", "d"];
+        let lexical = vec!["d", "c", "e"];
+        let fused = reciprocal_rank_fusion([semantic, lexical], |item| *item);
+        let items = fused.iter().map(|(item, _)| *item).collect::<Vec<_>>();
+        // Items found by both searches come first, even when only one of them ranked it highly.
+        assert_eq!(items, ["d", "c", "a", "b", "e"]);
+        assert_eq!(fused[0].1, 1. / 64. + 1. / 61.);
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let filter = SearchFilter {
+            files_to_include: PathMatcher::new(["src/**/*.rs"]).unwrap(),
+            files_to_exclude: PathMatcher::new(["**/tests/**"]).unwrap(),
+            languages: vec!["rust".into()],
+        };
+        assert!(filter.matches_path("zed", Path::new("src/lib.rs")));
+        assert!(filter.matches_path("zed", Path::new("src/a/b.rs")));
+        assert!(!filter.matches_path("zed", Path::new("src/tests/b.rs")));
+        assert!(!filter.matches_path("zed", Path::new("docs/lib.md")));
+        assert!(filter.matches_language(Some(&"Rust".into())));
+        assert!(!filter.matches_language(Some(&"Python".into())));
+        assert!(!filter.matches_language(None));
+
+        let filter = SearchFilter::default();
+        assert!(filter.matches_path("zed", Path::new("docs/lib.md")));
+        assert!(filter.matches_language(None));
+    }
+}
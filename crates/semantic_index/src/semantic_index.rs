@@ -2,8 +2,10 @@ mod chunking;
 mod embedding;
 mod embedding_index;
 mod indexing;
+mod lexical_index;
 mod project_index;
 mod project_index_debug_view;
+mod semantic_index_settings;
 mod summary_backlog;
 mod summary_index;
 mod worktree_index;
@@ -14,6 +16,7 @@ use fs::Fs;
 use gpui::{App, AppContext as _, AsyncApp, BorrowAppContext, Context, Entity, Global, WeakEntity};
 use language::LineEnding;
 use project::{Project, Worktree};
+use settings::{Settings as _, SettingsStore};
 use std::{
     cmp::Ordering,
     path::{Path, PathBuf},
@@ -23,10 +26,87 @@ use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
-pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
+pub use project_index::{LoadedSearchResult, ProjectIndex, SearchFilter, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use semantic_index_settings::{EmbeddingProviderSetting, SemanticIndexSettings};
 pub use summary_index::FileSummary;
 
+pub fn init(cx: &mut App) {
+    SemanticIndexSettings::register(cx);
+
+    let mut embedding_provider = SemanticIndexSettings::get_global(cx)
+        .embedding_provider
+        .clone();
+    load_semantic_db(embedding_provider.clone(), cx);
+    cx.observe_global::<SettingsStore>(move |cx| {
+        let new_embedding_provider = &SemanticIndexSettings::get_global(cx).embedding_provider;
+        if *new_embedding_provider != embedding_provider {
+            embedding_provider = new_embedding_provider.clone();
+            load_semantic_db(embedding_provider.clone(), cx);
+        }
+    })
+    .detach();
+}
+
+/// Replaces the global [`SemanticDb`] with one that embeds files with the given provider.
+fn load_semantic_db(embedding_provider: Option<EmbeddingProviderSetting>, cx: &mut App) {
+    if cx.has_global::<SemanticDb>() {
+        cx.remove_global::<SemanticDb>();
+    }
+    let Some(embedding_provider) = embedding_provider else {
+        return;
+    };
+
+    cx.spawn(async move |cx| {
+        let (db_path, provider) = match &embedding_provider {
+            EmbeddingProviderSetting::Local { model_path } => {
+                // Embeddings from different models can't be compared, so each model gets its own
+                // database.
+                let model_name = model_path
+                    .file_name()
+                    .context("invalid local embedding model path")?;
+                let db_path = paths::embeddings_dir().join("local").join(model_name);
+                let provider = load_local_embedding_provider(model_path.clone(), cx).await?;
+                (db_path, provider)
+            }
+        };
+        let semantic_db = SemanticDb::new(db_path, provider, cx).await?;
+        cx.update(|cx| {
+            // Another provider may have been selected while this one was loading.
+            if SemanticIndexSettings::get_global(cx)
+                .embedding_provider
+                .as_ref()
+                == Some(&embedding_provider)
+            {
+                cx.set_global(semantic_db);
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(feature = "local-embeddings")]
+async fn load_local_embedding_provider(
+    model_path: PathBuf,
+    cx: &AsyncApp,
+) -> Result<Arc<dyn EmbeddingProvider>> {
+    let executor = cx.background_executor().clone();
+    let provider = cx
+        .background_spawn(async move { LocalEmbeddingProvider::load(&model_path, executor) })
+        .await?;
+    Ok(Arc::new(provider))
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+async fn load_local_embedding_provider(
+    _model_path: PathBuf,
+    _cx: &AsyncApp,
+) -> Result<Arc<dyn EmbeddingProvider>> {
+    anyhow::bail!(
+        "local embedding models require Zed to be built with the `local-embeddings` feature"
+    )
+}
+
 pub struct SemanticDb {
     embedding_provider: Arc<dyn EmbeddingProvider>,
     db_connection: Option<heed::Env>,
@@ -278,7 +358,7 @@ mod tests {
     use settings::SettingsStore;
     use smol::channel;
     use std::{future, path::Path, sync::Arc};
-    use util::separator;
+    use util::{paths::PathMatcher, separator};
 
     fn init_test(cx: &mut TestAppContext) {
         env_logger::try_init().ok();
@@ -441,6 +521,121 @@ mod tests {
         assert!(content.contains("garbage in, garbage out"));
     }
 
+    #[gpui::test]
+    async fn test_hybrid_search(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        init_test(cx);
+
+        cx.update(|cx| {
+            // This functionality is staff-flagged.
+            cx.update_flags(true, vec![]);
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // Every chunk is equally similar to every query, so only the lexical index can tell
+        // them apart.
+        let mut semantic_index = SemanticDb::new(
+            temp_dir.path().into(),
+            Arc::new(TestEmbeddingProvider::new(16, |_| {
+                Ok(Embedding::new(vec![1., 0.]))
+            })),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+
+        let fs = FakeFs::new(cx.executor());
+        let project_path = Path::new("/fake_project");
+
+        fs.insert_tree(
+            project_path,
+            json!({
+                "src": {
+                    "main.rs": "fn main() {\n    println!(\"Hello, world!\");\n}\n",
+                    "widgets.rs": "pub fn frobnicate_widgets(widgets: &mut [Widget]) {\n    for widget in widgets {\n        widget.frobnicate();\n    }\n}\n",
+                },
+                "docs": {
+                    "widgets.md": "# Widgets\n\nCall `frobnicate_widgets` to frobnicate all the widgets.\n",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, [project_path], cx).await;
+
+        let project_index = cx.update(|cx| {
+            let language_registry = project.read(cx).languages().clone();
+            let node_runtime = project.read(cx).node_runtime().unwrap().clone();
+            languages::init(language_registry, node_runtime, cx);
+            semantic_index.create_project_index(project.clone(), cx)
+        });
+
+        cx.run_until_parked();
+        while cx
+            .update(|cx| semantic_index.remaining_summaries(&project.downgrade(), cx))
+            .unwrap()
+            > 0
+        {
+            cx.run_until_parked();
+        }
+
+        let mut search = |query: &str, filter: SearchFilter, hybrid: bool| {
+            let query = query.to_string();
+            let results = cx.update(|cx| {
+                let project_index = project_index.read(cx);
+                if hybrid {
+                    project_index.hybrid_search(query, filter, 10, cx)
+                } else {
+                    project_index.lexical_search(&query, filter, 10, cx)
+                }
+            });
+            async move {
+                let mut paths = results
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|result| result.path.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>();
+                paths.dedup();
+                paths
+            }
+        };
+
+        let mut paths = search("frobnicate", SearchFilter::default(), false).await;
+        paths.sort();
+        assert_eq!(
+            paths,
+            [separator!("docs/widgets.md"), separator!("src/widgets.rs")]
+        );
+
+        let paths = search(
+            "frobnicate",
+            SearchFilter {
+                languages: vec!["Markdown".into()],
+                ..Default::default()
+            },
+            false,
+        )
+        .await;
+        assert_eq!(paths, [separator!("docs/widgets.md")]);
+
+        let paths = search(
+            "frobnicate_widgets",
+            SearchFilter {
+                files_to_include: PathMatcher::new(["src/**"]).unwrap(),
+                ..Default::default()
+            },
+            true,
+        )
+        .await;
+        assert_eq!(
+            paths,
+            [separator!("src/widgets.rs"), separator!("src/main.rs")]
+        );
+    }
+
     #[gpui::test]
     async fn test_embed_files(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
@@ -474,6 +669,8 @@ mod tests {
                         digest: Default::default(),
                     })
                     .collect(),
+                language: None,
+                lexical_chunks: Vec::new(),
             })
             .unwrap();
         chunked_files_tx
@@ -489,6 +686,8 @@ mod tests {
                         digest: Default::default(),
                     })
                     .collect(),
+                language: None,
+                lexical_chunks: Vec::new(),
             })
             .unwrap();
         chunked_files_tx.close();
@@ -499,7 +698,7 @@ mod tests {
 
         let embedded_files_rx = embed_files_task.files;
         let mut embedded_files = Vec::new();
-        while let Ok((embedded_file, _)) = embedded_files_rx.recv().await {
+        while let Ok((embedded_file, _, _)) = embedded_files_rx.recv().await {
             embedded_files.push(embedded_file);
         }
 
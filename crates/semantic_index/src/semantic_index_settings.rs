@@ -0,0 +1,45 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::path::PathBuf;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SemanticIndexSettings {
+    pub embedding_provider: Option<EmbeddingProviderSetting>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum EmbeddingProviderSetting {
+    /// Runs a BERT sentence-embedding model, such as `all-MiniLM-L6-v2`, on this machine, so that
+    /// projects can be indexed offline.
+    ///
+    /// Requires Zed to be built with the `local-embeddings` feature.
+    Local {
+        /// The directory containing the model's `config.json`, `tokenizer.json` and
+        /// `model.safetensors` files.
+        model_path: PathBuf,
+    },
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SemanticIndexSettingsContent {
+    /// The provider used to embed the files of open projects for semantic search. Projects aren't
+    /// indexed when this is null.
+    ///
+    /// Default: null
+    pub embedding_provider: Option<EmbeddingProviderSetting>,
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = SemanticIndexSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::App,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
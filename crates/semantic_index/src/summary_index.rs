@@ -565,6 +565,11 @@ impl SummaryIndex {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
+            top_p: None,
+            reasoning_effort: None,
+            seed: None,
+            response_format: None,
         };
 
         let code_len = code.len();
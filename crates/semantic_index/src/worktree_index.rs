@@ -1,6 +1,7 @@
 use crate::embedding::EmbeddingProvider;
 use crate::embedding_index::EmbeddingIndex;
 use crate::indexing::IndexingEntrySet;
+use crate::lexical_index::LexicalIndex;
 use crate::summary_index::SummaryIndex;
 use anyhow::Result;
 use fs::Fs;
@@ -56,6 +57,25 @@ impl WorktreeIndex {
                     let db_connection = db_connection.clone();
                     async move {
                         let mut txn = db_connection.write_txn()?;
+                        let lexical_index = {
+                            let files_db = {
+                                let db_name =
+                                // Prepend something that wouldn't be found at the beginning of an
+                                // absolute path, so we don't get db key namespace conflicts with
+                                // embeddings, which use the abs path as a key.
+                                format!("lexical-files-{}", worktree_abs_path.to_string_lossy());
+                                db_connection.create_database(&mut txn, Some(&db_name))?
+                            };
+                            let postings_db = {
+                                let db_name =
+                                // Prepend something that wouldn't be found at the beginning of an
+                                // absolute path, so we don't get db key namespace conflicts with
+                                // embeddings, which use the abs path as a key.
+                                format!("lexical-postings-{}", worktree_abs_path.to_string_lossy());
+                                db_connection.create_database(&mut txn, Some(&db_name))?
+                            };
+                            LexicalIndex::new(files_db, postings_db)
+                        };
                         let embedding_index = {
                             let db_name = worktree_abs_path.to_string_lossy();
                             let db = db_connection.create_database(&mut txn, Some(&db_name))?;
@@ -65,6 +85,7 @@ impl WorktreeIndex {
                                 embedding_fs,
                                 db_connection.clone(),
                                 db,
+                                lexical_index,
                                 language_registry,
                                 embedding_provider,
                                 Arc::clone(&entries_being_indexed),
@@ -160,6 +181,10 @@ impl WorktreeIndex {
         &self.embedding_index
     }
 
+    pub fn lexical_index(&self) -> &LexicalIndex {
+        self.embedding_index.lexical_index()
+    }
+
     pub fn summary_index(&self) -> &SummaryIndex {
         &self.summary_index
     }
@@ -87,6 +87,23 @@ impl Connection {
         self.backup_main(&destination)
     }
 
+    /// Rebuilds the database file if at least `min_free_ratio` of its pages are unused, returning
+    /// whether it did. Must be called from a write capable connection outside of a transaction.
+    pub fn vacuum_if_fragmented(&self, min_free_ratio: f64) -> Result<bool> {
+        let page_count = self.select_row::<usize>("PRAGMA page_count")?()?.unwrap_or(0);
+        let free_pages = self.select_row::<usize>("PRAGMA freelist_count")?()?.unwrap_or(0);
+        if page_count == 0 || (free_pages as f64) < page_count as f64 * min_free_ratio {
+            return Ok(false);
+        }
+
+        self.exec("VACUUM")?()?;
+        if self.persistent {
+            // Shrink the write-ahead log too, which grows to the size of the rebuilt database.
+            self.select_row::<(i32, i32, i32)>("PRAGMA wal_checkpoint(TRUNCATE)")?()?;
+        }
+        Ok(true)
+    }
+
     pub fn sql_has_syntax_error(&self, sql: &str) -> Option<(String, usize)> {
         let sql = CString::new(sql).unwrap();
         let mut remaining_sql = sql.as_c_str();
@@ -293,6 +310,30 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn vacuum_if_fragmented() {
+        let connection = Connection::open_memory(Some("vacuum_if_fragmented"));
+        connection.exec("CREATE TABLE blobs (blob BLOB)").unwrap()().unwrap();
+        let mut insert = connection
+            .exec_bound::<Vec<u8>>("INSERT INTO blobs (blob) VALUES (?)")
+            .unwrap();
+        for _ in 0..64 {
+            insert(vec![0; 4096]).unwrap();
+        }
+        drop(insert);
+
+        assert!(!connection.vacuum_if_fragmented(0.25).unwrap());
+        connection.exec("DELETE FROM blobs").unwrap()().unwrap();
+        assert!(connection.vacuum_if_fragmented(0.25).unwrap());
+        assert_eq!(
+            connection
+                .select_row::<usize>("PRAGMA freelist_count")
+                .unwrap()()
+            .unwrap(),
+            Some(0)
+        );
+    }
+
     #[test]
     fn tuple_round_trips() {
         let connection = Connection::open_memory(Some("tuple_round_trips"));
@@ -0,0 +1,281 @@
+// Domains are exported to a standalone database file containing their tables and the migrations
+// that created them. On import, the export is first migrated to the importing database's version
+// of each domain, so files written by older versions can be read by newer ones.
+
+use std::path::Path;
+
+use anyhow::{Context as _, Result, anyhow};
+
+use crate::connection::Connection;
+
+const EXPORT_SCHEMA: &str = "export";
+
+impl Connection {
+    /// Returns the names of the domains that have been migrated in this database.
+    pub fn domains(&self) -> Result<Vec<String>> {
+        if !self.has_migrations_table()? {
+            return Ok(Vec::new());
+        }
+        self.select::<String>("SELECT DISTINCT domain FROM migrations ORDER BY domain")?()
+    }
+
+    /// Copies the tables of the given domains, along with the migrations that created them, to a
+    /// new database file at `destination`.
+    pub fn export_domains(&self, domains: &[&str], destination: &Path) -> Result<()> {
+        let export = Connection::open(&destination.to_string_lossy(), true)?;
+        let mut tables = Vec::new();
+        for domain in domains {
+            let migrations = self.domain_migrations(domain)?;
+            if migrations.is_empty() {
+                return Err(anyhow!("No migrations found for domain {domain}"));
+            }
+            let migrations = migrations.iter().map(String::as_str).collect::<Vec<_>>();
+            export
+                .migrate(domain, &migrations)
+                .with_context(|| format!("Failed to create tables for domain {domain}"))?;
+            for table in domain_tables(&migrations)? {
+                if !tables.contains(&table) {
+                    tables.push(table);
+                }
+            }
+        }
+        drop(export);
+
+        self.with_attached(destination, || {
+            self.with_savepoint("export_domains", || {
+                for table in &tables {
+                    let table = quote_identifier(table);
+                    self.exec(&format!(
+                        "INSERT INTO {EXPORT_SCHEMA}.{table} SELECT * FROM main.{table}"
+                    ))?()?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    /// Replaces the contents of every domain's tables with the ones in an export created by
+    /// [`Connection::export_domains`], returning the names of the imported domains.
+    ///
+    /// Exports from older versions are brought up to date by applying the migrations this
+    /// database has for each domain. Rows left referencing missing rows in other tables, such as
+    /// items of workspaces that weren't imported, are removed.
+    pub fn import_domains(&self, source: &Path) -> Result<Vec<String>> {
+        let import = Connection::open(&source.to_string_lossy(), true)?;
+        let domains = import.domains()?;
+        if domains.is_empty() {
+            return Err(anyhow!("Nothing to import"));
+        }
+
+        let mut tables = Vec::new();
+        for domain in &domains {
+            let imported_migrations = import.domain_migrations(domain)?;
+            let current_migrations = self.domain_migrations(domain)?;
+            let migrations = if current_migrations.is_empty() {
+                // The domain hasn't been used here yet. Create its tables as they were when they
+                // were exported, and they'll be migrated the first time the domain is opened.
+                let migrations = imported_migrations
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                self.migrate(domain, &migrations)?;
+                imported_migrations
+            } else if imported_migrations.len() > current_migrations.len() {
+                return Err(anyhow!(
+                    "Domain {domain} was exported from a newer version and can't be imported"
+                ));
+            } else {
+                let migrations = current_migrations
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                import
+                    .migrate(domain, &migrations)
+                    .with_context(|| format!("Failed to migrate imported domain {domain}"))?;
+                current_migrations
+            };
+            let migrations = migrations.iter().map(String::as_str).collect::<Vec<_>>();
+            for table in domain_tables(&migrations)? {
+                if !tables.contains(&table) {
+                    tables.push(table);
+                }
+            }
+        }
+        drop(import);
+
+        self.with_attached(source, || {
+            self.with_savepoint("import_domains", || {
+                for table in &tables {
+                    let table = quote_identifier(table);
+                    self.exec(&format!("DELETE FROM main.{table}"))?()?;
+                    self.exec(&format!(
+                        "INSERT INTO main.{table} SELECT * FROM {EXPORT_SCHEMA}.{table}"
+                    ))?()?;
+                }
+                self.delete_foreign_key_violations()
+            })
+        })?;
+
+        Ok(domains)
+    }
+
+    fn has_migrations_table(&self) -> Result<bool> {
+        Ok(self.select_row::<String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'migrations'",
+        )?()?
+        .is_some())
+    }
+
+    fn domain_migrations(&self, domain: &str) -> Result<Vec<String>> {
+        if !self.has_migrations_table()? {
+            return Ok(Vec::new());
+        }
+        self.select_bound::<&str, String>(
+            "SELECT migration FROM migrations WHERE domain = ? ORDER BY step",
+        )?(domain)
+    }
+
+    /// Attaches the database at `path` as the `export` schema, with foreign keys disabled so that
+    /// tables can be copied in any order.
+    fn with_attached<R>(&self, path: &Path, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        let foreign_keys = self.select_row::<bool>("PRAGMA foreign_keys")?()?.unwrap_or(false);
+        self.exec("PRAGMA foreign_keys = OFF")?()?;
+        self.exec_bound::<&str>(&format!("ATTACH DATABASE ? AS {EXPORT_SCHEMA}"))?(
+            &path.to_string_lossy(),
+        )?;
+
+        let result = f();
+
+        self.exec(&format!("DETACH DATABASE {EXPORT_SCHEMA}"))?()?;
+        if foreign_keys {
+            self.exec("PRAGMA foreign_keys = ON")?()?;
+        }
+        result
+    }
+
+    fn delete_foreign_key_violations(&self) -> Result<()> {
+        // Deleting a row can leave the rows referencing it dangling, so repeat until there are
+        // no violations left.
+        loop {
+            let violations = self
+                .select::<(String, Option<i64>, String, i64)>("PRAGMA main.foreign_key_check")?(
+            )?;
+            let mut deleted_any = false;
+            for (table, rowid, _, _) in violations {
+                // Tables without rowids can't be cleaned up this way.
+                let Some(rowid) = rowid else {
+                    continue;
+                };
+                self.exec_bound::<i64>(&format!(
+                    "DELETE FROM main.{} WHERE rowid = ?",
+                    quote_identifier(&table)
+                ))?(rowid)?;
+                deleted_any = true;
+            }
+            if !deleted_any {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Returns the tables created by a domain's migrations, by running them in an empty database.
+fn domain_tables(migrations: &[&str]) -> Result<Vec<String>> {
+    let connection = Connection::open_memory(None);
+    for migration in migrations {
+        connection.eager_exec(migration)?;
+    }
+    connection.select::<String>(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid",
+    )?()
+}
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::connection::Connection;
+
+    #[test]
+    fn test_export_and_import_applies_migrations() {
+        let source = Connection::open_memory(Some("test_export_source"));
+        source
+            .migrate(
+                "notes",
+                &["CREATE TABLE notes(id INTEGER PRIMARY KEY, text TEXT NOT NULL) STRICT;"],
+            )
+            .unwrap();
+        source
+            .migrate("other", &["CREATE TABLE other(value TEXT) STRICT;"])
+            .unwrap();
+        source
+            .exec("INSERT INTO notes(id, text) VALUES (1, 'one'), (2, 'two')")
+            .unwrap()()
+        .unwrap();
+        source
+            .exec("INSERT INTO other(value) VALUES ('not exported')")
+            .unwrap()()
+        .unwrap();
+
+        let path = std::env::temp_dir().join(format!("sqlez-export-{}.db", uuid::Uuid::new_v4()));
+        source.export_domains(&["notes"], &path).unwrap();
+
+        // The importing database has a newer version of the domain.
+        let target = Connection::open_memory(Some("test_export_target"));
+        target
+            .migrate(
+                "notes",
+                &[
+                    "CREATE TABLE notes(id INTEGER PRIMARY KEY, text TEXT NOT NULL) STRICT;",
+                    indoc! {"
+                        ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+                        UPDATE notes SET pinned = 1 WHERE id = 2;
+                    "},
+                ],
+            )
+            .unwrap();
+        target
+            .exec("INSERT INTO notes(id, text, pinned) VALUES (3, 'replaced', 0)")
+            .unwrap()()
+        .unwrap();
+
+        assert_eq!(target.import_domains(&path).unwrap(), vec!["notes"]);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(
+            target
+                .select::<(i64, String, bool)>("SELECT id, text, pinned FROM notes ORDER BY id")
+                .unwrap()()
+            .unwrap(),
+            vec![(1, "one".to_string(), false), (2, "two".to_string(), true)]
+        );
+        assert_eq!(target.domains().unwrap(), vec!["notes"]);
+    }
+
+    #[test]
+    fn test_import_from_newer_version_fails() {
+        let source = Connection::open_memory(Some("test_import_newer_source"));
+        source
+            .migrate(
+                "notes",
+                &[
+                    "CREATE TABLE notes(text TEXT) STRICT;",
+                    "ALTER TABLE notes ADD COLUMN pinned INTEGER;",
+                ],
+            )
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("sqlez-export-{}.db", uuid::Uuid::new_v4()));
+        source.export_domains(&["notes"], &path).unwrap();
+
+        let target = Connection::open_memory(Some("test_import_newer_target"));
+        target
+            .migrate("notes", &["CREATE TABLE notes(text TEXT) STRICT;"])
+            .unwrap();
+        assert!(target.import_domains(&path).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+}
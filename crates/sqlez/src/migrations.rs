@@ -13,7 +13,7 @@ use libsqlite3_sys::sqlite3_exec;
 use crate::connection::Connection;
 
 impl Connection {
-    fn eager_exec(&self, sql: &str) -> anyhow::Result<()> {
+    pub(crate) fn eager_exec(&self, sql: &str) -> anyhow::Result<()> {
         let sql_str = CString::new(sql).context("Error creating cstr")?;
         unsafe {
             sqlite3_exec(
@@ -34,7 +34,7 @@ impl Connection {
     /// Note: Unlike everything else in SQLez, migrations are run eagerly, without first
     /// preparing the SQL statements. This makes it possible to do multi-statement schema
     /// updates in a single string without running into prepare errors.
-    pub fn migrate(&self, domain: &'static str, migrations: &[&'static str]) -> Result<()> {
+    pub fn migrate(&self, domain: &str, migrations: &[&str]) -> Result<()> {
         self.with_savepoint("migrating", || {
             // Setup the migrations table unconditionally
             self.exec(indoc! {"
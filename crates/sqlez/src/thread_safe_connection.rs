@@ -1,5 +1,5 @@
 use anyhow::Context as _;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use futures::{Future, FutureExt, channel::oneshot};
 use parking_lot::{Mutex, RwLock};
 use std::{
@@ -7,13 +7,18 @@ use std::{
     ops::Deref,
     sync::{Arc, LazyLock},
     thread,
+    time::{Duration, Instant},
 };
 use thread_local::ThreadLocal;
+use util::ResultExt as _;
 
 use crate::{connection::Connection, domain::Migrator, util::UnboundedSyncSender};
 
 const MIGRATION_RETRIES: usize = 10;
 
+/// The share of a database's pages that have to be unused for periodic compaction to rebuild it.
+const VACUUM_FREE_PAGE_RATIO: f64 = 0.25;
+
 type QueuedWrite = Box<dyn 'static + Send + FnOnce()>;
 type WriteQueue = Box<dyn 'static + Send + Sync + Fn(QueuedWrite)>;
 type WriteQueueConstructor = Box<dyn 'static + Send + FnMut() -> WriteQueue>;
@@ -24,6 +29,9 @@ type WriteQueueConstructor = Box<dyn 'static + Send + FnMut() -> WriteQueue>;
 /// thread.
 static QUEUES: LazyLock<RwLock<HashMap<Arc<str>, WriteQueue>>> = LazyLock::new(Default::default);
 
+/// Database uris whose queued writes are no longer applied, see [`ThreadSafeConnection::block_writes`].
+static BLOCKED_WRITES: LazyLock<RwLock<HashSet<Arc<str>>>> = LazyLock::new(Default::default);
+
 /// Thread safe connection to a given database file or in memory db. This can be cloned, shared, static,
 /// whatever. It derefs to a synchronous connection by thread that is read only. A write capable connection
 /// may be accessed by passing a callback to the `write` function which will queue the callback
@@ -41,6 +49,7 @@ unsafe impl<M: Migrator> Sync for ThreadSafeConnection<M> {}
 pub struct ThreadSafeConnectionBuilder<M: Migrator + 'static = ()> {
     db_initialize_query: Option<&'static str>,
     write_queue_constructor: Option<WriteQueueConstructor>,
+    vacuum_interval: Option<Duration>,
     connection: ThreadSafeConnection<M>,
 }
 
@@ -72,9 +81,17 @@ impl<M: Migrator> ThreadSafeConnectionBuilder<M> {
         self
     }
 
+    /// Compacts the database file once enough of it is unused, checking at most once per interval
+    /// on the write queue, after a queued write. Only the first connection built for a database
+    /// file sets this up.
+    pub fn with_vacuum_interval(mut self, interval: Duration) -> Self {
+        self.vacuum_interval = Some(interval);
+        self
+    }
+
     pub async fn build(self) -> anyhow::Result<ThreadSafeConnection<M>> {
         self.connection
-            .initialize_queues(self.write_queue_constructor);
+            .initialize_queues(self.write_queue_constructor, self.vacuum_interval);
 
         let db_initialize_query = self.db_initialize_query;
 
@@ -112,13 +129,21 @@ impl<M: Migrator> ThreadSafeConnectionBuilder<M> {
 }
 
 impl<M: Migrator> ThreadSafeConnection<M> {
-    fn initialize_queues(&self, write_queue_constructor: Option<WriteQueueConstructor>) -> bool {
+    fn initialize_queues(
+        &self,
+        write_queue_constructor: Option<WriteQueueConstructor>,
+        vacuum_interval: Option<Duration>,
+    ) -> bool {
         if !QUEUES.read().contains_key(&self.uri) {
             let mut queues = QUEUES.write();
             if !queues.contains_key(&self.uri) {
                 let mut write_queue_constructor =
                     write_queue_constructor.unwrap_or_else(background_thread_queue);
-                queues.insert(self.uri.clone(), write_queue_constructor());
+                let mut write_queue = write_queue_constructor();
+                if let Some(interval) = vacuum_interval {
+                    write_queue = self.vacuum_periodically(write_queue, interval);
+                }
+                queues.insert(self.uri.clone(), write_queue);
                 return true;
             }
         }
@@ -129,6 +154,7 @@ impl<M: Migrator> ThreadSafeConnection<M> {
         ThreadSafeConnectionBuilder::<M> {
             db_initialize_query: None,
             write_queue_constructor: None,
+            vacuum_interval: None,
             connection: Self {
                 uri: Arc::from(uri),
                 persistent,
@@ -168,12 +194,56 @@ impl<M: Migrator> ThreadSafeConnection<M> {
         let thread_safe_connection = (*self).clone();
         write_channel(Box::new(move || {
             let connection = thread_safe_connection.deref();
-            let result = connection.with_write(|connection| callback(connection));
+            let result = if thread_safe_connection.writes_blocked() {
+                // Write statements fail to prepare on a connection that isn't write capable.
+                callback(connection)
+            } else {
+                connection.with_write(|connection| callback(connection))
+            };
             sender.send(result).ok();
         }));
         receiver.map(|response| response.expect("Write queue unexpectedly closed"))
     }
 
+    /// Stops applying the writes queued after this one for the database file, for the rest of the
+    /// process. Used after the database has been replaced, so that state loaded before can't be
+    /// written back over it.
+    pub fn block_writes(&self) {
+        BLOCKED_WRITES.write().insert(self.uri.clone());
+    }
+
+    fn writes_blocked(&self) -> bool {
+        BLOCKED_WRITES.read().contains(&self.uri)
+    }
+
+    /// Wraps the write queue so that a queued write is followed by compacting the database file
+    /// once the interval has passed since the last check.
+    fn vacuum_periodically(&self, write_queue: WriteQueue, interval: Duration) -> WriteQueue {
+        let connection = self.clone();
+        let last_checked_at = Mutex::new(Instant::now());
+        Box::new(move |queued_write| {
+            let vacuum_due = {
+                let mut last_checked_at = last_checked_at.lock();
+                let due = last_checked_at.elapsed() >= interval;
+                if due {
+                    *last_checked_at = Instant::now();
+                }
+                due
+            };
+            let connection = connection.clone();
+            write_queue(Box::new(move || {
+                queued_write();
+                if vacuum_due && !connection.writes_blocked() {
+                    connection
+                        .with_write(|connection| {
+                            connection.vacuum_if_fragmented(VACUUM_FREE_PAGE_RATIO)
+                        })
+                        .log_err();
+                }
+            }))
+        })
+    }
+
     pub(crate) fn create_connection(
         persistent: bool,
         uri: &str,
@@ -217,7 +287,7 @@ impl ThreadSafeConnection<()> {
             _migrator: PhantomData,
         };
 
-        connection.initialize_queues(write_queue_constructor);
+        connection.initialize_queues(write_queue_constructor, None);
         connection
     }
 }
@@ -280,7 +350,7 @@ mod test {
     use indoc::indoc;
     use std::ops::Deref;
 
-    use std::thread;
+    use std::{thread, time::Duration};
 
     use crate::{domain::Domain, thread_safe_connection::ThreadSafeConnection};
 
@@ -358,4 +428,62 @@ mod test {
 
         smol::block_on(builder.build()).unwrap();
     }
+
+    #[test]
+    fn vacuums_after_writes() {
+        let connection = smol::block_on(
+            ThreadSafeConnection::<()>::builder("vacuums_after_writes", false)
+                .with_vacuum_interval(Duration::ZERO)
+                .build(),
+        )
+        .unwrap();
+
+        smol::block_on(connection.write(|connection| {
+            connection.exec("CREATE TABLE blobs (blob BLOB)").unwrap()().unwrap();
+            let mut insert = connection
+                .exec_bound::<Vec<u8>>("INSERT INTO blobs (blob) VALUES (?)")
+                .unwrap();
+            for _ in 0..64 {
+                insert(vec![0; 4096]).unwrap();
+            }
+            drop(insert);
+            connection.exec("DELETE FROM blobs").unwrap()().unwrap();
+        }));
+        // The vacuum runs after the write has sent its result, so wait for the next queued write.
+        smol::block_on(connection.write(|_| ()));
+
+        assert_eq!(
+            connection
+                .select_row::<usize>("PRAGMA freelist_count")
+                .unwrap()()
+            .unwrap(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn blocked_writes_are_not_applied() {
+        let connection =
+            ThreadSafeConnection::new("blocked_writes_are_not_applied", false, None, None);
+        smol::block_on(connection.write(|connection| {
+            connection
+                .exec("CREATE TABLE test (value INTEGER)")
+                .unwrap()()
+            .unwrap();
+        }));
+
+        connection.block_writes();
+        let result = smol::block_on(
+            connection
+                .write(|connection| connection.exec("INSERT INTO test (value) VALUES (1)")?()),
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            connection
+                .select_row::<usize>("SELECT COUNT(*) FROM test")
+                .unwrap()()
+            .unwrap(),
+            Some(0)
+        );
+    }
 }
@@ -670,6 +670,8 @@ pub struct TaskState {
     pub label: String,
     pub command_label: String,
     pub status: TaskStatus,
+    /// The exit code of the task, once it completed.
+    pub exit_code: Option<i32>,
     pub completion_rx: Receiver<()>,
     pub hide: HideStrategy,
     pub show_summary: bool,
@@ -1448,6 +1450,37 @@ impl Terminal {
         lines
     }
 
+    /// Returns the text of the terminal, including the scrollback, with wrapped lines joined.
+    pub fn get_content(&self) -> String {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        let grid = terminal.grid();
+        let mut lines = Vec::new();
+
+        let mut current_line = grid.topmost_line().0;
+        let bottommost_line = grid.bottommost_line().0;
+        let last_column = Column(grid.columns() - 1);
+        while current_line <= bottommost_line {
+            let mut logical_line_end = current_line;
+            while logical_line_end < bottommost_line
+                && grid[Line(logical_line_end)][last_column]
+                    .flags
+                    .contains(Flags::WRAPLINE)
+            {
+                logical_line_end += 1;
+            }
+
+            let logical_line = self.construct_logical_line(grid, current_line, logical_line_end);
+            lines.push(logical_line.trim_end().to_string());
+            current_line = logical_line_end + 1;
+        }
+
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
     fn find_logical_line_start(&self, grid: &Grid<Cell>, current: i32, topmost: i32) -> i32 {
         let mut line_start = current;
         while line_start > topmost {
@@ -1847,6 +1880,17 @@ impl Terminal {
         self.debug_terminal
     }
 
+    /// Stops the task running in the terminal, if it's still running.
+    pub fn kill_active_task(&mut self) {
+        if self
+            .task
+            .as_ref()
+            .is_some_and(|task| task.status == TaskStatus::Running)
+        {
+            self.pty_tx.0.send(Msg::Shutdown).ok();
+        }
+    }
+
     pub fn wait_for_completed_task(&self, cx: &App) -> Task<()> {
         if let Some(task) = self.task() {
             if task.status == TaskStatus::Running {
@@ -1873,6 +1917,7 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        task.exit_code = error_code;
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
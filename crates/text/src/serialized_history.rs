@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// A buffer's undo and redo stacks, expressed as text edits rather than operations so that they
+/// can be restored into a buffer that was loaded from scratch, such as when a file is reopened.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedHistory {
+    /// The transactions that can be undone, oldest first.
+    pub undo: Vec<SerializedTransaction>,
+    /// The transactions that can be redone, in the order they would be redone.
+    pub redo: Vec<SerializedTransaction>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedTransaction {
+    /// The transaction's edits, ordered by offset. Offsets refer to the text as it was before the
+    /// transaction was applied.
+    pub edits: Vec<SerializedEdit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedEdit {
+    pub offset: usize,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+/// An approximation of the bookkeeping each edit adds on top of its text.
+const EDIT_OVERHEAD: usize = 16;
+
+impl SerializedHistory {
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+
+    /// The approximate number of bytes the history takes up.
+    pub fn size(&self) -> usize {
+        self.undo
+            .iter()
+            .chain(&self.redo)
+            .map(SerializedTransaction::size)
+            .sum()
+    }
+
+    /// Returns the edits that take `text`, the text the history was serialized with, back to the
+    /// text before the oldest undoable transaction, one batch per transaction. Returns `None` if
+    /// the history doesn't apply to `text`.
+    pub fn edits_to_oldest_state(&self, text: &str) -> Option<Vec<Vec<(Range<usize>, String)>>> {
+        let mut text = text.to_string();
+
+        // The redo stack must apply to the current text too.
+        let mut redone_text = text.clone();
+        for transaction in &self.redo {
+            transaction.apply(&mut redone_text)?;
+        }
+
+        let mut batches = Vec::with_capacity(self.undo.len());
+        for transaction in self.undo.iter().rev() {
+            let inverse = transaction.inverse()?;
+            for (range, expected_text, old_text) in inverse.iter().rev() {
+                if text.get(range.clone())? != expected_text {
+                    return None;
+                }
+                text.replace_range(range.clone(), old_text);
+            }
+            batches.push(
+                inverse
+                    .into_iter()
+                    .map(|(range, _, old_text)| (range, old_text.to_string()))
+                    .collect(),
+            );
+        }
+        Some(batches)
+    }
+}
+
+impl SerializedTransaction {
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.edits
+            .iter()
+            .map(|edit| edit.old_text.len() + edit.new_text.len() + EDIT_OVERHEAD)
+            .sum()
+    }
+
+    /// Returns the ranges the transaction's edits occupy after it was applied, along with the
+    /// text it inserted and replaced there.
+    fn inverse(&self) -> Option<Vec<(Range<usize>, &str, &str)>> {
+        let mut inverse = Vec::with_capacity(self.edits.len());
+        let mut previous_end = 0;
+        let mut delta = 0_isize;
+        for edit in &self.edits {
+            if edit.offset < previous_end {
+                return None;
+            }
+            previous_end = edit.offset + edit.old_text.len();
+            let start = edit.offset.checked_add_signed(delta)?;
+            inverse.push((
+                start..start + edit.new_text.len(),
+                edit.new_text.as_str(),
+                edit.old_text.as_str(),
+            ));
+            delta += edit.new_text.len() as isize - edit.old_text.len() as isize;
+        }
+        Some(inverse)
+    }
+
+    fn apply(&self, text: &mut String) -> Option<()> {
+        // Checks that the edits are ordered and don't overlap.
+        self.inverse()?;
+        for edit in &self.edits {
+            if text.get(edit.offset..edit.offset + edit.old_text.len())? != edit.old_text {
+                return None;
+            }
+        }
+        for edit in self.edits.iter().rev() {
+            text.replace_range(
+                edit.offset..edit.offset + edit.old_text.len(),
+                &edit.new_text,
+            );
+        }
+        Some(())
+    }
+}
@@ -623,6 +623,126 @@ fn test_history() {
     assert_eq!(buffer.text(), "X12cde6");
 }
 
+#[test]
+fn test_undo_tree() {
+    let mut now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "abc".into());
+
+    let mut edit = |buffer: &mut Buffer, range: Range<usize>, text: &str| {
+        now += Duration::from_secs(1);
+        buffer.start_transaction_at(now);
+        buffer.edit([(range, text)]);
+        buffer.end_transaction_at(now).unwrap().0
+    };
+    let one = edit(&mut buffer, 0..0, "1");
+    let two = edit(&mut buffer, 4..4, "2");
+    assert_eq!(buffer.text(), "1abc2");
+
+    // Undoing and making a new edit keeps the undone transaction as a branch.
+    buffer.undo();
+    let three = edit(&mut buffer, 4..4, "3");
+    assert_eq!(buffer.text(), "1abc3");
+    buffer.redo();
+    assert_eq!(buffer.text(), "1abc3");
+
+    let tree = buffer.undo_tree();
+    assert_eq!(
+        tree.iter()
+            .map(|node| (node.transaction_id, node.parent, node.is_applied))
+            .collect::<Vec<_>>(),
+        vec![
+            (one, None, true),
+            (two, Some(one), false),
+            (three, Some(one), true),
+        ]
+    );
+    assert_eq!(buffer.current_history_state(), Some(three));
+
+    // Any state can be reached, switching branches on the way.
+    buffer.go_to_history_state(Some(two));
+    assert_eq!(buffer.text(), "1abc2");
+    assert_eq!(buffer.current_history_state(), Some(two));
+    buffer.go_to_history_state(None);
+    assert_eq!(buffer.text(), "abc");
+    buffer.go_to_history_state(Some(three));
+    assert_eq!(buffer.text(), "1abc3");
+
+    // After switching, undo and redo follow the new branch.
+    buffer.go_to_history_state(Some(two));
+    buffer.undo();
+    buffer.redo();
+    assert_eq!(buffer.text(), "1abc2");
+
+    // Stepping through states goes by the order they were made in, across branches.
+    assert_eq!(buffer.history_state_by_steps(1), Some(three));
+    assert_eq!(buffer.history_state_by_steps(-1), Some(one));
+    assert_eq!(buffer.history_state_by_steps(-5), None);
+    assert_eq!(buffer.history_state_at(tree[1].edited_at), Some(two));
+
+    assert_eq!(
+        buffer.history_state_edits(three).unwrap(),
+        vec![SerializedEdit {
+            offset: 4,
+            old_text: String::new(),
+            new_text: "3".into(),
+        }]
+    );
+
+    // The edits of states on other branches and of applied states don't change the buffer.
+    buffer.go_to_history_state(Some(three));
+    assert_eq!(
+        buffer.history_state_edits(two).unwrap(),
+        vec![SerializedEdit {
+            offset: 4,
+            old_text: String::new(),
+            new_text: "2".into(),
+        }]
+    );
+    assert_eq!(
+        buffer.history_state_edits(one).unwrap(),
+        vec![SerializedEdit {
+            offset: 0,
+            old_text: String::new(),
+            new_text: "1".into(),
+        }]
+    );
+    assert_eq!(buffer.text(), "1abc3");
+    assert_eq!(buffer.current_history_state(), Some(three));
+}
+
+#[test]
+fn test_undo_tree_branch_limit() {
+    let mut now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "".into());
+
+    let mut edit = |buffer: &mut Buffer| {
+        now += Duration::from_millis(1);
+        buffer.start_transaction_at(now);
+        buffer.edit([(0..0, "a")]);
+        buffer.end_transaction_at(now).unwrap().0
+    };
+    let mut abandoned = Vec::new();
+    for _ in 0..MAX_BRANCH_TRANSACTIONS + 10 {
+        abandoned.push(edit(&mut buffer));
+        buffer.undo();
+        edit(&mut buffer);
+    }
+
+    // The branches that were abandoned first are dropped.
+    let tree = buffer.undo_tree();
+    let unapplied = tree
+        .iter()
+        .filter(|node| !node.is_applied)
+        .map(|node| node.transaction_id)
+        .collect::<Vec<_>>();
+    assert_eq!(unapplied, abandoned[10..]);
+    assert_eq!(
+        tree.len(),
+        MAX_BRANCH_TRANSACTIONS * 2 + 10,
+        "every applied state is kept"
+    );
+}
+
 #[test]
 fn test_finalize_last_transaction() {
     let now = Instant::now();
@@ -5,6 +5,7 @@ pub mod network;
 pub mod operation_queue;
 mod patch;
 mod selection;
+mod serialized_history;
 pub mod subscription;
 #[cfg(test)]
 mod tests;
@@ -23,6 +24,7 @@ use postage::{oneshot, prelude::*};
 use regex::Regex;
 pub use rope::*;
 pub use selection::*;
+pub use serialized_history::*;
 use std::{
     borrow::Cow,
     cmp::{self, Ordering, Reverse},
@@ -32,7 +34,7 @@ use std::{
     num::NonZeroU64,
     ops::{self, Deref, Range, Sub},
     str,
-    sync::{Arc, LazyLock},
+    sync::{Arc, LazyLock, OnceLock},
     time::{Duration, Instant},
 };
 pub use subscription::*;
@@ -142,15 +144,67 @@ impl HistoryEntry {
     }
 }
 
+#[derive(Clone)]
 struct History {
     base_text: Rope,
     operations: TreeMap<clock::Lamport, Operation>,
     undo_stack: Vec<HistoryEntry>,
     redo_stack: Vec<HistoryEntry>,
+    /// Redo stacks that were abandoned by making a new edit, kept so that they can still be
+    /// navigated to. Together with the undo and redo stacks, they form the undo tree.
+    branches: Vec<HistoryBranch>,
+    /// The undo tree, built from the stacks and branches the first time it's needed after they
+    /// change.
+    tree: OnceLock<Arc<UndoTree>>,
     transaction_depth: usize,
     group_interval: Duration,
 }
 
+/// The maximum number of transactions kept on abandoned branches of the undo tree. Past it, the
+/// branches that were abandoned first are dropped.
+const MAX_BRANCH_TRANSACTIONS: usize = 1000;
+
+#[derive(Clone, Debug)]
+struct HistoryBranch {
+    /// The transaction the branch starts after, or `None` if it starts at the initial state.
+    parent: Option<TransactionId>,
+    /// The branch's transactions, in the order they would be redone.
+    entries: Vec<HistoryEntry>,
+}
+
+struct UndoTree {
+    nodes: Arc<[UndoTreeNode]>,
+    parents: HashMap<TransactionId, Option<TransactionId>>,
+}
+
+/// A state in a buffer's undo tree, reached by applying a transaction on top of its parent.
+#[derive(Clone, Debug)]
+pub struct UndoTreeNode {
+    pub transaction_id: TransactionId,
+    /// The transaction the node's transaction was made on top of, or `None` if it was made in the
+    /// initial state.
+    pub parent: Option<TransactionId>,
+    pub edited_at: Instant,
+    /// Whether the transaction is currently applied, meaning that the node is the current state
+    /// or one of its ancestors.
+    pub is_applied: bool,
+}
+
+/// A copy of a buffer's text and undo history, returned by [`Buffer::history_snapshot`].
+pub struct HistorySnapshot(Buffer);
+
+impl HistorySnapshot {
+    /// The buffer's text when the snapshot was taken.
+    pub fn text(&self) -> &Rope {
+        self.0.as_rope()
+    }
+
+    /// See [`Buffer::serialize_history`].
+    pub fn serialize(&self, max_size: usize) -> SerializedHistory {
+        self.0.serialize_history_unchecked(max_size)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct InsertionSlice {
     edit_id: clock::Lamport,
@@ -191,6 +245,8 @@ impl History {
             operations: Default::default(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            branches: Vec::new(),
+            tree: OnceLock::new(),
             transaction_depth: 0,
             // Don't group transactions in tests unless we opt in, because it's a footgun.
             #[cfg(any(test, feature = "test-support"))]
@@ -212,6 +268,7 @@ impl History {
     ) -> Option<TransactionId> {
         self.transaction_depth += 1;
         if self.transaction_depth == 1 {
+            self.tree.take();
             let id = clock.tick();
             self.undo_stack.push(HistoryEntry {
                 transaction: Transaction {
@@ -233,6 +290,7 @@ impl History {
         assert_ne!(self.transaction_depth, 0);
         self.transaction_depth -= 1;
         if self.transaction_depth == 0 {
+            self.tree.take();
             if self
                 .undo_stack
                 .last()
@@ -244,7 +302,12 @@ impl History {
                 self.undo_stack.pop();
                 None
             } else {
-                self.redo_stack.clear();
+                let parent = self
+                    .undo_stack
+                    .iter()
+                    .nth_back(1)
+                    .map(|entry| entry.transaction.id);
+                self.branch_off_redo_stack(parent);
                 let entry = self.undo_stack.last_mut().unwrap();
                 entry.last_edit_at = now;
                 Some(entry)
@@ -287,6 +350,7 @@ impl History {
     }
 
     fn group_trailing(&mut self, n: usize) -> Option<TransactionId> {
+        self.tree.take();
         let new_len = self.undo_stack.len() - n;
         let (entries_to_keep, entries_to_merge) = self.undo_stack.split_at_mut(new_len);
         if let Some(last_entry) = entries_to_keep.last_mut() {
@@ -314,13 +378,129 @@ impl History {
 
     fn push_transaction(&mut self, transaction: Transaction, now: Instant) {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
+        let parent = self.undo_stack.last().map(|entry| entry.transaction.id);
+        self.branch_off_redo_stack(parent);
         self.undo_stack.push(HistoryEntry {
             transaction,
             first_edit_at: now,
             last_edit_at: now,
             suppress_grouping: false,
         });
-        self.redo_stack.clear();
+    }
+
+    /// Moves the redo stack to a branch of the undo tree, before a new transaction replaces it.
+    fn branch_off_redo_stack(&mut self, parent: Option<TransactionId>) {
+        if self.redo_stack.is_empty() {
+            return;
+        }
+        let entries = self.redo_stack.drain(..).rev().collect();
+        self.push_branch(HistoryBranch { parent, entries });
+        // Keep the state the branch starts from, rather than grouping it with later transactions.
+        if let Some(entry) = self
+            .undo_stack
+            .iter_mut()
+            .rfind(|entry| Some(entry.transaction.id) == parent)
+        {
+            entry.suppress_grouping = true;
+        }
+    }
+
+    /// Makes the branch of the undo tree that continues with `next` the redo stack, turning the
+    /// current redo stack into a branch. Returns false if `next` doesn't follow `parent`.
+    fn switch_to_branch(&mut self, parent: Option<TransactionId>, next: TransactionId) -> bool {
+        if self.redo_stack.last().map(|entry| entry.transaction.id) == Some(next) {
+            return true;
+        }
+        let Some(branch_ix) = self.branches.iter().position(|branch| {
+            branch.parent == parent
+                && branch.entries.first().map(|entry| entry.transaction.id) == Some(next)
+        }) else {
+            return false;
+        };
+        self.tree.take();
+        let branch = self.branches.remove(branch_ix);
+        if !self.redo_stack.is_empty() {
+            let entries = self.redo_stack.drain(..).rev().collect();
+            self.push_branch(HistoryBranch { parent, entries });
+        }
+        self.redo_stack = branch.entries.into_iter().rev().collect();
+        true
+    }
+
+    fn push_branch(&mut self, branch: HistoryBranch) {
+        self.branches.push(branch);
+        let mut len = self
+            .branches
+            .iter()
+            .map(|branch| branch.entries.len())
+            .sum::<usize>();
+        // A branch only grows from states that are applied, so it's always abandoned before the
+        // branch it grew from, and dropping the oldest branches never leaves one unreachable.
+        while len > MAX_BRANCH_TRANSACTIONS {
+            len -= self.branches.remove(0).entries.len();
+        }
+    }
+
+    /// Returns the entry of the given transaction, wherever it is in the undo tree.
+    fn entry(&self, transaction_id: TransactionId) -> Option<&HistoryEntry> {
+        self.undo_stack
+            .iter()
+            .chain(&self.redo_stack)
+            .chain(self.branches.iter().flat_map(|branch| &branch.entries))
+            .find(|entry| entry.transaction.id == transaction_id)
+    }
+
+    fn tree(&self) -> &UndoTree {
+        self.tree.get_or_init(|| {
+            let nodes = self.tree_nodes();
+            let parents = nodes
+                .iter()
+                .map(|node| (node.transaction_id, node.parent))
+                .collect();
+            Arc::new(UndoTree {
+                nodes: nodes.into(),
+                parents,
+            })
+        })
+    }
+
+    /// Returns the nodes of the undo tree, in the order their transactions were made.
+    fn tree_nodes(&self) -> Vec<UndoTreeNode> {
+        let mut nodes = Vec::new();
+        let mut parent = None;
+        for entry in &self.undo_stack {
+            nodes.push(UndoTreeNode {
+                transaction_id: entry.transaction.id,
+                parent,
+                edited_at: entry.last_edit_at,
+                is_applied: true,
+            });
+            parent = Some(entry.transaction.id);
+        }
+        for entry in self.redo_stack.iter().rev() {
+            nodes.push(UndoTreeNode {
+                transaction_id: entry.transaction.id,
+                parent,
+                edited_at: entry.last_edit_at,
+                is_applied: false,
+            });
+            parent = Some(entry.transaction.id);
+        }
+        for branch in &self.branches {
+            let mut parent = branch.parent;
+            for entry in &branch.entries {
+                nodes.push(UndoTreeNode {
+                    transaction_id: entry.transaction.id,
+                    parent,
+                    edited_at: entry.last_edit_at,
+                    is_applied: false,
+                });
+                parent = Some(entry.transaction.id);
+            }
+        }
+        nodes.sort_by_key(|node| (node.edited_at, node.transaction_id));
+        nodes
     }
 
     fn push_undo(&mut self, op_id: clock::Lamport) {
@@ -333,6 +513,7 @@ impl History {
 
     fn pop_undo(&mut self) -> Option<&HistoryEntry> {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
         if let Some(entry) = self.undo_stack.pop() {
             self.redo_stack.push(entry);
             self.redo_stack.last()
@@ -343,6 +524,7 @@ impl History {
 
     fn remove_from_undo(&mut self, transaction_id: TransactionId) -> Option<&HistoryEntry> {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
 
         let entry_ix = self
             .undo_stack
@@ -355,6 +537,7 @@ impl History {
 
     fn remove_from_undo_until(&mut self, transaction_id: TransactionId) -> &[HistoryEntry] {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
 
         let redo_stack_start_len = self.redo_stack.len();
         if let Some(entry_ix) = self
@@ -370,6 +553,7 @@ impl History {
 
     fn forget(&mut self, transaction_id: TransactionId) -> Option<Transaction> {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
         if let Some(entry_ix) = self
             .undo_stack
             .iter()
@@ -423,6 +607,7 @@ impl History {
 
     fn pop_redo(&mut self) -> Option<&HistoryEntry> {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
         if let Some(entry) = self.redo_stack.pop() {
             self.undo_stack.push(entry);
             self.undo_stack.last()
@@ -433,6 +618,7 @@ impl History {
 
     fn remove_from_redo(&mut self, transaction_id: TransactionId) -> &[HistoryEntry] {
         assert_eq!(self.transaction_depth, 0);
+        self.tree.take();
 
         let undo_stack_start_len = self.undo_stack.len();
         if let Some(entry_ix) = self
@@ -1356,6 +1542,252 @@ impl Buffer {
         self.history.redo_stack.last()
     }
 
+    /// Serializes the undo and redo stacks as text edits, by undoing and redoing their
+    /// transactions in a copy of the buffer. The most recent transactions are kept when the
+    /// history is larger than `max_size` bytes. Returns `None` while a transaction is in progress.
+    pub fn serialize_history(&self, max_size: usize) -> Option<SerializedHistory> {
+        Some(self.history_snapshot()?.serialize(max_size))
+    }
+
+    /// Copies the buffer's text and history, so that they can be serialized on a background
+    /// thread. Returns `None` while a transaction is in progress.
+    pub fn history_snapshot(&self) -> Option<HistorySnapshot> {
+        if self.history.transaction_depth > 0 {
+            return None;
+        }
+        Some(HistorySnapshot(self.history_scratch()))
+    }
+
+    /// Removes every transaction from the undo tree, making the current state its initial state.
+    pub fn forget_history(&mut self) {
+        self.history.undo_stack.clear();
+        self.history.redo_stack.clear();
+        self.history.branches.clear();
+        self.history.tree.take();
+    }
+
+    fn serialize_history_unchecked(&self, max_size: usize) -> SerializedHistory {
+        let mut size = 0;
+        let mut history = SerializedHistory::default();
+        let mut scratch = self.history_scratch();
+        loop {
+            let before = scratch.snapshot();
+            if scratch.undo().is_none() {
+                break;
+            }
+            // Undoing goes backwards through the transaction, so flip the edits around.
+            let edits = scratch
+                .edits_since::<usize>(&before.version)
+                .map(|edit| SerializedEdit {
+                    offset: edit.new.start,
+                    old_text: scratch.text_for_range(edit.new).collect(),
+                    new_text: before.text_for_range(edit.old).collect(),
+                })
+                .collect();
+            let transaction = SerializedTransaction { edits };
+            size += transaction.size();
+            if size > max_size {
+                break;
+            }
+            if !transaction.is_empty() {
+                history.undo.push(transaction);
+            }
+        }
+        history.undo.reverse();
+
+        let mut scratch = self.history_scratch();
+        loop {
+            let before = scratch.snapshot();
+            if scratch.redo().is_none() {
+                break;
+            }
+            let edits = scratch
+                .edits_since::<usize>(&before.version)
+                .map(|edit| SerializedEdit {
+                    offset: edit.old.start,
+                    old_text: before.text_for_range(edit.old).collect(),
+                    new_text: scratch.text_for_range(edit.new).collect(),
+                })
+                .collect();
+            let transaction = SerializedTransaction { edits };
+            size += transaction.size();
+            if size > max_size {
+                break;
+            }
+            if !transaction.is_empty() {
+                history.redo.push(transaction);
+            }
+        }
+
+        history
+    }
+
+    /// Returns every state in the undo tree, including the ones on abandoned branches, in the
+    /// order they were made.
+    pub fn undo_tree(&self) -> Arc<[UndoTreeNode]> {
+        self.history.tree().nodes.clone()
+    }
+
+    /// The transaction that was applied last, which identifies the current state in the undo
+    /// tree, or `None` if the buffer is in its initial state.
+    pub fn current_history_state(&self) -> Option<TransactionId> {
+        self.history
+            .undo_stack
+            .last()
+            .map(|entry| entry.transaction.id)
+    }
+
+    /// Returns the state that was current at the given time, following the undo tree in the
+    /// order its states were made regardless of branches.
+    pub fn history_state_at(&self, time: Instant) -> Option<TransactionId> {
+        self.history
+            .tree()
+            .nodes
+            .iter()
+            .take_while(|node| node.edited_at <= time)
+            .last()
+            .map(|node| node.transaction_id)
+    }
+
+    /// Returns the state `steps` states after the current one, or before it for negative steps,
+    /// in the order the undo tree's states were made regardless of branches.
+    pub fn history_state_by_steps(&self, steps: isize) -> Option<TransactionId> {
+        let tree = &self.history.tree().nodes;
+        let current_ix = self
+            .current_history_state()
+            .and_then(|current| tree.iter().position(|node| node.transaction_id == current));
+        let target_ix = match current_ix {
+            Some(ix) => ix as isize + steps,
+            None => steps - 1,
+        };
+        if target_ix < 0 {
+            None
+        } else {
+            let target_ix = (target_ix as usize).min(tree.len().saturating_sub(1));
+            tree.get(target_ix).map(|node| node.transaction_id)
+        }
+    }
+
+    /// Moves to the state right after the given transaction was applied, or to the initial state
+    /// for `None`, by undoing and redoing transactions and switching branches of the undo tree as
+    /// needed.
+    pub fn go_to_history_state(&mut self, target: Option<TransactionId>) -> Vec<Operation> {
+        if self.history.transaction_depth > 0 {
+            return Vec::new();
+        }
+        let Some(path) = self.history_path(target) else {
+            return Vec::new();
+        };
+
+        let mut operations = Vec::new();
+        while let Some(current) = self.current_history_state() {
+            if path.contains(&current) {
+                break;
+            }
+            operations.extend(self.undo().map(|(_, operation)| operation));
+        }
+        loop {
+            let current = self.current_history_state();
+            if current == target {
+                break;
+            }
+            let next_ix = current
+                .and_then(|current| path.iter().position(|id| *id == current))
+                .map_or(0, |ix| ix + 1);
+            let Some(next) = path.get(next_ix) else {
+                break;
+            };
+            if !self.history.switch_to_branch(current, *next) {
+                break;
+            }
+            operations.extend(self.redo().map(|(_, operation)| operation));
+        }
+        operations
+    }
+
+    /// Returns the edits the given transaction made to the state it was made in.
+    pub fn history_state_edits(
+        &self,
+        transaction_id: TransactionId,
+    ) -> Option<Vec<SerializedEdit>> {
+        if self.history.transaction_depth > 0 {
+            return None;
+        }
+        let path = self.history_path(Some(transaction_id))?;
+
+        // Only copy the transactions between the current state and the one before the given
+        // transaction, which are undone and redone to reach it.
+        let common_len = self
+            .history
+            .undo_stack
+            .iter()
+            .zip(&path)
+            .take_while(|(entry, id)| entry.transaction.id == **id)
+            .count()
+            .min(path.len() - 1);
+        let undo_stack = self.history.undo_stack[common_len..].to_vec();
+        let redo_stack = path[common_len..]
+            .iter()
+            .rev()
+            .map(|id| self.history.entry(*id).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        let mut scratch = self.scratch_with_history(History {
+            operations: self.history.operations.clone(),
+            undo_stack,
+            redo_stack,
+            ..History::new(self.history.base_text.clone())
+        });
+        while scratch.undo().is_some() {}
+        for _ in 1..path.len() - common_len {
+            scratch.redo();
+        }
+        let before = scratch.snapshot();
+        scratch.redo()?;
+        let edits = scratch
+            .edits_since::<usize>(&before.version)
+            .map(|edit| SerializedEdit {
+                offset: edit.old.start,
+                old_text: before.text_for_range(edit.old).collect(),
+                new_text: scratch.text_for_range(edit.new).collect(),
+            })
+            .collect();
+        Some(edits)
+    }
+
+    /// The transactions leading from the initial state to the given state of the undo tree.
+    fn history_path(&self, target: Option<TransactionId>) -> Option<Vec<TransactionId>> {
+        let parents = &self.history.tree().parents;
+        let mut path = Vec::new();
+        let mut node = target;
+        while let Some(id) = node {
+            if path.len() > parents.len() {
+                return None;
+            }
+            path.push(id);
+            node = *parents.get(&id)?;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// A copy of the buffer and its history that isn't connected to any replicas.
+    fn history_scratch(&self) -> Self {
+        self.scratch_with_history(self.history.clone())
+    }
+
+    fn scratch_with_history(&self, history: History) -> Self {
+        Self {
+            snapshot: self.snapshot.clone(),
+            history,
+            deferred_ops: OperationQueue::new(),
+            deferred_replicas: HashSet::default(),
+            lamport_clock: self.lamport_clock,
+            subscriptions: Default::default(),
+            edit_id_resolvers: Default::default(),
+            wait_for_version_txs: Default::default(),
+        }
+    }
+
     pub fn start_transaction(&mut self) -> Option<TransactionId> {
         self.start_transaction_at(Instant::now())
     }
@@ -351,13 +351,12 @@ impl TitleBar {
     }
 
     fn render_ssh_project_host(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
-        let options = self.project.read(cx).ssh_connection_options(cx)?;
+        let options = self.project.read(cx).remote_connection_options(cx)?;
         let host: SharedString = options.connection_string().into();
 
         let nickname = options
-            .nickname
-            .clone()
-            .map(|nick| nick.into())
+            .nickname()
+            .map(|nick| SharedString::from(nick.to_string()))
             .unwrap_or_else(|| host.clone());
 
         let (indicator_color, meta) = match self.project.read(cx).ssh_connection_state(cx)? {
@@ -0,0 +1,563 @@
+mod undo_tree_panel_settings;
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use fs::Fs;
+use gpui::{
+    App, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, Render, StatefulInteractiveElement, Styled,
+    Subscription, Task, WeakEntity, Window, actions, px,
+};
+use language::{BufferEvent, SerializedEdit, TransactionId, UndoTreeNode};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{ListItem, ListItemSpacing, Tab, prelude::*};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+pub use undo_tree_panel_settings::UndoTreePanelSettings;
+
+const UNDO_TREE_PANEL_KEY: &str = "UndoTreePanel";
+
+actions!(undo_tree_panel, [ToggleFocus]);
+
+pub fn init(cx: &mut App) {
+    UndoTreePanelSettings::register(cx);
+}
+
+/// Shows the undo tree of the active editor's buffer, where undone changes are kept as branches
+/// rather than discarded, and lets the user preview and move to any of its states.
+pub struct UndoTreePanel {
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    active_editor: Option<Entity<Editor>>,
+    entries: Vec<Entry>,
+    selected: Option<SelectedState>,
+    pending_serialization: Task<Option<()>>,
+    _buffer_subscription: Option<Subscription>,
+    _workspace_subscription: Subscription,
+}
+
+struct Entry {
+    node: UndoTreeNode,
+    depth: usize,
+    is_current: bool,
+}
+
+/// The state picked in the panel, along with the edits that led to it. `None` stands for the
+/// buffer's initial state.
+struct SelectedState {
+    transaction_id: Option<TransactionId>,
+    edits: Vec<SerializedEdit>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedUndoTreePanel {
+    width: Option<Pixels>,
+}
+
+impl UndoTreePanel {
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(async move |cx| {
+            let serialized_panel = if let Some(panel) = cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(UNDO_TREE_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedUndoTreePanel>(&panel)?)
+            } else {
+                None
+            };
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let panel = Self::new(workspace, window, cx);
+                if let Some(serialized_panel) = serialized_panel {
+                    panel.update(cx, |panel, cx| {
+                        panel.width = serialized_panel.width.map(|w| w.round());
+                        cx.notify();
+                    });
+                }
+                panel
+            })
+        })
+    }
+
+    fn new(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.entity();
+        cx.new(|cx| {
+            let workspace_subscription = cx.subscribe_in(
+                &workspace_handle,
+                window,
+                |this, workspace, event, _, cx| {
+                    if let workspace::Event::ActiveItemChanged = event {
+                        let editor = active_editor(workspace.read(cx), cx);
+                        this.set_active_editor(editor, cx);
+                    }
+                },
+            );
+            let mut this = Self {
+                fs,
+                width: None,
+                focus_handle: cx.focus_handle(),
+                active_editor: None,
+                entries: Vec::new(),
+                selected: None,
+                pending_serialization: Task::ready(None),
+                _buffer_subscription: None,
+                _workspace_subscription: workspace_subscription,
+            };
+            this.set_active_editor(active_editor(workspace, cx), cx);
+            this
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        UNDO_TREE_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedUndoTreePanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn set_active_editor(&mut self, editor: Option<Entity<Editor>>, cx: &mut Context<Self>) {
+        if editor == self.active_editor {
+            return;
+        }
+        let buffer = editor
+            .as_ref()
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        self._buffer_subscription = buffer.map(|buffer| {
+            cx.subscribe(&buffer, |this, _, event, cx| match event {
+                BufferEvent::Edited | BufferEvent::Reloaded => this.refresh(cx),
+                _ => {}
+            })
+        });
+        self.active_editor = editor;
+        self.selected = None;
+        self.refresh(cx);
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let Some(buffer) = self.active_buffer(cx) else {
+            self.entries.clear();
+            cx.notify();
+            return;
+        };
+        let buffer = buffer.read(cx);
+        self.entries = layout_tree(&buffer.undo_tree(), buffer.current_history_state());
+        if let Some(SelectedState {
+            transaction_id: Some(transaction_id),
+            ..
+        }) = self.selected
+        {
+            if !self
+                .entries
+                .iter()
+                .any(|entry| entry.node.transaction_id == transaction_id)
+            {
+                self.selected = None;
+            }
+        }
+        cx.notify();
+    }
+
+    fn active_buffer(&self, cx: &App) -> Option<Entity<language::Buffer>> {
+        self.active_editor
+            .as_ref()?
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .as_singleton()
+    }
+
+    fn select(&mut self, transaction_id: Option<TransactionId>, cx: &mut Context<Self>) {
+        let Some(buffer) = self.active_buffer(cx) else {
+            return;
+        };
+        let edits = match transaction_id {
+            Some(transaction_id) => buffer
+                .read(cx)
+                .history_state_edits(transaction_id)
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        self.selected = Some(SelectedState {
+            transaction_id,
+            edits,
+        });
+        cx.notify();
+    }
+
+    fn go_to_state(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(editor) = self.active_editor.clone() {
+            editor.update(cx, |editor, cx| {
+                editor.go_to_history_state(transaction_id, window, cx)
+            });
+        }
+    }
+
+    fn render_entry(&self, ix: usize, entry: &Entry, cx: &mut Context<Self>) -> impl IntoElement {
+        let transaction_id = entry.node.transaction_id;
+        let is_selected = self
+            .selected
+            .as_ref()
+            .is_some_and(|selected| selected.transaction_id == Some(transaction_id));
+        let icon = if entry.is_current {
+            IconName::ArrowRight
+        } else {
+            IconName::Dash
+        };
+        let icon_color = if entry.is_current {
+            Color::Accent
+        } else if entry.node.is_applied {
+            Color::Default
+        } else {
+            Color::Muted
+        };
+        ListItem::new(("undo-tree-entry", ix))
+            .spacing(ListItemSpacing::Sparse)
+            .indent_level(entry.depth)
+            .indent_step_size(px(12.))
+            .toggle_state(is_selected)
+            .start_slot(Icon::new(icon).size(IconSize::Small).color(icon_color))
+            .child(
+                Label::new(format_elapsed(entry.node.edited_at.elapsed()))
+                    .size(LabelSize::Small)
+                    .color(if entry.node.is_applied {
+                        Color::Default
+                    } else {
+                        Color::Muted
+                    }),
+            )
+            .on_click(
+                cx.listener(move |this, event: &gpui::ClickEvent, window, cx| {
+                    if event.down.click_count > 1 {
+                        this.go_to_state(Some(transaction_id), window, cx);
+                    } else {
+                        this.select(Some(transaction_id), cx);
+                    }
+                }),
+            )
+    }
+
+    fn render_initial_state(&self, is_current: bool, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_selected = self
+            .selected
+            .as_ref()
+            .is_some_and(|selected| selected.transaction_id.is_none());
+        ListItem::new("undo-tree-initial-state")
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(is_selected)
+            .start_slot(
+                Icon::new(if is_current {
+                    IconName::ArrowRight
+                } else {
+                    IconName::Dash
+                })
+                .size(IconSize::Small)
+                .color(if is_current {
+                    Color::Accent
+                } else {
+                    Color::Default
+                }),
+            )
+            .child(Label::new("Initial state").size(LabelSize::Small))
+            .on_click(cx.listener(|this, event: &gpui::ClickEvent, window, cx| {
+                if event.down.click_count > 1 {
+                    this.go_to_state(None, window, cx);
+                } else {
+                    this.select(None, cx);
+                }
+            }))
+    }
+
+    fn render_preview(&self, selected: &SelectedState, cx: &mut Context<Self>) -> impl IntoElement {
+        let transaction_id = selected.transaction_id;
+        let mut lines = Vec::new();
+        for edit in &selected.edits {
+            for line in edit.old_text.lines() {
+                lines.push((format!("- {line}"), Color::Deleted));
+            }
+            for line in edit.new_text.lines() {
+                lines.push((format!("+ {line}"), Color::Created));
+            }
+            lines.push((String::new(), Color::Default));
+        }
+        lines.pop();
+
+        v_flex()
+            .flex_1()
+            .min_h_0()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .child(
+                        Label::new("Preview")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("go-to-state", "Go to State")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.go_to_state(transaction_id, window, cx)
+                            })),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .id("undo-tree-preview")
+                    .flex_1()
+                    .px_2()
+                    .overflow_y_scroll()
+                    .when(lines.is_empty(), |this| {
+                        this.child(
+                            Label::new(if transaction_id.is_some() {
+                                "This state has no changes to show."
+                            } else {
+                                "The buffer before any of the changes in its history."
+                            })
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        )
+                    })
+                    .children(lines.into_iter().map(|(line, color)| {
+                        Label::new(line)
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .color(color)
+                            .single_line()
+                    })),
+            )
+    }
+}
+
+/// Orders the tree for display with every state shown above the one it was made in. A state's
+/// oldest child continues its branch, while later children start branches indented one level
+/// deeper.
+fn layout_tree(nodes: &[UndoTreeNode], current: Option<TransactionId>) -> Vec<Entry> {
+    let mut children = HashMap::<Option<TransactionId>, Vec<UndoTreeNode>>::default();
+    for node in nodes {
+        children.entry(node.parent).or_default().push(node.clone());
+    }
+
+    let mut entries = Vec::new();
+    let mut stack = Vec::new();
+    if let Some(roots) = children.remove(&None) {
+        stack.extend(
+            roots
+                .into_iter()
+                .enumerate()
+                .map(|(ix, node)| (node, ix.min(1))),
+        );
+    }
+    while let Some((node, depth)) = stack.pop() {
+        if let Some(node_children) = children.remove(&Some(node.transaction_id)) {
+            stack.extend(
+                node_children
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, child)| (child, depth + ix.min(1))),
+            );
+        }
+        entries.push(Entry {
+            is_current: current == Some(node.transaction_id),
+            node,
+            depth,
+        });
+    }
+    entries.reverse();
+    entries
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    match seconds {
+        0..5 => "Just now".to_string(),
+        5..60 => format!("{seconds} seconds ago"),
+        60..120 => "1 minute ago".to_string(),
+        120..3600 => format!("{} minutes ago", seconds / 60),
+        3600..7200 => "1 hour ago".to_string(),
+        7200..86400 => format!("{} hours ago", seconds / 3600),
+        86400..172800 => "1 day ago".to_string(),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
+
+fn active_editor(workspace: &Workspace, cx: &App) -> Option<Entity<Editor>> {
+    workspace
+        .active_item(cx)?
+        .act_as::<Editor>(cx)
+        .filter(|editor| {
+            let editor = editor.read(cx);
+            editor.mode().is_full() && editor.buffer().read(cx).is_singleton()
+        })
+}
+
+impl Focusable for UndoTreePanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for UndoTreePanel {}
+
+impl Render for UndoTreePanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .justify_between()
+            .px_2()
+            .py_1()
+            // Match the height of the tab bar so they line up.
+            .h(Tab::container_height(cx))
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new("Undo Tree"))
+            .child(Icon::new(IconName::Undo));
+
+        let content = if self.active_buffer(cx).is_none() {
+            v_flex()
+                .p_4()
+                .child(Label::new("Open a file to see its undo tree.").color(Color::Muted))
+                .into_any_element()
+        } else {
+            let is_initial_state_current = self.entries.iter().all(|entry| !entry.is_current);
+            v_flex()
+                .flex_1()
+                .min_h_0()
+                .child(
+                    v_flex()
+                        .id("undo-tree-entries")
+                        .flex_1()
+                        .p_1()
+                        .overflow_y_scroll()
+                        .children(
+                            self.entries
+                                .iter()
+                                .enumerate()
+                                .map(|(ix, entry)| self.render_entry(ix, entry, cx)),
+                        )
+                        .child(self.render_initial_state(is_initial_state_current, cx)),
+                )
+                .children(
+                    self.selected
+                        .as_ref()
+                        .map(|selected| self.render_preview(selected, cx)),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .key_context("UndoTreePanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(header)
+            .child(content)
+    }
+}
+
+impl Panel for UndoTreePanel {
+    fn persistent_name() -> &'static str {
+        "UndoTreePanel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        UndoTreePanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file::<UndoTreePanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| UndoTreePanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        if active {
+            // Relative times go stale while the panel is hidden.
+            self.refresh(cx);
+        }
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        UndoTreePanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Undo)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Undo Tree Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        10
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(2)), "Just now");
+        assert_eq!(format_elapsed(Duration::from_secs(30)), "30 seconds ago");
+        assert_eq!(format_elapsed(Duration::from_secs(90)), "1 minute ago");
+        assert_eq!(format_elapsed(Duration::from_secs(600)), "10 minutes ago");
+        assert_eq!(format_elapsed(Duration::from_secs(3 * 86400)), "3 days ago");
+    }
+}
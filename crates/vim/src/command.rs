@@ -22,7 +22,7 @@ use std::{
     process::Stdio,
     str::Chars,
     sync::OnceLock,
-    time::Instant,
+    time::{Duration, Instant},
 };
 use task::{HideStrategy, RevealStrategy, SpawnInTerminal, TaskId};
 use ui::ActiveTheme;
@@ -65,6 +65,19 @@ pub struct WithCount {
     action: WrappedAction,
 }
 
+/// `:earlier` and `:later`, which move through the undo tree in the order its states were made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeTravel {
+    step: TimeTravelStep,
+    later: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TimeTravelStep {
+    Count(usize),
+    Duration(Duration),
+}
+
 #[derive(Clone, Deserialize, JsonSchema, PartialEq)]
 pub enum VimOption {
     Wrap(bool),
@@ -168,6 +181,7 @@ impl_internal_actions!(
         OnMatchingLines,
         ShellExec,
         VimSet,
+        TimeTravel,
     ]
 );
 
@@ -211,6 +225,15 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
             });
         }
     });
+    Vim::action(editor, cx, |vim, action: &TimeTravel, window, cx| {
+        vim.update_editor(window, cx, |_, editor, window, cx| {
+            let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+                return;
+            };
+            let target = action.target(buffer.read(cx));
+            editor.go_to_history_state(target, window, cx);
+        });
+    });
     Vim::action(editor, cx, |vim, _: &VisualCommand, window, cx| {
         let Some(workspace) = vim.workspace(window) else {
             return;
@@ -1016,6 +1039,8 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
         } else {
             None
         }
+    } else if let Some(time_travel) = TimeTravel::parse(query) {
+        Some(time_travel.boxed_clone())
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else {
@@ -1071,6 +1096,75 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     positions
 }
 
+impl TimeTravel {
+    /// Parses `:ea[rlier] {N}` and `:lat[er] {N}`, where `{N}` is a number of states or a
+    /// duration with an `s`, `m`, `h` or `d` suffix.
+    fn parse(query: &str) -> Option<Self> {
+        let (command, argument) = query.split_once(' ').unwrap_or((query, ""));
+        let later = if command.len() >= 2 && "earlier".starts_with(command) {
+            false
+        } else if command.len() >= 3 && "later".starts_with(command) {
+            true
+        } else {
+            return None;
+        };
+
+        let argument = argument.trim();
+        let step = if argument.is_empty() {
+            TimeTravelStep::Count(1)
+        } else {
+            let digits_end = argument
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(argument.len());
+            let amount = argument[..digits_end].parse::<u64>().ok()?;
+            let unit_seconds = match &argument[digits_end..] {
+                "" => return Some(Self::count(usize::try_from(amount).ok()?, later)),
+                "s" => 1,
+                "m" => 60,
+                "h" => 60 * 60,
+                "d" => 60 * 60 * 24,
+                _ => return None,
+            };
+            TimeTravelStep::Duration(Duration::from_secs(amount.checked_mul(unit_seconds)?))
+        };
+        Some(Self { step, later })
+    }
+
+    fn count(count: usize, later: bool) -> Self {
+        Self {
+            step: TimeTravelStep::Count(count),
+            later,
+        }
+    }
+
+    fn target(&self, buffer: &language::Buffer) -> Option<language::TransactionId> {
+        match self.step {
+            TimeTravelStep::Count(count) => {
+                let count = isize::try_from(count).unwrap_or(isize::MAX);
+                buffer.history_state_by_steps(if self.later { count } else { -count })
+            }
+            TimeTravelStep::Duration(duration) => {
+                let tree = buffer.undo_tree();
+                let current_time = buffer
+                    .current_history_state()
+                    .and_then(|current| tree.iter().find(|node| node.transaction_id == current))
+                    .or_else(|| tree.first())?
+                    .edited_at;
+                let target_time = if self.later {
+                    current_time.checked_add(duration)
+                } else {
+                    current_time.checked_sub(duration)
+                };
+                match target_time {
+                    Some(target_time) => buffer.history_state_at(target_time),
+                    None if self.later => tree.last().map(|node| node.transaction_id),
+                    None => None,
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct OnMatchingLines {
     range: CommandRange,
@@ -1605,8 +1699,9 @@ impl ShellExec {
 
 #[cfg(test)]
 mod test {
-    use std::path::Path;
+    use std::{path::Path, time::Duration};
 
+    use super::{TimeTravel, TimeTravelStep};
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
@@ -1734,6 +1829,45 @@ mod test {
         assert_eq!(fs.load(path).await.unwrap().replace("\r\n", "\n"), "@@\n");
     }
 
+    #[gpui::test]
+    async fn test_earlier_and_later(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes("x x u");
+        assert_eq!(cx.buffer_text(), "bc");
+        // Making an edit after undoing keeps the undone deletion in the undo tree.
+        cx.simulate_keystrokes("A d escape");
+        assert_eq!(cx.buffer_text(), "bcd");
+
+        cx.simulate_keystrokes(": e a r l i e r enter");
+        assert_eq!(cx.buffer_text(), "c");
+        cx.simulate_keystrokes(": l a t enter");
+        assert_eq!(cx.buffer_text(), "bcd");
+        cx.simulate_keystrokes(": e a r l i e r space 3 enter");
+        assert_eq!(cx.buffer_text(), "abc");
+        cx.simulate_keystrokes(": l a t e r space 1 0 m enter");
+        assert_eq!(cx.buffer_text(), "bcd");
+    }
+
+    #[test]
+    fn test_parsing_time_travel() {
+        assert_eq!(
+            TimeTravel::parse("ea 2h"),
+            Some(TimeTravel {
+                step: TimeTravelStep::Duration(Duration::from_secs(2 * 60 * 60)),
+                later: false,
+            })
+        );
+        assert_eq!(TimeTravel::parse("lat 1w"), None);
+        // Durations too long to represent are rejected rather than overflowing.
+        assert_eq!(
+            TimeTravel::parse(&format!("later {}d", u64::MAX / 60)),
+            None
+        );
+        assert_eq!(TimeTravel::parse(&format!("earlier {}1s", u64::MAX)), None);
+    }
+
     #[gpui::test]
     async fn test_command_quit(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
@@ -17,7 +17,7 @@ use project::debugger::breakpoint_store::{BreakpointState, SourceBreakpoint};
 
 use language::{LanguageName, Toolchain};
 use project::WorktreeId;
-use remote::ssh_session::SshProjectId;
+use remote::{CommandConnectionOptions, ssh_session::SshProjectId};
 use sqlez::{
     bindable::{Bind, Column, StaticColumnCount},
     statement::{SqlType, Statement},
@@ -541,6 +541,9 @@ define_connection! {
         ALTER TABLE breakpoints ADD COLUMN condition TEXT;
         ALTER TABLE breakpoints ADD COLUMN hit_condition TEXT;
     ),
+    sql!(
+        ALTER TABLE ssh_projects ADD COLUMN command TEXT;
+    ),
     ];
 }
 
@@ -915,11 +918,32 @@ impl WorkspaceDb {
         }
     }
 
+    /// Like [`Self::get_or_create_ssh_project`], for projects reached through a command rather
+    /// than over ssh.
+    pub(crate) async fn get_or_create_command_project(
+        &self,
+        options: CommandConnectionOptions,
+        paths: Vec<String>,
+    ) -> Result<SerializedSshProject> {
+        let paths = serde_json::to_string(&paths)?;
+        let command = serde_json::to_string(&options)?;
+        if let Some(project) = self
+            .get_command_project(command.clone(), paths.clone())
+            .await?
+        {
+            Ok(project)
+        } else {
+            self.insert_command_project(options.connection_string(), paths, command)
+                .await?
+                .ok_or_else(|| anyhow!("failed to insert command project"))
+        }
+    }
+
     query! {
         async fn get_ssh_project(host: String, port: Option<u16>, paths: String, user: Option<String>) -> Result<Option<SerializedSshProject>> {
-            SELECT id, host, port, paths, user
+            SELECT id, host, port, paths, user, command
             FROM ssh_projects
-            WHERE host IS ? AND port IS ? AND paths IS ? AND user IS ?
+            WHERE host IS ? AND port IS ? AND paths IS ? AND user IS ? AND command IS NULL
             LIMIT 1
         }
     }
@@ -932,7 +956,27 @@ impl WorkspaceDb {
                 paths,
                 user
             ) VALUES (?1, ?2, ?3, ?4)
-            RETURNING id, host, port, paths, user
+            RETURNING id, host, port, paths, user, command
+        }
+    }
+
+    query! {
+        async fn get_command_project(command: String, paths: String) -> Result<Option<SerializedSshProject>> {
+            SELECT id, host, port, paths, user, command
+            FROM ssh_projects
+            WHERE command IS ? AND paths IS ?
+            LIMIT 1
+        }
+    }
+
+    query! {
+        async fn insert_command_project(host: String, paths: String, command: String) -> Result<Option<SerializedSshProject>> {
+            INSERT INTO ssh_projects(
+                host,
+                paths,
+                command
+            ) VALUES (?1, ?2, ?3)
+            RETURNING id, host, port, paths, user, command
         }
     }
 
@@ -978,14 +1022,14 @@ impl WorkspaceDb {
 
     query! {
         fn ssh_projects() -> Result<Vec<SerializedSshProject>> {
-            SELECT id, host, port, paths, user
+            SELECT id, host, port, paths, user, command
             FROM ssh_projects
         }
     }
 
     query! {
         fn ssh_project(id: u64) -> Result<SerializedSshProject> {
-            SELECT id, host, port, paths, user
+            SELECT id, host, port, paths, user, command
             FROM ssh_projects
             WHERE id = ?
         }
@@ -2374,6 +2418,37 @@ mod tests {
         assert_eq!(different_project.user, user2);
     }
 
+    #[gpui::test]
+    async fn test_get_or_create_command_project() {
+        let db = WorkspaceDb(open_test_db("test_get_or_create_command_project").await);
+
+        let options = CommandConnectionOptions::parse_command_line("docker exec -i dev").unwrap();
+        let paths = vec!["/workspace".to_string()];
+        let project = db
+            .get_or_create_command_project(options.clone(), paths.clone())
+            .await
+            .unwrap();
+        assert_eq!(project.host, "docker exec -i dev");
+        assert_eq!(project.paths, paths);
+        assert_eq!(project.command.as_ref(), Some(&options));
+
+        let same_project = db
+            .get_or_create_command_project(options.clone(), paths.clone())
+            .await
+            .unwrap();
+        assert_eq!(project.id, same_project.id);
+        assert_eq!(db.ssh_project(project.id.0).unwrap(), project);
+
+        // An ssh project whose host happens to match the command's connection string is a
+        // different project.
+        let ssh_project = db
+            .get_or_create_ssh_project(project.host.clone(), None, paths.clone(), None)
+            .await
+            .unwrap();
+        assert_ne!(project.id, ssh_project.id);
+        assert_eq!(ssh_project.command, None);
+    }
+
     #[gpui::test]
     async fn test_get_or_create_ssh_project_with_null_user() {
         let db = WorkspaceDb(open_test_db("test_get_or_create_ssh_project_with_null_user").await);
@@ -11,7 +11,7 @@ use db::sqlez::{
 use gpui::{AsyncWindowContext, Entity, WeakEntity};
 use itertools::Itertools as _;
 use project::{Project, debugger::breakpoint_store::SourceBreakpoint};
-use remote::ssh_session::SshProjectId;
+use remote::{CommandConnectionOptions, ssh_session::SshProjectId};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
@@ -28,6 +28,9 @@ pub struct SerializedSshProject {
     pub port: Option<u16>,
     pub paths: Vec<String>,
     pub user: Option<String>,
+    /// The command the project is reached through, for projects that aren't opened over ssh. The
+    /// host is then the command's connection string.
+    pub command: Option<CommandConnectionOptions>,
 }
 
 impl SerializedSshProject {
@@ -54,7 +57,7 @@ impl SerializedSshProject {
 
 impl StaticColumnCount for SerializedSshProject {
     fn column_count() -> usize {
-        5
+        6
     }
 }
 
@@ -65,7 +68,13 @@ impl Bind for &SerializedSshProject {
         let next_index = statement.bind(&self.port, next_index)?;
         let raw_paths = serde_json::to_string(&self.paths)?;
         let next_index = statement.bind(&raw_paths, next_index)?;
-        statement.bind(&self.user, next_index)
+        let next_index = statement.bind(&self.user, next_index)?;
+        let raw_command = self
+            .command
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        statement.bind(&raw_command, next_index)
     }
 }
 
@@ -78,6 +87,10 @@ impl Column for SerializedSshProject {
         let paths: Vec<String> = serde_json::from_str(&raw_paths)?;
 
         let (user, _) = Option::<String>::column(statement, start_index + 4)?;
+        let (raw_command, _) = Option::<String>::column(statement, start_index + 5)?;
+        let command = raw_command
+            .map(|raw_command| serde_json::from_str(&raw_command))
+            .transpose()?;
 
         Ok((
             Self {
@@ -86,8 +99,9 @@ impl Column for SerializedSshProject {
                 port,
                 paths,
                 user,
+                command,
             },
-            start_index + 5,
+            start_index + 6,
         ))
     }
 }
@@ -644,6 +658,7 @@ mod tests {
             port: Some(22),
             paths: urls.iter().map(|s| s.to_string()).collect(),
             user: Some("user".to_string()),
+            command: None,
         });
         assert_eq!(
             serialized.sorted_paths(),
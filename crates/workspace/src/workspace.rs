@@ -58,20 +58,17 @@ use notifications::{
 };
 pub use pane::*;
 pub use pane_group::*;
-use persistence::{
-    DB, SerializedWindowBounds,
-    model::{SerializedSshProject, SerializedWorkspace},
-};
+use persistence::{DB, SerializedWindowBounds, model::SerializedWorkspace};
 pub use persistence::{
     DB as WORKSPACE_DB, WorkspaceDb,
-    model::{ItemId, LocalPaths, SerializedWorkspaceLocation},
+    model::{ItemId, LocalPaths, SerializedSshProject, SerializedWorkspaceLocation},
 };
 use postage::stream::Stream;
 use project::{
     DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
     debugger::breakpoint_store::BreakpointStoreEvent,
 };
-use remote::{SshClientDelegate, SshConnectionOptions, ssh_session::ConnectionIdentifier};
+use remote::{RemoteConnectionOptions, SshClientDelegate, ssh_session::ConnectionIdentifier};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use session::AppSession;
@@ -6514,7 +6511,7 @@ pub fn create_and_open_local_file(
 
 pub fn open_ssh_project_with_new_connection(
     window: WindowHandle<Workspace>,
-    connection_options: SshConnectionOptions,
+    connection_options: RemoteConnectionOptions,
     cancel_rx: oneshot::Receiver<()>,
     delegate: Arc<dyn SshClientDelegate>,
     app_state: Arc<AppState>,
@@ -6568,7 +6565,7 @@ pub fn open_ssh_project_with_new_connection(
 }
 
 pub fn open_ssh_project_with_existing_connection(
-    connection_options: SshConnectionOptions,
+    connection_options: RemoteConnectionOptions,
     project: Entity<Project>,
     paths: Vec<PathBuf>,
     app_state: Arc<AppState>,
@@ -6596,7 +6593,7 @@ pub fn open_ssh_project_with_existing_connection(
 async fn open_ssh_project_inner(
     project: Entity<Project>,
     paths: Vec<PathBuf>,
-    serialized_ssh_project: SerializedSshProject,
+    serialized_ssh_project: Option<SerializedSshProject>,
     workspace_id: WorkspaceId,
     serialized_workspace: Option<SerializedWorkspace>,
     app_state: Arc<AppState>,
@@ -6640,7 +6637,9 @@ async fn open_ssh_project_inner(
 
             let mut workspace =
                 Workspace::new(Some(workspace_id), project, app_state.clone(), window, cx);
-            workspace.set_serialized_ssh_project(serialized_ssh_project);
+            if let Some(serialized_ssh_project) = serialized_ssh_project {
+                workspace.set_serialized_ssh_project(serialized_ssh_project);
+            }
             workspace.update_history(cx);
             workspace
         });
@@ -6668,29 +6667,41 @@ async fn open_ssh_project_inner(
     Ok(())
 }
 
+/// Only projects opened over SSH are persisted in the database, as the `ssh_projects` table is
+/// keyed by host. Projects on other remote connections get a fresh workspace each time.
 fn serialize_ssh_project(
-    connection_options: SshConnectionOptions,
+    connection_options: RemoteConnectionOptions,
     paths: Vec<PathBuf>,
     cx: &AsyncApp,
 ) -> Task<
     Result<(
-        SerializedSshProject,
+        Option<SerializedSshProject>,
         WorkspaceId,
         Option<SerializedWorkspace>,
     )>,
 > {
     cx.background_spawn(async move {
-        let serialized_ssh_project = persistence::DB
-            .get_or_create_ssh_project(
-                connection_options.host.clone(),
-                connection_options.port,
-                paths
-                    .iter()
-                    .map(|path| path.to_string_lossy().to_string())
-                    .collect::<Vec<_>>(),
-                connection_options.username.clone(),
-            )
-            .await?;
+        let paths = paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        let serialized_ssh_project = match connection_options {
+            RemoteConnectionOptions::Ssh(connection_options) => {
+                persistence::DB
+                    .get_or_create_ssh_project(
+                        connection_options.host.clone(),
+                        connection_options.port,
+                        paths,
+                        connection_options.username.clone(),
+                    )
+                    .await?
+            }
+            RemoteConnectionOptions::Command(connection_options) => {
+                persistence::DB
+                    .get_or_create_command_project(connection_options, paths)
+                    .await?
+            }
+        };
 
         let serialized_workspace =
             persistence::DB.workspace_for_ssh_project(&serialized_ssh_project);
@@ -6703,7 +6714,11 @@ fn serialize_ssh_project(
             persistence::DB.next_id().await?
         };
 
-        Ok((serialized_ssh_project, workspace_id, serialized_workspace))
+        Ok((
+            Some(serialized_ssh_project),
+            workspace_id,
+            serialized_workspace,
+        ))
     })
 }
 
@@ -534,15 +534,18 @@ fn main() {
 
         go_to_line::init(cx);
         file_finder::init(cx);
+        file_history::init(app_state.fs.clone(), cx);
         tab_switcher::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
         project_panel::init(cx);
         outline_panel::init(cx);
+        undo_tree_panel::init(cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
         search::init(cx);
+        semantic_index::init(cx);
         vim::init(cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
@@ -850,8 +853,7 @@ async fn restore_or_create_workspace(app_state: Arc<AppState>, cx: &mut AsyncApp
                 }
                 SerializedWorkspaceLocation::Ssh(ssh) => {
                     let connection_options = cx.update(|cx| {
-                        SshSettings::get_global(cx)
-                            .connection_options_for(ssh.host, ssh.port, ssh.user)
+                        SshSettings::get_global(cx).connection_options_for_project(&ssh)
                     })?;
                     let app_state = app_state.clone();
                     cx.spawn(async move |cx| {
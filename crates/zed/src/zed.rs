@@ -1,4 +1,5 @@
 mod app_menus;
+mod data_transfer;
 pub mod inline_completion_registry;
 #[cfg(target_os = "macos")]
 pub(crate) mod mac_only_instance;
@@ -18,11 +19,12 @@ use client::zed_urls;
 use collections::VecDeque;
 use command_palette_hooks::CommandPaletteFilter;
 use debugger_ui::debugger_panel::DebugPanel;
-use editor::ProposedChangesEditorToolbar;
 use editor::{Editor, MultiBuffer, scroll::Autoscroll};
+use editor::{FileDiffViewToolbar, ProposedChangesEditorToolbar};
 use feature_flags::{Debugger, FeatureFlagAppExt, FeatureFlagViewExt};
 use futures::{StreamExt, channel::mpsc, select_biased};
 use git_ui::git_panel::GitPanel;
+use git_ui::merge_view::MergeViewToolbar;
 use git_ui::project_diff::ProjectDiffToolbar;
 use gpui::{
     Action, App, AppContext as _, AsyncWindowContext, Context, DismissEvent, Element, Entity,
@@ -60,6 +62,7 @@ use std::{borrow::Cow, path::Path, sync::Arc};
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use theme::{ActiveTheme, ThemeSettings};
 use ui::{PopoverMenuHandle, prelude::*};
+use undo_tree_panel::UndoTreePanel;
 use util::markdown::MarkdownString;
 use util::{ResultExt, asset_str};
 use uuid::Uuid;
@@ -108,6 +111,7 @@ pub fn init(cx: &mut App) {
     cx.on_action(quit);
 
     cx.on_action(|_: &RestoreBanner, cx| title_bar::restore_banner(cx));
+    data_transfer::init(cx);
 
     if ReleaseChannel::global(cx) == ReleaseChannel::Dev {
         cx.on_action(test_panic);
@@ -415,6 +419,7 @@ fn initialize_panels(
             workspace_handle.clone(),
             cx.clone(),
         );
+        let undo_tree_panel = UndoTreePanel::load(workspace_handle.clone(), cx.clone());
 
         let (
             project_panel,
@@ -423,6 +428,7 @@ fn initialize_panels(
             channels_panel,
             chat_panel,
             notification_panel,
+            undo_tree_panel,
         ) = futures::try_join!(
             project_panel,
             outline_panel,
@@ -430,6 +436,7 @@ fn initialize_panels(
             channels_panel,
             chat_panel,
             notification_panel,
+            undo_tree_panel,
         )?;
 
         workspace_handle.update_in(cx, |workspace, window, cx| {
@@ -439,6 +446,7 @@ fn initialize_panels(
             workspace.add_panel(channels_panel, window, cx);
             workspace.add_panel(chat_panel, window, cx);
             workspace.add_panel(notification_panel, window, cx);
+            workspace.add_panel(undo_tree_panel, window, cx);
             cx.when_flag_enabled::<Debugger>(window, |_, window, cx| {
                 cx.spawn_in(
                     window,
@@ -801,6 +809,14 @@ fn register_actions(
                 workspace.toggle_panel_focus::<OutlinePanel>(window, cx);
             },
         )
+        .register_action(
+            |workspace: &mut Workspace,
+             _: &undo_tree_panel::ToggleFocus,
+             window: &mut Window,
+             cx: &mut Context<Workspace>| {
+                workspace.toggle_panel_focus::<UndoTreePanel>(window, cx);
+            },
+        )
         .register_action(
             |workspace: &mut Workspace,
              _: &collab_ui::collab_panel::ToggleFocus,
@@ -923,6 +939,10 @@ fn initialize_pane(
             toolbar.add_item(buffer_search_bar.clone(), window, cx);
             let proposed_change_bar = cx.new(|_| ProposedChangesEditorToolbar::new());
             toolbar.add_item(proposed_change_bar, window, cx);
+            let file_diff_view_toolbar = cx.new(|_| FileDiffViewToolbar::new());
+            toolbar.add_item(file_diff_view_toolbar, window, cx);
+            let merge_view_toolbar = cx.new(|_| MergeViewToolbar::new());
+            toolbar.add_item(merge_view_toolbar, window, cx);
             let quick_action_bar =
                 cx.new(|cx| QuickActionBar::new(buffer_search_bar, workspace, cx));
             toolbar.add_item(quick_action_bar, window, cx);
@@ -4244,6 +4264,8 @@ mod tests {
             git_ui::init(cx);
             project_panel::init(cx);
             outline_panel::init(cx);
+            undo_tree_panel::init(cx);
+            file_history::init(app_state.fs.clone(), cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
@@ -0,0 +1,271 @@
+use std::path::PathBuf;
+
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, PathPromptOptions,
+    PromptLevel, Task, Window, actions,
+};
+use ui::{Checkbox, Modal, ModalFooter, ModalHeader, Section, ToggleState, prelude::*};
+use util::ResultExt as _;
+use workspace::{CloseIntent, ModalView, Workspace, notifications::DetachAndPromptErr};
+
+actions!(zed, [ExportData, ImportData]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(|workspace, _: &ExportData, window, cx| {
+                let domains = db::export::exportable_domains(&KEY_VALUE_STORE)
+                    .log_err()
+                    .unwrap_or_default();
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    DataTransferModal::new(Mode::Export { domains }, window, cx)
+                });
+            })
+            .register_action(|workspace, _: &ImportData, window, cx| {
+                import_data(workspace, window, cx);
+            });
+    })
+    .detach();
+}
+
+fn import_data(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: false,
+    });
+    cx.spawn_in(window, async move |workspace, cx| {
+        let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+            return Ok(());
+        };
+        if db::export::is_encrypted(path.clone()).await? {
+            workspace.update_in(cx, |workspace, window, cx| {
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    DataTransferModal::new(Mode::Import { path }, window, cx)
+                });
+            })?;
+        } else {
+            let task = cx.update(|window, cx| run_import(path, None, window, cx))?;
+            task.await?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_prompt_err("Failed to import data", window, cx, |_, _, _| None);
+}
+
+/// Imports the data, then restarts, since open windows still hold the state from before the
+/// import. Database writes stop after the import, so that this state can't be written back over
+/// the imported data, which leaves Zed unusable until it restarts. The restart can't be declined,
+/// only put off until unsaved changes are dealt with.
+fn run_import(
+    path: PathBuf,
+    passphrase: Option<String>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Task<anyhow::Result<()>> {
+    let import = cx.background_spawn(async move {
+        db::export::import_domains(&KEY_VALUE_STORE, path, passphrase).await
+    });
+    window.spawn(cx, async move |cx| {
+        import.await?;
+        loop {
+            let prompt = cx.update(|window, cx| {
+                window.prompt(
+                    PromptLevel::Info,
+                    "Data imported",
+                    Some("Zed will restart to load the imported data."),
+                    &["Restart"],
+                    cx,
+                )
+            })?;
+            prompt.await.ok();
+
+            let workspace_windows = cx.update(|_, cx| {
+                cx.windows()
+                    .into_iter()
+                    .filter_map(|window| window.downcast::<Workspace>())
+                    .collect::<Vec<_>>()
+            })?;
+            let mut should_restart = true;
+            for window in workspace_windows {
+                if let Ok(should_close) = window.update(cx, |workspace, window, cx| {
+                    workspace.prepare_to_close(CloseIntent::Quit, window, cx)
+                }) {
+                    if !should_close.await? {
+                        should_restart = false;
+                        break;
+                    }
+                }
+            }
+            if should_restart {
+                return cx.update(|_, cx| cx.restart(None));
+            }
+        }
+    })
+}
+
+enum Mode {
+    Export { domains: Vec<String> },
+    Import { path: PathBuf },
+}
+
+/// Asks which data to export, or for the passphrase of an encrypted import.
+struct DataTransferModal {
+    mode: Mode,
+    excluded_domains: Vec<String>,
+    passphrase_editor: Entity<Editor>,
+}
+
+impl DataTransferModal {
+    fn new(mode: Mode, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let placeholder = match mode {
+            Mode::Export { .. } => "Passphrase (optional)",
+            Mode::Import { .. } => "Passphrase",
+        };
+        let passphrase_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(placeholder, cx);
+            editor.set_masked(true, cx);
+            editor
+        });
+        Self {
+            mode,
+            excluded_domains: Vec::new(),
+            passphrase_editor,
+        }
+    }
+
+    fn passphrase(&self, cx: &App) -> Option<String> {
+        Some(self.passphrase_editor.read(cx).text(cx)).filter(|passphrase| !passphrase.is_empty())
+    }
+
+    fn confirm(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let passphrase = self.passphrase(cx);
+        match &self.mode {
+            Mode::Export { domains } => {
+                let domains = domains
+                    .iter()
+                    .filter(|domain| !self.excluded_domains.contains(domain))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if domains.is_empty() {
+                    return;
+                }
+                let path = cx.prompt_for_new_path(util::paths::home_dir());
+                window
+                    .spawn(cx, async move |_| {
+                        let Some(path) = path.await?? else {
+                            return Ok(());
+                        };
+                        db::export::export_domains(&KEY_VALUE_STORE, domains, path, passphrase)
+                            .await
+                    })
+                    .detach_and_prompt_err("Failed to export data", window, cx, |_, _, _| None);
+            }
+            Mode::Import { path } => {
+                if passphrase.is_none() {
+                    return;
+                }
+                run_import(path.clone(), passphrase, window, cx).detach_and_prompt_err(
+                    "Failed to import data",
+                    window,
+                    cx,
+                    |_, _, _| None,
+                );
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn toggle_domain(&mut self, domain: &str, cx: &mut Context<Self>) {
+        if let Some(ix) = self.excluded_domains.iter().position(|d| d == domain) {
+            self.excluded_domains.remove(ix);
+        } else {
+            self.excluded_domains.push(domain.to_string());
+        }
+        cx.notify();
+    }
+}
+
+impl ModalView for DataTransferModal {}
+
+impl EventEmitter<DismissEvent> for DataTransferModal {}
+
+impl Focusable for DataTransferModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.passphrase_editor.focus_handle(cx)
+    }
+}
+
+impl Render for DataTransferModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (headline, description, confirm_label) = match self.mode {
+            Mode::Export { .. } => (
+                "Export Data",
+                "Export workspaces and other saved state to move them to another machine. \
+                 Exports are encrypted if you choose a passphrase.",
+                "Export…",
+            ),
+            Mode::Import { .. } => (
+                "Import Data",
+                "This export is encrypted. Enter the passphrase it was exported with.",
+                "Import",
+            ),
+        };
+
+        let mut section = Section::new().child(Label::new(description).color(Color::Muted));
+        if let Mode::Export { domains } = &self.mode {
+            for domain in domains {
+                let excluded = self.excluded_domains.contains(domain);
+                let domain_name = domain.clone();
+                section = section.child(
+                    Checkbox::new(
+                        SharedString::from(format!("export-domain-{domain}")),
+                        if excluded {
+                            ToggleState::Unselected
+                        } else {
+                            ToggleState::Selected
+                        },
+                    )
+                    .label(domain.clone())
+                    .on_click(
+                        cx.listener(move |this, _, _, cx| this.toggle_domain(&domain_name, cx)),
+                    ),
+                );
+            }
+        }
+        section = section.child(
+            div()
+                .px_2()
+                .py_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .child(self.passphrase_editor.clone()),
+        );
+
+        div()
+            .elevation_3(cx)
+            .w(rems(34.))
+            .key_context("DataTransferModal")
+            .on_action(cx.listener(|_, _: &menu::Cancel, _, cx| cx.emit(DismissEvent)))
+            .on_action(cx.listener(|this, _: &menu::Confirm, window, cx| this.confirm(window, cx)))
+            .child(
+                Modal::new("data-transfer", None)
+                    .header(ModalHeader::new().headline(headline))
+                    .section(section)
+                    .footer(
+                        ModalFooter::new()
+                            .start_slot(
+                                Button::new("cancel", "Cancel")
+                                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent))),
+                            )
+                            .end_slot(Button::new("confirm", confirm_label).on_click(
+                                cx.listener(|this, _, window, cx| this.confirm(window, cx)),
+                            )),
+                    ),
+            )
+    }
+}
@@ -1,18 +1,18 @@
 use crate::handle_open_request;
 use crate::restorable_workspace_locations;
 use anyhow::{Context as _, Result, anyhow};
-use cli::{CliRequest, CliResponse, ipc::IpcSender};
+use cli::{CliRequest, CliResponse, MergePaths, ipc::IpcSender};
 use cli::{IpcHandshake, ipc};
 use client::parse_zed_link;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use editor::Editor;
+use editor::{Editor, FileDiffView};
 use fs::Fs;
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::channel::{mpsc, oneshot};
 use futures::future::join_all;
-use futures::{FutureExt, SinkExt, StreamExt};
-use gpui::{App, AsyncApp, Global, WindowHandle};
+use futures::{Future, FutureExt, SinkExt, StreamExt};
+use gpui::{App, AsyncApp, BackgroundExecutor, Global, WindowHandle};
 use language::Point;
 use recent_projects::{SshSettings, open_ssh_project};
 use remote::SshConnectionOptions;
@@ -25,7 +25,7 @@ use util::ResultExt;
 use util::paths::PathWithPosition;
 use welcome::{FIRST_OPEN, show_welcome_view};
 use workspace::item::ItemHandle;
-use workspace::{AppState, OpenOptions, SerializedWorkspaceLocation, Workspace};
+use workspace::{AppState, OpenOptions, OpenVisible, SerializedWorkspaceLocation, Workspace};
 
 #[derive(Default, Debug)]
 pub struct OpenRequest {
@@ -258,6 +258,8 @@ pub async fn handle_cli_connection(
             CliRequest::Open {
                 urls,
                 paths,
+                diff_paths,
+                merge,
                 wait,
                 open_new_workspace,
                 env,
@@ -284,6 +286,34 @@ pub async fn handle_cli_connection(
                     return;
                 }
 
+                if !diff_paths.is_empty() || merge.is_some() {
+                    let status = match open_diffs(
+                        paths,
+                        diff_paths,
+                        merge,
+                        open_new_workspace,
+                        wait,
+                        &responses,
+                        env,
+                        app_state.clone(),
+                        cx,
+                    )
+                    .await
+                    {
+                        Ok(status) => status,
+                        Err(error) => {
+                            responses
+                                .send(CliResponse::Stderr {
+                                    message: format!("{error:#}"),
+                                })
+                                .log_err();
+                            1
+                        }
+                    };
+                    responses.send(CliResponse::Exit { status }).log_err();
+                    return;
+                }
+
                 let open_workspace_result = open_workspaces(
                     paths,
                     open_new_workspace,
@@ -376,8 +406,7 @@ async fn open_workspaces(
                 SerializedWorkspaceLocation::Ssh(ssh) => {
                     let app_state = app_state.clone();
                     let connection_options = cx.update(|cx| {
-                        SshSettings::get_global(cx)
-                            .connection_options_for(ssh.host, ssh.port, ssh.user)
+                        SshSettings::get_global(cx).connection_options_for_project(&ssh)
                     });
                     if let Ok(connection_options) = connection_options {
                         cx.spawn(async move |mut cx| {
@@ -467,36 +496,24 @@ async fn open_local_workspace(
 
             if wait {
                 let background = cx.background_executor().clone();
-                let wait = async move {
-                    if paths_with_position.is_empty() {
-                        let (done_tx, done_rx) = oneshot::channel();
-                        let _subscription = workspace.update(cx, |_, _, cx| {
-                            cx.on_release(move |_, _| {
-                                let _ = done_tx.send(());
-                            })
-                        });
-                        let _ = done_rx.await;
-                    } else {
-                        let _ = futures::future::try_join_all(item_release_futures).await;
-                    };
-                }
-                .fuse();
-
-                futures::pin_mut!(wait);
-
-                loop {
-                    // Repeatedly check if CLI is still open to avoid wasting resources
-                    // waiting for files or workspaces to close.
-                    let mut timer = background.timer(Duration::from_secs(1)).fuse();
-                    futures::select_biased! {
-                        _ = wait => break,
-                        _ = timer => {
-                            if responses.send(CliResponse::Ping).is_err() {
-                                break;
-                            }
-                        }
-                    }
-                }
+                wait_while_cli_is_open(
+                    async move {
+                        if paths_with_position.is_empty() {
+                            let (done_tx, done_rx) = oneshot::channel();
+                            let _subscription = workspace.update(cx, |_, _, cx| {
+                                cx.on_release(move |_, _| {
+                                    let _ = done_tx.send(());
+                                })
+                            });
+                            let _ = done_rx.await;
+                        } else {
+                            let _ = futures::future::try_join_all(item_release_futures).await;
+                        };
+                    },
+                    responses,
+                    background,
+                )
+                .await;
             }
         }
         Err(error) => {
@@ -511,6 +528,213 @@ async fn open_local_workspace(
     errored
 }
 
+/// Opens a diff view for each pair of `diff_paths`, and the files of a three-way `merge`,
+/// alongside any other `paths`.
+///
+/// Returns the status the CLI should exit with, which for a merge that was waited on reflects
+/// whether every conflict was resolved.
+async fn open_diffs(
+    paths: Vec<String>,
+    diff_paths: Vec<[String; 2]>,
+    merge: Option<MergePaths>,
+    open_new_workspace: Option<bool>,
+    wait: bool,
+    responses: &IpcSender<CliResponse>,
+    env: Option<HashMap<String, String>>,
+    app_state: Arc<AppState>,
+    cx: &mut AsyncApp,
+) -> Result<i32> {
+    if let Some(merge) = &merge {
+        write_conflicts_if_missing(merge, app_state.fs.as_ref()).await?;
+    }
+
+    let paths_with_position = derive_paths_with_position(app_state.fs.as_ref(), paths).await;
+    let (workspace, items) = open_paths_with_positions(
+        &paths_with_position,
+        app_state.clone(),
+        workspace::OpenOptions {
+            open_new_workspace,
+            env,
+            ..Default::default()
+        },
+        cx,
+    )
+    .await?;
+    for (item, path) in items.into_iter().zip(&paths_with_position) {
+        if let Some(Err(error)) = item {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("error opening {path:?}: {error}"),
+                })
+                .log_err();
+        }
+    }
+
+    let mut diff_views: Vec<Box<dyn ItemHandle>> = Vec::new();
+    for [old_path, new_path] in diff_paths {
+        let diff_view = workspace
+            .update(cx, |workspace, window, cx| {
+                FileDiffView::open_abs_paths(
+                    (PathBuf::from(old_path), None),
+                    (PathBuf::from(new_path), None),
+                    workspace,
+                    window,
+                    cx,
+                )
+            })?
+            .await?;
+        diff_views.push(Box::new(diff_view));
+    }
+
+    let mut merge_output = None;
+    if let Some(merge) = merge {
+        for (path, label) in [(merge.local, "Local"), (merge.remote, "Remote")] {
+            let base = PathBuf::from(&merge.base);
+            workspace
+                .update(cx, |workspace, window, cx| {
+                    FileDiffView::open_abs_paths(
+                        (base, Some("Base".into())),
+                        (PathBuf::from(path), Some(label.into())),
+                        workspace,
+                        window,
+                        cx,
+                    )
+                })?
+                .await?;
+        }
+
+        let output_path = PathBuf::from(merge.output);
+        let output = workspace
+            .update(cx, |workspace, window, cx| {
+                workspace.open_abs_path(
+                    output_path.clone(),
+                    OpenOptions {
+                        visible: Some(OpenVisible::None),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                )
+            })?
+            .await?;
+        merge_output = Some((output_path, output));
+    }
+
+    if !wait {
+        return Ok(0);
+    }
+
+    // A merge is finished once its result is closed, whereas diffs are only for viewing, so we
+    // wait for all of them.
+    let (output_path, items_to_close) = match merge_output {
+        Some((output_path, output)) => (Some(output_path), vec![output]),
+        None => (None, diff_views),
+    };
+    let released = cx.update(|cx| {
+        items_to_close
+            .iter()
+            .map(|item| {
+                let (released_tx, released_rx) = oneshot::channel();
+                item.on_release(
+                    cx,
+                    Box::new(move |_| {
+                        let _ = released_tx.send(());
+                    }),
+                )
+                .detach();
+                released_rx
+            })
+            .collect::<Vec<_>>()
+    })?;
+    drop(items_to_close);
+    wait_while_cli_is_open(
+        async move {
+            join_all(released).await;
+        },
+        responses,
+        cx.background_executor().clone(),
+    )
+    .await;
+
+    if let Some(output_path) = output_path {
+        let output = app_state.fs.load(&output_path).await?;
+        if has_conflict_markers(&output) {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("{} still contains conflicts", output_path.display()),
+                })
+                .log_err();
+            return Ok(1);
+        }
+    }
+    Ok(0)
+}
+
+/// Writes the result of merging the local and remote files, with conflict markers around the
+/// regions that differ, unless the output already exists. `git mergetool` writes it itself.
+async fn write_conflicts_if_missing(merge: &MergePaths, fs: &dyn Fs) -> Result<()> {
+    let output_path = Path::new(&merge.output);
+    if fs.is_file(output_path).await {
+        return Ok(());
+    }
+
+    let output = util::command::new_smol_command("git")
+        .args([
+            "merge-file",
+            "--stdout",
+            "-L",
+            "LOCAL",
+            "-L",
+            "BASE",
+            "-L",
+            "REMOTE",
+        ])
+        .args([&merge.local, &merge.base, &merge.remote])
+        .output()
+        .await
+        .context("running git merge-file")?;
+    // Statuses up to 127 count the conflicts, whereas errors are negative and so wrap around.
+    if output.status.code().map_or(true, |code| code > 127) {
+        return Err(anyhow!(
+            "failed to merge files: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    let merged = String::from_utf8(output.stdout).context("merged files are not UTF-8")?;
+    fs.atomic_write(output_path.to_path_buf(), merged).await
+}
+
+fn has_conflict_markers(text: &str) -> bool {
+    let mut lines = text.lines().map(str::trim_end);
+    lines.any(|line| line.starts_with("<<<<<<<"))
+        && lines.any(|line| line == "=======")
+        && lines.any(|line| line.starts_with(">>>>>>>"))
+}
+
+/// Waits for `wait` to finish, stopping early if the CLI exits in the meantime.
+async fn wait_while_cli_is_open(
+    wait: impl Future<Output = ()>,
+    responses: &IpcSender<CliResponse>,
+    background: BackgroundExecutor,
+) {
+    let wait = wait.fuse();
+    futures::pin_mut!(wait);
+
+    loop {
+        // Repeatedly check if CLI is still open to avoid wasting resources
+        // waiting for files or workspaces to close.
+        let mut timer = background.timer(Duration::from_secs(1)).fuse();
+        futures::select_biased! {
+            _ = wait => break,
+            _ = timer => {
+                if responses.send(CliResponse::Ping).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 pub async fn derive_paths_with_position(
     fs: &dyn Fs,
     path_strings: impl IntoIterator<Item = impl AsRef<str>>,
@@ -536,13 +760,16 @@ mod tests {
         CliResponse,
         ipc::{self},
     };
-    use editor::Editor;
+    use editor::{Editor, FileDiffView};
     use gpui::TestAppContext;
     use serde_json::json;
     use util::path;
     use workspace::{AppState, Workspace};
 
-    use crate::zed::{open_listener::open_local_workspace, tests::init_test};
+    use crate::zed::{
+        open_listener::{has_conflict_markers, open_diffs, open_local_workspace},
+        tests::init_test,
+    };
 
     #[gpui::test]
     async fn test_open_workspace_with_directory(cx: &mut TestAppContext) {
@@ -655,6 +882,67 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_open_diff(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                path!("/root"),
+                json!({
+                    "old.txt": "one\ntwo\n",
+                    "new.txt": "one\nthree\n",
+                }),
+            )
+            .await;
+
+        let (response_tx, _) = ipc::channel::<CliResponse>().unwrap();
+        let diff_paths = vec![[
+            path!("/root/old.txt").to_owned(),
+            path!("/root/new.txt").to_owned(),
+        ]];
+        let status = cx
+            .spawn(|mut cx| async move {
+                open_diffs(
+                    Vec::new(),
+                    diff_paths,
+                    None,
+                    None,
+                    false,
+                    &response_tx,
+                    None,
+                    app_state,
+                    &mut cx,
+                )
+                .await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(status, 0);
+        assert_eq!(cx.windows().len(), 1);
+        let workspace = cx.windows()[0].downcast::<Workspace>().unwrap();
+        workspace
+            .update(cx, |workspace, _, cx| {
+                assert!(workspace.active_item_as::<FileDiffView>(cx).is_some());
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        assert!(has_conflict_markers(
+            "a\n<<<<<<< LOCAL\nb\n=======\nc\n>>>>>>> REMOTE\nd\n"
+        ));
+        assert!(has_conflict_markers(
+            "<<<<<<< LOCAL\r\nb\r\n=======\r\nc\r\n>>>>>>> REMOTE\r\n"
+        ));
+        assert!(!has_conflict_markers("a\nb\n"));
+        assert!(!has_conflict_markers("=======\n<<<<<<< not a conflict\n"));
+    }
+
     async fn open_workspace_file(
         path: &str,
         open_new_workspace: Option<bool>,
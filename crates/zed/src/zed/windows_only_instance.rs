@@ -129,6 +129,8 @@ fn send_args_to_instance(args: &Args) -> anyhow::Result<()> {
         CliRequest::Open {
             paths,
             urls,
+            diff_paths: Vec::new(),
+            merge: None,
             wait: false,
             open_new_workspace: None,
             env: None,